@@ -0,0 +1,78 @@
+//! Compares the number of `write` syscalls of a synthetic 100 MB download for different write
+//! buffer sizes.
+//!
+//! Run with `cargo bench --bench write_buffer`. The syscalls are read from `/proc/self/io`, so
+//! they're only reported on Linux. Since the mock server runs in the same process, its writes are
+//! included in the count, but they're the same for every buffer size.
+
+use std::time::{Duration, Instant};
+
+use rustube::{SimpleDownloadOptions, Video};
+
+use common::*;
+
+#[allow(dead_code)]
+#[path = "../tests/common.rs"]
+mod common;
+
+const LEN: usize = 100 * 1000 * 1000;
+/// The size of the chunks, the mock server writes to the socket.
+const NETWORK_CHUNK: usize = 16 * 1024;
+const BUFFER_SIZES: [usize; 5] = [0, 16 * 1024, 64 * 1024, rustube::stream::DEFAULT_WRITE_BUFFER_SIZE, 1024 * 1024];
+
+/// The number of `write`-like syscalls, the process made so far.
+fn write_syscalls() -> Option<u64> {
+    std::fs::read_to_string("/proc/self/io")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("syscw:"))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// The multi audio fixture, with all streams pointing to `server`.
+fn video(server: &MockServer) -> Video {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in player_response["streamingData"][formats].as_array_mut().unwrap() {
+            format["contentLength"] = LEN.to_string().into();
+        }
+    }
+
+    let (video_info, mut streams) = video_from_player_response(player_response).into_parts();
+    for stream in streams.iter_mut() {
+        stream.signature_cipher.url = server.url.join("stream").unwrap();
+    }
+    Video::from_parts(video_info, streams)
+}
+
+#[tokio::main]
+async fn main() {
+    let body: Vec<u8> = (0..LEN).map(|b| (b % 251) as u8).collect();
+    let server = MockServer::start(move |_| {
+        MockResponse::ok(body.clone()).slow(NETWORK_CHUNK, Duration::ZERO)
+    }).await;
+    let video = video(&server);
+    let dir = std::env::temp_dir().join(format!("rustube_bench_write_buffer_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    println!("{:>12} {:>16} {:>10}", "buffer size", "write syscalls", "time");
+    for size in BUFFER_SIZES {
+        let syscalls = write_syscalls();
+        let start = Instant::now();
+        let options = SimpleDownloadOptions::new()
+            .target_dir(&dir)
+            .write_buffer_size(size);
+        video.download_with_summary(options).await.unwrap();
+        let elapsed = start.elapsed();
+
+        let syscalls = match (syscalls, write_syscalls()) {
+            (Some(before), Some(after)) => (after - before).to_string(),
+            _ => "n/a".to_owned(),
+        };
+        println!("{:>12} {:>16} {:>9.2?}", size, syscalls, elapsed);
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,19 @@
+#!/usr/bin/env sh
+# Builds a minimal, statically linked `rustube` binary, i.e. for scratch containers, and checks that it runs.
+#
+# Requires the musl target: `rustup target add x86_64-unknown-linux-musl`
+set -eu
+
+TARGET=x86_64-unknown-linux-musl
+cd "$(dirname "$0")"
+
+cargo build --release --target "$TARGET" --no-default-features --features download
+
+BINARY=../target/$TARGET/release/rustube
+if ldd "$BINARY" 2>&1 | grep -q "=>"; then
+    echo "$BINARY is dynamically linked" >&2
+    exit 1
+fi
+
+"$BINARY" --version
+ls -l "$BINARY"
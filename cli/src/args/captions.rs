@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use clap::ArgGroup;
+
+use rustube::LanguageTag;
+
+use crate::args::{CookieArgs, Identifier, LoggingArgs, NetworkArgs};
+use crate::args::output::OutputArgs;
+
+#[derive(clap::Parser)]
+#[clap(group(ArgGroup::new("track").args(&["lang", "all"])))]
+pub struct CaptionsArgs {
+    #[clap(flatten)]
+    pub identifier: Identifier,
+    #[clap(flatten)]
+    pub logging: LoggingArgs,
+    #[clap(flatten)]
+    pub output: OutputArgs,
+    #[clap(flatten)]
+    pub cookies: CookieArgs,
+    #[clap(flatten)]
+    pub network: NetworkArgs,
+
+    /// Downloads the captions in this language (i.e. `en`, or `pt-BR`)
+    /// Captions uploaded by the creator are preferred over automatically generated ones
+    #[clap(long, parse(try_from_str), value_name = "LANGUAGE")]
+    pub lang: Option<LanguageTag>,
+    /// Downloads all caption tracks into a directory named after the video id
+    #[clap(long)]
+    pub all: bool,
+    /// Lets YouTube translate the captions to this language
+    #[clap(long, parse(try_from_str), requires = "lang", conflicts_with = "all", value_name = "LANGUAGE")]
+    pub translate: Option<LanguageTag>,
+    /// The format of the downloaded captions
+    #[clap(long, default_value = "srt", possible_values = & ["srt", "vtt"], requires = "track")]
+    pub format: CaptionFormat,
+    /// Where to download the captions to [default: . for `--lang`, <VIDEO_ID> for `--all`]
+    #[clap(short, long, requires = "track")]
+    pub dir: Option<PathBuf>,
+    /// The filename of the captions file [default: <VIDEO_ID>.<LANGUAGE>.<FORMAT>]
+    #[clap(short, long, requires = "lang", conflicts_with = "all")]
+    pub filename: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum CaptionFormat {
+    Srt,
+    Vtt,
+}
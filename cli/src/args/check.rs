@@ -1,14 +1,39 @@
-use crate::args::{Identifier, LoggingArgs, StreamFilter};
+use rustube::{Id, IdBuf, Result};
+
+use crate::args::{CookieArgs, LoggingArgs, NetworkArgs, StreamFilter};
 use crate::args::output::OutputArgs;
 
 #[derive(clap::Parser)]
 pub struct CheckArgs {
-    #[clap(flatten)]
-    pub identifier: Identifier,
+    /// An arbitrary video identifier, like the videos URL or the video id
+    #[clap(required_unless_present = "self-test")]
+    identifier: Option<String>,
+    /// Instead of checking a video, check whether rustube still works with YouTube, without
+    /// downloading any media. Exits with a non-zero exit code, if any step of the test fails
+    #[clap(long, conflicts_with = "identifier")]
+    pub self_test: bool,
+    /// Fail the self test on values rustube does not know about, like new codecs, instead of
+    /// tolerating them. Useful to detect API changes early
+    #[clap(long, requires = "self-test")]
+    pub strict: bool,
     #[clap(flatten)]
     pub stream_filter: StreamFilter,
     #[clap(flatten)]
     pub logging: LoggingArgs,
     #[clap(flatten)]
     pub output: OutputArgs,
+    #[clap(flatten)]
+    pub cookies: CookieArgs,
+    #[clap(flatten)]
+    pub network: NetworkArgs,
+}
+
+impl CheckArgs {
+    /// The id of the video to check, which is only missing for `--self-test`.
+    pub fn id(&self) -> Result<Option<IdBuf>> {
+        self.identifier
+            .as_deref()
+            .map(|identifier| Ok(Id::from_raw(identifier)?.into_owned()))
+            .transpose()
+    }
 }
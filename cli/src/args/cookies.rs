@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+
+use rustube::fetcher::{cookies_from_file, Jar, save_cookies_to_file};
+use rustube::fetcher::auth::OAuth;
+
+#[derive(Clone, Parser)]
+pub struct CookieArgs {
+    /// A Netscape cookies.txt file (i.e. exported from a browser), whose cookies are used for all
+    /// requests. The file is updated with the new cookies on exit, and created if it doesn't exist
+    #[clap(long, value_name = "FILE")]
+    pub cookies: Option<PathBuf>,
+    /// Signs in with the OAuth device flow of YouTube's TV app, i.e. to download members-only
+    /// videos. The first time, a code has to be entered at the shown URL
+    #[clap(long)]
+    pub oauth: bool,
+    /// The file, the OAuth tokens are stored in. Delete it to sign in again
+    #[clap(long, requires = "oauth", default_value = "rustube_oauth.json", value_name = "FILE")]
+    pub oauth_token: PathBuf,
+}
+
+impl CookieArgs {
+    /// The cookie jar to use for all requests.
+    pub fn jar(&self) -> Result<Arc<Jar>> {
+        let jar = match &self.cookies {
+            Some(path) if path.exists() => cookies_from_file(path)
+                .with_context(|| format!("Could not load the cookies from {path:?}"))?,
+            _ => Jar::default(),
+        };
+        jar.add_recommended_cookies();
+
+        Ok(Arc::new(jar))
+    }
+
+    /// The OAuth authentication, if `--oauth` was passed.
+    pub fn oauth(&self) -> Option<OAuth> {
+        self.oauth.then(|| {
+            OAuth::new(&self.oauth_token).on_device_code(|code| eprintln!(
+                "To sign in, go to {} and enter the code {}",
+                code.verification_url, code.user_code,
+            ))
+        })
+    }
+
+    /// Writes the cookies of `jar` back to the cookie file, if there is one.
+    pub fn save(&self, jar: &Jar) -> Result<()> {
+        match &self.cookies {
+            Some(path) => save_cookies_to_file(jar, path)
+                .with_context(|| format!("Could not save the cookies to {path:?}")),
+            None => Ok(()),
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use std::path::PathBuf;
 
 use clap::Parser;
+use rustube::FilenameTemplate;
 
-use crate::args::Identifier;
+use crate::args::{CookieArgs, Identifier, NetworkArgs};
 use crate::args::logging::LoggingArgs;
 use crate::args::output::OutputArgs;
 use crate::args::stream_filter::StreamFilter;
@@ -17,6 +18,10 @@ pub struct DownloadArgs {
     pub logging: LoggingArgs,
     #[clap(flatten)]
     pub output: OutputArgs,
+    #[clap(flatten)]
+    pub cookies: CookieArgs,
+    #[clap(flatten)]
+    pub network: NetworkArgs,
 
     /// Where to download the video to [default: .]
     #[clap(short, long)]
@@ -25,4 +30,8 @@ pub struct DownloadArgs {
     /// If the file already exists, it will be removed, even if the download fails!
     #[clap(short, long)]
     pub filename: Option<PathBuf>,
+    /// Names the video file after a template, i.e. "{channel}/{title} [{id}].{ext}", relative to
+    /// --dir. Supports {id}, {title}, {channel}, {upload_date}, {quality}, {itag}, and {ext}
+    #[clap(long, parse(try_from_str), conflicts_with = "filename", value_name = "TEMPLATE")]
+    pub output_template: Option<FilenameTemplate>,
 }
@@ -1,4 +1,4 @@
-use crate::args::{Identifier, LoggingArgs};
+use crate::args::{CookieArgs, Identifier, LoggingArgs, NetworkArgs};
 use crate::args::output::OutputArgs;
 
 #[derive(clap::Parser)]
@@ -9,4 +9,8 @@ pub struct FetchArgs {
     pub logging: LoggingArgs,
     #[clap(flatten)]
     pub output: OutputArgs,
+    #[clap(flatten)]
+    pub cookies: CookieArgs,
+    #[clap(flatten)]
+    pub network: NetworkArgs,
 }
@@ -1,10 +1,10 @@
 use std::fmt::Arguments;
 
 use clap::Parser;
+#[cfg(feature = "color")]
 use fern::colors::{Color, ColoredLevelConfig};
 use fern::FormatCallback;
 use log::{LevelFilter, Record};
-use strum::EnumString;
 
 #[derive(Parser)]
 pub struct LoggingArgs {
@@ -14,38 +14,66 @@ pub struct LoggingArgs {
     #[clap(long, short, parse(from_occurrences))]
     verbose: u8,
 
-    /// Show a progress bar
-    #[clap(long, short, conflicts_with = "verbose")]
-    progress: bool,
+    /// How to report the download progress (`-p` is short for `--progress=bar`)
+    /// `bar` shows a progress bar on stderr, `json` prints one JSON object per progress event to
+    /// stdout (all other output is then written to stderr)
+    #[clap(
+    long, short,
+    default_value = "none",
+    default_missing_value = "bar",
+    min_values = 0,
+    max_values = 1,
+    require_equals = true,
+    possible_values = & ["bar", "json", "none"],
+    value_name = "MODE"
+    )]
+    #[cfg(feature = "download")]
+    pub progress: ProgressMode,
 
     /// When to log coloredd
     #[clap(long, default_value = "always", possible_values = & ["always", "never"], value_name = "WHEN")]
+    #[cfg(feature = "color")]
     color: ColorUsage,
 
     /// Turn off logging for all crates
     #[clap(long, short, conflicts_with = "verbose")]
     quiet: bool,
+
+    /// Print how long each phase (fetching, descrambling, downloading) took to stderr
+    #[clap(long)]
+    pub timings: bool,
 }
 
 impl LoggingArgs {
     pub fn init_logger(&self) {
-        if self.quiet || self.progress { return; }
+        if self.quiet { return; }
+        #[cfg(feature = "download")]
+        if self.progress == ProgressMode::Bar { return; }
 
         let formatter = self.log_msg_formatter();
+        #[cfg(feature = "download")]
+        let output: fern::Output = match self.progress {
+            // stdout is reserved for the progress events
+            ProgressMode::Json => std::io::stderr().into(),
+            _ => std::io::stdout().into(),
+        };
+        #[cfg(not(feature = "download"))]
+        let output: fern::Output = std::io::stdout().into();
 
         fern::Dispatch::new()
             .level(log::LevelFilter::Warn)
             .level_for("rustube", self.level_filter())
             .format(formatter)
-            .chain(std::io::stdout())
+            .chain(output)
             .apply()
             .expect("The global logger was already initialized");
     }
 
+    #[cfg(feature = "download")]
     pub fn init_progress_bar(&self, total: u64) -> pbr::ProgressBar<Box<dyn std::io::Write + Send + Sync>> {
         let writer = match self.progress {
-            true => Box::new(std::io::stderr()) as _,
-            false => Box::new(std::io::sink()) as _,
+            ProgressMode::Bar => Box::new(std::io::stderr()) as _,
+            _ => Box::new(std::io::sink()) as _,
         };
         let mut pb = pbr::ProgressBar::on(writer, total);
         pb.set_units(pbr::Units::Bytes);
@@ -69,6 +97,7 @@ impl LoggingArgs {
             ))
         }
 
+        #[cfg(feature = "color")]
         match self.color {
             ColorUsage::Always => {
                 |out: FormatCallback, message: &Arguments, record: &Record| {
@@ -99,6 +128,15 @@ impl LoggingArgs {
                 }
             }
         }
+        #[cfg(not(feature = "color"))]
+        |out: FormatCallback, message: &Arguments, record: &Record| {
+            format_msg(
+                out,
+                record.level(),
+                record,
+                message,
+            );
+        }
     }
 
     fn level_filter(&self) -> log::LevelFilter {
@@ -112,7 +150,17 @@ impl LoggingArgs {
     }
 }
 
-#[derive(Parser, EnumString)]
+#[cfg(feature = "download")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ProgressMode {
+    Bar,
+    Json,
+    None,
+}
+
+#[cfg(feature = "color")]
+#[derive(Parser, strum::EnumString)]
 #[strum(serialize_all = "kebab-case")]
 enum ColorUsage {
     Always,
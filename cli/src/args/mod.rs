@@ -1,16 +1,26 @@
 use clap::Parser;
 
+pub use captions::{CaptionFormat, CaptionsArgs};
 pub use check::CheckArgs;
+pub use cookies::CookieArgs;
+#[cfg(feature = "download")]
 pub use download::DownloadArgs;
 pub use fetch::FetchArgs;
 pub use logging::LoggingArgs;
+#[cfg(feature = "download")]
+pub use logging::ProgressMode;
+pub use network::NetworkArgs;
 use rustube::{Id, IdBuf, Result};
 pub use stream_filter::StreamFilter;
 
+mod captions;
 mod check;
+mod cookies;
+#[cfg(feature = "download")]
 mod download;
 mod fetch;
 mod logging;
+mod network;
 mod output;
 mod stream_filter;
 
@@ -25,6 +35,13 @@ For help with certain subcommands run `rustube <SUBCOMMAND> --help`.
 "
 )]
 pub enum Command {
+    #[clap(about = "\
+    Lists and downloads the captions of a YouTube video\n\
+    Without `--lang` or `--all`, the available caption tracks are listed. With `--lang`, the track \
+    in that language is downloaded, and with `--all`, all tracks are downloaded into a directory \
+    named after the video id.\
+    ")]
+    Captions(CaptionsArgs),
     #[clap(about = "\
     Checks if a video can be downloaded and fetches information about it\n\
     This command is similar to fetch, in the way that it also fetches information about a video, \
@@ -40,6 +57,7 @@ pub enum Command {
     By default, the Stream with the best quality and both a video, and an audio track will be \
     downloaded. To specify other download behavior, have a look the the subcommand help.\
     ")]
+    #[cfg(feature = "download")]
     Download(DownloadArgs),
     #[clap(about = "\
     Fetches information about a YouTube video\n\
@@ -54,6 +72,38 @@ pub enum Command {
     Fetch(FetchArgs),
 }
 
+impl Command {
+    pub fn cookies(&self) -> &CookieArgs {
+        match self {
+            Command::Captions(args) => &args.cookies,
+            Command::Check(args) => &args.cookies,
+            #[cfg(feature = "download")]
+            Command::Download(args) => &args.cookies,
+            Command::Fetch(args) => &args.cookies,
+        }
+    }
+
+    pub fn network(&self) -> &NetworkArgs {
+        match self {
+            Command::Captions(args) => &args.network,
+            Command::Check(args) => &args.network,
+            #[cfg(feature = "download")]
+            Command::Download(args) => &args.network,
+            Command::Fetch(args) => &args.network,
+        }
+    }
+
+    pub fn logging(&self) -> &LoggingArgs {
+        match self {
+            Command::Captions(args) => &args.logging,
+            Command::Check(args) => &args.logging,
+            #[cfg(feature = "download")]
+            Command::Download(args) => &args.logging,
+            Command::Fetch(args) => &args.logging,
+        }
+    }
+}
+
 #[derive(Parser)]
 pub struct Identifier {
     /// An arbitrary video identifier, like the videos URL or the video id
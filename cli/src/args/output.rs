@@ -1,18 +1,23 @@
+use rustube::serialize::OutputLevel;
+
 use crate::output_format::OutputFormat;
-use crate::output_level::OutputLevel;
 
 #[derive(clap::Parser)]
 pub struct OutputArgs {
     /// The format in which the information should be printed
-    #[clap(
-    short, long = "output",
+    #[clap(short, long = "output")]
+    #[cfg_attr(feature = "yaml", clap(
     default_value = "yaml",
     possible_values = & ["debug", "pretty-debug", "json", "pretty-json", "yaml"]
-    )]
+    ))]
+    #[cfg_attr(not(feature = "yaml"), clap(
+    default_value = "pretty-json",
+    possible_values = & ["debug", "pretty-debug", "json", "pretty-json"]
+    ))]
     pub output_format: OutputFormat,
     /// The amount of information printed to the terminal
     /// To get more information, different levels can be combined, by separating them with a `|`.
-    /// [possible values: url, general, video-track, audio-track, video]
+    /// [possible values: url, general, video-track, audio-track, thumbnails, verbose, full, video]
     #[clap(short = 'l', long = "level", default_value = "url | general | video-track | audio-track")]
     pub output_level: OutputLevel,
 }
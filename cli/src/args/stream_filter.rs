@@ -1,8 +1,6 @@
-use std::cmp::Ordering;
-
 use clap::Parser;
 
-use rustube::Stream;
+use rustube::{Stream, StreamQuery};
 use rustube::video_info::player_response::streaming_data::{AudioQuality, Quality, QualityLabel};
 
 #[derive(Parser)]
@@ -34,7 +32,7 @@ pub struct StreamFilter {
     long,
     possible_values = & ["tiny", "small", "medium", "large", "highres", "hd720", "hd1080", "hd1440", "hd2160"],
     conflicts_with_all(& ["best-quality", "worst-quality"]),
-    parse(try_from_str = parse_json)
+    parse(try_from_str)
     )]
     quality: Option<Quality>,
     /// Download the stream with this quality label
@@ -42,11 +40,11 @@ pub struct StreamFilter {
     long,
     possible_values = & ["144p", "144p60 HDR", "240p", "240p60 HDR", "360p", "360p60", "360p60 HDR",
     "480p", "480p60", "480p60 HDR", "720p", "720p50", "720p60", "720p60 HDR", "1080p", "1080p50",
-    "1080p60", "1080p60 HDR", "1440p", "1440p60", "1440p60 HDR", "2160p", "2160p60", "2160p60 HDR",
+    "1080p60", "1080p60 HDR", "1080p Premium", "1440p", "1440p60", "1440p60 HDR", "2160p", "2160p60", "2160p60 HDR",
     "4320p", "4320p60",
     ],
     conflicts_with_all(& ["best-quality", "worst-quality", "no-video"]),
-    parse(try_from_str = parse_json)
+    parse(try_from_str)
     )]
     video_quality: Option<QualityLabel>,
     /// Download the stream with this audio quality label (the lowercase values are aliases for the
@@ -58,69 +56,57 @@ pub struct StreamFilter {
     "low", "medium", "high"
     ],
     conflicts_with_all(& ["best-quality", "worst-quality", "no-audio"]),
-    parse(try_from_str = parse_json)
+    parse(try_from_str)
     )]
     audio_quality: Option<AudioQuality>,
+    /// Download the audio track in this language (i.e. `en` or `de-DE`), for videos with multiple
+    /// audio tracks [default: the original audio track]
+    #[clap(long, conflicts_with = "no-audio", value_name = "LANG")]
+    audio_language: Option<String>,
+    /// Also pick streams, that are only available to YouTube Premium subscribers (i.e.
+    /// `1080p Premium`). Downloading them fails without a Premium account
+    #[clap(long)]
+    include_premium: bool,
 }
 
 impl StreamFilter {
-    pub fn stream_matches(&self, stream: &Stream) -> bool {
-        let video_ok = !self.no_video ^ !(stream.includes_video_track || self.ignore_missing_video);
-        let audio_ok = !self.no_audio ^ !(stream.includes_audio_track || self.ignore_missing_audio);
-        let quality_ok = self.quality
-            .map(|q| stream.quality == q)
-            .unwrap_or(true);
-        let video_quality_ok = self.video_quality
-            .map(|ref q| stream.quality_label.as_ref() == Some(q))
-            .unwrap_or(true);
-        let audio_quality_ok = self.audio_quality
-            .map(|ref q| stream.audio_quality.as_ref() == Some(q))
-            .unwrap_or(true);
-
-        let quality_ok = quality_ok && video_quality_ok && audio_quality_ok;
-
-        video_ok && audio_ok && quality_ok
-    }
-
-    pub fn max_stream(&self, lhs: &Stream, rhs: &Stream) -> Ordering {
-        if self.best_quality || !self.worst_quality {
-            self.cmp_stream(lhs, rhs)
-        } else /* if self.worst_quality */ {
-            self.cmp_stream(rhs, lhs)
+    /// The [`StreamQuery`] of the arguments.
+    pub fn query(&self) -> StreamQuery {
+        let mut query = StreamQuery::new().include_premium(self.include_premium);
+        if self.no_video {
+            query = query.only_audio();
+        } else if self.no_audio {
+            query = query.only_video();
+        } else {
+            if !self.ignore_missing_video {
+                query = query.has_video(true);
+            }
+            if !self.ignore_missing_audio {
+                query = query.has_audio(true);
+            }
         }
-    }
 
-    fn cmp_stream(&self, lhs: &Stream, rhs: &Stream) -> Ordering {
-        macro_rules! try_ord {
-            ($lhs:expr, $rhs:expr) => {
-                if let Some(ord) = Self::cmp_opts($lhs, $rhs) {
-                    return ord;
-                }
-            };
+        if let Some(ref quality) = self.quality {
+            query = query.quality(quality.clone());
         }
-
-        try_ord!(&lhs.width, &rhs.width);
-        try_ord!(&lhs.bitrate, &rhs.bitrate);
-        try_ord!(&lhs.quality_label, &rhs.quality_label);
-        try_ord!(&lhs.audio_quality, &rhs.audio_quality);
-
-        lhs.quality.cmp(&rhs.quality)
+        if let Some(ref video_quality) = self.video_quality {
+            query = query.quality_label(video_quality.clone());
+        }
+        if let Some(audio_quality) = self.audio_quality {
+            query = query.audio_quality(audio_quality);
+        }
+        if let Some(ref audio_language) = self.audio_language {
+            query = query.audio_language(audio_language.as_str());
+        }
+        query
     }
 
-    fn cmp_opts<T: Ord>(lsh: &Option<T>, rhs: &Option<T>) -> Option<Ordering> {
-        match (lsh, rhs) {
-            (Some(lhs), Some(rhs)) => {
-                match lhs.cmp(rhs) {
-                    Ordering::Equal => None,
-                    ord => Some(ord)
-                }
-            }
-            _ => None
+    /// The best, or with `--worst-quality` the worst, of the `streams`, that match the arguments.
+    #[cfg(feature = "download")]
+    pub fn pick<'a>(&self, streams: impl IntoIterator<Item=&'a Stream>) -> Option<&'a Stream> {
+        match self.worst_quality {
+            true => self.query().worst(streams),
+            false => self.query().best(streams),
         }
     }
 }
-
-fn parse_json<T: for<'de> serde::Deserialize<'de>>(s: &str) -> anyhow::Result<T> {
-    let args = format!("\"{s}\"");
-    Ok(serde_json::from_str(&args)?)
-}
@@ -0,0 +1,34 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use rustube::captions::CaptionTrack;
+use rustube::LanguageTag;
+
+/// A caption track, and where it was downloaded to, if it was.
+#[derive(Debug, Serialize)]
+pub struct CaptionTrackSerializer<'a> {
+    language: &'a LanguageTag,
+    name: String,
+    auto_generated: bool,
+    translatable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<PathBuf>,
+}
+
+impl<'a> CaptionTrackSerializer<'a> {
+    pub fn new(track: &'a CaptionTrack, path: Option<PathBuf>) -> Self {
+        Self {
+            language: &track.language_code,
+            name: track.display_name(),
+            auto_generated: track.is_auto_generated(),
+            translatable: track.is_translatable,
+            path,
+        }
+    }
+
+    #[inline]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
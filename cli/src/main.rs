@@ -1,53 +1,123 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "download")]
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use clap::Parser;
 
+#[cfg(feature = "download")]
 use args::DownloadArgs;
 use args::StreamFilter;
-use rustube::{Error, Id, IdBuf, Stream, Video, VideoFetcher, VideoInfo};
+use rustube::{Error, Id, IdBuf, LanguageTag, Metrics, MetricsReport, NoStreamsReason, ParseMode, Stream, Video, VideoFetcher, VideoInfo};
+use rustube::captions::{CaptionTrack, Transcript};
+use rustube::fetcher::auth::OAuth;
+#[cfg(feature = "download")]
 use rustube::Callback;
+use rustube::reqwest::Client;
+use rustube::serialize::VideoSerializer;
+use rustube::url::Url;
 
-use crate::args::{CheckArgs, Command, FetchArgs};
-use crate::video_serializer::VideoSerializer;
+use crate::args::{CaptionFormat, CaptionsArgs, CheckArgs, Command, FetchArgs};
+#[cfg(feature = "download")]
+use crate::args::ProgressMode;
+use crate::caption_serializer::CaptionTrackSerializer;
+#[cfg(feature = "download")]
+use crate::output_format::ProgressEvent;
 
 mod args;
+mod caption_serializer;
 mod output_format;
-mod output_level;
-mod stream_serializer;
-mod video_serializer;
+
+/// The minimal time between two JSON progress events.
+#[cfg(feature = "download")]
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(250);
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let command: Command = Command::parse();
+    let cookies = command.cookies().clone();
+    let jar = cookies.jar()?;
+    let client = command.network().client(&jar)?;
+    let metrics = command.logging().timings.then(|| Arc::new(Metrics::new()));
+    let settings = FetcherSettings {
+        metrics: metrics.clone(),
+        oauth: cookies.oauth(),
+        base_url: command.network().base_url.clone(),
+    };
 
-    let res = match command {
-        Command::Check(args) => check(args).await,
-        Command::Download(args) => download(args).await,
-        Command::Fetch(args) => fetch(args).await,
+    let mut res = match command {
+        Command::Captions(args) => captions(args, client, settings).await,
+        Command::Check(args) => check(args, client, settings).await,
+        #[cfg(feature = "download")]
+        Command::Download(args) => download(args, client, settings).await,
+        Command::Fetch(args) => fetch(args, client, settings).await,
     };
 
+    if let Some(metrics) = metrics {
+        print_timings(&metrics.report());
+    }
+
+    match (cookies.save(&jar), &res) {
+        (Err(err), Ok(_)) => res = Err(err),
+        (Err(err), Err(_)) => log::error!("{:#}", err),
+        (Ok(_), _) => {}
+    }
+
     if let Err(ref err) = res {
         log::error!("{}\n", err);
-        eprintln!("\
-            If the error is caused by a change to the YouTube API, it would be great if you could \
-            report this. Common indicators of an API change are:\n\
-            1. repeated HTTP 403 status\n\
-            2. unexpected response errors\n\
-            3. deserialization errors\n\
-            There's a predefined issue template in our repo: https://github.com/DzenanJupic/rustube/issues/new?assignees=&labels=youtube-api-changed&template=youtube_api_changed.yml\
-        ");
+        if is_bot_check(err) {
+            eprintln!("\
+                YouTube asks to sign in to confirm you're not a bot. This is not caused by an API \
+                change, but happens for datacenter IPs (i.e. VPNs and servers), or after too many \
+                requests. You can:\n\
+                1. pass the cookies of a signed in account with `--cookies <FILE>` (in the \
+                `cookies.txt` format, which most browser extensions for exporting cookies support)\n\
+                2. use a different IP address, or wait a while\
+            ");
+        } else if let Some(reason) = no_streams_reason(err) {
+            eprintln!("The video has no downloadable streams: {reason}.\n\n    {}\n", reason.advice());
+        } else if let Some(hint) = forbidden_hint(err) {
+            eprintln!("YouTube refused to serve the stream (403 Forbidden). Most probable cause:\n\n    {hint}\n");
+        } else {
+            eprintln!("\
+                If the error is caused by a change to the YouTube API, it would be great if you could \
+                report this. Common indicators of an API change are:\n\
+                1. repeated HTTP 403 status\n\
+                2. unexpected response errors\n\
+                3. deserialization errors\n\
+                There's a predefined issue template in our repo: https://github.com/DzenanJupic/rustube/issues/new?assignees=&labels=youtube-api-changed&template=youtube_api_changed.yml\
+            ");
+        }
     }
 
     res
 }
 
-async fn check(args: CheckArgs) -> Result<()> {
+async fn check(args: CheckArgs, client: Client, settings: FetcherSettings) -> Result<()> {
     args.logging.init_logger();
 
-    let id = args.identifier.id()?;
-    let (video_info, streams) = get_streams(id, &args.stream_filter).await?;
-    let video_serializer = VideoSerializer::new(video_info, streams, args.output.output_level);
+    if args.self_test {
+        return self_test(args, client).await;
+    }
+
+    let id = args.id()?.context("missing video identifier")?;
+    let (video_info, streams) = match get_streams(id, client, settings, &args.stream_filter).await {
+        Ok((video_info, streams)) => (video_info, streams.collect::<Vec<_>>()),
+        // still print the metadata of i.e. upcoming premieres
+        Err(err) => match err.downcast::<Error>() {
+            Ok(Error::NoStreamingData { video_info, reason }) => {
+                match reason {
+                    Some(reason) => eprintln!("The video has no downloadable streams yet: {reason}"),
+                    None => eprintln!("The video has no downloadable streams yet"),
+                }
+                (*video_info, Vec::new())
+            }
+            Ok(err) => return Err(err.into()),
+            Err(err) => return Err(err),
+        },
+    };
+    let video_serializer = VideoSerializer::new(&video_info, &streams, args.output.output_level);
 
     let output = args
         .output
@@ -58,41 +128,121 @@ async fn check(args: CheckArgs) -> Result<()> {
     Ok(())
 }
 
-async fn download(args: DownloadArgs) -> Result<()> {
+async fn self_test(args: CheckArgs, client: Client) -> Result<()> {
+    let parse_mode = match args.strict {
+        true => ParseMode::Strict,
+        false => ParseMode::Lenient,
+    };
+    let id = Id::from_str(rustube::fetcher::SELF_CHECK_VIDEO_ID)?.into_owned();
+    let report = VideoFetcher::from_id_with_client(id, client)
+        .with_parse_mode(parse_mode)
+        .self_check()
+        .await;
+
+    let output = args
+        .output
+        .output_format
+        .serialize_output(&report)?;
+    println!("{output}");
+
+    match report.failures().count() {
+        0 => Ok(()),
+        failures => anyhow::bail!("{failures} of {} self test steps failed", report.steps.len()),
+    }
+}
+
+#[cfg(feature = "download")]
+async fn download(args: DownloadArgs, client: Client, settings: FetcherSettings) -> Result<()> {
     args.logging.init_logger();
 
     let id = args.identifier.id()?;
-    let (video_info, stream) = get_stream(id.as_owned(), args.stream_filter).await?;
-    let download_path = download_path(args.filename, stream.mime.subtype().as_str(), args.dir, id);
+    let progress = args.logging.progress;
+    let res = download_stream(args, id.as_owned(), client, settings).await;
 
-    let mut pb = args.logging.init_progress_bar(stream.content_length().await?);
+    if let (Err(err), ProgressMode::Json) = (&res, progress) {
+        ProgressEvent::Error { video_id: id.as_str(), message: format!("{err:#}") }.emit();
+    }
+
+    res
+}
+
+#[cfg(feature = "download")]
+async fn download_stream(args: DownloadArgs, id: IdBuf, client: Client, settings: FetcherSettings) -> Result<()> {
+    let json_progress = args.logging.progress == ProgressMode::Json;
+    let (video_info, stream) = get_stream(id.as_owned(), client, settings, args.stream_filter).await?;
+    let download_path = match args.output_template {
+        Some(ref template) => {
+            let path = args.dir.clone().unwrap_or_default().join(template.render(&video_info, &stream));
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent).with_context(|| format!("Could not create {parent:?}"))?;
+            }
+            path
+        }
+        None => download_path(args.filename, stream.mime.subtype().as_str(), args.dir, id.as_borrowed()),
+    };
+
+    let total = stream.content_length().await?;
+    if json_progress {
+        ProgressEvent::Start { video_id: id.as_str(), total: Some(total) }.emit();
+    }
+
+    let mut pb = args.logging.init_progress_bar(total);
+    let mut last_event: Option<Instant> = None;
     let callback = Callback::new()
         .connect_on_progress_closure(|cargs| {
             // update progress bar
             pb.set(cargs.current_chunk as u64);
+
+            let emit_event = json_progress && last_event
+                .map(|last| last.elapsed() >= PROGRESS_EVENT_INTERVAL)
+                .unwrap_or(true);
+            if emit_event {
+                last_event = Some(Instant::now());
+                ProgressEvent::Progress {
+                    video_id: id.as_str(),
+                    bytes: cargs.current_chunk as u64,
+                    total: cargs.content_length,
+                    speed: cargs.speed(),
+                    eta_secs: cargs.eta().map(|eta| eta.as_secs_f64()),
+                }.emit();
+            }
         });
 
     stream
         .download_to_with_callback(&download_path, callback)
         .await?;
     pb.finish_println(&format!("Finished downloading video to {download_path:?}\n"));
+    if json_progress {
+        ProgressEvent::Finish { video_id: id.as_str(), path: &download_path, bytes: total }.emit();
+    }
 
     let video_serializer = VideoSerializer::new(
-        video_info,
-        std::iter::once(stream),
+        &video_info,
+        std::iter::once(&stream),
         args.output.output_level,
     );
     let output = args.output.output_format.serialize_output(&video_serializer).unwrap();
-    println!("{output}");
+    match json_progress {
+        // stdout is reserved for the progress events
+        true => eprintln!("{output}"),
+        false => println!("{output}"),
+    }
 
     Ok(())
 }
 
-async fn fetch(args: FetchArgs) -> Result<()> {
+async fn fetch(args: FetchArgs, client: Client, settings: FetcherSettings) -> Result<()> {
     args.logging.init_logger();
 
     let id = args.identifier.id()?;
-    let video_info = rustube::VideoFetcher::from_id(id)?.fetch_info().await?;
+    let video_info = video_fetcher(id, client, settings).fetch_info().await?;
+
+    // stderr, so the output stays parsable
+    let playability = video_info.playability();
+    match playability.reason() {
+        Some(reason) => eprintln!("Playability: {:?} ({})", playability.kind(), reason),
+        None => eprintln!("Playability: {:?}", playability.kind()),
+    }
 
     let output = args.output.output_format.serialize_output(&video_info)?;
     println!("{output}");
@@ -100,12 +250,106 @@ async fn fetch(args: FetchArgs) -> Result<()> {
     Ok(())
 }
 
-async fn get_stream(id: IdBuf, stream_filter: StreamFilter) -> Result<(VideoInfo, Stream)> {
-    let (video_info, streams) = get_streams(id, &stream_filter).await?;
+async fn captions(args: CaptionsArgs, client: Client, settings: FetcherSettings) -> Result<()> {
+    args.logging.init_logger();
+
+    let id = args.identifier.id()?;
+    let video_info = video_fetcher(id.as_owned(), client.clone(), settings).fetch_info().await?;
+    let tracks = video_info.caption_tracks();
+    if tracks.is_empty() {
+        anyhow::bail!("The video has no captions");
+    }
+
+    let downloaded = match (&args.lang, args.all) {
+        (Some(lang), _) => {
+            let track = select_caption_track(tracks, lang)
+                .with_context(|| format!("The video has no `{lang}` captions"))?;
+            let transcript = match args.translate {
+                Some(ref translation) => track.download_translated(&client, translation).await?,
+                None => track.download(&client).await?,
+            };
+
+            let language = args.translate.as_ref().unwrap_or(lang);
+            let filename = args.filename.clone().unwrap_or_else(|| {
+                format!("{}.{}.{}", id.as_str(), language, args.format).into()
+            });
+            let path = args.dir.clone().unwrap_or_default().join(filename);
+            write_captions(&transcript, args.format, &path)?;
+            vec![CaptionTrackSerializer::new(track, Some(path))]
+        }
+        (None, true) => {
+            let dir = args.dir.clone().unwrap_or_else(|| id.as_str().into());
+            std::fs::create_dir_all(&dir).with_context(|| format!("Could not create {dir:?}"))?;
+
+            let mut downloaded: Vec<CaptionTrackSerializer> = Vec::with_capacity(tracks.len());
+            for track in tracks {
+                let transcript = track.download(&client).await?;
+                let path = caption_path(&dir, track, args.format, |path| {
+                    downloaded.iter().any(|d| d.path() == Some(path))
+                });
+                write_captions(&transcript, args.format, &path)?;
+                downloaded.push(CaptionTrackSerializer::new(track, Some(path)));
+            }
+            downloaded
+        }
+        (None, false) => tracks
+            .iter()
+            .map(|track| CaptionTrackSerializer::new(track, None))
+            .collect(),
+    };
+
+    let output = args.output.output_format.serialize_output(&downloaded)?;
+    println!("{output}");
+
+    Ok(())
+}
+
+/// The track in `lang`, preferring exact matches over i.e. `en-US` for `en`, and captions uploaded
+/// by the creator over automatically generated ones.
+fn select_caption_track<'a>(tracks: &'a [CaptionTrack], lang: &LanguageTag) -> Option<&'a CaptionTrack> {
+    tracks
+        .iter()
+        .filter(|track| track.language_code.matches(lang))
+        .max_by_key(|track| (track.language_code == *lang, !track.is_auto_generated()))
+}
+
+/// `<DIR>/<LANGUAGE>[.auto].<FORMAT>`, with a counter, if the path is already `taken`.
+fn caption_path(dir: &Path, track: &CaptionTrack, format: CaptionFormat, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    let stem = match track.is_auto_generated() {
+        true => format!("{}.auto", track.language_code),
+        false => track.language_code.to_string(),
+    };
+
+    let mut path = dir.join(format!("{stem}.{format}"));
+    let mut counter = 1;
+    while taken(&path) {
+        counter += 1;
+        path = dir.join(format!("{stem}.{counter}.{format}"));
+    }
+    path
+}
 
-    let stream = streams
-        .max_by(|lhs, rhs| stream_filter.max_stream(lhs, rhs))
-        .ok_or(Error::NoStreams)
+fn write_captions(transcript: &Transcript, format: CaptionFormat, path: &Path) -> Result<()> {
+    let captions = match format {
+        CaptionFormat::Srt => transcript.to_srt(),
+        CaptionFormat::Vtt => transcript.to_vtt(),
+    };
+    std::fs::write(path, captions).with_context(|| format!("Could not write the captions to {path:?}"))
+}
+
+#[cfg(feature = "download")]
+async fn get_stream(
+    id: IdBuf,
+    client: Client,
+    settings: FetcherSettings,
+    stream_filter: StreamFilter,
+) -> Result<(VideoInfo, Stream)> {
+    let (video_info, streams) = get_video(id, client, settings).await?.into_parts();
+
+    let stream = stream_filter
+        .pick(&streams)
+        .cloned()
+        .ok_or(Error::NoStreams(NoStreamsReason::NoMatchingStream))
         .context("There are no streams, that match all your criteria")?;
 
     Ok((video_info, stream))
@@ -113,19 +357,22 @@ async fn get_stream(id: IdBuf, stream_filter: StreamFilter) -> Result<(VideoInfo
 
 async fn get_streams(
     id: IdBuf,
+    client: Client,
+    settings: FetcherSettings,
     stream_filter: &'_ StreamFilter,
 ) -> Result<(VideoInfo, impl Iterator<Item=Stream> + '_)> {
-    let (video_info, streams) = get_video(id).await?.into_parts();
+    let (video_info, streams) = get_video(id, client, settings).await?.into_parts();
 
+    let query = stream_filter.query();
     let streams = streams
         .into_iter()
-        .filter(move |stream| stream_filter.stream_matches(stream));
+        .filter(move |stream| query.matches(stream));
 
     Ok((video_info, streams))
 }
 
-async fn get_video(id: IdBuf) -> Result<Video> {
-    VideoFetcher::from_id(id)?
+async fn get_video(id: IdBuf, client: Client, settings: FetcherSettings) -> Result<Video> {
+    video_fetcher(id, client, settings)
         .fetch()
         .await
         .context("Could not fetch the video information")?
@@ -133,6 +380,82 @@ async fn get_video(id: IdBuf) -> Result<Video> {
         .context("Could not descramble the video information")
 }
 
+/// Whether YouTube asked to sign in to confirm we're not a bot.
+fn is_bot_check(err: &anyhow::Error) -> bool {
+    err
+        .chain()
+        .filter_map(|err| err.downcast_ref::<Error>())
+        .any(Error::is_bot_check)
+}
+
+/// The reason of the first error in the chain of `err`, that's about the video having no streams.
+fn no_streams_reason(err: &anyhow::Error) -> Option<NoStreamsReason> {
+    err
+        .chain()
+        .filter_map(|err| err.downcast_ref::<Error>())
+        .find_map(Error::no_streams_reason)
+}
+
+/// The hint of the first [`Error::Forbidden`] in the chain of `err`.
+#[cfg(feature = "download")]
+fn forbidden_hint(err: &anyhow::Error) -> Option<&'static str> {
+    err
+        .chain()
+        .filter_map(|err| err.downcast_ref::<Error>())
+        .find_map(|err| match err {
+            Error::Forbidden { hint, .. } => Some(*hint),
+            Error::UrlExpired { .. } => Some(rustube::stream::forbidden::EXPIRED),
+            _ => None,
+        })
+}
+
+/// Without the `download` feature, no media is requested, so YouTube can't refuse to serve it.
+#[cfg(not(feature = "download"))]
+fn forbidden_hint(_err: &anyhow::Error) -> Option<&'static str> {
+    None
+}
+
+/// Prints the time and bytes spent per phase to stderr, so it doesn't mix with the output.
+fn print_timings(report: &MetricsReport) {
+    eprintln!("{:<12} {:>5} {:>10} {:>12}", "phase", "count", "time", "bytes");
+    for (phase, metrics) in &report.phases {
+        eprintln!(
+            "{:<12} {:>5} {:>9.3}s {:>12}",
+            phase.to_string(), metrics.count, metrics.duration.as_secs_f64(), metrics.bytes,
+        );
+    }
+    eprintln!(
+        "{:<12} {:>5} {:>9.3}s {:>12}",
+        "total", "", report.total_duration.as_secs_f64(), report.total_bytes,
+    );
+    if report.rate_limit_hits > 0 {
+        eprintln!("rate limited {} time(s)", report.rate_limit_hits);
+    }
+}
+
+/// The settings, all [`VideoFetcher`]s are built with.
+#[derive(Clone)]
+struct FetcherSettings {
+    metrics: Option<Arc<Metrics>>,
+    oauth: Option<OAuth>,
+    base_url: Option<Url>,
+}
+
+fn video_fetcher(id: IdBuf, client: Client, settings: FetcherSettings) -> VideoFetcher {
+    let mut fetcher = VideoFetcher::from_id_with_client(id, client);
+    if let Some(metrics) = settings.metrics {
+        fetcher = fetcher.with_metrics(metrics);
+    }
+    if let Some(oauth) = settings.oauth {
+        fetcher = fetcher.with_oauth(oauth);
+    }
+    if let Some(base_url) = settings.base_url {
+        fetcher = fetcher.with_base_url(base_url);
+    }
+    fetcher
+}
+
+#[cfg(feature = "download")]
 pub fn download_path(
     filename: Option<PathBuf>,
     extension: &str,
@@ -142,7 +465,7 @@ pub fn download_path(
     let filename =
         filename.unwrap_or_else(|| format!("{}.{}", video_id.as_str(), extension).into());
 
-    let mut path = dir.unwrap_or_else(PathBuf::new);
+    let mut path = dir.unwrap_or_default();
 
     path.push(filename);
     path
@@ -1,12 +1,19 @@
+#[cfg(feature = "download")]
+use std::path::Path;
+
 use anyhow::Result;
+#[cfg(feature = "download")]
+use serde::Serialize;
 
-#[derive(Clone, Copy, Debug, strum::EnumString)]
+#[derive(Clone, Copy, Debug, Default, strum::EnumString)]
 #[strum(serialize_all = "kebab-case")]
 pub enum OutputFormat {
     Debug,
     PrettyDebug,
     Json,
+    #[default]
     PrettyJson,
+    #[cfg(feature = "yaml")]
     Yaml,
 }
 
@@ -20,13 +27,45 @@ impl OutputFormat {
             PrettyDebug => Ok(format!("{output:#?}")),
             Json => Ok(serde_json::to_string(output)?),
             PrettyJson => Ok(serde_json::to_string_pretty(output)?),
-            Yaml => Ok(serde_yaml::to_string(output)?)
+            #[cfg(feature = "yaml")]
+            Yaml => Ok(serde_yaml::to_string(output)?),
         }
     }
 }
 
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::PrettyJson
+/// A single line of the machine-readable progress output (`--progress json`).
+#[cfg(feature = "download")]
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent<'a> {
+    Start {
+        video_id: &'a str,
+        total: Option<u64>,
+    },
+    Progress {
+        video_id: &'a str,
+        bytes: u64,
+        total: Option<u64>,
+        /// bytes per second
+        speed: Option<f64>,
+        eta_secs: Option<f64>,
+    },
+    Finish {
+        video_id: &'a str,
+        path: &'a Path,
+        bytes: u64,
+    },
+    Error {
+        video_id: &'a str,
+        message: String,
+    },
+}
+
+#[cfg(feature = "download")]
+impl ProgressEvent<'_> {
+    /// Prints the event as a single JSON line to stdout.
+    pub fn emit(&self) {
+        let line = serde_json::to_string(self).expect("ProgressEvent is always serializable");
+        println!("{line}");
     }
 }
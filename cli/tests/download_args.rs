@@ -0,0 +1,63 @@
+#![cfg(feature = "download")]
+
+use std::process::{Command, Output};
+
+/// Runs `rustube download` with `args`. The identifier is invalid, so arguments, that are
+/// accepted, fail afterwards without making any requests.
+fn download(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rustube"))
+        .arg("download")
+        .arg("not a video id")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn assert_rejected(args: &[&str], message: &str) {
+    let output = download(args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{:?}: {}", args, stderr);
+    assert!(stderr.contains(message), "{:?}: {}", args, stderr);
+}
+
+fn assert_accepted(args: &[&str]) {
+    let output = download(args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{:?}: {}", args, stderr);
+    assert!(stderr.contains("does not match any known Id-pattern"), "{:?}: {}", args, stderr);
+}
+
+#[test]
+fn output_template_conflicts_with_filename() {
+    assert_rejected(&["--output-template", "{id}.{ext}", "--filename", "video.mp4"], "cannot be used with");
+    assert_rejected(&["-f", "video.mp4", "--output-template", "{id}.{ext}"], "cannot be used with");
+}
+
+#[test]
+fn invalid_output_templates() {
+    assert_rejected(&["--output-template", "{title}.{extension}"], "unknown placeholder {extension}");
+    assert_rejected(&["--output-template", "../{id}.{ext}"], "is not allowed");
+    assert_rejected(&["--output-template", "{title"], "unclosed placeholder");
+}
+
+#[test]
+fn valid_output_templates() {
+    assert_accepted(&["--output-template", "{channel}/{upload_date} - {title} [{id}] [{quality}].{ext}"]);
+    assert_accepted(&["--output-template", "{itag}.{ext}", "--dir", "videos"]);
+    assert_accepted(&["--filename", "video.mp4"]);
+}
+
+#[test]
+fn headers() {
+    assert_accepted(&["--header", "Referer: https://www.youtube.com/"]);
+    assert_accepted(&["--header", "Referer: https://www.youtube.com/", "--header", "X-Custom:value"]);
+    assert_rejected(&["--header", "Referer"], "expected `NAME: VALUE`");
+    assert_rejected(&["--header", "Bad Name: value"], "invalid header name");
+}
+
+#[test]
+fn oauth() {
+    assert_accepted(&["--oauth"]);
+    assert_accepted(&["--oauth", "--oauth-token", "token.json"]);
+    assert_rejected(&["--oauth-token", "token.json"], "--oauth");
+}
@@ -0,0 +1,38 @@
+use std::process::{Command, Output};
+
+/// Runs `rustube` with `args`.
+fn rustube(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rustube"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Whether clap accepted `args`. The identifier is invalid, so accepted arguments fail
+/// afterwards without making any requests.
+fn is_accepted(args: &[&str]) -> bool {
+    let output = rustube(args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match output.status.code() {
+        Some(1) => true,
+        Some(2) => false,
+        code => panic!("{:?} exited with {:?}: {}", args, code, stderr),
+    }
+}
+
+#[test]
+fn yaml_output() {
+    assert_eq!(is_accepted(&["fetch", "not a video id", "--output", "yaml"]), cfg!(feature = "yaml"));
+    assert!(is_accepted(&["fetch", "not a video id", "--output", "pretty-json"]));
+}
+
+#[test]
+fn download_subcommand() {
+    assert_eq!(is_accepted(&["download", "not a video id"]), cfg!(feature = "download"));
+    assert_eq!(is_accepted(&["fetch", "not a video id", "--progress=json"]), cfg!(feature = "download"));
+}
+
+#[test]
+fn color() {
+    assert_eq!(is_accepted(&["fetch", "not a video id", "--color=never"]), cfg!(feature = "color"));
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "download")]
+
+use serde_json::{json, Value};
+
+use common::*;
+
+// the library's features, that `common` is gated on, are unknown to the CLI
+#[allow(unexpected_cfgs)]
+#[macro_use]
+#[path = "../../tests/common.rs"]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+
+fn body() -> Vec<u8> {
+    pattern(4096)
+}
+
+/// The multi audio fixture, with all streams pointing to `streams`, and the content length of the
+/// body.
+fn player_response(streams: &MockServer) -> Value {
+    let mut player_response: Value = serde_json::from_str(include_str!("../../tests/fixtures/multi_audio_player_response.json"))
+        .unwrap();
+    for kind in ["formats", "adaptiveFormats"] {
+        for format in player_response["streamingData"][kind].as_array_mut().unwrap() {
+            // the query contains the signature
+            let query = url::Url::parse(format["url"].as_str().unwrap()).unwrap().query().unwrap().to_owned();
+            format["url"] = streams.url.join(&format!("stream?{}", query)).unwrap().as_str().into();
+            format["contentLength"] = body().len().to_string().into();
+        }
+    }
+    player_response
+}
+
+#[tokio::test]
+async fn json_progress() {
+    let streams = MockServer::start(|_| MockResponse::ok(body())).await;
+    let player_response = player_response(&streams);
+    let youtube = mock_youtube(player_response.clone(), BASE_JS, move |_| player_response.clone()).await;
+    let dir = temp_dir("cli_progress");
+
+    let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_rustube"))
+        .args(["download", VIDEO_ID, "--progress=json", "--quiet"])
+        .arg("--base-url").arg(youtube.url.as_str())
+        .arg("--dir").arg(&dir)
+        .output()
+        .await
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{}", stderr);
+
+    let total = body().len() as u64;
+    let events: Vec<Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|err| panic!("{:?} is no JSON: {}", line, err)))
+        .collect();
+    assert!(events.iter().all(|event| event["video_id"] == VIDEO_ID), "{:?}", events);
+    assert_eq!(events.first().unwrap()["event"], "start");
+    assert_eq!(events.first().unwrap()["total"], json!(total));
+    assert_eq!(events.last().unwrap()["event"], "finish");
+    assert_eq!(events.last().unwrap()["bytes"], json!(total));
+
+    let progress: Vec<_> = events.iter().filter(|event| event["event"] == "progress").collect();
+    assert!(!progress.is_empty());
+    for event in progress {
+        assert_eq!(event["total"], json!(total));
+        assert!(event["bytes"].as_u64().unwrap() <= total);
+    }
+
+    // the human readable output of the downloaded stream goes to stderr
+    assert!(stderr.contains(streams.url.as_str()), "{}", stderr);
+    assert!(!stdout.contains(streams.url.as_str()), "{}", stdout);
+    assert_eq!(std::fs::read(dir.join(VIDEO_ID).with_extension("mp4")).unwrap(), body());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,28 @@
+//! Downloads only the audio of a video, using [`rustube::SimpleDownloadOptions::audio_only`].
+//!
+//! Usage: `cargo run --example audio_only -- <VIDEO_IDENTIFIER> [TARGET_DIR]`
+//!
+//! Set `RUSTUBE_OFFLINE` to skip the download, i.e. on CI.
+
+use rustube::SimpleDownloadOptions;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("RUSTUBE_OFFLINE").is_some() {
+        println!("RUSTUBE_OFFLINE is set, skipping the download");
+        return Ok(());
+    }
+
+    let mut args = std::env::args().skip(1);
+    let identifier = args.next().unwrap_or_else(|| "https://youtu.be/nv2wQvn6Wxc".to_owned());
+    let target_dir = args.next().map_or_else(std::env::temp_dir, Into::into);
+
+    let options = SimpleDownloadOptions::new()
+        .target_dir(target_dir)
+        .audio_only(true);
+
+    let path = rustube::download(&identifier, options).await?;
+    println!("downloaded the audio of {} to {:?}", identifier, path);
+
+    Ok(())
+}
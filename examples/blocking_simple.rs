@@ -0,0 +1,29 @@
+//! Downloads a video without an async runtime, using [`rustube::blocking`].
+//!
+//! Usage: `cargo run --example blocking_simple --features blocking -- <VIDEO_IDENTIFIER>`
+//!
+//! Set `RUSTUBE_OFFLINE` to skip the download, i.e. on CI.
+
+use rustube::blocking::Video;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("RUSTUBE_OFFLINE").is_some() {
+        println!("RUSTUBE_OFFLINE is set, skipping the download");
+        return Ok(());
+    }
+
+    let identifier = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "https://youtu.be/nv2wQvn6Wxc".to_owned());
+
+    let video = Video::from_url(&identifier.parse()?)?;
+    println!("downloading {}", video.title());
+
+    let path = video
+        .best_quality()
+        .ok_or("the video has no stream with video and audio")?
+        .blocking_download_to_dir(std::env::temp_dir())?;
+    println!("downloaded {} to {:?}", identifier, path);
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Fetches and downloads a video through a proxy, using a custom [`reqwest::Client`].
+//!
+//! The client is also used to download the streams. It's built with
+//! [`ClientBuilder::recommended`], so it has the cookie store and headers YouTube relies on.
+//!
+//! Usage: `cargo run --example custom_client_proxy -- <PROXY_URL> <VIDEO_IDENTIFIER>`,
+//! i.e. `http://localhost:8080`, or `socks5://localhost:1080` with the `socks` feature
+//!
+//! Set `RUSTUBE_OFFLINE` to skip the download, i.e. on CI.
+
+use rustube::{Id, VideoFetcher};
+use rustube::fetcher::ClientBuilder;
+use rustube::reqwest::Proxy;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let proxy = args.next().ok_or("missing the proxy url")?;
+    let identifier = args.next().unwrap_or_else(|| "https://youtu.be/nv2wQvn6Wxc".to_owned());
+
+    let client = ClientBuilder::recommended()
+        .proxy(Proxy::all(&proxy)?)
+        .build()?;
+
+    if std::env::var_os("RUSTUBE_OFFLINE").is_some() {
+        println!("RUSTUBE_OFFLINE is set, skipping the download");
+        return Ok(());
+    }
+
+    let id = Id::from_raw(&identifier)?.into_owned();
+    let video = VideoFetcher::from_id_with_client(id, client)
+        .fetch()
+        .await?
+        .descramble()?;
+
+    let stream = video.best_quality().ok_or("the video has no stream with video and audio")?;
+    let path = stream.download_to_dir(std::env::temp_dir()).await?;
+    println!("downloaded {} via {} to {:?}", video.title(), proxy, path);
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Downloads the best quality of a video, and shows the progress in a progress bar, that's updated
+//! by a slow [`rustube::Callback`] (once per downloaded MB).
+//!
+//! Usage: `cargo run --example download_with_progress --features callback -- <VIDEO_IDENTIFIER>`
+//!
+//! Set `RUSTUBE_OFFLINE` to skip the download, i.e. on CI.
+
+use std::sync::{Arc, Mutex};
+
+use pbr::{ProgressBar, Units};
+
+use rustube::{Callback, Video};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("RUSTUBE_OFFLINE").is_some() {
+        println!("RUSTUBE_OFFLINE is set, skipping the download");
+        return Ok(());
+    }
+
+    let identifier = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "https://youtu.be/nv2wQvn6Wxc".to_owned());
+
+    let video = Video::from_url(&identifier.parse()?).await?;
+    let stream = video.best_quality().ok_or("the video has no stream with video and audio")?;
+
+    let mut pb = ProgressBar::new(stream.content_length().await?);
+    pb.set_units(Units::Bytes);
+    let pb = Arc::new(Mutex::new(pb));
+
+    let callback = {
+        let on_progress = Arc::clone(&pb);
+        Callback::new()
+            .connect_on_progress_closure_slow(move |args| {
+                let mut pb = on_progress.lock().unwrap();
+                if let Some(content_length) = args.content_length {
+                    pb.total = content_length;
+                }
+                pb.set(args.current_chunk as u64);
+            })
+            .connect_on_complete_closure(move |_| pb.lock().unwrap().finish())
+    };
+
+    let path = stream.download_to_dir_with_callback(std::env::temp_dir(), callback).await?;
+    println!("downloaded {} to {:?}", video.title(), path);
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! Plays the audio of a video, while it's downloading, using [`rustube::Stream::open_reader`] and
+//! [rodio](https://docs.rs/rodio).
+//!
+//! Usage: `cargo run --manifest-path examples/play_audio/Cargo.toml -- <VIDEO_IDENTIFIER>`
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use rodio::{Decoder, OutputStream, Sink};
+use tokio::io::AsyncReadExt;
+use tokio::runtime::Handle;
+
+use rustube::{Stream, StreamReader, Video};
+
+/// A synchronous bridge from the [`StreamReader`] to the decoder.
+///
+/// The decoder requires [`Seek`], so everything read so far is kept in memory. Seeking forward
+/// reads (and waits for) the stream up to the new position.
+struct BlockingReader {
+    reader: StreamReader,
+    runtime: Handle,
+    buf: Vec<u8>,
+    pos: u64,
+    len: u64,
+}
+
+impl BlockingReader {
+    fn fill_to(&mut self, end: u64) -> io::Result<()> {
+        let mut chunk = [0; 16 * 1024];
+        while (self.buf.len() as u64) < end {
+            let n = self.runtime.block_on(self.reader.read(&mut chunk))?;
+            if n == 0 { break; }
+            self.buf.extend_from_slice(&chunk[..n]);
+        }
+        Ok(())
+    }
+}
+
+impl Read for BlockingReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        self.fill_to(self.pos + out.len() as u64)?;
+
+        let start = (self.pos as usize).min(self.buf.len());
+        let n = (self.buf.len() - start).min(out.len());
+        out[..n].copy_from_slice(&self.buf[start..start + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for BlockingReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        self.pos = pos.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek"))?;
+        Ok(self.pos)
+    }
+}
+
+/// The stream to play. The decoder cannot decode opus, so the best `mp4` audio stream is used, if
+/// the best audio stream is a `webm` stream.
+fn audio_stream(video: &Video) -> Option<&Stream> {
+    video
+        .best_audio()
+        .filter(|stream| stream.mime.subtype() == "mp4")
+        .or_else(|| video
+            .streams()
+            .iter()
+            .filter(|stream| stream.includes_audio_track && !stream.includes_video_track)
+            .filter(|stream| stream.mime.subtype() == "mp4")
+            .max_by_key(|stream| stream.bitrate)
+        )
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let identifier = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "https://youtu.be/nv2wQvn6Wxc".to_owned());
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let (reader, len) = runtime.block_on(async {
+        let video = Video::from_url(&identifier.parse()?).await?;
+        let stream = audio_stream(&video).ok_or("the video has no mp4 audio stream")?;
+        println!("playing {} ({})", video.title(), stream.mime);
+
+        let len = stream.content_length().await?;
+        Ok::<_, Box<dyn std::error::Error>>((stream.open_reader().await?, len))
+    })?;
+
+    let source = BlockingReader { reader, runtime: runtime.handle().clone(), buf: Vec::new(), pos: 0, len };
+    let (_output, output_handle) = OutputStream::try_default()?;
+    let sink = Sink::try_new(&output_handle)?;
+    sink.append(Decoder::new(source)?);
+    sink.sleep_until_end();
+
+    Ok(())
+}
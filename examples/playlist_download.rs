@@ -0,0 +1,62 @@
+//! Downloads the videos of a playlist one after another, and keeps going if some of them fail.
+//!
+//! rustube cannot resolve playlist urls (yet), so the playlist is either given as a list of
+//! video identifiers, or as a file with one identifier per line (empty lines and lines starting
+//! with `#` are ignored).
+//!
+//! Usage: `cargo run --example playlist_download -- <VIDEO_IDENTIFIER|@FILE>...`
+//!
+//! Set `RUSTUBE_OFFLINE` to skip the downloads, i.e. on CI.
+
+use rustube::{Id, SimpleDownloadOptions, Video, VideoFetcher};
+
+fn playlist() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut identifiers = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.strip_prefix('@') {
+            Some(file) => identifiers.extend(
+                std::fs::read_to_string(file)?
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_owned)
+            ),
+            None => identifiers.push(arg),
+        }
+    }
+    Ok(identifiers)
+}
+
+async fn download(identifier: &str) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    let id = Id::from_raw(identifier)?.into_owned();
+    let video: Video = VideoFetcher::from_id(id)?
+        .fetch()
+        .await?
+        .descramble()?;
+
+    let options = SimpleDownloadOptions::new().target_dir(std::env::temp_dir().join("playlist"));
+    Ok(video.download_with(options).await?)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("RUSTUBE_OFFLINE").is_some() {
+        println!("RUSTUBE_OFFLINE is set, skipping the downloads");
+        return Ok(());
+    }
+
+    let playlist = playlist()?;
+    let mut failed = 0;
+    for (i, identifier) in playlist.iter().enumerate() {
+        match download(identifier).await {
+            Ok(path) => println!("[{}/{}] downloaded {} to {:?}", i + 1, playlist.len(), identifier, path),
+            Err(err) => {
+                failed += 1;
+                eprintln!("[{}/{}] failed to download {}: {}", i + 1, playlist.len(), identifier, err);
+            }
+        }
+    }
+
+    println!("downloaded {} of {} videos", playlist.len() - failed, playlist.len());
+    Ok(())
+}
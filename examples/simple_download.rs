@@ -0,0 +1,21 @@
+//! Downloads a video using [`rustube::download`].
+//!
+//! Usage: `cargo run --example simple_download -- <VIDEO_IDENTIFIER> [MAX_RESOLUTION]`
+
+use rustube::SimpleDownloadOptions;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let identifier = args.next().unwrap_or_else(|| "https://youtu.be/nv2wQvn6Wxc".to_owned());
+
+    let mut options = SimpleDownloadOptions::new().target_dir(std::env::temp_dir());
+    if let Some(max_resolution) = args.next() {
+        options = options.max_resolution(max_resolution.parse()?);
+    }
+
+    let path = rustube::download(&identifier, options).await?;
+    println!("downloaded {} to {:?}", identifier, path);
+
+    Ok(())
+}
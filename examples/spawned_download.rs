@@ -0,0 +1,57 @@
+//! Downloads the best video and the best audio stream of a video concurrently, each in its own
+//! [`tokio::spawn`]ed task, with a [`rustube::Callback`], that reports the progress over a channel.
+//!
+//! This only compiles, because both the `Video` and the download futures, including the
+//! `Callback`, are `Send + 'static`.
+//!
+//! Usage: `cargo run --example spawned_download --features callback -- <VIDEO_IDENTIFIER>`
+//!
+//! Set `RUSTUBE_OFFLINE` to skip the download, i.e. on CI.
+
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use rustube::{Callback, CallbackArguments, Stream, Video};
+
+type Select = fn(&Video) -> Option<&Stream>;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("RUSTUBE_OFFLINE").is_some() {
+        println!("RUSTUBE_OFFLINE is set, skipping the download");
+        return Ok(());
+    }
+
+    let identifier = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "https://youtu.be/nv2wQvn6Wxc".to_owned());
+
+    let video = Arc::new(Video::from_url(&identifier.parse()?).await?);
+
+    let mut tasks = Vec::new();
+    for (name, best) in [("video", Video::best_video as Select), ("audio", Video::best_audio)] {
+        let video = Arc::clone(&video);
+        let (tx, mut rx) = mpsc::channel::<CallbackArguments>(16);
+
+        tokio::spawn(async move {
+            while let Some(args) = rx.recv().await {
+                println!("{}: {} of {:?} bytes", name, args.current_chunk, args.content_length);
+            }
+        });
+
+        tasks.push(tokio::spawn(async move {
+            let stream = best(&video).ok_or("no matching stream")?;
+            let callback = Callback::new().connect_on_progress_sender_slow(tx, false);
+            let path = stream.download_to_dir_with_callback(std::env::temp_dir(), callback).await?;
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>((name, path))
+        }));
+    }
+
+    for task in tasks {
+        let (name, path) = task.await?.map_err(|err| err.to_string())?;
+        println!("downloaded the {} stream to {:?}", name, path);
+    }
+
+    Ok(())
+}
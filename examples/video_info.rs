@@ -0,0 +1,45 @@
+//! Prints information about a video, and its streams, without cloning the [`rustube::VideoInfo`].
+//!
+//! Usage: `cargo run --example video_info -- <VIDEO_IDENTIFIER> [--no-streams]`
+//!
+//! Set `RUSTUBE_OFFLINE` to skip fetching the video, i.e. on CI.
+
+use rustube::{Id, VideoFetcher, VideoInfo};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("RUSTUBE_OFFLINE").is_some() {
+        println!("RUSTUBE_OFFLINE is set, skipping fetching the video");
+        return Ok(());
+    }
+
+    let mut args = std::env::args().skip(1);
+    let identifier = args.next().unwrap_or_else(|| "https://youtu.be/nv2wQvn6Wxc".to_owned());
+    let with_streams = args.next().as_deref() != Some("--no-streams");
+
+    let id = Id::from_raw(&identifier)?.into_owned();
+    let descrambler = VideoFetcher::from_id(id)?.fetch().await?;
+
+    if !with_streams {
+        print_video_info(&descrambler.into_video_info());
+        return Ok(());
+    }
+
+    let video = descrambler.descramble()?;
+    println!("{} streams, best quality: {:?}", video.streams().len(), video.best_quality().map(|s| s.itag));
+    print_video_info(video.info());
+
+    // takes ownership of both parts, i.e. to move the streams into download tasks
+    let (video_info, streams) = video.into_parts();
+    for stream in streams {
+        println!("{:>4} {} {:?}", stream.itag, stream.mime, stream.quality_label);
+    }
+    println!("playable: {}", video_info.is_playable());
+
+    Ok(())
+}
+
+fn print_video_info(video_info: &VideoInfo) {
+    let details = &video_info.player_response.video_details;
+    println!("{} ({}), {}s, by {}", details.title, details.video_id, details.length_seconds, details.author);
+}
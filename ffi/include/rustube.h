@@ -0,0 +1,221 @@
+#ifndef RUSTUBE_H
+#define RUSTUBE_H
+
+/* This file is generated by cbindgen from ffi/src/lib.rs. Do not edit it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of a fallible function.
+ *
+ * Everything but [`RustubeStatus::Ok`] is an error, that's described by
+ * [`rustube_last_error_message`].
+ */
+typedef enum RustubeStatus {
+  /**
+   * The function succeeded.
+   */
+  RUSTUBE_STATUS_OK = 0,
+  /**
+   * A required pointer argument was `NULL`.
+   */
+  RUSTUBE_STATUS_NULL_ARGUMENT,
+  /**
+   * An argument was invalid, i.e. not UTF-8, or not a video id or URL.
+   */
+  RUSTUBE_STATUS_INVALID_ARGUMENT,
+  /**
+   * The stream index is not less than [`rustube_video_stream_count`].
+   */
+  RUSTUBE_STATUS_INDEX_OUT_OF_BOUNDS,
+  /**
+   * The video is unavailable, i.e. private, removed, or YouTube asked for a bot check.
+   */
+  RUSTUBE_STATUS_UNAVAILABLE,
+  /**
+   * YouTube refused to serve the stream (`403 Forbidden`).
+   */
+  RUSTUBE_STATUS_FORBIDDEN,
+  /**
+   * A request failed.
+   */
+  RUSTUBE_STATUS_NETWORK,
+  /**
+   * Reading or writing a file failed.
+   */
+  RUSTUBE_STATUS_IO,
+  /**
+   * More media bytes than allowed were transferred.
+   */
+  RUSTUBE_STATUS_QUOTA_EXCEEDED,
+  /**
+   * `rustube` panicked. This is a bug.
+   */
+  RUSTUBE_STATUS_PANIC,
+  /**
+   * Any other error.
+   */
+  RUSTUBE_STATUS_OTHER,
+} RustubeStatus;
+
+/**
+ * A fetched and descrambled video. Create it with [`rustube_fetch_video`], and free it with
+ * [`rustube_free_video`].
+ */
+typedef struct RustubeVideo RustubeVideo;
+
+/**
+ * Information about a stream of a [`RustubeVideo`].
+ *
+ * The strings are owned by the video, and are valid until it is freed.
+ */
+typedef struct RustubeStreamInfo {
+  /**
+   * The [itag](https://gist.github.com/sidneys/7095afe4da4ae58694d128b1034e01e2) of the stream.
+   */
+  uint64_t itag;
+  /**
+   * The mime type, including the codecs, i.e. `video/mp4; codecs="avc1.640028"`.
+   */
+  const char *mime;
+  /**
+   * The quality label, i.e. `1080p60`, or `NULL` for audio only streams.
+   */
+  const char *quality_label;
+  /**
+   * Whether the stream contains both a video and an audio track.
+   */
+  bool is_progressive;
+  bool includes_video_track;
+  bool includes_audio_track;
+  /**
+   * The width in pixels, or `0` for audio only streams.
+   */
+  uint64_t width;
+  /**
+   * The height in pixels, or `0` for audio only streams.
+   */
+  uint64_t height;
+  /**
+   * The frames per second, or `0` for audio only streams.
+   */
+  uint8_t fps;
+  /**
+   * The bitrate in bits per second, or `0` if it's unknown.
+   */
+  uint64_t bitrate;
+  /**
+   * An estimate of the size in bytes, or `0` if it's unknown.
+   */
+  uint64_t estimated_size;
+} RustubeStreamInfo;
+
+/**
+ * Reports the progress of [`rustube_stream_download_to`].
+ *
+ * `downloaded` is the number of bytes received so far, and `total` the content length of the
+ * stream, or `0` if it's unknown. `userdata` is the pointer passed to
+ * [`rustube_stream_download_to`].
+ */
+typedef void (*RustubeProgressCb)(uint64_t downloaded, uint64_t total, void *userdata);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Fetches and descrambles the video with the id, or the URL `id`.
+ *
+ * On success, `*out` points to the video, which must be freed with [`rustube_free_video`].
+ * Otherwise, `*out` is set to `NULL`.
+ *
+ * # Safety
+ * `id` must be `NULL` or a valid, null terminated string. `out` must be `NULL` or valid for
+ * writes.
+ */
+enum RustubeStatus rustube_fetch_video(const char *id, struct RustubeVideo **out);
+
+/**
+ * Like [`rustube_fetch_video`], but requests the watch page and the player from `base_url`
+ * instead of `https://www.youtube.com/`, i.e. a mirror or a test server. A `NULL` `base_url`
+ * means YouTube.
+ *
+ * # Safety
+ * `id` and `base_url` must be `NULL` or valid, null terminated strings. `out` must be `NULL` or
+ * valid for writes.
+ */
+enum RustubeStatus rustube_fetch_video_with_base_url(const char *id,
+                                                     const char *base_url,
+                                                     struct RustubeVideo **out);
+
+/**
+ * The title of `video`, or `NULL` if `video` is `NULL`. The string is valid until the video is
+ * freed.
+ *
+ * # Safety
+ * `video` must be `NULL` or a video returned by [`rustube_fetch_video`], that was not freed yet.
+ */
+const char *rustube_video_title(const struct RustubeVideo *video);
+
+/**
+ * The number of streams of `video`, or `0` if `video` is `NULL`.
+ *
+ * # Safety
+ * `video` must be `NULL` or a video returned by [`rustube_fetch_video`], that was not freed yet.
+ */
+size_t rustube_video_stream_count(const struct RustubeVideo *video);
+
+/**
+ * Writes information about the stream at `index` of `video` to `*out`.
+ *
+ * # Safety
+ * `video` must be `NULL` or a video returned by [`rustube_fetch_video`], that was not freed yet.
+ * `out` must be `NULL` or valid for writes.
+ */
+enum RustubeStatus rustube_stream_info(const struct RustubeVideo *video,
+                                       size_t index,
+                                       struct RustubeStreamInfo *out);
+
+/**
+ * Downloads the stream at `index` of `video` to `path`.
+ *
+ * If `progress` is not `NULL`, it's called with `userdata` whenever a chunk was received. It's
+ * always called on the thread, that called this function, and never after this function
+ * returned. Updates may be skipped, if the callback is slower than the download.
+ *
+ * # Safety
+ * `video` must be `NULL` or a video returned by [`rustube_fetch_video`], that was not freed yet.
+ * `path` must be `NULL` or a valid, null terminated string. `progress` must be safe to call with
+ * `userdata` for the duration of the download.
+ */
+enum RustubeStatus rustube_stream_download_to(const struct RustubeVideo *video,
+                                              size_t index,
+                                              const char *path,
+                                              RustubeProgressCb progress,
+                                              void *userdata);
+
+/**
+ * Frees `video`. Does nothing if `video` is `NULL`.
+ *
+ * # Safety
+ * `video` must be `NULL` or a video returned by [`rustube_fetch_video`], that was not freed yet.
+ */
+void rustube_free_video(struct RustubeVideo *video);
+
+/**
+ * A description of the last error on the current thread, or `NULL` if the last fallible call
+ * succeeded.
+ *
+ * The string is valid until the next call of a fallible function on the same thread.
+ */
+const char *rustube_last_error_message(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* RUSTUBE_H */
@@ -0,0 +1,405 @@
+//! A minimal C API for `rustube`, i.e. for calling it from Swift, Kotlin, or C.
+//!
+//! The API covers fetching a video, inspecting its streams, and downloading one of them. All
+//! functions block until they are done, using the runtime of [`rustube::blocking`], so they must
+//! not be called from within an asynchronous Rust context.
+//!
+//! Fallible functions return a [`RustubeStatus`]. If it's not [`RustubeStatus::Ok`],
+//! [`rustube_last_error_message`] describes what went wrong. Panics never cross the FFI
+//! boundary, but are reported as [`RustubeStatus::Panic`].
+//!
+//! The header `include/rustube.h` is generated from this file by `cbindgen` (see `cbindgen.toml`).
+//!
+//! # Example
+//! ```c
+//! RustubeVideo *video = NULL;
+//! if (rustube_fetch_video("https://youtu.be/5jlI4uzZGjU", &video) != RUSTUBE_STATUS_OK) {
+//!     fprintf(stderr, "%s\n", rustube_last_error_message());
+//!     return 1;
+//! }
+//! RustubeStreamInfo info;
+//! for (size_t i = 0; i < rustube_video_stream_count(video); i++) {
+//!     rustube_stream_info(video, i, &info);
+//!     printf("%zu: itag %llu, %s\n", i, (unsigned long long) info.itag, info.mime);
+//! }
+//! rustube_stream_download_to(video, 0, "video.mp4", NULL, NULL);
+//! rustube_free_video(video);
+//! ```
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use rustube::blocking::{Video, VideoFetcher};
+use rustube::url::Url;
+use rustube::{Callback, CallbackArguments, Error, Id, Stream};
+
+/// The result of a fallible function.
+///
+/// Everything but [`RustubeStatus::Ok`] is an error, that's described by
+/// [`rustube_last_error_message`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RustubeStatus {
+    /// The function succeeded.
+    Ok = 0,
+    /// A required pointer argument was `NULL`.
+    NullArgument,
+    /// An argument was invalid, i.e. not UTF-8, or not a video id or URL.
+    InvalidArgument,
+    /// The stream index is not less than [`rustube_video_stream_count`].
+    IndexOutOfBounds,
+    /// The video is unavailable, i.e. private, removed, or YouTube asked for a bot check.
+    Unavailable,
+    /// YouTube refused to serve the stream (`403 Forbidden`).
+    Forbidden,
+    /// A request failed.
+    Network,
+    /// Reading or writing a file failed.
+    Io,
+    /// More media bytes than allowed were transferred.
+    QuotaExceeded,
+    /// `rustube` panicked. This is a bug.
+    Panic,
+    /// Any other error.
+    Other,
+}
+
+/// A fetched and descrambled video. Create it with [`rustube_fetch_video`], and free it with
+/// [`rustube_free_video`].
+pub struct RustubeVideo {
+    video: Video,
+    title: CString,
+    streams: Vec<StreamStrings>,
+}
+
+/// The strings of a stream, that are borrowed by [`RustubeStreamInfo`].
+struct StreamStrings {
+    mime: CString,
+    quality_label: Option<CString>,
+}
+
+/// Information about a stream of a [`RustubeVideo`].
+///
+/// The strings are owned by the video, and are valid until it is freed.
+#[repr(C)]
+#[derive(Debug)]
+pub struct RustubeStreamInfo {
+    /// The [itag](https://gist.github.com/sidneys/7095afe4da4ae58694d128b1034e01e2) of the stream.
+    pub itag: u64,
+    /// The mime type, including the codecs, i.e. `video/mp4; codecs="avc1.640028"`.
+    pub mime: *const c_char,
+    /// The quality label, i.e. `1080p60`, or `NULL` for audio only streams.
+    pub quality_label: *const c_char,
+    /// Whether the stream contains both a video and an audio track.
+    pub is_progressive: bool,
+    pub includes_video_track: bool,
+    pub includes_audio_track: bool,
+    /// The width in pixels, or `0` for audio only streams.
+    pub width: u64,
+    /// The height in pixels, or `0` for audio only streams.
+    pub height: u64,
+    /// The frames per second, or `0` for audio only streams.
+    pub fps: u8,
+    /// The bitrate in bits per second, or `0` if it's unknown.
+    pub bitrate: u64,
+    /// An estimate of the size in bytes, or `0` if it's unknown.
+    pub estimated_size: u64,
+}
+
+/// Reports the progress of [`rustube_stream_download_to`].
+///
+/// `downloaded` is the number of bytes received so far, and `total` the content length of the
+/// stream, or `0` if it's unknown. `userdata` is the pointer passed to
+/// [`rustube_stream_download_to`].
+pub type RustubeProgressCb = Option<unsafe extern "C" fn(downloaded: u64, total: u64, userdata: *mut c_void)>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Fetches and descrambles the video with the id, or the URL `id`.
+///
+/// On success, `*out` points to the video, which must be freed with [`rustube_free_video`].
+/// Otherwise, `*out` is set to `NULL`.
+///
+/// # Safety
+/// `id` must be `NULL` or a valid, null terminated string. `out` must be `NULL` or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn rustube_fetch_video(id: *const c_char, out: *mut *mut RustubeVideo) -> RustubeStatus {
+    rustube_fetch_video_with_base_url(id, ptr::null(), out)
+}
+
+/// Like [`rustube_fetch_video`], but requests the watch page and the player from `base_url`
+/// instead of `https://www.youtube.com/`, i.e. a mirror or a test server. A `NULL` `base_url`
+/// means YouTube.
+///
+/// # Safety
+/// `id` and `base_url` must be `NULL` or valid, null terminated strings. `out` must be `NULL` or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn rustube_fetch_video_with_base_url(
+    id: *const c_char,
+    base_url: *const c_char,
+    out: *mut *mut RustubeVideo,
+) -> RustubeStatus {
+    guard(|| {
+        let out = out.as_mut().ok_or_else(|| Failure::null_argument("out"))?;
+        *out = ptr::null_mut();
+
+        let id = Id::from_raw(str_argument(id, "id")?)
+            .map_err(|_| Failure::new(RustubeStatus::InvalidArgument, "`id` is not a video id or URL"))?;
+        let mut fetcher = VideoFetcher::from_id(id.into_owned())?;
+        if !base_url.is_null() {
+            let base_url = Url::parse(str_argument(base_url, "base_url")?)
+                .map_err(|e| Failure::new(RustubeStatus::InvalidArgument, format!("`base_url` is not a URL: {}", e)))?;
+            fetcher = fetcher.with_base_url(base_url);
+        }
+        let video = fetcher.fetch()?.descramble()?;
+
+        *out = Box::into_raw(Box::new(RustubeVideo::new(video)));
+        Ok(())
+    })
+}
+
+/// The title of `video`, or `NULL` if `video` is `NULL`. The string is valid until the video is
+/// freed.
+///
+/// # Safety
+/// `video` must be `NULL` or a video returned by [`rustube_fetch_video`], that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rustube_video_title(video: *const RustubeVideo) -> *const c_char {
+    match video.as_ref() {
+        Some(video) => video.title.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// The number of streams of `video`, or `0` if `video` is `NULL`.
+///
+/// # Safety
+/// `video` must be `NULL` or a video returned by [`rustube_fetch_video`], that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rustube_video_stream_count(video: *const RustubeVideo) -> usize {
+    video.as_ref().map_or(0, |video| video.streams.len())
+}
+
+/// Writes information about the stream at `index` of `video` to `*out`.
+///
+/// # Safety
+/// `video` must be `NULL` or a video returned by [`rustube_fetch_video`], that was not freed yet.
+/// `out` must be `NULL` or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn rustube_stream_info(
+    video: *const RustubeVideo,
+    index: usize,
+    out: *mut RustubeStreamInfo,
+) -> RustubeStatus {
+    guard(|| {
+        let video = video.as_ref().ok_or_else(|| Failure::null_argument("video"))?;
+        let out = out.as_mut().ok_or_else(|| Failure::null_argument("out"))?;
+        let stream = video.stream(index)?;
+        let strings = &video.streams[index];
+
+        *out = RustubeStreamInfo {
+            itag: stream.itag,
+            mime: strings.mime.as_ptr(),
+            quality_label: strings.quality_label.as_ref().map_or(ptr::null(), |label| label.as_ptr()),
+            is_progressive: stream.is_progressive,
+            includes_video_track: stream.includes_video_track,
+            includes_audio_track: stream.includes_audio_track,
+            width: stream.width.unwrap_or(0),
+            height: stream.height.unwrap_or(0),
+            fps: stream.fps,
+            bitrate: stream.bitrate.unwrap_or(0),
+            estimated_size: stream.estimated_content_length().unwrap_or(0),
+        };
+        Ok(())
+    })
+}
+
+/// Downloads the stream at `index` of `video` to `path`.
+///
+/// If `progress` is not `NULL`, it's called with `userdata` whenever a chunk was received. It's
+/// always called on the thread, that called this function, and never after this function
+/// returned. Updates may be skipped, if the callback is slower than the download.
+///
+/// # Safety
+/// `video` must be `NULL` or a video returned by [`rustube_fetch_video`], that was not freed yet.
+/// `path` must be `NULL` or a valid, null terminated string. `progress` must be safe to call with
+/// `userdata` for the duration of the download.
+#[no_mangle]
+pub unsafe extern "C" fn rustube_stream_download_to(
+    video: *const RustubeVideo,
+    index: usize,
+    path: *const c_char,
+    progress: RustubeProgressCb,
+    userdata: *mut c_void,
+) -> RustubeStatus {
+    guard(|| {
+        let video = video.as_ref().ok_or_else(|| Failure::null_argument("video"))?;
+        let path = str_argument(path, "path")?;
+        let stream = video.stream(index)?;
+
+        match progress {
+            Some(function) => {
+                let progress = Progress { function, userdata };
+                let callback = Callback::new()
+                    .connect_on_progress_closure(move |arguments| progress.report(arguments));
+                stream.blocking_download_to_with_callback(path, callback)?;
+            }
+            None => stream.blocking_download_to(path)?,
+        }
+        Ok(())
+    })
+}
+
+/// Frees `video`. Does nothing if `video` is `NULL`.
+///
+/// # Safety
+/// `video` must be `NULL` or a video returned by [`rustube_fetch_video`], that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rustube_free_video(video: *mut RustubeVideo) {
+    if !video.is_null() {
+        drop(Box::from_raw(video));
+    }
+}
+
+/// A description of the last error on the current thread, or `NULL` if the last fallible call
+/// succeeded.
+///
+/// The string is valid until the next call of a fallible function on the same thread.
+#[no_mangle]
+pub extern "C" fn rustube_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+impl RustubeVideo {
+    fn new(video: Video) -> Self {
+        let streams = video
+            .streams()
+            .iter()
+            .map(|stream| StreamStrings {
+                mime: c_string(stream.mime.to_string()),
+                quality_label: stream.quality_label.as_ref().map(|label| c_string(label.to_string())),
+            })
+            .collect();
+
+        Self {
+            title: c_string(video.title()),
+            streams,
+            video,
+        }
+    }
+
+    fn stream(&self, index: usize) -> Result<&Stream, Failure> {
+        self.video.streams().get(index).ok_or_else(|| Failure::new(
+            RustubeStatus::IndexOutOfBounds,
+            format!("the video has {} streams, but stream {} was requested", self.streams.len(), index),
+        ))
+    }
+}
+
+/// The C progress callback and its user data.
+struct Progress {
+    function: unsafe extern "C" fn(u64, u64, *mut c_void),
+    userdata: *mut c_void,
+}
+
+// SAFETY: The callback is only invoked on the thread, that called `rustube_stream_download_to`,
+// since the download future is polled by `block_on` on that thread.
+unsafe impl Send for Progress {}
+
+impl Progress {
+    fn report(&self, arguments: CallbackArguments) {
+        let total = arguments.content_length.unwrap_or(0);
+        // SAFETY: The caller of `rustube_stream_download_to` guarantees, that the callback is
+        // safe to call with `userdata`.
+        unsafe { (self.function)(arguments.current_chunk as u64, total, self.userdata) }
+    }
+}
+
+/// An error, that's reported to C as a [`RustubeStatus`] and a message.
+#[derive(Debug)]
+struct Failure {
+    status: RustubeStatus,
+    message: String,
+}
+
+impl Failure {
+    fn new(status: RustubeStatus, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+
+    fn null_argument(name: &str) -> Self {
+        Self::new(RustubeStatus::NullArgument, format!("`{}` must not be NULL", name))
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        let status = match error {
+            Error::BadIdFormat => RustubeStatus::InvalidArgument,
+            Error::VideoUnavailable(_) | Error::BotCheck(_) | Error::NoStreams(_) | Error::NoStreamingData { .. } =>
+                RustubeStatus::Unavailable,
+            Error::AllClientsFailed(_) if error.is_bot_check() => RustubeStatus::Unavailable,
+            Error::Forbidden { .. } => RustubeStatus::Forbidden,
+            Error::Request(_) | Error::RateLimited { .. } => RustubeStatus::Network,
+            Error::IO(_) => RustubeStatus::Io,
+            Error::QuotaExceeded { .. } => RustubeStatus::QuotaExceeded,
+            _ => RustubeStatus::Other,
+        };
+        Self::new(status, error.to_string())
+    }
+}
+
+/// Runs `f`, stores its error as the last error of the thread, and turns panics into
+/// [`RustubeStatus::Panic`].
+fn guard(f: impl FnOnce() -> Result<(), Failure>) -> RustubeStatus {
+    let failure = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => None,
+        Ok(Err(failure)) => Some(failure),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_owned());
+            Some(Failure::new(RustubeStatus::Panic, format!("rustube panicked: {}", message)))
+        }
+    };
+
+    let status = failure.as_ref().map_or(RustubeStatus::Ok, |failure| failure.status);
+    LAST_ERROR.with(|last_error| {
+        *last_error.borrow_mut() = failure.map(|failure| c_string(failure.message));
+    });
+    status
+}
+
+/// Borrows the null terminated string `ptr` as a `&str`.
+///
+/// # Safety
+/// `ptr` must be `NULL` or a valid, null terminated string, that outlives `'a`.
+unsafe fn str_argument<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, Failure> {
+    if ptr.is_null() {
+        return Err(Failure::null_argument(name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| Failure::new(RustubeStatus::InvalidArgument, format!("`{}` is not valid UTF-8", name)))
+}
+
+/// Converts `s` to a [`CString`], dropping interior null bytes.
+fn c_string(s: impl Into<String>) -> CString {
+    let mut bytes = s.into().into_bytes();
+    bytes.retain(|&byte| byte != 0);
+    CString::new(bytes).expect("the null bytes were removed")
+}
@@ -0,0 +1,68 @@
+/*
+ * Exercises the error paths of the C API without network access.
+ *
+ * cargo build -p rustube-ffi
+ * cc ffi/tests/c/error_paths.c -I ffi/include -L target/debug -l rustube_ffi -o target/error_paths
+ * LD_LIBRARY_PATH=target/debug target/error_paths
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "rustube.h"
+
+static int failures = 0;
+
+static void check(int condition, const char *description) {
+    if (!condition) {
+        fprintf(stderr, "FAILED: %s\n", description);
+        failures++;
+    }
+}
+
+static void check_error(RustubeStatus status, RustubeStatus expected, const char *message) {
+    const char *last_error = rustube_last_error_message();
+    check(status == expected, message);
+    check(last_error != NULL && strcmp(last_error, message) == 0, message);
+}
+
+int main(void) {
+    RustubeVideo *video = (RustubeVideo *) 1;
+    RustubeStreamInfo info;
+
+    check(rustube_last_error_message() == NULL, "no error before the first call");
+
+    check_error(rustube_fetch_video(NULL, &video), RUSTUBE_STATUS_NULL_ARGUMENT, "`id` must not be NULL");
+    check(video == NULL, "`out` is set to NULL on errors");
+    check_error(rustube_fetch_video("5jlI4uzZGjU", NULL), RUSTUBE_STATUS_NULL_ARGUMENT, "`out` must not be NULL");
+    check_error(
+        rustube_fetch_video("not an id", &video),
+        RUSTUBE_STATUS_INVALID_ARGUMENT,
+        "`id` is not a video id or URL"
+    );
+    check_error(
+        rustube_fetch_video("\xff", &video),
+        RUSTUBE_STATUS_INVALID_ARGUMENT,
+        "`id` is not valid UTF-8"
+    );
+    check(
+        rustube_fetch_video_with_base_url("5jlI4uzZGjU", "no url", &video) == RUSTUBE_STATUS_INVALID_ARGUMENT,
+        "`base_url` must be a URL"
+    );
+
+    check_error(rustube_stream_info(NULL, 0, &info), RUSTUBE_STATUS_NULL_ARGUMENT, "`video` must not be NULL");
+    check_error(
+        rustube_stream_download_to(NULL, 0, "video.mp4", NULL, NULL),
+        RUSTUBE_STATUS_NULL_ARGUMENT,
+        "`video` must not be NULL"
+    );
+
+    check(rustube_video_stream_count(NULL) == 0, "a NULL video has no streams");
+    check(rustube_video_title(NULL) == NULL, "a NULL video has no title");
+    rustube_free_video(NULL);
+
+    if (failures == 0) {
+        printf("all checks passed\n");
+    }
+    return failures == 0 ? 0 : 1;
+}
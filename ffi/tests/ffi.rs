@@ -0,0 +1,223 @@
+use std::ffi::{CStr, CString};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+use std::thread::{self, ThreadId};
+
+use rustube_ffi::*;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS_PATH: &str = "/s/player/3ba3e2d4/player_ias.vflset/en_US/base.js";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+const LEN: usize = 4000;
+const CHUNK: usize = 1000;
+
+fn c(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+fn last_error() -> Option<String> {
+    let message = rustube_last_error_message();
+    match message.is_null() {
+        true => None,
+        false => Some(unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_owned()),
+    }
+}
+
+/// Serves the watch page with the multi audio fixture, whose streams all point to `/stream`,
+/// `base.js`, and the body of the streams, in chunks of [`CHUNK`] bytes.
+fn server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../tests/fixtures/multi_audio_player_response.json");
+    let mut player_response: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    for key in &["formats", "adaptiveFormats"] {
+        for format in player_response["streamingData"][*key].as_array_mut().unwrap() {
+            // keep the query, since it contains the signature
+            let query = format["url"].as_str().unwrap().split_once('?').unwrap().1.to_owned();
+            format["url"] = format!("{}stream?{}", url, query).into();
+            format["contentLength"] = LEN.to_string().into();
+        }
+    }
+    let watch_html = format!(
+        "<html><script>var ytInitialPlayerResponse = {};</script>\
+        <script src=\"{}\"></script></html>",
+        player_response, BASE_JS_PATH,
+    );
+
+    thread::spawn(move || {
+        for socket in listener.incoming() {
+            respond(socket.unwrap(), &watch_html);
+        }
+    });
+    url
+}
+
+fn respond(mut socket: TcpStream, watch_html: &str) {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        match socket.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+    let (status, body) = if path.starts_with("/watch") {
+        ("200 OK", watch_html.as_bytes().to_vec())
+    } else if path == BASE_JS_PATH {
+        ("200 OK", BASE_JS.as_bytes().to_vec())
+    } else if path.starts_with("/stream") {
+        ("200 OK", vec![7; LEN])
+    } else {
+        ("404 Not Found", Vec::new())
+    };
+
+    let head = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, body.len());
+    let _ = socket.write_all(head.as_bytes());
+    for chunk in body.chunks(CHUNK) {
+        let _ = socket.write_all(chunk);
+        let _ = socket.flush();
+        thread::sleep(std::time::Duration::from_millis(5));
+    }
+}
+
+/// The progress reported to [`on_progress`].
+#[derive(Default)]
+struct Progress {
+    calls: Vec<(u64, u64, ThreadId)>,
+}
+
+unsafe extern "C" fn on_progress(downloaded: u64, total: u64, userdata: *mut c_void) {
+    let progress = &mut *(userdata as *mut Progress);
+    progress.calls.push((downloaded, total, thread::current().id()));
+}
+
+#[test]
+fn null_arguments() {
+    let mut video = ptr::null_mut();
+    let status = unsafe { rustube_fetch_video(ptr::null(), &mut video) };
+    assert_eq!(status, RustubeStatus::NullArgument);
+    assert_eq!(last_error().unwrap(), "`id` must not be NULL");
+    assert!(video.is_null());
+
+    let id = c(VIDEO_ID);
+    assert_eq!(unsafe { rustube_fetch_video(id.as_ptr(), ptr::null_mut()) }, RustubeStatus::NullArgument);
+    assert_eq!(last_error().unwrap(), "`out` must not be NULL");
+
+    let mut info = std::mem::MaybeUninit::<RustubeStreamInfo>::uninit();
+    assert_eq!(unsafe { rustube_stream_info(ptr::null(), 0, info.as_mut_ptr()) }, RustubeStatus::NullArgument);
+    let path = c("video.mp4");
+    let status = unsafe { rustube_stream_download_to(ptr::null(), 0, path.as_ptr(), None, ptr::null_mut()) };
+    assert_eq!(status, RustubeStatus::NullArgument);
+    assert_eq!(last_error().unwrap(), "`video` must not be NULL");
+
+    // the infallible functions accept NULL as well
+    unsafe {
+        assert_eq!(rustube_video_stream_count(ptr::null()), 0);
+        assert!(rustube_video_title(ptr::null()).is_null());
+        rustube_free_video(ptr::null_mut());
+    }
+}
+
+#[test]
+fn invalid_arguments() {
+    let mut video = ptr::null_mut();
+
+    let id = c("not an id");
+    assert_eq!(unsafe { rustube_fetch_video(id.as_ptr(), &mut video) }, RustubeStatus::InvalidArgument);
+    assert_eq!(last_error().unwrap(), "`id` is not a video id or URL");
+
+    let id = [0xff_u8, 0];
+    let status = unsafe { rustube_fetch_video(id.as_ptr() as *const c_char, &mut video) };
+    assert_eq!(status, RustubeStatus::InvalidArgument);
+    assert_eq!(last_error().unwrap(), "`id` is not valid UTF-8");
+
+    let (id, base_url) = (c(VIDEO_ID), c("no url"));
+    let status = unsafe { rustube_fetch_video_with_base_url(id.as_ptr(), base_url.as_ptr(), &mut video) };
+    assert_eq!(status, RustubeStatus::InvalidArgument);
+    assert!(last_error().unwrap().starts_with("`base_url` is not a URL"));
+    assert!(video.is_null());
+}
+
+#[test]
+fn unreachable_server() {
+    // nothing listens on the port anymore
+    let base_url = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        c(&format!("http://{}/", listener.local_addr().unwrap()))
+    };
+    let id = c(VIDEO_ID);
+    let mut video = ptr::null_mut();
+
+    let status = unsafe { rustube_fetch_video_with_base_url(id.as_ptr(), base_url.as_ptr(), &mut video) };
+    assert_eq!(status, RustubeStatus::Network, "{:?}", last_error());
+    assert!(last_error().is_some());
+    assert!(video.is_null());
+}
+
+#[test]
+fn fetch_and_download() {
+    let (id, base_url) = (c(&format!("https://youtu.be/{}", VIDEO_ID)), c(&server()));
+    let mut video = ptr::null_mut();
+    let status = unsafe { rustube_fetch_video_with_base_url(id.as_ptr(), base_url.as_ptr(), &mut video) };
+    assert_eq!(status, RustubeStatus::Ok, "{:?}", last_error());
+    assert_eq!(last_error(), None);
+
+    let title = unsafe { CStr::from_ptr(rustube_video_title(video)) };
+    assert_eq!(title.to_str().unwrap(), "Multi-language audio sample");
+
+    let count = unsafe { rustube_video_stream_count(video) };
+    let infos: Vec<_> = (0..count)
+        .map(|index| unsafe {
+            let mut info = std::mem::MaybeUninit::uninit();
+            assert_eq!(rustube_stream_info(video, index, info.as_mut_ptr()), RustubeStatus::Ok);
+            info.assume_init()
+        })
+        .collect();
+    assert_eq!(infos.len(), 8);
+
+    let (index, progressive) = infos.iter().enumerate().find(|(_, info)| info.itag == 18).unwrap();
+    assert!(progressive.is_progressive && progressive.includes_video_track && progressive.includes_audio_track);
+    assert!(unsafe { CStr::from_ptr(progressive.mime) }.to_str().unwrap().starts_with("video/mp4"));
+    assert_eq!(unsafe { CStr::from_ptr(progressive.quality_label) }.to_str().unwrap(), "360p");
+    assert!(progressive.width > 0 && progressive.height > 0 && progressive.fps > 0);
+    let audio = infos.iter().find(|info| info.itag == 140).unwrap();
+    assert!(audio.quality_label.is_null());
+    assert_eq!((audio.width, audio.height, audio.fps), (0, 0, 0));
+
+    let mut info = std::mem::MaybeUninit::uninit();
+    assert_eq!(unsafe { rustube_stream_info(video, count, info.as_mut_ptr()) }, RustubeStatus::IndexOutOfBounds);
+    assert_eq!(last_error().unwrap(), "the video has 8 streams, but stream 8 was requested");
+
+    let dir = std::env::temp_dir().join(format!("rustube_ffi_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("video.mp4");
+    let c_path = c(path.to_str().unwrap());
+    let mut progress = Progress::default();
+    let status = unsafe {
+        rustube_stream_download_to(video, index, c_path.as_ptr(), Some(on_progress), &mut progress as *mut Progress as *mut c_void)
+    };
+    assert_eq!(status, RustubeStatus::Ok, "{:?}", last_error());
+    assert_eq!(std::fs::read(&path).unwrap(), vec![7; LEN]);
+
+    // the callback runs on the calling thread
+    assert!(!progress.calls.is_empty());
+    for &(downloaded, total, thread) in &progress.calls {
+        assert!(downloaded > 0 && downloaded <= LEN as u64, "{}", downloaded);
+        assert_eq!(total, LEN as u64);
+        assert_eq!(thread, thread::current().id());
+    }
+
+    // a directory can't be written to
+    let c_dir = c(dir.to_str().unwrap());
+    let status = unsafe { rustube_stream_download_to(video, index, c_dir.as_ptr(), None, ptr::null_mut()) };
+    assert_eq!(status, RustubeStatus::Io, "{:?}", last_error());
+
+    unsafe { rustube_free_video(video) };
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustube::parser::extract_json_object;
+
+fuzz_target!(|text: &str| {
+    if let Some(range) = extract_json_object(text) {
+        let object = &text[range];
+        assert!(object.starts_with('{') && object.ends_with('}'));
+
+        // everything, that's valid json, must be extracted completely
+        if let Ok(serde_json::Value::Object(_)) = serde_json::from_str::<serde_json::Value>(object) {
+            let embedded = format!("junk = {};{}", object, text);
+            assert_eq!(extract_json_object(&embedded), Some(7..7 + object.len()));
+        }
+    }
+});
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use mime::Mime;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use tokio::task::JoinSet;
+
+use crate::{Error, Result, Stream, Video, VideoDetails};
+use crate::video_info::player_response::streaming_data::{AudioTrack, QualityLabel};
+
+/// The version of the [`ArchiveMetadata`] schema, that's written by
+/// [`Video::download_all_to_dir`]. It's incremented on every breaking change of the schema.
+pub const ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+/// The name of the metadata file, that's written next to the archived streams.
+pub const ARCHIVE_METADATA_FILE: &str = "metadata.json";
+
+type StreamFilter = Box<dyn Fn(&Stream) -> bool + Send + Sync>;
+
+/// Options for [`Video::download_all_to_dir`].
+///
+/// By default, all streams are downloaded, two at a time, a `metadata.json` is written, and
+/// files, that were already downloaded completely, are skipped.
+///
+/// # Example
+/// ```no_run
+///# use rustube::{ArchiveOptions, Id, Video};
+///# #[tokio::main]
+///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///# let video = Video::from_id(Id::from_str("5jlI4uzZGjU")?.into_owned()).await?;
+/// let options = ArchiveOptions::new()
+///     .filter(|stream| !stream.includes_video_track)
+///     .concurrency(4);
+/// let report = video.download_all_to_dir("archive", options).await?;
+/// for (itag, err) in &report.failed {
+///     eprintln!("failed to archive itag {}: {}", itag, err);
+/// }
+///# Ok(())
+///# }
+/// ```
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct ArchiveOptions {
+    #[derivative(Debug = "ignore")]
+    filter: Option<StreamFilter>,
+    write_metadata: bool,
+    concurrency: usize,
+    skip_existing: bool,
+}
+
+impl Default for ArchiveOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            filter: None,
+            write_metadata: true,
+            concurrency: 2,
+            skip_existing: true,
+        }
+    }
+}
+
+impl ArchiveOptions {
+    /// Creates the default options.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only archives the streams, for which `filter` returns `true`. [default: all streams]
+    #[inline]
+    pub fn filter(mut self, filter: impl Fn(&Stream) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Whether to write a [`metadata.json`](ArchiveMetadata) next to the streams. [default: `true`]
+    #[inline]
+    pub fn write_metadata(mut self, write_metadata: bool) -> Self {
+        self.write_metadata = write_metadata;
+        self
+    }
+
+    /// The maximum number of streams, that are downloaded at the same time. Values below `1` are
+    /// treated as `1`. [default: `2`]
+    #[inline]
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Whether to skip files, that already exist, and have the size of the streams content
+    /// length. [default: `true`]
+    ///
+    /// No requests are made to check this: the content length is taken from the format of the
+    /// stream, or the `metadata.json` of a previous run. Files, whose content length is known from
+    /// neither, are downloaded again.
+    #[inline]
+    pub fn skip_existing(mut self, skip_existing: bool) -> Self {
+        self.skip_existing = skip_existing;
+        self
+    }
+
+    #[inline]
+    fn includes(&self, stream: &Stream) -> bool {
+        self.filter.as_ref().is_none_or(|filter| filter(stream))
+    }
+}
+
+/// The outcome of [`Video::download_all_to_dir`]. Each entry starts with the itag of the stream.
+///
+/// A failing stream doesn't abort the other downloads, so a report can contain both successes
+/// and failures.
+#[derive(Debug, Default)]
+pub struct ArchiveReport {
+    /// The directory, the streams were archived to (`<dir>/<VIDEO_ID>`).
+    pub dir: PathBuf,
+    /// The streams, that were downloaded.
+    pub downloaded: Vec<(u64, PathBuf)>,
+    /// The streams, that were skipped, since they were already downloaded completely.
+    pub skipped: Vec<(u64, PathBuf)>,
+    /// The streams, that could not be downloaded.
+    pub failed: Vec<(u64, Error)>,
+}
+
+impl ArchiveReport {
+    /// Whether all selected streams are present in the archive.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// The content of the `metadata.json`, that's written by [`Video::download_all_to_dir`].
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ArchiveMetadata {
+    /// The [`ARCHIVE_SCHEMA_VERSION`] the file was written with.
+    pub schema_version: u32,
+    pub archived_at: DateTime<Utc>,
+    pub video_details: VideoDetails,
+    /// All streams present in the archive, ordered by their itag.
+    pub streams: Vec<ArchivedStream>,
+}
+
+/// A stream in the [`ArchiveMetadata`].
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ArchivedStream {
+    pub itag: u64,
+    /// The file name of the stream, relative to the `metadata.json`.
+    pub file: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub mime: Mime,
+    pub codecs: Vec<String>,
+    pub quality_label: Option<QualityLabel>,
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    pub fps: u8,
+    pub bitrate: Option<u64>,
+    pub average_bitrate: Option<u64>,
+    pub audio_track: Option<AudioTrack>,
+    pub content_length: u64,
+}
+
+impl ArchivedStream {
+    fn new(stream: &Stream, file: String, content_length: u64) -> Self {
+        Self {
+            itag: stream.itag,
+            file,
+            mime: stream.mime.clone(),
+            codecs: stream.codecs.clone(),
+            quality_label: stream.quality_label.clone(),
+            width: stream.width,
+            height: stream.height,
+            fps: stream.fps,
+            bitrate: stream.bitrate,
+            average_bitrate: stream.average_bitrate,
+            audio_track: stream.audio_track.clone(),
+            content_length,
+        }
+    }
+}
+
+enum Archived {
+    Downloaded(PathBuf, ArchivedStream),
+    Skipped(PathBuf, ArchivedStream),
+}
+
+impl Video {
+    /// Downloads all [`Stream`]s of the video, that match the `options`, to
+    /// `<dir>/<VIDEO_ID>/<ITAG>_<QUALITY>.<EXTENSION>`.
+    ///
+    /// `QUALITY` is the quality label (i.e. `1080p`) for streams with a video track, and the
+    /// bitrate in kbit/s (i.e. `128k`) for audio-only streams. If the video has multiple audio
+    /// tracks, the track id is appended (i.e. `140_128k_en-US.4.mp4`).
+    ///
+    /// Files, that were already downloaded completely, are skipped without making requests (see
+    /// [`ArchiveOptions::skip_existing`]). If a stream fails to download, the remaining streams are
+    /// still downloaded, and the error is included in the returned [`ArchiveReport`].
+    ///
+    /// ### Errors
+    /// - When the directory cannot be created.
+    /// - When the `metadata.json` cannot be written.
+    /// - When a download task is cancelled, i.e. since the runtime shuts down
+    ///   ([`Error::Cancelled`]).
+    pub async fn download_all_to_dir<P: AsRef<Path>>(&self, dir: P, options: ArchiveOptions) -> Result<ArchiveReport> {
+        let dir = dir.as_ref().join(self.id().as_str());
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let archived_lengths = match options.skip_existing {
+            true => archived_lengths(&dir).await,
+            false => HashMap::new(),
+        };
+        let mut report = ArchiveReport { dir: dir.clone(), ..ArchiveReport::default() };
+        let mut archived_streams = Vec::new();
+        let mut tasks = JoinSet::new();
+
+        let mut collect = |itag: u64, result: Result<Archived>| match result {
+            Ok(Archived::Downloaded(path, archived)) => {
+                report.downloaded.push((itag, path));
+                archived_streams.push(archived);
+            }
+            Ok(Archived::Skipped(path, archived)) => {
+                report.skipped.push((itag, path));
+                archived_streams.push(archived);
+            }
+            Err(err) => {
+                log::warn!("failed to archive itag {} of {}: {}", itag, self.id(), err);
+                report.failed.push((itag, err));
+            }
+        };
+
+        for stream in self.streams.iter().filter(|stream| options.includes(stream)) {
+            if tasks.len() >= options.concurrency {
+                let (itag, result) = join_next(&mut tasks).await?;
+                collect(itag, result);
+            }
+
+            let stream = stream.clone();
+            let file = archive_file_name(&stream);
+            let complete_length = match options.skip_existing {
+                true => stream.known_content_length().or_else(|| archived_lengths.get(&file).copied()),
+                false => None,
+            };
+            let path = dir.join(file);
+            tasks.spawn(async move {
+                (stream.itag, archive_stream(&stream, path, complete_length).await)
+            });
+        }
+        while !tasks.is_empty() {
+            let (itag, result) = join_next(&mut tasks).await?;
+            collect(itag, result);
+        }
+
+        report.downloaded.sort();
+        report.skipped.sort();
+        report.failed.sort_by_key(|(itag, _)| *itag);
+
+        if options.write_metadata {
+            archived_streams.sort_by(|a, b| (a.itag, &a.file).cmp(&(b.itag, &b.file)));
+            let metadata = ArchiveMetadata {
+                schema_version: ARCHIVE_SCHEMA_VERSION,
+                archived_at: std::time::SystemTime::now().into(),
+                video_details: VideoDetails::clone(&self.video_info.player_response.video_details),
+                streams: archived_streams,
+            };
+            tokio::fs::write(
+                dir.join(ARCHIVE_METADATA_FILE),
+                serde_json::to_vec_pretty(&metadata)?,
+            ).await?;
+        }
+
+        Ok(report)
+    }
+}
+
+/// The content lengths of the files in the `metadata.json` in `dir`, if there is one.
+async fn archived_lengths(dir: &Path) -> HashMap<String, u64> {
+    let metadata = match tokio::fs::read(dir.join(ARCHIVE_METADATA_FILE)).await {
+        Ok(metadata) => metadata,
+        Err(_) => return HashMap::new(),
+    };
+    match serde_json::from_slice::<ArchiveMetadata>(&metadata) {
+        Ok(metadata) => metadata.streams
+            .into_iter()
+            .map(|stream| (stream.file, stream.content_length))
+            .collect(),
+        Err(err) => {
+            log::warn!("ignoring the unreadable {:?}: {}", dir.join(ARCHIVE_METADATA_FILE), err);
+            HashMap::new()
+        }
+    }
+}
+
+/// Downloads `stream` to `path`, unless the file already has the `complete_length`.
+async fn archive_stream(stream: &Stream, path: PathBuf, complete_length: Option<u64>) -> Result<Archived> {
+    let file = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    if let Some(complete_length) = complete_length {
+        match tokio::fs::metadata(&path).await {
+            Ok(metadata) if metadata.len() == complete_length => {
+                log::debug!("skipping {:?}, since it's already downloaded", path);
+                let archived = ArchivedStream::new(stream, file, complete_length);
+                return Ok(Archived::Skipped(path, archived));
+            }
+            Ok(_) => log::debug!("{:?} is incomplete, downloading it again", path),
+            Err(_) => {}
+        }
+    }
+
+    stream.download_to(&path).await?;
+    let content_length = tokio::fs::metadata(&path).await?.len();
+    let archived = ArchivedStream::new(stream, file, content_length);
+    Ok(Archived::Downloaded(path, archived))
+}
+
+async fn join_next(tasks: &mut JoinSet<(u64, Result<Archived>)>) -> Result<(u64, Result<Archived>)> {
+    crate::error::join_next(tasks)
+        .await
+        .expect("join_next is only called while there are pending tasks")
+}
+
+/// The file name of `stream` in the archive: `<ITAG>_<QUALITY>[_<AUDIO_TRACK>].<EXTENSION>`.
+fn archive_file_name(stream: &Stream) -> String {
+    let quality = match (&stream.quality_label, stream.average_bitrate.or(stream.bitrate)) {
+        (Some(quality_label), _) => quality_label.as_str().replace(' ', "-"),
+        (None, Some(bitrate)) => format!("{}k", (bitrate + 500) / 1000),
+        (None, None) => "unknown".to_owned(),
+    };
+
+    match stream.audio_track {
+        Some(ref track) => format!("{}_{}_{}.{}", stream.itag, quality, track.id, stream.mime.subtype()),
+        None => format!("{}_{}.{}", stream.itag, quality, stream.mime.subtype()),
+    }
+}
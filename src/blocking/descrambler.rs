@@ -1,14 +1,21 @@
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 use crate::blocking::video::Video;
 use crate::descrambler::VideoDescrambler as AsyncVideoDescrambler;
-use crate::Result;
+use crate::{JsRuntime, Result};
 
 /// A synchronous wrapper around [`VideoDescrambler`](crate::VideoDescrambler).
 #[derive(Clone, Debug, derive_more::Display, PartialEq, Eq)]
 pub struct VideoDescrambler(pub(super) AsyncVideoDescrambler);
 
 impl VideoDescrambler {
+    /// See [`VideoDescrambler::with_js_runtime`](crate::VideoDescrambler::with_js_runtime).
+    #[inline]
+    pub fn with_js_runtime(self, js_runtime: Arc<dyn JsRuntime>) -> Self {
+        Self(self.0.with_js_runtime(js_runtime))
+    }
+
     /// A synchronous wrapper around [`VideoDescrambler::descramble`](crate::VideoDescrambler::descramble).
     #[inline]
     pub fn descramble(self) -> Result<Video> {
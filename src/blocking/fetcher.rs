@@ -1,9 +1,10 @@
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 use reqwest::Client;
 use url::Url;
 
-use crate::{IdBuf, Result};
+use crate::{IdBuf, InnertubeClient, JsRuntime, Result};
 use crate::blocking::descrambler::VideoDescrambler;
 use crate::fetcher::VideoFetcher as AsyncVideoFetcher;
 
@@ -36,6 +37,24 @@ impl VideoFetcher {
         Self(AsyncVideoFetcher::from_id_with_client(video_id, client))
     }
 
+    /// See [`VideoFetcher::with_innertube_clients`](crate::VideoFetcher::with_innertube_clients).
+    #[inline]
+    pub fn with_innertube_clients(self, clients: Vec<InnertubeClient>) -> Self {
+        Self(self.0.with_innertube_clients(clients))
+    }
+
+    /// See [`VideoFetcher::with_js_runtime`](crate::VideoFetcher::with_js_runtime).
+    #[inline]
+    pub fn with_js_runtime(self, js_runtime: Arc<dyn JsRuntime>) -> Self {
+        Self(self.0.with_js_runtime(js_runtime))
+    }
+
+    /// See [`VideoFetcher::with_base_url`](crate::VideoFetcher::with_base_url).
+    #[inline]
+    pub fn with_base_url(self, base_url: Url) -> Self {
+        Self(self.0.with_base_url(base_url))
+    }
+
     /// A synchronous wrapper around [`VideoFetcher::fetch`](crate::VideoFetcher::fetch).
     #[inline]
     pub fn fetch(self) -> Result<VideoDescrambler> {
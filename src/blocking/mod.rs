@@ -4,16 +4,19 @@
 //! in the [`crate`] documentation, and in the [`Video`](crate::Video) documentation, except for the
 //! last step:
 //! ```no_run
+//!# #[cfg(feature = "download")]
+//!# fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!# use rustube::blocking::Video;
 //!# use url::Url;
-//!# fn main() -> Result<(), Box<dyn std::error::Error>> {
-//! let url = Url::parse("https://youtube.com/watch?iv=5jlI4uzZGjU")?;
+//! let url = Url::parse("https://youtube.com/watch?v=5jlI4uzZGjU")?;
 //! let path_to_video = Video::from_url(&url)?
 //!    .best_quality()
 //!    .unwrap()
 //!    .blocking_download()?;
 //!#  Ok(())
 //!# }
+//!# #[cfg(not(feature = "download"))]
+//!# fn main() {}
 //!```
 //!   
 //! As you can see, there's no corresponding synchronous version of [`Stream`](crate::Stream), but
@@ -22,17 +25,20 @@
 //!  
 //! Another option is using the [`block`] macro:
 //! ```no_run
+//!# #[cfg(feature = "download")]
+//!# fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!# use rustube::blocking::Video;
 //!# use rustube::block;
 //!# use url::Url;
-//!# fn main() -> Result<(), Box<dyn std::error::Error>> {
-//! let url = Url::parse("https://youtube.com/watch?iv=5jlI4uzZGjU")?;
+//! let url = Url::parse("https://youtube.com/watch?v=5jlI4uzZGjU")?;
 //! let video = Video::from_url(&url)?;
 //! let best_quality = video.best_quality().unwrap();
 //!  
 //! let path_to_video = block!(best_quality.download());
 //!#  Ok(())
 //!# }
+//!# #[cfg(not(feature = "download"))]
+//!# fn main() {}
 //!```
 //!    
 //!This macro will utilize the [`Runtime`](tokio::runtime::Runtime) created for you by `rustube`, 
@@ -82,6 +88,16 @@ pub mod descrambler;
 pub mod video;
 
 
+/// A synchronous wrapper around [`download`](crate::download).
+#[inline]
+#[cfg(all(feature = "download", feature = "regex"))]
+pub fn download(
+    video_identifier: &str,
+    options: crate::SimpleDownloadOptions<'_>,
+) -> crate::Result<std::path::PathBuf> {
+    block!(crate::download(video_identifier, options))
+}
+
 /// A synchronous wrapper around [`download_best_quality`](crate::download_best_quality).
 #[inline]
 #[cfg(all(feature = "download", feature = "regex"))]
@@ -0,0 +1,210 @@
+//! An opt-in cache for the responses [`VideoFetcher`](crate::VideoFetcher) requests, i.e. the
+//! watch page, the player JavaScript, and the responses of the innertube API.
+//!
+//! Re-fetching the same video over and over (i.e. during development, or when re-processing
+//! many videos) wastes time, and quickly triggers YouTube's rate limits. With a
+//! [`ResponseCache`] (see [`VideoFetcher::with_response_cache`](crate::VideoFetcher::with_response_cache)),
+//! identical requests are answered from the cache instead.
+//!
+//! The media of streams is never cached, since it's way too large, and its URLs expire anyways.
+//!
+//! # Example
+//!```no_run
+//!# use std::sync::Arc;
+//!# use rustube::{Id, VideoFetcher};
+//!# use rustube::cache::{CacheMode, FileCache};
+//!# #[tokio::main]
+//!# async fn main() -> rustube::Result<()> {
+//! let id = Id::from_str("5jlI4uzZGjU")?.into_owned();
+//! let descrambler = VideoFetcher::from_id(id)?
+//!     .with_response_cache(Arc::new(FileCache::new("/tmp/rustube-cache")))
+//!     .with_cache_mode(CacheMode::ReadWrite)
+//!     .fetch()
+//!     .await?;
+//!# Ok(())
+//!# }
+//! ```
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use url::Url;
+
+use crate::stream::hash::HashAlgorithm;
+
+/// How long responses are cached by default.
+///
+/// The player responses contain stream URLs, which expire after about six hours, so responses
+/// should not be cached for much longer.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Whether [`VideoFetcher`](crate::VideoFetcher) reads from, and writes to its
+/// [`ResponseCache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CacheMode {
+    /// The cache is neither read, nor written.
+    Disabled,
+    /// Cached responses are used, and all other responses are cached.
+    #[default]
+    ReadWrite,
+    /// Cached responses are used, but new responses are not cached. This is mostly useful for
+    /// tests, that should not change the cache.
+    ReadOnly,
+    /// Cached responses are ignored, but all responses are cached, replacing the old ones.
+    Refresh,
+}
+
+impl CacheMode {
+    /// Whether cached responses are used.
+    #[inline]
+    pub fn reads(self) -> bool {
+        matches!(self, Self::ReadWrite | Self::ReadOnly)
+    }
+
+    /// Whether responses are cached.
+    #[inline]
+    pub fn writes(self) -> bool {
+        matches!(self, Self::ReadWrite | Self::Refresh)
+    }
+}
+
+/// The body of a cached response, and when it was cached.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedResponse {
+    pub body: Vec<u8>,
+    pub stored_at: SystemTime,
+}
+
+impl CachedResponse {
+    /// A response with `body`, that's cached right now.
+    #[inline]
+    pub fn new(body: Vec<u8>) -> Self {
+        Self { body, stored_at: SystemTime::now() }
+    }
+}
+
+/// A cache for the responses of [`VideoFetcher`](crate::VideoFetcher).
+///
+/// The keys are computed by [`key`]. Since the cache is accessed right before, and after a request,
+/// implementations should be fast, and must not block for long.
+pub trait ResponseCache: Send + Sync {
+    /// The response cached for `key`, unless there is none, or it expired.
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+
+    /// Caches `response` for `key`, for at most `ttl`.
+    ///
+    /// Failing to cache a response is not fatal, so implementations should only log errors.
+    fn put(&self, key: &str, response: CachedResponse, ttl: Duration);
+}
+
+/// The cache key of a request, which is the hex SHA-256 digest of its `method`, `url` and `body`.
+pub fn key(method: &str, url: &Url, body: &[u8]) -> String {
+    let mut data = format!("{} {}\n", method, url).into_bytes();
+    data.extend_from_slice(body);
+    HashAlgorithm::Sha256.digest(&data)
+}
+
+/// A [`ResponseCache`], that stores every response in a file named after its key, in a single
+/// directory.
+///
+/// Each file starts with a line containing the time the response was cached, and its TTL, in
+/// seconds, followed by the body. Expired files are removed, when they're read.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+impl FileCache {
+    /// A cache in `dir`, which is created with the first cached response.
+    #[inline]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The directory, the responses are stored in.
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Removes all cached responses.
+    ///
+    /// ### Errors
+    /// - When the directory exists, but can't be removed.
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            res => res,
+        }
+    }
+
+    #[inline]
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(key)
+    }
+
+    fn read(&self, key: &str) -> io::Result<Option<CachedResponse>> {
+        let path = self.path(key);
+        let content = match fs::read(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid cache file");
+        let header_end = content.iter().position(|&b| b == b'\n').ok_or_else(invalid)?;
+        let header = std::str::from_utf8(&content[..header_end]).map_err(|_| invalid())?;
+        let (stored_at, ttl) = header
+            .split_once(' ')
+            .and_then(|(stored_at, ttl)| Some((stored_at.parse::<u64>().ok()?, ttl.parse::<u64>().ok()?)))
+            .ok_or_else(invalid)?;
+
+        let stored_at = UNIX_EPOCH + Duration::from_secs(stored_at);
+        let expired = stored_at
+            .checked_add(Duration::from_secs(ttl))
+            .is_some_and(|expires_at| expires_at <= SystemTime::now());
+        if expired {
+            log::debug!("the cached response {} expired", key);
+            fs::remove_file(&path)?;
+            return Ok(None);
+        }
+
+        Ok(Some(CachedResponse {
+            body: content[header_end + 1..].to_vec(),
+            stored_at,
+        }))
+    }
+
+    fn write(&self, key: &str, response: &CachedResponse, ttl: Duration) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let stored_at = response.stored_at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |stored_at| stored_at.as_secs());
+
+        // written to a temporary file first, so concurrent readers never see a partial response
+        let path = self.path(key);
+        let tmp_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        writeln!(file, "{} {}", stored_at, ttl.as_secs())?;
+        file.write_all(&response.body)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)
+    }
+}
+
+impl ResponseCache for FileCache {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.read(key).unwrap_or_else(|e| {
+            log::warn!("failed to read the cached response {}: {}", key, e);
+            None
+        })
+    }
+
+    fn put(&self, key: &str, response: CachedResponse, ttl: Duration) {
+        if let Err(e) = self.write(key, &response, ttl) {
+            log::warn!("failed to cache the response {}: {}", key, e);
+        }
+    }
+}
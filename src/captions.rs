@@ -0,0 +1,201 @@
+//! Captions (subtitles) of a video, and converting them to SRT or WebVTT.
+//!
+//! The caption tracks of a video are listed in its [`PlayerResponse`](crate::PlayerResponse)
+//! (see [`Video::caption_tracks`](crate::Video::caption_tracks)). YouTube serves every track as
+//! an XML document, which is read into a [`Transcript`].
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{Error, LanguageTag, Result};
+use crate::video_info::player_response::playability_status::Reason;
+use crate::xml;
+
+/// The `captions` of a [`PlayerResponse`](crate::PlayerResponse).
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Captions {
+    pub player_captions_tracklist_renderer: CaptionTracklist,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptionTracklist {
+    #[serde(default)]
+    pub caption_tracks: Vec<CaptionTrack>,
+    /// The languages, [translatable](CaptionTrack::is_translatable) tracks can be translated to.
+    #[serde(default)]
+    pub translation_languages: Vec<TranslationLanguage>,
+}
+
+/// A caption track of a video, i.e. the English subtitles, or the automatically generated
+/// captions.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptionTrack {
+    /// The url of the track. It's only valid for a few hours.
+    pub base_url: Url,
+    pub name: Reason,
+    pub language_code: LanguageTag,
+    /// `asr` for automatically generated captions, and `None` for captions uploaded by the
+    /// creator of the video.
+    pub kind: Option<String>,
+    /// Whether YouTube can translate the track to the
+    /// [`translation_languages`](CaptionTracklist::translation_languages).
+    #[serde(default)]
+    pub is_translatable: bool,
+    pub vss_id: Option<String>,
+}
+
+impl CaptionTrack {
+    /// The name of the track, as shown in YouTube's player, i.e. `English (auto-generated)`.
+    #[inline]
+    pub fn display_name(&self) -> String {
+        self.name.to_text()
+    }
+
+    /// Whether the captions were generated by YouTube's speech recognition.
+    #[inline]
+    pub fn is_auto_generated(&self) -> bool {
+        self.kind.as_deref() == Some("asr")
+    }
+
+    /// Requests the track, and parses it into a [`Transcript`].
+    pub async fn download(&self, client: &reqwest::Client) -> Result<Transcript> {
+        Self::request(client, &self.base_url).await
+    }
+
+    /// Requests the track translated to `language` by YouTube, and parses it into a
+    /// [`Transcript`].
+    ///
+    /// ### Errors
+    /// - [`Error::Custom`], when the track is not translatable.
+    /// - When the request fails, or the response is invalid.
+    pub async fn download_translated(&self, client: &reqwest::Client, language: &LanguageTag) -> Result<Transcript> {
+        if !self.is_translatable {
+            return Err(Error::Custom(format!(
+                "the `{}` captions can't be translated", self.language_code
+            ).into()));
+        }
+
+        let mut url = self.base_url.clone();
+        url.query_pairs_mut().append_pair("tlang", language.as_str());
+        Self::request(client, &url).await
+    }
+
+    async fn request(client: &reqwest::Client, url: &Url) -> Result<Transcript> {
+        log::debug!("request the captions {}", url.as_str());
+        let xml = client
+            .get(url.as_str())
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        Transcript::parse(&xml)
+    }
+}
+
+/// A language, caption tracks can be translated to.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationLanguage {
+    pub language_code: LanguageTag,
+    pub language_name: Reason,
+}
+
+/// The captions of a [`CaptionTrack`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Transcript {
+    pub cues: Vec<Cue>,
+}
+
+/// A caption, that's shown for `duration` from `start` on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cue {
+    pub start: Duration,
+    pub duration: Duration,
+    pub text: String,
+}
+
+impl Transcript {
+    /// Parses a track in YouTube's XML format
+    /// (`<transcript><text start="1.2" dur="3.4">...</text></transcript>`).
+    ///
+    /// ### Errors
+    /// - [`Error::UnexpectedResponse`], when `xml` is not a valid track.
+    pub fn parse(xml: &str) -> Result<Self> {
+        let transcript = xml::parse(xml).map_err(invalid)?;
+        if transcript.name != "transcript" {
+            return Err(invalid(format!("unexpected root element `{}`", transcript.name)));
+        }
+
+        let cues = transcript
+            .children("text")
+            .map(|text| {
+                let seconds = |name: &str| -> Result<Duration> {
+                    text.attribute(name)
+                        .unwrap_or("0")
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|seconds| seconds.is_finite() && *seconds >= 0.)
+                        .map(|seconds| Duration::from_millis((seconds * 1000.).round() as u64))
+                        .ok_or_else(|| invalid(format!("invalid `{}` of a cue", name)))
+                };
+
+                // the text is escaped twice, i.e. `&amp;#39;` for `'`
+                let text_content = xml::unescape(&text.text).unwrap_or_else(|_| text.text.clone());
+                Ok(Cue {
+                    start: seconds("start")?,
+                    duration: seconds("dur")?,
+                    text: text_content.trim().to_owned(),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { cues })
+    }
+
+    /// The transcript in the SubRip (`.srt`) format.
+    pub fn to_srt(&self) -> String {
+        let mut srt = String::new();
+        for (i, cue) in self.cues.iter().enumerate() {
+            let _ = write!(
+                srt,
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1, timestamp(cue.start, ','), timestamp(cue.start + cue.duration, ','), cue.text,
+            );
+        }
+        srt
+    }
+
+    /// The transcript in the WebVTT (`.vtt`) format.
+    pub fn to_vtt(&self) -> String {
+        let mut vtt = String::from("WEBVTT\n\n");
+        for cue in &self.cues {
+            let _ = write!(
+                vtt,
+                "{} --> {}\n{}\n\n",
+                timestamp(cue.start, '.'), timestamp(cue.start + cue.duration, '.'), cue.text,
+            );
+        }
+        vtt
+    }
+}
+
+/// Formats `time` as `HH:MM:SS<separator>mmm`.
+fn timestamp(time: Duration, separator: char) -> String {
+    let millis = time.as_millis();
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000, millis / 60_000 % 60, millis / 1000 % 60, separator, millis % 1000,
+    )
+}
+
+#[inline]
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::UnexpectedResponse(format!("invalid captions: {}", reason.into()).into())
+}
@@ -0,0 +1,94 @@
+//! The chapters of a video.
+//!
+//! YouTube shows chapters for videos, whose description lists timestamps, or whose creator
+//! added them manually. The chapter markers of the player are part of the `ytInitialData` of the
+//! watch page, which is only kept, if the [`VideoFetcher`](crate::VideoFetcher) was told to with
+//! [`with_initial_data`](crate::VideoFetcher::with_initial_data). Otherwise, the chapters are
+//! read from the description the same way YouTube does.
+
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::id::parse_timestamp;
+
+/// The minimum number of timestamps in a description, for YouTube to show them as chapters.
+const MIN_DESCRIPTION_CHAPTERS: usize = 3;
+
+/// A chapter of a video, that lasts from `start` until the start of the next chapter, or the end
+/// of the video.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chapter {
+    pub start: Duration,
+    pub title: String,
+}
+
+impl Chapter {
+    /// The chapter markers of the player in the `ytInitialData` of a watch page (see
+    /// [`VideoInfo::initial_data`](crate::VideoInfo::initial_data)), in the order they appear
+    /// in the video.
+    ///
+    /// Returns an empty list, if there are no chapter markers.
+    pub fn from_initial_data(initial_data: &Value) -> Vec<Self> {
+        let markers_map = &initial_data["playerOverlays"]["playerOverlayRenderer"]["decoratedPlayerBarRenderer"]
+            ["decoratedPlayerBarRenderer"]["playerBar"]["multiMarkersPlayerBarRenderer"]["markersMap"];
+
+        let chapters = markers_map
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|marker| marker["key"] == "DESCRIPTION_CHAPTERS" || marker["key"] == "AUTO_CHAPTERS")
+            .find_map(|marker| marker["value"]["chapters"].as_array());
+
+        let mut chapters: Vec<_> = chapters
+            .into_iter()
+            .flatten()
+            .filter_map(|chapter| {
+                let renderer = &chapter["chapterRenderer"];
+                let title = match renderer["title"]["simpleText"].as_str() {
+                    Some(title) => title.to_owned(),
+                    None => renderer["title"]["runs"]
+                        .as_array()?
+                        .iter()
+                        .filter_map(|run| run["text"].as_str())
+                        .collect(),
+                };
+                Some(Self {
+                    start: Duration::from_millis(renderer["timeRangeStartMillis"].as_u64()?),
+                    title,
+                })
+            })
+            .collect();
+        chapters.sort_by_key(|chapter| chapter.start);
+        chapters
+    }
+
+    /// The chapters listed in a description, i.e. `0:00 Intro`, one per line.
+    ///
+    /// Like on YouTube, the timestamps only count as chapters, if the first one is `0:00`, there
+    /// are at least three of them, and they are in ascending order. Otherwise, the list is empty.
+    pub fn from_description(description: &str) -> Vec<Self> {
+        let chapters: Vec<_> = description
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                let (timestamp, title) = line.split_once(char::is_whitespace)?;
+                // only clock times, since a plain number is no timestamp in a description
+                if !timestamp.contains(':') {
+                    return None;
+                }
+                let start = parse_timestamp(timestamp)?;
+                let title = title.trim_start_matches(|c: char| c.is_whitespace() || c == '-' || c == '–').trim_end();
+                Some(Self { start, title: title.to_owned() })
+            })
+            .collect();
+
+        let valid = chapters.len() >= MIN_DESCRIPTION_CHAPTERS
+            && chapters[0].start == Duration::ZERO
+            && chapters.windows(2).all(|pair| pair[0].start < pair[1].start);
+        match valid {
+            true => chapters,
+            false => Vec::new(),
+        }
+    }
+}
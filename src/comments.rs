@@ -0,0 +1,420 @@
+//! Fetching the top-level comments of a video.
+//!
+//! The comments are not part of the watch page. Its response of the `next` endpoint of the
+//! innertube API only contains a continuation token, that loads the first page of comments, along
+//! with a sort menu, whose items are continuation tokens as well. Each page then ends with the
+//! token of the next one.
+//!
+//! Comment pagination quickly runs into YouTube's rate limit, so rate limited requests are retried
+//! after the `Retry-After` delay, or an exponential backoff (see
+//! [`Comments::with_rate_limit_retries`]).
+//!
+//! # Example
+//! ```no_run
+//!# use rustube::{Id, comments::{Comments, CommentSort}};
+//!# use tokio_stream::StreamExt;
+//!# #[tokio::main]
+//!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = rustube::fetcher::ClientBuilder::recommended().build()?;
+//! let mut comments = Comments::new(Id::from_str("5jlI4uzZGjU")?, &client)
+//!     .sort(CommentSort::Newest)
+//!     .stream();
+//! while let Some(comment) = comments.next().await {
+//!     let comment = comment?;
+//!     println!("{}: {}", comment.author, comment.text);
+//! }
+//!# Ok(())
+//!# }
+//! ```
+
+use reqwest::Client;
+use serde_json::Value;
+use url::Url;
+
+use crate::{Error, Id, IdBuf, InnertubeClient, Result};
+use crate::fetcher::{is_rate_limited, RATE_LIMIT_BACKOFF, retry_after};
+
+/// The base URL of all requests to YouTube.
+const YOUTUBE_URL: &str = "https://www.youtube.com/";
+
+/// The number of retries of rate limited requests, if not set with
+/// [`Comments::with_rate_limit_retries`].
+const DEFAULT_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// The `sectionIdentifier` of the item section of the watch page, that loads the comments.
+const COMMENT_SECTION: &str = "comment-item-section";
+
+/// The order of the comments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CommentSort {
+    /// The comments YouTube ranks highest, which is the order of the watch page.
+    #[default]
+    Top,
+    /// The newest comments first.
+    Newest,
+}
+
+/// A top-level comment of a video.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    pub comment_id: String,
+    /// The display name of the author, i.e. `@handle`.
+    pub author: String,
+    pub author_channel_id: Option<String>,
+    pub text: String,
+    /// The number of likes. YouTube rounds large counts, i.e. `1.2K`, so they are not exact.
+    pub like_count: u64,
+    /// When the comment was published, relative to now, i.e. `2 years ago (edited)`.
+    pub published_time_text: String,
+    pub reply_count: u64,
+    /// Whether the uploader pinned the comment to the top.
+    pub is_pinned: bool,
+}
+
+/// Fetches the top-level comments of a video (see the [module docs](self)).
+#[derive(Clone, Debug)]
+pub struct Comments {
+    video_id: IdBuf,
+    client: Client,
+    base_url: Url,
+    sort: CommentSort,
+    rate_limit_retries: u32,
+}
+
+impl Comments {
+    /// Creates a [`Comments`] for the video with the id `video_id`, that uses `client` for all
+    /// requests.
+    #[inline]
+    pub fn new(video_id: Id<'_>, client: &Client) -> Self {
+        Self {
+            video_id: video_id.into_owned(),
+            client: client.clone(),
+            base_url: Url::parse(YOUTUBE_URL).unwrap(),
+            sort: CommentSort::default(),
+            rate_limit_retries: DEFAULT_RATE_LIMIT_RETRIES,
+        }
+    }
+
+    /// Streams the comments of the video with the id `video_id` with the default options.
+    /// Shorthand for `Comments::new(video_id, client).stream()`.
+    ///
+    /// # Panics
+    /// When called outside of a tokio runtime.
+    #[inline]
+    pub fn fetch(video_id: Id<'_>, client: &Client) -> impl tokio_stream::Stream<Item=Result<Comment>> {
+        Self::new(video_id, client).stream()
+    }
+
+    /// Sets the order of the comments.
+    ///
+    /// [default: [`CommentSort::Top`]]
+    #[inline]
+    pub fn sort(mut self, sort: CommentSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Sets how often a rate limited request is retried, after waiting for the `Retry-After`
+    /// delay, or, if there is none, for [`RATE_LIMIT_BACKOFF`], doubled with each retry. After the
+    /// last retry, the stream yields [`Error::RateLimited`].
+    ///
+    /// [default: `3`]
+    #[inline]
+    pub fn with_rate_limit_retries(mut self, retries: u32) -> Self {
+        self.rate_limit_retries = retries;
+        self
+    }
+
+    /// Sets the URL all requests are made to, instead of `https://www.youtube.com/`.
+    /// This is mostly useful for testing against a local server.
+    #[inline]
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Streams the comments, page by page. The next page is only requested, once the comments of
+    /// the current one were consumed.
+    ///
+    /// Videos with disabled comments yield no comments. After the first error, the stream ends.
+    /// The comments are fetched in a background task, that stops, once the returned stream is
+    /// dropped.
+    ///
+    /// # Panics
+    /// When called outside of a tokio runtime.
+    pub fn stream(self) -> impl tokio_stream::Stream<Item=Result<Comment>> {
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+
+        tokio::spawn(async move {
+            let mut pages = self.pages();
+            loop {
+                let comments = match pages.next().await {
+                    Ok(Some(comments)) => comments.into_iter().map(Ok).collect(),
+                    Ok(None) => break,
+                    Err(err) => vec![Err(err)],
+                };
+                for comment in comments {
+                    let is_err = comment.is_err();
+                    if sender.send(comment).await.is_err() || is_err {
+                        // the stream was dropped, or the pagination failed
+                        return;
+                    }
+                }
+            }
+        });
+
+        tokio_stream::wrappers::ReceiverStream::new(receiver)
+    }
+
+    fn pages(self) -> Pages {
+        Pages { comments: self, state: PageState::Start }
+    }
+
+    /// Requests the `next` endpoint with `body`, and retries, if the request is rate limited.
+    async fn request(&self, body: &Value) -> Result<Value> {
+        let mut retries = 0;
+        loop {
+            match self.request_once(body).await {
+                Err(Error::RateLimited { retry_after, .. }) if retries < self.rate_limit_retries => {
+                    let delay = retry_after.unwrap_or(RATE_LIMIT_BACKOFF * 2u32.saturating_pow(retries));
+                    log::warn!("the next endpoint is rate limited, retrying in {:?}", delay);
+                    tokio::time::sleep(delay).await;
+                    retries += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Requests the `next` endpoint as the [`InnertubeClient::Web`] client once.
+    async fn request_once(&self, body: &Value) -> Result<Value> {
+        let client = InnertubeClient::Web;
+        let mut url = self.base_url.join("youtubei/v1/next")?;
+        url
+            .query_pairs_mut()
+            .append_pair("prettyPrint", "false");
+
+        let response = self.client
+            .post(url.as_str())
+            .header(reqwest::header::USER_AGENT, client.user_agent())
+            .header("X-YouTube-Client-Name", client.id())
+            .header("X-YouTube-Client-Version", client.version())
+            .json(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(retry_after);
+        if let Err(err) = response.error_for_status_ref() {
+            let body = response.bytes().await.unwrap_or_default();
+            return match is_rate_limited(status, &body) {
+                true => Err(Error::RateLimited { retry_after, endpoint: "next" }),
+                false => Err(err.into()),
+            };
+        }
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+}
+
+/// The pagination of the comments.
+struct Pages {
+    comments: Comments,
+    state: PageState,
+}
+
+enum PageState {
+    /// The watch page was not requested yet.
+    Start,
+    /// The page of `continuation` is requested next. If `sorted` is `false`, the response is only
+    /// used to pick the sort menu item.
+    Next { continuation: String, sorted: bool },
+    Done,
+}
+
+impl Pages {
+    /// The comments of the next page, or `None`, if there are no more pages.
+    async fn next(&mut self) -> Result<Option<Vec<Comment>>> {
+        loop {
+            let (continuation, sorted) = match std::mem::replace(&mut self.state, PageState::Done) {
+                PageState::Start => {
+                    let body = InnertubeClient::Web.next_request(self.comments.video_id.as_borrowed());
+                    let response = self.comments.request(&body).await?;
+                    match comment_section_continuation(&response) {
+                        Some(continuation) => {
+                            // the first page is sorted by top comments already
+                            let sorted = self.comments.sort == CommentSort::Top;
+                            self.state = PageState::Next { continuation, sorted };
+                        }
+                        None => log::info!("{} has no comment section, the comments may be disabled", self.comments.video_id),
+                    }
+                    continue;
+                }
+                PageState::Next { continuation, sorted } => (continuation, sorted),
+                PageState::Done => return Ok(None),
+            };
+
+            let body = InnertubeClient::Web.continuation_request(&continuation);
+            let response = self.comments.request(&body).await?;
+
+            if !sorted {
+                match sort_continuation(&response, self.comments.sort) {
+                    Some(continuation) => self.state = PageState::Next { continuation, sorted: true },
+                    None => return Err(Error::UnexpectedResponse(
+                        "the first page of comments does not contain a sort menu".into()
+                    )),
+                }
+                continue;
+            }
+
+            let (comments, continuation) = parse_page(&response);
+            if let Some(continuation) = continuation {
+                self.state = PageState::Next { continuation, sorted: true };
+            }
+            return Ok(Some(comments));
+        }
+    }
+}
+
+/// The continuation token of the comment section of a watch page's `next` response.
+fn comment_section_continuation(response: &Value) -> Option<String> {
+    response["contents"]["twoColumnWatchNextResults"]["results"]["results"]["contents"]
+        .as_array()?
+        .iter()
+        .map(|content| &content["itemSectionRenderer"])
+        .find(|section| section["sectionIdentifier"] == COMMENT_SECTION)?
+        ["contents"]
+        .as_array()?
+        .iter()
+        .find_map(|item| continuation(&item["continuationItemRenderer"]))
+}
+
+/// The continuation token of the item of the sort menu, that sorts the comments by `sort`. The
+/// menu lists the top comments first, and the newest second.
+fn sort_continuation(response: &Value, sort: CommentSort) -> Option<String> {
+    let index = match sort {
+        CommentSort::Top => 0,
+        CommentSort::Newest => 1,
+    };
+    continuation_items(response)
+        .find_map(|item| item["commentsHeaderRenderer"]["sortMenu"]["sortFilterSubMenuRenderer"]["subMenuItems"].as_array())?
+        .get(index)?
+        ["serviceEndpoint"]["continuationCommand"]["token"]
+        .as_str()
+        .map(str::to_owned)
+}
+
+/// The comments and the continuation token of the next page of a page of comments.
+fn parse_page(response: &Value) -> (Vec<Comment>, Option<String>) {
+    let mut comments = Vec::new();
+    let mut next = None;
+    for item in continuation_items(response) {
+        if let Some(comment) = comment_thread(response, &item["commentThreadRenderer"]) {
+            comments.push(comment);
+        }
+        if let Some(continuation) = continuation(&item["continuationItemRenderer"]) {
+            next = Some(continuation);
+        }
+    }
+    (comments, next)
+}
+
+/// The items of all `reloadContinuationItemsCommand`s and `appendContinuationItemsAction`s of
+/// a response. The first page reloads the header and the body of the comment section, the
+/// following pages are appended.
+fn continuation_items(response: &Value) -> impl Iterator<Item=&Value> {
+    response["onResponseReceivedEndpoints"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|endpoint| {
+            endpoint["reloadContinuationItemsCommand"]["continuationItems"]
+                .as_array()
+                .or_else(|| endpoint["appendContinuationItemsAction"]["continuationItems"].as_array())
+        })
+        .flatten()
+}
+
+/// The continuation token of a `continuationItemRenderer`, which is either loaded, once it's
+/// scrolled into view, or by clicking a button.
+fn continuation(renderer: &Value) -> Option<String> {
+    renderer["continuationEndpoint"]["continuationCommand"]["token"]
+        .as_str()
+        .or_else(|| renderer["button"]["buttonRenderer"]["command"]["continuationCommand"]["token"].as_str())
+        .map(str::to_owned)
+}
+
+/// Parses a `commentThreadRenderer`.
+///
+/// YouTube either sends the comment as `commentRenderer`, or as `commentViewModel`, whose data is
+/// part of the `frameworkUpdates` of the response, keyed by its `commentKey`.
+fn comment_thread(response: &Value, thread: &Value) -> Option<Comment> {
+    let renderer = &thread["comment"]["commentRenderer"];
+    if renderer.is_object() {
+        return Some(Comment {
+            comment_id: renderer["commentId"].as_str()?.to_owned(),
+            author: text(&renderer["authorText"]).unwrap_or_default(),
+            author_channel_id: renderer["authorEndpoint"]["browseEndpoint"]["browseId"].as_str().map(str::to_owned),
+            text: text(&renderer["contentText"]).unwrap_or_default(),
+            like_count: text(&renderer["voteCount"]).and_then(|count| parse_count(&count)).unwrap_or_default(),
+            published_time_text: text(&renderer["publishedTimeText"]).unwrap_or_default(),
+            reply_count: renderer["replyCount"].as_u64().unwrap_or_default(),
+            is_pinned: renderer["pinnedCommentBadge"].is_object(),
+        });
+    }
+
+    let view_model = &thread["commentViewModel"]["commentViewModel"];
+    let key = view_model["commentKey"].as_str()?;
+    let payload = response["frameworkUpdates"]["entityBatchUpdate"]["mutations"]
+        .as_array()?
+        .iter()
+        .find(|mutation| mutation["entityKey"] == key)?
+        ["payload"]
+        .get("commentEntityPayload")?;
+    let count = |value: &Value| value.as_str().and_then(parse_count).unwrap_or_default();
+    Some(Comment {
+        comment_id: payload["properties"]["commentId"].as_str()?.to_owned(),
+        author: payload["author"]["displayName"].as_str().unwrap_or_default().to_owned(),
+        author_channel_id: payload["author"]["channelId"].as_str().map(str::to_owned),
+        text: payload["properties"]["content"]["content"].as_str().unwrap_or_default().to_owned(),
+        like_count: count(&payload["toolbar"]["likeCountNotliked"]),
+        published_time_text: payload["properties"]["publishedTime"].as_str().unwrap_or_default().to_owned(),
+        reply_count: count(&payload["toolbar"]["replyCount"]),
+        is_pinned: view_model["pinnedText"].is_string(),
+    })
+}
+
+/// The text of a `simpleText`, or `runs` object.
+fn text(value: &Value) -> Option<String> {
+    match value["simpleText"].as_str() {
+        Some(text) => Some(text.to_owned()),
+        None => Some(
+            value["runs"]
+                .as_array()?
+                .iter()
+                .filter_map(|run| run["text"].as_str())
+                .collect()
+        ),
+    }
+}
+
+/// Parses a count, as YouTube displays it, i.e. `987`, `1,234`, `1.2K`, or `3M`. An empty count
+/// means `0`.
+fn parse_count(count: &str) -> Option<u64> {
+    let count = count.trim().replace(',', "");
+    if count.is_empty() {
+        return Some(0);
+    }
+
+    let (number, factor) = match count.char_indices().last()? {
+        (i, 'K') => (&count[..i], 1_000.),
+        (i, 'M') => (&count[..i], 1_000_000.),
+        (i, 'B') => (&count[..i], 1_000_000_000.),
+        _ => return count.parse().ok(),
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    Some((number * factor).round() as u64)
+}
@@ -0,0 +1,582 @@
+//! Reading DASH manifests (MPD), and downloading the segments of one of their representations.
+//!
+//! YouTube often serves finished live streams, especially ones longer than four hours, only
+//! completely through the DASH manifest of the video. The adaptive formats of such videos fail
+//! with `404`, or end after four hours (see
+//! [`Video::prefers_dash_manifest`](crate::Video::prefers_dash_manifest)).
+//!
+//! Only the parts of the MPD format YouTube uses are supported: `BaseURL`s, `SegmentList`s, and
+//! `SegmentTemplate`s with or without a `SegmentTimeline`. Segments, that are addressed by byte
+//! ranges, are not supported.
+
+use std::convert::TryFrom;
+#[cfg(feature = "download")]
+use std::path::Path;
+use std::str::FromStr;
+#[cfg(feature = "download")]
+use std::sync::Arc;
+use std::time::Duration;
+
+use url::Url;
+
+#[cfg(feature = "download")]
+use crate::transfer::TransferStats;
+use crate::{Error, Result};
+use crate::xml::{self, Element};
+
+/// The maximum number of segments of a single representation, so a broken manifest can't make
+/// rustube allocate arbitrary amounts of memory.
+const MAX_SEGMENTS: u64 = 1 << 20;
+
+/// A parsed DASH manifest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DashManifest {
+    /// The `mediaPresentationDuration` of the manifest, if it has one.
+    pub duration: Option<Duration>,
+    /// All representations of all periods. Representations with the same id in multiple periods
+    /// are merged, so their segments are concatenated.
+    pub representations: Vec<Representation>,
+}
+
+impl DashManifest {
+    /// Parses the manifest `xml`, which was requested from `manifest_url`. Relative urls in the
+    /// manifest are resolved against `manifest_url`, and all segment templates and timelines are
+    /// expanded, so every [`Representation`] contains the url of each of its segments.
+    ///
+    /// ### Errors
+    /// - [`Error::UnexpectedResponse`], when `xml` is not a valid manifest, or uses features, that
+    ///   are not supported.
+    pub fn parse(xml: &str, manifest_url: &Url) -> Result<Self> {
+        let mpd = xml::parse(xml).map_err(invalid)?;
+        if mpd.name != "MPD" {
+            return Err(invalid(format!("expected an `MPD` element, found `{}`", mpd.name)));
+        }
+
+        let duration = mpd.duration_attribute("mediaPresentationDuration")?;
+        let mpd_base = base_url(&mpd, manifest_url)?;
+        let mut representations: Vec<Representation> = Vec::new();
+
+        for period in mpd.children("Period") {
+            let period_duration = period.duration_attribute("duration")?.or(duration);
+            let period_base = base_url(period, &mpd_base)?;
+            let period_template = SegmentTemplate::from_parent(period, None)?;
+
+            for adaptation_set in period.children("AdaptationSet") {
+                let set_base = base_url(adaptation_set, &period_base)?;
+                let set_template = SegmentTemplate::from_parent(adaptation_set, period_template.as_ref())?;
+
+                for element in adaptation_set.children("Representation") {
+                    let representation = Representation::parse(
+                        element,
+                        adaptation_set,
+                        &set_base,
+                        set_template.as_ref(),
+                        period_duration,
+                    )?;
+
+                    match representations.iter_mut().find(|r| r.id == representation.id) {
+                        Some(existing) => existing.segments.extend(representation.segments),
+                        None => representations.push(representation),
+                    }
+                }
+            }
+        }
+
+        Ok(Self { duration, representations })
+    }
+
+    /// The representation with the id `id`.
+    #[inline]
+    pub fn representation(&self, id: &str) -> Option<&Representation> {
+        self.representations.iter().find(|r| r.id == id)
+    }
+
+    /// The representation of the format with the itag `itag`.
+    #[inline]
+    pub fn representation_by_itag(&self, itag: u64) -> Option<&Representation> {
+        self.representations.iter().find(|r| r.itag() == Some(itag))
+    }
+}
+
+/// A single representation (format) of a [`DashManifest`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Representation {
+    /// The id of the representation, which is the itag of the format in manifests of YouTube.
+    pub id: String,
+    pub mime_type: Option<String>,
+    pub codecs: Option<String>,
+    pub bandwidth: Option<u64>,
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    /// The url of the initialization segment, if there is one.
+    pub initialization: Option<Url>,
+    /// The urls of the media segments, in the order, they have to be concatenated.
+    pub segments: Vec<Url>,
+}
+
+impl Representation {
+    /// The itag of the representation, if its id is one.
+    #[inline]
+    pub fn itag(&self) -> Option<u64> {
+        self.id.parse().ok()
+    }
+
+    /// The urls of all segments, starting with the initialization segment.
+    #[inline]
+    pub fn segment_urls(&self) -> impl Iterator<Item=&Url> {
+        self.initialization.iter().chain(&self.segments)
+    }
+
+    /// The number of all segments, including the initialization segment.
+    #[inline]
+    pub fn segment_count(&self) -> usize {
+        self.initialization.iter().count() + self.segments.len()
+    }
+
+    fn parse(
+        element: &Element,
+        adaptation_set: &Element,
+        base: &Url,
+        set_template: Option<&SegmentTemplate>,
+        period_duration: Option<Duration>,
+    ) -> Result<Self> {
+        let id = element
+            .attribute("id")
+            .ok_or_else(|| invalid("a `Representation` has no id"))?
+            .to_owned();
+        let inherited = |name: &str| element
+            .attribute(name)
+            .or_else(|| adaptation_set.attribute(name))
+            .map(str::to_owned);
+        let bandwidth = element.parse_attribute("bandwidth")?;
+        let base = base_url(element, base)?;
+
+        let segment_list = element
+            .child("SegmentList")
+            .or_else(|| adaptation_set.child("SegmentList"));
+        let template = SegmentTemplate::from_parent(element, set_template)?;
+
+        let (initialization, segments) = match (segment_list, template) {
+            (Some(list), _) => {
+                let initialization = list
+                    .child("Initialization")
+                    .and_then(|init| init.attribute("sourceURL"))
+                    .map(|init| base.join(init))
+                    .transpose()?;
+                let segments = list
+                    .children("SegmentURL")
+                    .map(|segment| match segment.attribute("media") {
+                        Some(media) => Ok(base.join(media)?),
+                        None => Err(invalid("segments with byte ranges are not supported")),
+                    })
+                    .collect::<Result<_>>()?;
+                (initialization, segments)
+            }
+            (None, Some(template)) => template.expand(&id, bandwidth, &base, period_duration)?,
+            // the whole representation is a single file
+            (None, None) => (None, vec![base]),
+        };
+
+        Ok(Self {
+            mime_type: inherited("mimeType"),
+            codecs: inherited("codecs"),
+            width: element.parse_attribute("width")?,
+            height: element.parse_attribute("height")?,
+            bandwidth,
+            initialization,
+            segments,
+            id,
+        })
+    }
+}
+
+/// The progress of [`Video::download_from_dash_manifest`](crate::Video::download_from_dash_manifest),
+/// which is reported after each segment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SegmentProgress {
+    /// The number of segments downloaded so far, including the initialization segment.
+    pub downloaded_segments: usize,
+    /// The number of all segments, including the initialization segment.
+    pub total_segments: usize,
+    /// The number of bytes written so far.
+    pub bytes: u64,
+}
+
+/// The [`Client`](reqwest::Client), headers and [`TransferStats`], the manifests and segments of a
+/// video are requested with.
+#[cfg(feature = "download")]
+#[derive(Clone, Debug)]
+pub(crate) struct SegmentClient {
+    pub(crate) client: reqwest::Client,
+    pub(crate) headers: reqwest::header::HeaderMap,
+    pub(crate) transfer_stats: Option<Arc<TransferStats>>,
+}
+
+#[cfg(feature = "download")]
+impl SegmentClient {
+    /// Requests `url`, and fails for error statuses.
+    pub(crate) async fn get(&self, url: &Url) -> Result<reqwest::Response> {
+        if let Some(ref transfer_stats) = self.transfer_stats {
+            transfer_stats.record_request();
+        }
+        Ok(self.client
+            .get(url.as_str())
+            .headers(self.headers.clone())
+            .send()
+            .await?
+            .error_for_status()?)
+    }
+
+    /// Requests the manifest, or playlist at `url`, which counts as metadata.
+    pub(crate) async fn get_text(&self, url: &Url) -> Result<String> {
+        let text = self.get(url).await?.text().await?;
+        if let Some(ref transfer_stats) = self.transfer_stats {
+            transfer_stats.record_metadata(text.len() as u64);
+        }
+        Ok(text)
+    }
+
+    /// Counts `bytes` of a segment, and fails, once the media cap of the [`TransferStats`] is
+    /// exceeded.
+    pub(crate) fn record_media(&self, bytes: usize) -> Result<()> {
+        match self.transfer_stats {
+            Some(ref transfer_stats) => transfer_stats.record_media(bytes as u64),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Downloads all segments of `representation`, and concatenates them in `<path>.part`, which is
+/// renamed to `path` once it's complete, or removed, if the download fails.
+#[cfg(feature = "download")]
+pub(crate) async fn download_representation(
+    client: &SegmentClient,
+    representation: &Representation,
+    path: &Path,
+    mut on_progress: impl FnMut(SegmentProgress),
+) -> Result<u64> {
+    log::debug!(
+        "download the {} segments of representation {} to {:?}",
+        representation.segment_count(), representation.id, path
+    );
+    let part_path = crate::stream::part_path(path);
+
+    let result = match write_segments(client, representation, &part_path, &mut on_progress).await {
+        Ok(bytes) => crate::stream::replace_file(&part_path, path)
+            .await
+            .map(|_| bytes)
+            .map_err(Error::from),
+        Err(e) => Err(e),
+    };
+
+    if let Err(ref e) = result {
+        log::error!("failed to download representation {}: {:?}", representation.id, e);
+        if let Err(e) = tokio::fs::remove_file(&part_path).await {
+            log::warn!("failed to remove the partial download {:?}: {}", part_path, e);
+        }
+    }
+    result
+}
+
+#[cfg(feature = "download")]
+async fn write_segments(
+    client: &SegmentClient,
+    representation: &Representation,
+    part_path: &Path,
+    on_progress: &mut impl FnMut(SegmentProgress),
+) -> Result<u64> {
+    use tokio::io::AsyncWriteExt;
+    use tokio_stream::StreamExt;
+
+    let mut file = tokio::fs::File::create(part_path).await?;
+    let mut progress = SegmentProgress {
+        downloaded_segments: 0,
+        total_segments: representation.segment_count(),
+        bytes: 0,
+    };
+
+    for url in representation.segment_urls() {
+        log::trace!("get segment: {}", url.as_str());
+        let mut body = client.get(url).await?.bytes_stream();
+
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk?;
+            client.record_media(chunk.len())?;
+            file.write_all(&chunk).await?;
+            progress.bytes += chunk.len() as u64;
+        }
+
+        progress.downloaded_segments += 1;
+        on_progress(progress);
+    }
+
+    file.flush().await?;
+    file.sync_all().await?;
+    Ok(progress.bytes)
+}
+
+/// A `SegmentTemplate`, whose missing attributes may be inherited from the template of a parent
+/// element.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct SegmentTemplate {
+    media: Option<String>,
+    initialization: Option<String>,
+    start_number: Option<u64>,
+    timescale: Option<u64>,
+    duration: Option<u64>,
+    timeline: Option<Vec<TimelineEntry>>,
+}
+
+/// An `S` element of a `SegmentTimeline`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TimelineEntry {
+    time: Option<u64>,
+    duration: u64,
+    /// The number of repetitions after the first segment. `-1` repeats the segment until the
+    /// next entry, or the end of the period.
+    repeat: i64,
+}
+
+impl SegmentTemplate {
+    /// The `SegmentTemplate` child of `parent`, combined with the inherited template `outer`.
+    fn from_parent(parent: &Element, outer: Option<&SegmentTemplate>) -> Result<Option<Self>> {
+        let element = match parent.child("SegmentTemplate") {
+            Some(element) => element,
+            None => return Ok(outer.cloned()),
+        };
+
+        let timeline = element
+            .child("SegmentTimeline")
+            .map(|timeline| timeline
+                .children("S")
+                .map(|s| Ok(TimelineEntry {
+                    time: s.parse_attribute("t")?,
+                    duration: s.parse_attribute("d")?.ok_or_else(|| invalid("a timeline entry has no duration"))?,
+                    repeat: s.parse_attribute("r")?.unwrap_or(0),
+                }))
+                .collect::<Result<Vec<_>>>()
+            )
+            .transpose()?;
+
+        let template = Self {
+            media: element.attribute("media").map(str::to_owned),
+            initialization: element.attribute("initialization").map(str::to_owned),
+            start_number: element.parse_attribute("startNumber")?,
+            timescale: element.parse_attribute("timescale")?,
+            duration: element.parse_attribute("duration")?,
+            timeline,
+        };
+
+        Ok(Some(match outer {
+            Some(outer) => Self {
+                media: template.media.or_else(|| outer.media.clone()),
+                initialization: template.initialization.or_else(|| outer.initialization.clone()),
+                start_number: template.start_number.or(outer.start_number),
+                timescale: template.timescale.or(outer.timescale),
+                duration: template.duration.or(outer.duration),
+                timeline: template.timeline.or_else(|| outer.timeline.clone()),
+            },
+            None => template,
+        }))
+    }
+
+    /// The urls of the initialization segment, and of all media segments.
+    fn expand(
+        &self,
+        id: &str,
+        bandwidth: Option<u64>,
+        base: &Url,
+        period_duration: Option<Duration>,
+    ) -> Result<(Option<Url>, Vec<Url>)> {
+        let media = self.media
+            .as_deref()
+            .ok_or_else(|| invalid("a `SegmentTemplate` has no media template"))?;
+        let initialization = self.initialization
+            .as_deref()
+            .map(|init| -> Result<Url> { Ok(base.join(&expand_template(init, id, bandwidth, None)?)?) })
+            .transpose()?;
+
+        let segments = self
+            .segment_times(period_duration)?
+            .into_iter()
+            .map(|number_and_time| -> Result<Url> {
+                Ok(base.join(&expand_template(media, id, bandwidth, Some(number_and_time))?)?)
+            })
+            .collect::<Result<_>>()?;
+
+        Ok((initialization, segments))
+    }
+
+    /// The number and the start time of each segment.
+    fn segment_times(&self, period_duration: Option<Duration>) -> Result<Vec<(u64, u64)>> {
+        let start_number = self.start_number.unwrap_or(1);
+        let timescale = self.timescale.unwrap_or(1);
+        let period_end = period_duration.map(|d| (d.as_secs_f64() * timescale as f64).round() as u64);
+        let mut segments = Vec::new();
+        let push = |segments: &mut Vec<(u64, u64)>, time: u64| {
+            if segments.len() as u64 >= MAX_SEGMENTS {
+                return Err(invalid("the manifest contains too many segments"));
+            }
+            segments.push((start_number + segments.len() as u64, time));
+            Ok(())
+        };
+
+        match (&self.timeline, self.duration) {
+            (Some(timeline), _) => {
+                let mut time = 0;
+                for (i, entry) in timeline.iter().enumerate() {
+                    if entry.duration == 0 {
+                        return Err(invalid("a timeline entry has a duration of zero"));
+                    }
+                    time = entry.time.unwrap_or(time);
+
+                    let count = match u64::try_from(entry.repeat) {
+                        Ok(repeat) => repeat + 1,
+                        Err(_) => {
+                            let end = timeline
+                                .get(i + 1)
+                                .and_then(|next| next.time)
+                                .or(period_end)
+                                .ok_or_else(|| invalid("a timeline entry repeats until the unknown end of the period"))?;
+                            end.saturating_sub(time).div_ceil(entry.duration)
+                        }
+                    };
+                    for _ in 0..count {
+                        push(&mut segments, time)?;
+                        time += entry.duration;
+                    }
+                }
+            }
+            (None, Some(duration)) if duration > 0 => {
+                let end = period_end
+                    .ok_or_else(|| invalid("a `SegmentTemplate` without timeline needs the duration of the period"))?;
+                for i in 0..end.div_ceil(duration) {
+                    push(&mut segments, i * duration)?;
+                }
+            }
+            (None, _) => return Err(invalid("a `SegmentTemplate` has neither a timeline nor a duration")),
+        }
+
+        Ok(segments)
+    }
+}
+
+/// Replaces the identifiers (i.e. `$Number%05d$`) of a segment template. The initialization
+/// template has no `number_and_time`.
+fn expand_template(
+    template: &str,
+    id: &str,
+    bandwidth: Option<u64>,
+    number_and_time: Option<(u64, u64)>,
+) -> Result<String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after
+            .find('$')
+            .ok_or_else(|| invalid(format!("unclosed identifier in the template `{}`", template)))?;
+        let identifier = &after[..end];
+        rest = &after[end + 1..];
+
+        let (name, format) = match identifier.find('%') {
+            Some(i) => (&identifier[..i], Some(&identifier[i..])),
+            None => (identifier, None),
+        };
+        let value = match (name, number_and_time) {
+            ("", _) => {
+                expanded.push('$');
+                continue;
+            }
+            ("RepresentationID", _) => {
+                expanded.push_str(id);
+                continue;
+            }
+            ("Bandwidth", _) => bandwidth,
+            ("Number", Some((number, _))) => Some(number),
+            ("Time", Some((_, time))) => Some(time),
+            _ => None,
+        };
+        let value = value.ok_or_else(|| invalid(format!("unsupported identifier `{}` in the template `{}`", name, template)))?;
+
+        // the only format tag is `%0[width]d`
+        let width = match format {
+            Some(format) => format
+                .strip_prefix('%')
+                .and_then(|format| format.strip_suffix('d'))
+                .and_then(|width| match width {
+                    "" => Some(0),
+                    width => width.parse::<usize>().ok(),
+                })
+                .ok_or_else(|| invalid(format!("unsupported format tag `{}`", format)))?,
+            None => 0,
+        };
+        expanded.push_str(&format!("{:0width$}", value, width = width));
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// `base` joined with the `BaseURL` child of `element`, if it has one.
+fn base_url(element: &Element, base: &Url) -> Result<Url> {
+    match element.child("BaseURL") {
+        Some(base_url) => Ok(base.join(base_url.text.trim())?),
+        None => Ok(base.clone()),
+    }
+}
+
+/// Parses an ISO 8601 duration, like `PT4H12M3.5S`, as used by MPD attributes.
+fn parse_duration(duration: &str) -> Option<Duration> {
+    let mut rest = duration.strip_prefix('P')?;
+    let mut seconds = 0.;
+    let mut in_time = false;
+
+    while !rest.is_empty() {
+        if let Some(time) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = time;
+            continue;
+        }
+
+        let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let value: f64 = rest[..end].parse().ok()?;
+        let unit = match (rest[end..].chars().next()?, in_time) {
+            ('D', false) => 24. * 60. * 60.,
+            ('H', true) => 60. * 60.,
+            ('M', true) => 60.,
+            ('S', true) => 1.,
+            _ => return None,
+        };
+        seconds += value * unit;
+        rest = &rest[end + 1..];
+    }
+
+    Some(Duration::from_secs_f64(seconds))
+}
+
+#[inline]
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::UnexpectedResponse(format!("invalid DASH manifest: {}", reason.into()).into())
+}
+
+impl Element {
+    fn parse_attribute<T: FromStr>(&self, name: &str) -> Result<Option<T>> {
+        self.attribute(name)
+            .map(|value| value
+                .parse()
+                .map_err(|_| invalid(format!("invalid attribute {}=\"{}\" of `{}`", name, value, self.name)))
+            )
+            .transpose()
+    }
+
+    fn duration_attribute(&self, name: &str) -> Result<Option<Duration>> {
+        self.attribute(name)
+            .map(|value| parse_duration(value)
+                .ok_or_else(|| invalid(format!("invalid duration {}=\"{}\"", name, value)))
+            )
+            .transpose()
+    }
+}
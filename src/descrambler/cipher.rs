@@ -1,9 +1,15 @@
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
 
 use crate::{Error, Result, TryCollect};
+use crate::metrics::Metrics;
+
+use super::throttling::{extract_function, JsFunction};
 
 pub(crate) type TransformerFn = (fn(&mut Vec<u8>, Option<isize>), &'static str);
 
@@ -11,6 +17,53 @@ static JS_FUNCTION_REGEX: Lazy<Regex> = Lazy::new(||
     Regex::new(r"\w+\.(\w+)\(\w,(\d+)\)").unwrap()
 );
 
+/// A parsed [`Cipher`], or the reason, why it could not be parsed.
+pub(crate) type CipherResult = std::result::Result<Cipher, String>;
+
+/// The number of player JavaScripts, whose ciphers are cached. YouTube changes its player only
+/// every few days, so the cache is cleared, once it's full, instead of evicting single entries.
+const CIPHER_CACHE_CAPACITY: usize = 8;
+
+/// The parsed [`Cipher`]s of all descramblers of the process, by the hash and the length of the
+/// player JavaScript. The JavaScript itself is the key, since a
+/// [`VideoDescrambler`](super::VideoDescrambler) does not know the url it was requested from, and
+/// hashing it is a lot cheaper than parsing it.
+static CIPHER_CACHE: Lazy<Mutex<CipherCache>> = Lazy::new(Mutex::default);
+
+type CipherCache = HashMap<(u64, usize), Arc<CipherResult>>;
+
+/// The [`Cipher`] of the player JavaScript `js`. If `cached`, the cipher is looked up in, and
+/// stored into, the process wide cache, so `js` is only parsed once.
+pub(crate) fn cipher_for(js: &str, cached: bool, metrics: Option<&Metrics>) -> Arc<CipherResult> {
+    let parse = || {
+        if let Some(metrics) = metrics {
+            metrics.record_cipher_parse();
+        }
+        Arc::new(Cipher::from_js(js).map_err(|err| err.to_string()))
+    };
+    if !cached {
+        return parse();
+    }
+
+    let key = {
+        let mut hasher = DefaultHasher::new();
+        js.hash(&mut hasher);
+        (hasher.finish(), js.len())
+    };
+    if let Some(cipher) = CIPHER_CACHE.lock().unwrap().get(&key) {
+        return Arc::clone(cipher);
+    }
+
+    // the lock is not held while parsing, so concurrent descramblers may parse `js` twice
+    let cipher = parse();
+    let mut cache = CIPHER_CACHE.lock().unwrap();
+    if cache.len() >= CIPHER_CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(key, Arc::clone(&cipher));
+    cipher
+}
+
 pub(crate) struct Cipher {
     transform_plan: Vec<String>,
     transform_map: HashMap<String, TransformerFn>,
@@ -21,7 +74,7 @@ impl Cipher {
         let transform_plan = get_transform_plan(js)?;
 
         let (var, _): (&str, &str) = transform_plan
-            .get(0)
+            .first()
             .ok_or_else(|| Error::UnexpectedResponse(
                 "the provided JavaScript has an empty transform-plan".into()
             ))?
@@ -131,6 +184,27 @@ impl Cipher {
     }
 }
 
+/// Extracts the signature function together with the transform-object it calls, so it can be
+/// evaluated by a [`JsRuntime`](super::JsRuntime).
+pub(crate) fn extract_signature_function(js: &str) -> Result<JsFunction> {
+    let name = get_initial_function_name(js)?;
+    let function = extract_function(js, name)?;
+
+    let transform_plan = get_transform_plan(js)?;
+    let (var, _): (&str, &str) = transform_plan
+        .first()
+        .and_then(|call| call.split('.').try_collect())
+        .ok_or_else(|| Error::UnexpectedResponse(
+            "could not extract the transform-object from the transform-plan".into()
+        ))?;
+    let transform_object = get_transform_object(js, var)?;
+
+    Ok(JsFunction {
+        name: name.to_owned(),
+        source: format!("var {var}={{{transform_object}}};\n{function}"),
+    })
+}
+
 fn get_transform_plan(js: &str) -> Result<Vec<String>> {
     let name = regex::escape(get_initial_function_name(js)?);
     let pattern = Regex::new(&format!(r#"{name}=function\(\w\)\{{[a-z=.(")]*;(.*);(?:.+)}}"#)).unwrap();
@@ -0,0 +1,51 @@
+use crate::Result;
+
+/// A JavaScript engine, that can evaluate the functions YouTube uses to scramble stream URLs.
+///
+/// By default, `rustube` reimplements the signature function, and the function transforming the
+/// `n` parameter (used for throttling) in Rust. If a [`JsRuntime`] is provided (see
+/// [`VideoFetcher::with_js_runtime`](crate::VideoFetcher::with_js_runtime)), it's used to
+/// descramble signatures, and to transform the `n` parameter of stream URLs, whenever the Rust
+/// implementations fail.
+///
+/// With the `boa` feature enabled, [`BoaRuntime`] provides an implementation backed by the
+/// [boa](https://boajs.dev) JavaScript engine.
+pub trait JsRuntime: Send + Sync {
+    /// Evaluates `js_source`, calls the function `fn_name` it defines with the string argument
+    /// `arg`, and returns the result.
+    ///
+    /// ### Errors
+    /// - When `js_source` cannot be evaluated.
+    /// - When calling `fn_name` fails or does not return a string.
+    fn eval_function(&self, js_source: &str, fn_name: &str, arg: &str) -> Result<String>;
+}
+
+/// A [`JsRuntime`] backed by the [boa](https://boajs.dev) JavaScript engine.
+///
+/// Each call to [`JsRuntime::eval_function`] uses a fresh context.
+#[cfg(feature = "boa")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BoaRuntime;
+
+#[cfg(feature = "boa")]
+impl JsRuntime for BoaRuntime {
+    fn eval_function(&self, js_source: &str, fn_name: &str, arg: &str) -> Result<String> {
+        use boa_engine::{Context, Source};
+
+        use crate::Error;
+
+        let script = format!("{}\n{}({});", js_source, fn_name, serde_json::to_string(arg)?);
+        let mut context = Context::default();
+
+        let value = context
+            .eval(Source::from_bytes(&script))
+            .map_err(|err| Error::Custom(format!("failed to evaluate `{fn_name}`: {err}").into()))?;
+
+        value
+            .as_string()
+            .map(|string| string.to_std_string_escaped())
+            .ok_or_else(|| Error::Custom(format!(
+                "expected `{fn_name}` to return a string, got: {}", value.display()
+            ).into()))
+    }
+}
@@ -1,28 +1,42 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use reqwest::Client;
 use url::Url;
 
-use cipher::Cipher;
+use cipher::{Cipher, CipherResult};
+use n_cipher::NCipher;
+#[cfg(feature = "boa")]
+pub use js_runtime::BoaRuntime;
+pub use js_runtime::JsRuntime;
+use throttling::JsFunction;
 
-use crate::{IdBuf, Stream, Video, VideoDetails, VideoInfo};
+use crate::{IdBuf, NoStreamsReason, Stream, Video, VideoDetails, VideoInfo};
 use crate::error::Error;
+use crate::fetcher::no_streaming_data;
+use crate::fetcher::UserAgent;
+use crate::metrics::{Metrics, Phase, PhaseTimer};
+use crate::transfer::TransferStats;
 use crate::video_info::player_response::streaming_data::RawFormat;
 use crate::video_info::player_response::streaming_data::StreamingData;
 
-mod cipher;
+pub(crate) mod cipher;
+mod js_runtime;
+pub(crate) mod n_cipher;
+pub(crate) mod throttling;
 
 /// A descrambler used to decrypt the data fetched by [`VideoFetcher`].
 ///
 /// You will probably rarely use this type directly, and use [`Video`] instead. 
-/// There's no public way of directly constructing a [`VideoDescrambler`]. The only way of getting
-/// one is by calling [`VideoFetcher::fetch`].
+/// Usually, a [`VideoDescrambler`] is obtained by calling [`VideoFetcher::fetch`]. To descramble
+/// stored video data without making any requests, use [`VideoFetcher::fetch_offline`], or
+/// [`VideoDescrambler::from_parts`].
 ///
 /// # Example
 /// ```no_run
 ///# use rustube::{VideoFetcher, Id, VideoDescrambler};
 ///# use url::Url;
-/// let url = Url::parse("https://youtube.com/watch?iv=5jlI4uzZGjU").unwrap();
+/// let url = Url::parse("https://youtube.com/watch?v=5jlI4uzZGjU").unwrap();
 /// 
 ///# tokio_test::block_on(async {
 /// let fetcher: VideoFetcher =  VideoFetcher::from_url(&url).unwrap();
@@ -65,6 +79,10 @@ mod cipher;
 /// correct signature, and convert them to [`Stream`]s. At the end of the day, `Stream`s are just
 /// `RawFormat`s with some extra information.
 /// 
+/// If a [`JsRuntime`] is configured, it's additionally used to transform the `n` parameter of each
+/// stream URL, which YouTube uses to throttle downloads, and to descramble signatures whenever the
+/// Rust implementation fails.
+///
 /// And that's it! We can now download a YouTube video like we would download any other
 /// video from the internet. The only difference is that the [`Stream`]s [`url`]
 /// will eventually expire.
@@ -75,6 +93,7 @@ mod cipher;
 /// [`VideoFetcher::fetch`]: crate::fetcher::VideoFetcher::fetch
 /// [`VideoFetcher`]: crate::fetcher::VideoFetcher
 /// [`VideoFetcher::fetch`]: crate::fetcher::VideoFetcher::fetch
+/// [`VideoFetcher::fetch_offline`]: crate::fetcher::VideoFetcher::fetch_offline
 #[derive(Clone, derive_more::Display, derivative::Derivative)]
 #[display(fmt = "VideoDescrambler({})", "video_info.player_response.video_details.video_id")]
 #[derivative(Debug, PartialEq, Eq)]
@@ -83,50 +102,143 @@ pub struct VideoDescrambler {
     #[derivative(Debug = "ignore", PartialEq = "ignore")]
     pub(crate) client: Client,
     pub(crate) js: String,
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub(crate) js_runtime: Option<Arc<dyn JsRuntime>>,
 }
 
 impl VideoDescrambler {
+    /// Creates a [`VideoDescrambler`] from an already fetched [`VideoInfo`] and the player
+    /// JavaScript `js` (`base.js`), i.e. to descramble videos, that were cached, without making
+    /// any requests. The [`Stream`]s of the [`Video`] use `client` to download.
+    ///
+    /// `js` may be empty for videos, whose formats are all pre-signed.
+    /// [`VideoFetcher::fetch_offline`](crate::VideoFetcher::fetch_offline) extracts the
+    /// [`VideoInfo`] from a stored watch page.
+    #[inline]
+    pub fn from_parts(video_info: VideoInfo, js: String, client: Client) -> Self {
+        Self {
+            video_info,
+            client,
+            js,
+            js_runtime: None,
+        }
+    }
+
     /// Descrambles the data fetched by YouTubeFetcher.
     /// For more information have a look at the [`Video`] documentation.
     ///
+    /// Formats, whose signature cannot be descrambled, are skipped. Their errors are available
+    /// via [`Video::descramble_errors`].
+    ///
+    /// This includes the case, where the signature cipher cannot be extracted from `base.js` at all
+    /// (i.e. after YouTube changed its player). Then, only the formats, that are already signed,
+    /// are kept, and all others fail with [`Error::CipherUnavailable`].
+    ///
+    /// Descrambling is synchronous, since it does not make any requests: `base.js` was already
+    /// requested by [`VideoFetcher::fetch`](crate::VideoFetcher::fetch), and the [`JsRuntime`]
+    /// evaluates the scrambling functions synchronously.
+    ///
     /// ### Errors
+    /// - [`Error::NoStreamingData`], when the video has no streams (yet), i.e. because it's still
+    ///   being processed, or it's a premiere, that did not start yet. The error contains the
+    ///   [`VideoInfo`], so the metadata of the video is still accessible.
+    /// - [`Error::NoStreams`], when all streams are DRM protected, or only available via server
+    ///   ABR streaming ([`NoStreamsReason::AllDrm`], and [`NoStreamsReason::AllSabrOnly`]).
     /// - When the streaming data of the video is incomplete.
-    /// - When descrambling the videos signatures fails.
+    /// - When descrambling the signatures of all formats fails ([`Error::AllFormatsFailed`]).
+    ///
+    /// The signature cipher, that's parsed from `base.js`, is cached for the whole process, so
+    /// descrambling many videos, that share the same player, parses it only once. Use
+    /// [`VideoDescrambler::descramble_uncached`] to bypass the cache.
+    #[inline]
+    pub fn descramble(self) -> crate::Result<Video> {
+        self.descramble_with(true)
+    }
+
+    /// Like [`VideoDescrambler::descramble`], but always parses the signature cipher from
+    /// `base.js`, instead of using the cached one.
+    #[inline]
+    pub fn descramble_uncached(self) -> crate::Result<Video> {
+        self.descramble_with(false)
+    }
+
     #[log_derive::logfn(ok = "Trace", err = "Error")]
     #[log_derive::logfn_inputs(Trace)]
-    pub fn descramble(mut self) -> crate::Result<Video> {
-        let streaming_data = self.video_info.player_response.streaming_data
-            .as_mut()
-            .ok_or_else(|| Error::Custom(
-                "VideoInfo contained no StreamingData, which is essential for downloading.".into()
-            ))?;
+    fn descramble_with(mut self, cache_cipher: bool) -> crate::Result<Video> {
+        let metrics = self.video_info.metrics.clone();
+        let timer = PhaseTimer::start(metrics.as_deref(), Phase::Descramble);
+        match NoStreamsReason::from_player_response(&self.video_info.player_response) {
+            None => {}
+            Some(reason @ (NoStreamsReason::AllDrm | NoStreamsReason::AllSabrOnly)) =>
+                return Err(Error::NoStreams(reason)),
+            Some(_) => return Err(no_streaming_data(self.video_info)),
+        }
+        let streaming_data = match self.video_info.player_response.streaming_data {
+            Some(ref mut streaming_data) => streaming_data,
+            None => return Err(no_streaming_data(self.video_info)),
+        };
 
         if let Some(ref adaptive_fmts_raw) = self.video_info.adaptive_fmts_raw {
             // fixme: this should probably be part of fetch.
             apply_descrambler_adaptive_fmts(streaming_data, adaptive_fmts_raw)?;
         }
 
-        apply_signature(streaming_data, &self.js)?;
+        let descramble_errors = apply_signature(
+            streaming_data,
+            &self.js,
+            self.js_runtime.as_deref(),
+            cache_cipher,
+            metrics.as_deref(),
+        );
+        if !descramble_errors.is_empty() && streaming_data.formats.is_empty() && streaming_data.adaptive_formats.is_empty() {
+            return Err(Error::AllFormatsFailed(descramble_errors));
+        }
+        apply_n_transform(streaming_data, &self.js, self.js_runtime.as_deref());
         let mut streams = Vec::new();
         Self::initialize_streams(
             streaming_data,
             &mut streams,
             &self.client,
             &self.video_info.player_response.video_details,
+            &metrics,
+            &self.video_info.transfer_stats,
+            &self.video_info.user_agent,
+            &self.video_info.extra_headers,
         );
+        drop(timer);
 
         Ok(Video {
             video_info: self.video_info,
             streams,
+            descramble_errors: Arc::new(descramble_errors),
+            js: Some(Arc::from(self.js)).filter(|js: &Arc<str>| !js.is_empty()),
         })
     }
 
+    /// Sets the [`JsRuntime`] used to evaluate the JavaScript functions, that scramble the stream
+    /// URLs. Without a runtime, the `n` parameter of the stream URLs is left untouched.
+    #[inline]
+    pub fn with_js_runtime(mut self, js_runtime: Arc<dyn JsRuntime>) -> Self {
+        self.js_runtime = Some(js_runtime);
+        self
+    }
+
     /// The [`VideoInfo`] of the video.
     #[inline]
     pub fn video_info(&self) -> &VideoInfo {
         &self.video_info
     }
 
+    /// Takes the [`VideoInfo`] of the video, without descrambling the streams.
+    ///
+    /// If only the [`VideoInfo`] is needed in the first place,
+    /// [`VideoFetcher::fetch_info`](crate::VideoFetcher::fetch_info) is cheaper, since it does not
+    /// request the player JavaScript.
+    #[inline]
+    pub fn into_video_info(self) -> VideoInfo {
+        self.video_info
+    }
+
     /// The [`VideoDetails`] of the video.
     #[inline]
     pub fn video_details(&self) -> &VideoDetails {
@@ -147,18 +259,27 @@ impl VideoDescrambler {
 
     /// Consumes all [`RawFormat`]s and constructs [`Stream`]s from them. 
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     fn initialize_streams(
         streaming_data: &mut StreamingData,
         streams: &mut Vec<Stream>,
         client: &Client,
         video_details: &Arc<VideoDetails>,
+        metrics: &Option<Arc<Metrics>>,
+        transfer_stats: &Option<Arc<TransferStats>>,
+        user_agent: &Option<UserAgent>,
+        extra_headers: &reqwest::header::HeaderMap,
     ) {
         for raw_format in streaming_data.formats.drain(..).chain(streaming_data.adaptive_formats.drain(..)) {
-            let stream = Stream::from_raw_format(
+            let mut stream = Stream::from_raw_format(
                 raw_format,
                 client.clone(),
                 Arc::clone(video_details),
             );
+            stream.metrics = metrics.clone();
+            stream.transfer_stats = transfer_stats.clone();
+            stream.user_agent = user_agent.clone();
+            stream.extra_headers = extra_headers.clone();
             streams.push(stream);
         }
     }
@@ -183,28 +304,178 @@ fn apply_descrambler_adaptive_fmts(streaming_data: &mut StreamingData, adaptive_
     Ok(())
 }
 
-/// Descrambles the signature of a video.
+/// Descrambles the signatures of all formats of a video.
+///
+/// Formats, whose signature cannot be descrambled, are removed from `streaming_data`, and
+/// returned together with the error, so a single broken format doesn't make the whole video
+/// unavailable.
+///
+/// If a [`JsRuntime`] is provided, it's used as a fallback, whenever the [`Cipher`] cannot be
+/// constructed, or fails to decrypt a signature.
 #[inline]
-fn apply_signature(streaming_data: &mut StreamingData, js: &str) -> crate::Result<()> {
-    let cipher = Cipher::from_js(js)?;
+fn apply_signature(
+    streaming_data: &mut StreamingData,
+    js: &str,
+    js_runtime: Option<&dyn JsRuntime>,
+    cache_cipher: bool,
+    metrics: Option<&Metrics>,
+) -> Vec<(u64, Error)> {
+    // the cipher is only constructed, once the first format requires it
+    let mut cipher = None;
+    let mut signature_function = None;
+    let mut failures = Vec::new();
+    let get_cipher = || {
+        let cipher = cipher::cipher_for(js, cache_cipher, metrics);
+        if let Err(ref err) = *cipher {
+            log::warn!("failed to construct the cipher, only pre-signed formats will be available: {}", err);
+        }
+        cipher
+    };
+
+    let mut descramble = |raw_format: &mut RawFormat| {
+        match descramble_format(raw_format, &mut cipher, get_cipher, js_runtime, js, &mut signature_function) {
+            Ok(()) => true,
+            Err(err) => {
+                log::warn!("failed to descramble the signature of itag {}, skipping it: {}", raw_format.itag, err);
+                failures.push((raw_format.itag, err));
+                false
+            }
+        }
+    };
+    streaming_data.formats.retain_mut(&mut descramble);
+    streaming_data.adaptive_formats.retain_mut(&mut descramble);
+
+    failures
+}
+
+/// Descrambles the signature of a single format, and appends it to the formats url.
+fn descramble_format(
+    raw_format: &mut RawFormat,
+    cipher: &mut Option<Arc<CipherResult>>,
+    get_cipher: impl FnOnce() -> Arc<CipherResult>,
+    js_runtime: Option<&dyn JsRuntime>,
+    js: &str,
+    signature_function: &mut Option<JsFunction>,
+) -> crate::Result<()> {
+    let url = &mut raw_format.signature_cipher.url;
+    let s = match raw_format.signature_cipher.s {
+        Some(ref mut s) => s,
+        None if url_already_contains_signature(url) => return Ok(()),
+        None => return Err(Error::UnexpectedResponse(
+            "RawFormat did not contain a signature (s), nor did the url".into()
+        ))
+    };
+
+    let cipher = match &**cipher.get_or_insert_with(get_cipher) {
+        Ok(cipher) => Some(cipher),
+        // the JsRuntime is used instead
+        Err(_) if js_runtime.is_some() => None,
+        Err(err) => return Err(Error::CipherUnavailable(err.clone())),
+    };
+
+    *s = decrypt_signature(s, cipher, js_runtime, js, signature_function)?;
+    url
+        .query_pairs_mut()
+        .append_pair("sig", s);
+
+    Ok(())
+}
+
+/// Decrypts a single signature using the [`Cipher`], and, if that fails, the [`JsRuntime`].
+/// At least one of both has to be provided.
+fn decrypt_signature(
+    s: &str,
+    cipher: Option<&Cipher>,
+    js_runtime: Option<&dyn JsRuntime>,
+    js: &str,
+    signature_function: &mut Option<JsFunction>,
+) -> crate::Result<String> {
+    if let Some(cipher) = cipher {
+        let mut decrypted = s.to_owned();
+        match cipher.decrypt_signature(&mut decrypted) {
+            Ok(()) => return Ok(decrypted),
+            Err(err) if js_runtime.is_none() => return Err(err),
+            Err(err) => log::warn!("failed to decrypt the signature, falling back to the JsRuntime: {}", err),
+        }
+    }
+
+    let js_runtime = js_runtime.expect("either the cipher or the JsRuntime must be provided");
+    let function = match signature_function {
+        Some(function) => function,
+        None => signature_function.insert(cipher::extract_signature_function(js)?),
+    };
+    js_runtime.eval_function(&function.source, &function.name, s)
+}
+
+/// Transforms the `n` parameter of all stream URLs using the [`NCipher`], and, if that fails,
+/// the [`JsRuntime`].
+///
+/// Failing to transform the `n` parameter is not fatal, since the streams can still be
+/// downloaded (just a lot slower). So in this case, the URLs are left untouched.
+fn apply_n_transform(streaming_data: &mut StreamingData, js: &str, js_runtime: Option<&dyn JsRuntime>) {
+    let mut n_cipher = None;
+    let mut n_function = None;
+    // most streams share the same `n` parameter
+    let mut transformed = HashMap::new();
 
     for raw_format in streaming_data.formats.iter_mut().chain(streaming_data.adaptive_formats.iter_mut()) {
         let url = &mut raw_format.signature_cipher.url;
-        let s = match raw_format.signature_cipher.s {
-            Some(ref mut s) => s,
-            None if url_already_contains_signature(url) => continue,
-            None => return Err(Error::UnexpectedResponse(
-                "RawFormat did not contain a signature (s), nor did the url".into()
-            ))
+        let n = match throttling::n_param(url) {
+            Some(n) => n,
+            None => continue,
         };
 
-        cipher.decrypt_signature(s)?;
-        url
-            .query_pairs_mut()
-            .append_pair("sig", s);
+        if !transformed.contains_key(&n) {
+            let n_cipher = n_cipher.get_or_insert_with(|| NCipher::from_js(js).map_err(|err| {
+                log::warn!("failed to translate the n-parameter function: {}", err);
+            }));
+            match transform_n(&n, n_cipher.as_ref().ok(), js_runtime, js, &mut n_function) {
+                Ok(new_n) => {
+                    transformed.insert(n.clone(), new_n);
+                }
+                Err(err) => {
+                    log::warn!("failed to transform the n-parameter `{}`: {}", n, err);
+                    return;
+                }
+            }
+        }
+
+        throttling::set_n_param(url, &transformed[&n]);
+        raw_format.signature_cipher.n_transformed = true;
     }
+}
 
-    Ok(())
+/// Transforms a single `n` parameter using the [`NCipher`], and, if that fails, the
+/// [`JsRuntime`].
+fn transform_n(
+    n: &str,
+    n_cipher: Option<&NCipher>,
+    js_runtime: Option<&dyn JsRuntime>,
+    js: &str,
+    n_function: &mut Option<JsFunction>,
+) -> crate::Result<String> {
+    if let Some(n_cipher) = n_cipher {
+        match n_cipher.transform(n) {
+            Ok(new_n) => return Ok(new_n),
+            Err(err) if js_runtime.is_none() => return Err(err),
+            Err(err) => log::warn!("failed to transform the n-parameter, falling back to the JsRuntime: {}", err),
+        }
+    }
+
+    let js_runtime = js_runtime.ok_or_else(|| Error::UnexpectedResponse(
+        "the n-parameter function could not be translated, and there's no JsRuntime".into()
+    ))?;
+    let function = match n_function {
+        Some(function) => function,
+        None => n_function.insert(throttling::extract_n_function(js)?),
+    };
+    match js_runtime.eval_function(&function.source, &function.name, n)? {
+        // YouTube signals errors by returning `enhanced_except_...`
+        new_n if new_n.starts_with("enhanced_except_") => Err(Error::UnexpectedResponse(format!(
+            "the n-parameter function failed: {new_n}"
+        ).into())),
+        new_n => Ok(new_n),
+    }
 }
 
 /// Checks whether or not the video url is already signed.
@@ -0,0 +1,455 @@
+//! A Rust translation of the function YouTube uses to transform the `n` parameter of stream URLs.
+//!
+//! The function splits `n` into an array, and declares a second array of helper functions,
+//! numbers, strings, and references to both arrays. A fixed plan of calls like
+//! `c[40](c[13],c[31])` then shuffles both arrays, so which helper is called depends on the
+//! calls before. The helpers are recognized by their source, and reimplemented in Rust.
+
+use std::convert::TryFrom;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{Error, Result};
+
+use super::throttling::{extract_n_function, skip_regex, skip_string, starts_regex};
+
+/// The array, `n` is split into.
+const N: usize = 0;
+/// The array of helper functions.
+const HELPERS: usize = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Value {
+    Number(i64),
+    String(String),
+    /// A reference to [`N`] or [`HELPERS`].
+    Array(usize),
+    Helper(Helper),
+    /// An element, that's not understood. Calling it, or passing it to a helper, fails.
+    Unknown(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Helper {
+    Reverse,
+    Push,
+    /// Swaps the first element and the one at the index.
+    Swap,
+    /// Removes the element at the index.
+    Remove,
+    /// Moves the last elements to the start.
+    Rotate,
+    /// Replaces each character with one of `alphabet`, depending on a key.
+    Cipher { alphabet: Vec<char>, counter: i64, offset: i64 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Arg {
+    /// `c[i]`, which is looked up, when the helper is called.
+    Element(usize),
+    Value(Value),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Step {
+    Call { helper: usize, args: Vec<Arg> },
+    Assign { index: usize, value: Value },
+}
+
+/// The `n` function of a player JavaScript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct NCipher {
+    elements: Vec<Value>,
+    steps: Vec<Step>,
+}
+
+impl NCipher {
+    pub(crate) fn from_js(js: &str) -> Result<Self> {
+        let function = extract_n_function(js)?;
+        Self::from_function(&function.source)
+    }
+
+    /// Parses the source of the `n` function (`var Xy=function(a){...};`).
+    fn from_function(source: &str) -> Result<Self> {
+        static HEADER: Lazy<Regex> = Lazy::new(|| Regex::new(
+            r#"^var [\w$]+=function\(([\w$]+)\)\{var ([\w$]+)=([\w$]+)\.split\((?:""|''|[\w$]+\.slice\(0,0\))\),([\w$]+)=\["#
+        ).unwrap());
+
+        let header = HEADER
+            .captures(source)
+            .filter(|header| header[1] == header[3])
+            .ok_or_else(|| unexpected("the n-parameter function does not start with splitting its argument"))?;
+        let names = Names { n: &header[2], helpers: &header[4] };
+
+        let array_start = header.get(0).unwrap().end();
+        let (raw_elements, array_len) = split_array(&source[array_start..])
+            .ok_or_else(|| unexpected("could not find the end of the n-parameter function array"))?;
+        let elements = raw_elements
+            .into_iter()
+            .map(|element| parse_element(element, &names))
+            .collect();
+        let steps = parse_steps(&source[array_start + array_len..], &names)?;
+
+        Ok(Self { elements, steps })
+    }
+
+    /// Transforms `n` by running the plan of the function.
+    pub(crate) fn transform(&self, n: &str) -> Result<String> {
+        let mut arrays = [
+            n.chars().map(|c| Value::String(c.to_string())).collect::<Vec<_>>(),
+            self.elements.clone(),
+        ];
+
+        for step in self.steps.iter() {
+            match step {
+                Step::Call { helper, args } => {
+                    let helper = match arrays[HELPERS].get(*helper) {
+                        Some(Value::Helper(helper)) => helper.clone(),
+                        element => return Err(unexpected(format!(
+                            "the n-parameter function calls `{element:?}` (at {helper}), which is not a known helper"
+                        ))),
+                    };
+                    let args: Vec<Value> = args
+                        .iter()
+                        .map(|arg| match arg {
+                            Arg::Element(i) => arrays[HELPERS]
+                                .get(*i)
+                                .cloned()
+                                .unwrap_or_else(|| Value::Unknown("undefined".to_owned())),
+                            Arg::Value(value) => value.clone(),
+                        })
+                        .collect();
+                    helper.call(&mut arrays, &args)?;
+                }
+                Step::Assign { index, value } => {
+                    let elements = &mut arrays[HELPERS];
+                    if *index >= elements.len() {
+                        elements.resize(*index + 1, Value::Unknown("undefined".to_owned()));
+                    }
+                    elements[*index] = value.clone();
+                }
+            }
+        }
+
+        arrays[N]
+            .iter()
+            .map(|value| match value {
+                Value::String(s) => Ok(s.clone()),
+                Value::Number(number) => Ok(number.to_string()),
+                value => Err(unexpected(format!("the n-parameter array contains `{value:?}`"))),
+            })
+            .collect()
+    }
+}
+
+impl Helper {
+    fn call(&self, arrays: &mut [Vec<Value>; 2], args: &[Value]) -> Result<()> {
+        let d = match args.first() {
+            Some(&Value::Array(d)) => d,
+            arg => return Err(unexpected(format!("expected an array as first argument, got `{arg:?}`"))),
+        };
+        let arg = args.get(1);
+
+        match self {
+            Helper::Reverse => arrays[d].reverse(),
+            Helper::Push => arrays[d].push(arg.cloned().unwrap_or_else(|| Value::Unknown("undefined".to_owned()))),
+            Helper::Swap => {
+                let e = index(arg, arrays[d].len())?;
+                arrays[d].swap(0, e);
+            }
+            Helper::Remove => {
+                let e = index(arg, arrays[d].len())?;
+                arrays[d].remove(e);
+            }
+            Helper::Rotate => {
+                let e = index(arg, arrays[d].len())?;
+                arrays[d].rotate_right(e);
+            }
+            Helper::Cipher { alphabet, counter, offset } => {
+                let mut key: Vec<String> = match arg {
+                    Some(Value::String(key)) => key.chars().map(String::from).collect(),
+                    arg => return Err(unexpected(format!("expected a string as cipher key, got `{arg:?}`"))),
+                };
+                let position = |c: Option<&String>| {
+                    let mut chars = c.map(|c| c.chars()).into_iter().flatten();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => alphabet.iter().position(|&a| a == c).map_or(-1, |p| p as i64),
+                        _ => -1,
+                    }
+                };
+
+                let mut counter = *counter;
+                for m in 0..arrays[d].len() {
+                    let l = match arrays[d][m] {
+                        Value::String(ref l) => l,
+                        ref l => return Err(unexpected(format!("expected a character to encrypt, got `{l:?}`"))),
+                    };
+                    let i = position(Some(l)) - position(key.get(m)) + m as i64 - offset + counter;
+                    counter -= 1;
+                    // the remainder of JavaScript has the sign of the dividend
+                    let c = usize::try_from(i % alphabet.len() as i64)
+                        .ok()
+                        .and_then(|i| alphabet.get(i))
+                        .ok_or_else(|| unexpected("the cipher of the n-parameter function left its alphabet"))?
+                        .to_string();
+                    key.push(c.clone());
+                    arrays[d][m] = Value::String(c);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The names of the two arrays in the function.
+struct Names<'a> {
+    n: &'a str,
+    helpers: &'a str,
+}
+
+impl Names<'_> {
+    /// The array, `name` refers to, if any.
+    fn array(&self, name: &str) -> Option<usize> {
+        match name {
+            name if name == self.n => Some(N),
+            name if name == self.helpers => Some(HELPERS),
+            _ => None,
+        }
+    }
+}
+
+/// Turns `e` into an index of an array of length `len`, like `(e%d.length+d.length)%d.length`.
+fn index(e: Option<&Value>, len: usize) -> Result<usize> {
+    match e {
+        Some(&Value::Number(e)) if len > 0 => Ok(e.rem_euclid(len as i64) as usize),
+        Some(Value::Number(_)) => Err(unexpected("the n-parameter function indexes an empty array")),
+        e => Err(unexpected(format!("expected a number as index, got `{e:?}`"))),
+    }
+}
+
+fn parse_element(element: &str, names: &Names<'_>) -> Value {
+    if let Some(array) = names.array(element) {
+        return Value::Array(array);
+    }
+    match parse_literal(element) {
+        Some(value) => value,
+        None if element.starts_with("function") => parse_helper(element)
+            .unwrap_or_else(|| Value::Unknown(element.to_owned())),
+        None => Value::Unknown(element.to_owned()),
+    }
+}
+
+/// Parses a number, or a string literal.
+fn parse_literal(literal: &str) -> Option<Value> {
+    if let Ok(number) = literal.parse::<i64>() {
+        return Some(Value::Number(number));
+    }
+    if literal.starts_with('"') {
+        return serde_json::from_str(literal).ok().map(Value::String);
+    }
+    literal
+        .strip_prefix('\'')?
+        .strip_suffix('\'')
+        .filter(|literal| !literal.contains(['\\', '\'']))
+        .map(|literal| Value::String(literal.to_owned()))
+}
+
+/// Recognizes a helper function by its source.
+fn parse_helper(source: &str) -> Option<Value> {
+    // `e=(e%d.length+d.length)%d.length;`, which turns the second argument into an index
+    const INDEX: &str = r"^function\([\w$]+,[\w$]+\)\{[\w$]+=\([\w$]+%[\w$]+\.length\+[\w$]+\.length\)%[\w$]+\.length;";
+    static HELPERS: Lazy<Vec<(Regex, Helper)>> = Lazy::new(|| vec![
+        // for(e=(e%d.length+d.length)%d.length;e--;)d.unshift(d.pop())
+        (r"^function\([\w$]+,[\w$]+\)\{for\([\w$]+=\([\w$]+%[\w$]+\.length\+[\w$]+\.length\)%[\w$]+\.length;[\w$]+--;\)[\w$]+\.unshift\([\w$]+\.pop\(\)\)\}$".to_owned(), Helper::Rotate),
+        (format!(r"{INDEX}[\w$]+\.splice\(-[\w$]+\)\.reverse\(\)\.forEach\(function\([\w$]+\)\{{[\w$]+\.unshift\([\w$]+\)\}}\)\}}$"), Helper::Rotate),
+        (r"^function\([\w$]+\)\{[\w$]+\.reverse\(\)\}$".to_owned(), Helper::Reverse),
+        // for(var e=d.length;e;)d.push(d.splice(--e,1)[0])
+        (r"^function\([\w$]+\)\{for\(var [\w$]+=[\w$]+\.length;[\w$]+;\)[\w$]+\.push\([\w$]+\.splice\(--[\w$]+,1\)\[0\]\)\}$".to_owned(), Helper::Reverse),
+        (r"^function\([\w$]+,[\w$]+\)\{[\w$]+\.push\([\w$]+\)\}$".to_owned(), Helper::Push),
+        (format!(r"{INDEX}[\w$]+\.splice\(0,1,[\w$]+\.splice\([\w$]+,1,[\w$]+\[0\]\)\[0\]\)\}}$"), Helper::Swap),
+        (format!(r"{INDEX}var [\w$]+=[\w$]+\[0\];[\w$]+\[0\]=[\w$]+\[[\w$]+\];[\w$]+\[[\w$]+\]=[\w$]+\}}$"), Helper::Swap),
+        (format!(r"{INDEX}[\w$]+\.splice\([\w$]+,1\)\}}$"), Helper::Remove),
+    ]
+        .into_iter()
+        .map(|(pattern, helper)| (Regex::new(&pattern).unwrap(), helper))
+        .collect());
+
+    if source.contains("case") {
+        return parse_cipher(source);
+    }
+
+    let (_, helper) = HELPERS.iter().find(|(pattern, _)| pattern.is_match(source))?;
+    Some(Value::Helper(helper.clone()))
+}
+
+/// Parses the cipher helper, which builds its alphabet in a loop, i.e.
+/// `for(var f=64,h=[];++f-h.length-32;){switch(f){case 58:f-=14;case 91:...default:h.push(String.fromCharCode(f))}}`,
+/// and then encrypts the characters with `h[(h.indexOf(l)-h.indexOf(this[m])+m-32+f--)%h.length]`.
+fn parse_cipher(source: &str) -> Option<Value> {
+    static ALPHABET: Lazy<Regex> = Lazy::new(|| Regex::new(
+        r"for\(var [\w$]+=(\d+),[\w$]+=\[\];\+\+[\w$]+-[\w$]+\.length-(\d+);\)\{?switch\([\w$]+\)\{([^{}]*)\}"
+    ).unwrap());
+    static SWITCH: Lazy<Regex> = Lazy::new(|| Regex::new(
+        r"case (\d+):|default:|[\w$]+-=(\d+)|[\w$]+\+=(\d+)|[\w$]+=(\d+)|continue|[\w$]+\.push\(String\.fromCharCode\([\w$]+\)\)"
+    ).unwrap());
+    static ENCRYPT: Lazy<Regex> = Lazy::new(|| Regex::new(
+        r"\.indexOf\([\w$]+\)-[\w$]+\.indexOf\(this\[[\w$]+\]\)\+[\w$]+-(\d+)\+[\w$]+--\)%[\w$]+\.length\]"
+    ).unwrap());
+
+    let alphabet = ALPHABET.captures(source)?;
+    let start: i64 = alphabet[1].parse().ok()?;
+    let limit: i64 = alphabet[2].parse().ok()?;
+    let switch = &alphabet[3];
+    if SWITCH.replace_all(switch, "").chars().any(|c| c != ';') {
+        return None;
+    }
+    let ops: Vec<SwitchOp> = SWITCH
+        .captures_iter(switch)
+        .map(|op| {
+            let number = |i| op.get(i).and_then(|n| n.as_str().parse::<i64>().ok());
+            match &op[0] {
+                "default:" => Some(SwitchOp::Default),
+                "continue" => Some(SwitchOp::Continue),
+                _ if op.get(1).is_some() => number(1).map(SwitchOp::Case),
+                _ if op.get(2).is_some() => number(2).map(SwitchOp::Sub),
+                _ if op.get(3).is_some() => number(3).map(SwitchOp::Add),
+                _ if op.get(4).is_some() => number(4).map(SwitchOp::Set),
+                _ => Some(SwitchOp::Push),
+            }
+        })
+        .collect::<Option<_>>()?;
+    let offset = ENCRYPT.captures(source)?[1].parse().ok()?;
+
+    let (alphabet, counter) = build_alphabet(start, limit, &ops)?;
+    Some(Value::Helper(Helper::Cipher { alphabet, counter, offset }))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SwitchOp {
+    Case(i64),
+    Default,
+    Sub(i64),
+    Add(i64),
+    Set(i64),
+    Continue,
+    Push,
+}
+
+/// Runs the loop, that builds the alphabet of the cipher, and returns the alphabet, and the
+/// final value of the counter.
+fn build_alphabet(mut f: i64, limit: i64, ops: &[SwitchOp]) -> Option<(Vec<char>, i64)> {
+    let mut alphabet = Vec::new();
+
+    // the loop has to terminate
+    for _ in 0..4096 {
+        f += 1;
+        if f - alphabet.len() as i64 - limit == 0 {
+            return Some((alphabet, f)).filter(|(alphabet, _)| !alphabet.is_empty());
+        }
+
+        let start = ops
+            .iter()
+            .position(|&op| op == SwitchOp::Case(f))
+            .or_else(|| ops.iter().position(|&op| op == SwitchOp::Default));
+        let start = match start {
+            Some(start) => start,
+            None => continue,
+        };
+        // without `break`, the cases fall through
+        for op in ops[start..].iter() {
+            match *op {
+                SwitchOp::Case(_) | SwitchOp::Default => {}
+                SwitchOp::Sub(n) => f -= n,
+                SwitchOp::Add(n) => f += n,
+                SwitchOp::Set(n) => f = n,
+                SwitchOp::Continue => break,
+                SwitchOp::Push => alphabet.push(char::from_u32(u32::try_from(f).ok()?)?),
+            }
+        }
+    }
+
+    None
+}
+
+/// Parses the calls of the helpers (`c[40](c[13],c[31])`), and assignments of the arrays to the
+/// elements (`c[30]=c`), that follow the array.
+fn parse_steps(code: &str, names: &Names<'_>) -> Result<Vec<Step>> {
+    let helpers = regex::escape(names.helpers);
+    let pattern = Regex::new(&format!(
+        r"(?:^|[^\w$]){helpers}\[(\d+)\]\(([^()]*)\)|(?:^|[^\w$]){helpers}\[(\d+)\]=([\w$]+)[;,}}]"
+    )).unwrap();
+
+    let steps: Vec<Step> = pattern
+        .captures_iter(code)
+        .map(|captures| match captures.get(1) {
+            Some(helper) => Step::Call {
+                helper: helper.as_str().parse().expect("only matches digits"),
+                args: captures[2]
+                    .split(',')
+                    .map(|arg| parse_arg(arg.trim(), names))
+                    .collect(),
+            },
+            None => Step::Assign {
+                index: captures[3].parse().expect("only matches digits"),
+                value: parse_element(&captures[4], names),
+            },
+        })
+        .collect();
+
+    match steps.is_empty() {
+        true => Err(unexpected("the n-parameter function does not call any helpers")),
+        false => Ok(steps),
+    }
+}
+
+fn parse_arg(arg: &str, names: &Names<'_>) -> Arg {
+    static ELEMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"^([\w$]+)\[(\d+)\]$").unwrap());
+
+    match ELEMENT.captures(arg) {
+        Some(element) if element[1] == *names.helpers => Arg::Element(
+            element[2].parse().expect("only matches digits")
+        ),
+        _ => Arg::Value(parse_element(arg, names)),
+    }
+}
+
+/// Splits the array literal, that `code` starts with (without the `[`), into its elements, and
+/// returns them, together with the length of the literal.
+///
+/// Strings and regular expression literals, which may contain brackets, are skipped.
+fn split_array(code: &str) -> Option<(Vec<&str>, usize)> {
+    let mut elements = Vec::new();
+    let mut element_start = 0;
+    let mut depth = 0usize;
+    let mut chars = code.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' | '\'' | '`' => skip_string(&mut chars, c)?,
+            '/' if code[element_start..i].trim().is_empty() || starts_regex(&code[..i]) => skip_regex(&mut chars)?,
+            '(' | '[' | '{' => depth += 1,
+            ']' if depth == 0 => {
+                let element = code[element_start..i].trim();
+                if !element.is_empty() || !elements.is_empty() {
+                    elements.push(element);
+                }
+                return Some((elements, i + 1));
+            }
+            ')' | ']' | '}' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                elements.push(code[element_start..i].trim());
+                element_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[inline]
+fn unexpected(reason: impl Into<String>) -> Error {
+    Error::UnexpectedResponse(reason.into().into())
+}
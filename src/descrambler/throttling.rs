@@ -0,0 +1,168 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use url::Url;
+
+use crate::{Error, Result};
+
+/// A JavaScript function extracted from the player JavaScript, together with everything it needs
+/// to be evaluated on its own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct JsFunction {
+    pub(crate) name: String,
+    pub(crate) source: String,
+}
+
+/// Extracts the function YouTube uses to transform the `n` parameter of stream URLs.
+/// Requests with an untransformed `n` parameter get throttled.
+pub(crate) fn extract_n_function(js: &str) -> Result<JsFunction> {
+    let name = n_function_name(js)?;
+    let source = extract_function(js, &name)?;
+    Ok(JsFunction { name, source })
+}
+
+/// Extracts the name of the `n` function.
+///
+/// The function is either called directly (`b=Xy(b)`), or via an array (`b=Xy[0](b)`).
+fn n_function_name(js: &str) -> Result<String> {
+    static PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(
+        r#"(?:\.get\("n"\)\)&&\(b=|b=String\.fromCharCode\(110\),c=a\.get\(b\)\)&&\(c=)(?P<name>[a-zA-Z0-9$]+)(?:\[(?P<idx>\d+)\])?\([a-zA-Z0-9]\)"#
+    ).unwrap());
+
+    let captures = PATTERN
+        .captures(js)
+        .ok_or_else(|| Error::UnexpectedResponse(
+            "could not find the name of the n-parameter function".into()
+        ))?;
+    let name = &captures["name"];
+
+    let idx = match captures.name("idx") {
+        Some(idx) => idx.as_str().parse::<usize>().expect("idx only matches digits"),
+        None => return Ok(name.to_owned()),
+    };
+
+    let array = Regex::new(&format!(r"var {}\s*=\s*\[(.+?)\]", regex::escape(name)))
+        .unwrap()
+        .captures(js)
+        .ok_or_else(|| Error::UnexpectedResponse(format!(
+            "could not find the n-parameter function array `{name}`"
+        ).into()))?;
+
+    array[1]
+        .split(',')
+        .nth(idx)
+        .map(|name| name.trim().to_owned())
+        .ok_or_else(|| Error::UnexpectedResponse(format!(
+            "the n-parameter function array `{name}` has no index {idx}"
+        ).into()))
+}
+
+/// Extracts the definition of the function `name` (`name=function(...){...}`) as a standalone
+/// JavaScript statement.
+pub(crate) fn extract_function(js: &str, name: &str) -> Result<String> {
+    let pattern = Regex::new(&format!(
+        r"(?:^|[^a-zA-Z0-9_$]){}\s*=\s*function\s*\(",
+        regex::escape(name)
+    )).unwrap();
+
+    let start = pattern
+        .find(js)
+        .ok_or_else(|| Error::UnexpectedResponse(format!(
+            "could not find the JavaScript function `{name}`"
+        ).into()))?
+        .end();
+    let body_start = start + js[start..]
+        .find('{')
+        .ok_or(Error::Internal("a function must have a body"))?;
+    let body_end = body_start + js_block_end(&js[body_start..])
+        .ok_or_else(|| Error::UnexpectedResponse(format!(
+            "could not find the end of the JavaScript function `{name}`"
+        ).into()))?;
+
+    Ok(format!("var {}=function({};", name, &js[start..=body_end]))
+}
+
+/// Finds the index of the `}`, that closes the block starting at the beginning of `code`.
+/// String and regular expression literals are skipped, so they may contain braces.
+fn js_block_end(code: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut chars = code.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' | '\'' | '`' => skip_string(&mut chars, c)?,
+            '/' if starts_regex(&code[..i]) => skip_regex(&mut chars)?,
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 { return Some(i); }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Whether a `/` after `code` starts a regular expression literal, and not a division, which is
+/// the case, if it's not preceded by a value.
+pub(crate) fn starts_regex(code: &str) -> bool {
+    match code.trim_end().chars().last() {
+        Some(c) => "(,=:[!&|?{};".contains(c),
+        None => true,
+    }
+}
+
+/// Skips a string literal, whose opening `delimiter` was already consumed.
+pub(crate) fn skip_string(chars: &mut impl Iterator<Item = (usize, char)>, delimiter: char) -> Option<()> {
+    let mut escaped = false;
+    for (_, c) in chars {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == delimiter => return Some(()),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Skips a regular expression literal, whose opening `/` was already consumed.
+pub(crate) fn skip_regex(chars: &mut impl Iterator<Item = (usize, char)>) -> Option<()> {
+    let mut escaped = false;
+    let mut in_class = false;
+    for (_, c) in chars {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => return Some(()),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The `n` parameter of a stream URL, if there is one.
+pub(crate) fn n_param(url: &Url) -> Option<String> {
+    url
+        .query_pairs()
+        .find(|(key, _)| key == "n")
+        .map(|(_, n)| n.into_owned())
+}
+
+/// Replaces the `n` parameter of a stream URL.
+pub(crate) fn set_n_param(url: &mut Url, n: &str) {
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| match key == "n" {
+            true => (key.into_owned(), n.to_owned()),
+            false => (key.into_owned(), value.into_owned()),
+        })
+        .collect();
+
+    url
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs);
+}
@@ -0,0 +1,275 @@
+#[cfg(not(feature = "callback"))]
+use std::marker::PhantomData;
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+#[cfg(feature = "callback")]
+use crate::Callback;
+use crate::{DownloadSummary, Error, FilenameTemplate, HashAlgorithm, Result, RetryPolicy, ShutdownHandle, Stream, StreamQuery, Video};
+use crate::stream::DEFAULT_WRITE_BUFFER_SIZE;
+use crate::text;
+
+/// Options for [`download`](crate::download), which cover the most common download scenarios.
+///
+/// By default, the stream with the best quality, that contains both a video and an audio track, is
+/// downloaded to `<VIDEO_ID>.<EXTENSION>` in the current working directory.
+///
+/// # Example
+/// ```no_run
+///# use rustube::SimpleDownloadOptions;
+///# #[tokio::main]
+///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let options = SimpleDownloadOptions::new()
+///     .target_dir("videos")
+///     .max_resolution(720);
+/// let path = rustube::download("https://youtube.com/watch?v=5jlI4uzZGjU", options).await?;
+///# Ok(())
+///# }
+/// ```
+#[derive(Debug, Default)]
+pub struct SimpleDownloadOptions<'a> {
+    target_dir: Option<PathBuf>,
+    max_resolution: Option<u32>,
+    audio_only: bool,
+    filename: Option<String>,
+    filename_template: Option<FilenameTemplate>,
+    filename_from_title: bool,
+    keep_partial: bool,
+    include_premium: bool,
+    respect_max_bitrate: bool,
+    hash: Option<HashAlgorithm>,
+    write_buffer_size: Option<usize>,
+    shutdown: Option<ShutdownHandle>,
+    retry_policy: Option<RetryPolicy>,
+    #[cfg(feature = "callback")]
+    on_progress: Option<Callback<'a>>,
+    #[cfg(not(feature = "callback"))]
+    _lifetime: PhantomData<&'a ()>,
+}
+
+impl<'a> SimpleDownloadOptions<'a> {
+    /// Creates the default options.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The directory to download the video to. [default: the current working directory]
+    #[inline]
+    pub fn target_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.target_dir = Some(dir.into());
+        self
+    }
+
+    /// Only considers streams with a height of at most `max_resolution` pixels (i.e. `720`).
+    /// Has no effect when combined with [`audio_only`](Self::audio_only).
+    #[inline]
+    pub fn max_resolution(mut self, max_resolution: u32) -> Self {
+        self.max_resolution = Some(max_resolution);
+        self
+    }
+
+    /// Downloads the best audio-only stream instead of a stream with both video and audio.
+    #[inline]
+    pub fn audio_only(mut self, audio_only: bool) -> Self {
+        self.audio_only = audio_only;
+        self
+    }
+
+    /// The filename of the downloaded file, including its extension.
+    /// [default: `<VIDEO_ID>.<EXTENSION>`]
+    #[inline]
+    pub fn filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Names the downloaded file after the `template`, i.e. `{channel}/{title} [{id}].{ext}`
+    /// (see [`FilenameTemplate`]). Directories of the rendered path are created inside the
+    /// [`target_dir`](Self::target_dir). Has no effect when combined with
+    /// [`filename`](Self::filename). [default: none]
+    #[inline]
+    pub fn filename_template(mut self, template: FilenameTemplate) -> Self {
+        self.filename_template = Some(template);
+        self
+    }
+
+    /// Names the downloaded file after the title of the video, i.e. `<TITLE>.<EXTENSION>`, instead
+    /// of its id. The title is made safe for all common file systems with
+    /// [`text::safe_title_for_filename`](crate::text::safe_title_for_filename). Titles, that
+    /// leave nothing usable, fall back to the id. Has no effect when combined with
+    /// [`filename`](Self::filename), or [`filename_template`](Self::filename_template).
+    /// [default: `false`]
+    #[inline]
+    pub fn filename_from_title(mut self, filename_from_title: bool) -> Self {
+        self.filename_from_title = filename_from_title;
+        self
+    }
+
+    /// Keeps the partially downloaded `<FILENAME>.part` file, if the download fails.
+    /// [default: `false`]
+    ///
+    /// Downloads are always written to `<FILENAME>.part` first, and only renamed to `<FILENAME>`
+    /// once they are complete.
+    #[inline]
+    pub fn keep_partial(mut self, keep_partial: bool) -> Self {
+        self.keep_partial = keep_partial;
+        self
+    }
+
+    /// Also considers streams, that are only available to YouTube Premium subscribers
+    /// (see [`Stream::is_premium_only`]). Downloading them fails without a Premium account.
+    /// [default: `false`]
+    #[inline]
+    pub fn include_premium(mut self, include_premium: bool) -> Self {
+        self.include_premium = include_premium;
+        self
+    }
+
+    /// Skips streams with a bitrate above the [`Video::suggested_max_bitrate`], if YouTube
+    /// suggested one. Has no effect when combined with [`audio_only`](Self::audio_only), since
+    /// audio streams stay far below the suggested bitrates. [default: `false`]
+    #[inline]
+    pub fn respect_max_bitrate(mut self, respect_max_bitrate: bool) -> Self {
+        self.respect_max_bitrate = respect_max_bitrate;
+        self
+    }
+
+    /// Computes the checksum of the downloaded file with `algorithm`, while it's written.
+    /// The digest is returned by [`Video::download_with_summary`].
+    #[inline]
+    pub fn hash(mut self, algorithm: HashAlgorithm) -> Self {
+        self.hash = Some(algorithm);
+        self
+    }
+
+    /// The number of bytes, that are collected in memory before they're written to the file.
+    /// `0` writes every network chunk right away.
+    /// [default: [`DEFAULT_WRITE_BUFFER_SIZE`](crate::stream::DEFAULT_WRITE_BUFFER_SIZE), 256 KiB]
+    ///
+    /// Progress callbacks are still notified about every network chunk, regardless of this size.
+    #[inline]
+    pub fn write_buffer_size(mut self, size: usize) -> Self {
+        self.write_buffer_size = Some(size);
+        self
+    }
+
+    /// A [`ShutdownHandle`], that stops the download, once it's triggered. The download then fails
+    /// with [`Error::Cancelled`] (see [`shutdown`](crate::shutdown)). [default: none]
+    #[inline]
+    pub fn shutdown(mut self, shutdown: ShutdownHandle) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// The [`RetryPolicy`], a download, that fails for transient reasons, is repeated with.
+    /// [default: none]
+    #[inline]
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// A [`Callback`], that gets notified about the download progress.
+    #[inline]
+    #[cfg(feature = "callback")]
+    pub fn on_progress(mut self, callback: Callback<'a>) -> Self {
+        self.on_progress = Some(callback);
+        self
+    }
+
+    /// Picks the [`Stream`] of `video`, that matches these options best.
+    pub(crate) fn select_stream<'v>(&self, video: &'v Video) -> Option<&'v Stream> {
+        if self.audio_only {
+            return video.best_audio();
+        }
+
+        let mut query = StreamQuery::new()
+            .progressive()
+            .include_premium(self.include_premium);
+        if let Some(max_resolution) = self.max_resolution {
+            query = query.max_resolution(u64::from(max_resolution));
+        }
+        if let Some(max_bitrate) = video.suggested_max_bitrate().filter(|_| self.respect_max_bitrate) {
+            query = query.max_bitrate(max_bitrate);
+        }
+        query.best(video)
+    }
+
+    /// Downloads `video` according to these options.
+    pub(crate) async fn download(self, video: &Video) -> Result<DownloadSummary> {
+        let stream = self
+            .select_stream(video)
+            .ok_or_else(|| Error::NoStreams(video.no_streams_reason()))?;
+        let mut stream = Cow::Borrowed(stream);
+        if let Some(ref shutdown) = self.shutdown {
+            stream = Cow::Owned(stream.into_owned().with_shutdown(shutdown.clone()));
+        }
+        if let Some(ref retry_policy) = self.retry_policy {
+            stream = Cow::Owned(stream.into_owned().with_retry_policy(retry_policy.clone()));
+        }
+
+        let filename = match (&self.filename, &self.filename_template) {
+            (Some(filename), _) => PathBuf::from(filename),
+            (None, Some(template)) => template.render(&video.video_info, &stream),
+            (None, None) => {
+                let title = match self.filename_from_title {
+                    true => text::safe_title_for_filename(video.title(), text::MAX_FILENAME_BYTES),
+                    false => String::new(),
+                };
+                match title.is_empty() {
+                    true => stream.file_name().into(),
+                    false => format!("{}.{}", title, stream.extension()).into(),
+                }
+            }
+        };
+        let path = match self.target_dir {
+            Some(ref dir) => dir.join(filename),
+            None => filename,
+        };
+        if self.filename_template.is_some() {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+        }
+
+        let keep_partial = self.keep_partial;
+        let hash = self.hash;
+        let write_buffer_size = self.write_buffer_size.unwrap_or(DEFAULT_WRITE_BUFFER_SIZE);
+
+        #[cfg(feature = "callback")]
+        if let Some(callback) = self.on_progress {
+            return stream
+                .wrap_callback(|channel| stream.internal_download_to(&path, channel, keep_partial, hash, write_buffer_size), callback)
+                .await;
+        }
+
+        stream.internal_download_to(&path, None, keep_partial, hash, write_buffer_size).await
+    }
+}
+
+impl Video {
+    /// Downloads the [`Stream`] of the video, that matches the [`SimpleDownloadOptions`] best.
+    /// Returns the path of the downloaded file.
+    ///
+    /// This is [`download`](crate::download) for a video, that was already fetched.
+    ///
+    /// ### Errors
+    /// - [`Error::NoStreams`], when no stream matches the options.
+    /// - When the download fails.
+    #[inline]
+    pub async fn download_with(&self, options: SimpleDownloadOptions<'_>) -> Result<PathBuf> {
+        options.download(self).await.map(|download| download.path)
+    }
+
+    /// Like [`Video::download_with`], but returns the size of the downloaded file, and its
+    /// digest, if [`SimpleDownloadOptions::hash`] is set.
+    ///
+    /// ### Errors
+    /// - [`Error::NoStreams`], when no stream matches the options.
+    /// - When the download fails.
+    #[inline]
+    pub async fn download_with_summary(&self, options: SimpleDownloadOptions<'_>) -> Result<DownloadSummary> {
+        options.download(self).await
+    }
+}
@@ -0,0 +1,152 @@
+//! Detecting streams, whose duration does not match the duration of the video.
+//!
+//! Occasionally, YouTube's encoder truncates a single format of a video, so one stream is a few
+//! seconds shorter than all others. Muxing such a stream with a complete one results in audio
+//! and video going out of sync. [`Video::duration_consistency`] compares the
+//! [`approx_duration_ms`](Stream::approx_duration_ms) of each stream against the
+//! [`length_seconds`](crate::VideoDetails::length_seconds) of the video, without making any
+//! requests.
+//!
+//! # Example
+//! ```no_run
+//!# use rustube::Video;
+//!# fn f(video: &Video) {
+//! let report = video.duration_consistency();
+//! for mismatch in &report.mismatches {
+//!     eprintln!("itag {} is {} ms off", mismatch.itag, mismatch.deviation_ms());
+//! }
+//!# }
+//! ```
+
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::{Error, Result, Stream, Video};
+
+/// The deviation, up to which a stream still counts as complete.
+///
+/// `length_seconds` is truncated to whole seconds, and the durations of the audio and the video
+/// formats of a video usually differ by a few hundred milliseconds.
+pub const DEFAULT_DURATION_TOLERANCE: Duration = Duration::from_millis(1500);
+
+/// A stream, whose duration deviates from the duration of the video by more than the tolerance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct DurationMismatch {
+    pub itag: u64,
+    /// The duration of the video.
+    pub expected_ms: u64,
+    /// The duration of the stream.
+    pub got_ms: u64,
+}
+
+impl DurationMismatch {
+    /// The absolute difference between the duration of the stream and the video.
+    #[inline]
+    pub fn deviation_ms(&self) -> u64 {
+        self.expected_ms.abs_diff(self.got_ms)
+    }
+
+    /// Whether the stream is shorter than the video, i.e. it was truncated.
+    #[inline]
+    pub fn is_truncated(&self) -> bool {
+        self.got_ms < self.expected_ms
+    }
+}
+
+impl From<DurationMismatch> for Error {
+    #[inline]
+    fn from(mismatch: DurationMismatch) -> Self {
+        Error::DurationMismatch {
+            itag: mismatch.itag,
+            expected_ms: mismatch.expected_ms,
+            got_ms: mismatch.got_ms,
+        }
+    }
+}
+
+/// The result of [`Video::duration_consistency`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct DurationReport {
+    /// The duration of the video, or `None`, if it's unknown, i.e. for live streams. In this case,
+    /// no stream is compared.
+    pub expected_ms: Option<u64>,
+    pub tolerance_ms: u64,
+    /// The streams, that deviate by more than the tolerance, in the order of the streams.
+    pub mismatches: Vec<DurationMismatch>,
+    /// The itags of the streams without an `approx_duration_ms`.
+    pub unknown: Vec<u64>,
+}
+
+impl DurationReport {
+    /// Compares the duration of each of the `streams` against `expected_ms`.
+    ///
+    /// A stream matches, if it deviates by at most `tolerance`.
+    pub fn new<'a>(
+        expected_ms: Option<u64>,
+        streams: impl IntoIterator<Item=&'a Stream>,
+        tolerance: Duration,
+    ) -> Self {
+        let tolerance_ms = u64::try_from(tolerance.as_millis()).unwrap_or(u64::MAX);
+        let mut report = Self { expected_ms, tolerance_ms, ..Self::default() };
+        let expected_ms = match expected_ms {
+            Some(expected_ms) => expected_ms,
+            None => return report,
+        };
+
+        for stream in streams {
+            match stream.approx_duration_ms {
+                Some(got_ms) if got_ms.abs_diff(expected_ms) > tolerance_ms => {
+                    report.mismatches.push(DurationMismatch { itag: stream.itag, expected_ms, got_ms });
+                }
+                Some(_) => {}
+                None => report.unknown.push(stream.itag),
+            }
+        }
+        report
+    }
+
+    /// Whether no stream deviates by more than the tolerance.
+    #[inline]
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// The mismatch of the stream with `itag`, if it deviates by more than the tolerance.
+    #[inline]
+    pub fn mismatch(&self, itag: u64) -> Option<&DurationMismatch> {
+        self.mismatches.iter().find(|mismatch| mismatch.itag == itag)
+    }
+
+    /// Fails with the first mismatch of the streams with one of the `itags`.
+    ///
+    /// ### Errors
+    /// - [`Error::DurationMismatch`], when one of the streams deviates by more than the tolerance.
+    pub fn ensure_consistent(&self, itags: &[u64]) -> Result<()> {
+        match self.mismatches.iter().find(|mismatch| itags.contains(&mismatch.itag)) {
+            Some(&mismatch) => Err(mismatch.into()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Video {
+    /// Compares the duration of each [`Stream`] against the duration of the video, with the
+    /// [`DEFAULT_DURATION_TOLERANCE`]. See the [module level documentation](crate::duration).
+    #[inline]
+    pub fn duration_consistency(&self) -> DurationReport {
+        self.duration_consistency_with_tolerance(DEFAULT_DURATION_TOLERANCE)
+    }
+
+    /// Compares the duration of each [`Stream`] against the duration of the video. Streams, that
+    /// deviate by more than `tolerance`, are reported as [`DurationMismatch`]es.
+    pub fn duration_consistency_with_tolerance(&self, tolerance: Duration) -> DurationReport {
+        let length_seconds = self.video_info.player_response.video_details.length_seconds;
+        let expected_ms = match length_seconds {
+            0 => None,
+            length_seconds => Some(length_seconds * 1000),
+        };
+        DurationReport::new(expected_ms, &self.streams, tolerance)
+    }
+}
@@ -6,11 +6,157 @@ pub enum Error {
     #[error("the provided raw Id does not match any known Id-pattern")]
     BadIdFormat,
     #[cfg(feature = "fetch")]
+    #[error("`{0}` is not a valid country code or language tag")]
+    BadLocaleFormat(String),
+    #[cfg(feature = "fetch")]
+    #[error("`{0}` is not a known quality, quality label, or audio quality")]
+    BadQualityFormat(String),
+    #[cfg(feature = "serialize")]
+    #[error("`{0}` is not a known output level")]
+    BadOutputLevel(String),
+    #[cfg(feature = "fetch")]
     #[error("the video you requested is unavailable:\n{0:#?}")]
     VideoUnavailable(Box<crate::video_info::player_response::playability_status::PlayabilityStatus>),
+    #[cfg(feature = "fetch")]
+    #[error(
+    "YouTube asks to sign in to confirm you're not a bot. This usually happens for datacenter IPs, \
+    or after too many requests. Try to use the cookies of a signed in account, a po_token, or \
+    a different IP address."
+    )]
+    BotCheck(Box<crate::video_info::player_response::playability_status::PlayabilityStatus>),
+    /// The video has no stream, that can be downloaded. The [`NoStreamsReason`] tells why.
+    #[cfg(feature = "fetch")]
+    #[error("the video contains no downloadable streams: {0}")]
+    NoStreams(NoStreamsReason),
+    /// The video is playable, but YouTube did not return any streams (yet), i.e. because it was
+    /// just uploaded and is still being processed, or it's a premiere, that did not start yet.
+    ///
+    /// Contains the [`VideoInfo`](crate::VideoInfo), so the metadata of the video is still
+    /// accessible.
+    #[cfg(feature = "fetch")]
+    #[error("the video has no downloadable streams yet{}", fmt_reason(.reason))]
+    NoStreamingData {
+        video_info: Box<crate::VideoInfo>,
+        reason: Option<String>,
+    },
     #[cfg(feature = "download")]
-    #[error("the video contains no streams")]
-    NoStreams,
+    #[error(
+    "the live stream {video_id} just ended, and is still being processed by YouTube, so its \
+    streams may not be available yet. Try again later. ({source})"
+    )]
+    PostLiveProcessing {
+        video_id: crate::IdBuf,
+        #[source]
+        source: Box<Error>,
+    },
+    /// YouTube refused to serve a stream (`403 Forbidden`). The `hint` names the most probable
+    /// cause, and is one of the constants in [`stream::forbidden`](crate::stream::forbidden).
+    #[cfg(feature = "download")]
+    #[error("YouTube refused to serve the stream {itag} of {video_id} (403 Forbidden): {hint}")]
+    Forbidden {
+        video_id: crate::IdBuf,
+        itag: u64,
+        has_sig: bool,
+        has_n_transformed: bool,
+        has_pot: bool,
+        url_ip: Option<std::net::IpAddr>,
+        expire: Option<chrono::DateTime<chrono::Utc>>,
+        /// The `googlevideo.com` host, that refused the request.
+        host: Option<String>,
+        hint: &'static str,
+    },
+    /// YouTube refused to serve a stream (`403 Forbidden`), since its URL expired at `expire`
+    /// (usually about six hours after the video was fetched). [`Stream::refresh`] gets a fresh
+    /// URL.
+    ///
+    /// [`Stream::refresh`]: crate::Stream::refresh
+    #[cfg(feature = "download")]
+    #[error("the URL of the stream {itag} of {video_id} expired at {expire}. Fetch the video again to get fresh URLs")]
+    UrlExpired {
+        video_id: crate::IdBuf,
+        itag: u64,
+        expire: chrono::DateTime<chrono::Utc>,
+    },
+    /// The download was stopped by its [`ShutdownHandle`](crate::ShutdownHandle), or one of its
+    /// tasks was cancelled, since the runtime shut down.
+    #[cfg(feature = "download")]
+    #[error("the download was cancelled")]
+    Cancelled,
+    /// YouTube rate limited a request to `endpoint` (`429 Too Many Requests`, or a
+    /// `403 Forbidden` saying so), and asked to wait for `retry_after`, if it said so. Stream
+    /// downloads are reported with the endpoint `videoplayback`.
+    #[cfg(feature = "fetch")]
+    #[error("YouTube rate limited the request to the {endpoint} endpoint{}", fmt_retry_after(.retry_after))]
+    RateLimited {
+        retry_after: Option<std::time::Duration>,
+        endpoint: &'static str,
+    },
+    /// More than `cap` media bytes were transferred with a [`TransferStats::limit`]ed collector.
+    ///
+    /// [`TransferStats::limit`]: crate::TransferStats::limit
+    #[cfg(feature = "fetch")]
+    #[error("the transfer quota of {cap} media bytes was exceeded ({media_bytes} bytes)")]
+    QuotaExceeded {
+        cap: u64,
+        media_bytes: u64,
+    },
+    /// YouTube returned a value, rustube does not know about, and the
+    /// [`ParseMode`](crate::fetcher::ParseMode) is strict.
+    #[cfg(feature = "fetch")]
+    #[error("`{value}` is not a known {type_name}")]
+    UnknownVariant {
+        type_name: &'static str,
+        value: String,
+    },
+    #[cfg(feature = "fetch")]
+    #[error("none of the innertube clients returned usable streams:{}", fmt_client_failures(.0))]
+    AllClientsFailed(Vec<(crate::fetcher::InnertubeClient, Error)>),
+
+    /// The signature cipher could not be extracted from `base.js`, i.e. since YouTube changed the
+    /// layout of its player. Only formats, that are already signed, are available.
+    #[cfg(feature = "descramble")]
+    #[error("the signature cipher could not be extracted from base.js: {0}")]
+    CipherUnavailable(String),
+    #[cfg(feature = "descramble")]
+    #[error("the signatures of all formats failed to descramble:{}", fmt_format_failures(.0))]
+    AllFormatsFailed(Vec<(u64, Error)>),
+    /// The duration of the stream `itag` deviates from the duration of the video, i.e. since it
+    /// was truncated (see [`Video::duration_consistency`](crate::Video::duration_consistency)).
+    #[cfg(feature = "descramble")]
+    #[error("the stream {itag} is {got_ms} ms long, but the video is {expected_ms} ms long")]
+    DurationMismatch {
+        itag: u64,
+        expected_ms: u64,
+        got_ms: u64,
+    },
+
+    /// Signing in failed, i.e. since the OAuth refresh token expired or was revoked (see
+    /// [`fetcher::auth`](crate::fetcher::auth)).
+    #[cfg(feature = "fetch")]
+    #[error("authentication failed: {0}")]
+    Authentication(Cow<'static, str>),
+
+    #[cfg(feature = "fetch")]
+    #[error("invalid cookie file, line {line}: {reason}")]
+    CookieFile {
+        line: usize,
+        reason: Cow<'static, str>,
+    },
+
+    #[cfg(feature = "descramble")]
+    #[error("invalid filename template {template:?}: {reason}")]
+    InvalidFilenameTemplate {
+        template: String,
+        reason: Cow<'static, str>,
+    },
+
+    #[cfg(feature = "remux")]
+    #[error("failed to remux the fragmented mp4 file: {0}")]
+    Remux(Cow<'static, str>),
+
+    #[cfg(feature = "package")]
+    #[error("failed to package the video: {0}")]
+    Package(Cow<'static, str>),
 
     #[error(transparent)]
     #[cfg(feature = "fetch")]
@@ -42,3 +188,186 @@ pub enum Error {
     #[cfg(feature = "callback")]
     ChannelClosed,
 }
+
+impl Error {
+    /// Whether YouTube asked to sign in to confirm you're not a bot, either directly, or for
+    /// one of the [`InnertubeClient`](crate::InnertubeClient)s, that were tried.
+    #[cfg(feature = "fetch")]
+    pub fn is_bot_check(&self) -> bool {
+        match self {
+            Error::BotCheck(_) => true,
+            Error::AllClientsFailed(failures) => failures.iter().any(|(_, err)| err.is_bot_check()),
+            _ => false,
+        }
+    }
+
+    /// Whether repeating the request, that failed with this error, might succeed, i.e. since it
+    /// was [rate limited](Error::RateLimited), the server failed, or the connection was lost.
+    ///
+    /// [`Error::Forbidden`] and [`Error::UrlExpired`] are not retryable as they are, since the
+    /// stream needs a fresh URL first (see [`Stream::refresh`](crate::Stream::refresh)).
+    #[cfg(feature = "fetch")]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::RateLimited { .. } => true,
+            // without a status, the connection failed, timed out, or was closed
+            Error::Request(e) => e.status().is_none_or(|status| status.is_server_error()),
+            _ => false,
+        }
+    }
+
+    /// Why the video has no downloadable streams, if that's what the error is about.
+    ///
+    /// Besides [`Error::NoStreams`], this classifies [`Error::NoStreamingData`],
+    /// [`Error::AllFormatsFailed`], and [`Error::AllClientsFailed`], if all clients failed for
+    /// such a reason (the reason of the first client is returned).
+    #[cfg(feature = "fetch")]
+    pub fn no_streams_reason(&self) -> Option<NoStreamsReason> {
+        match self {
+            Error::NoStreams(reason) => Some(*reason),
+            Error::NoStreamingData { video_info, .. } => Some(
+                NoStreamsReason::from_player_response(&video_info.player_response)
+                    .unwrap_or(NoStreamsReason::EmptyStreamingData)
+            ),
+            #[cfg(feature = "descramble")]
+            Error::AllFormatsFailed(_) => Some(NoStreamsReason::AllDescrambleFailed),
+            Error::AllClientsFailed(failures) => failures
+                .iter()
+                .map(|(_, err)| err.no_streams_reason())
+                .collect::<Option<Vec<_>>>()
+                .and_then(|reasons| reasons.first().copied()),
+            _ => None,
+        }
+    }
+}
+
+/// Joins the next task of `tasks`, like [`JoinSet::join_next`](tokio::task::JoinSet::join_next).
+///
+/// The panic of a task is resumed on the current thread, and a cancelled task is reported as
+/// [`Error::Cancelled`].
+#[cfg(feature = "download")]
+pub(crate) async fn join_next<T: 'static>(tasks: &mut tokio::task::JoinSet<T>) -> Option<Result<T, Error>> {
+    match tasks.join_next().await? {
+        Ok(output) => Some(Ok(output)),
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(_) => Some(Err(Error::Cancelled)),
+    }
+}
+
+/// Why a video has no downloadable streams (see [`Error::NoStreams`]).
+#[cfg(feature = "fetch")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NoStreamsReason {
+    /// All streams are DRM protected, i.e. for movies and some music videos.
+    AllDrm,
+    /// All formats lack a URL, and can only be streamed with YouTube's server ABR (SABR)
+    /// protocol, which rustube does not support (see
+    /// [`StreamingData::server_abr_streaming_url`](crate::video_info::player_response::streaming_data::StreamingData::server_abr_streaming_url)).
+    AllSabrOnly,
+    /// The signatures of all formats failed to descramble.
+    AllDescrambleFailed,
+    /// The video is a premiere or live stream, that did not start yet.
+    LiveNotStarted,
+    /// YouTube did not return any formats, i.e. since the video is still being processed.
+    EmptyStreamingData,
+    /// The video has streams, but none of them matches the selection.
+    NoMatchingStream,
+}
+
+#[cfg(feature = "fetch")]
+impl NoStreamsReason {
+    /// What the user can do about it.
+    pub fn advice(&self) -> &'static str {
+        match self {
+            Self::AllDrm => "DRM protected streams can't be downloaded. Other innertube clients \
+                may still return unprotected streams.",
+            Self::AllSabrOnly => "YouTube only offered streams via its SABR protocol, which is not \
+                supported. Try a different innertube client, i.e. the Android client, or pass a po_token.",
+            Self::AllDescrambleFailed => "YouTube probably changed its player JavaScript. Try to \
+                use a JsRuntime, and if that doesn't help, please report this.",
+            Self::LiveNotStarted => "Try again once the premiere or live stream started.",
+            Self::EmptyStreamingData => "The video may still be processing. Try again later.",
+            Self::NoMatchingStream => "Relax the stream filters, i.e. the quality or the codecs.",
+        }
+    }
+
+    /// Classifies the streaming data of `player_response`. Returns `None`, if it contains
+    /// formats, that may be downloadable, or an HLS manifest, i.e. of a live stream.
+    pub(crate) fn from_player_response(
+        player_response: &crate::video_info::player_response::PlayerResponse,
+    ) -> Option<Self> {
+        use crate::video_info::player_response::video_details::LiveStatus;
+
+        let (mut formats, has_sabr_only, has_hls_manifest) = match player_response.streaming_data {
+            Some(ref streaming_data) => (
+                streaming_data.formats.iter().chain(&streaming_data.adaptive_formats).peekable(),
+                !streaming_data.sabr_only_itags.is_empty(),
+                streaming_data.hls_manifest_url.is_some(),
+            ),
+            None if player_response.live_status() == LiveStatus::UpcomingPremiere => return Some(Self::LiveNotStarted),
+            None => return Some(Self::EmptyStreamingData),
+        };
+
+        if formats.peek().is_some() {
+            return match formats.all(|format| !format.drm_families.is_empty()) {
+                true => Some(Self::AllDrm),
+                false => None,
+            };
+        }
+        if has_hls_manifest {
+            // live streams only have an HLS manifest (see `Video::hls_playlist`)
+            return None;
+        }
+        match (has_sabr_only, player_response.live_status()) {
+            (true, _) => Some(Self::AllSabrOnly),
+            (false, LiveStatus::UpcomingPremiere) => Some(Self::LiveNotStarted),
+            (false, _) => Some(Self::EmptyStreamingData),
+        }
+    }
+}
+
+#[cfg(feature = "fetch")]
+impl core::fmt::Display for NoStreamsReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::AllDrm => "all streams are DRM protected",
+            Self::AllSabrOnly => "all streams are only available via server ABR streaming (SABR)",
+            Self::AllDescrambleFailed => "the signatures of all streams failed to descramble",
+            Self::LiveNotStarted => "the premiere or live stream did not start yet",
+            Self::EmptyStreamingData => "YouTube did not return any streams",
+            Self::NoMatchingStream => "no stream matches the selection",
+        })
+    }
+}
+
+#[cfg(feature = "fetch")]
+fn fmt_reason(reason: &Option<String>) -> String {
+    match reason {
+        Some(reason) => format!(": {reason}"),
+        None => String::new(),
+    }
+}
+
+#[cfg(feature = "fetch")]
+fn fmt_retry_after(retry_after: &Option<std::time::Duration>) -> String {
+    match retry_after {
+        Some(retry_after) => format!(", retry after {}s", retry_after.as_secs()),
+        None => String::new(),
+    }
+}
+
+#[cfg(feature = "descramble")]
+fn fmt_format_failures(failures: &[(u64, Error)]) -> String {
+    failures
+        .iter()
+        .map(|(itag, err)| format!("\n- itag {itag}: {err}"))
+        .collect()
+}
+
+#[cfg(feature = "fetch")]
+fn fmt_client_failures(failures: &[(crate::fetcher::InnertubeClient, Error)]) -> String {
+    failures
+        .iter()
+        .map(|(client, err)| format!("\n- {client}: {err}"))
+        .collect()
+}
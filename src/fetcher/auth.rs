@@ -0,0 +1,342 @@
+//! Authentication for videos, that require being signed in, i.e. members-only or age restricted
+//! videos.
+//!
+//! There are two ways to authenticate:
+//! - the cookies of a signed in account, exported from a browser into a Netscape `cookies.txt`
+//!   file (see [`cookies_from_file`](super::cookies_from_file))
+//! - the OAuth device flow of YouTube's TV app ([`OAuth`]), which asks to enter a code at
+//!   `https://www.google.com/device` once, and stores the refresh token afterwards
+//!
+//! Both are combined in an [`AuthConfig`], which is passed to
+//! [`VideoFetcher::from_id_authenticated`](super::VideoFetcher::from_id_authenticated).
+//!
+//! # Example
+//! ```no_run
+//!# use rustube::{Id, VideoFetcher};
+//!# use rustube::fetcher::auth::{AuthConfig, OAuth};
+//!# #[tokio::main]
+//!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let auth = AuthConfig::new()
+//!     .cookies("cookies.txt")
+//!     .oauth(OAuth::new("oauth_token.json"));
+//!
+//! let id = Id::from_str("5jlI4uzZGjU")?.into_owned();
+//! let video = VideoFetcher::from_id_authenticated(id, auth)?
+//!     .fetch()
+//!     .await?
+//!     .descramble()?;
+//!# Ok(())
+//!# }
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::{Error, Result};
+
+/// The OAuth client of YouTube's TV app.
+const CLIENT_ID: &str = "861556708454-d6dlm3lh05idd8npek18k6be8ba3oc68.apps.googleusercontent.com";
+const CLIENT_SECRET: &str = "SboVhoG9s0rNafixCSGGKXAT";
+const SCOPE: &str = "https://www.googleapis.com/auth/youtube";
+const OAUTH_URL: &str = "https://oauth2.googleapis.com/";
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+/// The time before the expiration of the access token, at which it is refreshed already.
+const EXPIRATION_MARGIN: Duration = Duration::from_secs(60);
+
+/// How [`VideoFetcher::from_id_authenticated`](super::VideoFetcher::from_id_authenticated)
+/// authenticates (see the [module docs](self)).
+#[derive(Clone, Debug, Default)]
+pub struct AuthConfig {
+    pub(crate) cookies: Option<PathBuf>,
+    pub(crate) oauth: Option<OAuth>,
+}
+
+impl AuthConfig {
+    /// Creates a config, that does not authenticate at all.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the cookies of a signed in account from a Netscape `cookies.txt` file (see
+    /// [`cookies_from_file`](super::cookies_from_file)).
+    #[inline]
+    pub fn cookies(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cookies = Some(path.into());
+        self
+    }
+
+    /// Authenticates the requests to YouTube's internal API with an OAuth access token.
+    #[inline]
+    pub fn oauth(mut self, oauth: OAuth) -> Self {
+        self.oauth = Some(oauth);
+        self
+    }
+}
+
+/// The code, the user has to enter at [`DeviceCode::verification_url`] to sign in.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct DeviceCode {
+    pub user_code: String,
+    pub verification_url: String,
+    /// The number of seconds, after which the code expires.
+    pub expires_in: u64,
+}
+
+type DeviceCodeHandler = Arc<dyn Fn(&DeviceCode) + Send + Sync>;
+
+/// The OAuth device flow of YouTube's TV app.
+///
+/// The first time an access token is needed, the device flow is started: the
+/// [`DeviceCode`] is passed to the handler set with [`OAuth::on_device_code`] (or logged as
+/// warning without one), and rustube waits until the user signed in. The tokens are then stored
+/// in the token file, and refreshed when the access token expires.
+///
+/// When the refresh token expired or was revoked, requests fail with
+/// [`Error::Authentication`]. Deleting the token file starts the device flow again.
+///
+/// The clones of an [`OAuth`] share their tokens.
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug)]
+pub struct OAuth {
+    token_path: Option<PathBuf>,
+    oauth_url: Url,
+    #[derivative(Debug = "ignore")]
+    on_device_code: Option<DeviceCodeHandler>,
+    #[derivative(Debug = "ignore")]
+    token: Arc<Mutex<Option<OAuthToken>>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct OAuthToken {
+    access_token: String,
+    refresh_token: String,
+    /// The unix timestamp in seconds, at which the access token expires.
+    expires_at: u64,
+}
+
+/// The response of the token endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TokenResponse {
+    Token {
+        access_token: String,
+        refresh_token: Option<String>,
+        expires_in: u64,
+    },
+    Error {
+        error: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    #[serde(flatten)]
+    code: DeviceCode,
+    interval: u64,
+}
+
+impl OAuth {
+    /// Creates an [`OAuth`], that stores its tokens in `token_path`. The file is created, if it
+    /// doesn't exist, and on Unix only the current user can read it (mode `0600`).
+    #[inline]
+    pub fn new(token_path: impl Into<PathBuf>) -> Self {
+        Self {
+            token_path: Some(token_path.into()),
+            ..Self::in_memory()
+        }
+    }
+
+    /// Creates an [`OAuth`], that keeps its tokens in memory only, so the device flow has to be
+    /// completed on every run.
+    #[inline]
+    pub fn in_memory() -> Self {
+        Self {
+            token_path: None,
+            oauth_url: Url::parse(OAUTH_URL).unwrap(),
+            on_device_code: None,
+            token: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Sets the handler, that shows the [`DeviceCode`] to the user, when the device flow starts.
+    #[inline]
+    pub fn on_device_code(mut self, f: impl Fn(&DeviceCode) + Send + Sync + 'static) -> Self {
+        self.on_device_code = Some(Arc::new(f));
+        self
+    }
+
+    /// Sets the URL of the OAuth endpoints.
+    /// This is mostly useful for testing against a local server.
+    ///
+    /// [default: `https://oauth2.googleapis.com/`]
+    #[inline]
+    pub fn with_oauth_url(mut self, oauth_url: Url) -> Self {
+        self.oauth_url = oauth_url;
+        self
+    }
+
+    /// The path of the token file, if there is one.
+    #[inline]
+    pub fn token_path(&self) -> Option<&Path> {
+        self.token_path.as_deref()
+    }
+
+    /// A valid access token. The token is loaded from the token file, refreshed, or requested
+    /// with the device flow, if necessary.
+    /// ### Errors
+    /// - When the token file cannot be read or written.
+    /// - When the refresh token expired or was revoked, or the user did not sign in in time
+    ///   ([`Error::Authentication`]).
+    /// - When a request fails.
+    pub async fn access_token(&self, client: &Client) -> Result<String> {
+        let mut token = self.token.lock().await;
+
+        if token.is_none() {
+            *token = self.load().await?;
+        }
+        let new_token = match *token {
+            Some(ref token) if token.expires_at > now() + EXPIRATION_MARGIN.as_secs() => None,
+            Some(ref token) => Some(self.refresh(client, token).await?),
+            None => Some(self.device_flow(client).await?),
+        };
+        if let Some(new_token) = new_token {
+            self.save(&new_token).await?;
+            *token = Some(new_token);
+        }
+
+        Ok(token.as_ref().unwrap().access_token.clone())
+    }
+
+    async fn load(&self) -> Result<Option<OAuthToken>> {
+        let path = match self.token_path {
+            Some(ref path) => path,
+            None => return Ok(None),
+        };
+        match tokio::fs::read(path).await {
+            Ok(token) => Ok(Some(serde_json::from_slice(&token)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes `token` to the token file, which only the current user can read, since the refresh
+    /// token grants access to the account.
+    async fn save(&self, token: &OAuthToken) -> Result<()> {
+        let path = match self.token_path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+
+        // written to a temporary file first, so the token file is never left half written
+        let tmp_path = path.with_extension("tmp");
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&tmp_path).await?;
+        file.write_all(&serde_json::to_vec(token)?).await?;
+        file.sync_all().await?;
+        tokio::fs::rename(&tmp_path, path).await?;
+        Ok(())
+    }
+
+    async fn refresh(&self, client: &Client, token: &OAuthToken) -> Result<OAuthToken> {
+        log::debug!("refreshing the OAuth access token");
+        let response = self.request_token(client, &[
+            ("client_id", CLIENT_ID),
+            ("client_secret", CLIENT_SECRET),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", &token.refresh_token),
+        ]).await?;
+
+        match response {
+            TokenResponse::Token { access_token, refresh_token, expires_in } => Ok(OAuthToken {
+                access_token,
+                refresh_token: refresh_token.unwrap_or_else(|| token.refresh_token.clone()),
+                expires_at: now() + expires_in,
+            }),
+            TokenResponse::Error { error } if error == "invalid_grant" => Err(Error::Authentication(
+                "the refresh token expired or was revoked, delete the token file to sign in again".into()
+            )),
+            TokenResponse::Error { error } => Err(Error::Authentication(
+                format!("refreshing the access token failed: {error}").into()
+            )),
+        }
+    }
+
+    async fn device_flow(&self, client: &Client) -> Result<OAuthToken> {
+        let response: DeviceCodeResponse = client
+            .post(self.oauth_url.join("device/code")?.as_str())
+            .form(&[("client_id", CLIENT_ID), ("scope", SCOPE)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        match self.on_device_code {
+            Some(ref on_device_code) => on_device_code(&response.code),
+            None => log::warn!(
+                "to sign in, go to {} and enter the code {}",
+                response.code.verification_url, response.code.user_code,
+            ),
+        }
+
+        let mut interval = Duration::from_secs(response.interval);
+        loop {
+            tokio::time::sleep(interval).await;
+            let token = self.request_token(client, &[
+                ("client_id", CLIENT_ID),
+                ("client_secret", CLIENT_SECRET),
+                ("grant_type", DEVICE_CODE_GRANT_TYPE),
+                ("device_code", &response.device_code),
+            ]).await?;
+
+            match token {
+                TokenResponse::Token { access_token, refresh_token: Some(refresh_token), expires_in } => {
+                    log::info!("signed in with OAuth");
+                    return Ok(OAuthToken { access_token, refresh_token, expires_at: now() + expires_in });
+                }
+                TokenResponse::Token { refresh_token: None, .. } => return Err(Error::UnexpectedResponse(
+                    "the token endpoint did not return a refresh token".into()
+                )),
+                TokenResponse::Error { error } if error == "authorization_pending" => {}
+                TokenResponse::Error { error } if error == "slow_down" => interval += Duration::from_secs(5),
+                TokenResponse::Error { error } => return Err(Error::Authentication(
+                    format!("signing in failed: {error}").into()
+                )),
+            }
+        }
+    }
+
+    /// Requests the token endpoint. Errors are returned with a status code other than `200`, so
+    /// the body is deserialized regardless of the status.
+    async fn request_token(&self, client: &Client, form: &[(&str, &str)]) -> Result<TokenResponse> {
+        let response = client
+            .post(self.oauth_url.join("token")?.as_str())
+            .form(form)
+            .send()
+            .await?
+            .bytes()
+            .await?;
+        Ok(serde_json::from_slice(&response)?)
+    }
+}
+
+/// The current unix timestamp in seconds.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,153 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{Client, Proxy};
+use reqwest::cookie::CookieStore;
+use reqwest::header::HeaderMap;
+use reqwest::redirect::Policy;
+
+use super::{Jar, recommended_headers};
+
+/// The time, after which connecting to a server is given up.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// The maximal number of redirects per request.
+const MAX_REDIRECTS: usize = 10;
+
+/// A thin wrapper around [`reqwest::ClientBuilder`], that builds the [`Client`]s rustube uses
+/// internally, i.e. in [`VideoFetcher::from_id`](crate::VideoFetcher::from_id).
+///
+/// [`ClientBuilder::recommended`] is the starting point for custom clients. It applies the
+/// [`recommended_cookies`](super::recommended_cookies) and [`recommended_headers`], a connect
+/// timeout, and a redirect limit. Everything not covered by the wrapper can be configured on the
+/// [`reqwest::ClientBuilder`] directly, using [`ClientBuilder::map`].
+///
+/// There is no overall request timeout, since it would also cancel long running downloads.
+///
+/// The [`Stream`](crate::Stream)s of a video use the client of the
+/// [`VideoFetcher`](crate::VideoFetcher), so a proxy configured here applies to all requests,
+/// including the downloads.
+///
+/// # Example
+/// ```no_run
+///# use rustube::{Id, VideoFetcher};
+///# use rustube::fetcher::ClientBuilder;
+///# use rustube::reqwest::Proxy;
+///# fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = ClientBuilder::recommended()
+///     .proxy(Proxy::all("socks5://localhost:1080")?)
+///     .build()?;
+///
+/// let id = Id::from_str("5jlI4uzZGjU")?.into_owned();
+/// let fetcher = VideoFetcher::from_id_with_client(id, client);
+///# Ok(())
+///# }
+/// ```
+#[derive(Debug)]
+pub struct ClientBuilder(reqwest::ClientBuilder);
+
+impl ClientBuilder {
+    /// A builder with the default settings of [`reqwest`], without any cookies or headers.
+    #[inline]
+    pub fn new() -> Self {
+        Self(Client::builder())
+    }
+
+    /// A builder with the recommended cookies, headers, timeouts and redirect policy.
+    #[inline]
+    pub fn recommended() -> Self {
+        Self::recommended_with_jar(Arc::new(Jar::default()))
+    }
+
+    /// Like [`ClientBuilder::recommended`], but the cookies are stored in `jar`, i.e. to persist
+    /// them with [`save_cookies_to_file`](super::save_cookies_to_file). The recommended cookies
+    /// are added to `jar`, unless it already contains cookies with the same names.
+    pub fn recommended_with_jar(jar: Arc<Jar>) -> Self {
+        jar.add_recommended_cookies();
+
+        Self::new()
+            .default_headers(recommended_headers())
+            .cookie_provider(jar)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .redirect(Policy::limited(MAX_REDIRECTS))
+    }
+
+    /// See [`reqwest::ClientBuilder::cookie_provider`].
+    #[inline]
+    pub fn cookie_provider<C: CookieStore + 'static>(self, cookie_store: Arc<C>) -> Self {
+        self.map(|builder| builder.cookie_provider(cookie_store))
+    }
+
+    /// See [`reqwest::ClientBuilder::default_headers`]. Replaces the recommended headers.
+    #[inline]
+    pub fn default_headers(self, headers: HeaderMap) -> Self {
+        self.map(|builder| builder.default_headers(headers))
+    }
+
+    /// See [`reqwest::ClientBuilder::proxy`].
+    #[inline]
+    pub fn proxy(self, proxy: Proxy) -> Self {
+        self.map(|builder| builder.proxy(proxy))
+    }
+
+    /// See [`reqwest::ClientBuilder::local_address`]. Requests are sent from `address`, i.e. to
+    /// choose between the IPv4 and IPv6 address of the machine.
+    #[inline]
+    pub fn local_address(self, address: IpAddr) -> Self {
+        self.map(|builder| builder.local_address(address))
+    }
+
+    /// See [`reqwest::ClientBuilder::connect_timeout`].
+    #[inline]
+    pub fn connect_timeout(self, timeout: Duration) -> Self {
+        self.map(|builder| builder.connect_timeout(timeout))
+    }
+
+    /// See [`reqwest::ClientBuilder::timeout`]. Note, that the timeout also applies to
+    /// downloads, which may take a long time.
+    #[inline]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.map(|builder| builder.timeout(timeout))
+    }
+
+    /// See [`reqwest::ClientBuilder::redirect`].
+    #[inline]
+    pub fn redirect(self, policy: Policy) -> Self {
+        self.map(|builder| builder.redirect(policy))
+    }
+
+    /// Applies `f` to the wrapped [`reqwest::ClientBuilder`], i.e. to configure TLS.
+    #[inline]
+    pub fn map(self, f: impl FnOnce(reqwest::ClientBuilder) -> reqwest::ClientBuilder) -> Self {
+        Self(f(self.0))
+    }
+
+    /// The wrapped [`reqwest::ClientBuilder`].
+    #[inline]
+    pub fn into_inner(self) -> reqwest::ClientBuilder {
+        self.0
+    }
+
+    /// Builds the [`Client`].
+    /// ### Errors
+    /// When [`reqwest`] fails to initialize the [`Client`], i.e. because the TLS backend cannot
+    /// be initialized.
+    #[inline]
+    pub fn build(self) -> crate::Result<Client> {
+        Ok(self.0.build()?)
+    }
+}
+
+impl Default for ClientBuilder {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<reqwest::ClientBuilder> for ClientBuilder {
+    #[inline]
+    fn from(builder: reqwest::ClientBuilder) -> Self {
+        Self(builder)
+    }
+}
@@ -0,0 +1,225 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::RwLock;
+
+use cookie::time::OffsetDateTime;
+use cookie_store::{CookieDomain, CookieExpiration, CookieStore, RawCookie};
+use reqwest::header::HeaderValue;
+use url::Url;
+
+use crate::{Error, Result};
+
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// A cookie jar, that can be loaded from, and saved to, a Netscape `cookies.txt` file.
+///
+/// This is the format browser extensions for exporting cookies and other downloaders, like
+/// `yt-dlp`, use. Use [`cookies_from_file`] and [`save_cookies_to_file`] to persist cookies
+/// across runs, and pass the jar to [`ClientBuilder::cookie_provider`](reqwest::ClientBuilder::cookie_provider)
+/// to use it for requests.
+#[derive(Debug, Default)]
+pub struct Jar(RwLock<CookieStore>);
+
+impl Jar {
+    /// Adds a cookie in the `Set-Cookie` header format, as if it was received from `url`.
+    pub fn add_cookie_str(&self, cookie: &str, url: &Url) {
+        if let Ok(cookie) = RawCookie::parse(cookie) {
+            let _ = self.0.write().unwrap().insert_raw(&cookie, url);
+        }
+    }
+
+    /// Adds the cookies of [`recommended_cookies`](super::recommended_cookies), unless the jar
+    /// already contains cookies with the same names.
+    pub fn add_recommended_cookies(&self) {
+        if !self.0.read().unwrap().contains_any("youtube.com", "/", "CONSENT") {
+            self.add_cookie_str(super::CONSENT_COOKIE, &super::CONSENT_COOKIE_URL.parse().unwrap());
+        }
+    }
+
+    /// The number of cookies in the jar, that did not expire yet.
+    pub fn len(&self) -> usize {
+        self.0.read().unwrap().iter_unexpired().count()
+    }
+
+    /// Whether the jar contains any cookies, that did not expire yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert_netscape_line(&self, line: &str) -> core::result::Result<(), String> {
+        let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+
+        let mut fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 6 {
+            // some exporters separate the fields by spaces instead of tabs
+            fields = line.split_whitespace().collect();
+        }
+
+        let (domain, include_subdomains, path, secure, expires, name, value) = match fields[..] {
+            [domain, include_subdomains, path, secure, expires, name] =>
+                (domain, include_subdomains, path, secure, expires, name, String::new()),
+            [domain, include_subdomains, path, secure, expires, name, ref value @ ..] =>
+                (domain, include_subdomains, path, secure, expires, name, value.join(" ")),
+            _ => return Err(format!("expected 7 fields, found {}", fields.len())),
+        };
+
+        let include_subdomains = parse_flag(include_subdomains)
+            .ok_or_else(|| format!("invalid subdomain flag `{include_subdomains}`"))?;
+        let secure = parse_flag(secure)
+            .ok_or_else(|| format!("invalid secure flag `{secure}`"))?;
+        // some exporters write fractional timestamps
+        let expires = expires
+            .parse::<i64>()
+            .or_else(|_| expires.parse::<f64>().map(|expires| expires as i64))
+            .map_err(|_| format!("invalid expiration timestamp `{expires}`"))?;
+
+        let host = domain.trim_start_matches('.');
+        if host.is_empty() {
+            return Err("missing domain".to_owned());
+        }
+        let url = Url::parse(&format!("https://{host}{path}"))
+            .map_err(|err| format!("invalid domain or path: {err}"))?;
+
+        let mut cookie = RawCookie::build(name.to_owned(), value)
+            .path(path.to_owned())
+            .secure(secure)
+            .http_only(http_only);
+        if include_subdomains {
+            cookie = cookie.domain(host.to_owned());
+        }
+        // a timestamp of 0 marks a session cookie
+        if expires > 0 {
+            let expires = OffsetDateTime::from_unix_timestamp(expires)
+                .map_err(|_| format!("invalid expiration timestamp `{expires}`"))?;
+            cookie = cookie.expires(expires);
+        }
+
+        match self.0.write().unwrap().insert_raw(&cookie.finish(), &url) {
+            Ok(_) => Ok(()),
+            Err(cookie_store::CookieError::Expired) => {
+                log::debug!("skipping the expired cookie `{name}` for `{domain}`");
+                Ok(())
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn to_netscape(&self) -> String {
+        let mut netscape = String::from(
+            "# Netscape HTTP Cookie File\n\
+            # This file was generated by rustube. Edit at your own risk.\n\n"
+        );
+
+        for cookie in self.0.read().unwrap().iter_unexpired() {
+            let (domain, include_subdomains) = match &cookie.domain {
+                CookieDomain::HostOnly(domain) => (domain.clone(), false),
+                CookieDomain::Suffix(domain) => (format!(".{domain}"), true),
+                CookieDomain::NotPresent | CookieDomain::Empty => continue,
+            };
+            let expires = match cookie.expires {
+                CookieExpiration::AtUtc(expires) => expires.unix_timestamp(),
+                CookieExpiration::SessionEnd => 0,
+            };
+
+            let _ = writeln!(
+                netscape,
+                "{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                if cookie.http_only().unwrap_or(false) { HTTP_ONLY_PREFIX } else { "" },
+                domain,
+                fmt_flag(include_subdomains),
+                String::from(&cookie.path),
+                fmt_flag(cookie.secure().unwrap_or(false)),
+                expires,
+                cookie.name(),
+                cookie.value(),
+            );
+        }
+
+        netscape
+    }
+}
+
+impl reqwest::cookie::CookieStore for Jar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item=&HeaderValue>, url: &Url) {
+        let cookies = cookie_headers
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| RawCookie::parse(value).ok())
+            .map(RawCookie::into_owned);
+        self.0.write().unwrap().store_response_cookies(cookies, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let cookies = self.0
+            .read()
+            .unwrap()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        match cookies.is_empty() {
+            true => None,
+            false => HeaderValue::from_str(&cookies).ok(),
+        }
+    }
+}
+
+/// Loads a Netscape `cookies.txt` file.
+///
+/// Comments, empty lines and expired cookies are skipped. Cookies prefixed with `#HttpOnly_` are
+/// loaded as HTTP only cookies. Lines, where the fields are separated by spaces instead of tabs,
+/// are accepted as well.
+///
+/// ### Errors
+/// - When the file cannot be read.
+/// - When a line is not valid UTF-8, or not a valid cookie ([`Error::CookieFile`]).
+pub fn cookies_from_file<P: AsRef<Path>>(path: P) -> Result<Jar> {
+    let content = std::fs::read(path)?;
+    let jar = Jar::default();
+
+    for (i, line) in content.split(|&b| b == b'\n').enumerate() {
+        let cookie_file_error = |reason: String| Error::CookieFile { line: i + 1, reason: reason.into() };
+
+        let line = std::str::from_utf8(line)
+            .map_err(|_| cookie_file_error("the line is not valid UTF-8".to_owned()))?
+            .trim_end_matches('\r');
+        let is_comment = line.starts_with('#') && !line.starts_with(HTTP_ONLY_PREFIX);
+        if line.trim().is_empty() || is_comment { continue; }
+
+        jar
+            .insert_netscape_line(line)
+            .map_err(cookie_file_error)?;
+    }
+
+    Ok(jar)
+}
+
+/// Saves all cookies of `jar`, that did not expire yet, to a Netscape `cookies.txt` file.
+/// An existing file will be overwritten.
+///
+/// ### Errors
+/// - When the file cannot be written.
+pub fn save_cookies_to_file<P: AsRef<Path>>(jar: &Jar, path: P) -> Result<()> {
+    std::fs::write(path, jar.to_netscape())?;
+    Ok(())
+}
+
+#[inline]
+fn parse_flag(flag: &str) -> Option<bool> {
+    match flag {
+        _ if flag.eq_ignore_ascii_case("TRUE") => Some(true),
+        _ if flag.eq_ignore_ascii_case("FALSE") => Some(false),
+        _ => None,
+    }
+}
+
+#[inline]
+fn fmt_flag(flag: bool) -> &'static str {
+    match flag {
+        true => "TRUE",
+        false => "FALSE",
+    }
+}
@@ -0,0 +1,258 @@
+//! Skipping videos before their streams are fetched, i.e. in crawlers, that only download
+//! videos up to a certain length.
+//!
+//! [`VideoFetcher::fetch_filtered`] requests the watch page like
+//! [`VideoFetcher::fetch_info`], and asks a [`FetchFilter`] about the resulting [`VideoInfo`].
+//! Only if the filter keeps the video, `base.js` and the player responses are requested, and the
+//! video is descrambled. [`fetch_many`] does the same for many videos concurrently.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{Result, Video, VideoFetcher, VideoInfo};
+#[cfg(feature = "download")]
+use crate::IdBuf;
+
+/// What a [`FetchFilter`] decided about a video.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FilterDecision {
+    /// The video is fetched and descrambled.
+    Keep,
+    /// The video is skipped for the given reason.
+    Skip(String),
+}
+
+/// Decides, based on the [`VideoInfo`], whether a video is fetched and descrambled (see
+/// [`VideoFetcher::fetch_filtered`]).
+///
+/// Any closure `Fn(&VideoInfo) -> FilterDecision` can be turned into a filter. The default filter
+/// keeps all videos.
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug)]
+pub struct FetchFilter {
+    #[derivative(Debug = "ignore")]
+    filter: Arc<dyn Fn(&VideoInfo) -> FilterDecision + Send + Sync>,
+}
+
+impl Default for FetchFilter {
+    #[inline]
+    fn default() -> Self {
+        Self::new(|_| FilterDecision::Keep)
+    }
+}
+
+impl<F> From<F> for FetchFilter
+    where F: Fn(&VideoInfo) -> FilterDecision + Send + Sync + 'static {
+    #[inline]
+    fn from(filter: F) -> Self {
+        Self::new(filter)
+    }
+}
+
+impl FetchFilter {
+    /// Creates a filter from a closure.
+    #[inline]
+    pub fn new(filter: impl Fn(&VideoInfo) -> FilterDecision + Send + Sync + 'static) -> Self {
+        Self { filter: Arc::new(filter) }
+    }
+
+    /// Skips videos, that are longer than `max`. Videos with an unknown
+    /// [`duration`](VideoInfo::duration), i.e. live streams, are kept.
+    pub fn max_duration(max: Duration) -> Self {
+        Self::new(move |video_info| match video_info.duration() {
+            duration if duration > max => FilterDecision::Skip(format!(
+                "the video is {} s long, which is longer than {} s",
+                duration.as_secs(), max.as_secs(),
+            )),
+            _ => FilterDecision::Keep,
+        })
+    }
+
+    /// Skips videos, that are not tagged with `keyword`, ignoring the case (see
+    /// [`VideoInfo::has_keyword`]).
+    pub fn keyword(keyword: impl Into<String>) -> Self {
+        let keyword = keyword.into();
+        Self::new(move |video_info| match video_info.has_keyword(&keyword) {
+            true => FilterDecision::Keep,
+            false => FilterDecision::Skip(format!("the video is not tagged with {:?}", keyword)),
+        })
+    }
+
+    /// Keeps only videos, that both filters keep. If this filter skips a video, `other` is not
+    /// asked.
+    pub fn and(self, other: impl Into<FetchFilter>) -> Self {
+        let other = other.into();
+        Self::new(move |video_info| match self.decide(video_info) {
+            FilterDecision::Keep => other.decide(video_info),
+            skip => skip,
+        })
+    }
+
+    /// Decides about the video.
+    #[inline]
+    pub fn decide(&self, video_info: &VideoInfo) -> FilterDecision {
+        (self.filter)(video_info)
+    }
+}
+
+/// The result of [`VideoFetcher::fetch_filtered`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum FetchOutcome {
+    /// The filter kept the video, and it was fetched and descrambled.
+    Fetched(Video),
+    /// The filter skipped the video. `video_info` is the one of [`VideoFetcher::fetch_info`].
+    Skipped {
+        video_info: VideoInfo,
+        reason: String,
+    },
+}
+
+impl VideoFetcher {
+    /// Fetches the [`VideoInfo`] like [`VideoFetcher::fetch_info`], and, if `filter` keeps the
+    /// video, continues like [`VideoFetcher::fetch`], and descrambles it.
+    ///
+    /// The watch page is only requested once. For skipped videos, neither `base.js`, nor the
+    /// player responses of the innertube clients are requested.
+    ///
+    /// ### Errors
+    /// - When fetching the [`VideoInfo`] fails (see [`VideoFetcher::fetch_info`]).
+    /// - For kept videos, when fetching, or descrambling fails (see [`VideoFetcher::fetch`], and
+    ///   [`VideoDescrambler::descramble`](crate::VideoDescrambler::descramble)).
+    pub async fn fetch_filtered(self, filter: &FetchFilter) -> Result<FetchOutcome> {
+        let (video_info, watch_html) = self.fetch_info_and_watch_html().await?;
+        match filter.decide(&video_info) {
+            FilterDecision::Keep => {}
+            FilterDecision::Skip(reason) => {
+                log::debug!("skipping {}: {}", self.video_id, reason);
+                return Ok(FetchOutcome::Skipped { video_info, reason });
+            }
+        }
+
+        self
+            .fetch_with_watch_html(watch_html)
+            .await?
+            .descramble()
+            .map(FetchOutcome::Fetched)
+    }
+}
+
+/// Options for [`fetch_many`].
+///
+/// By default, all videos are kept, and four videos are fetched at a time.
+#[cfg(feature = "download")]
+#[derive(Clone, Debug)]
+pub struct FetchManyOptions {
+    filter: FetchFilter,
+    concurrency: usize,
+}
+
+#[cfg(feature = "download")]
+impl Default for FetchManyOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            filter: FetchFilter::default(),
+            concurrency: 4,
+        }
+    }
+}
+
+#[cfg(feature = "download")]
+impl FetchManyOptions {
+    /// Creates the default options.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only fetches the videos, `filter` keeps. [default: all videos]
+    #[inline]
+    pub fn filter(mut self, filter: impl Into<FetchFilter>) -> Self {
+        self.filter = filter.into();
+        self
+    }
+
+    /// How many videos are fetched at a time. [default: 4]
+    ///
+    /// # Panics
+    /// When `concurrency` is 0.
+    #[inline]
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        assert!(concurrency > 0, "the concurrency must be at least 1");
+        self.concurrency = concurrency;
+        self
+    }
+}
+
+/// Fetches all videos of `fetchers` with [`VideoFetcher::fetch_filtered`], and yields the id
+/// and the [`FetchOutcome`] of each video, in the order they finish.
+///
+/// A failing video does not stop the others. The videos are fetched in a background task, that
+/// stops, once the returned stream is dropped.
+///
+/// # Panics
+/// When called outside of a tokio runtime.
+///
+/// # Example
+/// ```no_run
+///# use std::time::Duration;
+///# use rustube::{fetch_many, FetchFilter, FetchManyOptions, FetchOutcome, Id, VideoFetcher};
+///# use tokio_stream::StreamExt;
+///# #[tokio::main]
+///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let fetchers = ["5jlI4uzZGjU", "fDzQ3kUg8Ss"]
+///     .iter()
+///     .map(|id| VideoFetcher::from_id(Id::from_str(id)?.into_owned()))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// let filter = FetchFilter::max_duration(Duration::from_secs(20 * 60))
+///     .and(FetchFilter::keyword("rust"));
+///
+/// let mut results = fetch_many(fetchers, FetchManyOptions::new().filter(filter));
+/// while let Some((id, result)) = results.next().await {
+///     match result? {
+///         FetchOutcome::Fetched(video) => println!("{}: {}", id, video.title()),
+///         FetchOutcome::Skipped { reason, .. } => println!("skipped {}: {}", id, reason),
+///     }
+/// }
+///# Ok(())
+///# }
+/// ```
+#[cfg(feature = "download")]
+pub fn fetch_many(
+    fetchers: impl IntoIterator<Item=VideoFetcher>,
+    options: FetchManyOptions,
+) -> impl tokio_stream::Stream<Item=(IdBuf, Result<FetchOutcome>)> {
+    let fetchers: Vec<VideoFetcher> = fetchers.into_iter().collect();
+    let (sender, receiver) = tokio::sync::mpsc::channel(options.concurrency);
+
+    tokio::spawn(async move {
+        let mut tasks = tokio::task::JoinSet::new();
+        let mut fetchers = fetchers.into_iter();
+        loop {
+            while tasks.len() < options.concurrency {
+                let fetcher = match fetchers.next() {
+                    Some(fetcher) => fetcher,
+                    None => break,
+                };
+                let filter = options.filter.clone();
+                tasks.spawn(async move {
+                    let id = fetcher.video_id.clone();
+                    (id, fetcher.fetch_filtered(&filter).await)
+                });
+            }
+
+            let result = match crate::error::join_next(&mut tasks).await {
+                Some(Ok(result)) => result,
+                // the fetches are only cancelled, when the runtime shuts down, so there's no
+                // consumer left, that the `Error::Cancelled` could be reported to
+                Some(Err(_)) | None => break,
+            };
+            if sender.send(result).await.is_err() {
+                // the stream was dropped, dropping `tasks` aborts the remaining fetches
+                break;
+            }
+        }
+    });
+
+    tokio_stream::wrappers::ReceiverStream::new(receiver)
+}
@@ -0,0 +1,188 @@
+use serde_json::{json, Value};
+
+use crate::Id;
+
+/// A client, YouTube's internal API (innertube) can be requested as.
+///
+/// YouTube does not treat all clients the same. Some videos, i.e. videos made for kids, are only
+/// playable for some clients, and some clients receive pre-signed stream URLs, which do not need
+/// to be descrambled.
+#[derive(Clone, Copy, Debug, derive_more::Display, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InnertubeClient {
+    /// The desktop website.
+    #[display(fmt = "WEB")]
+    Web,
+    /// The Android app.
+    #[display(fmt = "ANDROID")]
+    Android,
+    /// The player, that is embedded into third party websites.
+    #[display(fmt = "WEB_EMBEDDED_PLAYER")]
+    WebEmbedded,
+    /// The iOS app.
+    #[display(fmt = "IOS")]
+    Ios,
+    /// The player, that is embedded into smart TV apps.
+    #[display(fmt = "TVHTML5_SIMPLY_EMBEDDED_PLAYER")]
+    TvEmbedded,
+}
+
+impl InnertubeClient {
+    /// The clients [`VideoFetcher::fetch`](crate::VideoFetcher::fetch) tries by default, in order.
+    pub const DEFAULT_CHAIN: [InnertubeClient; 4] = [Self::Web, Self::Android, Self::WebEmbedded, Self::Ios];
+
+    /// The name of the client, as used in the client context.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Web => "WEB",
+            Self::Android => "ANDROID",
+            Self::WebEmbedded => "WEB_EMBEDDED_PLAYER",
+            Self::Ios => "IOS",
+            Self::TvEmbedded => "TVHTML5_SIMPLY_EMBEDDED_PLAYER",
+        }
+    }
+
+    /// The numeric id of the client, as used in the `X-YouTube-Client-Name` header.
+    #[inline]
+    pub fn id(&self) -> u32 {
+        match self {
+            Self::Web => 1,
+            Self::Android => 3,
+            Self::WebEmbedded => 56,
+            Self::Ios => 5,
+            Self::TvEmbedded => 85,
+        }
+    }
+
+    /// The client version, YouTube expects.
+    #[inline]
+    pub fn version(&self) -> &'static str {
+        match self {
+            Self::Web => "2.20241126.01.00",
+            Self::Android => "19.44.38",
+            Self::WebEmbedded => "1.20241201.00.00",
+            Self::Ios => "19.45.4",
+            Self::TvEmbedded => "2.0",
+        }
+    }
+
+    /// The user agent of the client.
+    #[inline]
+    pub fn user_agent(&self) -> &'static str {
+        match self {
+            Self::Web | Self::WebEmbedded =>
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) \
+                Chrome/131.0.0.0 Safari/537.36",
+            Self::Android => "com.google.android.youtube/19.44.38 (Linux; U; Android 11) gzip",
+            Self::Ios => "com.google.ios.youtube/19.45.4 (iPhone16,2; U; CPU iOS 18_1_0 like Mac OS X;)",
+            Self::TvEmbedded =>
+                "Mozilla/5.0 (PlayStation; PlayStation 4/12.00) AppleWebKit/605.1.15 (KHTML, like Gecko) \
+                Version/15.4 Safari/605.1.15",
+        }
+    }
+
+    /// Whether the client is a native app, that always identifies with its own user agent.
+    #[inline]
+    pub(crate) fn is_app(&self) -> bool {
+        matches!(self, Self::Android | Self::Ios)
+    }
+
+    /// The `context` object, every innertube request has to contain.
+    pub(crate) fn context(&self) -> Value {
+        let mut client = json!({
+            "clientName": self.name(),
+            "clientVersion": self.version(),
+            "hl": "en",
+            "gl": "US",
+        });
+
+        match self {
+            Self::Web => {}
+            Self::Android => {
+                client["androidSdkVersion"] = json!(30);
+                client["osName"] = json!("Android");
+                client["osVersion"] = json!("11");
+                client["userAgent"] = json!(self.user_agent());
+            }
+            Self::Ios => {
+                client["deviceMake"] = json!("Apple");
+                client["deviceModel"] = json!("iPhone16,2");
+                client["osName"] = json!("iPhone");
+                client["osVersion"] = json!("18.1.0.22B83");
+                client["userAgent"] = json!(self.user_agent());
+            }
+            Self::WebEmbedded | Self::TvEmbedded => {
+                client["clientScreen"] = json!("EMBED");
+            }
+        }
+
+        let mut context = json!({ "client": client });
+        if let Self::WebEmbedded | Self::TvEmbedded = self {
+            context["thirdParty"] = json!({ "embedUrl": "https://www.youtube.com/" });
+        }
+        context
+    }
+
+    /// The body of a request to the `player` endpoint.
+    pub(crate) fn player_request(
+        &self,
+        video_id: Id<'_>,
+        signature_timestamp: Option<u64>,
+        po_token: Option<&str>,
+    ) -> Value {
+        let mut body = json!({
+            "context": self.context(),
+            "videoId": video_id.as_str(),
+            "contentCheckOk": true,
+            "racyCheckOk": true,
+        });
+
+        if let Self::WebEmbedded | Self::TvEmbedded = self {
+            // the embedded players pretend to be embedded into the embed page of the video
+            body["context"]["thirdParty"]["embedUrl"] = json!(format!("https://www.youtube.com/embed/{}", video_id));
+        }
+        if let Some(sts) = signature_timestamp {
+            body["playbackContext"] = json!({
+                "contentPlaybackContext": { "signatureTimestamp": sts }
+            });
+        }
+        if let Some(po_token) = po_token {
+            body["serviceIntegrityDimensions"] = json!({ "poToken": po_token });
+        }
+        body
+    }
+
+    /// The body of a request to the `next` endpoint, which returns the data of the watch page,
+    /// apart from the player.
+    pub(crate) fn next_request(&self, video_id: Id<'_>) -> Value {
+        json!({
+            "context": self.context(),
+            "videoId": video_id.as_str(),
+            "contentCheckOk": true,
+            "racyCheckOk": true,
+        })
+    }
+
+    /// The body of a request to the `next` endpoint, that continues a previous response, i.e. with
+    /// the next page of comments.
+    #[cfg(feature = "comments")]
+    pub(crate) fn continuation_request(&self, continuation: &str) -> Value {
+        json!({
+            "context": self.context(),
+            "continuation": continuation,
+        })
+    }
+
+    /// The body of a request to the `search` endpoint. Either the first page of the results of
+    /// `query` is requested, or, with a `continuation` token, the next page.
+    #[cfg(feature = "search")]
+    pub(crate) fn search_request(&self, query: &str, continuation: Option<&str>) -> Value {
+        let mut body = json!({ "context": self.context() });
+        match continuation {
+            Some(continuation) => body["continuation"] = json!(continuation),
+            None => body["query"] = json!(query),
+        }
+        body
+    }
+}
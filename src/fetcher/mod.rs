@@ -0,0 +1,1467 @@
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{Client, StatusCode};
+use reqwest::header::HeaderMap;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use url::Url;
+
+pub use client::ClientBuilder;
+pub use cookies::{cookies_from_file, Jar, save_cookies_to_file};
+#[cfg(feature = "download")]
+pub use filter::{fetch_many, FetchManyOptions};
+pub use filter::{FetchFilter, FetchOutcome, FilterDecision};
+pub use innertube::InnertubeClient;
+pub use parse_mode::ParseMode;
+pub use self_check::{
+    API_CONTRACT_VERSION, SELF_CHECK_VIDEO_ID, SelfCheckReport, SelfCheckStep, StepReport, StepStatus,
+};
+pub use user_agent::UserAgentPolicy;
+pub(crate) use user_agent::UserAgent;
+
+use auth::{AuthConfig, OAuth};
+
+use crate::{Error, Id, IdBuf, JsRuntime, NoStreamsReason, PlayerResponse, Video, VideoDescrambler, VideoInfo};
+#[cfg(feature = "cache")]
+use crate::cache::{self, CachedResponse, CacheMode, ResponseCache};
+use crate::metrics::{Metrics, Phase, PhaseTimer};
+use crate::transfer::TransferStats;
+use crate::parser::extract_json_object;
+use crate::video_info::extended_metadata::ExtendedMetadata;
+use crate::video_info::player_response::playability_status::PlayabilityStatus;
+use crate::video_info::player_response::video_details::{LiveStatus, VideoDetails};
+
+pub mod auth;
+mod client;
+mod cookies;
+mod filter;
+mod innertube;
+mod parse_mode;
+mod self_check;
+mod user_agent;
+
+/// A fetcher used to download all necessary data from YouTube, which then could be used
+/// to extract video-URLs.
+///
+/// You will probably rarely use this type directly, and use [`Video`] instead.
+///
+/// # Example
+///```no_run
+///# use rustube::{Id, VideoFetcher};
+///# use url::Url;
+/// const URL: &str = "https://youtube.com/watch?v=5jlI4uzZGjU";
+/// let url = Url::parse(URL).unwrap();
+///
+/// let fetcher: VideoFetcher =  VideoFetcher::from_url(&url).unwrap();
+/// ```
+/// # How it works
+/// So you want to download a YouTube video? You probably already noticed, that YouTube makes
+/// this quite hard, and does not just provide static URLs for their videos. In fact, there's
+/// not the one URL for each video. When currently nobody is watching a video, there's actually
+/// no URL for this video at all!
+///
+/// So we need to somehow show YouTube that we want to watch the video, so the YouTube server
+/// generates a URL for us. To do this, we do what every 'normal' human being would do: we
+/// request the webpage of the video. To do so, we need nothing more, then the video's id (If you
+/// want to learn more about the id, you can have a look at [`Id`]. But you don't need to know
+/// anything about it for now). Let's, for example, take the id '5jlI4uzZGjU'. With this id, we
+/// can then visit <https://youtube.com/watch?v=5jlI4uzZGjU>, the site, you as a human would visit
+/// when just watching the video.
+///
+/// The next step is to extract as much information from <https://youtube.com/watch?v=5jlI4uzZGjU>
+/// as possible. This is, i.e., information like "is the video age-restricted?", or "can we watch
+/// the video without being a member of that channel?".
+///
+/// But there's information, which is a lot more important then knowing if we are old enough to watch the video: The [`VideoInfo`], the [`PlayerResponse`] and the JavaScript of the
+/// page. [`VideoInfo`] and [`PlayerResponse`] are JSON objects, which contain the most
+/// important information about the video. If you are feeling brave, feel free to have a look
+/// at the definitions of those two types, their subtypes, and all the information they contain
+/// (It's huge!). The JavaScript is not processed by `fetch`, but is used later by
+/// [`VideoDescrambler::descramble`] to generate the `transform_plan` and the `transform_map`
+/// (you will learn about both when it comes to descrambling).
+///
+/// To get the videos [`VideoInfo`], we actually need to request one more page. One you probably
+/// don't frequently visit as a 'normal' human being. Because we, programmers, are really
+/// creative when it comes to naming stuff, a video's [`VideoInfo`] can be requested at
+/// <https://youtube.com/get_video_info>. Btw.: If you want to see how the computer feels, when
+/// we ask him to deserialize the response into the [`VideoInfo`] struct, you can have a look
+/// at <https://www.youtube.com/get_video_info?video_id=5jlI4uzZGjU&eurl=https%3A%2F%2Fyoutube.com%2Fwatch%3Fiv%3D5jlI4uzZGjU&sts=>
+/// (most browsers, will download a text file!). This is the actual [`VideoInfo`] for the
+/// video with the id '5jlI4uzZGjU'.
+///
+/// Since the player response of the watch page is not always usable, i.e. for videos made for
+/// kids, `fetch` walks a chain of [`InnertubeClient`]s (see
+/// [`VideoFetcher::with_innertube_clients`]), and requests the player response as each of them,
+/// until one returns streams, that are not DRM protected.
+///
+/// That's it! Of course, we cannot download the video yet. But that's not the task of `fetch`.
+/// `fetch` is just responsible for requesting all the essential information. To learn how the
+/// journey continues, have a look at [`VideoDescrambler`].
+///
+/// [`Video`]: crate::video::Video
+#[derive(Clone, derive_more::Display, derivative::Derivative)]
+#[display(fmt = "VideoFetcher({video_id})")]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct VideoFetcher {
+    video_id: IdBuf,
+    watch_url: Url,
+    base_url: Url,
+    innertube_clients: Vec<InnertubeClient>,
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    js_runtime: Option<Arc<dyn JsRuntime>>,
+    #[derivative(PartialEq = "ignore")]
+    metrics: Option<Arc<Metrics>>,
+    #[derivative(PartialEq = "ignore")]
+    transfer_stats: Option<Arc<TransferStats>>,
+    #[derivative(Debug = "ignore")]
+    po_token: Option<String>,
+    user_agent: Option<UserAgent>,
+    #[derivative(Debug = "ignore")]
+    headers: HeaderMap,
+    #[derivative(PartialEq = "ignore")]
+    oauth: Option<OAuth>,
+    keep_initial_data: bool,
+    parse_mode: ParseMode,
+    #[cfg(feature = "cache")]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    cache: Option<Arc<dyn ResponseCache>>,
+    #[cfg(feature = "cache")]
+    cache_mode: CacheMode,
+    #[cfg(feature = "cache")]
+    cache_ttl: std::time::Duration,
+    rate_limit_retries: u32,
+    #[derivative(PartialEq = "ignore")]
+    client: Client,
+}
+
+impl VideoFetcher {
+    /// Constructs a [`VideoFetcher`] from an `Url`.
+    /// ### Errors
+    /// - When [`Id::from_raw`] fails to extracted the videos id from the url.
+    /// - When [`reqwest`] fails to initialize an new [`Client`].
+    #[inline]
+    #[cfg(feature = "regex")]
+    pub fn from_url(url: &Url) -> crate::Result<Self> {
+        let id = Id::from_raw(url.as_str())?
+            .into_owned();
+        Self::from_id(id)
+    }
+
+    /// Constructs a [`VideoFetcher`] from an `Id`.
+    /// ### Errors
+    /// When [`reqwest`] fails to initialize an new [`Client`].
+    #[inline]
+    pub fn from_id(video_id: IdBuf) -> crate::Result<Self> {
+        let client = ClientBuilder::recommended().build()?;
+        Ok(Self::from_id_with_client(video_id, client))
+    }
+
+    /// Constructs a [`VideoFetcher`] from an `Id`, that authenticates as described by `auth` (see
+    /// [`auth`]). The cookies are loaded into the cookie jar of a new, recommended [`Client`].
+    /// ### Errors
+    /// - When the cookie file cannot be loaded (see [`cookies_from_file`]).
+    /// - When [`reqwest`] fails to initialize an new [`Client`].
+    pub fn from_id_authenticated(video_id: IdBuf, auth: AuthConfig) -> crate::Result<Self> {
+        let jar = match auth.cookies {
+            Some(ref path) => cookies_from_file(path)?,
+            None => Jar::default(),
+        };
+        let client = ClientBuilder::recommended_with_jar(Arc::new(jar)).build()?;
+
+        let fetcher = Self::from_id_with_client(video_id, client);
+        Ok(match auth.oauth {
+            Some(oauth) => fetcher.with_oauth(oauth),
+            None => fetcher,
+        })
+    }
+
+    /// Constructs a [`VideoFetcher`] from an [`Id`] and an existing [`Client`].
+    /// There are no special constrains, what the [`Client`] has to look like.
+    /// It's recommended to build it with [`ClientBuilder::recommended`].
+    #[inline]
+    pub fn from_id_with_client(video_id: IdBuf, client: Client) -> Self {
+        Self {
+            watch_url: video_id.watch_url(),
+            base_url: Url::parse(YOUTUBE_URL).unwrap(),
+            innertube_clients: InnertubeClient::DEFAULT_CHAIN.to_vec(),
+            js_runtime: None,
+            metrics: None,
+            transfer_stats: None,
+            po_token: None,
+            user_agent: None,
+            headers: HeaderMap::new(),
+            oauth: None,
+            keep_initial_data: false,
+            parse_mode: ParseMode::default(),
+            #[cfg(feature = "cache")]
+            cache: None,
+            #[cfg(feature = "cache")]
+            cache_mode: CacheMode::default(),
+            #[cfg(feature = "cache")]
+            cache_ttl: cache::DEFAULT_TTL,
+            rate_limit_retries: 0,
+            video_id,
+            client,
+        }
+    }
+
+    /// Sets the [`InnertubeClient`]s [`VideoFetcher::fetch`] requests the player response as,
+    /// in order, until one of them returns usable streams. Age restricted videos are requested as
+    /// [`InnertubeClient::TvEmbedded`] first, since it does not require a login for them.
+    ///
+    /// [default: [`InnertubeClient::DEFAULT_CHAIN`]]
+    #[inline]
+    pub fn with_innertube_clients(mut self, clients: Vec<InnertubeClient>) -> Self {
+        self.innertube_clients = clients;
+        self
+    }
+
+    /// Requests the player response as `client` first. The other [`InnertubeClient`]s of the
+    /// chain are only tried, if it returns no usable streams, i.e. when the video requires a
+    /// login for `client`.
+    #[inline]
+    pub fn with_innertube_client(mut self, client: InnertubeClient) -> Self {
+        self.innertube_clients.retain(|&other| other != client);
+        self.innertube_clients.insert(0, client);
+        self
+    }
+
+    /// Sets the [`JsRuntime`] the [`VideoDescrambler`] uses to evaluate the JavaScript functions,
+    /// that scramble the stream URLs (see [`VideoDescrambler::with_js_runtime`]).
+    #[inline]
+    pub fn with_js_runtime(mut self, js_runtime: Arc<dyn JsRuntime>) -> Self {
+        self.js_runtime = Some(js_runtime);
+        self
+    }
+
+    /// Sets the [`Metrics`] collector, that records the time spent in, and the bytes transferred
+    /// by, each [`Phase`](crate::metrics::Phase) of fetching, descrambling and downloading the
+    /// video. Without a collector, nothing is recorded.
+    #[inline]
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Sets the [`TransferStats`], that count the requests and bytes of fetching, and, since
+    /// they're handed on to the [`Stream`](crate::Stream)s, of downloading the video.
+    ///
+    /// With a [`TransferStats::limit`]ed collector, downloads abort with
+    /// [`Error::QuotaExceeded`], once the cap is exceeded.
+    #[inline]
+    pub fn with_transfer_stats(mut self, transfer_stats: Arc<TransferStats>) -> Self {
+        self.transfer_stats = Some(transfer_stats);
+        self
+    }
+
+    /// Sets the proof of origin token (`po_token`), that's sent to the innertube API, when YouTube
+    /// asks to sign in to confirm you're not a bot.
+    ///
+    /// [`VideoFetcher::fetch`] then retries each client, that failed with a bot check, once with
+    /// the token, before giving up with [`Error::BotCheck`].
+    #[inline]
+    pub fn with_po_token(mut self, po_token: impl Into<String>) -> Self {
+        self.po_token = Some(po_token.into());
+        self
+    }
+
+    /// Sets the [`UserAgentPolicy`], that decides which user agent all requests belonging to the
+    /// video are made with, including the downloads of its streams.
+    ///
+    /// The user agent is picked right away, so each [`VideoFetcher`] uses a single user agent.
+    /// Without a policy, the default headers of the [`Client`] are used (see
+    /// [`recommended_headers`]).
+    #[inline]
+    pub fn with_user_agent_policy(mut self, policy: &UserAgentPolicy) -> Self {
+        self.user_agent = Some(policy.pick());
+        self
+    }
+
+    /// Sets additional headers, that are sent with all requests belonging to the video, including
+    /// the downloads of its streams, i.e. a `Referer` or `Origin`. They take precedence over the
+    /// headers of the [`Client`] and the [`UserAgentPolicy`].
+    ///
+    /// See [`recommended_headers_for`] for the headers of a specific [`InnertubeClient`].
+    #[inline]
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Authenticates the requests to YouTube's internal API with the access token of `oauth`
+    /// (see [`auth`]). YouTube only accepts the token of the TV app, so
+    /// [`InnertubeClient::TvEmbedded`] is requested first, unless it's already part of the
+    /// clients (see [`VideoFetcher::with_innertube_clients`]).
+    #[inline]
+    pub fn with_oauth(mut self, oauth: OAuth) -> Self {
+        if !self.innertube_clients.contains(&InnertubeClient::TvEmbedded) {
+            self.innertube_clients.insert(0, InnertubeClient::TvEmbedded);
+        }
+        self.oauth = Some(oauth);
+        self
+    }
+
+    /// Whether to keep the `ytInitialData` of the watch page, which contains the data of the
+    /// renderers around the player, i.e. the like count, the description or the comments
+    /// continuation token. rustube does not model these, but they are available as raw JSON via
+    /// [`VideoInfo::initial_data`], without requesting the page again.
+    ///
+    /// [default: `false`]
+    #[inline]
+    pub fn with_initial_data(mut self, keep: bool) -> Self {
+        self.keep_initial_data = keep;
+        self
+    }
+
+    /// Sets how strictly values, that rustube does not know about, are treated (see [`ParseMode`]).
+    /// With [`ParseMode::Strict`], fetching fails with [`Error::UnknownVariant`] instead of
+    /// keeping them as `Other`.
+    ///
+    /// [default: [`ParseMode::Lenient`]]
+    #[inline]
+    pub fn with_parse_mode(mut self, parse_mode: ParseMode) -> Self {
+        self.parse_mode = parse_mode;
+        self
+    }
+
+    /// Sets the [`ResponseCache`], the watch page, the player JavaScript, and the responses of the
+    /// innertube API are cached in (see [`cache`]). Stream downloads are never cached.
+    #[cfg(feature = "cache")]
+    #[inline]
+    pub fn with_response_cache(mut self, cache: Arc<dyn ResponseCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Sets whether the [`ResponseCache`] is read from, and written to.
+    ///
+    /// [default: [`CacheMode::ReadWrite`]]
+    #[cfg(feature = "cache")]
+    #[inline]
+    pub fn with_cache_mode(mut self, mode: CacheMode) -> Self {
+        self.cache_mode = mode;
+        self
+    }
+
+    /// Sets how long responses are cached.
+    ///
+    /// [default: [`cache::DEFAULT_TTL`]]
+    #[cfg(feature = "cache")]
+    #[inline]
+    pub fn with_cache_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Sets how often a request is retried, after YouTube answered it with
+    /// [`Error::RateLimited`].
+    ///
+    /// Before each retry, `fetch` waits for the delay YouTube asked for (`Retry-After`), or, if
+    /// there is none, for [`RATE_LIMIT_BACKOFF`], doubled with each retry.
+    ///
+    /// [default: `0`]
+    #[inline]
+    pub fn with_rate_limit_retries(mut self, retries: u32) -> Self {
+        self.rate_limit_retries = retries;
+        self
+    }
+
+    /// Sets the URL all requests to YouTube are made against.
+    /// This is mostly useful for testing against a local server.
+    ///
+    /// [default: `https://www.youtube.com/`]
+    #[inline]
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        let mut watch_url = base_url.join("watch").expect("watch is a valid relative URL");
+        watch_url
+            .query_pairs_mut()
+            .append_pair("v", self.video_id.as_str());
+
+        self.watch_url = watch_url;
+        self.base_url = base_url;
+        self
+    }
+
+    /// Fetches all available video data and deserializes it into [`VideoInfo`].
+    ///
+    /// ### Errors
+    /// - When the video is private, only for members, or otherwise not accessible.
+    /// - When requests to some video resources fail.
+    /// - When deserializing the raw response fails.
+    /// - [`Error::AllClientsFailed`], when none of the [`InnertubeClient`]s returned usable streams.
+    ///   If all clients report the video as playable, but none of them returns any streams (yet),
+    ///   `fetch` succeeds, and [`VideoDescrambler::descramble`] fails with
+    ///   [`Error::NoStreamingData`] instead.
+    /// - [`Error::BotCheck`], when YouTube asks to sign in to confirm you're not a bot (also for
+    ///   every client, after retrying with the [`po_token`](VideoFetcher::with_po_token), if
+    ///   there's one).
+    ///
+    /// When having a good internet connection, only errors due to inaccessible videos should occur.
+    /// Other errors usually mean, that YouTube changed their API, and `rustube` did not adapt to
+    /// this change yet. Please feel free to open a GitHub issue if this is the case.
+    #[cfg(feature = "fetch")]
+    #[log_derive::logfn(ok = "Trace", err = "Error")]
+    #[log_derive::logfn_inputs(Trace)]
+    pub async fn fetch(self) -> crate::Result<VideoDescrambler> {
+        // fixme:
+        //  It seems like watch_html also contains a PlayerResponse in all cases. VideoInfo
+        //  only contains the  extra field `adaptive_fmts_raw`. It may be possible to just use the
+        //  watch_html PlayerResponse. This would eliminate one request and therefore improve
+        //  performance.
+        //  To do so, two things must happen:
+        //       1. I need a video, which has `adaptive_fmts_raw` set, so I can examine
+        //          both the watch_html as well as the video_info. (adaptive_fmts_raw even may be
+        //          a legacy thing, which isn't used by YouTube anymore).
+        //       2. I need to have some kind of evidence, that watch_html comes with the
+        //          PlayerResponse in most cases. (It would also be possible to just check, whether
+        //          or not watch_html contains PlayerResponse, and otherwise request video_info).
+
+        let watch_html = self.get_html(&self.watch_url, Phase::WatchHtml).await?;
+        self.fetch_with_watch_html(watch_html).await
+    }
+
+    /// Continues [`VideoFetcher::fetch`] with the already requested `watch_html`, so `base.js`,
+    /// and the player responses are only requested from here on.
+    async fn fetch_with_watch_html(self, watch_html: String) -> crate::Result<VideoDescrambler> {
+        let is_age_restricted = is_age_restricted(&watch_html);
+        match Self::check_downloadability(&watch_html, is_age_restricted) {
+            // the innertube clients are retried with the po_token
+            Err(Error::BotCheck(_)) if self.po_token.is_some() =>
+                log::warn!("the watch page requires a bot check, retrying with the po_token"),
+            res => { res?; }
+        }
+
+        let (js, player_response) = self.get_js(is_age_restricted, &watch_html).await?;
+        let (client, player_response) = self.get_player_response(player_response, &js, is_age_restricted).await?;
+        self.video_descrambler(client, player_response, is_age_restricted, &watch_html, js)
+    }
+
+    /// Like [`VideoFetcher::fetch`], but extracts the video data from the `watch_html` and the
+    /// player JavaScript `base_js`, that were requested (or stored) by the caller, instead of
+    /// requesting them. This performs no requests at all, so the resulting [`VideoDescrambler`]
+    /// is deterministic, i.e. for tests with stored pages, or for caching layers.
+    ///
+    /// The same extraction is used as by [`VideoFetcher::fetch`]: the [`PlayabilityStatus`] is
+    /// checked, and the [`PlayerResponse`] is extracted from the watch page. Since no other
+    /// [`InnertubeClient`]s can be asked, the player response of the watch page is used, even if
+    /// it does not contain any streams.
+    ///
+    /// ### Errors
+    /// - When the video is unavailable ([`Error::VideoUnavailable`]), or the watch page asks to
+    ///   confirm you're not a bot ([`Error::BotCheck`]).
+    /// - When the watch page does not contain a [`PlayabilityStatus`], a [`PlayerResponse`], or
+    ///   the url of the player JavaScript.
+    /// - When the player response violates the [`ParseMode`] of the fetcher.
+    #[cfg(feature = "fetch")]
+    pub fn fetch_offline(self, watch_html: &str, base_js: &str) -> crate::Result<VideoDescrambler> {
+        let is_age_restricted = is_age_restricted(watch_html);
+        Self::check_downloadability(watch_html, is_age_restricted)?;
+
+        // the url of `base.js` is not requested, but a watch page without one is not usable
+        let (_, player_response) = js_url(watch_html, &self.base_url)?;
+        let player_response = player_response.ok_or_else(|| Error::UnexpectedResponse(
+            "Could not find ytplayer_config in the watch html.".into()
+        ))?;
+
+        self.video_descrambler(InnertubeClient::Web, player_response, is_age_restricted, watch_html, base_js.to_owned())
+    }
+
+    /// Builds the [`VideoDescrambler`] of [`VideoFetcher::fetch`] from the `player_response` of
+    /// `client`.
+    fn video_descrambler(
+        self,
+        client: InnertubeClient,
+        player_response: PlayerResponse,
+        is_age_restricted: bool,
+        watch_html: &str,
+        js: String,
+    ) -> crate::Result<VideoDescrambler> {
+        self.parse_mode.check(&player_response)?;
+        let mut video_info = VideoInfo::from_player_response(player_response, is_age_restricted);
+        video_info.metrics = self.metrics.clone();
+        video_info.transfer_stats = self.transfer_stats.clone();
+        video_info.user_agent = self.user_agent_for(client);
+        video_info.extra_headers = self.headers.clone();
+        video_info.client = Some(self.client.clone());
+        video_info.initial_data = self.initial_data(watch_html);
+
+        Ok(VideoDescrambler {
+            video_info,
+            client: self.client,
+            js,
+            js_runtime: self.js_runtime,
+        })
+    }
+
+    /// Fetches all available video data, and deserializes it into [`VideoInfo`].
+    ///
+    /// This method will only return the [`VideoInfo`]. You won't have the ability to download
+    /// the video afterwards. If you want to download videos, have a look at [`VideoFetcher::fetch`].
+    ///
+    /// This method is useful if you want to find out something about a video that is not available
+    /// for download, like live streams that are offline, or private and deleted videos. Use
+    /// [`VideoInfo::playability`] and [`VideoInfo::is_playable`] to find out, whether the video is
+    /// available.
+    ///
+    /// If the watch page does not contain the video data, the player endpoint is requested with
+    /// the first of the [`innertube_clients`](Self::innertube_clients). For videos, that YouTube
+    /// does not return any details for (i.e. private or deleted ones), the
+    /// [`VideoDetails`](crate::VideoDetails) are empty, except for the `video_id`.
+    ///
+    /// ### Errors
+    /// - When requests to some video resources fail.
+    /// - When neither the watch page, nor the player endpoint return a response with a
+    ///   [`PlayabilityStatus`].
+    ///
+    /// When having a good internet connection, this method should not fail. Errors usually mean,
+    /// that YouTube changed their API, and `rustube` did not adapt to this change yet. Please feel
+    /// free to open a GitHub issue if this is the case.
+    #[cfg(feature = "fetch")]
+    pub async fn fetch_info(self) -> crate::Result<VideoInfo> {
+        self.fetch_info_and_watch_html().await.map(|(video_info, _)| video_info)
+    }
+
+    /// Fetches the free trailer, YouTube offers instead of the video, i.e. for rentals, and movies,
+    /// that have to be bought (see [`PlayabilityStatus::trailer`]), and descrambles it, so it can
+    /// be downloaded like any other [`Video`].
+    ///
+    /// If the trailer was included in the response, it's used directly. If only the id of the
+    /// trailer was included, it's fetched like any other video. `base.js` is only requested, if
+    /// the trailer has streams, that are not signed yet, or a [`JsRuntime`] was set.
+    ///
+    /// Returns `None`, if the video has no trailer, which includes all videos, that are playable.
+    ///
+    /// ### Errors
+    /// - When fetching the video fails (see [`VideoFetcher::fetch_info`]).
+    /// - When fetching, or descrambling the trailer fails (see [`VideoDescrambler::descramble`]).
+    #[cfg(feature = "fetch")]
+    pub async fn fetch_trailer(self) -> crate::Result<Option<Video>> {
+        let (video_info, watch_html) = self.fetch_info_and_watch_html().await?;
+        let renderer = match video_info.player_response.playability_status.trailer_renderer() {
+            Some(renderer) => renderer,
+            None => return Ok(None),
+        };
+
+        let player_response = renderer
+            .player_response()
+            .filter(|player_response| player_response.streaming_data.is_some());
+        let player_response = match (player_response, &renderer.trailer_video_id) {
+            (Some(player_response), _) => player_response,
+            (None, Some(trailer_id)) => {
+                log::debug!("the trailer {} was not included, fetching it", trailer_id);
+                let mut fetcher = self.clone();
+                fetcher.video_id = trailer_id.clone();
+                let fetcher = fetcher.with_base_url(self.base_url.clone());
+                return fetcher.fetch().await?.descramble().map(Some);
+            }
+            (None, None) => return Ok(None),
+        };
+        self.parse_mode.check(&player_response)?;
+
+        let needs_js = self.js_runtime.is_some() || needs_signature(&player_response);
+        let js = match needs_js {
+            true => self.get_js(video_info.is_age_restricted, &watch_html).await?.0,
+            // all streams of the trailer are pre-signed
+            false => String::new(),
+        };
+
+        let mut trailer_info = VideoInfo::from_player_response(player_response, false);
+        trailer_info.metrics = self.metrics.clone();
+        trailer_info.transfer_stats = self.transfer_stats.clone();
+        trailer_info.user_agent = self.user_agent_for(InnertubeClient::Web);
+        trailer_info.extra_headers = self.headers.clone();
+        trailer_info.client = Some(self.client.clone());
+
+        VideoDescrambler {
+            video_info: trailer_info,
+            client: self.client,
+            js,
+            js_runtime: self.js_runtime,
+        }
+            .descramble()
+            .map(Some)
+    }
+
+    /// Requests fresh streams for the already fetched `video`, and descrambles them, i.e. with a
+    /// [`Client`] in another network, so the stream URLs are valid for it (see
+    /// [`Stream::is_ip_bound`](crate::Stream::is_ip_bound)). The metadata of the returned video
+    /// is the one of the new player response.
+    ///
+    /// Unlike [`VideoFetcher::fetch`], the watch page is not requested. Only the player endpoint
+    /// is requested as the [`innertube_clients`](Self::innertube_clients), and the streams are
+    /// descrambled with the player JavaScript of `video`. If `video` has none, i.e. since its streams
+    /// did not need it, the watch page and `base.js` are only requested, if the new streams are not
+    /// signed yet, or a [`JsRuntime`] was set.
+    ///
+    /// ### Errors
+    /// - [`Error::AllClientsFailed`], when none of the [`InnertubeClient`]s returned usable streams.
+    /// - When requesting the JavaScript fails.
+    /// - When descrambling fails (see [`VideoDescrambler::descramble`]).
+    #[cfg(feature = "fetch")]
+    pub async fn refetch_streams(self, video: &Video) -> crate::Result<Video> {
+        let js = video.js.as_deref().map(str::to_owned);
+        let (client, player_response) = self
+            .get_player_response(None, js.as_deref().unwrap_or_default(), video.video_info.is_age_restricted)
+            .await?;
+        self.parse_mode.check(&player_response)?;
+
+        let js = match js {
+            Some(js) => js,
+            None if self.js_runtime.is_some() || needs_signature(&player_response) => {
+                log::debug!("{} has no player JavaScript, requesting it", video.id());
+                let watch_html = self.get_html(&self.watch_url, Phase::WatchHtml).await?;
+                self.get_js(video.video_info.is_age_restricted, &watch_html).await?.0
+            }
+            // all new streams are pre-signed
+            None => String::new(),
+        };
+
+        let mut video_info = VideoInfo::from_player_response(player_response, video.video_info.is_age_restricted);
+        video_info.metrics = self.metrics.clone();
+        video_info.transfer_stats = self.transfer_stats.clone();
+        video_info.user_agent = self.user_agent_for(client);
+        video_info.extra_headers = self.headers.clone();
+        video_info.client = Some(self.client.clone());
+        video_info.initial_data = video.video_info.initial_data.clone();
+
+        VideoDescrambler {
+            video_info,
+            client: self.client,
+            js,
+            js_runtime: self.js_runtime,
+        }
+            .descramble()
+    }
+
+    /// The [`VideoInfo`] of [`VideoFetcher::fetch_info`], and the watch page it was extracted
+    /// from.
+    async fn fetch_info_and_watch_html(&self) -> crate::Result<(VideoInfo, String)> {
+        let watch_html = self.get_html(&self.watch_url, Phase::WatchHtml).await?;
+        let is_age_restricted = is_age_restricted(&watch_html);
+
+        let (client, player_response) = match get_ytplayer_config(&watch_html) {
+            Ok(player_response) => (InnertubeClient::Web, player_response),
+            Err(err) => {
+                log::debug!("using the player endpoint, since the watch html was not usable: {}", err);
+                let client = self.first_client();
+                let response = self.request_player_value(client, None, None).await?;
+                (client, player_response_or_status(response, &self.video_id)?)
+            }
+        };
+
+        self.parse_mode.check(&player_response)?;
+
+        let mut video_info = VideoInfo::from_player_response(player_response, is_age_restricted);
+        video_info.metrics = self.metrics.clone();
+        video_info.transfer_stats = self.transfer_stats.clone();
+        video_info.user_agent = self.user_agent_for(client);
+        video_info.extra_headers = self.headers.clone();
+        video_info.client = Some(self.client.clone());
+        video_info.initial_data = self.initial_data(&watch_html);
+
+        Ok((video_info, watch_html))
+    }
+
+    /// Requests only the [`PlayabilityStatus`] of the video, using a single request to the player
+    /// endpoint with the first of the [`innertube_clients`](Self::innertube_clients).
+    ///
+    /// This is the cheapest way to find out, whether a video is available. Use
+    /// [`PlayabilityStatus::kind`] to match on it.
+    ///
+    /// ### Errors
+    /// - When the request fails.
+    /// - When the response does not contain a [`PlayabilityStatus`].
+    #[cfg(feature = "fetch")]
+    pub async fn fetch_playability(self) -> crate::Result<PlayabilityStatus> {
+        let mut response = self
+            .request_player_value(self.first_client(), None, self.po_token.as_deref())
+            .await?;
+
+        Ok(serde_json::from_value(response["playabilityStatus"].take())?)
+    }
+
+    /// Requests the [`ExtendedMetadata`] of the video, i.e. its category, license, and whether it
+    /// includes paid promotion, using a single request to the `next` endpoint as
+    /// [`InnertubeClient::Web`], since only the website renders this metadata.
+    ///
+    /// This request is independent of [`VideoFetcher::fetch`], and does not request the watch
+    /// page, or the player response.
+    ///
+    /// ### Errors
+    /// - When the request fails.
+    /// - When the response is not JSON.
+    #[cfg(feature = "fetch")]
+    pub async fn fetch_extended_metadata(self) -> crate::Result<ExtendedMetadata> {
+        let client = InnertubeClient::Web;
+        let mut body = client.next_request(self.video_id.as_borrowed());
+        if let Some(ref user_agent) = self.user_agent_for(client) {
+            body["context"]["client"]["userAgent"] = user_agent.as_str().into();
+        }
+        let response: serde_json::Value = self
+            .request_api("next", client, &body, Phase::NextApi)
+            .await?;
+
+        Ok(ExtendedMetadata::from_next_response(&response))
+    }
+
+    /// The id of the video.
+    #[inline]
+    pub fn video_id(&self) -> Id<'_> {
+        self.video_id.as_borrowed()
+    }
+
+    /// The url, under witch the video can be watched.
+    #[inline]
+    pub fn watch_url(&self) -> &Url {
+        &self.watch_url
+    }
+
+    /// The [`InnertubeClient`]s [`VideoFetcher::fetch`] tries, in order.
+    #[inline]
+    pub fn innertube_clients(&self) -> &[InnertubeClient] {
+        &self.innertube_clients
+    }
+
+    /// The [`ParseMode`] of the fetcher.
+    #[inline]
+    pub fn parse_mode(&self) -> ParseMode {
+        self.parse_mode
+    }
+
+    fn check_downloadability(watch_html: &str, is_age_restricted: bool) -> crate::Result<PlayabilityStatus> {
+        let playability_status = Self::extract_playability_status(watch_html)?;
+
+        match playability_status {
+            PlayabilityStatus::Ok { .. } => Ok(playability_status),
+            PlayabilityStatus::LoginRequired { .. } if is_age_restricted && !playability_status.is_bot_check() =>
+                Ok(playability_status),
+            ps => Err(unavailable(ps))
+        }
+    }
+
+    /// Checks, whether or not the video is accessible for normal users.
+    fn extract_playability_status(watch_html: &str) -> crate::Result<PlayabilityStatus> {
+        static PLAYABILITY_STATUS: Lazy<Regex> = Lazy::new(||
+            Regex::new(r#"["']?playabilityStatus["']?\s*[:=]\s*"#).unwrap()
+        );
+
+        PLAYABILITY_STATUS
+            .find_iter(watch_html)
+            .filter_map(|m| json_object(&watch_html[m.end()..]))
+            .map(serde_json::from_str::<PlayabilityStatus>)
+            .filter_map(Result::ok)
+            .next()
+            .ok_or_else(|| Error::UnexpectedResponse(
+                "watch html did not contain a PlayabilityStatus".into()
+            ))
+    }
+
+    /// Extracts or requests the JavaScript used to descramble the video signature.
+    #[inline]
+    async fn get_js(
+        &self,
+        is_age_restricted: bool,
+        watch_html: &str,
+    ) -> crate::Result<(String, Option<PlayerResponse>)> {
+        let (js_url, player_response) = match is_age_restricted {
+            true => {
+                let embed_url = self.base_url.join(&format!("embed/{}", self.video_id))?;
+                let embed_html = self.get_html(&embed_url, Phase::EmbedHtml).await?;
+                js_url(&embed_html, &self.base_url)?
+            }
+            false => js_url(watch_html, &self.base_url)?
+        };
+
+        self
+            .get_html(&js_url, Phase::BaseJs)
+            .await
+            .map(|html| (html, player_response))
+    }
+
+    /// Requests the [`VideoInfo`] of a video
+    #[inline]
+    #[allow(unused)]
+    async fn get_video_info(&self, is_age_restricted: bool) -> crate::Result<VideoInfo> {
+        // FIXME: Currently no in use + broken due to #38
+        let video_info_url = self.get_video_info_url(is_age_restricted);
+        let video_info_raw = self.get_html(&video_info_url, Phase::PlayerApi).await?;
+
+        let mut video_info = serde_qs::from_str::<VideoInfo>(video_info_raw.as_str())?;
+        video_info.is_age_restricted = is_age_restricted;
+
+        Ok(video_info)
+    }
+
+    /// Generates the url under which the [`VideoInfo`] can be requested.
+    #[inline]
+    #[log_derive::logfn_inputs(Debug)]
+    #[log_derive::logfn(Trace, fmt = "get_video_info_url() => {}")]
+    fn get_video_info_url(&self, is_age_restricted: bool) -> Url {
+        if is_age_restricted {
+            video_info_url_age_restricted(
+                self.video_id.as_borrowed(),
+                &self.watch_url,
+            )
+        } else {
+            video_info_url(
+                self.video_id.as_borrowed(),
+                &self.watch_url,
+            )
+        }
+    }
+
+    /// Requests a website, and records it as `phase`.
+    #[inline]
+    #[log_derive::logfn_inputs(Debug)]
+    #[log_derive::logfn(ok = "Trace", err = "Error", fmt = "get_html() => `{}`")]
+    async fn get_html(&self, url: &Url, phase: Phase) -> crate::Result<String> {
+        let mut request = self.client.get(url.as_str());
+        if let Some(ref user_agent) = self.user_agent {
+            request = request.headers(user_agent.headers());
+        }
+        request = request.headers(self.headers.clone());
+        let endpoint = match phase {
+            Phase::WatchHtml => "watch",
+            Phase::EmbedHtml => "embed",
+            Phase::BaseJs => "base.js",
+            Phase::PlayerApi => "get_video_info",
+            Phase::NextApi | Phase::Descramble | Phase::Download => "unknown",
+        };
+        let html = self.send(request, endpoint, "GET", url, &[], phase).await?;
+
+        Ok(String::from_utf8_lossy(&html).into_owned())
+    }
+
+    /// Walks the chain of [`InnertubeClient`]s, until one of them returns a [`PlayerResponse`]
+    /// with usable streams.
+    ///
+    /// For [`InnertubeClient::Web`], the player response of the watch page is used, if there is one.
+    async fn get_player_response(
+        &self,
+        mut watch_player_response: Option<PlayerResponse>,
+        js: &str,
+        is_age_restricted: bool,
+    ) -> crate::Result<(InnertubeClient, PlayerResponse)> {
+        let signature_timestamp = signature_timestamp(js);
+        let mut failures = Vec::new();
+
+        for client in self.client_chain(is_age_restricted) {
+            let mut player_response = match watch_player_response.take() {
+                Some(player_response) if client == InnertubeClient::Web => Ok(player_response),
+                other => {
+                    watch_player_response = other;
+                    self.request_player(client, signature_timestamp, None).await
+                }
+            }.and_then(check_streaming_data);
+
+            if let (Err(Error::BotCheck(_)), Some(po_token)) = (&player_response, &self.po_token) {
+                log::info!("the {} client requires a bot check, retrying with the po_token", client);
+                player_response = self
+                    .request_player(client, signature_timestamp, Some(po_token))
+                    .await
+                    .and_then(check_streaming_data);
+            }
+
+            match player_response {
+                Ok(player_response) => {
+                    log::debug!("using the player response of the {} client", client);
+                    return Ok((client, player_response));
+                }
+                // the other clients would fail the same way
+                Err(err @ Error::Authentication(_)) => return Err(err),
+                Err(err) => {
+                    log::warn!("the {} client did not return usable streams: {}", client, err);
+                    failures.push((client, err));
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            return Err(Error::AllClientsFailed(failures));
+        }
+        if failures.iter().all(|(_, err)| matches!(err, Error::BotCheck(_))) {
+            return Err(failures.swap_remove(0).1);
+        }
+        // all clients agree, that the video has no streams (yet), so VideoDescrambler::descramble
+        // reports it along with the metadata
+        if failures.iter().all(|(_, err)| matches!(err, Error::NoStreamingData { .. })) {
+            if let (client, Error::NoStreamingData { video_info, .. }) = failures.swap_remove(0) {
+                log::info!("none of the clients returned streams, using the player response of the {} client", client);
+                return Ok((client, video_info.player_response));
+            }
+        }
+        Err(Error::AllClientsFailed(failures))
+    }
+
+    /// Requests the [`PlayerResponse`] as `client`.
+    ///
+    /// Responses, that are not playable, often don't contain all fields of a [`PlayerResponse`],
+    /// so their playability status is returned as error instead of failing to deserialize them.
+    async fn request_player(
+        &self,
+        client: InnertubeClient,
+        signature_timestamp: Option<u64>,
+        po_token: Option<&str>,
+    ) -> crate::Result<PlayerResponse> {
+        let mut response = self.request_player_value(client, signature_timestamp, po_token).await?;
+
+        PlayerResponse::deserialize(&response).map_err(|err| {
+            match serde_json::from_value::<PlayabilityStatus>(response["playabilityStatus"].take()) {
+                Ok(ps) if !matches!(ps, PlayabilityStatus::Ok { .. }) => unavailable(ps),
+                _ => err.into(),
+            }
+        })
+    }
+
+    /// Requests the raw response of the player endpoint as `client`.
+    async fn request_player_value(
+        &self,
+        client: InnertubeClient,
+        signature_timestamp: Option<u64>,
+        po_token: Option<&str>,
+    ) -> crate::Result<serde_json::Value> {
+        let mut body = client.player_request(self.video_id.as_borrowed(), signature_timestamp, po_token);
+        if let Some(ref user_agent) = self.user_agent_for(client) {
+            body["context"]["client"]["userAgent"] = user_agent.as_str().into();
+        }
+        self
+            .request_api("player", client, &body, Phase::PlayerApi)
+            .await
+    }
+
+    /// The `ytInitialData` of the watch page, if it should be kept.
+    fn initial_data(&self, watch_html: &str) -> Option<serde_json::Value> {
+        if !self.keep_initial_data {
+            return None;
+        }
+
+        let initial_data = get_initial_data(watch_html);
+        if initial_data.is_none() {
+            log::warn!("could not extract ytInitialData from the watch html");
+        }
+        initial_data
+    }
+
+    /// The user agent of the requests as `client`, if there's a [`UserAgentPolicy`].
+    /// The apps, i.e. [`InnertubeClient::Android`], always identify as themselves.
+    fn user_agent_for(&self, client: InnertubeClient) -> Option<UserAgent> {
+        match client {
+            client if client.is_app() => self.user_agent
+                .as_ref()
+                .map(|_| UserAgent::new(client.user_agent().to_owned())),
+            _ => self.user_agent.clone(),
+        }
+    }
+
+    /// The [`InnertubeClient`]s, the player response is requested as, in order.
+    ///
+    /// The other clients require a login for age restricted videos, so they are requested as
+    /// [`InnertubeClient::TvEmbedded`] first.
+    fn client_chain(&self, is_age_restricted: bool) -> Vec<InnertubeClient> {
+        match is_age_restricted {
+            true => std::iter::once(InnertubeClient::TvEmbedded)
+                .chain(self.innertube_clients.iter().copied().filter(|&client| client != InnertubeClient::TvEmbedded))
+                .collect(),
+            false => self.innertube_clients.clone(),
+        }
+    }
+
+    /// The client used for single requests to the player endpoint.
+    #[inline]
+    fn first_client(&self) -> InnertubeClient {
+        self.innertube_clients
+            .first()
+            .copied()
+            .unwrap_or(InnertubeClient::Web)
+    }
+
+    /// Requests an endpoint of YouTube's internal API (innertube) as `client`, and records it as
+    /// `phase`.
+    #[log_derive::logfn_inputs(Debug)]
+    #[log_derive::logfn(ok = "Trace", err = "Error", fmt = "request_api() => `{:?}`")]
+    async fn request_api<T: DeserializeOwned + std::fmt::Debug>(
+        &self,
+        endpoint: &'static str,
+        client: InnertubeClient,
+        body: &serde_json::Value,
+        phase: Phase,
+    ) -> crate::Result<T> {
+        let mut url = self.base_url.join(&format!("youtubei/v1/{endpoint}"))?;
+        url
+            .query_pairs_mut()
+            .append_pair("prettyPrint", "false");
+
+        let mut request = self.client
+            .post(url.as_str())
+            .header(reqwest::header::USER_AGENT, client.user_agent())
+            .header("X-YouTube-Client-Name", client.id())
+            .header("X-YouTube-Client-Version", client.version());
+        if let Some(user_agent) = self.user_agent_for(client) {
+            request = request.headers(user_agent.headers());
+        }
+        request = request.headers(self.headers.clone());
+        if let Some(ref oauth) = self.oauth {
+            request = request.bearer_auth(oauth.access_token(&self.client).await?);
+        }
+        let body = serde_json::to_vec(body)?;
+        let request = request
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        let response = self.send(request, endpoint, "POST", &url, &body, phase).await?;
+
+        Ok(serde_json::from_slice(&response)?)
+    }
+
+    /// Sends `request` to `endpoint`, and records it as `phase`. Rate limited requests are
+    /// retried (see [`VideoFetcher::with_rate_limit_retries`]).
+    ///
+    /// If there's a [`ResponseCache`], the response is looked up in, and stored to it, keyed by
+    /// the `method`, `url` and `body` of the request.
+    #[cfg_attr(not(feature = "cache"), allow(unused_variables))]
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        endpoint: &'static str,
+        method: &str,
+        url: &Url,
+        body: &[u8],
+        phase: Phase,
+    ) -> crate::Result<bytes::Bytes> {
+        #[cfg(feature = "cache")]
+        let cache = self.cache
+            .as_deref()
+            .filter(|_| self.cache_mode != CacheMode::Disabled)
+            .map(|cache| (cache, cache::key(method, url, body)));
+        #[cfg(feature = "cache")]
+        if let Some((cache, ref key)) = cache {
+            if self.cache_mode.reads() {
+                if let Some(response) = cache.get(key) {
+                    log::debug!("using the cached response of {} {}", method, url.as_str());
+                    return Ok(response.body.into());
+                }
+            }
+        }
+
+        let mut retries = 0;
+        let response = loop {
+            let request = request
+                .try_clone()
+                .expect("requests to YouTube don't have streaming bodies");
+            let mut timer = PhaseTimer::start(self.metrics.as_deref(), phase);
+            match self.send_once(request, endpoint).await {
+                Err(Error::RateLimited { retry_after, .. }) if retries < self.rate_limit_retries => {
+                    drop(timer);
+                    let delay = retry_after.unwrap_or(RATE_LIMIT_BACKOFF * 2u32.saturating_pow(retries));
+                    log::warn!("the {} endpoint is rate limited, retrying in {:?}", endpoint, delay);
+                    tokio::time::sleep(delay).await;
+                    retries += 1;
+                }
+                res => {
+                    let response = res?;
+                    timer.add_bytes(response.len() as u64);
+                    break response;
+                }
+            }
+        };
+
+        #[cfg(feature = "cache")]
+        if let Some((cache, ref key)) = cache {
+            if self.cache_mode.writes() {
+                cache.put(key, CachedResponse::new(response.to_vec()), self.cache_ttl);
+            }
+        }
+        Ok(response)
+    }
+
+    /// Sends `request` to `endpoint` once.
+    ///
+    /// ### Errors
+    /// - [`Error::RateLimited`], when YouTube answers with `429 Too Many Requests`, or a
+    ///   `403 Forbidden`, whose body says the request was rate limited.
+    async fn send_once(&self, request: reqwest::RequestBuilder, endpoint: &'static str) -> crate::Result<bytes::Bytes> {
+        if let Some(ref transfer_stats) = self.transfer_stats {
+            transfer_stats.record_request();
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            let body = response.bytes().await?;
+            self.record_metadata(&body);
+            return Ok(body);
+        }
+
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(retry_after);
+        let err = response
+            .error_for_status_ref()
+            .expect_err("the status is not successful");
+        let body = response.bytes().await.unwrap_or_default();
+        self.record_metadata(&body);
+
+        if !is_rate_limited(status, &body) {
+            return Err(err.into());
+        }
+        if let Some(ref metrics) = self.metrics {
+            metrics.record_rate_limit();
+        }
+        Err(Error::RateLimited { retry_after, endpoint })
+    }
+
+    #[inline]
+    fn record_metadata(&self, body: &[u8]) {
+        if let Some(ref transfer_stats) = self.transfer_stats {
+            transfer_stats.record_metadata(body.len() as u64);
+        }
+    }
+}
+
+/// The base URL of all requests to YouTube.
+const YOUTUBE_URL: &str = "https://www.youtube.com/";
+const YOUTUBE_ORIGIN: &str = "https://www.youtube.com";
+
+/// The delay before the first retry of a rate limited request, if YouTube did not send a
+/// `Retry-After` header (see [`VideoFetcher::with_rate_limit_retries`]).
+pub const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Parts of the bodies of `403 Forbidden` responses, that mean the request was rate limited.
+const RATE_LIMIT_SIGNATURES: &[&str] = &["unusual traffic", "RESOURCE_EXHAUSTED", "rateLimitExceeded"];
+
+/// Whether a response with `status` and `body` means, that the request was rate limited.
+pub(crate) fn is_rate_limited(status: StatusCode, body: &[u8]) -> bool {
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return true;
+    }
+
+    let body = String::from_utf8_lossy(body);
+    status == StatusCode::FORBIDDEN && RATE_LIMIT_SIGNATURES.iter().any(|signature| body.contains(signature))
+}
+
+/// The delay of a `Retry-After` header, which is either a number of seconds, or an HTTP date.
+pub(crate) fn retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    let secs = u64::try_from(date.timestamp()).ok()?;
+    Some(Duration::from_secs(secs).saturating_sub(now))
+}
+
+/// Whether any format of `player_response` has a signature, that has to be descrambled with the
+/// player JavaScript.
+fn needs_signature(player_response: &PlayerResponse) -> bool {
+    player_response.streaming_data
+        .iter()
+        .flat_map(|streaming_data| streaming_data.formats.iter().chain(&streaming_data.adaptive_formats))
+        .any(|raw_format| raw_format.signature_cipher.s.is_some())
+}
+
+/// Checks whether or not a [`PlayerResponse`] contains streams, that can be downloaded.
+fn check_streaming_data(player_response: PlayerResponse) -> crate::Result<PlayerResponse> {
+    if !matches!(player_response.playability_status, PlayabilityStatus::Ok { .. }) {
+        return Err(unavailable(player_response.playability_status));
+    }
+
+    match NoStreamsReason::from_player_response(&player_response) {
+        None => {}
+        Some(reason @ (NoStreamsReason::AllDrm | NoStreamsReason::AllSabrOnly)) => {
+            let sabr_expiry = player_response.streaming_data
+                .as_ref()
+                .and_then(|streaming_data| streaming_data.server_abr_expiry());
+            if let Some(sabr_expiry) = sabr_expiry {
+                log::debug!("the server ABR streaming url expires at {}", sabr_expiry);
+            }
+            return Err(Error::NoStreams(reason));
+        }
+        Some(_) => return Err(no_streaming_data(VideoInfo::from_player_response(player_response, false))),
+    }
+
+    Ok(player_response)
+}
+
+/// Deserializes a response of the player endpoint into a [`PlayerResponse`].
+///
+/// Responses for unavailable videos often lack the [`VideoDetails`](crate::VideoDetails), in which
+/// case a [`PlayerResponse`] with only the [`PlayabilityStatus`] is returned.
+fn player_response_or_status(mut response: serde_json::Value, video_id: &IdBuf) -> crate::Result<PlayerResponse> {
+    let err = match PlayerResponse::deserialize(&response) {
+        Ok(player_response) => return Ok(player_response),
+        Err(err) => err,
+    };
+
+    let playability_status = serde_json::from_value::<PlayabilityStatus>(response["playabilityStatus"].take())
+        .map_err(|_| err)?;
+    log::debug!("the player response contained only the playability status: {:?}", playability_status);
+
+    Ok(PlayerResponse {
+        assets: None,
+        captions: None,
+        cards: None,
+        endscreen: None,
+        #[cfg(feature = "microformat")]
+        microformat: None,
+        playability_status,
+        player_config: None,
+        storyboards: None,
+        streaming_data: None,
+        video_details: Arc::new(VideoDetails::unknown(video_id.clone())),
+        tracking_params: response["trackingParams"].as_str().unwrap_or_default().to_owned(),
+    })
+}
+
+/// The error for a video, that is playable, but has no streams (yet).
+pub(crate) fn no_streaming_data(video_info: VideoInfo) -> Error {
+    let player_response = &video_info.player_response;
+    let reason = match (&player_response.playability_status, player_response.live_status()) {
+        (PlayabilityStatus::Ok { messages, .. }, _) if !messages.is_empty() => Some(messages.join(" ")),
+        (_, LiveStatus::UpcomingPremiere) => Some("the premiere did not start yet".to_owned()),
+        (_, LiveStatus::PostLiveProcessing) => Some("the live stream is still being processed".to_owned()),
+        _ => None,
+    };
+
+    Error::NoStreamingData { video_info: Box::new(video_info), reason }
+}
+
+/// The error for a [`PlayabilityStatus`], that's not playable.
+#[inline]
+fn unavailable(playability_status: PlayabilityStatus) -> Error {
+    match playability_status.is_bot_check() {
+        true => Error::BotCheck(Box::new(playability_status)),
+        false => Error::VideoUnavailable(Box::new(playability_status)),
+    }
+}
+
+/// Extracts the signature timestamp from the JavaScript used for descrambling.
+/// YouTube expects it in player requests, to return signatures matching this JavaScript.
+#[inline]
+fn signature_timestamp(js: &str) -> Option<u64> {
+    static PATTERN: Lazy<Regex> = Lazy::new(||
+        Regex::new(r"(?:signatureTimestamp|sts)\s*:\s*(\d{5})").unwrap()
+    );
+
+    PATTERN
+        .captures(js)
+        .and_then(|c| c.get(1))
+        .and_then(|m| m.as_str().parse().ok())
+}
+
+/// Extracts whether or not a particular video is age restricted.
+#[inline]
+fn is_age_restricted(watch_html: &str) -> bool {
+    static PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new("og:restrictions:age").unwrap());
+    PATTERN.is_match(watch_html)
+}
+
+/// Generates the url under which the [`VideoInfo`] of a video can be requested.
+#[inline]
+fn video_info_url(video_id: Id<'_>, watch_url: &Url) -> Url {
+    let params: &[(&str, &str)] = &[
+        ("video_id", video_id.as_str()),
+        ("ps", "default"),
+        ("eurl", watch_url.as_str()),
+        ("hl", "en_US"),
+        ("html5", "1"),
+        ("c", "TVHTML5"),
+        ("cver", "7.20211231"),
+    ];
+    _video_info_url(params)
+}
+
+/// Generates the url under which the [`VideoInfo`] of an age restricted video can be requested.
+#[inline]
+fn video_info_url_age_restricted(video_id: Id<'_>, watch_url: &Url) -> Url {
+    static PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#""sts"\s*:\s*(\d+)"#).unwrap());
+
+    let sts = match PATTERN.captures(watch_url.as_str()) {
+        Some(c) => c.get(1).unwrap().as_str(),
+        None => ""
+    };
+
+    let eurl = format!("https://youtube.googleapis.com/v/{}", video_id.as_str());
+    let params: &[(&str, &str)] = &[
+        ("video_id", video_id.as_str()),
+        ("eurl", eurl.as_str()),
+        ("sts", sts),
+        ("html5", "1"),
+        ("c", "TVHTML5"),
+        ("cver", "7.20211231"),
+    ];
+    _video_info_url(params)
+}
+
+/// Helper for assembling th video info url.
+#[inline]
+fn _video_info_url(params: &[(&str, &str)]) -> Url {
+    Url::parse_with_params(
+        "https://www.youtube.com/get_video_info?",
+        params,
+    ).unwrap()
+}
+
+/// Generates the url under which the JavaScript used for descrambling can be requested.
+#[inline]
+fn js_url(html: &str, base_url: &Url) -> crate::Result<(Url, Option<PlayerResponse>)> {
+    let player_response = get_ytplayer_config(html);
+    let base_js = match player_response {
+        Ok(PlayerResponse { assets: Some(ref assets), .. }) => assets.js.as_str(),
+        _ => get_ytplayer_js(html)?
+    };
+
+    Ok((base_url.join(base_js)?, player_response.ok()))
+}
+
+/// Extracts the [`PlayerResponse`] from the watch html.
+#[inline]
+fn get_ytplayer_config(html: &str) -> crate::Result<PlayerResponse> {
+    static CONFIG_PATTERNS: Lazy<[Regex; 3]> = Lazy::new(|| [
+        Regex::new(r"ytplayer\.config\s*=\s*").unwrap(),
+        Regex::new(r"ytInitialPlayerResponse\s*=\s*").unwrap(),
+        // fixme
+        // pytube handles `setConfig` little bit differently. It parses the entire argument
+        // to `setConfig()` and then uses load json to find `PlayerResponse` inside of it.
+        // We currently handle both the same way, and just deserialize into the `PlayerConfig` enum.
+        // This *should* have the same effect.
+        //
+        // In the future, it may be a good idea, to also handle both cases differently, so we don't
+        // loose performance on deserializing into an enum, but deserialize `CONFIG_PATTERNS` directly
+        // into `PlayerResponse`, and `SET_CONFIG_PATTERNS` into `Args`. The problem currently is, that
+        // I don't know, if CONFIG_PATTERNS can also contain `Args`.
+        Regex::new(r#"yt\.setConfig\(.*['"]PLAYER_CONFIG['"]:\s*"#).unwrap()
+    ]);
+
+    CONFIG_PATTERNS
+        .iter()
+        .find_map(|pattern| {
+            let json = parse_for_object(html, pattern)?;
+            deserialize_ytplayer_config(json).ok()
+        })
+        .ok_or_else(|| Error::UnexpectedResponse(
+            "Could not find ytplayer_config in the watch html.".into()
+        ))
+}
+
+/// Extracts the `ytInitialData` from the watch html.
+fn get_initial_data(html: &str) -> Option<serde_json::Value> {
+    static INITIAL_DATA_PATTERN: Lazy<Regex> = Lazy::new(||
+        Regex::new(r#"(?:window\s*\[\s*["']ytInitialData["']\s*\]|ytInitialData)\s*=\s*"#).unwrap()
+    );
+
+    let json = parse_for_object(html, &INITIAL_DATA_PATTERN)?;
+    serde_json::from_str(json).ok()
+}
+
+/// Extracts a json object from a string starting after a pattern.
+#[inline]
+fn parse_for_object<'a>(html: &'a str, regex: &Regex) -> Option<&'a str> {
+    let json_obj_start = regex.find(html)?.end();
+    json_object(&html[json_obj_start..])
+}
+
+/// Extracts the first complete json object from a string.
+#[inline]
+fn json_object(html: &str) -> Option<&str> {
+    extract_json_object(html).map(|range| &html[range])
+}
+
+/// Deserializes the [`PalyerResponse`] which can be found in the watch html.
+#[inline]
+#[log_derive::logfn(Debug, fmt = "player response: {:?}")]
+#[log_derive::logfn_inputs(Trace, fmt = "player response json: {:?}")]
+fn deserialize_ytplayer_config(json: &str) -> crate::Result<PlayerResponse> {
+    #[derive(Deserialize)]
+    struct Args {
+        player_response: PlayerResponse,
+    }
+
+    // There are multiple possible formats the PlayerResponse could be in. So we basically
+    // have an untagged enum here.
+    // ```rust
+    // #[derive(Deserialize)]
+    // #[serde(untagged)]
+    // enum PlayerConfig {
+    //     Args { args: Args },
+    //     Response(PlayerResponse)
+    // }
+    // ```
+    // The only problem with deserializing this enum is, that we don't get any information about
+    // the cause in case of a failed deserialization. That's why we do this manually here, so that
+    // the log contains information about the error cause.
+
+    let args_err = match serde_json::from_str::<PlayerResponse>(json) {
+        Ok(pr) => return Ok(pr),
+        Err(err) => err,
+    };
+
+    let pr_err = match serde_json::from_str::<Args>(json) {
+        Ok(args) => return Ok(args.player_response),
+        Err(err) => err,
+    };
+
+    Err(Error::JsonDeserialization(serde::de::Error::custom(format_args!(
+        "data did not match any variant of untagged enum PlayerConfig:\n\
+        \tArgs:{args_err}\n\
+        \tPlayerResponse:{pr_err}",
+    ))))
+}
+
+/// Extracts the JavaScript used for descrambling from the watch html.
+#[inline]
+fn get_ytplayer_js(html: &str) -> crate::Result<&str> {
+    static JS_URL_PATTERNS: Lazy<Regex> = Lazy::new(||
+        Regex::new(r"(/s/player/[\w\d]+/[\w\d_/.]+/base\.js)").unwrap()
+    );
+
+    match JS_URL_PATTERNS.captures(html) {
+        Some(function_match) => Ok(function_match.get(1).unwrap().as_str()),
+        None => Err(Error::UnexpectedResponse(
+            "could not extract the ytplayer-javascript url from the watch html".into()
+        ))
+    }
+}
+
+const CONSENT_COOKIE: &str = "CONSENT=YES+; Path=/; Domain=youtube.com; Secure; Expires=Fri, 01 Jan 2038 00:00:00 GMT;";
+const CONSENT_COOKIE_URL: &str = "https://youtube.com";
+
+pub fn recommended_cookies() -> reqwest::cookie::Jar {
+    let url = CONSENT_COOKIE_URL.parse().unwrap();
+
+    let jar = reqwest::cookie::Jar::default();
+    jar.add_cookie_str(CONSENT_COOKIE, &url);
+    jar
+}
+
+pub fn recommended_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+
+    headers.insert(reqwest::header::ACCEPT_LANGUAGE, "en-US,en".parse().unwrap());
+    headers.insert(reqwest::header::USER_AGENT, "Mozilla/5.0".parse().unwrap());
+
+    headers
+}
+
+/// The headers, the official `client` sends with its requests, i.e. its user agent. They can be
+/// passed to [`VideoFetcher::with_headers`], or used as the default headers of a [`Client`]
+/// (see [`ClientBuilder::default_headers`]).
+///
+/// The headers only approximate the ones of the client, so they do not guarantee, that YouTube
+/// does not throttle or block the requests.
+pub fn recommended_headers_for(client: InnertubeClient) -> HeaderMap {
+    let mut headers = recommended_headers();
+
+    headers.insert(reqwest::header::USER_AGENT, client.user_agent().parse().unwrap());
+    if !client.is_app() {
+        headers.insert(reqwest::header::ORIGIN, YOUTUBE_ORIGIN.parse().unwrap());
+        headers.insert(reqwest::header::REFERER, YOUTUBE_URL.parse().unwrap());
+    }
+
+    headers
+}
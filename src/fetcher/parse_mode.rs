@@ -0,0 +1,67 @@
+use crate::{AudioCodec, Error, PlayerResponse, VideoCodec};
+use crate::video_info::player_response::streaming_data::{Quality, QualityLabel};
+
+/// How strictly [`VideoFetcher`](crate::VideoFetcher) treats values, it does not know about (see
+/// [`VideoFetcher::with_parse_mode`](crate::VideoFetcher::with_parse_mode)).
+///
+/// By default, unknown values degrade gracefully: a codec, that `rustube` can't parse, is kept as
+/// [`VideoCodec::Other`] or [`AudioCodec::Other`], and a quality, or quality label, as
+/// [`Quality::Unknown`] or [`QualityLabel::Unknown`], and the stream can still be downloaded.
+/// Monitoring deployments can instead fail fast on them, to learn about API changes before users do.
+///
+/// The other enums of the player response, like the status of a
+/// [`PlayabilityStatus`](crate::video_info::player_response::playability_status::PlayabilityStatus),
+/// reject unknown values in both modes, since `rustube` could not tell whether a video is
+/// available without them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ParseMode {
+    /// Unknown values are kept as `Other`.
+    #[default]
+    Lenient,
+    /// Unknown values fail with [`Error::UnknownVariant`].
+    Strict,
+}
+
+impl ParseMode {
+    /// Whether unknown values are errors.
+    #[inline]
+    pub fn is_strict(&self) -> bool {
+        matches!(self, Self::Strict)
+    }
+
+    /// Checks `player_response` for unknown values, if the mode is [`ParseMode::Strict`].
+    ///
+    /// A codec string only counts as unknown, if it's neither a known video, nor a known audio
+    /// codec, since progressive formats list both.
+    pub(crate) fn check(&self, player_response: &PlayerResponse) -> crate::Result<()> {
+        if !self.is_strict() {
+            return Ok(());
+        }
+
+        let formats = player_response.streaming_data
+            .iter()
+            .flat_map(|streaming_data| streaming_data.formats.iter().chain(&streaming_data.adaptive_formats));
+        for format in formats {
+            if let Quality::Unknown(ref quality) = format.quality {
+                return Err(Error::UnknownVariant { type_name: "Quality", value: quality.clone() });
+            }
+            if let Some(QualityLabel::Unknown(ref label)) = format.quality_label {
+                return Err(Error::UnknownVariant { type_name: "QualityLabel", value: label.clone() });
+            }
+            let unknown = format.mime_type.codecs
+                .iter()
+                .find(|codec| {
+                    matches!(VideoCodec::parse(codec), VideoCodec::Other(_))
+                        && matches!(AudioCodec::parse(codec), AudioCodec::Other(_))
+                });
+            if let Some(codec) = unknown {
+                let type_name = match format.mime_type.mime.type_() {
+                    mime::AUDIO => "AudioCodec",
+                    _ => "VideoCodec",
+                };
+                return Err(Error::UnknownVariant { type_name, value: codec.trim().to_owned() });
+            }
+        }
+        Ok(())
+    }
+}
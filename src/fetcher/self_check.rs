@@ -0,0 +1,161 @@
+use serde::Serialize;
+
+use crate::{Error, IdBuf, VideoFetcher};
+use crate::descrambler::cipher;
+use crate::descrambler::throttling::extract_n_function;
+use crate::metrics::Phase;
+
+use super::{check_streaming_data, get_ytplayer_config, js_url, signature_timestamp};
+
+/// The id of the video [`self_check`](crate::self_check) uses. It's the first video ever
+/// uploaded to YouTube, and is not expected to go away.
+pub const SELF_CHECK_VIDEO_ID: &str = "jNQXAC9IVRw";
+
+/// The version of the set of requests and extractions rustube relies on, which
+/// [`VideoFetcher::self_check`] covers.
+///
+/// It follows semantic versioning: the minor version is increased, when steps are added, and the
+/// major version, when steps are removed or change their meaning. Monitoring systems can use it to
+/// tell, whether reports of different rustube versions are comparable.
+pub const API_CONTRACT_VERSION: &str = "1.0.0";
+
+/// A step of [`VideoFetcher::self_check`], in the order they are run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum SelfCheckStep {
+    /// Requesting the watch page.
+    WatchHtml,
+    /// Extracting the player response from the watch page.
+    PlayerConfig,
+    /// Extracting the url of the player JavaScript from the watch page.
+    JsUrl,
+    /// Requesting the player JavaScript.
+    BaseJs,
+    /// Extracting the signature cipher from the player JavaScript.
+    Cipher,
+    /// Extracting the signature timestamp from the player JavaScript.
+    SignatureTimestamp,
+    /// Extracting the function, that transforms the `n` parameter, from the player JavaScript.
+    NFunction,
+    /// Requesting the player endpoint, which has to return streams.
+    PlayerEndpoint,
+}
+
+/// Whether a [`SelfCheckStep`] passed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    /// The step was not run, since a step it depends on failed.
+    Skipped,
+}
+
+/// The outcome of a single [`SelfCheckStep`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct StepReport {
+    pub step: SelfCheckStep,
+    pub status: StepStatus,
+    /// The error, if the step failed.
+    pub error: Option<String>,
+}
+
+/// The outcome of [`VideoFetcher::self_check`], which can be serialized for monitoring systems.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SelfCheckReport {
+    /// The [`API_CONTRACT_VERSION`] of the rustube version, that created the report.
+    pub contract_version: &'static str,
+    /// The version of rustube, that created the report.
+    pub rustube_version: &'static str,
+    pub video_id: IdBuf,
+    pub steps: Vec<StepReport>,
+}
+
+impl SelfCheckReport {
+    fn new(video_id: IdBuf) -> Self {
+        Self {
+            contract_version: API_CONTRACT_VERSION,
+            rustube_version: env!("CARGO_PKG_VERSION"),
+            video_id,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Whether all steps passed.
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.steps.iter().all(|step| step.status == StepStatus::Passed)
+    }
+
+    /// The steps, that failed. Skipped steps are not included.
+    pub fn failures(&self) -> impl Iterator<Item=&StepReport> {
+        self.steps.iter().filter(|step| step.status == StepStatus::Failed)
+    }
+
+    /// The report of `step`.
+    pub fn step(&self, step: SelfCheckStep) -> Option<&StepReport> {
+        self.steps.iter().find(|report| report.step == step)
+    }
+
+    /// Records the outcome of `step`, which was skipped, if `result` is `None`.
+    fn record<T>(&mut self, step: SelfCheckStep, result: Option<crate::Result<T>>) -> Option<T> {
+        let (status, error, value) = match result {
+            Some(Ok(value)) => (StepStatus::Passed, None, Some(value)),
+            Some(Err(err)) => {
+                log::warn!("self check step {:?} failed: {}", step, err);
+                (StepStatus::Failed, Some(err.to_string()), None)
+            }
+            None => (StepStatus::Skipped, None, None),
+        };
+
+        self.steps.push(StepReport { step, status, error });
+        value
+    }
+}
+
+impl VideoFetcher {
+    /// Checks, whether the requests and extractions rustube relies on still work for this video,
+    /// without downloading any media. Steps, that depend on a failed step, are skipped.
+    ///
+    /// With [`ParseMode::Strict`](crate::ParseMode::Strict), the player responses of the watch page
+    /// and the player endpoint also fail on values, rustube does not know about.
+    ///
+    /// This is the building block of [`self_check`](crate::self_check), which should usually be
+    /// used instead.
+    pub async fn self_check(self) -> SelfCheckReport {
+        use SelfCheckStep::*;
+        let mut report = SelfCheckReport::new(self.video_id.clone());
+
+        let watch_html = report.record(WatchHtml, Some(self.get_html(&self.watch_url, Phase::WatchHtml).await));
+        let watch_html = watch_html.as_deref();
+        report.record(PlayerConfig, watch_html.map(|html| {
+            get_ytplayer_config(html).and_then(|player_response| self.parse_mode.check(&player_response))
+        }));
+        let js_url = report.record(JsUrl, watch_html.map(|html| js_url(html, &self.base_url).map(|(url, _)| url)));
+
+        let js = match js_url {
+            Some(ref js_url) => Some(self.get_html(js_url, Phase::BaseJs).await),
+            None => None,
+        };
+        let js = report.record(BaseJs, js);
+        let js = js.as_deref();
+        report.record(Cipher, js.map(|js| cipher::Cipher::from_js(js).map(drop)));
+        let signature_timestamp = report.record(SignatureTimestamp, js.map(|js| {
+            signature_timestamp(js).ok_or_else(|| Error::UnexpectedResponse(
+                "the player JavaScript did not contain the signature timestamp".into()
+            ))
+        }));
+        report.record(NFunction, js.map(|js| extract_n_function(js).map(drop)));
+
+        // the player endpoint also works without the signature timestamp
+        let player_response = self
+            .request_player(self.first_client(), signature_timestamp, self.po_token.as_deref())
+            .await
+            .and_then(check_streaming_data)
+            .and_then(|player_response| self.parse_mode.check(&player_response));
+        report.record(PlayerEndpoint, Some(player_response));
+
+        report
+    }
+}
@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+
+/// The user agent of [`UserAgentPolicy::ModernChrome`]. It matches the one of
+/// [`InnertubeClient::Web`](crate::InnertubeClient::Web).
+const CHROME_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 \
+    (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
+/// The client hints Chrome sends along with [`CHROME_USER_AGENT`].
+const CHROME_CLIENT_HINTS: [(&str, &str); 3] = [
+    ("sec-ch-ua", r#""Google Chrome";v="131", "Chromium";v="131", "Not_A Brand";v="24""#),
+    ("sec-ch-ua-mobile", "?0"),
+    ("sec-ch-ua-platform", r#""Windows""#),
+];
+
+/// The position in the list of [`UserAgentPolicy::RotatePerRequest`], shared by all fetchers.
+static NEXT_USER_AGENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Decides, which user agent the requests for a video are made with
+/// (see [`VideoFetcher::with_user_agent_policy`](crate::VideoFetcher::with_user_agent_policy)).
+///
+/// The user agent is chosen once per [`VideoFetcher`](crate::VideoFetcher), and then used for all
+/// requests belonging to the video: the watch page, `base.js`, the innertube API (including the
+/// `userAgent` of the client context), and the downloads of its streams. Mismatching user agents
+/// are easy to detect for YouTube.
+///
+/// [`InnertubeClient::Android`](crate::InnertubeClient::Android) and
+/// [`InnertubeClient::Ios`](crate::InnertubeClient::Ios) always identify as their app, since
+/// YouTube ties their streams to the app's user agent.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UserAgentPolicy {
+    /// Always use this user agent.
+    Fixed(String),
+    /// A recent desktop Chrome user agent, including the matching `sec-ch-ua*` client hints.
+    ModernChrome,
+    /// Use the next user agent of the list for each fetched video, round robin.
+    /// Falls back to [`UserAgentPolicy::ModernChrome`], if the list is empty.
+    RotatePerRequest(Vec<String>),
+}
+
+impl UserAgentPolicy {
+    /// Picks the user agent for the next video.
+    pub(crate) fn pick(&self) -> UserAgent {
+        match self {
+            Self::Fixed(user_agent) => UserAgent::new(user_agent.clone()),
+            Self::RotatePerRequest(user_agents) if !user_agents.is_empty() => {
+                let next = NEXT_USER_AGENT.fetch_add(1, Ordering::Relaxed);
+                UserAgent::new(user_agents[next % user_agents.len()].clone())
+            }
+            Self::ModernChrome | Self::RotatePerRequest(_) => UserAgent {
+                value: CHROME_USER_AGENT.to_owned(),
+                client_hints: true,
+            },
+        }
+    }
+}
+
+/// A user agent, that's used for all requests belonging to a video.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct UserAgent {
+    value: String,
+    /// Whether the Chrome client hints are sent along.
+    client_hints: bool,
+}
+
+impl UserAgent {
+    #[inline]
+    pub(crate) fn new(value: String) -> Self {
+        Self { value, client_hints: false }
+    }
+
+    #[inline]
+    pub(crate) fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// The headers of all requests made with this user agent. Values, that are not valid header
+    /// values, are skipped.
+    pub(crate) fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        match HeaderValue::from_str(&self.value) {
+            Ok(value) => { headers.insert(USER_AGENT, value); }
+            Err(_) => log::warn!("the user agent {:?} is not a valid header value", self.value),
+        }
+        if self.client_hints {
+            for (name, value) in CHROME_CLIENT_HINTS {
+                headers.insert(name, HeaderValue::from_static(value));
+            }
+        }
+        headers
+    }
+}
@@ -0,0 +1,193 @@
+//! Naming downloaded files after the video, i.e. `{channel}/{upload_date} - {title} [{id}].{ext}`.
+//!
+//! A [`FilenameTemplate`] is a relative path with placeholders, that are replaced by the
+//! properties of the video and the stream:
+//!
+//! | placeholder     | value                                                                  |
+//! |-----------------|------------------------------------------------------------------------|
+//! | `{id}`          | the id of the video                                                    |
+//! | `{title}`       | the title of the video                                                 |
+//! | `{channel}`     | the name of the channel, that uploaded the video                       |
+//! | `{upload_date}` | the upload date as `YYYYMMDD`, or `NA`, if it's unknown                |
+//! | `{quality}`     | the quality label (i.e. `1080p`), or the audio bitrate (i.e. `128k`)   |
+//! | `{itag}`        | the itag of the stream                                                 |
+//! | `{ext}`         | the file extension of the stream (i.e. `mp4`)                          |
+//!
+//! The upload date is only known with the `microformat` feature.
+//!
+//! Unknown placeholders are rejected, when the template is parsed. Each value is made safe for
+//! file names with [`safe_title_for_filename`](crate::text::safe_title_for_filename) before
+//! it's substituted, so a `/` in a title is replaced, and cannot escape the directory. Only the
+//! `/`s of the template itself separate directories.
+//!
+//! # Example
+//! ```
+//!# use rustube::FilenameTemplate;
+//! let template = FilenameTemplate::parse("{channel}/{title} [{id}].{ext}").unwrap();
+//! assert!(FilenameTemplate::parse("{title}.{extension}").is_err());
+//! ```
+
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::{Error, Result, Stream, VideoInfo};
+use crate::text::{MAX_FILENAME_BYTES, safe_title_for_filename};
+
+/// The placeholders, a [`FilenameTemplate`] supports.
+const PLACEHOLDERS: &[&str] = &["id", "title", "channel", "upload_date", "quality", "itag", "ext"];
+
+/// A template for the path of a downloaded file. See the
+/// [module level documentation](crate::filename_template).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, derive_more::Display)]
+#[display(fmt = "{template}")]
+pub struct FilenameTemplate {
+    template: String,
+    /// The components of the path, separated by `/`.
+    components: Vec<Vec<Segment>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Segment {
+    Literal(String),
+    Placeholder(&'static str),
+}
+
+impl FilenameTemplate {
+    /// Parses `template`.
+    ///
+    /// ### Errors
+    /// - [`Error::InvalidFilenameTemplate`], when the template contains an unknown, or unclosed
+    ///   placeholder, or a `}` without a placeholder.
+    /// - [`Error::InvalidFilenameTemplate`], when the template is empty, absolute, or contains
+    ///   empty, `.`, or `..` components, or a `\\`.
+    pub fn parse(template: &str) -> Result<Self> {
+        let invalid = |reason: Cow<'static, str>| Error::InvalidFilenameTemplate {
+            template: template.to_owned(),
+            reason,
+        };
+
+        let mut components = Vec::new();
+        for component in template.split('/') {
+            if component.contains('\\') {
+                return Err(invalid("directories must be separated by `/`".into()));
+            }
+            if matches!(component, "" | "." | "..") {
+                return Err(invalid(match template.is_empty() {
+                    true => "the template is empty".into(),
+                    false => format!("the path component {:?} is not allowed", component).into(),
+                }));
+            }
+
+            let mut segments = Vec::new();
+            let mut rest = component;
+            while !rest.is_empty() {
+                let start = rest.find(['{', '}']).unwrap_or(rest.len());
+                if start > 0 {
+                    segments.push(Segment::Literal(rest[..start].to_owned()));
+                }
+                rest = &rest[start..];
+                if rest.starts_with('}') {
+                    return Err(invalid("`}` without a placeholder".into()));
+                }
+                if rest.is_empty() {
+                    break;
+                }
+
+                let end = rest
+                    .find('}')
+                    .ok_or_else(|| invalid("unclosed placeholder".into()))?;
+                let name = &rest[1..end];
+                let placeholder = PLACEHOLDERS
+                    .iter()
+                    .find(|&&placeholder| placeholder == name)
+                    .ok_or_else(|| invalid(format!("unknown placeholder {{{}}}", name).into()))?;
+                segments.push(Segment::Placeholder(placeholder));
+                rest = &rest[end + 1..];
+            }
+            components.push(segments);
+        }
+
+        Ok(Self { template: template.to_owned(), components })
+    }
+
+    /// The template, as it was parsed.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    /// The relative path of `stream` of the video with `video_info`.
+    ///
+    /// Path components, that are empty after the substitution, i.e. for a video without a
+    /// channel, become `_`.
+    pub fn render(&self, video_info: &VideoInfo, stream: &Stream) -> PathBuf {
+        self.components
+            .iter()
+            .map(|segments| {
+                let component: String = segments
+                    .iter()
+                    .map(|segment| match segment {
+                        Segment::Literal(literal) => Cow::Borrowed(literal.as_str()),
+                        Segment::Placeholder(name) => Cow::Owned(safe_value(name, video_info, stream)),
+                    })
+                    .collect();
+                match component.trim().is_empty() {
+                    true => "_".to_owned(),
+                    false => component,
+                }
+            })
+            .collect()
+    }
+}
+
+impl FromStr for FilenameTemplate {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(template: &str) -> Result<Self> {
+        Self::parse(template)
+    }
+}
+
+/// The value of the placeholder `name`, made safe for file names. Titles, that leave nothing
+/// usable, fall back to the id.
+fn safe_value(name: &str, video_info: &VideoInfo, stream: &Stream) -> String {
+    let value = safe_title_for_filename(&value(name, video_info, stream), MAX_FILENAME_BYTES);
+    match (name, value.is_empty()) {
+        ("title", true) => video_info.player_response.video_details.video_id.as_str().to_owned(),
+        _ => value,
+    }
+}
+
+/// The value of the placeholder `name`, before it's made safe for file names.
+fn value(name: &str, video_info: &VideoInfo, stream: &Stream) -> String {
+    let video_details = &video_info.player_response.video_details;
+    match name {
+        "id" => video_details.video_id.as_str().to_owned(),
+        "title" => video_details.title.clone(),
+        "channel" => video_details.author.clone(),
+        "upload_date" => upload_date(video_info).unwrap_or_else(|| "NA".to_owned()),
+        "quality" => match (&stream.quality_label, stream.average_bitrate.or(stream.bitrate)) {
+            (Some(quality_label), _) => quality_label.to_string(),
+            (None, Some(bitrate)) => format!("{}k", (bitrate + 500) / 1000),
+            (None, None) => "NA".to_owned(),
+        },
+        "itag" => stream.itag.to_string(),
+        "ext" => stream.mime.subtype().as_str().to_owned(),
+        name => unreachable!("unknown placeholder {}", name),
+    }
+}
+
+#[cfg(feature = "microformat")]
+fn upload_date(video_info: &VideoInfo) -> Option<String> {
+    video_info.player_response.microformat
+        .as_ref()
+        .map(|microformat| microformat.player_microformat_renderer.upload_date.format("%Y%m%d").to_string())
+}
+
+#[cfg(not(feature = "microformat"))]
+#[inline]
+fn upload_date(_: &VideoInfo) -> Option<String> {
+    None
+}
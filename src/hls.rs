@@ -0,0 +1,357 @@
+//! Reading HLS playlists (M3U8), and downloading the segments of one of their variants.
+//!
+//! Live streams have no usable [`Stream`](crate::Stream)s, but an HLS manifest, which YouTube
+//! also keeps serving for a while after the stream ended. Its master playlist lists a variant
+//! playlist per format, whose segments are MPEG-TS files, that can simply be concatenated.
+//!
+//! The media playlist of a running live stream only contains the most recent segments, and is
+//! refreshed regularly, until it's ended with `#EXT-X-ENDLIST`. The playlists of finished
+//! streams are static.
+
+#[cfg(feature = "download")]
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use url::Url;
+
+#[cfg(feature = "download")]
+use crate::dash::{SegmentClient, SegmentProgress};
+use crate::{Error, Result};
+
+/// The maximum number of segments of a single media playlist, so a broken playlist can't make
+/// rustube allocate arbitrary amounts of memory.
+const MAX_SEGMENTS: usize = 1 << 20;
+
+/// A parsed HLS master playlist.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HlsMasterPlaylist {
+    /// The variants (`#EXT-X-STREAM-INF`), in the order of the playlist.
+    pub variants: Vec<HlsVariant>,
+}
+
+impl HlsMasterPlaylist {
+    /// Parses the master playlist `m3u8`, which was requested from `playlist_url`. Relative urls
+    /// in the playlist are resolved against `playlist_url`.
+    ///
+    /// ### Errors
+    /// - [`Error::UnexpectedResponse`], when `m3u8` is not a master playlist.
+    pub fn parse(m3u8: &str, playlist_url: &Url) -> Result<Self> {
+        let mut lines = playlist_lines(m3u8)?;
+        let mut variants = Vec::new();
+
+        while let Some(line) = lines.next() {
+            let attributes = match line.strip_prefix("#EXT-X-STREAM-INF:") {
+                Some(attributes) => attributes,
+                None => continue,
+            };
+            let uri = lines
+                .find(|line| !line.starts_with('#'))
+                .ok_or_else(|| invalid("a variant has no url"))?;
+
+            let mut variant = HlsVariant {
+                url: playlist_url.join(uri)?,
+                bandwidth: None,
+                width: None,
+                height: None,
+                codecs: None,
+            };
+            for (name, value) in parse_attributes(attributes) {
+                match name {
+                    "BANDWIDTH" => variant.bandwidth = Some(parse_attribute(name, value)?),
+                    "CODECS" => variant.codecs = Some(value.to_owned()),
+                    "RESOLUTION" => {
+                        let (width, height) = value
+                            .split_once('x')
+                            .ok_or_else(|| invalid(format!("invalid resolution `{}`", value)))?;
+                        variant.width = Some(parse_attribute(name, width)?);
+                        variant.height = Some(parse_attribute(name, height)?);
+                    }
+                    _ => {}
+                }
+            }
+            variants.push(variant);
+        }
+
+        if variants.is_empty() {
+            return Err(invalid("the master playlist has no variants"));
+        }
+        Ok(Self { variants })
+    }
+
+    /// The variant of the format with the itag `itag`.
+    #[inline]
+    pub fn variant_by_itag(&self, itag: u64) -> Option<&HlsVariant> {
+        self.variants.iter().find(|variant| variant.itag() == Some(itag))
+    }
+
+    /// The variant with the highest bandwidth.
+    #[inline]
+    pub fn best_variant(&self) -> Option<&HlsVariant> {
+        self.variants.iter().max_by_key(|variant| variant.bandwidth)
+    }
+}
+
+/// A variant (format) of an [`HlsMasterPlaylist`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HlsVariant {
+    /// The url of the media playlist of the variant.
+    pub url: Url,
+    /// The peak bitrate of the variant in bits per second.
+    pub bandwidth: Option<u64>,
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    pub codecs: Option<String>,
+}
+
+impl HlsVariant {
+    /// The itag of the variant, which YouTube puts into the path of the playlist url
+    /// (`.../itag/<itag>/...`).
+    pub fn itag(&self) -> Option<u64> {
+        let mut segments = self.url.path_segments()?;
+        segments.find(|&segment| segment == "itag")?;
+        segments.next()?.parse().ok()
+    }
+}
+
+/// A parsed HLS media playlist.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HlsMediaPlaylist {
+    /// The maximal duration of a segment (`#EXT-X-TARGETDURATION`).
+    pub target_duration: Duration,
+    /// The segments, in the order, they have to be concatenated.
+    pub segments: Vec<HlsSegment>,
+    /// Whether the playlist is complete (`#EXT-X-ENDLIST`). Playlists of running live streams are
+    /// not, and have to be requested again for new segments.
+    pub ended: bool,
+}
+
+impl HlsMediaPlaylist {
+    /// Parses the media playlist `m3u8`, which was requested from `playlist_url`. Relative urls in
+    /// the playlist are resolved against `playlist_url`.
+    ///
+    /// ### Errors
+    /// - [`Error::UnexpectedResponse`], when `m3u8` is not a media playlist, or its segments are
+    ///   encrypted, which is not supported.
+    pub fn parse(m3u8: &str, playlist_url: &Url) -> Result<Self> {
+        let mut target_duration = None;
+        let mut sequence = 0;
+        let mut segment_duration = None;
+        let mut segments = Vec::new();
+        let mut ended = false;
+
+        for line in playlist_lines(m3u8)? {
+            if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+                target_duration = Some(Duration::from_secs(parse_attribute("EXT-X-TARGETDURATION", value)?));
+            } else if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+                sequence = parse_attribute("EXT-X-MEDIA-SEQUENCE", value)?;
+            } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+                let duration = value.split(',').next().unwrap_or_default().trim();
+                let duration: f64 = parse_attribute("EXTINF", duration)?;
+                if !duration.is_finite() || duration < 0. {
+                    return Err(invalid(format!("invalid segment duration `{}`", duration)));
+                }
+                segment_duration = Some(Duration::from_secs_f64(duration));
+            } else if let Some(attributes) = line.strip_prefix("#EXT-X-KEY:") {
+                let method = parse_attributes(attributes).find(|&(name, _)| name == "METHOD");
+                if !matches!(method, Some((_, "NONE"))) {
+                    return Err(invalid("encrypted segments are not supported"));
+                }
+            } else if line == "#EXT-X-ENDLIST" {
+                ended = true;
+            } else if line.starts_with("#EXT-X-STREAM-INF:") {
+                return Err(invalid("expected a media playlist, but got a master playlist"));
+            } else if !line.starts_with('#') {
+                if segments.len() >= MAX_SEGMENTS {
+                    return Err(invalid("the playlist contains too many segments"));
+                }
+                segments.push(HlsSegment {
+                    sequence,
+                    duration: segment_duration.take().ok_or_else(|| invalid("a segment has no duration"))?,
+                    url: playlist_url.join(line)?,
+                });
+                sequence += 1;
+            }
+        }
+
+        Ok(Self {
+            target_duration: target_duration.ok_or_else(|| invalid("the playlist has no target duration"))?,
+            segments,
+            ended,
+        })
+    }
+
+    /// The total duration of all segments.
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        self.segments.iter().map(|segment| segment.duration).sum()
+    }
+}
+
+/// A segment of an [`HlsMediaPlaylist`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HlsSegment {
+    /// The media sequence number of the segment, which stays the same, when the playlist of a
+    /// live stream is refreshed.
+    pub sequence: u64,
+    pub duration: Duration,
+    pub url: Url,
+}
+
+/// Downloads the segments of the media playlist at `playlist_url`, and concatenates them in
+/// `<path>.part`, which is renamed to `path` once it's complete, or removed, if the download
+/// fails.
+///
+/// The playlist of a live stream is requested again, until it ends, or `max_segments` were
+/// downloaded. A segment, that's refused with a `403 Forbidden`, is requested once more from the
+/// refreshed playlist, since its url may have expired.
+#[cfg(feature = "download")]
+pub(crate) async fn download_variant(
+    client: &SegmentClient,
+    playlist_url: &Url,
+    path: &Path,
+    max_segments: Option<usize>,
+    mut on_progress: impl FnMut(SegmentProgress),
+) -> Result<u64> {
+    log::debug!("download the segments of the HLS playlist {} to {:?}", playlist_url, path);
+    let part_path = crate::stream::part_path(path);
+
+    let result = match write_segments(client, playlist_url, &part_path, max_segments, &mut on_progress).await {
+        Ok(bytes) => crate::stream::replace_file(&part_path, path)
+            .await
+            .map(|_| bytes)
+            .map_err(Error::from),
+        Err(e) => Err(e),
+    };
+
+    if let Err(ref e) = result {
+        log::error!("failed to download the HLS playlist {}: {:?}", playlist_url, e);
+        if let Err(e) = tokio::fs::remove_file(&part_path).await {
+            log::warn!("failed to remove the partial download {:?}: {}", part_path, e);
+        }
+    }
+    result
+}
+
+#[cfg(feature = "download")]
+async fn write_segments(
+    client: &SegmentClient,
+    playlist_url: &Url,
+    part_path: &Path,
+    max_segments: Option<usize>,
+    on_progress: &mut impl FnMut(SegmentProgress),
+) -> Result<u64> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut file = tokio::fs::File::create(part_path).await?;
+    let mut progress = SegmentProgress {
+        downloaded_segments: 0,
+        total_segments: 0,
+        bytes: 0,
+    };
+    let max_segments = max_segments.unwrap_or(usize::MAX);
+    let mut next_sequence = None;
+    let mut forbidden_sequence = None;
+
+    'refresh: loop {
+        let playlist = get_media_playlist(client, playlist_url).await?;
+        let pending: Vec<&HlsSegment> = playlist.segments
+            .iter()
+            .filter(|segment| next_sequence.is_none_or(|next| segment.sequence >= next))
+            .collect();
+        if let (Some(next), Some(first)) = (next_sequence, pending.first()) {
+            if first.sequence > next {
+                log::warn!("the segments {}..{} left the live window before they were downloaded", next, first.sequence);
+            }
+        }
+        progress.total_segments = max_segments.min(progress.downloaded_segments + pending.len());
+
+        for segment in pending {
+            if progress.downloaded_segments >= max_segments {
+                break 'refresh;
+            }
+
+            log::trace!("get segment {}: {}", segment.sequence, segment.url.as_str());
+            let bytes = match get_segment(client, &segment.url).await {
+                Err(Error::Request(e))
+                if e.status() == Some(reqwest::StatusCode::FORBIDDEN) && forbidden_sequence != Some(segment.sequence) => {
+                    log::warn!("segment {} was refused, refreshing the playlist", segment.sequence);
+                    forbidden_sequence = Some(segment.sequence);
+                    continue 'refresh;
+                }
+                result => result?,
+            };
+            file.write_all(&bytes).await?;
+
+            next_sequence = Some(segment.sequence + 1);
+            progress.bytes += bytes.len() as u64;
+            progress.downloaded_segments += 1;
+            on_progress(progress);
+        }
+
+        if playlist.ended || progress.downloaded_segments >= max_segments {
+            break;
+        }
+        // new segments of a live stream are added about once per target duration
+        tokio::time::sleep(playlist.target_duration / 2).await;
+    }
+
+    file.flush().await?;
+    file.sync_all().await?;
+    Ok(progress.bytes)
+}
+
+/// Requests and parses the media playlist at `url`.
+#[cfg(feature = "download")]
+async fn get_media_playlist(client: &SegmentClient, url: &Url) -> Result<HlsMediaPlaylist> {
+    let m3u8 = client.get_text(url).await?;
+    HlsMediaPlaylist::parse(&m3u8, url)
+}
+
+#[cfg(feature = "download")]
+async fn get_segment(client: &SegmentClient, url: &Url) -> Result<bytes::Bytes> {
+    let bytes = client.get(url).await?.bytes().await?;
+    client.record_media(bytes.len())?;
+    Ok(bytes)
+}
+
+/// The non-empty lines of a playlist, after checking the `#EXTM3U` header.
+fn playlist_lines(m3u8: &str) -> Result<impl Iterator<Item=&str>> {
+    let mut lines = m3u8
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    match lines.next() {
+        Some("#EXTM3U") => Ok(lines),
+        _ => Err(invalid("the playlist does not start with `#EXTM3U`")),
+    }
+}
+
+/// The `NAME=value` pairs of an attribute list, with the quotes of quoted values removed.
+fn parse_attributes(attributes: &str) -> impl Iterator<Item=(&str, &str)> {
+    let mut rest = attributes;
+    std::iter::from_fn(move || {
+        let (name, after) = rest.split_once('=')?;
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                (&quoted[..end], quoted.get(end + 1..).unwrap_or_default())
+            }
+            None => after.split_once(',').map_or((after, ""), |(value, after)| (value, after)),
+        };
+        rest = after.trim_start_matches(',');
+        Some((name.trim(), value))
+    })
+}
+
+fn parse_attribute<T: FromStr>(name: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| invalid(format!("invalid value `{}` of `{}`", value, name)))
+}
+
+#[inline]
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::UnexpectedResponse(format!("invalid HLS playlist: {}", reason.into()).into())
+}
@@ -1,5 +1,5 @@
 use alloc::borrow::{Cow, ToOwned};
-use alloc::string::String;
+use alloc::string::{String, ToString};
 
 #[cfg(feature = "regex")]
 use regex::Regex;
@@ -10,6 +10,7 @@ use serde::{
 use url::Url;
 
 use core::cmp::Ordering;
+use core::time::Duration;
 
 #[cfg(feature = "std")]
 use crate::{Error, Result};
@@ -35,7 +36,7 @@ pub static ID_PATTERNS: [&once_cell::sync::Lazy<Regex>; 5] = [
 #[cfg(feature = "regex")]
 pub static WATCH_URL_PATTERN: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(||
     // watch url    (i.e. https://youtube.com/watch?v=video_id)
-    Regex::new(r"^(https?://)?(www\.)?youtube.\w\w\w?/watch\?v=(?P<id>[a-zA-Z0-9_-]{11})(&.*)?$").unwrap()
+    Regex::new(r"^(https?://)?(www\.)?youtube.\w\w\w?/watch\?([^#]*&)?v=(?P<id>[a-zA-Z0-9_-]{11})([&#].*)?$").unwrap()
 );
 /// A pattern matching the shorts url of a video (i.e. `https://youtube.com/shorts/<ID>`).
 #[cfg(feature = "regex")]
@@ -52,7 +53,7 @@ pub static EMBED_URL_PATTERN: once_cell::sync::Lazy<Regex> = once_cell::sync::La
 #[cfg(feature = "regex")]
 pub static SHARE_URL_PATTERN: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(||
     // share url    (i.e. https://youtu.be/video_id)
-    Regex::new(r"^(https?://)?youtu\.be/(?P<id>[a-zA-Z0-9_-]{11})$").unwrap()
+    Regex::new(r"^(https?://)?youtu\.be/(?P<id>[a-zA-Z0-9_-]{11})([?#].*)?$").unwrap()
 );
 /// A pattern matching the id of a video (`^[a-zA-Z0-9_-]{11}$`).
 #[cfg(feature = "regex")]
@@ -90,7 +91,7 @@ pub struct Id<'a>(Cow<'a, str>);
 #[allow(clippy::should_implement_trait)]
 impl<'a> Id<'a> {
     cfg_if::cfg_if! {
-        if #[cfg(feature = "regex")] {
+        if #[cfg(all(feature = "regex", feature = "std"))] {
             pub fn from_raw(raw: &'a str) -> Result<Self> {
                 ID_PATTERNS
                     .iter()
@@ -106,6 +107,34 @@ impl<'a> Id<'a> {
                     .ok_or(Error::BadIdFormat)
             }
 
+            /// Like [`Id::from_raw`], but also returns the timestamp of the url, if there is one.
+            ///
+            /// The timestamp is taken from the `t` or `start` parameter of the query or the
+            /// fragment, and may have any format supported by [`parse_timestamp`]. Timestamps, that
+            /// cannot be parsed, are ignored, just like YouTube does.
+            ///
+            /// # Example
+            /// ```
+            ///# use rustube::Id;
+            ///# use std::time::Duration;
+            /// let (id, t) = Id::from_raw_with_timestamp("https://youtu.be/nv2wQvn6Wxc?t=1m30s").unwrap();
+            /// assert_eq!(id.as_str(), "nv2wQvn6Wxc");
+            /// assert_eq!(t, Some(Duration::from_secs(90)));
+            /// ```
+            pub fn from_raw_with_timestamp(raw: &'a str) -> Result<(Self, Option<Duration>)> {
+                let id = Self::from_raw(raw)?;
+                let timestamp = raw
+                    .find(['?', '#'])
+                    .map(|start| &raw[start + 1..])
+                    .into_iter()
+                    .flat_map(|params| params.split(['&', '#', '?']))
+                    .filter_map(|param| param.split_once('='))
+                    .filter(|(key, _)| *key == "t" || *key == "start")
+                    .find_map(|(_, value)| parse_timestamp(value));
+
+                Ok((id, timestamp))
+            }
+
             #[inline]
             pub fn from_str(id: &'a str) -> Result<Self> {
                 match ID_PATTERN.is_match(id) {
@@ -234,6 +263,208 @@ impl<'a> Id<'a> {
             .push(self.as_str());
         url
     }
+
+    /// The url of the thumbnail of the video in the [`ThumbnailQuality`] `quality`
+    /// (i.e. `i.ytimg.com/vi/<ID>/maxresdefault.jpg`).
+    ///
+    /// The url is constructed, not fetched, so it works for videos, whose thumbnails YouTube
+    /// didn't list. Not every video has a thumbnail in every quality, though.
+    /// [`ThumbnailQuality::MaxRes`], for example, only exists for videos uploaded in at least 720p.
+    #[inline]
+    #[must_use]
+    pub fn thumbnail_url(&self, quality: ThumbnailQuality) -> Url {
+        let mut url = Url::parse("https://i.ytimg.com/vi")
+            .unwrap();
+        url
+            .path_segments_mut()
+            .unwrap()
+            .push(self.as_str())
+            .push(quality.file_name());
+        url
+    }
+
+    /// The watch url, that starts playing at `timestamp` (i.e. `youtube.com/watch?v=<ID>&t=90s`).
+    ///
+    /// Fractions of a second are truncated.
+    #[inline]
+    #[must_use]
+    pub fn watch_url_at(&self, timestamp: Duration) -> Url {
+        let mut url = self.watch_url();
+        url
+            .query_pairs_mut()
+            .append_pair("t", &format_timestamp(timestamp));
+        url
+    }
+
+    /// The share url, that starts playing at `timestamp` (i.e. `youtu.be/<ID>?t=90s`).
+    ///
+    /// Fractions of a second are truncated.
+    #[inline]
+    #[must_use]
+    pub fn share_url_at(&self, timestamp: Duration) -> Url {
+        let mut url = self.share_url();
+        url
+            .query_pairs_mut()
+            .append_pair("t", &format_timestamp(timestamp));
+        url
+    }
+
+    /// The embed url with the player parameters of `options`
+    /// (i.e. `youtube.com/embed/<ID>?start=90&autoplay=1`).
+    #[must_use]
+    pub fn embed_url_with(&self, options: &EmbedOptions) -> Url {
+        let mut url = self.embed_url();
+        let secs = |duration: Duration| duration.as_secs().to_string();
+
+        let mut params: alloc::vec::Vec<(&str, String)> = alloc::vec::Vec::new();
+        if let Some(start) = options.start {
+            params.push(("start", secs(start)));
+        }
+        if let Some(end) = options.end {
+            params.push(("end", secs(end)));
+        }
+        if options.autoplay {
+            params.push(("autoplay", "1".into()));
+        }
+        if options.loop_ {
+            params.push(("loop", "1".into()));
+            // the embedded player only loops single videos, if they are also passed as playlist
+            params.push(("playlist", self.as_str().into()));
+        }
+        if options.mute {
+            params.push(("mute", "1".into()));
+        }
+
+        if !params.is_empty() {
+            url
+                .query_pairs_mut()
+                .extend_pairs(params);
+        }
+        url
+    }
+}
+
+/// The player parameters of an embed url. See [`Id::embed_url_with`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EmbedOptions {
+    /// Where to start playing (in whole seconds).
+    pub start: Option<Duration>,
+    /// Where to stop playing (in whole seconds).
+    pub end: Option<Duration>,
+    /// Whether to start playing, once the player is loaded. Most browsers only allow this, if the
+    /// video is also muted.
+    pub autoplay: bool,
+    /// Whether to play the video in a loop.
+    pub loop_: bool,
+    /// Whether to mute the video.
+    pub mute: bool,
+}
+
+/// The qualities of the thumbnails, YouTube generates for every video. See [`Id::thumbnail_url`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ThumbnailQuality {
+    /// 120x90 pixels (`default.jpg`).
+    Default,
+    /// 320x180 pixels (`mqdefault.jpg`).
+    Medium,
+    /// 480x360 pixels (`hqdefault.jpg`).
+    High,
+    /// 640x480 pixels (`sddefault.jpg`).
+    Standard,
+    /// 1280x720 pixels (`maxresdefault.jpg`).
+    MaxRes,
+}
+
+impl ThumbnailQuality {
+    /// The file name of the thumbnail, i.e. `maxresdefault.jpg`.
+    #[inline]
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            Self::Default => "default.jpg",
+            Self::Medium => "mqdefault.jpg",
+            Self::High => "hqdefault.jpg",
+            Self::Standard => "sddefault.jpg",
+            Self::MaxRes => "maxresdefault.jpg",
+        }
+    }
+
+    /// The width and height of the thumbnail in pixels.
+    #[inline]
+    pub const fn size(self) -> (u64, u64) {
+        match self {
+            Self::Default => (120, 90),
+            Self::Medium => (320, 180),
+            Self::High => (480, 360),
+            Self::Standard => (640, 480),
+            Self::MaxRes => (1280, 720),
+        }
+    }
+}
+
+/// Parses a YouTube timestamp, like the `t` parameter of watch and share urls.
+///
+/// Supported formats are plain seconds (`90`, `90s`), units (`1m30s`, `1h2m3s`) and clock
+/// times (`01:30`, `1:02:03`). Returns `None` for all other formats.
+///
+/// # Example
+/// ```
+///# use rustube::id::parse_timestamp;
+///# use std::time::Duration;
+/// assert_eq!(parse_timestamp("90"), Some(Duration::from_secs(90)));
+/// assert_eq!(parse_timestamp("1m30s"), Some(Duration::from_secs(90)));
+/// assert_eq!(parse_timestamp("01:30"), Some(Duration::from_secs(90)));
+/// ```
+pub fn parse_timestamp(timestamp: &str) -> Option<Duration> {
+    fn number(digits: &str) -> Option<u64> {
+        match !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            true => digits.parse().ok(),
+            false => None,
+        }
+    }
+
+    if timestamp.contains(':') {
+        let mut parts = timestamp.split(':');
+        let mut secs = number(parts.next()?)?;
+        let mut count = 1;
+        for part in parts {
+            // only the leading part may exceed 59
+            let value = number(part).filter(|value| *value < 60)?;
+            secs = secs.checked_mul(60)?.checked_add(value)?;
+            count += 1;
+        }
+        return match count {
+            2 | 3 => Some(Duration::from_secs(secs)),
+            _ => None,
+        };
+    }
+
+    if let Some(secs) = number(timestamp) {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let mut secs = 0u64;
+    let mut rest = timestamp;
+    // the units have to be in order, and each unit may only occur once
+    let mut units = [('h', 3600), ('m', 60), ('s', 1)].iter();
+    while !rest.is_empty() {
+        let unit_pos = rest.find(|c: char| !c.is_ascii_digit())?;
+        let value = number(&rest[..unit_pos])?;
+        let unit = rest[unit_pos..].chars().next()?;
+        let (_, factor) = units.by_ref().find(|(u, _)| *u == unit)?;
+        secs = secs.checked_add(value.checked_mul(*factor)?)?;
+        rest = &rest[unit_pos + unit.len_utf8()..];
+    }
+
+    match timestamp.is_empty() {
+        true => None,
+        false => Some(Duration::from_secs(secs)),
+    }
+}
+
+/// Formats a timestamp the way YouTube does in its share urls (i.e. `90s`).
+#[inline]
+fn format_timestamp(timestamp: Duration) -> String {
+    alloc::format!("{}s", timestamp.as_secs())
 }
 
 impl IdBuf {
@@ -26,17 +26,20 @@
 //!
 //! That's it:
 //! ```no_run
+//!# #[cfg(feature = "download")]
 //!# #[tokio::main]
 //!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! let url = "https://www.youtube.com/watch?v=Edx9D2yaOGs&ab_channel=CollegeHumor";
 //! let path_to_video = rustube::download_best_quality(url).await?;
 //!# Ok(())
 //!# }
+//!# #[cfg(not(feature = "download"))]
+//!# fn main() {}
 //! ```
 //! And with the `blocking` feature enabled, you don't even have to bring your own runtime:
 //! ```no_run
 //!# fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!# #[cfg(feature = "blocking")]
+//!# #[cfg(all(feature = "blocking", feature = "download"))]
 //!# {
 //! let url = "https://youtu.be/nv2wQvn6Wxc";
 //! let path_to_video = rustube::blocking::download_best_quality(url)?;
@@ -44,6 +47,59 @@
 //!# Ok(())
 //!# }
 //! ```
+//! If you need a little more control, like a target directory, a maximum resolution, or just the
+//! audio, have a look at [`download`] and [`SimpleDownloadOptions`]:
+//! ```no_run
+//!# #[cfg(feature = "download")]
+//!# #[tokio::main]
+//!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!# use rustube::SimpleDownloadOptions;
+//! let url = "https://youtu.be/nv2wQvn6Wxc";
+//! let options = SimpleDownloadOptions::new().target_dir("videos").max_resolution(720);
+//! let path_to_video = rustube::download(url, options).await?;
+//!# Ok(())
+//!# }
+//!# #[cfg(not(feature = "download"))]
+//!# fn main() {}
+//! ```
+//!
+//! ## Quickstart
+//! The [`prelude`] contains the types you'll need most of the time. This example fetches a video,
+//! picks the best stream with audio and video up to 720p, and downloads it, while printing the
+//! progress (downloading with a [`Callback`] requires the `callback` feature):
+//! ```no_run
+//!# #[cfg(feature = "callback")]
+//!# #[tokio::main]
+//!# async fn main() -> rustube::Result<()> {
+//! use rustube::prelude::*;
+//!
+//! let id = Id::from_raw("https://www.youtube.com/watch?v=bKldI-XGHIw")?;
+//! let video: Video = VideoFetcher::from_id(id.into_owned())?
+//!     .fetch()
+//!     .await?
+//!     .descramble()?;
+//!
+//! let stream: &Stream = video
+//!     .streams()
+//!     .iter()
+//!     .filter(|stream| stream.includes_video_track && stream.includes_audio_track)
+//!     .filter(|stream| stream.quality_label.as_ref().is_some_and(|label| *label <= QualityLabel::P720))
+//!     .max_by_key(|stream| stream.rank())
+//!     .ok_or_else(|| Error::NoStreams(video.no_streams_reason()))?;
+//!
+//! let callback = Callback::new().connect_on_progress_closure(|args: CallbackArguments| {
+//!     match args.content_length {
+//!         Some(total) => println!("{} of {} bytes", args.current_chunk, total),
+//!         None => println!("{} bytes", args.current_chunk),
+//!     }
+//! });
+//! let path = stream.download_with_callback(callback).await?;
+//! println!("downloaded {} to {:?}", video.title(), path);
+//!# Ok(())
+//!# }
+//!# #[cfg(not(feature = "callback"))]
+//!# fn main() {}
+//! ```
 //!
 //! ## Getting video information
 //! Of course, there's also the use case, where you want to find out information about a video,
@@ -54,9 +110,10 @@
 //! The [`VideoDescrambler`] returned by [`VideoFetcher::fetch`] will probaply fit your usecase a
 //! lot better:
 //! ```no_run
-//!# use rustube::{Id, VideoFetcher};
+//!# #[cfg(feature = "fetch")]
 //!# #[tokio::main]
 //!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!# use rustube::{Id, VideoFetcher};
 //! let id = Id::from_raw("https://www.youtube.com/watch?v=bKldI-XGHIw")?;
 //! let descrambler = VideoFetcher::from_id(id.into_owned())?
 //!    .fetch()
@@ -66,13 +123,16 @@
 //! let the_only_truth = &video_info.player_response.tracking_params;
 //!# Ok(())
 //!# }
+//!# #[cfg(not(feature = "fetch"))]
+//!# fn main() {}
 //! ```
 //! If, after finding out everything about a video, you suddenly decide downloading it is worth it,
 //! you, of curse, can keep using the [`VideoDescrambler`] for that:
 //! ```no_run
-//!# use rustube::{Id, VideoFetcher};
+//!# #[cfg(feature = "download")]
 //!# #[tokio::main]
 //!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!# use rustube::{Id, VideoFetcher};
 //!# let id = Id::from_raw("https://www.youtube.com/watch?v=bKldI-XGHIw")?;
 //!# let descrambler = VideoFetcher::from_id(id.into_owned())?
 //!#    .fetch()
@@ -81,7 +141,11 @@
 //! let path_to_video = video.best_quality().unwrap().download().await?;
 //!# Ok(())
 //!# }
+//!# #[cfg(not(feature = "download"))]
+//!# fn main() {}
 //! ```
+//! Note, that [`VideoDescrambler::descramble`] is synchronous. [`VideoFetcher::fetch`] already
+//! requested everything descrambling needs, so it does not make any requests.
 //!
 //! ## Maybe something in between?
 //! So then, what does `rustube` offer, if I already know, that I want information as well as
@@ -89,9 +153,10 @@
 //! Those methods provide easy to use shortcuts with no need for first fetching and
 //! then descrambeling the video seperatly:
 //!```no_run
-//!# use rustube::{Video, Id};
+//!# #[cfg(feature = "download")]
 //!# #[tokio::main]
 //!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!# use rustube::{Video, Id};
 //! let id = Id::from_str("hFZFjoX2cGg")?;
 //! let video = Video::from_id(id.into_owned()).await?;
 //!
@@ -103,6 +168,8 @@
 //!    .await?;
 //!# Ok(())
 //!# }
+//!# #[cfg(not(feature = "download"))]
+//!# fn main() {}
 //!```
 //!
 //! ## Choosing something exotic
@@ -118,6 +185,7 @@
 //! For doing so, and to get a little more control over which [`Stream`] of a [`Video`] to download,
 //! we can use [`Video::streams`], the [`Stream`] attributes, and Rusts amazing [`Iterator`] methods:
 //! ```no_run
+//!# #[cfg(feature = "download")]
 //!# #[tokio::main]
 //!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!# use rustube::{Video, Id};
@@ -127,9 +195,11 @@
 //!    .streams()
 //!    .iter()
 //!    .filter(|stream| stream.includes_video_track && stream.includes_audio_track)
-//!    .max_by_key(|stream| stream.quality_label);
+//!    .max_by_key(|stream| stream.quality_label.as_ref());
 //!# Ok(())
 //!# }
+//!# #[cfg(not(feature = "download"))]
+//!# fn main() {}
 //!```
 //!
 //! Note, that often the video-audio streams have slightly worse quality than the video-only or
@@ -153,6 +223,19 @@
 //!
 //! The [`Callback`] struct can take up to one `on_progress` method and one `on_complete` method.
 //!
+//! If you don't want to write the stream to a file at all, i.e. to play it while it's
+//! downloading, [`Stream::open_reader`] returns a [`StreamReader`], which implements
+//! [`AsyncRead`](tokio::io::AsyncRead). It downloads the stream in the background, up to a
+//! configurable number of bytes ahead of the reader (see [`Stream::open_reader_with_prefetch`]).
+//! To forward the chunks as they arrive, i.e. in a proxy, [`Stream::bytes_stream`] returns the
+//! same reader as a [`futures::Stream`](tokio_stream::Stream) of [`Bytes`](bytes::Bytes).
+//! `examples/play_audio` shows how to play the audio of a video using
+//! [rodio](https://docs.rs/rodio).
+//!
+//! To archive a video, [`Video::download_all_to_dir`] downloads all of its streams (or the ones
+//! selected by [`ArchiveOptions::filter`]) into one directory per video, next to a
+//! [`metadata.json`](archive::ArchiveMetadata) describing them.
+//!
 //! For even more control, or when you just want to access the videos URL, have a look at the
 //! [`url`](crate::video_info::player_response::streaming_data::SignatureCipher::url) field inside
 //! of [`Stream::signature_cipher`]. This field contains the video URL of that particular Stream.
@@ -189,6 +272,15 @@
 //!   , so you don't have to care about it yourself. (Keep in mind, that this feature does not enable
 //!   any of the other features above automatically)
 //! - `callback`: Enables to add callbacks to downlaods and the [`Callback`] struct itself
+//! - `remux`: Enables [`postprocess::remux_fmp4_to_mp4`], which rewrites fragmented mp4 downloads
+//!   (see [`Stream::needs_remux_for_playback`]) into progressive mp4 files
+//! - `cache`: Enables the [`cache`] module, which caches the responses of [`VideoFetcher`] on
+//!   disk (see [`VideoFetcher::with_response_cache`])
+//! - `serialize`: Enables the [`serialize`] module, which serializes [`Video`]s and [`Stream`]s
+//!   with only the fields of an [`OutputLevel`](serialize::OutputLevel), i.e. only the stream URLs
+//! - `search`: Enables the [`search`] module, which searches YouTube for videos (see [`Search`])
+//! - `comments`: Enables the [`comments`] module, which streams the top-level comments of a video
+//!   (see [`Comments`])
 //!
 //!
 //! [view count]: crate::video_info::player_response::video_details::VideoDetails::view_count
@@ -201,29 +293,73 @@ extern crate alloc;
 #[cfg(feature = "tokio")]
 pub use tokio;
 pub use url;
+#[cfg(feature = "fetch")]
 pub use reqwest;
 
+#[cfg(feature = "download")]
+pub use crate::archive::{ArchiveOptions, ArchiveReport};
+#[cfg(feature = "boa")]
+pub use crate::descrambler::BoaRuntime;
+#[cfg(feature = "descramble")]
+pub use crate::descrambler::{JsRuntime, VideoDescrambler};
+#[cfg(feature = "download")]
+pub use crate::download_options::SimpleDownloadOptions;
 #[cfg(feature = "descramble")]
-pub use crate::descrambler::VideoDescrambler;
+pub use crate::duration::DurationReport;
 #[cfg(feature = "std")]
 pub use crate::error::Error;
 #[cfg(feature = "fetch")]
-pub use crate::fetcher::VideoFetcher;
-pub use crate::id::{Id, IdBuf};
+pub use crate::error::NoStreamsReason;
+#[cfg(feature = "fetch")]
+pub use crate::fetcher::{
+    FetchFilter, FetchOutcome, FilterDecision, InnertubeClient, ParseMode, SelfCheckReport, UserAgentPolicy, VideoFetcher,
+};
+#[cfg(feature = "download")]
+pub use crate::fetcher::{fetch_many, FetchManyOptions};
+#[cfg(feature = "descramble")]
+pub use crate::filename_template::FilenameTemplate;
+pub use crate::id::{EmbedOptions, Id, IdBuf, ThumbnailQuality};
+#[cfg(feature = "fetch")]
+pub use crate::locale::{CountryCode, LanguageTag};
+#[cfg(feature = "fetch")]
+pub use crate::metrics::{Metrics, MetricsReport, Phase};
+#[cfg(feature = "fetch")]
+pub use crate::transfer::TransferStats;
+#[cfg(feature = "download")]
+pub use crate::shutdown::ShutdownHandle;
+#[cfg(feature = "search")]
+pub use crate::search::{Search, SearchResults, SearchVideo};
+#[cfg(feature = "comments")]
+pub use crate::comments::{Comment, Comments, CommentSort};
 #[cfg(feature = "regex")]
 pub use crate::id::{EMBED_URL_PATTERN, ID_PATTERN, ID_PATTERNS, SHARE_URL_PATTERN, WATCH_URL_PATTERN};
 #[cfg(feature = "callback")]
 pub use crate::stream::callback::{Callback, CallbackArguments, OnCompleteType, OnProgressType};
 #[cfg(feature = "stream")]
 pub use crate::stream::Stream;
+#[cfg(feature = "stream")]
+pub use crate::stream::codec::{AudioCodec, CodecSupport, VideoCodec};
+#[cfg(feature = "stream")]
+pub use crate::stream::filter::StreamQuery;
+#[cfg(feature = "stream")]
+pub use crate::stream::rank::StreamRank;
+#[cfg(feature = "download")]
+pub use crate::stream::{DownloadSummary, hash::HashAlgorithm};
+#[cfg(feature = "download")]
+pub use crate::stream::reader::StreamReader;
+#[cfg(feature = "download")]
+pub use crate::stream::chunked::ChunkedDownloadOptions;
+#[cfg(feature = "download")]
+pub use crate::stream::retry::RetryPolicy;
 #[cfg(feature = "descramble")]
 pub use crate::video::Video;
 #[doc(inline)]
 #[cfg(feature = "fetch")]
 pub use crate::video_info::{
+    extended_metadata::ExtendedMetadata,
     player_response::{
         PlayerResponse,
-        video_details::VideoDetails,
+        video_details::{LiveStatus, VideoDetails},
     },
     VideoInfo,
 };
@@ -237,6 +373,7 @@ pub type Result<T, E = Error> = core::result::Result<T, E>;
 
 #[cfg(feature = "blocking")]
 pub mod blocking;
+pub mod prelude;
 #[doc(hidden)]
 #[cfg(feature = "std")]
 pub mod error;
@@ -256,52 +393,166 @@ pub mod descrambler;
 #[doc(hidden)]
 #[cfg(feature = "descramble")]
 pub mod video;
+#[doc(hidden)]
+#[cfg(feature = "download")]
+pub mod download_options;
+#[cfg(feature = "descramble")]
+pub mod duration;
+#[cfg(feature = "descramble")]
+pub mod filename_template;
+#[cfg(feature = "download")]
+pub mod archive;
+#[cfg(feature = "download")]
+pub mod shutdown;
+#[cfg(feature = "remux")]
+pub mod postprocess;
+#[cfg(feature = "package")]
+pub mod package;
+#[cfg(feature = "cache")]
+pub mod cache;
+#[cfg(feature = "serialize")]
+pub mod serialize;
+#[cfg(feature = "search")]
+pub mod search;
+#[cfg(feature = "comments")]
+pub mod comments;
+#[cfg(feature = "fetch")]
+pub mod captions;
+#[cfg(feature = "fetch")]
+pub mod chapters;
+#[cfg(feature = "fetch")]
+pub mod dash;
+#[cfg(feature = "fetch")]
+pub mod hls;
+
+#[cfg(feature = "fetch")]
+pub mod locale;
+#[cfg(feature = "fetch")]
+pub mod metrics;
+#[cfg(feature = "fetch")]
+pub mod parser;
+#[cfg(feature = "fetch")]
+pub mod text;
+#[cfg(feature = "fetch")]
+pub mod transfer;
 
 #[cfg(feature = "fetch")]
 mod serde_impl;
+#[cfg(feature = "fetch")]
+mod xml;
+
+/// Downloads a YouTube video in one call, covering the most common download scenarios.
+///
+/// Takes an arbitrary video identifier, like any video URL, or the video id, and downloads the
+/// stream, that matches the [`SimpleDownloadOptions`] best. Returns the path of the downloaded file.
+///
+/// # Example
+/// ```no_run
+///# use rustube::SimpleDownloadOptions;
+///# #[tokio::main]
+///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let path = rustube::download(
+///     "https://youtube.com/watch?v=5jlI4uzZGjU",
+///     SimpleDownloadOptions::new().audio_only(true),
+/// ).await?;
+///# Ok(())
+///# }
+/// ```
+///
+/// ### Cancellation
+/// The download is driven by the returned future. Dropping it cancels the download, but leaves
+/// the partially downloaded `<FILENAME>.part` file behind.
+///
+/// ### Errors
+/// - When `video_identifier` is not a valid video identifier.
+/// - When fetching or descrambling the video fails (see [`Video::from_id`]).
+/// - [`Error::NoStreams`], when no stream matches the options.
+/// - When the download itself fails. In this case the partially downloaded file is removed, unless
+///   [`SimpleDownloadOptions::keep_partial`] is set. An existing file at the target path is only
+///   replaced, once the download is complete.
+#[cfg(all(feature = "download", feature = "regex"))]
+pub async fn download(
+    video_identifier: &str,
+    options: SimpleDownloadOptions<'_>,
+) -> Result<std::path::PathBuf> {
+    let id = Id::from_raw(video_identifier)?;
+    let video = Video::from_id(id.into_owned()).await?;
+    options.download(&video).await.map(|download| download.path)
+}
 
 /// The absolute most straightforward way of downloading a YouTube video in high quality!
 ///
 /// Takes an arbitrary video identifier, like any video URL, or the video id, and downloads
-/// the video to `<VIDEO_ID>.mp4` in the current working directory.
+/// the video to `<VIDEO_ID>.<extension>` (usually `<VIDEO_ID>.mp4`) in the current working directory.
 ///
-/// For more control over the download process have a look at the [`crate`] level documentation,
-/// or at the [`Video`] struct.
+/// For more control over the download process have a look at [`download`], the [`crate`] level
+/// documentation, or at the [`Video`] struct.
 #[cfg(all(feature = "download", feature = "regex"))]
 pub async fn download_best_quality(video_identifier: &str) -> Result<std::path::PathBuf> {
-    let id = Id::from_raw(video_identifier)?;
-    Video::from_id(id.into_owned())
-        .await?
-        .best_quality()
-        .ok_or(Error::NoStreams)?
-        .download()
-        .await
+    download(video_identifier, SimpleDownloadOptions::new()).await
 }
 
 /// The absolute most straightforward way of downloading a YouTube video in low quality!
 ///
 /// Takes an arbitrary video identifier, like any video URL, or the video id, and downloads
-/// the video to `<VIDEO_ID>.mp4` in the current working directory.
+/// the video to `<VIDEO_ID>.<extension>` (usually `<VIDEO_ID>.mp4`) in the current working directory.
 ///
-/// For more control over the download process have a look at the [`crate`] level documentation,
-/// or at the [`Video`] struct.
+/// For more control over the download process have a look at [`download`], the [`crate`] level
+/// documentation, or at the [`Video`] struct.
 #[cfg(all(feature = "download", feature = "regex"))]
 pub async fn download_worst_quality(video_identifier: &str) -> Result<std::path::PathBuf> {
     let id = Id::from_raw(video_identifier)?;
-    Video::from_id(id.into_owned())
-        .await?
+    let video = Video::from_id(id.into_owned()).await?;
+    video
         .worst_quality()
-        .ok_or(Error::NoStreams)?
+        .ok_or_else(|| Error::NoStreams(video.no_streams_reason()))?
         .download()
         .await
 }
 
+/// Checks, whether rustube still works with YouTube, without downloading any media.
+///
+/// Runs all [`SelfCheckStep`](fetcher::SelfCheckStep)s, like requesting the watch page, or
+/// extracting the signature cipher from the player JavaScript, for the video
+/// [`SELF_CHECK_VIDEO_ID`](fetcher::SELF_CHECK_VIDEO_ID), and reports, which of them passed. If no
+/// `client` is passed, one with the [recommended](fetcher::ClientBuilder::recommended) settings
+/// is used.
+///
+/// # Example
+/// ```no_run
+///# #[tokio::main]
+///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let report = rustube::self_check(None).await?;
+/// for failure in report.failures() {
+///     eprintln!("{:?} failed: {:?}", failure.step, failure.error);
+/// }
+///# Ok(())
+///# }
+/// ```
+///
+/// ### Errors
+/// - When building the default [`Client`](reqwest::Client) fails.
+///
+/// Failing steps are not errors, but part of the [`SelfCheckReport`].
+#[cfg(feature = "fetch")]
+pub async fn self_check(client: Option<reqwest::Client>) -> Result<SelfCheckReport> {
+    let id = Id::from_str(fetcher::SELF_CHECK_VIDEO_ID)?.into_owned();
+    let fetcher = match client {
+        Some(client) => VideoFetcher::from_id_with_client(id, client),
+        None => VideoFetcher::from_id(id)?,
+    };
+    Ok(fetcher.self_check().await)
+}
+
 /// A trait for collecting iterators into arbitrary, in particular fixed-sized, types.
+#[cfg(feature = "fetch")]
 trait TryCollect<T>: Iterator {
     fn try_collect(self) -> Option<T>;
+    #[allow(dead_code)]
     fn try_collect_lossy(self) -> Option<T> where Self: Sized { None }
 }
 
+#[cfg(feature = "fetch")]
 impl<T> TryCollect<(T::Item, )> for T
     where T: Iterator {
     #[inline]
@@ -318,6 +569,7 @@ impl<T> TryCollect<(T::Item, )> for T
     }
 }
 
+#[cfg(feature = "fetch")]
 impl<T> TryCollect<(T::Item, T::Item)> for T
     where T: Iterator {
     #[inline]
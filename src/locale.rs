@@ -0,0 +1,241 @@
+use core::fmt;
+use core::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Error, Result};
+
+/// An ISO 3166-1 alpha-2 country code, i.e. `DE` or `US`.
+///
+/// Parsing via [`FromStr`] is case-insensitive and strict. Deserialization is lenient: values,
+/// that are not two ASCII letters, are preserved as they are (see [`CountryCode::is_valid`]),
+/// since YouTube occasionally includes pseudo codes. Valid codes are always upper case.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CountryCode(String);
+
+impl CountryCode {
+    /// Creates a [`CountryCode`] from any string. If `raw` is not a valid country code, it's
+    /// preserved as is.
+    pub fn from_raw(raw: impl Into<String>) -> Self {
+        let mut raw = raw.into();
+        if Self::is_valid_code(&raw) {
+            raw.make_ascii_uppercase();
+        }
+        Self(raw)
+    }
+
+    /// Whether the code consists of exactly two ASCII letters.
+    ///
+    /// This does not check, whether the code is actually assigned to a country.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        Self::is_valid_code(&self.0)
+    }
+
+    /// The code, upper case if it's valid, and as it was received otherwise.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[inline]
+    fn is_valid_code(code: &str) -> bool {
+        code.len() == 2 && code.bytes().all(|b| b.is_ascii_alphabetic())
+    }
+}
+
+impl FromStr for CountryCode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::is_valid_code(s) {
+            true => Ok(Self(s.to_ascii_uppercase())),
+            false => Err(Error::BadLocaleFormat(s.to_owned())),
+        }
+    }
+}
+
+impl PartialEq<str> for CountryCode {
+    /// Compares case-insensitively, so `"de"` equals `DE`.
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.0.eq_ignore_ascii_case(other)
+    }
+}
+
+impl PartialEq<&str> for CountryCode {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl fmt::Display for CountryCode {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for CountryCode {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for CountryCode {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from_raw)
+    }
+}
+
+/// A BCP 47 language tag, i.e. `en`, `en-US` or `zh-Hant-TW`.
+///
+/// Only the structure is checked: a primary language subtag of two or three letters, followed by
+/// subtags of one to eight ASCII letters or digits. Valid tags are normalized to the canonical
+/// casing (`en-US`, `zh-Hant`), and `_` is accepted as a separator (`en_us`).
+///
+/// Like [`CountryCode`], parsing via [`FromStr`] is strict, while deserialization preserves
+/// invalid tags as they are.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LanguageTag(String);
+
+impl LanguageTag {
+    /// Creates a [`LanguageTag`] from any string. If `raw` is not a valid language tag, it's
+    /// preserved as is.
+    pub fn from_raw(raw: impl Into<String>) -> Self {
+        let raw = raw.into();
+        match Self::normalize(&raw) {
+            Some(tag) => Self(tag),
+            None => Self(raw),
+        }
+    }
+
+    /// Whether the tag is structurally valid.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        Self::normalize(&self.0).is_some()
+    }
+
+    /// The tag, normalized if it's valid, and as it was received otherwise.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The primary language subtag, i.e. `en` for `en-US`.
+    #[inline]
+    pub fn primary(&self) -> &str {
+        self.subtags().next().unwrap_or_default()
+    }
+
+    /// The region subtag, i.e. `US` for `en-US`, if there is one.
+    pub fn region(&self) -> Option<&str> {
+        self.subtags()
+            .skip(1)
+            .take_while(|subtag| subtag.len() > 1)
+            .find(|subtag| is_region(subtag))
+    }
+
+    /// Whether one of the tags is a prefix of the other, so `en` matches `en-US`, and `en-US`
+    /// matches `en`, but `en-US` doesn't match `en-GB`. Tags are compared case-insensitively.
+    pub fn matches(&self, other: &LanguageTag) -> bool {
+        self
+            .subtags()
+            .zip(other.subtags())
+            .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    }
+
+    #[inline]
+    fn subtags(&self) -> impl Iterator<Item=&str> {
+        self.0.split(['-', '_'])
+    }
+
+    fn normalize(raw: &str) -> Option<String> {
+        let mut subtags = raw.split(['-', '_']);
+
+        let primary = subtags.next()?;
+        if !(2..=3).contains(&primary.len()) || !primary.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        let mut tag = primary.to_ascii_lowercase();
+        let mut extension = false;
+        for subtag in subtags {
+            if subtag.is_empty() || subtag.len() > 8 || !subtag.bytes().all(|b| b.is_ascii_alphanumeric()) {
+                return None;
+            }
+
+            tag.push('-');
+            // after a singleton (i.e. `x-` for private use), the casing is not defined
+            extension |= subtag.len() == 1;
+            if extension {
+                tag.push_str(&subtag.to_ascii_lowercase());
+            } else if is_region(subtag) {
+                tag.push_str(&subtag.to_ascii_uppercase());
+            } else if subtag.len() == 4 && subtag.bytes().all(|b| b.is_ascii_alphabetic()) {
+                // script, i.e. `Hant`
+                tag.push_str(&subtag[..1].to_ascii_uppercase());
+                tag.push_str(&subtag[1..].to_ascii_lowercase());
+            } else {
+                tag.push_str(&subtag.to_ascii_lowercase());
+            }
+        }
+
+        Some(tag)
+    }
+}
+
+#[inline]
+fn is_region(subtag: &str) -> bool {
+    (subtag.len() == 2 && subtag.bytes().all(|b| b.is_ascii_alphabetic()))
+        || (subtag.len() == 3 && subtag.bytes().all(|b| b.is_ascii_digit()))
+}
+
+impl FromStr for LanguageTag {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::normalize(s)
+            .map(Self)
+            .ok_or_else(|| Error::BadLocaleFormat(s.to_owned()))
+    }
+}
+
+impl PartialEq<str> for LanguageTag {
+    /// Compares case-insensitively, so `"en-us"` equals `en-US`.
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.0.eq_ignore_ascii_case(other)
+    }
+}
+
+impl PartialEq<&str> for LanguageTag {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl fmt::Display for LanguageTag {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for LanguageTag {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for LanguageTag {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from_raw)
+    }
+}
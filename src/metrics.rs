@@ -0,0 +1,250 @@
+//! Opt-in timing and transfer metrics, i.e. for finding out where the time per video goes.
+//!
+//! Pass a [`Metrics`] collector to [`VideoFetcher::with_metrics`](crate::VideoFetcher::with_metrics).
+//! It's handed on to the [`VideoDescrambler`](crate::VideoDescrambler), the [`Video`](crate::Video)
+//! and its [`Stream`](crate::Stream)s, so all phases of fetching, descrambling and downloading a
+//! video are recorded. [`VideoInfo::metrics`](crate::VideoInfo::metrics) and
+//! [`Video::metrics`](crate::Video::metrics) return a snapshot of the recorded [`MetricsReport`].
+//!
+//! Without a collector, nothing is recorded, and the clock is never read.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{DurationSecondsWithFrac, serde_as};
+
+/// A phase of fetching, descrambling or downloading a video.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Requesting the watch page.
+    WatchHtml,
+    /// Requesting the embed page (only for age restricted videos).
+    EmbedHtml,
+    /// Requesting the player response from the innertube API (once per tried client).
+    PlayerApi,
+    /// Requesting the data of the watch page from the innertube API (see
+    /// [`VideoFetcher::fetch_extended_metadata`](crate::VideoFetcher::fetch_extended_metadata)).
+    NextApi,
+    /// Requesting the JavaScript of the player.
+    BaseJs,
+    /// Descrambling the signatures and `n` parameters of the stream URLs.
+    Descramble,
+    /// Downloading streams (once per download).
+    Download,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Phase::WatchHtml => "watch html",
+            Phase::EmbedHtml => "embed html",
+            Phase::PlayerApi => "player api",
+            Phase::NextApi => "next api",
+            Phase::BaseJs => "base.js",
+            Phase::Descramble => "descramble",
+            Phase::Download => "download",
+        })
+    }
+}
+
+/// A monotonic clock, used by [`Metrics`] to measure durations.
+pub trait Clock: Send + Sync {
+    /// The time passed since an arbitrary, but fixed, point in time.
+    fn now(&self) -> Duration;
+}
+
+/// The default [`Clock`], backed by [`Instant`].
+#[derive(Debug)]
+struct InstantClock(Instant);
+
+impl Clock for InstantClock {
+    #[inline]
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// The accumulated metrics of a single [`Phase`].
+#[serde_as]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PhaseMetrics {
+    /// How often the phase was entered, i.e. the number of requests.
+    pub count: u64,
+    /// The total wall time spent in the phase.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub duration: Duration,
+    /// The total number of bytes transferred in the phase.
+    pub bytes: u64,
+}
+
+/// A snapshot of all metrics recorded by a [`Metrics`] collector.
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct MetricsReport {
+    /// The metrics of all phases, that were entered at least once.
+    pub phases: BTreeMap<Phase, PhaseMetrics>,
+    /// The sum of the durations of all phases.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub total_duration: Duration,
+    /// The sum of the bytes transferred in all phases.
+    pub total_bytes: u64,
+    /// How often YouTube rate limited a request (see [`Error::RateLimited`](crate::Error::RateLimited)).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub rate_limit_hits: u64,
+    /// How often the signature cipher was parsed from the player JavaScript, instead of being
+    /// taken from the cache of parsed ciphers.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub cipher_parses: u64,
+}
+
+#[inline]
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+/// A thread safe collector of [`PhaseMetrics`]. See the [module level documentation](self).
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct Metrics {
+    #[derivative(Debug = "ignore")]
+    clock: Box<dyn Clock>,
+    phases: Mutex<BTreeMap<Phase, PhaseMetrics>>,
+    rate_limit_hits: AtomicU64,
+    cipher_parses: AtomicU64,
+}
+
+impl Default for Metrics {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// Creates an empty collector, that measures the wall time.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_clock(InstantClock(Instant::now()))
+    }
+
+    /// Creates an empty collector, that uses `clock` to measure durations.
+    #[inline]
+    pub fn with_clock(clock: impl Clock + 'static) -> Self {
+        Self {
+            clock: Box::new(clock),
+            phases: Mutex::default(),
+            rate_limit_hits: AtomicU64::new(0),
+            cipher_parses: AtomicU64::new(0),
+        }
+    }
+
+    /// Starts measuring `phase`. The measurement is recorded, once the returned [`PhaseTimer`]
+    /// is dropped.
+    #[inline]
+    pub fn start(&self, phase: Phase) -> PhaseTimer<'_> {
+        PhaseTimer::start(Some(self), phase)
+    }
+
+    /// Records one occurrence of `phase`, that took `duration`, and transferred `bytes`.
+    pub fn record(&self, phase: Phase, duration: Duration, bytes: u64) {
+        let mut phases = self.phases.lock().unwrap();
+        let metrics = phases.entry(phase).or_default();
+        metrics.count += 1;
+        metrics.duration += duration;
+        metrics.bytes += bytes;
+    }
+
+    /// Records, that YouTube rate limited a request.
+    #[inline]
+    pub fn record_rate_limit(&self) {
+        self.rate_limit_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// How often YouTube rate limited a request so far.
+    #[inline]
+    pub fn rate_limit_hits(&self) -> u64 {
+        self.rate_limit_hits.load(Ordering::Relaxed)
+    }
+
+    /// Records, that the signature cipher was parsed from the player JavaScript.
+    #[inline]
+    pub fn record_cipher_parse(&self) {
+        self.cipher_parses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// How often the signature cipher was parsed from the player JavaScript so far. Descrambling
+    /// videos, that share the same player, parses it only once.
+    #[inline]
+    pub fn cipher_parses(&self) -> u64 {
+        self.cipher_parses.load(Ordering::Relaxed)
+    }
+
+    /// The metrics of `phase`, recorded so far.
+    #[inline]
+    pub fn phase(&self, phase: Phase) -> PhaseMetrics {
+        self.phases
+            .lock()
+            .unwrap()
+            .get(&phase)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// A snapshot of all metrics recorded so far.
+    pub fn report(&self) -> MetricsReport {
+        let phases = self.phases.lock().unwrap().clone();
+        MetricsReport {
+            total_duration: phases.values().map(|metrics| metrics.duration).sum(),
+            total_bytes: phases.values().map(|metrics| metrics.bytes).sum(),
+            phases,
+            rate_limit_hits: self.rate_limit_hits(),
+            cipher_parses: self.cipher_parses(),
+        }
+    }
+}
+
+/// Measures a single occurrence of a [`Phase`], and records it on drop.
+///
+/// Timers are also recorded, when the phase fails, so failed requests count as well.
+#[derive(Debug)]
+#[must_use = "the phase is recorded, when the timer is dropped"]
+pub struct PhaseTimer<'a> {
+    metrics: Option<&'a Metrics>,
+    phase: Phase,
+    start: Duration,
+    bytes: u64,
+}
+
+impl<'a> PhaseTimer<'a> {
+    /// Starts a timer, that does nothing, if there are no `metrics`.
+    #[inline]
+    pub(crate) fn start(metrics: Option<&'a Metrics>, phase: Phase) -> Self {
+        Self {
+            start: metrics.map_or(Duration::ZERO, |metrics| metrics.clock.now()),
+            metrics,
+            phase,
+            bytes: 0,
+        }
+    }
+
+    /// Adds `bytes` to the transferred bytes of this occurrence.
+    #[inline]
+    pub fn add_bytes(&mut self, bytes: u64) {
+        self.bytes += bytes;
+    }
+}
+
+impl Drop for PhaseTimer<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(metrics) = self.metrics {
+            let duration = metrics.clock.now().saturating_sub(self.start);
+            metrics.record(self.phase, duration, self.bytes);
+        }
+    }
+}
@@ -0,0 +1,564 @@
+//! Downloading the video, the audio, subtitles and chapters of a video into a single file.
+//!
+//! [`Video::download_package_to`] downloads the best video and audio streams, and the selected
+//! caption tracks to a temporary directory next to the target file, and muxes them with the
+//! `ffmpeg` binary, which has to be installed. The chapters are embedded from an `ffmetadata`
+//! file (see [`ffmetadata`]).
+//!
+//! The `ffmpeg` command line is built by [`MuxJob`], which can also be used on its own, i.e. to
+//! mux streams, that were downloaded in a different way.
+//!
+//! # Example
+//! ```no_run
+//!# use rustube::{Id, Video};
+//!# use rustube::package::{Container, PackageOptions};
+//!# #[tokio::main]
+//!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!# let video = Video::from_id(Id::from_str("5jlI4uzZGjU")?.into_owned()).await?;
+//! let options = PackageOptions::new()
+//!     .subtitle_langs(["en", "de"])
+//!     .container(Container::Mkv)
+//!     .on_progress(|progress| println!("{:?}", progress));
+//! video.download_package_to("video.mkv", options).await?;
+//!# Ok(())
+//!# }
+//! ```
+
+use std::ffi::{OsStr, OsString};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::process::Command;
+
+use crate::{Callback, Error, Result, Stream, Video};
+use crate::captions::CaptionTrack;
+use crate::chapters::Chapter;
+use crate::duration::DEFAULT_DURATION_TOLERANCE;
+use crate::video_info::player_response::streaming_data::QualityLabel;
+
+type OnProgress<'a> = Box<dyn FnMut(PackageProgress) + Send + 'a>;
+
+/// The container format of a package.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Container {
+    /// An `mp4` file. Subtitles are converted to `mov_text`, which most players support.
+    Mp4,
+    /// A Matroska (`mkv`) file. It can hold all codecs YouTube uses, and `srt` subtitles.
+    #[default]
+    Mkv,
+}
+
+impl Container {
+    /// The file extension of the container, without a leading dot.
+    #[inline]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Mp4 => "mp4",
+            Self::Mkv => "mkv",
+        }
+    }
+
+    /// The name of the `ffmpeg` muxer.
+    #[inline]
+    fn muxer(&self) -> &'static str {
+        match self {
+            Self::Mp4 => "mp4",
+            Self::Mkv => "matroska",
+        }
+    }
+
+    /// The `ffmpeg` codec, subtitles are stored with.
+    #[inline]
+    fn subtitle_codec(&self) -> &'static str {
+        match self {
+            Self::Mp4 => "mov_text",
+            Self::Mkv => "srt",
+        }
+    }
+
+    /// Whether the container is the natural choice for `stream`, so it can be copied without any
+    /// surprises.
+    #[inline]
+    fn fits(&self, stream: &Stream) -> bool {
+        match self {
+            Self::Mp4 => stream.mime.subtype() == mime::MP4,
+            Self::Mkv => true,
+        }
+    }
+}
+
+/// The steps of [`Video::download_package_to`], in the order they happen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PackagePhase {
+    /// The video stream is downloaded. The progress is measured in bytes.
+    Video,
+    /// The audio stream is downloaded. The progress is measured in bytes.
+    Audio,
+    /// The caption tracks are downloaded. The progress is measured in tracks.
+    Subtitles,
+    /// `ffmpeg` muxes the files. The progress is `0` when it starts, and `1` when it's done.
+    Muxing,
+}
+
+/// The progress of [`Video::download_package_to`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackageProgress {
+    pub phase: PackagePhase,
+    /// The bytes or tracks processed so far in the `phase`.
+    pub current: u64,
+    /// The total of the `phase`, if it's known.
+    pub total: Option<u64>,
+}
+
+/// Options for [`Video::download_package_to`].
+///
+/// By default, the best video and audio streams, no subtitles, and the chapters are packaged
+/// into a [`Container::Mkv`], using the `ffmpeg` binary in the `PATH`.
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct PackageOptions<'a> {
+    video_quality: Option<QualityLabel>,
+    audio_language: Option<String>,
+    subtitle_langs: Vec<String>,
+    embed_chapters: bool,
+    container: Container,
+    ffmpeg: PathBuf,
+    duration_tolerance: Duration,
+    strict_duration: bool,
+    #[derivative(Debug = "ignore")]
+    on_progress: Option<OnProgress<'a>>,
+}
+
+impl<'a> Default for PackageOptions<'a> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            video_quality: None,
+            audio_language: None,
+            subtitle_langs: Vec::new(),
+            embed_chapters: true,
+            container: Container::default(),
+            ffmpeg: PathBuf::from("ffmpeg"),
+            duration_tolerance: DEFAULT_DURATION_TOLERANCE,
+            strict_duration: false,
+            on_progress: None,
+        }
+    }
+}
+
+impl<'a> PackageOptions<'a> {
+    /// Creates the default options.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only considers video streams with the quality label `video_quality`.
+    /// [default: the best video stream]
+    #[inline]
+    pub fn video_quality(mut self, video_quality: QualityLabel) -> Self {
+        self.video_quality = Some(video_quality);
+        self
+    }
+
+    /// Only considers audio streams of the audio track in `language` (see
+    /// [`AudioTrack::is_language`](crate::video_info::player_response::streaming_data::AudioTrack::is_language)).
+    /// [default: the default audio track]
+    #[inline]
+    pub fn audio_language(mut self, language: impl Into<String>) -> Self {
+        self.audio_language = Some(language.into());
+        self
+    }
+
+    /// The languages of the caption tracks, that are added as subtitles, in this order. Captions
+    /// uploaded by the creator are preferred over automatically generated ones. [default: none]
+    #[inline]
+    pub fn subtitle_langs<S: Into<String>>(mut self, languages: impl IntoIterator<Item=S>) -> Self {
+        self.subtitle_langs = languages.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Whether to embed the [chapters](Video::chapters) of the video. [default: `true`]
+    #[inline]
+    pub fn embed_chapters(mut self, embed_chapters: bool) -> Self {
+        self.embed_chapters = embed_chapters;
+        self
+    }
+
+    /// The container of the package. For [`Container::Mp4`], `mp4` streams are preferred, even
+    /// if a `webm` stream has a higher quality. [default: [`Container::Mkv`]]
+    #[inline]
+    pub fn container(mut self, container: Container) -> Self {
+        self.container = container;
+        self
+    }
+
+    /// The path of the `ffmpeg` binary. [default: `ffmpeg`]
+    #[inline]
+    pub fn ffmpeg(mut self, ffmpeg: impl Into<PathBuf>) -> Self {
+        self.ffmpeg = ffmpeg.into();
+        self
+    }
+
+    /// How far the duration of the video and the audio stream may deviate from the duration of
+    /// the video (see [`Video::duration_consistency`]). [default: [`DEFAULT_DURATION_TOLERANCE`]]
+    #[inline]
+    pub fn duration_tolerance(mut self, duration_tolerance: Duration) -> Self {
+        self.duration_tolerance = duration_tolerance;
+        self
+    }
+
+    /// Whether to fail with [`Error::DurationMismatch`], instead of logging a warning, when the
+    /// video or the audio stream deviates from the duration of the video, so the tracks of the
+    /// package would go out of sync. [default: `false`]
+    #[inline]
+    pub fn strict_duration(mut self, strict_duration: bool) -> Self {
+        self.strict_duration = strict_duration;
+        self
+    }
+
+    /// Reports the [`PackageProgress`] of each phase to `on_progress`.
+    #[inline]
+    pub fn on_progress(mut self, on_progress: impl FnMut(PackageProgress) + Send + 'a) -> Self {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    fn video_stream<'v>(&self, video: &'v Video) -> Result<&'v Stream> {
+        video
+            .streams()
+            .iter()
+            .filter(|stream| stream.includes_video_track && !stream.includes_audio_track)
+            .filter(|stream| !stream.is_premium_only())
+            .filter(|stream| self.video_quality.as_ref().is_none_or(|quality| stream.quality_label.as_ref() == Some(quality)))
+            .max_by_key(|stream| (self.container.fits(stream), stream.rank()))
+            .ok_or_else(|| match self.video_quality {
+                Some(ref quality) => package_error(format!("the video has no video stream in {}", quality)),
+                None => package_error("the video has no video stream"),
+            })
+    }
+
+    fn audio_stream<'v>(&self, video: &'v Video) -> Result<&'v Stream> {
+        video
+            .streams()
+            .iter()
+            .filter(|stream| stream.includes_audio_track && !stream.includes_video_track)
+            .filter(|stream| match self.audio_language {
+                Some(ref language) => stream.audio_track.as_ref().is_some_and(|track| track.is_language(language)),
+                None => true,
+            })
+            .max_by_key(|stream| (
+                self.container.fits(stream),
+                stream.audio_track.as_ref().is_none_or(|track| track.is_default),
+                stream.rank(),
+            ))
+            .ok_or_else(|| match self.audio_language {
+                Some(ref language) => package_error(format!("the video has no audio stream in `{}`", language)),
+                None => package_error("the video has no audio stream"),
+            })
+    }
+
+    fn caption_tracks<'v>(&self, video: &'v Video) -> Result<Vec<&'v CaptionTrack>> {
+        self.subtitle_langs
+            .iter()
+            .map(|language| {
+                video
+                    .caption_tracks()
+                    .iter()
+                    .filter(|track| track.language_code.as_str().eq_ignore_ascii_case(language))
+                    .min_by_key(|track| track.is_auto_generated())
+                    .ok_or_else(|| package_error(format!("the video has no captions in `{}`", language)))
+            })
+            .collect()
+    }
+
+    /// Fails, or warns, if one of the `streams` deviates from the duration of the `video`.
+    fn check_durations(&self, video: &Video, streams: &[&Stream]) -> Result<()> {
+        let report = video.duration_consistency_with_tolerance(self.duration_tolerance);
+        let itags: Vec<u64> = streams.iter().map(|stream| stream.itag).collect();
+        if self.strict_duration {
+            return report.ensure_consistent(&itags);
+        }
+
+        for mismatch in report.mismatches.iter().filter(|mismatch| itags.contains(&mismatch.itag)) {
+            log::warn!(
+                "the stream {} is {} ms long, but the video is {} ms long, so the package may go out of sync",
+                mismatch.itag, mismatch.got_ms, mismatch.expected_ms,
+            );
+        }
+        Ok(())
+    }
+
+    fn report(&mut self, phase: PackagePhase, current: u64, total: Option<u64>) {
+        if let Some(ref mut on_progress) = self.on_progress {
+            on_progress(PackageProgress { phase, current, total });
+        }
+    }
+}
+
+/// A subtitle file, that's added to a [`MuxJob`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubtitleInput {
+    pub path: PathBuf,
+    /// The language of the subtitles, i.e. `en`.
+    pub language: String,
+}
+
+/// The inputs and the output of an `ffmpeg` call, that muxes a video, an audio, subtitles and
+/// chapters into a single file, without re-encoding the video and the audio.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MuxJob {
+    pub video: PathBuf,
+    pub audio: PathBuf,
+    pub subtitles: Vec<SubtitleInput>,
+    /// An `ffmetadata` file with the chapters (see [`ffmetadata`]).
+    pub chapters: Option<PathBuf>,
+    pub title: Option<String>,
+    pub container: Container,
+    pub output: PathBuf,
+}
+
+impl MuxJob {
+    /// The arguments for `ffmpeg`.
+    ///
+    /// The video is the first input, the audio the second one, followed by the subtitles and
+    /// the chapters. The output is overwritten, if it exists.
+    pub fn args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = Vec::new();
+        let mut push = |arg: &dyn AsRef<OsStr>| args.push(arg.as_ref().to_owned());
+
+        for arg in &["-hide_banner", "-loglevel", "error", "-y"] {
+            push(arg);
+        }
+        push(&"-i");
+        push(&self.video);
+        push(&"-i");
+        push(&self.audio);
+        for subtitle in &self.subtitles {
+            push(&"-i");
+            push(&subtitle.path);
+        }
+        if let Some(ref chapters) = self.chapters {
+            push(&"-f");
+            push(&"ffmetadata");
+            push(&"-i");
+            push(chapters);
+        }
+
+        for arg in &["-map", "0:v:0", "-map", "1:a:0"] {
+            push(arg);
+        }
+        for i in 0..self.subtitles.len() {
+            push(&"-map");
+            push(&format!("{}:s:0", i + 2));
+        }
+        if self.chapters.is_some() {
+            push(&"-map_chapters");
+            push(&(self.subtitles.len() + 2).to_string());
+        }
+
+        for arg in &["-c:v", "copy", "-c:a", "copy"] {
+            push(arg);
+        }
+        if !self.subtitles.is_empty() {
+            push(&"-c:s");
+            push(&self.container.subtitle_codec());
+        }
+        for (i, subtitle) in self.subtitles.iter().enumerate() {
+            push(&format!("-metadata:s:s:{}", i));
+            push(&format!("language={}", subtitle.language));
+        }
+        if let Some(ref title) = self.title {
+            push(&"-metadata");
+            push(&format!("title={}", title));
+        }
+
+        push(&"-f");
+        push(&self.container.muxer());
+        push(&self.output);
+        args
+    }
+
+    /// The number of tracks of the output: the video, the audio and the subtitles.
+    #[inline]
+    pub fn track_count(&self) -> usize {
+        2 + self.subtitles.len()
+    }
+}
+
+/// The `chapters` in the `ffmetadata` format, that `ffmpeg` reads chapters from. The last chapter
+/// ends at `duration`.
+pub fn ffmetadata(chapters: &[Chapter], duration: Duration) -> String {
+    let mut metadata = String::from(";FFMETADATA1\n");
+    for (i, chapter) in chapters.iter().enumerate() {
+        let end = chapters
+            .get(i + 1)
+            .map_or(duration, |next| next.start)
+            .max(chapter.start);
+        let _ = write!(
+            metadata,
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            chapter.start.as_millis(), end.as_millis(), escape_ffmetadata(&chapter.title),
+        );
+    }
+    metadata
+}
+
+/// The number of streams `ffmpeg -i <FILE>` found in the first input, given its output.
+pub fn probed_stream_count(ffmpeg_output: &str) -> usize {
+    ffmpeg_output
+        .lines()
+        .filter(|line| line.trim_start().starts_with("Stream #0:"))
+        .count()
+}
+
+impl Video {
+    /// Downloads the video and the audio stream, the subtitles and the chapters, that match the
+    /// `options`, and muxes them into a single file at `path` with `ffmpeg`. See the
+    /// [module level documentation](crate::package).
+    ///
+    /// The files are downloaded to the directory `<path>.package`, which is removed afterwards,
+    /// even if packaging fails. Before the package is moved to `path`, `ffmpeg` checks, that it
+    /// contains all tracks.
+    ///
+    /// ### Errors
+    /// - [`Error::Package`], when there's no stream, or caption track, that matches the `options`,
+    ///   when `ffmpeg` cannot be run, or fails, or when the package misses tracks.
+    /// - [`Error::DurationMismatch`], when the video or the audio stream deviates from the duration
+    ///   of the video, and [`PackageOptions::strict_duration`] is set. Nothing is downloaded then.
+    /// - When a download fails.
+    pub async fn download_package_to<P: AsRef<Path>>(&self, path: P, mut options: PackageOptions<'_>) -> Result<()> {
+        let path = path.as_ref();
+        let video_stream = options.video_stream(self)?;
+        let audio_stream = options.audio_stream(self)?;
+        let caption_tracks = options.caption_tracks(self)?;
+        options.check_durations(self, &[video_stream, audio_stream])?;
+
+        let mut workspace = path.as_os_str().to_owned();
+        workspace.push(".package");
+        let workspace = PathBuf::from(workspace);
+        tokio::fs::create_dir_all(&workspace).await?;
+
+        let result = self
+            .package(path, &workspace, video_stream, audio_stream, &caption_tracks, &mut options)
+            .await;
+        if let Err(e) = tokio::fs::remove_dir_all(&workspace).await {
+            log::warn!("failed to remove the package workspace {:?}: {}", workspace, e);
+        }
+        result
+    }
+
+    async fn package(
+        &self,
+        path: &Path,
+        workspace: &Path,
+        video_stream: &Stream,
+        audio_stream: &Stream,
+        caption_tracks: &[&CaptionTrack],
+        options: &mut PackageOptions<'_>,
+    ) -> Result<()> {
+        let video = workspace.join(format!("video.{}", video_stream.mime.subtype()));
+        download(video_stream, &video, PackagePhase::Video, options).await?;
+        let audio = workspace.join(format!("audio.{}", audio_stream.mime.subtype()));
+        download(audio_stream, &audio, PackagePhase::Audio, options).await?;
+
+        let total = Some(caption_tracks.len() as u64);
+        let mut subtitles = Vec::with_capacity(caption_tracks.len());
+        for (i, track) in caption_tracks.iter().enumerate() {
+            options.report(PackagePhase::Subtitles, i as u64, total);
+            let transcript = track.download(&video_stream.client).await?;
+            let path = workspace.join(format!("subtitles_{}.srt", i));
+            tokio::fs::write(&path, transcript.to_srt()).await?;
+            subtitles.push(SubtitleInput { path, language: track.language_code.as_str().to_owned() });
+        }
+        options.report(PackagePhase::Subtitles, caption_tracks.len() as u64, total);
+
+        let chapters = match options.embed_chapters {
+            true => self.chapters(),
+            false => Vec::new(),
+        };
+        let chapters = match chapters.is_empty() {
+            true => None,
+            false => {
+                let duration = Duration::from_secs(self.video_details().length_seconds);
+                let path = workspace.join("chapters.txt");
+                tokio::fs::write(&path, ffmetadata(&chapters, duration)).await?;
+                Some(path)
+            }
+        };
+
+        let job = MuxJob {
+            video,
+            audio,
+            subtitles,
+            chapters,
+            title: Some(self.title().to_owned()),
+            container: options.container,
+            output: workspace.join(format!("package.{}", options.container.extension())),
+        };
+        options.report(PackagePhase::Muxing, 0, Some(1));
+        let output = run_ffmpeg(&options.ffmpeg, job.args()).await?;
+        if !output.status.success() {
+            return Err(package_error(format!(
+                "ffmpeg failed ({}): {}", output.status, String::from_utf8_lossy(&output.stderr).trim(),
+            )));
+        }
+
+        // without an output file, ffmpeg lists the streams of the input, and exits with an error
+        let probe = run_ffmpeg(&options.ffmpeg, vec!["-hide_banner".into(), "-i".into(), job.output.clone().into()]).await?;
+        let track_count = probed_stream_count(&String::from_utf8_lossy(&probe.stderr));
+        if track_count != job.track_count() {
+            return Err(package_error(format!(
+                "the package should contain {} tracks, but ffmpeg found {}", job.track_count(), track_count,
+            )));
+        }
+        options.report(PackagePhase::Muxing, 1, Some(1));
+
+        tokio::fs::rename(&job.output, path).await?;
+        Ok(())
+    }
+}
+
+/// Downloads `stream` to `path`, and reports the progress as `phase`.
+async fn download(stream: &Stream, path: &Path, phase: PackagePhase, options: &mut PackageOptions<'_>) -> Result<()> {
+    match options.on_progress {
+        Some(ref mut on_progress) => {
+            let callback = Callback::new().connect_on_progress_closure(|arguments| on_progress(PackageProgress {
+                phase,
+                current: arguments.current_chunk as u64,
+                total: arguments.content_length,
+            }));
+            stream.download_to_with_callback(path, callback).await
+        }
+        None => stream.download_to(path).await,
+    }
+}
+
+async fn run_ffmpeg(ffmpeg: &Path, args: Vec<OsString>) -> Result<std::process::Output> {
+    log::debug!("run {:?} {:?}", ffmpeg, args);
+    Command::new(ffmpeg)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| package_error(format!("failed to run {:?}: {}", ffmpeg, e)))
+}
+
+/// Escapes the special characters of `ffmetadata` values.
+fn escape_ffmetadata(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[inline]
+fn package_error(reason: impl Into<String>) -> Error {
+    Error::Package(reason.into().into())
+}
@@ -0,0 +1,58 @@
+//! Helpers for extracting data embedded in YouTube's HTML and JavaScript.
+
+use core::ops::Range;
+
+/// Finds the first JSON object in `text`, and returns its byte range, so `&text[range]` is the
+/// complete object, including the outer braces.
+///
+/// The object starts at the first `{` in `text`. Strings (including escaped quotes, backslashes
+/// and unicode escapes) are skipped, so braces inside of them don't count. The object itself is
+/// not validated, it's only guaranteed, that all brackets and strings are balanced.
+///
+/// Returns `None`, if `text` contains no `{`, the object is not closed, or the brackets don't
+/// match (i.e. `{"a": [1}`).
+///
+/// # Example
+/// ```
+///# use rustube::parser::extract_json_object;
+/// let html = r#"var ytInitialPlayerResponse = {"a": "}", "b": [{}]};var meta = {};"#;
+/// let range = extract_json_object(html).unwrap();
+/// assert_eq!(&html[range], r#"{"a": "}", "b": [{}]}"#);
+/// ```
+pub fn extract_json_object(text: &str) -> Option<Range<usize>> {
+    let start = text.find('{')?;
+    let mut stack = Vec::new();
+    let mut in_string = false;
+    let mut bytes = text.as_bytes()[start..].iter().enumerate();
+
+    // all delimiters are ASCII, so `i` always is a char boundary
+    while let Some((i, &byte)) = bytes.next() {
+        if in_string {
+            match byte {
+                // skips the escaped char. For unicode escapes (`\uXXXX`), this is the `u`, and
+                // the hex digits can't be delimiters
+                b'\\' => { bytes.next()?; }
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'{' => stack.push(b'}'),
+            b'[' => stack.push(b']'),
+            b'}' | b']' => {
+                if stack.pop()? != byte {
+                    return None;
+                }
+                if stack.is_empty() {
+                    return Some(start..start + i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
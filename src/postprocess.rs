@@ -0,0 +1,529 @@
+//! Post processing of downloaded files.
+//!
+//! Adaptive `mp4` streams are fragmented mp4 files (see [`Stream::needs_remux_for_playback`]),
+//! which contain the samples in a sequence of `moof`/`mdat` pairs, instead of indexing them in
+//! the `moov` box. Some players refuse to play such files. [`remux_fmp4_to_mp4`] rewrites them
+//! into progressive mp4 files without touching the samples themselves.
+//!
+//! Only a subset of fragmented mp4 files is supported: a single track, without edit lists. This
+//! covers all adaptive `mp4` streams YouTube serves.
+//!
+//! [`Stream::needs_remux_for_playback`]: crate::Stream::needs_remux_for_playback
+
+use std::borrow::Cow;
+use std::convert::{TryFrom, TryInto};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::{Error, Result};
+
+type FourCC = [u8; 4];
+
+/// `sample_is_non_sync_sample` of the sample flags.
+const NON_SYNC_SAMPLE: u32 = 0x0001_0000;
+
+/// Rewrites the fragmented mp4 file at `path` into a progressive mp4 file in place.
+///
+/// The remuxed file is first written to `<path>.part`, which replaces `path` once it's complete.
+/// If remuxing fails, `path` is left untouched.
+///
+/// This function does blocking IO. In an async context, consider running it with
+/// [`tokio::task::spawn_blocking`].
+///
+/// ### Errors
+/// - When `path` is not a fragmented mp4 file, or uses features, that are not supported (i.e.
+///   multiple tracks, or edit lists), [`Error::Remux`] is returned.
+/// - When reading or writing the files fails, [`Error::IO`] is returned.
+pub fn remux_fmp4_to_mp4<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    let part_path = crate::stream::part_path(path);
+
+    let result = File::open(path)
+        .and_then(|input| Ok((input, File::create(&part_path)?)))
+        .map_err(Error::from)
+        .and_then(|(input, output)| {
+            let mut output = BufWriter::new(output);
+            remux_fmp4(BufReader::new(input), &mut output)?;
+            output.flush()?;
+            Ok(())
+        });
+
+    match result {
+        Ok(()) => Ok(std::fs::rename(&part_path, path)?),
+        Err(e) => {
+            if let Err(e) = std::fs::remove_file(&part_path) {
+                log::warn!("failed to remove {:?} after remuxing failed: {}", part_path, e);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Reads a fragmented mp4 file from `input`, and writes it as a progressive mp4 file to `output`.
+///
+/// Returns the number of bytes written. See [`remux_fmp4_to_mp4`] for the supported files.
+pub fn remux_fmp4<R: Read + Seek, W: Write>(mut input: R, mut output: W) -> Result<u64> {
+    let (moov, samples) = read_fragmented(&mut input)?;
+    let track = Track::from_moov(&moov)?;
+
+    let ftyp = encode_box(b"ftyp", &[*b"isom", 0x200_u32.to_be_bytes(), *b"isom", *b"iso2", *b"mp41"].concat());
+    let data_len = samples.iter().map(|sample| u64::from(sample.size)).sum::<u64>();
+    let mdat_header = match u32::try_from(data_len + 8) {
+        Ok(size) => [&size.to_be_bytes()[..], b"mdat"].concat(),
+        Err(_) => [&1_u32.to_be_bytes()[..], b"mdat", &(data_len + 16).to_be_bytes()].concat(),
+    };
+
+    // the length of the moov box does not depend on the chunk offset
+    let moov_len = rewrite_moov(&moov, &track, &samples, 0)?.len();
+    let chunk_offset = (ftyp.len() + moov_len + mdat_header.len())
+        .try_into()
+        .map_err(|_| remux_error("the moov box is too large"))?;
+    let moov = rewrite_moov(&moov, &track, &samples, chunk_offset)?;
+
+    output.write_all(&ftyp)?;
+    output.write_all(&moov)?;
+    output.write_all(&mdat_header)?;
+
+    // samples of one mdat are usually stored one after another, so they can be copied at once
+    let mut i = 0;
+    while i < samples.len() {
+        let start = samples[i].offset;
+        let mut end = start + u64::from(samples[i].size);
+        i += 1;
+        while i < samples.len() && samples[i].offset == end {
+            end += u64::from(samples[i].size);
+            i += 1;
+        }
+
+        input.seek(SeekFrom::Start(start))?;
+        let copied = io::copy(&mut (&mut input).take(end - start), &mut output)?;
+        if copied != end - start {
+            return Err(remux_error("the sample data exceeds the end of the file"));
+        }
+    }
+
+    Ok((ftyp.len() + moov.len() + mdat_header.len()) as u64 + data_len)
+}
+
+/// A sample of a track fragment.
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    /// The offset of the sample data in the input file.
+    offset: u64,
+    size: u32,
+    duration: u32,
+    flags: u32,
+    composition_offset: i64,
+}
+
+/// The defaults of the samples of all fragments (`trex`).
+#[derive(Clone, Copy, Debug, Default)]
+struct TrackDefaults {
+    track_id: u32,
+    duration: u32,
+    size: u32,
+    flags: u32,
+}
+
+/// The information of the single track, that's required to rewrite the `moov` box.
+#[derive(Clone, Copy, Debug)]
+struct Track {
+    movie_timescale: u32,
+    media_timescale: u32,
+}
+
+impl Track {
+    fn from_moov(moov: &[u8]) -> Result<Self> {
+        let mvhd = find_box(moov, b"mvhd")?;
+        let movie_timescale = read_u32(mvhd, if mvhd.first() == Some(&1) { 20 } else { 12 })?;
+
+        let trak = find_box(moov, b"trak")?;
+        if find_box(trak, b"edts").is_ok() {
+            return Err(remux_error("edit lists are not supported"));
+        }
+        let mdhd = find_box(find_box(trak, b"mdia")?, b"mdhd")?;
+        let media_timescale = read_u32(mdhd, if mdhd.first() == Some(&1) { 20 } else { 12 })?;
+
+        if movie_timescale == 0 || media_timescale == 0 {
+            return Err(remux_error("the timescale is zero"));
+        }
+        Ok(Self { movie_timescale, media_timescale })
+    }
+}
+
+/// Reads the `moov` box, and the samples of all fragments of a fragmented mp4 file.
+fn read_fragmented<R: Read + Seek>(input: &mut R) -> Result<(Vec<u8>, Vec<Sample>)> {
+    let file_len = input.seek(SeekFrom::End(0))?;
+    let mut position = 0;
+    let mut moov = None;
+    let mut defaults = None;
+    let mut samples = Vec::new();
+
+    while position < file_len {
+        input.seek(SeekFrom::Start(position))?;
+        let (kind, header_len, size) = read_box_header(input, file_len - position)?;
+
+        match &kind {
+            b"moov" => {
+                let payload = read_payload(input, size - header_len)?;
+                defaults = Some(read_track_defaults(&payload)?);
+                moov = Some(payload);
+            }
+            b"moof" => {
+                let defaults = defaults
+                    .as_ref()
+                    .ok_or_else(|| remux_error("the file contains a moof box before the moov box"))?;
+                let payload = read_payload(input, size - header_len)?;
+                read_fragment(&payload, position, defaults, &mut samples)?;
+            }
+            // the sample data is read later on, and all other boxes, like `sidx`, are only
+            // meaningful for fragmented files
+            _ => {}
+        }
+        position += size;
+    }
+
+    let moov = moov.ok_or_else(|| remux_error("the file contains no moov box"))?;
+    if samples.is_empty() {
+        return Err(remux_error("the file contains no samples"));
+    }
+    Ok((moov, samples))
+}
+
+/// Reads the header of the box at the current position, and returns its type, the length of the
+/// header, and the size of the whole box.
+fn read_box_header<R: Read>(input: &mut R, remaining: u64) -> Result<(FourCC, u64, u64)> {
+    let mut header = [0; 8];
+    input.read_exact(&mut header)?;
+    let kind: FourCC = header[4..].try_into().unwrap();
+
+    let (header_len, size) = match u32::from_be_bytes(header[..4].try_into().unwrap()) {
+        0 => (8, remaining),
+        1 => {
+            let mut size = [0; 8];
+            input.read_exact(&mut size)?;
+            (16, u64::from_be_bytes(size))
+        }
+        size => (8, u64::from(size)),
+    };
+
+    if size < header_len || size > remaining {
+        return Err(remux_error(format!("the {} box has an invalid size", String::from_utf8_lossy(&kind))));
+    }
+    Ok((kind, header_len, size))
+}
+
+fn read_payload<R: Read>(input: &mut R, len: u64) -> Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    input.take(len).read_to_end(&mut payload)?;
+    Ok(payload)
+}
+
+fn read_track_defaults(moov: &[u8]) -> Result<TrackDefaults> {
+    let tracks = children(moov)?
+        .into_iter()
+        .filter(|(kind, _)| kind == b"trak")
+        .count();
+    if tracks != 1 {
+        return Err(remux_error(format!("only files with a single track are supported, found {}", tracks)));
+    }
+
+    let stsz = find_box(find_box(find_box(find_box(find_box(moov, b"trak")?, b"mdia")?, b"minf")?, b"stbl")?, b"stsz")?;
+    if read_u32(stsz, 8)? != 0 {
+        return Err(remux_error("the moov box already contains samples"));
+    }
+
+    let mvex = find_box(moov, b"mvex")
+        .map_err(|_| remux_error("the file is not a fragmented mp4"))?;
+    let trex = find_box(mvex, b"trex")?;
+    Ok(TrackDefaults {
+        track_id: read_u32(trex, 4)?,
+        duration: read_u32(trex, 12)?,
+        size: read_u32(trex, 16)?,
+        flags: read_u32(trex, 20)?,
+    })
+}
+
+/// Reads the samples of the `moof` box at `moof_offset`.
+fn read_fragment(moof: &[u8], moof_offset: u64, defaults: &TrackDefaults, samples: &mut Vec<Sample>) -> Result<()> {
+    // the end of the data of the previous track fragment
+    let mut data_end = moof_offset;
+
+    for (_, traf) in children(moof)?.into_iter().filter(|(kind, _)| kind == b"traf") {
+        let tfhd = find_box(traf, b"tfhd")?;
+        let tfhd_flags = read_u32(tfhd, 0)? & 0x00ff_ffff;
+        if read_u32(tfhd, 4)? != defaults.track_id {
+            return Err(remux_error("only files with a single track are supported"));
+        }
+
+        let mut field = 8;
+        let mut optional = |flag: u32, len: usize| -> Result<Option<u64>> {
+            if tfhd_flags & flag == 0 {
+                return Ok(None);
+            }
+            let value = match len {
+                8 => read_u64(tfhd, field)?,
+                _ => u64::from(read_u32(tfhd, field)?),
+            };
+            field += len;
+            Ok(Some(value))
+        };
+        let base_data_offset = optional(0x01, 8)?;
+        let _sample_description_index = optional(0x02, 4)?;
+        let default_duration = optional(0x08, 4)?.map_or(defaults.duration, |d| d as u32);
+        let default_size = optional(0x10, 4)?.map_or(defaults.size, |s| s as u32);
+        let default_flags = optional(0x20, 4)?.map_or(defaults.flags, |f| f as u32);
+
+        let base = match base_data_offset {
+            Some(offset) => offset,
+            None if tfhd_flags & 0x02_0000 != 0 => moof_offset,
+            None => data_end,
+        };
+        let mut offset = base;
+
+        for (_, trun) in children(traf)?.into_iter().filter(|(kind, _)| kind == b"trun") {
+            let version = trun.first().copied().unwrap_or_default();
+            let flags = read_u32(trun, 0)? & 0x00ff_ffff;
+            let sample_count = read_u32(trun, 4)?;
+
+            let mut field = 8;
+            if flags & 0x001 != 0 {
+                let data_offset = read_u32(trun, field)? as i32;
+                offset = base
+                    .checked_add_signed(i64::from(data_offset))
+                    .ok_or_else(|| remux_error("a track run has an invalid data offset"))?;
+                field += 4;
+            }
+            let first_sample_flags = match flags & 0x004 {
+                0 => None,
+                _ => {
+                    field += 4;
+                    Some(read_u32(trun, field - 4)?)
+                }
+            };
+
+            for i in 0..sample_count {
+                let mut next = |flag: u32| -> Result<Option<u32>> {
+                    if flags & flag == 0 {
+                        return Ok(None);
+                    }
+                    field += 4;
+                    read_u32(trun, field - 4).map(Some)
+                };
+                let duration = next(0x100)?.unwrap_or(default_duration);
+                let size = next(0x200)?.unwrap_or(default_size);
+                let sample_flags = next(0x400)?;
+                let composition_offset = match next(0x800)? {
+                    Some(cto) if version == 0 => i64::from(cto),
+                    Some(cto) => i64::from(cto as i32),
+                    None => 0,
+                };
+                let flags = match (i, first_sample_flags) {
+                    (0, Some(flags)) => flags,
+                    _ => sample_flags.unwrap_or(default_flags),
+                };
+
+                samples.push(Sample { offset, size, duration, flags, composition_offset });
+                offset += u64::from(size);
+            }
+        }
+
+        data_end = offset;
+    }
+
+    Ok(())
+}
+
+/// Writes the `moov` box of the progressive file, which indexes all `samples` in a single chunk
+/// at `chunk_offset`.
+fn rewrite_moov(moov: &[u8], track: &Track, samples: &[Sample], chunk_offset: u32) -> Result<Vec<u8>> {
+    let media_duration = samples.iter().map(|sample| u64::from(sample.duration)).sum::<u64>();
+    let movie_duration = (u128::from(media_duration) * u128::from(track.movie_timescale)
+        / u128::from(track.media_timescale)) as u64;
+
+    fn rewrite(kind: &FourCC, payload: &[u8], out: &mut Vec<u8>, durations: (u64, u64), stbl: &dyn Fn() -> Vec<u8>) -> Result<()> {
+        let (movie_duration, media_duration) = durations;
+        match kind {
+            b"moov" | b"trak" | b"mdia" | b"minf" => {
+                let mut content = Vec::new();
+                for (kind, payload) in children(payload)? {
+                    rewrite(&kind, payload, &mut content, durations, stbl)?;
+                }
+                out.extend(encode_box(kind, &content));
+            }
+            b"mvex" => {}
+            b"mvhd" | b"mdhd" => out.extend(encode_box(kind, &with_duration(
+                payload,
+                (16, 24),
+                if kind == b"mvhd" { movie_duration } else { media_duration },
+            )?)),
+            b"tkhd" => out.extend(encode_box(kind, &with_duration(payload, (20, 28), movie_duration)?)),
+            b"stbl" => {
+                let stsd = find_box(payload, b"stsd")?;
+                out.extend(encode_box(kind, &[encode_box(b"stsd", stsd), stbl()].concat()));
+            }
+            _ => out.extend(encode_box(kind, payload)),
+        }
+        Ok(())
+    }
+
+    let mut out = Vec::new();
+    rewrite(b"moov", moov, &mut out, (movie_duration, media_duration), &|| sample_table(samples, chunk_offset))?;
+    Ok(out)
+}
+
+/// The sample table boxes after the `stsd` box.
+fn sample_table(samples: &[Sample], chunk_offset: u32) -> Vec<u8> {
+    let sample_count = samples.len() as u32;
+    let mut table = Vec::new();
+
+    let durations = run_lengths(samples.iter().map(|sample| sample.duration));
+    let mut stts = full_box_header(0);
+    stts.extend((durations.len() as u32).to_be_bytes());
+    for (count, duration) in durations {
+        stts.extend(count.to_be_bytes());
+        stts.extend(duration.to_be_bytes());
+    }
+    table.extend(encode_box(b"stts", &stts));
+
+    if samples.iter().any(|sample| sample.composition_offset != 0) {
+        let negative = samples.iter().any(|sample| sample.composition_offset < 0);
+        let offsets = run_lengths(samples.iter().map(|sample| sample.composition_offset));
+        let mut ctts = full_box_header(if negative { 1 } else { 0 });
+        ctts.extend((offsets.len() as u32).to_be_bytes());
+        for (count, offset) in offsets {
+            ctts.extend(count.to_be_bytes());
+            ctts.extend((offset as u32).to_be_bytes());
+        }
+        table.extend(encode_box(b"ctts", &ctts));
+    }
+
+    if samples.iter().any(|sample| sample.flags & NON_SYNC_SAMPLE != 0) {
+        let sync_samples = (1..=sample_count)
+            .zip(samples)
+            .filter(|(_, sample)| sample.flags & NON_SYNC_SAMPLE == 0)
+            .map(|(number, _)| number)
+            .collect::<Vec<_>>();
+        let mut stss = full_box_header(0);
+        stss.extend((sync_samples.len() as u32).to_be_bytes());
+        sync_samples.iter().for_each(|number| stss.extend(number.to_be_bytes()));
+        table.extend(encode_box(b"stss", &stss));
+    }
+
+    let mut stsc = full_box_header(0);
+    stsc.extend([1_u32, 1, sample_count, 1].iter().flat_map(|n| n.to_be_bytes()));
+    table.extend(encode_box(b"stsc", &stsc));
+
+    let mut stsz = full_box_header(0);
+    match samples.iter().all(|sample| sample.size == samples[0].size) {
+        true => stsz.extend([samples[0].size, sample_count].iter().flat_map(|n| n.to_be_bytes())),
+        false => {
+            stsz.extend([0, sample_count].iter().flat_map(|n| n.to_be_bytes()));
+            samples.iter().for_each(|sample| stsz.extend(sample.size.to_be_bytes()));
+        }
+    }
+    table.extend(encode_box(b"stsz", &stsz));
+
+    let mut stco = full_box_header(0);
+    stco.extend([1, chunk_offset].iter().flat_map(|n| n.to_be_bytes()));
+    table.extend(encode_box(b"stco", &stco));
+
+    table
+}
+
+/// Replaces the duration of a `mvhd`, `tkhd`, or `mdhd` box, which is stored at one of `offsets`,
+/// depending on the version of the box.
+fn with_duration(payload: &[u8], offsets: (usize, usize), duration: u64) -> Result<Vec<u8>> {
+    let mut payload = payload.to_vec();
+    let (offset, len) = match payload.first() {
+        Some(1) => (offsets.1, 8),
+        _ => (offsets.0, 4),
+    };
+    let field = payload
+        .get_mut(offset..offset + len)
+        .ok_or_else(|| remux_error("a header box is too short"))?;
+
+    match len {
+        8 => field.copy_from_slice(&duration.to_be_bytes()),
+        // all bits set means, that the duration is unknown
+        _ => field.copy_from_slice(&u32::try_from(duration).unwrap_or(u32::MAX).to_be_bytes()),
+    }
+    Ok(payload)
+}
+
+fn run_lengths<T: PartialEq + Copy>(values: impl Iterator<Item=T>) -> Vec<(u32, T)> {
+    let mut runs: Vec<(u32, T)> = Vec::new();
+    for value in values {
+        match runs.last_mut() {
+            Some((count, last)) if *last == value => *count += 1,
+            _ => runs.push((1, value)),
+        }
+    }
+    runs
+}
+
+/// The boxes contained in `payload`, as pairs of their type and payload.
+fn children(mut payload: &[u8]) -> Result<Vec<(FourCC, &[u8])>> {
+    let mut children = Vec::new();
+    while !payload.is_empty() {
+        let remaining = payload.len() as u64;
+        let (kind, header_len, size) = read_box_header(&mut payload, remaining)?;
+        let content_len = (size - header_len) as usize;
+        children.push((kind, &payload[..content_len]));
+        payload = &payload[content_len..];
+    }
+    Ok(children)
+}
+
+/// The payload of the first child box of type `kind`.
+fn find_box<'a>(payload: &'a [u8], kind: &FourCC) -> Result<&'a [u8]> {
+    children(payload)?
+        .into_iter()
+        .find(|(child, _)| child == kind)
+        .map(|(_, payload)| payload)
+        .ok_or_else(|| remux_error(format!("missing {} box", String::from_utf8_lossy(kind))))
+}
+
+fn encode_box(kind: &FourCC, payload: &[u8]) -> Vec<u8> {
+    let size = payload.len() as u64 + 8;
+    let mut out = Vec::with_capacity(size as usize + 8);
+    match u32::try_from(size) {
+        Ok(size) => {
+            out.extend(size.to_be_bytes());
+            out.extend(kind);
+        }
+        Err(_) => {
+            out.extend(1_u32.to_be_bytes());
+            out.extend(kind);
+            out.extend((size + 8).to_be_bytes());
+        }
+    }
+    out.extend(payload);
+    out
+}
+
+/// The version and (empty) flags of a full box.
+#[inline]
+fn full_box_header(version: u8) -> Vec<u8> {
+    vec![version, 0, 0, 0]
+}
+
+fn read_u32(payload: &[u8], offset: usize) -> Result<u32> {
+    payload
+        .get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| remux_error("a box is too short"))
+}
+
+fn read_u64(payload: &[u8], offset: usize) -> Result<u64> {
+    payload
+        .get(offset..offset + 8)
+        .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| remux_error("a box is too short"))
+}
+
+#[inline]
+fn remux_error(reason: impl Into<Cow<'static, str>>) -> Error {
+    Error::Remux(reason.into())
+}
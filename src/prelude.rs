@@ -0,0 +1,18 @@
+//! The types most programs using `rustube` need, so they can be imported at once:
+//! ```
+//! use rustube::prelude::*;
+//! ```
+
+#[cfg(feature = "std")]
+pub use crate::{Error, Result};
+pub use crate::id::{Id, IdBuf};
+#[cfg(feature = "callback")]
+pub use crate::stream::callback::{Callback, CallbackArguments};
+#[cfg(feature = "stream")]
+pub use crate::stream::Stream;
+#[cfg(feature = "descramble")]
+pub use crate::{Video, VideoDescrambler};
+#[cfg(feature = "fetch")]
+pub use crate::VideoFetcher;
+#[cfg(feature = "fetch")]
+pub use crate::video_info::player_response::streaming_data::{AudioQuality, Quality, QualityLabel};
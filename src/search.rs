@@ -0,0 +1,233 @@
+//! Searching YouTube for videos.
+//!
+//! [`Search::query`] requests the first page of results from the `search` endpoint of the
+//! innertube API, the same way [`VideoFetcher`](crate::VideoFetcher) requests the player
+//! response. Further pages are requested with [`SearchResults::next_page`].
+//!
+//! Only videos are returned. Playlists, channels, shelves and ads in the results are skipped.
+//!
+//! # Example
+//! ```no_run
+//!# use rustube::search::Search;
+//!# #[tokio::main]
+//!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let search = Search::new()?;
+//! let mut page = Some(search.query("rust programming").await?);
+//! while let Some(results) = page {
+//!     for video in &results.videos {
+//!         println!("{}: {}", video.video_id, video.title);
+//!     }
+//!     page = results.next_page().await?;
+//! }
+//!# Ok(())
+//!# }
+//! ```
+
+use reqwest::Client;
+use serde_json::Value;
+use url::Url;
+
+use crate::{Error, IdBuf, InnertubeClient, Result};
+use crate::fetcher::{ClientBuilder, is_rate_limited, retry_after};
+use crate::id::parse_timestamp;
+use crate::video_info::player_response::video_details::Thumbnails;
+
+/// The base URL of all requests to YouTube.
+const YOUTUBE_URL: &str = "https://www.youtube.com/";
+
+/// Searches YouTube for videos.
+///
+/// A [`Search`] only holds the [`Client`], so it's cheap to clone, and can be used for any
+/// number of queries.
+#[derive(Clone, Debug)]
+pub struct Search {
+    client: Client,
+    base_url: Url,
+}
+
+/// A page of the results of a [`Search::query`].
+#[derive(Clone, Debug)]
+pub struct SearchResults {
+    /// The videos of the page, in the order YouTube ranked them.
+    pub videos: Vec<SearchVideo>,
+    continuation: Option<String>,
+    search: Search,
+}
+
+/// A video in the results of a [`Search::query`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchVideo {
+    pub video_id: IdBuf,
+    pub title: String,
+    /// The name of the channel, that uploaded the video.
+    pub author: Option<String>,
+    /// The length of the video in seconds. Running live streams and upcoming premieres don't
+    /// have a length yet.
+    pub length_seconds: Option<u64>,
+    /// The view count as displayed by YouTube, i.e. `1,234,567 views`, or `1.2K watching` for
+    /// live streams.
+    pub view_count_text: Option<String>,
+    pub thumbnails: Thumbnails,
+}
+
+impl Search {
+    /// Creates a [`Search`] with a [`ClientBuilder::recommended`] [`Client`].
+    /// ### Errors
+    /// When [`reqwest`] fails to initialize an new [`Client`].
+    #[inline]
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_client(ClientBuilder::recommended().build()?))
+    }
+
+    /// Creates a [`Search`] from an existing [`Client`], i.e. the one of a
+    /// [`VideoFetcher`](crate::VideoFetcher).
+    #[inline]
+    pub fn with_client(client: Client) -> Self {
+        Self {
+            client,
+            base_url: Url::parse(YOUTUBE_URL).unwrap(),
+        }
+    }
+
+    /// Sets the URL all requests are made to, instead of `https://www.youtube.com/`.
+    /// This is mostly useful for testing against a local server.
+    #[inline]
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Requests the first page of the results of `query`.
+    /// ### Errors
+    /// - [`Error::RateLimited`], when YouTube rate limits the request.
+    /// - When the request fails, or the response is no JSON.
+    pub async fn query(&self, query: &str) -> Result<SearchResults> {
+        let response = self.request(query, None).await?;
+        Ok(self.results(&response))
+    }
+
+    /// Requests the `search` endpoint as the [`InnertubeClient::Web`] client.
+    async fn request(&self, query: &str, continuation: Option<&str>) -> Result<Value> {
+        let client = InnertubeClient::Web;
+        let mut url = self.base_url.join("youtubei/v1/search")?;
+        url
+            .query_pairs_mut()
+            .append_pair("prettyPrint", "false");
+
+        let response = self.client
+            .post(url.as_str())
+            .header(reqwest::header::USER_AGENT, client.user_agent())
+            .header("X-YouTube-Client-Name", client.id())
+            .header("X-YouTube-Client-Version", client.version())
+            .json(&client.search_request(query, continuation))
+            .send()
+            .await?;
+
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(retry_after);
+        if let Err(err) = response.error_for_status_ref() {
+            let body = response.bytes().await.unwrap_or_default();
+            return match is_rate_limited(status, &body) {
+                true => Err(Error::RateLimited { retry_after, endpoint: "search" }),
+                false => Err(err.into()),
+            };
+        }
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+
+    fn results(&self, response: &Value) -> SearchResults {
+        let (videos, continuation) = parse_response(response);
+        SearchResults { videos, continuation, search: self.clone() }
+    }
+}
+
+impl SearchResults {
+    /// Whether YouTube has more results, than the ones of this page.
+    #[inline]
+    pub fn has_next_page(&self) -> bool {
+        self.continuation.is_some()
+    }
+
+    /// Requests the next page of results, or returns `None`, if this is the last page.
+    /// ### Errors
+    /// The same as [`Search::query`].
+    pub async fn next_page(&self) -> Result<Option<SearchResults>> {
+        let continuation = match self.continuation {
+            Some(ref continuation) => continuation,
+            None => return Ok(None),
+        };
+        let response = self.search.request("", Some(continuation)).await?;
+        Ok(Some(self.search.results(&response)))
+    }
+}
+
+/// The videos and the continuation token of a response of the `search` endpoint.
+///
+/// The first page lists its items in the `sectionListRenderer`, the following pages in an
+/// `appendContinuationItemsAction`. Both contain `itemSectionRenderer`s with the results, and a
+/// `continuationItemRenderer` with the token of the next page.
+fn parse_response(response: &Value) -> (Vec<SearchVideo>, Option<String>) {
+    let sections = response["contents"]["twoColumnSearchResultsRenderer"]["primaryContents"]
+        ["sectionListRenderer"]["contents"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .chain(
+            response["onResponseReceivedCommands"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|command| command["appendContinuationItemsAction"]["continuationItems"].as_array())
+                .flatten()
+        );
+
+    let mut videos = Vec::new();
+    let mut continuation = None;
+    for section in sections {
+        let items = section["itemSectionRenderer"]["contents"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        videos.extend(items.iter().filter_map(|item| video(&item["videoRenderer"])));
+
+        if let Some(token) = section["continuationItemRenderer"]["continuationEndpoint"]
+            ["continuationCommand"]["token"].as_str() {
+            continuation = Some(token.to_owned());
+        }
+    }
+    (videos, continuation)
+}
+
+/// Parses a `videoRenderer`. Other renderers, and videos without a valid id, are skipped.
+fn video(renderer: &Value) -> Option<SearchVideo> {
+    let video_id = IdBuf::from_string(renderer["videoId"].as_str()?.to_owned()).ok()?;
+    Some(SearchVideo {
+        video_id,
+        title: text(&renderer["title"]).unwrap_or_default(),
+        author: text(&renderer["ownerText"]).or_else(|| text(&renderer["longBylineText"])),
+        length_seconds: renderer["lengthText"]["simpleText"]
+            .as_str()
+            .and_then(parse_timestamp)
+            .map(|length| length.as_secs()),
+        view_count_text: text(&renderer["viewCountText"]),
+        thumbnails: serde_json::from_value(renderer["thumbnail"].clone()).unwrap_or_default(),
+    })
+}
+
+/// The text of a `simpleText`, or `runs` object.
+fn text(value: &Value) -> Option<String> {
+    match value["simpleText"].as_str() {
+        Some(text) => Some(text.to_owned()),
+        None => Some(
+            value["runs"]
+                .as_array()?
+                .iter()
+                .filter_map(|run| run["text"].as_str())
+                .collect()
+        ),
+    }
+}
@@ -55,18 +55,11 @@ pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<MimeType, <D as Des
 pub(crate) fn serialize<S>(mime_type: &MimeType, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer {
-    let mut s = format!(
-        r#"{}/{}; codecs=""#,
+    // the inverse of `deserialize`, so the codecs are joined by `, `, without a trailing separator
+    format!(
+        r#"{}/{}; codecs="{}""#,
         mime_type.mime.type_(),
         mime_type.mime.subtype(),
-    );
-
-    for codec in mime_type.codecs.iter() {
-        s.push_str(codec);
-        s.push(',');
-        s.push(' ');
-    }
-
-    s.push('"');
-    s.serialize(serializer)
+        mime_type.codecs.join(", "),
+    ).serialize(serializer)
 }
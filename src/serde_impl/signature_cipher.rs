@@ -1,4 +1,4 @@
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{Error, Unexpected};
 use url::Url;
 
@@ -19,13 +19,40 @@ pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<SignatureCipher, D:
 
     let both: EitherUrlOrCipher = serde_with::serde::Deserialize::deserialize(deserializer)?;
     match (both.url, both.signature_cipher) {
-        (Some(url), None) => Ok(SignatureCipher { url, s: None }),
+        (Some(url), None) => Ok(SignatureCipher { url, s: None, n_transformed: false }),
         (None, Some(s)) => Ok(s),
         (None, None) => Err(serde_with::serde::de::Error::missing_field("signatureCipher")),
         (Some(_), Some(_)) => Err(serde_with::serde::de::Error::duplicate_field("url")),
     }
 }
 
+/// The inverse of [`deserialize`]: a plain `url`, if the signature is already descrambled, and a
+/// `signatureCipher` query string otherwise. Used with `#[serde(flatten)]`.
+pub(crate) fn serialize<S>(signature_cipher: &SignatureCipher, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct EitherUrlOrCipher<'a> {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        url: Option<&'a Url>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        signature_cipher: Option<String>,
+    }
+
+    let either = match signature_cipher.s {
+        None => EitherUrlOrCipher { url: Some(&signature_cipher.url), signature_cipher: None },
+        Some(_) => EitherUrlOrCipher {
+            url: None,
+            signature_cipher: Some(
+                serde_qs::to_string(signature_cipher)
+                    .map_err(serde::ser::Error::custom)?
+            ),
+        },
+    };
+    either.serialize(serializer)
+}
+
 fn deserialize_signature_cipher<'de, D>(deserializer: D) -> Result<Option<SignatureCipher>, <D as Deserializer<'de>>::Error> where
     D: Deserializer<'de> {
     let s = String::deserialize(deserializer)?;
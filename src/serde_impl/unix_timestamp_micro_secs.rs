@@ -4,31 +4,32 @@ use serde::de::{Error, Unexpected};
 use serde_with::{DeserializeAs, SerializeAs};
 use serde_with::json::JsonString;
 
-pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, <D as Deserializer<'de>>::Error> where
-    D: Deserializer<'de> {
-    let micro_seconds: i64 = JsonString::deserialize_as(deserializer)?;
+pub(crate) fn serialize<S>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer {
+    JsonString::serialize_as(&time.timestamp_micros(), serializer)
+}
+
+fn from_micro_seconds<E: Error>(micro_seconds: i64) -> Result<DateTime<Utc>, E> {
     Utc
-        .timestamp_millis_opt(micro_seconds / 1000)
+        .timestamp_micros(micro_seconds)
         .single()
-        .ok_or_else(|| D::Error::invalid_value(
+        .ok_or_else(|| E::invalid_value(
             Unexpected::Signed(micro_seconds),
             &"a valid UNIX time stamp in microseconds",
         ))
 }
 
-pub(crate) fn serialize<S>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer {
-    let micro_seconds: i64 = time.timestamp_millis() * 1000;
-    JsonString::serialize_as(&micro_seconds, serializer)
-}
-
 pub(crate) mod option {
     use super::*;
 
     pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, <D as Deserializer<'de>>::Error> where
         D: Deserializer<'de> {
-        super::deserialize(deserializer).map(Some)
+        // `null` is accepted as well, since that's how `serialize` writes `None`
+        let micro_seconds: Option<i64> = Option::<JsonString>::deserialize_as(deserializer)?;
+        micro_seconds
+            .map(from_micro_seconds)
+            .transpose()
     }
 
     pub(crate) fn serialize<S>(time: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
@@ -0,0 +1,224 @@
+//! Scoped views of a [`Video`](crate::Video) and its [`Stream`]s for serialization, i.e. only the
+//! general information, or only the URLs of the streams.
+//!
+//! The [`OutputLevel`] decides which fields are included. See
+//! [`Video::serialize_with_level`](crate::Video::serialize_with_level) and
+//! [`Stream::serialize_with_level`].
+//!
+//! # Example
+//! ```no_run
+//!# use rustube::{Id, VideoFetcher};
+//!# use rustube::serialize::OutputLevel;
+//!# #[tokio::main]
+//!# async fn main() -> rustube::Result<()> {
+//! let id = Id::from_str("5jlI4uzZGjU")?;
+//! let video = VideoFetcher::from_id(id.into_owned())?.fetch().await?.descramble()?;
+//!
+//! let urls = video.serialize_with_level(OutputLevel::URL);
+//! let general = video.serialize_with_level("general | video-track".parse()?);
+//!# Ok(())
+//!# }
+//! ```
+
+use core::str::FromStr;
+
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeMap;
+
+use crate::{Error, Stream, VideoInfo};
+
+bitflags::bitflags! {
+    /// The groups of fields, that are serialized. They can be combined, i.e.
+    /// `OutputLevel::URL | OutputLevel::GENERAL`.
+    ///
+    /// When parsed from a string, the levels are separated by whitespace, `,` or `|`, i.e.
+    /// `url | general`. `full` is the same as [`OutputLevel::all`].
+    pub struct OutputLevel: u8 {
+        /// The URLs of the streams (`url`).
+        const URL           = 0b00000001;
+        /// The mime type, quality, duration and size of the streams (`general`).
+        const GENERAL       = 0b00000010;
+        /// The resolution, frame rate and video codec of the streams (`video-track`).
+        const VIDEO_TRACK   = 0b00000100;
+        /// The audio quality, bitrate, audio track and audio codec of the streams (`audio-track`).
+        const AUDIO_TRACK   = 0b00001000;
+        /// The thumbnails of the video (`thumbnails`).
+        const THUMBNAILS    = 0b00010000;
+        /// Additional fields of the other levels (`verbose`).
+        const VERBOSE       = 0b10000000;
+
+        /// The whole [`VideoInfo`] (`video`).
+        const VIDEO         = 0b01000000;
+    }
+}
+
+impl FromStr for OutputLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.trim().split(|c: char| c.is_whitespace() || c == ',' || c == '|');
+        let mut level = Self::empty();
+
+        for s in split {
+            if s.is_empty() { continue; }
+
+            let next_level = match s {
+                "url" => Self::URL,
+                "general" => Self::GENERAL,
+                "video-track" => Self::VIDEO_TRACK,
+                "audio-track" => Self::AUDIO_TRACK,
+                "thumbnails" => Self::THUMBNAILS,
+                "verbose" => Self::VERBOSE,
+                "full" => Self::all(),
+
+                "video" => Self::VIDEO,
+
+                _ => return Err(Error::BadOutputLevel(s.to_owned())),
+            };
+            level |= next_level;
+        }
+
+        Ok(level)
+    }
+}
+
+/// Serializes a [`VideoInfo`] and [`Stream`]s according to an [`OutputLevel`].
+#[derive(Clone, Debug)]
+pub struct VideoSerializer<'a> {
+    output_level: OutputLevel,
+    video_info: &'a VideoInfo,
+    streams: Vec<StreamSerializer<'a>>,
+}
+
+impl<'a> VideoSerializer<'a> {
+    #[inline]
+    pub fn new(video_info: &'a VideoInfo, streams: impl IntoIterator<Item=&'a Stream>, output_level: OutputLevel) -> Self {
+        let streams = streams
+            .into_iter()
+            .map(|stream| StreamSerializer::new(stream, output_level))
+            .collect::<Vec<_>>();
+
+        Self {
+            output_level,
+            video_info,
+            streams,
+        }
+    }
+}
+
+impl Serialize for VideoSerializer<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error> where
+        S: Serializer {
+        let mut map = serializer.serialize_map(None)?;
+
+        if self.output_level.contains(OutputLevel::VIDEO) {
+            map.serialize_entry("video_info", &self.video_info)?;
+        }
+        if self.output_level.contains(OutputLevel::THUMBNAILS) {
+            map.serialize_entry("thumbnails", &self.video_info.player_response.video_details.thumbnails.0)?;
+        }
+        map.serialize_entry("streams", &self.streams)?;
+
+        map.end()
+    }
+}
+
+/// Serializes a [`Stream`] according to an [`OutputLevel`].
+#[derive(Clone, Debug)]
+pub struct StreamSerializer<'a> {
+    pub output_level: OutputLevel,
+    pub stream: &'a Stream,
+}
+
+impl<'a> StreamSerializer<'a> {
+    #[inline]
+    pub fn new(stream: &'a Stream, output_level: OutputLevel) -> Self {
+        Self { output_level, stream }
+    }
+}
+
+impl Serialize for StreamSerializer<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error> where
+        S: Serializer {
+        macro_rules! serialize {
+            ($self:ident, $map:ident; $($level:expr => { $($field:ident $(with $func:ident)?),* $(,)? })*) => {
+                $(
+                    if self.output_level.contains($level) {
+                        $(
+                            $map.serialize_entry(
+                                stringify!($field),
+                                &serialize!{ @__ser($self.stream, $field $(=> $func)?) }
+                            )?;
+                        )*
+                    }
+                )*
+            };
+            (@__ser($stream:expr, $field:ident)) => { $stream.$field };
+            (@__ser($stream:expr, $field:ident => $func:ident)) => { $func($stream) };
+        }
+
+        let mut map = serializer.serialize_map(None)?;
+
+        serialize!(self, map;
+            OutputLevel::URL => {
+                signature_cipher
+            }
+
+            OutputLevel::GENERAL => {
+                mime with mime, quality, includes_video_track, includes_audio_track,
+                approx_duration_ms, content_length with content_length,
+                estimated_content_length with estimated_content_length
+            }
+            OutputLevel::GENERAL | OutputLevel::VERBOSE => {
+                codecs, is_progressive
+            }
+
+            OutputLevel::VIDEO_TRACK => {
+                height, width, quality_label, fps, video_codec with video_codec
+            }
+            OutputLevel::VIDEO_TRACK | OutputLevel::VERBOSE => {
+                format_type, color_info, high_replication, is_otf
+            }
+
+            OutputLevel::AUDIO_TRACK => {
+                audio_quality, bitrate, audio_sample_rate, audio_channels, loudness_db, audio_track,
+                audio_codec with audio_codec
+            }
+            OutputLevel::AUDIO_TRACK | OutputLevel::VERBOSE => {
+                average_bitrate, is_drc
+            }
+
+            OutputLevel::all() => {
+                index_range, init_range, itag, last_modified, projection_type
+            }
+        );
+
+        map.end()
+    }
+}
+
+#[inline]
+fn mime(stream: &Stream) -> &str {
+    stream.mime.as_ref()
+}
+
+/// The content length, if it's already known, since serializing must not make requests.
+#[inline]
+fn content_length(stream: &Stream) -> Option<u64> {
+    stream.known_content_length()
+}
+
+#[inline]
+fn estimated_content_length(stream: &Stream) -> Option<u64> {
+    stream.estimated_content_length()
+}
+
+#[inline]
+fn video_codec(stream: &Stream) -> Option<crate::VideoCodec> {
+    stream.video_codec()
+}
+
+#[inline]
+fn audio_codec(stream: &Stream) -> Option<crate::AudioCodec> {
+    stream.audio_codec()
+}
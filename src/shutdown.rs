@@ -0,0 +1,140 @@
+//! Stopping all in-flight downloads, i.e. when a service receives `SIGTERM`.
+//!
+//! A [`ShutdownHandle`] is attached to [`Stream`](crate::Stream)s with
+//! [`Stream::with_shutdown`](crate::Stream::with_shutdown), to all streams of a video with
+//! [`Video::with_shutdown`](crate::Video::with_shutdown), or to a
+//! [`SimpleDownloadOptions::shutdown`](crate::SimpleDownloadOptions::shutdown) download. Clones of
+//! a handle share their state, so one handle can be attached to any number of downloads.
+//!
+//! Once the handle is [triggered](ShutdownHandle::trigger), each download stops after the chunk
+//! it's currently writing, flushes the bytes received so far, and fails with
+//! [`Error::Cancelled`](crate::Error::Cancelled). Like after any other failure, the
+//! `<FILENAME>.part` file is removed, unless it should be kept (see
+//! [`SimpleDownloadOptions::keep_partial`](crate::SimpleDownloadOptions::keep_partial)), and the
+//! `on_complete` handler of a [`Callback`](crate::Callback) is called without a path.
+//!
+//! # Example
+//! ```no_run
+//!# use std::time::Duration;
+//!# use rustube::{ShutdownHandle, Video};
+//!# async fn f(video: Video) {
+//! let shutdown = ShutdownHandle::new();
+//! let video = video.with_shutdown(shutdown.clone());
+//! tokio::spawn(async move { video.best_quality().unwrap().download().await });
+//!
+//! // i.e. once the service received SIGTERM
+//! if !shutdown.shutdown(Duration::from_secs(5)).await {
+//!     eprintln!("{} downloads did not stop in time", shutdown.active_downloads());
+//! }
+//!# }
+//! ```
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::sync::{Notify, watch};
+
+/// A shared signal, that stops all downloads it's attached to. See the
+/// [module level documentation](self).
+#[derive(Clone, Debug)]
+pub struct ShutdownHandle {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    signal: watch::Sender<bool>,
+    // keeps the channel open, so sending never fails
+    receiver: watch::Receiver<bool>,
+    active: AtomicUsize,
+    finished: Notify,
+}
+
+impl Default for ShutdownHandle {
+    #[inline]
+    fn default() -> Self {
+        let (signal, receiver) = watch::channel(false);
+        Self {
+            inner: Arc::new(Inner {
+                signal,
+                receiver,
+                active: AtomicUsize::new(0),
+                finished: Notify::new(),
+            }),
+        }
+    }
+}
+
+impl ShutdownHandle {
+    /// Creates a handle, that's not triggered yet.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops all downloads, the handle is attached to, including the ones, that start later.
+    /// Returns immediately (see [`ShutdownHandle::shutdown`]).
+    #[inline]
+    pub fn trigger(&self) {
+        let _ = self.inner.signal.send(true);
+    }
+
+    /// Whether the handle was triggered.
+    #[inline]
+    pub fn is_triggered(&self) -> bool {
+        *self.inner.receiver.borrow()
+    }
+
+    /// The number of downloads, the handle is attached to, that did not finish yet.
+    #[inline]
+    pub fn active_downloads(&self) -> usize {
+        self.inner.active.load(Ordering::SeqCst)
+    }
+
+    /// Triggers the handle, and waits at most `timeout` for all active downloads to stop.
+    ///
+    /// Returns, whether all downloads stopped in time.
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        self.trigger();
+        let all_stopped = async {
+            loop {
+                // registered before checking the count, so no notification is missed
+                let finished = self.inner.finished.notified();
+                if self.active_downloads() == 0 {
+                    break;
+                }
+                finished.await;
+            }
+        };
+        tokio::time::timeout(timeout, all_stopped).await.is_ok()
+    }
+
+    /// Counts a download as active, until the returned guard is dropped.
+    #[inline]
+    pub(crate) fn register(&self) -> ActiveDownload {
+        self.inner.active.fetch_add(1, Ordering::SeqCst);
+        ActiveDownload(Arc::clone(&self.inner))
+    }
+
+    /// Resolves once the handle is triggered.
+    pub(crate) async fn triggered(&self) {
+        let mut receiver = self.inner.receiver.clone();
+        while !*receiver.borrow_and_update() {
+            if receiver.changed().await.is_err() {
+                // the handle itself holds the sender, so this never happens
+                return std::future::pending().await;
+            }
+        }
+    }
+}
+
+/// An active download of a [`ShutdownHandle`].
+pub(crate) struct ActiveDownload(Arc<Inner>);
+
+impl Drop for ActiveDownload {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::SeqCst);
+        self.0.finished.notify_waiters();
+    }
+}
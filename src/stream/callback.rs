@@ -2,12 +2,15 @@ use std::fmt;
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::time::{Duration, Instant};
 
 use futures::FutureExt;
+use tokio::io::AsyncWrite;
 use tokio::sync::{mpsc::{Receiver, Sender}, Mutex};
 use tokio::sync::mpsc;
 
 use crate::Result;
+use super::chunked::ChunkedDownloadOptions;
 
 pub type OnProgressClosure<'a> = Box<dyn FnMut(CallbackArguments) + Send + 'a>;
 pub type OnProgressAsyncClosure<'a> = Box<dyn FnMut(CallbackArguments) -> Pin<Box<dyn Future<Output=()> + Send + 'a>> + Send + Sync + 'a>;
@@ -26,13 +29,45 @@ pub(crate) type InternalSender = Sender<InternalSignal>;
 #[derive(Clone, derivative::Derivative)]
 #[derivative(Debug)]
 pub struct CallbackArguments {
+    /// The number of bytes received so far. This is updated for every network chunk, so it may
+    /// include bytes, that are still in the write buffer, and not written to the file yet.
     pub current_chunk: usize,
     /// It's more idiomatic to use this content length instead of a prefetched value
     /// since the content of this field might change in the future during the download.
+    ///
+    /// If the content length is unavailable, this is the
+    /// [`estimated_content_length`](crate::Stream::estimated_content_length) of the stream.
     pub content_length: Option<u64>,
+    /// The time that passed since the download started.
+    pub elapsed: Duration,
+}
+
+impl CallbackArguments {
+    /// The average download speed in bytes per second since the download started.
+    ///
+    /// Returns `None` if no time has passed yet.
+    #[inline]
+    pub fn speed(&self) -> Option<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        match secs > 0. {
+            true => Some(self.current_chunk as f64 / secs),
+            false => None,
+        }
+    }
+
+    /// The estimated time until the download completes, based on the average [`speed`](Self::speed).
+    ///
+    /// Returns `None` if either the content length or the speed is unknown.
+    #[inline]
+    pub fn eta(&self) -> Option<Duration> {
+        let speed = self.speed().filter(|speed| *speed > 0.)?;
+        let remaining = self.content_length?.saturating_sub(self.current_chunk as u64);
+        Some(Duration::from_secs_f64(remaining as f64 / speed))
+    }
 }
 
 /// Type to process on_progress
+#[derive(Default)]
 pub enum OnProgressType<'a> {
     /// Box containing a closure to execute on progress
     Closure(OnProgressClosure<'a>),
@@ -51,6 +86,7 @@ pub enum OnProgressType<'a> {
     /// bool indicates whether or not to cancel on a closed channel
     /// Will get executed for every MB downloaded
     SlowChannel(Sender<CallbackArguments>, bool),
+    #[default]
     None,
 }
 
@@ -69,18 +105,14 @@ impl<'a> fmt::Debug for OnProgressType<'a> {
     }
 }
 
-impl<'a> Default for OnProgressType<'a> {
-    fn default() -> Self {
-        OnProgressType::None
-    }
-}
-
 /// Type to process on_progress
+#[derive(Default)]
 pub enum OnCompleteType<'a> {
     /// Box containing a closure to execute on complete
     Closure(OnCompleteClosure<'a>),
     /// Box containing a async closure to execute on complete
     AsyncClosure(OnCompleteAsyncClosure<'a>),
+    #[default]
     None,
 }
 
@@ -95,13 +127,24 @@ impl<'a> fmt::Debug for OnCompleteType<'a> {
     }
 }
 
-impl<'a> Default for OnCompleteType<'a> {
-    fn default() -> Self {
-        OnCompleteType::None
-    }
-}
-
 /// Methods and streams to process either on_progress or on_complete
+///
+/// A `Callback<'static>` is `Send`, as is the future returned by the `*_with_callback` methods,
+/// so it can be built in one task, and moved into a [`tokio::spawn`]ed one, that runs the
+/// download.
+///
+/// ```no_run
+///# #[tokio::main(flavor = "current_thread")]
+///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///# use rustube::{Callback, Id, Video};
+/// let video = Video::from_id(Id::from_str("5jlI4uzZGjU")?.into_owned()).await?;
+/// let callback = Callback::new().connect_on_progress_closure(|args| println!("{}", args.current_chunk));
+/// let path = tokio::spawn(async move {
+///     video.best_quality().unwrap().download_with_callback(callback).await
+/// }).await??;
+///# Ok(())
+///# }
+/// ```
 #[derive(Debug)]
 pub struct Callback<'a> {
     pub on_progress: OnProgressType<'a>,
@@ -225,21 +268,21 @@ impl<'a> Default for Callback<'a> {
 
 impl super::Stream {
     /// Attempts to downloads the [`Stream`](super::Stream)s resource.
-    /// This will download the video to <video_id>.mp4 in the current working directory.
+    /// This will download the video to its [`file_name`](Stream::file_name) in the current working directory.
     /// Takes an [`Callback`](crate::stream::callback::Callback)
     #[inline]
-    pub async fn download_with_callback<'a>(&'a self, callback: Callback<'a>) -> Result<PathBuf> {
+    pub async fn download_with_callback<'a>(&self, callback: Callback<'a>) -> Result<PathBuf> {
         self.wrap_callback(|channel| {
             self.internal_download(channel)
         }, callback).await
     }
 
     /// Attempts to downloads the [`Stream`](super::Stream)s resource.
-    /// This will download the video to <video_id>.mp4 in the provided directory.
+    /// This will download the video to its [`file_name`](Stream::file_name) in the provided directory.
     /// Takes an [`Callback`](crate::stream::callback::Callback)
     #[inline]
     pub async fn download_to_dir_with_callback<'a, P: AsRef<Path>>(
-        &'a self,
+        &self,
         dir: P,
         callback: Callback<'a>,
     ) -> Result<PathBuf> {
@@ -252,18 +295,77 @@ impl super::Stream {
     /// This will download the video to the provided file path.
     /// Takes an [`Callback`](crate::stream::callback::Callback)
     #[inline]
-    pub async fn download_to_with_callback<'a, P: AsRef<Path>>(&'a self, path: P, callback: Callback<'a>) -> Result<()> {
+    pub async fn download_to_with_callback<'a, P: AsRef<Path>>(&self, path: P, callback: Callback<'a>) -> Result<()> {
+        let _ = self.wrap_callback(|channel| {
+            self.internal_download_to(path, channel, false, None, super::DEFAULT_WRITE_BUFFER_SIZE)
+        }, callback).await?;
+        Ok(())
+    }
+
+    /// Attempts to downloads the [`Stream`](super::Stream)s resource to the provided file path,
+    /// and resumes an earlier, interrupted download (see
+    /// [`Stream::download_to_resumable`](super::Stream::download_to_resumable)).
+    /// Takes an [`Callback`](crate::stream::callback::Callback)
+    ///
+    /// The progress starts at the number of bytes, that were already downloaded.
+    #[inline]
+    pub async fn download_to_resumable_with_callback<'a, P: AsRef<Path>>(&self, path: P, callback: Callback<'a>) -> Result<()> {
+        let _ = self.wrap_callback(|channel| {
+            self.internal_download_to_resumable(path.as_ref(), channel)
+        }, callback).await?;
+        Ok(())
+    }
+
+    /// Attempts to downloads the [`Stream`](super::Stream)s resource to the provided file path in
+    /// concurrently requested chunks (see [`Stream::download_chunked_with`](super::Stream::download_chunked_with)).
+    /// Takes an [`Callback`](crate::stream::callback::Callback)
+    ///
+    /// The progress is the number of bytes of all chunks, that were received so far.
+    #[inline]
+    pub async fn download_chunked_with_callback<'a, P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: ChunkedDownloadOptions,
+        callback: Callback<'a>,
+    ) -> Result<()> {
         let _ = self.wrap_callback(|channel| {
-            self.internal_download_to(path, channel)
+            self.internal_download_chunked(path.as_ref(), options, channel)
         }, callback).await?;
         Ok(())
     }
 
-    async fn wrap_callback<'a, F: Future<Output=Result<PathBuf>>>(
-        &'a self,
+    /// Attempts to downloads the [`Stream`](super::Stream)s resource into `writer`, and returns
+    /// the number of bytes written (see [`Stream::download_to_writer`](super::Stream::download_to_writer)).
+    /// Takes an [`Callback`](crate::stream::callback::Callback)
+    ///
+    /// Since there's no file, the on_complete callback always receives `None`.
+    #[inline]
+    pub async fn download_to_writer_with_callback<'a, W: AsyncWrite + Unpin + ?Sized>(
+        &self,
+        writer: &mut W,
+        callback: Callback<'a>,
+    ) -> Result<u64> {
+        self.wrap_callback_with(|channel| {
+            self.internal_download_to_writer(writer, channel)
+        }, callback, |_| None).await
+    }
+
+    pub(crate) async fn wrap_callback<'a, T: AsRef<Path>, F: Future<Output=Result<T>>>(
+        &self,
+        to_wrap: impl FnOnce(Option<InternalSender>) -> F,
+        callback: Callback<'a>,
+    ) -> Result<T> {
+        self.wrap_callback_with(to_wrap, callback, |path| Some(path.as_ref().to_path_buf())).await
+    }
+
+    /// Runs the future of `to_wrap` and the callback concurrently. The on_complete callback
+    /// receives the path, `path_of` returns for the result of a successful download.
+    async fn wrap_callback_with<'a, T, F: Future<Output=Result<T>>>(
+        &self,
         to_wrap: impl FnOnce(Option<InternalSender>) -> F,
         mut callback: Callback<'a>,
-    ) -> Result<PathBuf> {
+        path_of: impl FnOnce(&T) -> Option<PathBuf>,
+    ) -> Result<T> {
         let wrap_fut = to_wrap(Some(callback.internal_sender.clone()));
         let aid_fut = self.on_progress(
             callback.internal_receiver.take().expect("Callback cannot be used twice"),
@@ -271,7 +373,7 @@ impl super::Stream {
         );
         let (result, _) = futures::future::join(wrap_fut, aid_fut).await;
 
-        let path = result.as_ref().map(|p| p.clone()).ok();
+        let path = result.as_ref().ok().and_then(path_of);
 
         Self::on_complete(std::mem::take(&mut callback.on_complete), path).await;
 
@@ -279,9 +381,14 @@ impl super::Stream {
     }
 
     #[inline]
-    async fn on_progress<'a>(&'a self, mut receiver: Receiver<InternalSignal>, on_progress: OnProgressType<'a>) {
+    async fn on_progress<'a>(&self, mut receiver: Receiver<InternalSignal>, on_progress: OnProgressType<'a>) {
         let last_trigger = Mutex::new(0);
-        let content_length = self.content_length().await.ok();
+        let started = Instant::now();
+        // the estimate is good enough for progress reports, if the content length is unavailable
+        let content_length = match self.content_length().await {
+            Ok(cl) => Some(cl),
+            Err(_) => self.estimated_content_length(),
+        };
         match on_progress {
             OnProgressType::None => {}
             OnProgressType::Closure(mut closure) => {
@@ -291,6 +398,7 @@ impl super::Stream {
                             let arguments = CallbackArguments {
                                 current_chunk: data,
                                 content_length,
+                                elapsed: started.elapsed(),
                             };
                             closure(arguments);
                         }
@@ -305,6 +413,7 @@ impl super::Stream {
                             let arguments = CallbackArguments {
                                 current_chunk: data,
                                 content_length,
+                                elapsed: started.elapsed(),
                             };
                             closure(arguments).await;
                         }
@@ -319,6 +428,7 @@ impl super::Stream {
                             let arguments = CallbackArguments {
                                 current_chunk: data,
                                 content_length,
+                                elapsed: started.elapsed(),
                             };
                             // await if channel is full
                             if sender.send(arguments).await.is_err() && cancel_on_close {
@@ -341,6 +451,7 @@ impl super::Stream {
                                     let arguments = CallbackArguments {
                                         current_chunk: data,
                                         content_length,
+                                        elapsed: started.elapsed(),
                                     };
                                     closure(arguments)
                                 }
@@ -362,6 +473,7 @@ impl super::Stream {
                                     let arguments = CallbackArguments {
                                         current_chunk: data,
                                         content_length,
+                                        elapsed: started.elapsed(),
                                     };
                                     closure(arguments).await
                                 }
@@ -383,6 +495,7 @@ impl super::Stream {
                                     let arguments = CallbackArguments {
                                         current_chunk: data,
                                         content_length,
+                                        elapsed: started.elapsed(),
                                     };
                                     if sender.send(arguments).await.is_err() && cancel_on_close {
                                         receiver.close()
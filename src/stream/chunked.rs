@@ -0,0 +1,320 @@
+//! Downloading a stream in chunks, that are requested concurrently.
+//!
+//! `googlevideo.com` throttles single connections, so large streams download considerably faster,
+//! when their ranges are requested over multiple connections at once. Each chunk is written to
+//! its offset of the preallocated `.part` file, which is only renamed once all chunks are complete.
+
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt, BufWriter};
+#[cfg(feature = "callback")]
+use tokio::sync::mpsc::error::TrySendError;
+use tokio_stream::StreamExt;
+
+#[cfg(feature = "callback")]
+use super::callback::InternalSignal;
+use super::{DEFAULT_WRITE_BUFFER_SIZE, InternalSender, Stream};
+use crate::{Error, Result};
+use crate::metrics::{Phase, PhaseTimer};
+use crate::shutdown::ShutdownHandle;
+
+/// The default size of a chunk (10 MiB).
+pub const DEFAULT_CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Options for [`Stream::download_chunked_with`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkedDownloadOptions {
+    chunk_size: u64,
+    concurrency: usize,
+    retries: u32,
+}
+
+impl Default for ChunkedDownloadOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            concurrency: 4,
+            retries: 3,
+        }
+    }
+}
+
+impl ChunkedDownloadOptions {
+    /// Creates the default options.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of bytes requested at once. [default: [`DEFAULT_CHUNK_SIZE`]]
+    ///
+    /// # Panics
+    /// When `chunk_size` is 0.
+    #[inline]
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        assert!(chunk_size > 0, "the chunk size must be at least 1");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// How many chunks are requested at a time. [default: 4]
+    ///
+    /// # Panics
+    /// When `concurrency` is 0.
+    #[inline]
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        assert!(concurrency > 0, "the concurrency must be at least 1");
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// How often a chunk, that failed with a [retryable](Error::is_retryable) error, is requested
+    /// again, before the download fails. [default: 3]
+    #[inline]
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+}
+
+impl Stream {
+    /// Attempts to downloads the [`Stream`]s resource to the provided file path in chunks of
+    /// `chunk_size` bytes, of which `concurrency` are requested at a time.
+    ///
+    /// See [`Stream::download_chunked_with`].
+    #[inline]
+    pub async fn download_chunked<P: AsRef<Path>>(&self, path: P, chunk_size: u64, concurrency: usize) -> Result<()> {
+        let options = ChunkedDownloadOptions::new()
+            .chunk_size(chunk_size)
+            .concurrency(concurrency);
+        self.download_chunked_with(path, options).await
+    }
+
+    /// Attempts to downloads the [`Stream`]s resource to the provided file path, by requesting
+    /// its ranges concurrently, which is considerably faster for large streams.
+    ///
+    /// A chunk, whose request fails with a [retryable](Error::is_retryable) error, is requested
+    /// again, up to [`retries`](ChunkedDownloadOptions::retries) times. Streams with an unknown content
+    /// length, and OTF streams, which cannot be requested in ranges, are downloaded sequentially,
+    /// like with [`Stream::download_to`].
+    ///
+    /// ### Errors
+    /// - When a chunk still fails after all retries, or the server does not support ranges.
+    #[inline]
+    pub async fn download_chunked_with<P: AsRef<Path>>(&self, path: P, options: ChunkedDownloadOptions) -> Result<()> {
+        let _ = self.internal_download_chunked(path.as_ref(), options, None).await?;
+        Ok(())
+    }
+
+    pub(super) async fn internal_download_chunked(
+        &self,
+        path: &Path,
+        options: ChunkedDownloadOptions,
+        channel: Option<InternalSender>,
+    ) -> Result<PathBuf> {
+        let content_length = match self.is_otf {
+            true => None,
+            false => self.content_length().await.ok(),
+        };
+        let content_length = match content_length {
+            Some(content_length) => content_length,
+            None => {
+                log::info!("downloading {} sequentially, since it cannot be requested in ranges", self.itag);
+                return self.internal_download_to(path, channel, false, None, DEFAULT_WRITE_BUFFER_SIZE)
+                    .await
+                    .map(|download| download.path);
+            }
+        };
+
+        log::trace!("download_chunked: {:?}", path);
+        let _active = self.shutdown.as_ref().map(ShutdownHandle::register);
+        let mut timer = PhaseTimer::start(self.metrics.as_deref(), Phase::Download);
+        let part_path = super::part_path(path);
+
+        let result = match self.download_chunks(&part_path, content_length, &options, &channel).await {
+            Ok(()) => {
+                timer.add_bytes(content_length);
+                super::replace_file(&part_path, path).await.map_err(Error::from)
+            }
+            Err(e) => Err(e),
+        };
+        drop(timer);
+
+        let result = match result {
+            Ok(()) => {
+                log::info!("downloaded {} successfully to {:?}", self.video_details.video_id, path);
+                Ok(path.to_path_buf())
+            }
+            Err(e) => {
+                log::error!("failed to download {}: {:?}", self.video_details.video_id, e);
+                if let Err(e) = tokio::fs::remove_file(&part_path).await {
+                    log::warn!("failed to remove the partial download {:?}: {}", part_path, e);
+                }
+                Err(self.processing_hint(e))
+            }
+        };
+
+        #[cfg(feature = "callback")]
+        if let Some(channel) = channel {
+            let _ = channel.send(InternalSignal::Finished).await;
+        }
+
+        result
+    }
+
+    /// Preallocates `part_path`, and downloads all chunks into it.
+    // without the `callback` feature, the channel is `Option<()>`
+    #[allow(clippy::clone_on_copy)]
+    async fn download_chunks(
+        &self,
+        part_path: &Path,
+        content_length: u64,
+        options: &ChunkedDownloadOptions,
+        channel: &Option<InternalSender>,
+    ) -> Result<()> {
+        File::create(part_path).await?.set_len(content_length).await?;
+
+        let stream = Arc::new(self.clone());
+        let progress = Arc::new(AtomicUsize::new(0));
+        let chunk_size = options.chunk_size;
+        let mut chunks = (0..content_length.div_ceil(chunk_size))
+            .map(|chunk| chunk * chunk_size)
+            .map(|start| (start, (start + chunk_size).min(content_length)));
+
+        let mut tasks = tokio::task::JoinSet::new();
+        loop {
+            while tasks.len() < options.concurrency {
+                let (start, end) = match chunks.next() {
+                    Some(chunk) => chunk,
+                    None => break,
+                };
+                let stream = Arc::clone(&stream);
+                let part_path = part_path.to_path_buf();
+                let progress = Arc::clone(&progress);
+                let channel = channel.clone();
+                let retries = options.retries;
+                tasks.spawn(async move {
+                    stream.download_chunk_with_retries(&part_path, start, end, retries, &progress, &channel).await
+                });
+            }
+
+            match crate::error::join_next(&mut tasks).await {
+                // dropping `tasks` aborts the remaining chunks
+                Some(result) => result??,
+                None => break,
+            }
+        }
+
+        File::open(part_path).await?.sync_all().await?;
+        Ok(())
+    }
+
+    async fn download_chunk_with_retries(
+        &self,
+        part_path: &Path,
+        start: u64,
+        end: u64,
+        retries: u32,
+        progress: &AtomicUsize,
+        channel: &Option<InternalSender>,
+    ) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            let mut written = 0;
+            let result = self.download_chunk(part_path, start, end, &mut written, progress, channel).await;
+            match result {
+                Err(e) if attempt < retries && e.is_retryable() => {
+                    // the bytes of the failed attempt are written again
+                    progress.fetch_sub(written, Ordering::SeqCst);
+                    attempt += 1;
+                    log::warn!(
+                        "failed to download the bytes {}..{} of {} (attempt {} of {}): {}",
+                        start, end, self.video_details.video_id, attempt, retries + 1, e,
+                    );
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Downloads the bytes `start..end` to their offset in `part_path`. The number of bytes
+    /// written is added to both `written` and `progress`.
+    #[allow(unused_variables)]
+    async fn download_chunk(
+        &self,
+        part_path: &Path,
+        start: u64,
+        end: u64,
+        written: &mut usize,
+        progress: &AtomicUsize,
+        channel: &Option<InternalSender>,
+    ) -> Result<()> {
+        let range = format!("bytes={}-{}", start, end - 1);
+        let res = tokio::select! {
+            biased;
+            _ = self.shutdown_triggered() => return Err(Error::Cancelled),
+            res = self.get_range(&self.signature_cipher.url, &range) => res?,
+        };
+        if res.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Err(Error::UnexpectedResponse(format!(
+                "expected the bytes {}..{}, but the server ignored the range (status {})",
+                start, end, res.status(),
+            ).into()));
+        }
+        if Self::extract_content_range_start(&res)? != start {
+            return Err(Error::UnexpectedResponse(format!(
+                "expected the bytes {}..{}, but got a different range", start, end
+            ).into()));
+        }
+
+        let mut file = OpenOptions::new().write(true).open(part_path).await?;
+        file.seek(SeekFrom::Start(start)).await?;
+        let mut file = BufWriter::with_capacity(DEFAULT_WRITE_BUFFER_SIZE, file);
+
+        let shutdown = self.shutdown_triggered();
+        tokio::pin!(shutdown);
+        let mut stream = res.bytes_stream();
+        loop {
+            let chunk = tokio::select! {
+                biased;
+                _ = &mut shutdown => return Err(Error::Cancelled),
+                chunk = stream.next() => chunk,
+            };
+            let chunk = match chunk {
+                Some(chunk) => chunk?,
+                None => break,
+            };
+            if *written as u64 + chunk.len() as u64 > end - start {
+                return Err(Error::UnexpectedResponse(format!(
+                    "the server sent more than the bytes {}..{}", start, end
+                ).into()));
+            }
+            if let Some(ref transfer_stats) = self.transfer_stats {
+                transfer_stats.record_media(chunk.len() as u64)?;
+            }
+
+            file.write_all(&chunk).await?;
+            *written += chunk.len();
+            let total = progress.fetch_add(chunk.len(), Ordering::SeqCst) + chunk.len();
+            #[cfg(feature = "callback")]
+            if let Some(channel) = channel {
+                if let Err(TrySendError::Closed(_)) = channel.try_send(InternalSignal::Value(total)) {
+                    return Err(Error::ChannelClosed);
+                }
+            }
+        }
+        file.flush().await?;
+
+        match *written as u64 == end - start {
+            true => Ok(()),
+            false => Err(Error::UnexpectedResponse(format!(
+                "the bytes {}..{} ended after {} bytes", start, end, written
+            ).into())),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Parsers for the codec strings of [`Stream`](crate::Stream)s (RFC 6381), i.e. `avc1.640028`,
+//! `vp09.00.10.08`, `av01.0.08M.08` or `mp4a.40.2`.
+//!
+//! Parsing never fails. Codecs, that are unknown or malformed, are kept as `Other`.
+
+/// A video codec, including the details players need to know, to decide whether they can play it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum VideoCodec {
+    /// H.264 (`avc1.PPCCLL`).
+    Avc1 {
+        /// The `profile_idc`, i.e. 66 (Baseline), 77 (Main) or 100 (High).
+        profile: u8,
+        /// The `level_idc`, which is ten times the level, i.e. 41 for level 4.1.
+        level: u8,
+    },
+    /// VP9 (`vp9`, or `vp09.PP.LL.DD`).
+    Vp9 {
+        /// The profile (0-3). Plain `vp9` is profile 0.
+        profile: u8,
+        /// The level times ten, i.e. 41 for level 4.1, if specified.
+        level: Option<u8>,
+        /// The bit depth (8, 10 or 12), if specified.
+        bit_depth: Option<u8>,
+    },
+    /// AV1 (`av01.P.LLT.DD`).
+    Av1 {
+        /// The profile (0: Main, 1: High, 2: Professional).
+        profile: u8,
+        /// The `seq_level_idx`, i.e. 8 for level 4.0.
+        level: u8,
+        tier: Av1Tier,
+        /// The bit depth (8, 10 or 12).
+        bit_depth: u8,
+    },
+    /// A codec, that is unknown, or could not be parsed.
+    Other(String),
+}
+
+/// The tier of an [`VideoCodec::Av1`] stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Av1Tier {
+    Main,
+    High,
+}
+
+/// An audio codec, including the details players need to know, to decide whether they can play it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AudioCodec {
+    /// AAC (`mp4a.40.OT`).
+    Aac {
+        /// The MPEG-4 audio object type, i.e. 2 (AAC-LC), 5 (HE-AAC) or 29 (HE-AAC v2).
+        object_type: u8,
+    },
+    /// Opus (`opus`).
+    Opus,
+    /// Vorbis (`vorbis`).
+    Vorbis,
+    /// Dolby Digital (`ac-3`).
+    Ac3,
+    /// Dolby Digital Plus (`ec-3`).
+    Ec3,
+    /// FLAC (`flac`).
+    Flac,
+    /// A codec, that is unknown, or could not be parsed.
+    Other(String),
+}
+
+/// The codecs a player supports. See [`VideoCodec::is_supported_by`],
+/// [`AudioCodec::is_supported_by`] and [`Stream::is_supported_by`](crate::Stream::is_supported_by).
+///
+/// By default, nothing is supported.
+///
+/// # Example
+/// ```
+///# use rustube::{CodecSupport, VideoCodec};
+/// // H.264 up to High@4.1, and stereo AAC
+/// let support = CodecSupport {
+///     max_avc: Some((100, 41)),
+///     aac: true,
+///     max_audio_channels: Some(2),
+///     ..CodecSupport::default()
+/// };
+///
+/// assert!(VideoCodec::parse("avc1.640028").is_supported_by(&support));
+/// assert!(!VideoCodec::parse("avc1.640033").is_supported_by(&support));
+/// assert!(!VideoCodec::parse("vp9").is_supported_by(&support));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CodecSupport {
+    /// The highest supported H.264 `profile_idc` and `level_idc` (i.e. `(100, 41)` for High@4.1),
+    /// if H.264 is supported.
+    pub max_avc: Option<(u8, u8)>,
+    /// The highest supported VP9 profile, if VP9 is supported.
+    pub max_vp9_profile: Option<u8>,
+    /// The highest supported AV1 profile and `seq_level_idx`, if AV1 is supported.
+    pub max_av1: Option<(u8, u8)>,
+    /// The highest supported bit depth of VP9 and AV1 streams, or `None` for no limit.
+    pub max_bit_depth: Option<u8>,
+    /// Whether AAC-LC is supported.
+    pub aac: bool,
+    /// Whether HE-AAC (v1 and v2) is supported, in addition to AAC-LC.
+    pub he_aac: bool,
+    pub opus: bool,
+    pub vorbis: bool,
+    /// The highest number of audio channels, or `None` for no limit.
+    pub max_audio_channels: Option<u8>,
+}
+
+impl VideoCodec {
+    /// Parses a single codec string, i.e. `avc1.640028`. Unknown or malformed codecs are returned
+    /// as [`VideoCodec::Other`].
+    pub fn parse(codec: &str) -> Self {
+        let codec = codec.trim();
+        Self::parse_known(codec).unwrap_or_else(|| Self::Other(codec.to_owned()))
+    }
+
+    fn parse_known(codec: &str) -> Option<Self> {
+        let (fourcc, params) = codec.split_once('.').unwrap_or((codec, ""));
+
+        match fourcc {
+            "avc1" | "avc3" => {
+                // also guarantees, that the params can be sliced at any index
+                if params.len() != 6 || !params.is_ascii() {
+                    return None;
+                }
+                Some(Self::Avc1 {
+                    profile: hex_byte(&params[0..2])?,
+                    level: hex_byte(&params[4..6])?,
+                })
+            }
+            "vp9" => match params {
+                "" => Some(Self::Vp9 { profile: 0, level: None, bit_depth: None }),
+                profile => Some(Self::Vp9 { profile: decimal(profile)?, level: None, bit_depth: None }),
+            },
+            "vp09" => {
+                let mut params = params.split('.');
+                let vp9 = Self::Vp9 {
+                    profile: decimal(params.next()?)?,
+                    level: Some(decimal(params.next()?)?),
+                    bit_depth: Some(decimal(params.next()?)?),
+                };
+                // the remaining parameters (chroma subsampling, color info, ...) are optional
+                params.try_for_each(|param| decimal(param).map(drop))?;
+                Some(vp9)
+            }
+            "av01" => {
+                let mut params = params.split('.');
+                let profile = decimal(params.next()?)?;
+                let level_tier = params.next()?;
+                let (level, tier) = match level_tier.strip_suffix('M') {
+                    Some(level) => (level, Av1Tier::Main),
+                    None => (level_tier.strip_suffix('H')?, Av1Tier::High),
+                };
+                let av1 = Self::Av1 {
+                    profile,
+                    level: decimal(level)?,
+                    tier,
+                    bit_depth: decimal(params.next()?)?,
+                };
+                params.try_for_each(|param| decimal(param).map(drop))?;
+                Some(av1)
+            }
+            _ => None,
+        }
+    }
+
+    /// The bit depth of the codec, if it's known.
+    #[inline]
+    pub fn bit_depth(&self) -> Option<u8> {
+        match self {
+            Self::Vp9 { bit_depth, .. } => *bit_depth,
+            Self::Av1 { bit_depth, .. } => Some(*bit_depth),
+            Self::Avc1 { .. } | Self::Other(_) => None,
+        }
+    }
+
+    /// Whether a player with the capabilities `support` can play this codec.
+    /// [`VideoCodec::Other`] is never supported.
+    pub fn is_supported_by(&self, support: &CodecSupport) -> bool {
+        let bit_depth_supported = match (self.bit_depth(), support.max_bit_depth) {
+            (Some(bit_depth), Some(max)) => bit_depth <= max,
+            _ => true,
+        };
+
+        bit_depth_supported && match *self {
+            Self::Avc1 { profile, level } => support.max_avc
+                .is_some_and(|(max_profile, max_level)| profile <= max_profile && level <= max_level),
+            Self::Vp9 { profile, .. } => support.max_vp9_profile
+                .is_some_and(|max_profile| profile <= max_profile),
+            Self::Av1 { profile, level, .. } => support.max_av1
+                .is_some_and(|(max_profile, max_level)| profile <= max_profile && level <= max_level),
+            Self::Other(_) => false,
+        }
+    }
+}
+
+impl AudioCodec {
+    /// Parses a single codec string, i.e. `mp4a.40.2`. Unknown or malformed codecs are returned
+    /// as [`AudioCodec::Other`].
+    pub fn parse(codec: &str) -> Self {
+        let codec = codec.trim();
+        Self::parse_known(codec).unwrap_or_else(|| Self::Other(codec.to_owned()))
+    }
+
+    fn parse_known(codec: &str) -> Option<Self> {
+        match codec {
+            "opus" => Some(Self::Opus),
+            "vorbis" => Some(Self::Vorbis),
+            "ac-3" => Some(Self::Ac3),
+            "ec-3" => Some(Self::Ec3),
+            "flac" | "fLaC" => Some(Self::Flac),
+            _ => {
+                // 40 is the object type indication of MPEG-4 audio
+                let object_type = codec.strip_prefix("mp4a.40.")?;
+                Some(Self::Aac { object_type: decimal(object_type)? })
+            }
+        }
+    }
+
+    /// Whether a player with the capabilities `support` can play this codec.
+    /// [`AudioCodec::Other`] is never supported.
+    pub fn is_supported_by(&self, support: &CodecSupport) -> bool {
+        match self {
+            // AAC-LC
+            Self::Aac { object_type: 2 } => support.aac,
+            // HE-AAC (v2) streams can be decoded as AAC-LC, but only with half the sample rate
+            Self::Aac { object_type: 5 | 29 } => support.he_aac,
+            Self::Opus => support.opus,
+            Self::Vorbis => support.vorbis,
+            Self::Aac { .. } | Self::Ac3 | Self::Ec3 | Self::Flac | Self::Other(_) => false,
+        }
+    }
+}
+
+#[inline]
+fn hex_byte(s: &str) -> Option<u8> {
+    match s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        true => u8::from_str_radix(s, 16).ok(),
+        false => None,
+    }
+}
+
+#[inline]
+fn decimal(s: &str) -> Option<u8> {
+    match !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+        true => s.parse().ok(),
+        false => None,
+    }
+}
@@ -0,0 +1,269 @@
+//! Selecting [`Stream`]s by their tracks, format, and quality, with a single query, that's shared
+//! by the library and the CLI.
+
+use std::cmp::Ordering;
+
+use super::Stream;
+use crate::video_info::player_response::streaming_data::{AudioQuality, Quality, QualityLabel};
+
+/// A query, that selects [`Stream`]s of a video.
+///
+/// The constraints are combined, so a stream has to satisfy all of them. Setting a constraint
+/// again replaces it, i.e. `.only_video().only_audio()` selects audio only streams. Constraints,
+/// that contradict each other, like `.only_audio().progressive()`, select no stream at all.
+///
+/// Like the selection helpers of [`Video`](crate::Video), a query skips streams, that are only
+/// available to YouTube Premium subscribers (see [`StreamQuery::include_premium`]), and, for videos
+/// with multiple audio tracks, streams of other than the original audio track (see
+/// [`StreamQuery::audio_language`]).
+///
+/// # Example
+/// ```no_run
+///# use rustube::{StreamQuery, Video};
+///# fn f(video: &Video) {
+/// let stream = StreamQuery::new()
+///     .only_video()
+///     .mime("video/mp4")
+///     .min_resolution(720)
+///     .worst(video);
+///# }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreamQuery {
+    video: Option<bool>,
+    audio: Option<bool>,
+    progressive: Option<bool>,
+    mime: Option<String>,
+    quality: Option<Quality>,
+    quality_label: Option<QualityLabel>,
+    audio_quality: Option<AudioQuality>,
+    min_resolution: Option<u64>,
+    max_resolution: Option<u64>,
+    max_bitrate: Option<u64>,
+    audio_language: Option<String>,
+    include_premium: bool,
+    order: StreamOrder,
+}
+
+/// The order, in which [`StreamQuery::best`] and [`StreamQuery::worst`] compare streams.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+enum StreamOrder {
+    /// By their [`Stream::rank`].
+    #[default]
+    Rank,
+    /// By their bitrate, and by their rank for equal bitrates.
+    Bitrate,
+}
+
+impl StreamQuery {
+    /// Creates a query, that selects all streams, apart from Premium streams and other than the
+    /// original audio tracks.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Selects streams, that have (`true`), or don't have (`false`) a video track.
+    /// [default: both]
+    #[inline]
+    pub fn has_video(mut self, has_video: bool) -> Self {
+        self.video = Some(has_video);
+        self
+    }
+
+    /// Selects streams, that have (`true`), or don't have (`false`) an audio track.
+    /// [default: both]
+    #[inline]
+    pub fn has_audio(mut self, has_audio: bool) -> Self {
+        self.audio = Some(has_audio);
+        self
+    }
+
+    /// Selects streams with a video, but without an audio track.
+    #[inline]
+    pub fn only_video(self) -> Self {
+        self.has_video(true).has_audio(false)
+    }
+
+    /// Selects streams with an audio, but without a video track.
+    #[inline]
+    pub fn only_audio(self) -> Self {
+        self.has_video(false).has_audio(true)
+    }
+
+    /// Selects progressive streams, which contain both a video and an audio track.
+    #[inline]
+    pub fn progressive(mut self) -> Self {
+        self.progressive = Some(true);
+        self
+    }
+
+    /// Selects adaptive (DASH) streams, which contain either a video, or an audio track.
+    #[inline]
+    pub fn adaptive(mut self) -> Self {
+        self.progressive = Some(false);
+        self
+    }
+
+    /// Selects streams with the mime type `mime`, i.e. `video/mp4`. The parameters of the stream's
+    /// mime type, like the codecs, are ignored.
+    #[inline]
+    pub fn mime(mut self, mime: impl Into<String>) -> Self {
+        self.mime = Some(mime.into());
+        self
+    }
+
+    /// Selects streams with the [`Quality`] `quality`.
+    #[inline]
+    pub fn quality(mut self, quality: Quality) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    /// Selects streams with the [`QualityLabel`] `quality_label`. Premium streams are selected
+    /// as well, if `quality_label` is a Premium label.
+    #[inline]
+    pub fn quality_label(mut self, quality_label: QualityLabel) -> Self {
+        self.quality_label = Some(quality_label);
+        self
+    }
+
+    /// Selects streams with the [`AudioQuality`] `audio_quality`.
+    #[inline]
+    pub fn audio_quality(mut self, audio_quality: AudioQuality) -> Self {
+        self.audio_quality = Some(audio_quality);
+        self
+    }
+
+    /// Selects streams with a video track of at least `height` pixels, i.e. `720`. Streams
+    /// without a video track are not selected.
+    #[inline]
+    pub fn min_resolution(mut self, height: u64) -> Self {
+        self.min_resolution = Some(height);
+        self
+    }
+
+    /// Selects streams with a video track of at most `height` pixels, i.e. `1080`. Streams
+    /// without a video track, or without a known height, are not selected.
+    #[inline]
+    pub fn max_resolution(mut self, height: u64) -> Self {
+        self.max_resolution = Some(height);
+        self
+    }
+
+    /// Selects streams with a bitrate of at most `bitrate` bits per second. Streams without a
+    /// known bitrate are selected as well.
+    #[inline]
+    pub fn max_bitrate(mut self, bitrate: u64) -> Self {
+        self.max_bitrate = Some(bitrate);
+        self
+    }
+
+    /// Selects streams with the audio track in the language `lang_tag` (i.e. `en` or `de-DE`),
+    /// for videos with multiple audio tracks (see
+    /// [`AudioTrack::is_language`](crate::video_info::player_response::streaming_data::AudioTrack::is_language)).
+    /// Streams with an audio track, but without audio track information, are not selected.
+    ///
+    /// [default: the original audio track]
+    #[inline]
+    pub fn audio_language(mut self, lang_tag: impl Into<String>) -> Self {
+        self.audio_language = Some(lang_tag.into());
+        self
+    }
+
+    /// Whether to select streams, that are only available to YouTube Premium subscribers (see
+    /// [`Stream::is_premium_only`]). Downloading them fails without a Premium account.
+    ///
+    /// [default: `false`]
+    #[inline]
+    pub fn include_premium(mut self, include_premium: bool) -> Self {
+        self.include_premium = include_premium;
+        self
+    }
+
+    /// Compares streams by their bitrate, instead of their [`Stream::rank`].
+    #[inline]
+    pub fn order_by_bitrate(mut self) -> Self {
+        self.order = StreamOrder::Bitrate;
+        self
+    }
+
+    /// Whether `stream` satisfies all constraints of the query.
+    pub fn matches(&self, stream: &Stream) -> bool {
+        let tracks_ok = self.video.is_none_or(|video| stream.includes_video_track == video)
+            && self.audio.is_none_or(|audio| stream.includes_audio_track == audio)
+            && self.progressive.is_none_or(|progressive| {
+                (stream.includes_video_track && stream.includes_audio_track) == progressive
+            });
+        let mime_ok = self.mime
+            .as_deref()
+            .is_none_or(|mime| stream.mime.essence_str().eq_ignore_ascii_case(mime));
+        let quality_ok = self.quality.as_ref().is_none_or(|quality| stream.quality == *quality)
+            && self.quality_label.as_ref().is_none_or(|label| stream.quality_label.as_ref() == Some(label))
+            && self.audio_quality.as_ref().is_none_or(|quality| stream.audio_quality.as_ref() == Some(quality))
+            && self.min_resolution.is_none_or(|height| {
+                stream.includes_video_track && stream.height.unwrap_or(0) >= height
+            })
+            && self.max_resolution.is_none_or(|height| {
+                stream.includes_video_track && stream.height.is_some_and(|h| h <= height)
+            })
+            && self.max_bitrate.is_none_or(|max| stream.bitrate.is_none_or(|bitrate| bitrate <= max));
+
+        let audio_language_ok = match (&self.audio_language, &stream.audio_track) {
+            (Some(lang), Some(track)) => track.is_language(lang),
+            (Some(_), None) => !stream.includes_audio_track,
+            (None, Some(track)) => track.is_default,
+            (None, None) => true,
+        };
+        let premium_ok = self.include_premium
+            || !stream.is_premium_only()
+            || self.quality_label.as_ref().is_some_and(QualityLabel::is_premium);
+
+        tracks_ok && mime_ok && quality_ok && audio_language_ok && premium_ok
+    }
+
+    /// Compares `lhs` and `rhs` in the order of the query. A greater stream is a better one.
+    pub fn compare(&self, lhs: &Stream, rhs: &Stream) -> Ordering {
+        match self.order {
+            StreamOrder::Rank => lhs.rank().cmp(&rhs.rank()),
+            StreamOrder::Bitrate => bitrate(lhs)
+                .cmp(&bitrate(rhs))
+                .then_with(|| lhs.rank().cmp(&rhs.rank())),
+        }
+    }
+
+    /// The best of the `streams`, that match the query.
+    ///
+    /// `streams` is either a [`Video`](crate::Video), or any iterator over [`Stream`]s.
+    #[inline]
+    pub fn best<'a>(&self, streams: impl IntoIterator<Item=&'a Stream>) -> Option<&'a Stream> {
+        streams
+            .into_iter()
+            .filter(|stream| self.matches(stream))
+            .max_by(|lhs, rhs| self.compare(lhs, rhs))
+    }
+
+    /// The worst of the `streams`, that match the query.
+    #[inline]
+    pub fn worst<'a>(&self, streams: impl IntoIterator<Item=&'a Stream>) -> Option<&'a Stream> {
+        streams
+            .into_iter()
+            .filter(|stream| self.matches(stream))
+            .min_by(|lhs, rhs| self.compare(lhs, rhs))
+    }
+
+    /// All `streams`, that match the query, from the best to the worst one.
+    pub fn all<'a>(&self, streams: impl IntoIterator<Item=&'a Stream>) -> Vec<&'a Stream> {
+        let mut streams: Vec<_> = streams
+            .into_iter()
+            .filter(|stream| self.matches(stream))
+            .collect();
+        streams.sort_by(|lhs, rhs| self.compare(rhs, lhs));
+        streams
+    }
+}
+
+#[inline]
+fn bitrate(stream: &Stream) -> u64 {
+    stream.bitrate.or(stream.average_bitrate).unwrap_or(0)
+}
@@ -0,0 +1,90 @@
+//! Diagnosing why YouTube refused to serve a stream (`403 Forbidden`).
+//!
+//! YouTube doesn't tell why it refuses a request, but the query parameters of the stream URL
+//! usually reveal the most probable cause. The constants below are the possible `hint`s of an
+//! [`Error::Forbidden`](crate::Error::Forbidden).
+
+use std::net::IpAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{TimeZone, Utc};
+use url::Url;
+
+use crate::{Error, IdBuf};
+
+/// The URL expired (after about six hours). Expired URLs are reported as
+/// [`Error::UrlExpired`](crate::Error::UrlExpired) instead of with this hint.
+pub const EXPIRED: &str = "the stream URL expired. Fetch the video again to get fresh URLs";
+/// The URL has no signature, so the signature cipher was not applied.
+pub const UNSIGNED: &str = "the stream URL is not signed, so the signature was probably not \
+descrambled correctly";
+/// The `n` parameter was not transformed.
+pub const N_NOT_TRANSFORMED: &str = "the `n` parameter of the stream URL was not transformed. \
+Configure a JsRuntime (see `VideoFetcher::with_js_runtime`), or use a different innertube client";
+/// The URL belongs to a web client, and has no proof of origin token.
+pub const MISSING_PO_TOKEN: &str = "the stream URL has no proof of origin token (`pot`), which \
+YouTube requires for the web clients. Use a different innertube client";
+/// The URL is bound to the IP address, it was fetched from.
+pub const IP_MISMATCH: &str = "the stream URL is bound to the IP address it was fetched from. \
+Download it from the same network (i.e. without switching proxies or VPNs in between), or get URLs \
+for the downloading host with `Video::refetch_streams_via`";
+/// None of the above.
+pub const UNKNOWN: &str = "the cause is unknown. Fetch the video again, or use a different \
+innertube client";
+
+/// The innertube clients (the `c` parameter of the URL), whose streams require a `pot`.
+const PO_TOKEN_CLIENTS: &[&str] = &["WEB", "MWEB", "WEB_EMBEDDED_PLAYER"];
+
+/// Builds an [`Error::Forbidden`] from the query parameters of `url`, or an
+/// [`Error::UrlExpired`], if its `expire` parameter is in the past.
+///
+/// `host` is the host, that refused the request, which differs from the one of `url`, if the
+/// request was redirected. `n_transformed` tells whether the `n` parameter of the URL was
+/// replaced by the descrambler. URLs without an `n` parameter count as transformed.
+pub(crate) fn diagnose(video_id: IdBuf, itag: u64, url: &Url, host: Option<&str>, n_transformed: bool) -> Error {
+    let param = |name: &str| url
+        .query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned());
+
+    let has_sig = param("sig").is_some() || param("signature").is_some();
+    let has_n_transformed = n_transformed || param("n").is_none();
+    let has_pot = param("pot").is_some();
+    let url_ip = param("ip").and_then(|ip| ip.parse::<IpAddr>().ok());
+    let expire_secs = param("expire").and_then(|expire| expire.parse::<i64>().ok());
+    let expire = expire_secs.and_then(|secs| Utc.timestamp_opt(secs, 0).single());
+    let client = param("c");
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs() as i64);
+
+    if let Some(expire) = expire.filter(|_| expire_secs.is_some_and(|expire| expire <= now)) {
+        return Error::UrlExpired { video_id, itag, expire };
+    }
+
+    let hint = if !has_sig {
+        UNSIGNED
+    } else if !has_n_transformed {
+        N_NOT_TRANSFORMED
+    } else if !has_pot && client.is_some_and(|c| PO_TOKEN_CLIENTS.contains(&c.as_str())) {
+        MISSING_PO_TOKEN
+    } else if url_ip.is_some() {
+        IP_MISMATCH
+    } else {
+        UNKNOWN
+    };
+
+    Error::Forbidden {
+        video_id,
+        itag,
+        has_sig,
+        has_n_transformed,
+        has_pot,
+        url_ip,
+        expire,
+        host: host.map(str::to_owned),
+        hint,
+    }
+}
+
@@ -1,22 +1,29 @@
+use std::net::IpAddr;
 use std::ops::Range;
 #[cfg(feature = "download")]
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use mime::Mime;
 use reqwest::Client;
 use serde_with::{DisplayFromStr, serde_as};
+use tokio::sync::OnceCell;
 #[cfg(feature = "download")]
 use tokio::{
-    fs::File,
-    io::AsyncWriteExt,
+    fs::{File, OpenOptions},
+    io::{AsyncWrite, AsyncWriteExt, BufWriter},
 };
 #[cfg(feature = "callback")]
 use tokio::sync::mpsc::error::TrySendError;
 #[cfg(feature = "download")]
 use tokio_stream::StreamExt;
+#[cfg(feature = "download")]
+use reader::{DEFAULT_PREFETCH, StreamReader};
+#[cfg(feature = "download")]
+use hash::{HashAlgorithm, Hasher};
+#[cfg(feature = "download")]
+use retry::RetryPolicy;
 
 #[cfg(feature = "callback")]
 use callback::{InternalSender, InternalSignal};
@@ -25,9 +32,17 @@ use callback::Callback;
 
 #[cfg(feature = "download")]
 use crate::{Error, Result};
+use crate::fetcher::UserAgent;
+use crate::metrics::Metrics;
+use crate::transfer::TransferStats;
+use codec::{AudioCodec, CodecSupport, VideoCodec};
+#[cfg(feature = "download")]
+use crate::metrics::{Phase, PhaseTimer};
+#[cfg(feature = "download")]
+use crate::shutdown::ShutdownHandle;
 use crate::{
     video_info::player_response::streaming_data::{
-        AudioQuality, ColorInfo, FormatType, ProjectionType,
+        AudioQuality, AudioTrack, ColorInfo, FormatType, ProjectionType,
         Quality, QualityLabel, RawFormat, SignatureCipher,
     },
     VideoDetails,
@@ -35,6 +50,21 @@ use crate::{
 
 #[cfg(feature = "callback")]
 pub mod callback;
+#[cfg(feature = "download")]
+pub mod chunked;
+pub mod codec;
+pub mod filter;
+#[cfg(feature = "download")]
+pub mod forbidden;
+#[cfg(any(feature = "download", feature = "cache"))]
+pub mod hash;
+pub mod rank;
+#[cfg(feature = "download")]
+pub mod reader;
+#[cfg(feature = "download")]
+mod redirector;
+#[cfg(feature = "download")]
+pub mod retry;
 
 // todo:
 //  there are different types of streams: video, audio, and video + audio
@@ -43,6 +73,37 @@ pub mod callback;
 #[cfg(all(not(feature = "callback"), feature = "download"))]
 type InternalSender = ();
 
+/// The default number of bytes, that are collected in memory before they're written to the file.
+///
+/// Network chunks are often only a few kilobytes large, so writing each of them separately
+/// would cost one syscall per chunk.
+#[cfg(feature = "download")]
+pub const DEFAULT_WRITE_BUFFER_SIZE: usize = 256 * 1024;
+
+/// A completed download.
+#[cfg(feature = "download")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownloadSummary {
+    /// The path of the downloaded file.
+    pub path: PathBuf,
+    /// The size of the downloaded file in bytes, which is also the number of media bytes
+    /// transferred.
+    pub bytes: u64,
+    /// The lowercase hex digest of the file, if a [`HashAlgorithm`] was requested.
+    pub digest: Option<String>,
+    /// The host, the stream was finally downloaded from, after following redirects to other
+    /// `googlevideo.com` mirrors (i.e. `rr3---sn-4g5e6nsz.googlevideo.com`).
+    pub host: Option<String>,
+}
+
+#[cfg(feature = "download")]
+impl AsRef<Path> for DownloadSummary {
+    #[inline]
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
 /// A downloadable video Stream, that contains all the important information.
 #[serde_as]
 #[derive(Clone, derivative::Derivative, serde::Deserialize, serde::Serialize)]
@@ -59,16 +120,19 @@ pub struct Stream {
     pub audio_channels: Option<u8>,
     pub audio_quality: Option<AudioQuality>,
     pub audio_sample_rate: Option<u64>,
+    pub audio_track: Option<AudioTrack>,
     pub average_bitrate: Option<u64>,
     pub bitrate: Option<u64>,
     pub color_info: Option<ColorInfo>,
-    #[derivative(PartialEq(compare_with = "atomic_u64_is_eq"))]
-    content_length: Arc<AtomicU64>,
+    #[serde(with = "content_length")]
+    #[derivative(PartialEq(compare_with = "content_length::is_eq"))]
+    content_length: Arc<OnceCell<ContentLength>>,
     pub fps: u8,
     pub height: Option<u64>,
     pub high_replication: Option<bool>,
     pub index_range: Option<Range<u64>>,
     pub init_range: Option<Range<u64>>,
+    pub is_drc: bool,
     pub is_otf: bool,
     pub itag: u64,
     pub last_modified: Option<DateTime<Utc>>,
@@ -82,13 +146,41 @@ pub struct Stream {
     #[allow(dead_code)]
     #[serde(skip)]
     #[derivative(Debug = "ignore", PartialEq = "ignore")]
-    client: Client,
+    pub(crate) client: Client,
+    #[serde(skip)]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub(crate) metrics: Option<Arc<Metrics>>,
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore")]
+    pub(crate) transfer_stats: Option<Arc<TransferStats>>,
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore")]
+    pub(crate) user_agent: Option<UserAgent>,
+    #[serde(skip)]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub(crate) extra_headers: reqwest::header::HeaderMap,
+    #[cfg(feature = "download")]
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore")]
+    pub(crate) shutdown: Option<ShutdownHandle>,
+    #[cfg(feature = "download")]
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore")]
+    pub(crate) retry_policy: Option<RetryPolicy>,
 }
 
 
 impl Stream {
+    /// Creates a [`Stream`] from a [`RawFormat`].
+    ///
+    /// The [`SignatureCipher`] of `raw_format` is expected to already be descrambled, otherwise
+    /// downloading the stream will fail.
+    ///
+    /// Not part of the public API: it only exists, so the integration tests can build streams from
+    /// fixtures, and may change at any time.
     // maybe deserialize RawFormat seeded with client and VideoDetails
-    pub(crate) fn from_raw_format(raw_format: RawFormat, client: Client, video_details: Arc<VideoDetails>) -> Self {
+    #[doc(hidden)]
+    pub fn from_raw_format(raw_format: RawFormat, client: Client, video_details: Arc<VideoDetails>) -> Self {
         Self {
             is_progressive: is_progressive(&raw_format.mime_type.codecs),
             includes_video_track: includes_video_track(&raw_format.mime_type.codecs, &raw_format.mime_type.mime),
@@ -100,15 +192,17 @@ impl Stream {
             audio_channels: raw_format.audio_channels,
             audio_quality: raw_format.audio_quality,
             audio_sample_rate: raw_format.audio_sample_rate,
+            audio_track: raw_format.audio_track,
             average_bitrate: raw_format.average_bitrate,
             bitrate: raw_format.bitrate,
             color_info: raw_format.color_info,
-            content_length: Arc::new(AtomicU64::new(raw_format.content_length.unwrap_or(0))),
+            content_length: content_length::new(raw_format.content_length),
             fps: raw_format.fps,
             height: raw_format.height,
             high_replication: raw_format.high_replication,
             index_range: raw_format.index_range,
             init_range: raw_format.init_range,
+            is_drc: raw_format.is_drc,
             is_otf: matches!(raw_format.format_type, Some(FormatType::Otf)),
             itag: raw_format.itag,
             last_modified: raw_format.last_modified,
@@ -120,7 +214,291 @@ impl Stream {
             width: raw_format.width,
             client,
             video_details,
+            metrics: None,
+            transfer_stats: None,
+            user_agent: None,
+            extra_headers: reqwest::header::HeaderMap::new(),
+            #[cfg(feature = "download")]
+            shutdown: None,
+            #[cfg(feature = "download")]
+            retry_policy: None,
+        }
+    }
+
+    /// Sets the [`TransferStats`], the requests and bytes of downloading the stream are added to.
+    ///
+    /// Streams of a [`VideoFetcher`](crate::VideoFetcher) with
+    /// [`TransferStats`](crate::VideoFetcher::with_transfer_stats) already have them.
+    #[inline]
+    pub fn with_transfer_stats(mut self, transfer_stats: Arc<TransferStats>) -> Self {
+        self.transfer_stats = Some(transfer_stats);
+        self
+    }
+
+    /// Sets the [`ShutdownHandle`], that stops downloads of the stream, once it's triggered (see
+    /// [`shutdown`](crate::shutdown)).
+    #[cfg(feature = "download")]
+    #[inline]
+    pub fn with_shutdown(mut self, shutdown: ShutdownHandle) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Sets the [`RetryPolicy`], downloads of the stream, that fail for transient reasons, are
+    /// repeated with (see [`retry`]).
+    ///
+    /// A URL, that's refreshed by the policy, i.e. after a `403 Forbidden`, is only used for the
+    /// download, the stream itself keeps its URL (see [`Stream::refresh`]).
+    #[cfg(feature = "download")]
+    #[inline]
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+}
+
+impl core::fmt::Display for Stream {
+    /// A one line summary of the stream, i.e. `137: mp4, 1920x1080@25fps, video only, 180.24 MB`
+    /// for a video stream, or `251: webm, 140kbps, audio only` for an audio stream. The size is
+    /// only included, if the content length is known without a request.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}: {}", self.itag, self.mime.subtype())?;
+
+        if self.includes_video_track {
+            match (self.width, self.height) {
+                (Some(width), Some(height)) => write!(f, ", {}x{}@{}fps", width, height, self.fps)?,
+                _ => write!(f, ", {}fps", self.fps)?,
+            }
+        } else if let Some(bitrate) = self.average_bitrate.or(self.bitrate) {
+            write!(f, ", {}kbps", (bitrate + 500) / 1000)?;
         }
+
+        match (self.includes_video_track, self.includes_audio_track) {
+            (true, false) => f.write_str(", video only")?,
+            (false, true) => f.write_str(", audio only")?,
+            _ => {}
+        }
+
+        if let Some(&ContentLength::Known(content_length)) = self.content_length.get() {
+            write!(f, ", {}", fmt_size(content_length))?;
+        }
+        Ok(())
+    }
+}
+
+impl Stream {
+    /// Whether the stream belongs to a live stream, that just ended, and is still being processed
+    /// by YouTube. Such streams are often not downloadable (yet).
+    #[inline]
+    pub fn is_post_live_processing(&self) -> bool {
+        self.video_details.is_post_live_dvr
+    }
+
+    /// The headers of all requests for the stream, which contain the user agent the video was
+    /// fetched with, if there was a [`UserAgentPolicy`](crate::UserAgentPolicy), and the headers
+    /// set with [`VideoFetcher::with_headers`](crate::VideoFetcher::with_headers).
+    #[inline]
+    #[cfg(feature = "download")]
+    pub(crate) fn headers(&self) -> reqwest::header::HeaderMap {
+        let mut headers = self.user_agent
+            .as_ref()
+            .map(UserAgent::headers)
+            .unwrap_or_default();
+        headers.extend(self.extra_headers.clone());
+        headers
+    }
+
+    /// Whether the stream is only available to YouTube Premium subscribers (i.e. `1080p Premium`).
+    /// Downloading such streams fails without a Premium account.
+    ///
+    /// The selection helpers of [`Video`](crate::Video), like [`Video::best_video`](crate::Video::best_video),
+    /// skip these streams.
+    #[inline]
+    pub fn is_premium_only(&self) -> bool {
+        self.quality_label.as_ref().is_some_and(QualityLabel::is_premium)
+    }
+
+    /// The file extension of the stream, which is the subtype of its container (i.e. `mp4`, or
+    /// `webm`).
+    #[inline]
+    pub fn extension(&self) -> &str {
+        self.mime.subtype().as_str()
+    }
+
+    /// The default file name of the stream, `<video_id>.<extension>` (i.e. `5jlI4uzZGjU.webm`),
+    /// which [`Stream::download`] and [`Stream::download_to_dir`] write to.
+    #[inline]
+    pub fn file_name(&self) -> String {
+        format!("{}.{}", self.video_details.video_id.as_str(), self.extension())
+    }
+
+    /// The IP address, the stream URL was issued for (its `ip` parameter), if it has one.
+    ///
+    /// YouTube refuses to serve URLs, that are [bound](Self::is_ip_bound) to an address, with
+    /// `403 Forbidden`, when they are requested from another one, i.e. when the video is fetched
+    /// on a server, but downloaded by a worker in a different network. The worker can get its own
+    /// URLs with [`Video::refetch_streams_via`](crate::Video::refetch_streams_via).
+    pub fn bound_ip(&self) -> Option<IpAddr> {
+        self.signature_cipher.url
+            .query_pairs()
+            .find(|(key, _)| key == "ip")
+            .and_then(|(_, ip)| ip.parse().ok())
+    }
+
+    /// Whether the URL is only valid for the IP address, it was issued for (see
+    /// [`Stream::bound_ip`]), which is the case, if the `ip` parameter is one of the signed
+    /// parameters (`sparams`).
+    pub fn is_ip_bound(&self) -> bool {
+        self.signature_cipher.url
+            .query_pairs()
+            .find(|(key, _)| key == "sparams")
+            .is_some_and(|(_, sparams)| sparams.split(',').any(|param| param == "ip"))
+    }
+
+    /// When the stream URL expires (its `expire` parameter), if it has one.
+    ///
+    /// URLs usually expire about six hours after the video was fetched. YouTube refuses to serve
+    /// them afterwards with `403 Forbidden`.
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        let (_, expire) = self.signature_cipher.url
+            .query_pairs()
+            .find(|(key, _)| key == "expire")?;
+        Utc.timestamp_opt(expire.parse().ok()?, 0).single()
+    }
+
+    /// Whether the stream URL [expired](Self::expires_at) already. Expired streams can get a
+    /// fresh URL with [`Stream::refresh`].
+    pub fn is_expired(&self) -> bool {
+        self.expires_at().is_some_and(|expires_at| expires_at <= DateTime::<Utc>::from(std::time::SystemTime::now()))
+    }
+
+    /// Replaces the URL of the stream with a fresh one, i.e. once it [expired](Self::is_expired).
+    ///
+    /// The video is fetched and descrambled again with the client of the stream, and the URL of
+    /// the stream with the same itag and audio track is taken over.
+    ///
+    /// ### Errors
+    /// - When fetching or descrambling the video fails.
+    /// - When the new player response does not contain the stream.
+    #[inline]
+    pub async fn refresh(&mut self) -> crate::Result<()> {
+        let fetcher = crate::VideoFetcher::from_id_with_client(
+            self.video_details.video_id.clone(),
+            self.client.clone(),
+        );
+        self.refresh_with(fetcher).await
+    }
+
+    /// Like [`Stream::refresh`], but fetches the video with `fetcher`, i.e. one with other
+    /// innertube clients or cookies. The fetcher has to fetch the video of the stream.
+    pub async fn refresh_with(&mut self, fetcher: crate::VideoFetcher) -> crate::Result<()> {
+        log::debug!("refreshing the URL of the stream {} of {}", self.itag, self.video_details.video_id);
+        let video = fetcher.fetch().await?.descramble()?;
+        let fresh = video
+            .streams()
+            .iter()
+            .find(|stream| stream.itag == self.itag && stream.audio_track == self.audio_track)
+            .ok_or_else(|| crate::Error::UnexpectedResponse(format!(
+                "the refreshed player response of {} does not contain the stream {}",
+                self.video_details.video_id, self.itag,
+            ).into()))?;
+
+        self.signature_cipher = fresh.signature_cipher.clone();
+        // the content length is requested again for the new URL, unless its format includes it
+        self.content_length = Arc::clone(&fresh.content_length);
+        Ok(())
+    }
+
+    /// The parsed codec of the video track, if the stream has one.
+    ///
+    /// Codecs, that are not known, are returned as [`VideoCodec::Other`].
+    pub fn video_codec(&self) -> Option<VideoCodec> {
+        if !self.includes_video_track {
+            return None;
+        }
+
+        self.codecs
+            .iter()
+            .map(|codec| VideoCodec::parse(codec))
+            .find(|codec| !matches!(codec, VideoCodec::Other(_)))
+            .or_else(|| self.codecs
+                .iter()
+                .find(|codec| matches!(AudioCodec::parse(codec), AudioCodec::Other(_)))
+                .map(|codec| VideoCodec::Other(codec.trim().to_owned()))
+            )
+    }
+
+    /// The parsed codec of the audio track, if the stream has one.
+    ///
+    /// Codecs, that are not known, are returned as [`AudioCodec::Other`].
+    pub fn audio_codec(&self) -> Option<AudioCodec> {
+        if !self.includes_audio_track {
+            return None;
+        }
+
+        self.codecs
+            .iter()
+            .map(|codec| AudioCodec::parse(codec))
+            .find(|codec| !matches!(codec, AudioCodec::Other(_)))
+            .or_else(|| self.codecs
+                .iter()
+                .rev()
+                .find(|codec| matches!(VideoCodec::parse(codec), VideoCodec::Other(_)))
+                .map(|codec| AudioCodec::Other(codec.trim().to_owned()))
+            )
+    }
+
+    /// Whether a player with the capabilities `support` can play all tracks of the stream,
+    /// including the number of audio channels.
+    pub fn is_supported_by(&self, support: &CodecSupport) -> bool {
+        let channels_supported = match (self.audio_channels, support.max_audio_channels) {
+            (Some(channels), Some(max)) if self.includes_audio_track => channels <= max,
+            _ => true,
+        };
+
+        channels_supported
+            && self.video_codec().is_none_or(|codec| codec.is_supported_by(support))
+            && self.audio_codec().is_none_or(|codec| codec.is_supported_by(support))
+    }
+
+    /// Whether the downloaded file is a fragmented mp4, which some players refuse to play.
+    ///
+    /// This is the case for adaptive (audio or video only) `mp4` streams, which YouTube serves as
+    /// DASH segments (they have an init and an index range), and for OTF streams. With the `remux`
+    /// feature, such files can be rewritten with
+    /// [`remux_fmp4_to_mp4`](crate::postprocess::remux_fmp4_to_mp4).
+    pub fn needs_remux_for_playback(&self) -> bool {
+        !self.is_progressive
+            && self.mime.subtype() == mime::MP4
+            && (self.is_otf || (self.init_range.is_some() && self.index_range.is_some()))
+    }
+
+    /// An estimate of the content length, based on the bitrate and the duration of the stream.
+    ///
+    /// Unlike [`Stream::content_length`], this never makes a request, but can be off by a few
+    /// percent.
+    pub fn estimated_content_length(&self) -> Option<u64> {
+        let bitrate = self.average_bitrate.or(self.bitrate)?;
+        let duration_ms = self.approx_duration_ms?;
+        Some(bitrate * duration_ms / 8_000)
+    }
+
+    /// The content length, if it was included in the [`RawFormat`], or was already requested.
+    #[cfg(any(feature = "serialize", feature = "download"))]
+    #[inline]
+    pub(crate) fn known_content_length(&self) -> Option<u64> {
+        match self.content_length.get() {
+            Some(&ContentLength::Known(content_length)) => Some(content_length),
+            _ => None,
+        }
+    }
+
+    /// The stream, serialized with only the fields of `level`. See [`serialize`](crate::serialize).
+    #[cfg(feature = "serialize")]
+    #[inline]
+    pub fn serialize_with_level(&self, level: crate::serialize::OutputLevel) -> serde_json::Value {
+        let serializer = crate::serialize::StreamSerializer::new(self, level);
+        serde_json::to_value(serializer).expect("serializing a stream never fails")
     }
 }
 
@@ -133,15 +511,42 @@ impl Stream {
     /// If the content length was not included in the [`RawFormat`], this method will make a `HEAD`
     /// request, to try to figure it out.
     ///
+    /// The `HEAD` request is made at most once per stream (and its clones): concurrent callers
+    /// wait for the same request, and its outcome, including a failure, is cached until the URL
+    /// is [refreshed](Self::refresh).
+    ///
     /// ### Errors:
-    /// - When the content length was not included in the [`RawFormat`], and the request fails.
-    #[inline]
+    /// - When the content length was not included in the [`RawFormat`], and the request fails, or
+    ///   failed before.
     pub async fn content_length(&self) -> Result<u64> {
-        let cl = self.content_length.load(Ordering::SeqCst);
-        if cl != 0 { return Ok(cl); }
+        let mut error = None;
+        let content_length = self.content_length
+            .get_or_init(|| async {
+                match self.request_content_length().await {
+                    Ok(cl) => ContentLength::Known(cl),
+                    Err(e) => {
+                        log::warn!("failed to request the content length of {}: {}", self.itag, e);
+                        error = Some(e);
+                        ContentLength::Unavailable
+                    }
+                }
+            })
+            .await;
+
+        match (content_length, error) {
+            (ContentLength::Known(cl), _) => Ok(*cl),
+            (ContentLength::Unavailable, Some(e)) => Err(e),
+            (ContentLength::Unavailable, None) => Err(Error::UnexpectedResponse(
+                "the content length of the stream is unavailable".into()
+            )),
+        }
+    }
 
+    async fn request_content_length(&self) -> Result<u64> {
+        self.record_request();
         self.client
             .head(self.signature_cipher.url.as_str())
+            .headers(self.headers())
             .send()
             .await?
             .error_for_status()?
@@ -149,18 +554,19 @@ impl Stream {
             .get(reqwest::header::CONTENT_LENGTH)
             .and_then(|cl| cl.to_str().ok())
             .and_then(|cl| cl.parse::<u64>().ok())
-            .map(|cl| {
-                log::trace!("content length of {:?} is {}", self, cl);
-                self.content_length.store(cl, Ordering::SeqCst);
-                cl
-            })
+            .inspect(|&cl| log::trace!("content length of {:?} is {}", self, cl))
             .ok_or_else(|| Error::UnexpectedResponse(
                 "the response did not contain a valid content-length field".into()
             ))
     }
 
     /// Attempts to downloads the [`Stream`]s resource.
-    /// This will download the video to <video_id>.mp4 in the current working directory.
+    /// This will download the video to its [`file_name`](Stream::file_name)
+    /// (`<video_id>.<extension>`) in the current working directory.
+    ///
+    /// While downloading, the resource is written to `<file_name>.part`, which is only renamed
+    /// once the download is complete, and removed, if it fails. The same applies to all other
+    /// download methods.
     #[inline]
     pub async fn download(&self) -> Result<PathBuf> {
         self.internal_download(None).await
@@ -168,14 +574,15 @@ impl Stream {
 
     #[inline]
     async fn internal_download(&self, channel: Option<InternalSender>) -> Result<PathBuf> {
-        let path = Path::new(self.video_details.video_id.as_str())
-            .with_extension(self.mime.subtype().as_str());
-        self.internal_download_to(&path, channel)
+        let path = PathBuf::from(self.file_name());
+        self.internal_download_to(&path, channel, false, None, DEFAULT_WRITE_BUFFER_SIZE)
             .await
+            .map(|download| download.path)
     }
 
     /// Attempts to downloads the [`Stream`]s resource.
-    /// This will download the video to <video_id>.mp4 in the provided directory.
+    /// This will download the video to its [`file_name`](Stream::file_name)
+    /// (`<video_id>.<extension>`) in the provided directory.
     #[inline]
     pub async fn download_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<PathBuf> {
         self.internal_download_to_dir(dir, None).await
@@ -187,42 +594,522 @@ impl Stream {
         dir: P,
         channel: Option<InternalSender>,
     ) -> Result<PathBuf> {
-        let mut path = dir
+        let path = dir
             .as_ref()
-            .join(self.video_details.video_id.as_str());
-        path.set_extension(self.mime.subtype().as_str());
-        self.internal_download_to(&path, channel)
+            .join(self.file_name());
+        self.internal_download_to(&path, channel, false, None, DEFAULT_WRITE_BUFFER_SIZE)
             .await
+            .map(|download| download.path)
     }
 
     /// Attempts to downloads the [`Stream`]s resource.
     /// This will download the video to the provided file path.
     #[inline]
     pub async fn download_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let _ = self.internal_download_to(path, None).await?;
+        let _ = self.internal_download_to(path, None, false, None, DEFAULT_WRITE_BUFFER_SIZE).await?;
+        Ok(())
+    }
+
+    /// Attempts to downloads the [`Stream`]s resource to the provided file path, and returns a
+    /// [`DownloadSummary`], which contains the number of bytes transferred.
+    #[inline]
+    pub async fn download_to_with_summary<P: AsRef<Path>>(&self, path: P) -> Result<DownloadSummary> {
+        self.internal_download_to(path, None, false, None, DEFAULT_WRITE_BUFFER_SIZE).await
+    }
+
+    /// Attempts to downloads the [`Stream`]s resource to the provided file path, and computes
+    /// the checksum of the file with `algorithm` while writing it.
+    ///
+    /// Each chunk is hashed right after it was written, so the file does not have to be read
+    /// again. Segmented streams are hashed in order, so the digest is always the one of the
+    /// complete file.
+    #[inline]
+    pub async fn download_to_with_hash<P: AsRef<Path>>(
+        &self,
+        path: P,
+        algorithm: HashAlgorithm,
+    ) -> Result<DownloadSummary> {
+        self.internal_download_to(path, None, false, Some(algorithm), DEFAULT_WRITE_BUFFER_SIZE).await
+    }
+
+    /// Attempts to downloads the [`Stream`]s resource to the provided file path, and resumes an
+    /// earlier, interrupted download, instead of starting from byte zero.
+    ///
+    /// The bytes of `<path>.part`, that's left over from a failed download (see
+    /// [`SimpleDownloadOptions::keep_partial`](crate::SimpleDownloadOptions::keep_partial)), are
+    /// kept, and only the rest of the resource is requested with a `Range` header. If the server
+    /// ignores the range, the resource is downloaded from the start. Unlike the other download
+    /// methods, the `.part` file is also kept, if this download fails, so it can be resumed again.
+    ///
+    /// Progress callbacks report the absolute offset in the file, including the resumed bytes.
+    ///
+    /// ### Errors
+    /// - When the content length of the stream is unknown, and cannot be requested.
+    /// - When the file does not have the content length of the stream in the end.
+    #[inline]
+    pub async fn download_to_resumable<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let _ = self.internal_download_to_resumable(path.as_ref(), None).await?;
         Ok(())
     }
 
+    /// Attempts to downloads the [`Stream`]s resource into `writer`, i.e. a socket, an in-memory
+    /// buffer, or an encoder, and returns the number of bytes written.
+    ///
+    /// Nothing is written to the file system. The writer is flushed once the download is
+    /// complete, but not shut down. If the download fails, the bytes received until then were
+    /// already written to `writer`.
+    #[inline]
+    pub async fn download_to_writer<W: AsyncWrite + Unpin + ?Sized>(&self, writer: &mut W) -> Result<u64> {
+        self.internal_download_to_writer(writer, None).await
+    }
+
+    /// Opens the [`Stream`]s resource as an [`AsyncRead`](tokio::io::AsyncRead), i.e. for
+    /// playing it while it's downloading.
+    ///
+    /// The resource is downloaded in the background, at most [`DEFAULT_PREFETCH`] bytes ahead of
+    /// the reader. Use [`Stream::open_reader_with_prefetch`] to configure the buffer size.
+    ///
+    /// ### Errors
+    /// - When the initial request fails. Errors occurring later on are returned by the reader.
+    #[inline]
+    pub async fn open_reader(&self) -> Result<StreamReader> {
+        self.open_reader_with_prefetch(DEFAULT_PREFETCH).await
+    }
+
+    /// Opens the [`Stream`]s resource as an [`AsyncRead`](tokio::io::AsyncRead), that downloads at
+    /// most `prefetch` bytes ahead of the reader.
+    ///
+    /// Like [`Stream::download`], this falls back to requesting the resource in sequences, if it
+    /// cannot be requested as a whole.
+    ///
+    /// ### Errors
+    /// - When the initial request fails. Errors occurring later on are returned by the reader.
+    pub async fn open_reader_with_prefetch(&self, prefetch: usize) -> Result<StreamReader> {
+        self.internal_open_reader(prefetch)
+            .await
+            .map_err(|e| self.processing_hint(e))
+    }
+
+    /// Opens the [`Stream`]s resource as a [`futures::Stream`](tokio_stream::Stream) of
+    /// [`Bytes`](bytes::Bytes), i.e. for forwarding the chunks in a proxy, as they arrive.
+    ///
+    /// The returned [`StreamReader`] is `Send`, so it can be returned from `axum` or `hyper`
+    /// handlers, i.e. with `Body::wrap_stream`. Like with [`Stream::open_reader`], the resource is
+    /// downloaded in the background, at most [`DEFAULT_PREFETCH`] bytes ahead of the consumer, and
+    /// falls back to sequenced requests, if it cannot be requested as a whole. Progress can be
+    /// tracked by counting the bytes of the chunks. Since the reader also implements
+    /// [`AsyncRead`](tokio::io::AsyncRead), it can be piped with [`tokio::io::copy`], too.
+    ///
+    /// # Example
+    /// ```no_run
+    ///# use rustube::{Id, Video};
+    /// use tokio_stream::StreamExt;
+    /// use tokio::io::AsyncWriteExt;
+    ///
+    ///# #[tokio::main]
+    ///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///# let video = Video::from_id(Id::from_str("5jlI4uzZGjU")?.into_owned()).await?;
+    ///# let mut socket = tokio::io::sink();
+    /// let stream = video.best_audio().unwrap();
+    ///
+    /// let mut bytes = stream.bytes_stream().await?;
+    /// let mut forwarded = 0;
+    /// while let Some(chunk) = bytes.next().await {
+    ///     let chunk = chunk?;
+    ///     socket.write_all(&chunk).await?;
+    ///     forwarded += chunk.len();
+    /// }
+    ///
+    /// // or, without looking at the chunks
+    /// tokio::io::copy(&mut stream.bytes_stream().await?, &mut socket).await?;
+    ///# Ok(())
+    ///# }
+    /// ```
+    ///
+    /// ### Errors
+    /// - When the initial request fails. Errors occurring later on are returned by the stream.
+    #[inline]
+    pub async fn bytes_stream(&self) -> Result<StreamReader> {
+        self.bytes_stream_from(0).await
+    }
+
+    /// Like [`Stream::bytes_stream`], but starts at the byte `offset` of the resource, i.e. for
+    /// answering a `Range` request.
+    ///
+    /// ### Errors
+    /// - When the initial request fails. Errors occurring later on are returned by the stream.
+    /// - When the server does not support ranges, which is the case for segmented streams, that
+    ///   can only be requested in sequences.
+    pub async fn bytes_stream_from(&self, offset: u64) -> Result<StreamReader> {
+        let result = match offset {
+            0 => self.internal_open_reader(DEFAULT_PREFETCH).await,
+            offset => self.internal_open_reader_at(offset).await,
+        };
+        result.map_err(|e| self.processing_hint(e))
+    }
+
+    async fn internal_open_reader_at(&self, offset: u64) -> Result<StreamReader> {
+        log::debug!("open a reader for {} at byte {}", self.video_details.video_id, offset);
+        let range = format!("bytes={}-", offset);
+        let response = self.get_range(&self.signature_cipher.url, &range).await?;
+
+        if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Err(Error::UnexpectedResponse(format!(
+                "requested the range starting at byte {}, but the server ignored it (status {})",
+                offset, response.status(),
+            ).into()));
+        }
+        let start = Self::extract_content_range_start(&response)?;
+        if start != offset {
+            return Err(Error::UnexpectedResponse(format!(
+                "requested the range starting at byte {offset}, but got the one starting at byte {start}"
+            ).into()));
+        }
+
+        Ok(self.spawn_reader(response, Vec::new(), DEFAULT_PREFETCH))
+    }
+
+    async fn internal_open_reader(&self, prefetch: usize) -> Result<StreamReader> {
+        log::debug!("open a reader for {}", self.video_details.video_id);
+        let url = &self.signature_cipher.url;
+
+        let (response, following) = match self.is_otf {
+            // OTF streams are only served in segments
+            true => self.get_sequenced(url).await?,
+            false => match self.get(url).await {
+                Ok(response) => (response, Vec::new()),
+                Err(Error::Request(e)) if matches!(e.status(), Some(reqwest::StatusCode::NOT_FOUND)) => {
+                    log::info!("try to open {} using sequenced requests", self.video_details.video_id);
+                    self.get_sequenced(url).await?
+                }
+                Err(e) => return Err(e),
+            },
+        };
+
+        Ok(self.spawn_reader(response, following, prefetch))
+    }
+
+    /// Requests the 0th segment of `url`, which contains the file headers, and returns it
+    /// together with the URLs of the following segments.
+    async fn get_sequenced(&self, url: &url::Url) -> Result<(reqwest::Response, Vec<url::Url>)> {
+        let mut url = url.clone();
+        let base_query = url
+            .query()
+            .map(str::to_owned)
+            .unwrap_or_default();
+
+        // The 0th sequential request provides the file headers, which tell us
+        // information about how the file is segmented.
+        Self::set_url_seq_query(&mut url, &base_query, 0);
+        let response = self.get(&url).await?;
+        let segment_count = Stream::extract_segment_count(response.headers())?;
+
+        let following = (1..segment_count)
+            .map(|i| {
+                let mut url = url.clone();
+                Self::set_url_seq_query(&mut url, &base_query, i);
+                url
+            })
+            .collect();
+        Ok((response, following))
+    }
+
+    fn spawn_reader(&self, response: reqwest::Response, following: Vec<url::Url>, prefetch: usize) -> StreamReader {
+        StreamReader::spawn(
+            self.client.clone(),
+            self.headers(),
+            self.transfer_stats.clone(),
+            response,
+            following,
+            prefetch,
+        )
+    }
+
+    /// Downloads the resource to `<path>.part`, and renames it to `path` once it's complete, so
+    /// there's never an incomplete file at `path`.
+    ///
+    /// The `.part` file is removed, if the download fails, unless `keep_partial` is set.
+    /// If there's a `hash` algorithm, the digest of the file is computed while writing it.
+    /// Up to `write_buffer_size` bytes are collected in memory, before they're written to the file.
     #[allow(unused_mut, clippy::let_and_return)]
-    async fn internal_download_to<P: AsRef<Path>>(&self, path: P, channel: Option<InternalSender>) -> Result<PathBuf> {
+    pub(crate) async fn internal_download_to<P: AsRef<Path>>(
+        &self,
+        path: P,
+        channel: Option<InternalSender>,
+        keep_partial: bool,
+        hash: Option<HashAlgorithm>,
+        write_buffer_size: usize,
+    ) -> Result<DownloadSummary> {
         log::trace!("download_to: {:?}", path.as_ref());
         log::debug!("start downloading {}", self.video_details.video_id);
-        let mut file = File::create(&path).await?;
+        let _active = self.shutdown.as_ref().map(ShutdownHandle::register);
+        let mut timer = PhaseTimer::start(self.metrics.as_deref(), Phase::Download);
+        let part_path = part_path(path.as_ref());
+
+        let mut host = None;
+        let result = match self.download_to_part_with_retries(&part_path, &channel, hash, write_buffer_size, &mut host).await {
+            Ok((bytes, digest)) => {
+                timer.add_bytes(bytes);
+                replace_file(&part_path, path.as_ref())
+                    .await
+                    .map(|_| (bytes, digest))
+                    .map_err(Error::from)
+            }
+            Err(e) => Err(e),
+        };
+        drop(timer);
 
-        let result = match self.download_full(&self.signature_cipher.url, &mut file, &channel, 0).await {
-            Ok(_) => {
+        let result = match result {
+            Ok((bytes, digest)) => {
                 log::info!(
                     "downloaded {} successfully to {:?}",
                     self.video_details.video_id, path.as_ref()
                 );
                 log::debug!("downloaded stream {:?}", &self);
-                Ok(())
+                Ok(DownloadSummary { path: path.as_ref().to_path_buf(), bytes, digest, host })
+            }
+            Err(e) => {
+                log::error!("failed to download {}: {:?}", self.video_details.video_id, e);
+                if !keep_partial {
+                    if let Err(e) = tokio::fs::remove_file(&part_path).await {
+                        log::warn!("failed to remove the partial download {:?}: {}", part_path, e);
+                    }
+                }
+                Err(self.processing_hint(e))
+            }
+        };
+
+        #[cfg(feature = "callback")]
+        if let Some(channel) = channel {
+            let _ = channel.send(InternalSignal::Finished).await;
+        }
+
+        result
+    }
+
+    /// Resumes the download of `<path>.part`, and renames it to `path` once it's complete.
+    async fn internal_download_to_resumable(
+        &self,
+        path: &Path,
+        channel: Option<InternalSender>,
+    ) -> Result<PathBuf> {
+        log::trace!("download_to_resumable: {:?}", path);
+        let _active = self.shutdown.as_ref().map(ShutdownHandle::register);
+        let mut timer = PhaseTimer::start(self.metrics.as_deref(), Phase::Download);
+        let part_path = part_path(path);
+
+        let mut host = None;
+        let result = match self.resume_part(&part_path, &channel, &mut host).await {
+            Ok(transferred) => {
+                timer.add_bytes(transferred);
+                replace_file(&part_path, path).await.map_err(Error::from)
+            }
+            Err(e) => Err(e),
+        };
+        drop(timer);
+
+        let result = match result {
+            Ok(()) => {
+                log::info!("downloaded {} successfully to {:?}", self.video_details.video_id, path);
+                Ok(path.to_path_buf())
+            }
+            Err(e) => {
+                // the `.part` file is kept, so the download can be resumed
+                log::error!("failed to download {}: {:?}", self.video_details.video_id, e);
+                Err(self.processing_hint(e))
+            }
+        };
+
+        #[cfg(feature = "callback")]
+        if let Some(channel) = channel {
+            let _ = channel.send(InternalSignal::Finished).await;
+        }
+
+        result
+    }
+
+    /// Downloads the part of the resource, that's missing in `part_path`, and returns the number
+    /// of bytes transferred. The host, the resource was downloaded from, is written to `host`.
+    ///
+    /// Fails, if the file does not have the content length in the end.
+    async fn resume_part(
+        &self,
+        part_path: &Path,
+        channel: &Option<InternalSender>,
+        host: &mut Option<String>,
+    ) -> Result<u64> {
+        let content_length = self.content_length().await?;
+        let mut offset = match tokio::fs::metadata(part_path).await {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+        if offset > content_length {
+            log::warn!(
+                "{:?} is larger than the stream ({} of {} bytes), starting over",
+                part_path, offset, content_length,
+            );
+            offset = 0;
+        }
+
+        if offset == content_length {
+            log::debug!("{:?} is already complete", part_path);
+        } else if offset == 0 {
+            let mut file = BufWriter::with_capacity(DEFAULT_WRITE_BUFFER_SIZE, File::create(part_path).await?);
+            let result = self.download_into(&mut file, channel, None, host).await;
+            let flushed = file.flush().await;
+            result?;
+            flushed?;
+        } else {
+            log::debug!("resume downloading {} at byte {}", self.video_details.video_id, offset);
+            let range = format!("bytes={}-", offset);
+            let res = tokio::select! {
+                biased;
+                _ = self.shutdown_triggered() => return Err(Error::Cancelled),
+                res = self.get_range(&self.signature_cipher.url, &range) => res?,
+            };
+            *host = res.url().host_str().map(str::to_owned);
+
+            let file = match res.status() {
+                reqwest::StatusCode::PARTIAL_CONTENT => {
+                    let start = Self::extract_content_range_start(&res)?;
+                    if start != offset {
+                        return Err(Error::UnexpectedResponse(format!(
+                            "requested the range starting at byte {offset}, but got the one starting at byte {start}"
+                        ).into()));
+                    }
+                    OpenOptions::new().append(true).open(part_path).await?
+                }
+                _ => {
+                    log::info!("the server ignored the range, downloading {} from the start", self.video_details.video_id);
+                    offset = 0;
+                    File::create(part_path).await?
+                }
+            };
+
+            let mut file = BufWriter::with_capacity(DEFAULT_WRITE_BUFFER_SIZE, file);
+            let result = self.write_stream(res.bytes_stream(), &mut file, &mut None, channel, offset as usize).await;
+            let flushed = file.flush().await;
+            result?;
+            flushed?;
+        }
+
+        let file = File::open(part_path).await?;
+        file.sync_all().await?;
+        let len = file.metadata().await?.len();
+        self.check_length(len)?;
+
+        // `offset` is the number of bytes, that were kept
+        Ok(len - offset)
+    }
+
+    /// Downloads the resource into `writer`, without touching the file system.
+    ///
+    /// Fails, if the number of bytes does not match the content length, if it's known.
+    async fn internal_download_to_writer<W: AsyncWrite + Unpin + ?Sized>(
+        &self,
+        writer: &mut W,
+        channel: Option<InternalSender>,
+    ) -> Result<u64> {
+        log::debug!("start downloading {} to a writer", self.video_details.video_id);
+        let _active = self.shutdown.as_ref().map(ShutdownHandle::register);
+        let mut timer = PhaseTimer::start(self.metrics.as_deref(), Phase::Download);
+
+        let mut host = None;
+        let result = match self.download_into(writer, &channel, None, &mut host).await {
+            Ok((bytes, _)) => match writer.flush().await {
+                Ok(()) => self.check_length(bytes).map(|_| bytes),
+                Err(e) => Err(e.into()),
+            },
+            Err(e) => Err(e),
+        };
+        if let Ok(bytes) = result {
+            timer.add_bytes(bytes);
+        }
+        drop(timer);
+
+        let result = match result {
+            Ok(bytes) => {
+                log::info!("downloaded {} successfully ({} bytes)", self.video_details.video_id, bytes);
+                Ok(bytes)
+            }
+            Err(e) => {
+                log::error!("failed to download {}: {:?}", self.video_details.video_id, e);
+                Err(self.processing_hint(e))
             }
+        };
+
+        #[cfg(feature = "callback")]
+        if let Some(channel) = channel {
+            let _ = channel.send(InternalSignal::Finished).await;
+        }
+
+        result
+    }
+
+    /// Downloads the resource to `part_path`, and returns the number of bytes written, and the
+    /// digest of the file, if there's a `hash` algorithm. The host, the resource was downloaded
+    /// from, is written to `host`.
+    ///
+    /// Fails, if the number of bytes does not match the content length, if it's known.
+    async fn download_to_part(
+        &self,
+        part_path: &Path,
+        channel: &Option<InternalSender>,
+        hash: Option<HashAlgorithm>,
+        write_buffer_size: usize,
+        host: &mut Option<String>,
+    ) -> Result<(u64, Option<String>)> {
+        let mut file = BufWriter::with_capacity(write_buffer_size, File::create(part_path).await?);
+
+        let digest = match self.download_into(&mut file, channel, hash, host).await {
+            Ok((_, digest)) => digest,
+            Err(e) => {
+                // the buffered bytes are written anyway, so a kept partial download contains all
+                // of the bytes, that were received
+                if let Err(flush_err) = file.flush().await {
+                    log::warn!("failed to flush the partial download {:?}: {}", part_path, flush_err);
+                }
+                return Err(e);
+            }
+        };
+
+        // the buffer has to be flushed before the length of the file can be checked
+        file.flush().await?;
+        let file = file.into_inner();
+        file.sync_all().await?;
+        let len = file.metadata().await?.len();
+        self.check_length(len)?;
+
+        Ok((len, digest))
+    }
+
+    /// Downloads the resource into `writer`, and returns the number of bytes written, and the
+    /// digest of them, if there's a `hash` algorithm. The host, the resource was downloaded
+    /// from, is written to `host`.
+    async fn download_into<W: AsyncWrite + Unpin + ?Sized>(
+        &self,
+        writer: &mut W,
+        channel: &Option<InternalSender>,
+        hash: Option<HashAlgorithm>,
+        host: &mut Option<String>,
+    ) -> Result<(u64, Option<String>)> {
+        let mut hasher = hash.map(Hasher::new);
+
+        if self.is_otf {
+            // OTF streams are only served in segments
+            let bytes = self.download_full_seq(writer, &mut hasher, channel, host).await?;
+            return Ok((bytes as u64, hasher.map(Hasher::finalize)));
+        }
+
+        let bytes = match self.download_full(&self.signature_cipher.url, writer, &mut hasher, channel, 0, host).await {
+            Ok(bytes) => bytes,
             Err(Error::Request(e)) if matches!(e.status(), Some(reqwest::StatusCode::NOT_FOUND)) => {
                 log::error!("failed to download {}: {:?}", self.video_details.video_id, e);
                 log::info!("try to download {} using sequenced download", self.video_details.video_id);
                 // Some adaptive streams need to be requested with sequence numbers
-                self.download_full_seq(&mut file, &channel)
+                hasher = hash.map(Hasher::new);
+                self.download_full_seq(writer, &mut hasher, channel, host)
                     .await
                     .map_err(|e| {
                         log::error!(
@@ -230,104 +1117,196 @@ impl Stream {
                             self.video_details.video_id, e
                         );
                         e
-                    })
+                    })?
             }
-            Err(e) => {
-                log::error!("failed to download {}: {:?}", self.video_details.video_id, e);
-                drop(file);
-                tokio::fs::remove_file(path.as_ref()).await?;
-                Err(e)
-            }
-        }.map(|_| path.as_ref().to_path_buf());
-
-        #[cfg(feature = "callback")]
-        if let Some(channel) = channel {
-            let _ = channel.send(InternalSignal::Finished).await;
-        }
+            Err(e) => return Err(e),
+        };
 
-        result
+        Ok((bytes as u64, hasher.map(Hasher::finalize)))
     }
 
-    async fn download_full_seq(&self, file: &mut File, channel: &Option<InternalSender>) -> Result<()> {
-        // fixme: this implementation is **not** tested yet!
-        // To test it, I would need an url of a video, which does require sequenced downloading.
-        log::warn!(
-            "`download_full_seq` is not tested yet and probably broken!\n\
-            Please open a GitHub issue (https://github.com/DzenanJupic/rustube/issues) and paste \
-            the whole warning message in:\n\
-            id: {}\n\
-            url: {}",
-            self.video_details.video_id,
-            self.signature_cipher.url.as_str()
-        );
+    /// Fails, if `len` does not match the content length, if it's known.
+    fn check_length(&self, len: u64) -> Result<()> {
+        match self.content_length.get() {
+            Some(&ContentLength::Known(content_length)) if content_length != len => {
+                Err(Error::UnexpectedResponse(format!(
+                    "the download ended after {len} of {content_length} bytes"
+                ).into()))
+            }
+            _ => Ok(()),
+        }
+    }
 
+    /// Downloads the resource segment by segment (`&sq=<n>`), which is how OTF streams are
+    /// served. The 0th segment contains the file headers, and tells the number of segments.
+    /// Returns the number of bytes written, including the file headers.
+    async fn download_full_seq<W: AsyncWrite + Unpin + ?Sized>(
+        &self,
+        writer: &mut W,
+        hasher: &mut Option<Hasher>,
+        channel: &Option<InternalSender>,
+        host: &mut Option<String>,
+    ) -> Result<usize> {
         let mut url = self.signature_cipher.url.clone();
         let base_query = url
             .query()
             .map(str::to_owned)
-            .unwrap_or_else(String::new);
+            .unwrap_or_default();
 
         // The 0th sequential request provides the file headers, which tell us
         // information about how the file is segmented.
         Self::set_url_seq_query(&mut url, &base_query, 0);
-        let res = self.get(&url).await?;
-        let segment_count = Stream::extract_segment_count(&res)?;
-        // No callback action since this is not really part of the progress
-        self.write_stream_to_file(res.bytes_stream(), file, &None, 0).await?;
-        let mut count = 0;
+        let res = tokio::select! {
+            biased;
+            _ = self.shutdown_triggered() => return Err(Error::Cancelled),
+            res = self.get(&url) => res?,
+        };
+        let segment_count = Stream::extract_segment_count(res.headers())?;
+        log::debug!("download the {} segments of {}", segment_count, self.video_details.video_id);
+        *host = res.url().host_str().map(str::to_owned);
+        // the following segments are requested from the host, the redirector chose
+        if let Some(redirected) = redirector::with_host_of(&url, res.url()) {
+            url = redirected;
+        }
+        let mut count = self.write_stream(res.bytes_stream(), writer, hasher, channel, 0).await?;
 
         for i in 1..segment_count {
             Self::set_url_seq_query(&mut url, &base_query, i);
-            count = self.download_full(&url, file, channel, count).await?;
+            count = self.download_full(&url, writer, hasher, channel, count, host).await?;
         }
 
-        Ok(())
+        Ok(count)
     }
 
     #[inline]
-    async fn download_full(
+    async fn download_full<W: AsyncWrite + Unpin + ?Sized>(
         &self,
         url: &url::Url,
-        file: &mut File,
+        writer: &mut W,
+        hasher: &mut Option<Hasher>,
         channel: &Option<InternalSender>,
         count: usize,
+        host: &mut Option<String>,
     ) -> Result<usize> {
-        let res = self.get(url).await?;
-        self.write_stream_to_file(res.bytes_stream(), file, channel, count).await
+        let res = tokio::select! {
+            biased;
+            _ = self.shutdown_triggered() => return Err(Error::Cancelled),
+            res = self.get(url) => res?,
+        };
+        *host = res.url().host_str().map(str::to_owned);
+        self.write_stream(res.bytes_stream(), writer, hasher, channel, count).await
     }
 
+    /// Requests `url`, and follows the redirector of `googlevideo.com` to alternate hosts (see
+    /// [`redirector`]). The URL of the returned response is the one, the media is served from.
     #[inline]
     async fn get(&self, url: &url::Url) -> Result<reqwest::Response> {
-        log::trace!("get: {}", url.as_str());
-        Ok(
-            self.client
+        self.get_with_range(url, None).await
+    }
+
+    /// Like [`Stream::get`], but only requests the bytes of `range` (i.e. `bytes=1024-`). The
+    /// server may ignore the range, and respond with the whole resource.
+    #[inline]
+    async fn get_range(&self, url: &url::Url, range: &str) -> Result<reqwest::Response> {
+        self.get_with_range(url, Some(range)).await
+    }
+
+    /// [`Stream::get`] with an optional `Range` header, that's sent to every host.
+    async fn get_with_range(&self, url: &url::Url, range: Option<&str>) -> Result<reqwest::Response> {
+        let mut url = url.clone();
+        for _ in 0..=redirector::MAX_HOPS {
+            log::trace!("get: {} (range: {:?})", url.as_str(), range);
+            self.record_request();
+            let mut request = self.client
                 .get(url.as_str())
-                .send()
-                .await?
-                .error_for_status()?
-        )
+                .headers(self.headers());
+            if let Some(range) = range {
+                request = request.header(reqwest::header::RANGE, range);
+            }
+            let response = request.send().await?;
+
+            // without its query, the redirected request usually fails, so it's repeated,
+            // regardless of the status
+            let location = if redirector::lost_query(&url, response.url()) {
+                response.url().clone()
+            } else if response.status() == reqwest::StatusCode::FORBIDDEN {
+                // only the host is taken from the response, since the query of the requested URL
+                // is the interesting part of the report
+                return Err(forbidden::diagnose(
+                    self.video_details.video_id.clone(),
+                    self.itag,
+                    &url,
+                    response.url().host_str(),
+                    self.signature_cipher.n_transformed,
+                ));
+            } else if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(crate::fetcher::retry_after);
+                return Err(Error::RateLimited { retry_after, endpoint: "videoplayback" });
+            } else if let Err(e) = response.error_for_status_ref() {
+                return Err(e.into());
+            } else if redirector::is_alternate_host(&response) {
+                let body = response.text().await?;
+                redirector::parse_alternate_host(&body, &url).ok_or_else(|| Error::UnexpectedResponse(
+                    format!("expected media, but got the plaintext response {:?}", body).into()
+                ))?
+            } else {
+                return Ok(response);
+            };
+
+            log::debug!("the redirector sent {} to {}", self.video_details.video_id, location);
+            url = redirector::with_host_of(&url, &location).ok_or_else(|| Error::UnexpectedResponse(
+                format!("the redirector sent the stream to the invalid location {}", location).into()
+            ))?;
+        }
+
+        Err(Error::UnexpectedResponse(format!(
+            "the stream was still redirected after {} hops", redirector::MAX_HOPS
+        ).into()))
     }
 
     #[inline]
     #[allow(unused_variables, unused_mut)]
-    async fn write_stream_to_file(
+    async fn write_stream<W: AsyncWrite + Unpin + ?Sized>(
         &self,
         mut stream: impl tokio_stream::Stream<Item=reqwest::Result<bytes::Bytes>> + Unpin,
-        file: &mut File,
+        writer: &mut W,
+        hasher: &mut Option<Hasher>,
         channel: &Option<InternalSender>,
         mut counter: usize,
     ) -> Result<usize> {
-        // Counter will be 0 if callback is not enabled
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
+        let shutdown = self.shutdown_triggered();
+        tokio::pin!(shutdown);
+
+        loop {
+            // the chunk, that's currently written, is always completed
+            let chunk = tokio::select! {
+                biased;
+                _ = &mut shutdown => return Err(Error::Cancelled),
+                chunk = stream.next() => chunk,
+            };
+            let chunk = match chunk {
+                Some(chunk) => chunk?,
+                None => break,
+            };
             let len = chunk.len();
             log::trace!("received {} byte chunk ", len);
+            if let Some(ref transfer_stats) = self.transfer_stats {
+                transfer_stats.record_media(len as u64)?;
+            }
 
-            file.write_all(&chunk).await?;
+            writer.write_all(&chunk).await?;
+            if let Some(hasher) = hasher {
+                hasher.update(&chunk);
+            }
+            counter += len;
             #[cfg(feature = "callback")]
             if let Some(channel) = &channel {
-                // network chunks of ~10kb size
-                counter += len;
+                // The progress is reported per network chunk, not per write to the file, so it
+                // doesn't stall while the write buffer fills up.
                 // Will abort if the receiver is closed
                 // Will ignore if the channel is full and thus not slow down the download
                 if let Err(TrySendError::Closed(_)) =
@@ -340,6 +1319,33 @@ impl Stream {
         Ok(counter)
     }
 
+    /// Resolves once the [`ShutdownHandle`] of the stream is triggered, and never, if there's none.
+    async fn shutdown_triggered(&self) {
+        match self.shutdown {
+            Some(ref shutdown) => shutdown.triggered().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    #[inline]
+    fn record_request(&self) {
+        if let Some(ref transfer_stats) = self.transfer_stats {
+            transfer_stats.record_request();
+        }
+    }
+
+    /// Wraps errors of streams, that are still being processed, so the error tells why the
+    /// stream could not be downloaded.
+    fn processing_hint(&self, error: Error) -> Error {
+        match self.is_post_live_processing() {
+            true => Error::PostLiveProcessing {
+                video_id: self.video_details.video_id.clone(),
+                source: Box::new(error),
+            },
+            false => error,
+        }
+    }
+
     #[inline]
     fn set_url_seq_query(url: &mut url::Url, base_query: &str, sq: u64) {
         url.set_query(Some(base_query));
@@ -348,21 +1354,38 @@ impl Stream {
             .append_pair("sq", &sq.to_string());
     }
 
-    #[inline]
-    fn extract_segment_count(res: &reqwest::Response) -> Result<u64> {
+    /// The first byte of the `Content-Range` of a partial response (`bytes <start>-<end>/<len>`).
+    fn extract_content_range_start(res: &reqwest::Response) -> Result<u64> {
         res
             .headers()
-            .get("Segment-Count")
+            .get(reqwest::header::CONTENT_RANGE)
+            .and_then(|content_range| content_range.to_str().ok())
+            .and_then(|content_range| content_range.strip_prefix("bytes "))
+            .and_then(|range| range.split('-').next())
+            .and_then(|start| start.trim().parse::<u64>().ok())
             .ok_or_else(|| Error::UnexpectedResponse(
-                "sequence download request did not contain a Segment-Count".into()
-            ))?
+                "the partial response did not contain a valid Content-Range".into()
+            ))
+    }
+
+    /// The number of segments of a sequenced resource, from the `Segment-Count` header of its
+    /// 0th segment. Some servers send the sequence number of the last segment
+    /// (`X-Head-Seqnum`) instead.
+    fn extract_segment_count(headers: &reqwest::header::HeaderMap) -> Result<u64> {
+        let (name, value, offset) = match (headers.get("Segment-Count"), headers.get("X-Head-Seqnum")) {
+            (Some(value), _) => ("Segment-Count", value, 0),
+            (None, Some(value)) => ("X-Head-Seqnum", value, 1),
+            (None, None) => return Err(Error::UnexpectedResponse(
+                "sequence download request did not contain a Segment-Count, or X-Head-Seqnum".into()
+            )),
+        };
+        value
             .to_str()
-            .map_err(|_| Error::UnexpectedResponse(
-                "Segment-Count is not valid utf-8".into()
-            ))?
-            .parse::<u64>()
-            .map_err(|_| Error::UnexpectedResponse(
-                "Segment-Count could not be parsed into an integer".into()
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .and_then(|value| value.checked_add(offset))
+            .ok_or_else(|| Error::UnexpectedResponse(
+                format!("{} {:?} could not be parsed into an integer", name, value).into()
             ))
     }
 }
@@ -378,7 +1401,7 @@ impl Stream {
     /// A synchronous wrapper around [`Stream::download_with_callback`](crate::Stream::download_with_callback).
     #[cfg(feature = "callback")]
     #[inline]
-    pub fn blocking_download_with_callback<'a>(&'a self, callback: Callback<'a>) -> Result<PathBuf> {
+    pub fn blocking_download_with_callback<'a>(&self, callback: Callback<'a>) -> Result<PathBuf> {
         crate::block!(self.download_with_callback(callback))
     }
 
@@ -392,7 +1415,7 @@ impl Stream {
     #[cfg(feature = "callback")]
     #[inline]
     pub fn blocking_download_to_dir_with_callback<'a, P: AsRef<Path>>(
-        &'a self,
+        &self,
         dir: P,
         callback: Callback<'a>,
     ) -> Result<PathBuf> {
@@ -406,10 +1429,44 @@ impl Stream {
 
     /// A synchronous wrapper around [`Stream::download_to_with_callback`](crate::Stream::download_to_with_callback).
     #[cfg(feature = "callback")]
-    pub fn blocking_download_to_with_callback<'a, P: AsRef<Path>>(&'a self, path: P, callback: Callback<'a>) -> Result<()> {
+    pub fn blocking_download_to_with_callback<'a, P: AsRef<Path>>(&self, path: P, callback: Callback<'a>) -> Result<()> {
         crate::block!(self.download_to_with_callback(path, callback))
     }
 
+    /// A synchronous wrapper around [`Stream::download_to_resumable`](crate::Stream::download_to_resumable).
+    #[inline]
+    pub fn blocking_download_to_resumable<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        crate::block!(self.download_to_resumable(path))
+    }
+
+    /// A synchronous wrapper around [`Stream::download_to_resumable_with_callback`](crate::Stream::download_to_resumable_with_callback).
+    #[cfg(feature = "callback")]
+    #[inline]
+    pub fn blocking_download_to_resumable_with_callback<'a, P: AsRef<Path>>(
+        &self,
+        path: P,
+        callback: Callback<'a>,
+    ) -> Result<()> {
+        crate::block!(self.download_to_resumable_with_callback(path, callback))
+    }
+
+    /// A synchronous wrapper around [`Stream::download_to_writer`](crate::Stream::download_to_writer).
+    #[inline]
+    pub fn blocking_download_to_writer<W: AsyncWrite + Unpin + ?Sized>(&self, writer: &mut W) -> Result<u64> {
+        crate::block!(self.download_to_writer(writer))
+    }
+
+    /// A synchronous wrapper around [`Stream::download_to_writer_with_callback`](crate::Stream::download_to_writer_with_callback).
+    #[cfg(feature = "callback")]
+    #[inline]
+    pub fn blocking_download_to_writer_with_callback<'a, W: AsyncWrite + Unpin + ?Sized>(
+        &self,
+        writer: &mut W,
+        callback: Callback<'a>,
+    ) -> Result<u64> {
+        crate::block!(self.download_to_writer_with_callback(writer, callback))
+    }
+
     /// A synchronous wrapper around [`Stream::content_length`](crate::Stream::content_length).
     #[inline]
     pub fn blocking_content_length(&self) -> Result<u64> {
@@ -417,9 +1474,33 @@ impl Stream {
     }
 }
 
+/// The path, a download to `path` is written to, until it's complete (`<path>.part`).
+#[cfg(feature = "download")]
+pub(crate) fn part_path(path: &Path) -> PathBuf {
+    let mut part_path = path.as_os_str().to_owned();
+    part_path.push(".part");
+    PathBuf::from(part_path)
+}
+
+/// Renames `from` to `to`, replacing `to`, if it exists.
+#[cfg(feature = "download")]
+pub(crate) async fn replace_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    match tokio::fs::rename(from, to).await {
+        Ok(()) => Ok(()),
+        // renaming replaces existing files on all platforms, but on Windows it fails, i.e. if the
+        // existing file is read-only, or was opened without `FILE_SHARE_DELETE`
+        Err(e) if tokio::fs::try_exists(to).await.unwrap_or(false) => {
+            log::debug!("failed to replace {:?} ({}), removing it first", to, e);
+            tokio::fs::remove_file(to).await?;
+            tokio::fs::rename(from, to).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
 #[inline]
 fn is_adaptive(codecs: &[String]) -> bool {
-    codecs.len() % 2 != 0
+    !codecs.len().is_multiple_of(2)
 }
 
 #[inline]
@@ -437,7 +1518,64 @@ fn is_progressive(codecs: &[String]) -> bool {
     !is_adaptive(codecs)
 }
 
-#[inline]
-fn atomic_u64_is_eq(lhs: &Arc<AtomicU64>, rhs: &Arc<AtomicU64>) -> bool {
-    lhs.load(Ordering::Acquire) == rhs.load(Ordering::Acquire)
+/// Formats a number of bytes with a decimal unit, i.e. `180.24 MB`.
+fn fmt_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1000.;
+    let mut unit = 0;
+    while size >= 1000. && unit < UNITS.len() - 1 {
+        size /= 1000.;
+        unit += 1;
+    }
+    format!("{:.2} {}", size, UNITS[unit])
+}
+
+/// The content length of a [`Stream`], once it's known, or a request for it failed. An empty
+/// [`OnceCell`] means, it's not known yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ContentLength {
+    Known(u64),
+    /// Requesting the content length failed, which only happens with the `download` feature.
+    #[cfg_attr(not(feature = "download"), allow(dead_code))]
+    Unavailable,
+}
+
+/// (De)serializes the content length as a number, which is `0`, if it's not known.
+mod content_length {
+    use std::sync::Arc;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+    use tokio::sync::OnceCell;
+
+    use super::ContentLength;
+
+    pub(super) fn serialize<S: Serializer>(cell: &Arc<OnceCell<ContentLength>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match cell.get() {
+            Some(&ContentLength::Known(cl)) => serializer.serialize_u64(cl),
+            _ => serializer.serialize_u64(0),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<OnceCell<ContentLength>>, D::Error> {
+        let cl = u64::deserialize(deserializer)?;
+        Ok(new(Some(cl).filter(|&cl| cl != 0)))
+    }
+
+    /// A cell, that already contains the content length, if it's known.
+    pub(super) fn new(content_length: Option<u64>) -> Arc<OnceCell<ContentLength>> {
+        let cell = OnceCell::new();
+        if let Some(cl) = content_length {
+            let _ = cell.set(ContentLength::Known(cl));
+        }
+        Arc::new(cell)
+    }
+
+    #[inline]
+    pub(super) fn is_eq(lhs: &Arc<OnceCell<ContentLength>>, rhs: &Arc<OnceCell<ContentLength>>) -> bool {
+        lhs.get() == rhs.get()
+    }
 }
@@ -0,0 +1,92 @@
+//! The single ordering of [`Stream`]s by quality, which all selection helpers, like
+//! [`Video::best_quality`](crate::Video::best_quality), are built on.
+
+use super::Stream;
+use super::codec::{AudioCodec, VideoCodec};
+use crate::video_info::player_response::streaming_data::QualityLabel;
+
+/// A key, that ranks [`Stream`]s by their quality (see [`Stream::rank`]). A greater rank means a
+/// better quality.
+///
+/// Ranks are compared field by field, in the order of declaration:
+/// 1. whether the stream has both a video and an audio track
+/// 2. the resolution of the video track, in pixels
+/// 3. the frame rate of the video track
+/// 4. whether the video track is HDR
+/// 5. the bitrate of streams with a video track (including the audio track of progressive streams)
+/// 6. the bitrate of audio only streams
+/// 7. the codec preference: AV1 over VP9 over H.264 for video, and Opus over AAC for audio, since
+///    the newer codecs achieve a better quality at the same bitrate
+///
+/// Unknown values count as zero, or `false`. Different streams can have the same rank.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamRank {
+    pub has_video_and_audio: bool,
+    pub resolution: u64,
+    pub fps: u8,
+    pub is_hdr: bool,
+    pub video_bitrate: u64,
+    pub audio_bitrate: u64,
+    pub codec_preference: u8,
+}
+
+impl Stream {
+    /// The [`StreamRank`] of the stream, which orders streams by their quality.
+    ///
+    /// # Example
+    /// ```no_run
+    ///# use rustube::Video;
+    ///# fn f(video: &Video) {
+    /// let best = video.streams().iter().max_by_key(|stream| stream.rank());
+    ///# }
+    /// ```
+    pub fn rank(&self) -> StreamRank {
+        let bitrate = self.bitrate.or(self.average_bitrate).unwrap_or(0);
+
+        if !self.includes_video_track {
+            return StreamRank {
+                audio_bitrate: bitrate,
+                codec_preference: self.audio_codec().map_or(0, |codec| audio_codec_preference(&codec)),
+                ..StreamRank::default()
+            };
+        }
+
+        StreamRank {
+            has_video_and_audio: self.includes_audio_track,
+            resolution: self.width.unwrap_or(0) * self.height.unwrap_or(0),
+            fps: self.fps,
+            is_hdr: self.is_hdr(),
+            video_bitrate: bitrate,
+            audio_bitrate: 0,
+            codec_preference: self.video_codec().map_or(0, |codec| video_codec_preference(&codec)),
+        }
+    }
+
+    /// Whether the video track of the stream is HDR, according to the transfer characteristics of
+    /// its color info (PQ, or HLG), or its quality label.
+    pub fn is_hdr(&self) -> bool {
+        let hdr_transfer = self.color_info
+            .as_ref()
+            .is_some_and(|info| info.transfer_characteristics.is_hdr());
+
+        self.includes_video_track
+            && (hdr_transfer || self.quality_label.as_ref().is_some_and(QualityLabel::is_hdr))
+    }
+}
+
+fn video_codec_preference(codec: &VideoCodec) -> u8 {
+    match codec {
+        VideoCodec::Av1 { .. } => 3,
+        VideoCodec::Vp9 { .. } => 2,
+        VideoCodec::Avc1 { .. } => 1,
+        _ => 0,
+    }
+}
+
+fn audio_codec_preference(codec: &AudioCodec) -> u8 {
+    match codec {
+        AudioCodec::Opus => 2,
+        AudioCodec::Aac { .. } => 1,
+        _ => 0,
+    }
+}
@@ -0,0 +1,227 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::{Buf, Bytes};
+use reqwest::{Client, Response};
+use reqwest::header::HeaderMap;
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+use url::Url;
+
+use crate::Result;
+use crate::transfer::TransferStats;
+
+/// The default number of bytes a [`StreamReader`] prefetches.
+pub const DEFAULT_PREFETCH: usize = 1024 * 1024;
+
+/// An [`AsyncRead`] over the resource of a [`Stream`](crate::Stream).
+///
+/// Created by [`Stream::open_reader`](crate::Stream::open_reader). The resource is downloaded by a
+/// background task, that runs ahead of the reader by at most `prefetch` bytes. Chunks are passed
+/// to the reader via a channel, which is bounded by the number of bytes it holds, not by the number
+/// of chunks. Once the buffer is full, the download pauses until the reader consumed some of it.
+///
+/// Dropping the reader cancels the download.
+#[derive(Debug)]
+pub struct StreamReader {
+    receiver: mpsc::UnboundedReceiver<Result<Bytes>>,
+    permits: Arc<Semaphore>,
+    prefetch: usize,
+    chunk: Bytes,
+    // the number of permits, the current chunk holds
+    chunk_permits: usize,
+    task: JoinHandle<()>,
+}
+
+impl StreamReader {
+    /// Starts downloading `first`, followed by the resources at `following`.
+    /// The requests and bytes are added to `transfer_stats`, if there are any.
+    pub(crate) fn spawn(
+        client: Client,
+        headers: HeaderMap,
+        transfer_stats: Option<Arc<TransferStats>>,
+        first: Response,
+        following: Vec<Url>,
+        prefetch: usize,
+    ) -> Self {
+        let prefetch = prefetch.clamp(1, Semaphore::MAX_PERMITS.min(u32::MAX as usize));
+        let permits = Arc::new(Semaphore::new(prefetch));
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn(Self::forward_all(
+            client,
+            headers,
+            transfer_stats,
+            first,
+            following,
+            sender,
+            Arc::clone(&permits),
+            prefetch,
+        ));
+
+        Self {
+            receiver,
+            permits,
+            prefetch,
+            chunk: Bytes::new(),
+            chunk_permits: 0,
+            task,
+        }
+    }
+
+    /// The maximum number of bytes, that are downloaded ahead of the reader.
+    #[inline]
+    pub fn prefetch(&self) -> usize {
+        self.prefetch
+    }
+
+    /// The number of bytes, that are downloaded, but not read yet.
+    #[inline]
+    pub fn buffered(&self) -> usize {
+        self.prefetch - self.permits.available_permits()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn forward_all(
+        client: Client,
+        headers: HeaderMap,
+        transfer_stats: Option<Arc<TransferStats>>,
+        first: Response,
+        following: Vec<Url>,
+        sender: mpsc::UnboundedSender<Result<Bytes>>,
+        permits: Arc<Semaphore>,
+        prefetch: usize,
+    ) {
+        let mut response = first;
+        let mut following = following.into_iter();
+
+        loop {
+            match Self::forward(response, transfer_stats.as_deref(), &sender, &permits, prefetch).await {
+                Ok(true) => {}
+                // the reader was dropped
+                Ok(false) => return,
+                Err(err) => {
+                    let _ = sender.send(Err(err));
+                    return;
+                }
+            }
+
+            let url = match following.next() {
+                Some(url) => url,
+                None => return,
+            };
+            log::trace!("get: {}", url.as_str());
+            if let Some(ref transfer_stats) = transfer_stats {
+                transfer_stats.record_request();
+            }
+            let request = client.get(url.as_str()).headers(headers.clone());
+            response = match request.send().await.and_then(Response::error_for_status) {
+                Ok(response) => response,
+                Err(err) => {
+                    let _ = sender.send(Err(err.into()));
+                    return;
+                }
+            };
+        }
+    }
+
+    /// Forwards the body of `response` to the reader. Returns `false` if the reader was dropped.
+    async fn forward(
+        response: Response,
+        transfer_stats: Option<&TransferStats>,
+        sender: &mpsc::UnboundedSender<Result<Bytes>>,
+        permits: &Semaphore,
+        prefetch: usize,
+    ) -> Result<bool> {
+        let mut stream = response.bytes_stream();
+
+        while let Some(chunk) = stream.next().await {
+            let mut chunk = chunk?;
+            log::trace!("received {} byte chunk ", chunk.len());
+            if let Some(transfer_stats) = transfer_stats {
+                transfer_stats.record_media(chunk.len() as u64)?;
+            }
+
+            while !chunk.is_empty() {
+                let part = chunk.split_to(chunk.len().min(prefetch));
+                let permit = match permits.acquire_many(part.len() as u32).await {
+                    Ok(permit) => permit,
+                    Err(_) => return Ok(false),
+                };
+                // the reader gives the permits back, once it consumed the part
+                permit.forget();
+
+                if sender.send(Ok(part)).is_err() {
+                    return Ok(false);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl AsyncRead for StreamReader {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        loop {
+            if !self.chunk.is_empty() {
+                let n = self.chunk.len().min(buf.remaining());
+                buf.put_slice(&self.chunk[..n]);
+                self.chunk.advance(n);
+
+                if self.chunk.is_empty() {
+                    let permits = std::mem::take(&mut self.chunk_permits);
+                    self.permits.add_permits(permits);
+                }
+                return Poll::Ready(Ok(()));
+            }
+
+            match self.receiver.poll_recv(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    self.chunk_permits = chunk.len();
+                    self.chunk = chunk;
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Err(io::Error::other(err))),
+                // the download completed
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// The chunks of the resource as they arrive, i.e. for forwarding them (see
+/// [`Stream::bytes_stream`](crate::Stream::bytes_stream)).
+///
+/// A chunk leaves the prefetch buffer, once it's returned. If the reader was already used as an
+/// [`AsyncRead`], the rest of the current chunk is returned first.
+impl tokio_stream::Stream for StreamReader {
+    type Item = Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if !self.chunk.is_empty() {
+            let chunk = std::mem::take(&mut self.chunk);
+            let permits = std::mem::take(&mut self.chunk_permits);
+            self.permits.add_permits(permits);
+            return Poll::Ready(Some(Ok(chunk)));
+        }
+
+        match self.receiver.poll_recv(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                self.permits.add_permits(chunk.len());
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            poll => poll,
+        }
+    }
+}
+
+impl Drop for StreamReader {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
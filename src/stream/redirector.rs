@@ -0,0 +1,60 @@
+//! Following the redirector of `googlevideo.com`.
+//!
+//! When the mirrors of a stream are overloaded, `videoplayback` requests are either redirected to
+//! a different host (`302 Found`), or answered with a short plaintext body, that names an
+//! alternate host. Some proxies drop the query of redirected requests, which contains the
+//! signature, so in both cases the request is repeated against the new host, with the original
+//! path and query.
+
+use reqwest::Response;
+use reqwest::header::CONTENT_TYPE;
+use url::Url;
+
+/// The maximal number of times, a request is repeated against a different host.
+pub(crate) const MAX_HOPS: usize = 3;
+
+/// The maximal length of a plaintext body, that names an alternate host. Media is never served
+/// as plaintext, but the limit keeps misconfigured servers from being read into memory.
+const MAX_BODY_LEN: u64 = 1024;
+
+/// Whether `response` names an alternate host in its body, instead of containing media.
+pub(crate) fn is_alternate_host(response: &Response) -> bool {
+    let is_plaintext = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/plain"));
+    is_plaintext && response.content_length().is_some_and(|len| len <= MAX_BODY_LEN)
+}
+
+/// Parses the body of an alternate host response to the request of `url`, which is either a URL,
+/// or a bare host with an optional port. Bare hosts keep the scheme of `url`.
+pub(crate) fn parse_alternate_host(body: &str, url: &Url) -> Option<Url> {
+    let body = body.trim();
+    Url::parse(body)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .or_else(|| {
+            Url::parse(&format!("{}://{}", url.scheme(), body))
+                .ok()
+                .filter(|url| url.path() == "/" && url.query().is_none())
+        })
+        .filter(|url| url.host_str().is_some())
+}
+
+/// Whether `response_url` misses any of the query parameters of `url`, i.e. since a proxy dropped
+/// them while following a redirect.
+pub(crate) fn lost_query(url: &Url, response_url: &Url) -> bool {
+    response_url != url && url
+        .query_pairs()
+        .any(|pair| !response_url.query_pairs().any(|other| other == pair))
+}
+
+/// `url` with the scheme, the host and the port of `location`, but its own path and query.
+pub(crate) fn with_host_of(url: &Url, location: &Url) -> Option<Url> {
+    let mut url = url.clone();
+    url.set_scheme(location.scheme()).ok()?;
+    url.set_host(location.host_str()).ok()?;
+    url.set_port(location.port()).ok()?;
+    Some(url)
+}
@@ -0,0 +1,165 @@
+//! Retrying downloads, that failed for transient reasons.
+//!
+//! `googlevideo.com` occasionally answers with a `403 Forbidden`, or a `5xx`, for URLs, that
+//! work a moment later. A [`RetryPolicy`], that's set with [`Stream::with_retry_policy`], repeats
+//! such downloads after a backoff. Since a `403` often means, that the signature of the URL
+//! expired, the URL is refreshed from a new player response before the download is repeated.
+//! URLs, that are known to be [expired](Stream::is_expired), are refreshed before the download
+//! even starts.
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::time::Duration;
+
+use super::{InternalSender, Stream};
+use super::hash::HashAlgorithm;
+use crate::{Error, Result, VideoFetcher};
+
+/// The delay before the first retry of a download (see [`RetryPolicy::backoff`]).
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// How downloads of a [`Stream`], that fail with a `403 Forbidden`, a `5xx`, or a network error,
+/// are repeated (see [`Stream::with_retry_policy`]).
+///
+/// Only the download methods, that write to a file, are retried, since the bytes, that were
+/// already passed to a writer, cannot be taken back.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    retries: u32,
+    backoff: Duration,
+    refresh_on_forbidden: bool,
+    refresh_expired: bool,
+    fetcher: Option<VideoFetcher>,
+}
+
+impl Default for RetryPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            backoff: DEFAULT_RETRY_BACKOFF,
+            refresh_on_forbidden: true,
+            refresh_expired: true,
+            fetcher: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates the default policy.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How often a failed download is repeated. [default: 3]
+    #[inline]
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// The delay before the first retry, which is doubled with each further retry.
+    /// [default: [`DEFAULT_RETRY_BACKOFF`]]
+    #[inline]
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Whether the URL of the stream is refreshed from a new player response, before a download,
+    /// that failed with a `403 Forbidden`, is repeated. [default: true]
+    #[inline]
+    pub fn refresh_on_forbidden(mut self, refresh_on_forbidden: bool) -> Self {
+        self.refresh_on_forbidden = refresh_on_forbidden;
+        self
+    }
+
+    /// Whether the URL of the stream is refreshed before the download starts, if it already
+    /// [expired](Stream::is_expired). [default: true]
+    #[inline]
+    pub fn refresh_expired(mut self, refresh_expired: bool) -> Self {
+        self.refresh_expired = refresh_expired;
+        self
+    }
+
+    /// The [`VideoFetcher`], the URL is refreshed with. It has to fetch the video of the stream.
+    /// [default: a fetcher for the video, that uses the client of the stream]
+    #[inline]
+    pub fn fetcher(mut self, fetcher: VideoFetcher) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    /// The delay before the `retry`th retry (starting at 1).
+    #[inline]
+    fn delay(&self, retry: u32) -> Duration {
+        self.backoff * 2u32.saturating_pow(retry - 1)
+    }
+
+    /// The [`fetcher`](Self::fetcher), or one for the video of `stream`, that uses its client.
+    fn fetcher_for(&self, stream: &Stream) -> VideoFetcher {
+        match self.fetcher {
+            Some(ref fetcher) => fetcher.clone(),
+            None => VideoFetcher::from_id_with_client(
+                stream.video_details.video_id.clone(),
+                stream.client.clone(),
+            ),
+        }
+    }
+}
+
+impl Stream {
+    /// [`Stream::download_to_part`], repeated according to the [`RetryPolicy`] of the stream.
+    pub(super) async fn download_to_part_with_retries(
+        &self,
+        part_path: &Path,
+        channel: &Option<InternalSender>,
+        hash: Option<HashAlgorithm>,
+        write_buffer_size: usize,
+        host: &mut Option<String>,
+    ) -> Result<(u64, Option<String>)> {
+        let policy = match self.retry_policy {
+            Some(ref policy) => policy,
+            None => return self.download_to_part(part_path, channel, hash, write_buffer_size, host).await,
+        };
+
+        let mut stream = Cow::Borrowed(self);
+        if policy.refresh_expired && self.is_expired() {
+            log::info!("the URL of {} expired, refreshing it", self.video_details.video_id);
+            stream.to_mut().refresh_with(policy.fetcher_for(self)).await?;
+        }
+
+        let mut retry = 0;
+        loop {
+            let error = match stream.download_to_part(part_path, channel, hash, write_buffer_size, host).await {
+                Err(e) if retry < policy.retries && is_transient(&e) => e,
+                result => return result,
+            };
+            retry += 1;
+
+            let delay = match error {
+                Error::RateLimited { retry_after: Some(retry_after), .. } => retry_after,
+                _ => policy.delay(retry),
+            };
+            log::warn!(
+                "failed to download {} ({}), retrying in {:?} ({} of {})",
+                self.video_details.video_id, error, delay, retry, policy.retries,
+            );
+            tokio::select! {
+                biased;
+                _ = self.shutdown_triggered() => return Err(Error::Cancelled),
+                _ = tokio::time::sleep(delay) => {}
+            }
+
+            if policy.refresh_on_forbidden && matches!(error, Error::Forbidden { .. } | Error::UrlExpired { .. }) {
+                stream.to_mut().refresh_with(policy.fetcher_for(self)).await?;
+            }
+        }
+    }
+}
+
+/// Whether a download, that failed with `error`, might succeed, when it's repeated.
+fn is_transient(error: &Error) -> bool {
+    error.is_retryable() || matches!(error, Error::Forbidden { .. } | Error::UrlExpired { .. })
+}
@@ -0,0 +1,166 @@
+//! Cleaning up text from YouTube, i.e. titles, before showing it, or using it as a file name.
+//!
+//! Titles are chosen by the uploader, and may contain anything: control characters, right-to-left
+//! overrides, which reorder the text they are shown in, or emoji sequences, that are cut in half by
+//! a naive truncation. Some renderers additionally deliver their text with HTML entities, like
+//! `&amp;`.
+//!
+//! # Example
+//! ```
+//!# use rustube::text::{safe_title_for_filename, sanitize_for_display};
+//! assert_eq!(sanitize_for_display("Tom &amp; Jerry\u{202e}\0"), "Tom & Jerry");
+//! assert_eq!(safe_title_for_filename("AC/DC: Live?", 200), "AC_DC_ Live_");
+//! ```
+
+use std::borrow::Cow;
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The default maximum length of a file name in bytes, that leaves room for an extension on all
+/// common file systems, which allow 255 bytes.
+pub const MAX_FILENAME_BYTES: usize = 200;
+
+/// The characters, that are not allowed in file names on at least one common platform.
+const RESERVED_FILENAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// The file names, Windows reserves for devices, regardless of their extension.
+const RESERVED_FILENAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Replaces the named HTML entities YouTube uses, and all numeric entities.
+///
+/// Unlike an XML parser, this never fails: unknown or unterminated entities are kept as they are,
+/// since a title may well contain a literal `&`.
+pub fn decode_html_entities(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let c = rest
+            .find(';')
+            .filter(|&end| end > 1)
+            .and_then(|end| Some((entity(&rest[1..end])?, end)));
+        match c {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    Cow::Owned(decoded)
+}
+
+/// The character of the entity `&<name>;`.
+pub(crate) fn entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" | "#39" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => name
+            .strip_prefix("#x")
+            .or_else(|| name.strip_prefix("#X"))
+            .map(|hex| u32::from_str_radix(hex, 16))
+            .or_else(|| name.strip_prefix('#').map(str::parse))
+            .and_then(|code| code.ok())
+            .and_then(char::from_u32),
+    }
+}
+
+/// Whether `c` changes the direction of the text around it, i.e. the right-to-left override
+/// `U+202E`. Plain direction marks, like `U+200F`, only affect neutral characters, and are kept.
+#[inline]
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+/// Decodes the HTML entities of `text`, and removes control characters and bidi overrides, so
+/// it can be printed on a single line without garbling the output around it.
+///
+/// Line breaks and tabs become spaces, all other control characters, like `NUL`, are removed.
+/// Zero width joiners are kept, since emoji sequences need them.
+pub fn sanitize_for_display(text: &str) -> String {
+    decode_html_entities(text)
+        .chars()
+        .filter(|&c| !is_bidi_control(c))
+        .filter_map(|c| match c {
+            '\t' | '\n' | '\r' | '\u{2028}' | '\u{2029}' => Some(' '),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+/// Turns `title` into a file name (without extension), that is valid on all common platforms,
+/// and at most `max_bytes` bytes long (see [`MAX_FILENAME_BYTES`]).
+///
+/// The title is [sanitized](sanitize_for_display), characters that are reserved on some platforms
+/// (`/ \ : * ? " < > |`) are replaced with `_`, and the result is normalized to NFC. Long titles
+/// are truncated at a grapheme boundary, so no character or emoji sequence is cut in half.
+/// Leading and trailing whitespace and dots are removed, and names Windows reserves for devices,
+/// like `CON`, get a trailing `_`.
+///
+/// Returns an empty string, if nothing of the title is left, i.e. for a title only consisting of
+/// control characters. Callers should fall back to something else, like the video id, then.
+pub fn safe_title_for_filename(title: &str, max_bytes: usize) -> String {
+    let sanitized: String = sanitize_for_display(title)
+        .chars()
+        .map(|c| match RESERVED_FILENAME_CHARS.contains(&c) {
+            true => '_',
+            false => c,
+        })
+        .nfc()
+        .collect();
+
+    let trimmed = trim_filename(&sanitized);
+    let mut filename = trim_filename(truncate_graphemes(trimmed, max_bytes)).to_owned();
+
+    let stem = filename.split('.').next().unwrap_or_default();
+    if RESERVED_FILENAMES.iter().any(|reserved| stem.eq_ignore_ascii_case(reserved)) {
+        match filename.len() < max_bytes {
+            true => filename.push('_'),
+            false => filename.replace_range(..1, "_"),
+        }
+    }
+    filename
+}
+
+/// Removes whitespace and dots from both ends of a file name. Windows silently drops trailing
+/// dots and spaces, and leading dots hide files on Unix.
+#[inline]
+fn trim_filename(filename: &str) -> &str {
+    filename.trim_matches(|c: char| c.is_whitespace() || c == '.')
+}
+
+/// The longest prefix of `text` of at most `max_bytes` bytes, that ends at a grapheme boundary.
+fn truncate_graphemes(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+
+    let end = text
+        .grapheme_indices(true)
+        .map(|(start, grapheme)| start + grapheme.len())
+        .take_while(|&end| end <= max_bytes)
+        .last()
+        .unwrap_or(0);
+    &text[..end]
+}
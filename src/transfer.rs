@@ -0,0 +1,123 @@
+//! Opt-in accounting of the bytes transferred per session, i.e. for staying below a data cap.
+//!
+//! Pass a shared [`TransferStats`] to [`VideoFetcher::with_transfer_stats`](crate::VideoFetcher::with_transfer_stats).
+//! Like [`Metrics`](crate::Metrics), it's handed on to the [`Video`](crate::Video) and its
+//! [`Stream`](crate::Stream)s, so the same counters can be shared by any number of fetchers and
+//! downloads. They can be read, and reset, at any time.
+//!
+//! A [`TransferStats::limit`]ed collector additionally aborts downloads with
+//! [`Error::QuotaExceeded`](crate::Error::QuotaExceeded), once more media bytes than the cap were
+//! transferred.
+//!
+//! # Example
+//! ```no_run
+//!# #[cfg(feature = "download")]
+//!# #[tokio::main]
+//!# async fn main() -> rustube::Result<()> {
+//! use std::sync::Arc;
+//! use rustube::{Id, TransferStats, VideoFetcher};
+//!
+//! let stats = Arc::new(TransferStats::limit(500 * 1024 * 1024));
+//! let id = Id::from_str("5jlI4uzZGjU")?;
+//! let video = VideoFetcher::from_id(id.into_owned())?
+//!     .with_transfer_stats(Arc::clone(&stats))
+//!     .fetch()
+//!     .await?
+//!     .descramble()?;
+//!
+//! video.best_quality().unwrap().download().await?;
+//! println!("{} media bytes in {} requests", stats.media_bytes(), stats.request_count());
+//!# Ok(())
+//!# }
+//!# #[cfg(not(feature = "download"))]
+//!# fn main() {}
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Thread safe counters of the bytes and requests of a session. See the
+/// [module level documentation](self).
+#[derive(Debug, Default)]
+pub struct TransferStats {
+    media_bytes: AtomicU64,
+    metadata_bytes: AtomicU64,
+    request_count: AtomicU64,
+    media_cap: Option<u64>,
+}
+
+impl TransferStats {
+    /// Creates counters, that start at zero, and never abort a download.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates counters, that abort all downloads with
+    /// [`Error::QuotaExceeded`](crate::Error::QuotaExceeded), once more than `media_bytes_cap`
+    /// media bytes were transferred.
+    ///
+    /// The cap is checked after each received chunk, so the chunk crossing it is counted, but
+    /// not written anymore.
+    #[inline]
+    pub fn limit(media_bytes_cap: u64) -> Self {
+        Self {
+            media_cap: Some(media_bytes_cap),
+            ..Self::default()
+        }
+    }
+
+    /// The number of bytes of stream resources transferred so far.
+    #[inline]
+    pub fn media_bytes(&self) -> u64 {
+        self.media_bytes.load(Ordering::Relaxed)
+    }
+
+    /// The number of bytes of watch pages, player responses, `base.js` and other metadata
+    /// transferred so far.
+    #[inline]
+    pub fn metadata_bytes(&self) -> u64 {
+        self.metadata_bytes.load(Ordering::Relaxed)
+    }
+
+    /// The number of requests sent so far, including the ones, that failed.
+    #[inline]
+    pub fn request_count(&self) -> u64 {
+        self.request_count.load(Ordering::Relaxed)
+    }
+
+    /// The cap of the media bytes, if the counters were created with [`TransferStats::limit`].
+    #[inline]
+    pub fn media_bytes_cap(&self) -> Option<u64> {
+        self.media_cap
+    }
+
+    /// Sets all counters back to zero. The cap stays the same.
+    pub fn reset(&self) {
+        self.media_bytes.store(0, Ordering::Relaxed);
+        self.metadata_bytes.store(0, Ordering::Relaxed);
+        self.request_count.store(0, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn record_request(&self) {
+        self.request_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn record_metadata(&self, bytes: u64) {
+        self.metadata_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Adds `bytes` to the media bytes.
+    ///
+    /// ### Errors
+    /// - [`Error::QuotaExceeded`](crate::Error::QuotaExceeded), when the media bytes now exceed the cap.
+    #[cfg(feature = "download")]
+    pub(crate) fn record_media(&self, bytes: u64) -> crate::Result<()> {
+        let media_bytes = self.media_bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        match self.media_cap {
+            Some(cap) if media_bytes > cap => Err(crate::Error::QuotaExceeded { cap, media_bytes }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,9 +1,51 @@
+use std::convert::TryFrom;
 use std::sync::Arc;
 
 use derive_more::Display;
+use url::Url;
 
-use crate::{Id, Stream, VideoInfo};
-use crate::video_info::player_response::video_details::VideoDetails;
+use crate::{Id, NoStreamsReason, Stream, StreamQuery, ThumbnailQuality, VideoInfo};
+#[cfg(feature = "download")]
+use crate::dash::{DashManifest, Representation, SegmentClient, SegmentProgress};
+#[cfg(feature = "download")]
+use crate::hls::{HlsMasterPlaylist, HlsVariant};
+use crate::video_info::player_response::annotations::{Card, EndscreenElement};
+use crate::video_info::player_response::player_config::Loudness;
+use crate::video_info::player_response::streaming_data::AudioTrack;
+use crate::video_info::player_response::video_details::{LiveStatus, VideoDetails};
+
+impl TryFrom<crate::VideoDescrambler> for Video {
+    type Error = crate::Error;
+
+    /// Descrambles the video (see [`VideoDescrambler::descramble`](crate::VideoDescrambler::descramble)).
+    #[inline]
+    fn try_from(descrambler: crate::VideoDescrambler) -> crate::Result<Self> {
+        descrambler.descramble()
+    }
+}
+
+impl From<Video> for VideoInfo {
+    /// Takes the [`VideoInfo`] of the video, and drops its streams.
+    #[inline]
+    fn from(video: Video) -> Self {
+        video.video_info
+    }
+}
+
+impl<'a> IntoIterator for &'a Video {
+    type Item = &'a Stream;
+    type IntoIter = std::slice::Iter<'a, Stream>;
+
+    /// Iterates over the [`streams`](Video::streams) of the video, so a [`Video`] can be passed to
+    /// the terminal operations of a [`StreamQuery`].
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.streams.iter()
+    }
+}
+
+/// The duration, after which the adaptive formats of finished live streams often end.
+const ADAPTIVE_FORMAT_MAX_SECONDS: u64 = 4 * 60 * 60;
 
 /// A YouTube downloader, which allows you to download all available formats and qualities of a 
 /// YouTube video. 
@@ -13,23 +55,26 @@ use crate::video_info::player_response::video_details::VideoDetails;
 ///
 /// There are two ways of constructing an instance of [`Video`]:
 /// 1. By using the asynchronous `Video::from_*` methods. These methods will take some kind of 
-/// video-identifier, like an [`Url`] or an [`Id`], will then internally download the necessary video 
-/// information and finally descramble it.
+///    video-identifier, like an [`Url`] or an [`Id`], will then internally download the necessary video 
+///    information and finally descramble it.
 /// 2. By calling [`VideoDescrambler::descramble`]. Since a [`VideoDescrambler`] already 
-/// contains the necessary video information, and just need to descramble it, no requests are
-/// performed. (This gives you more control over the process).
+///    contains the necessary video information, and just need to descramble it, no requests are
+///    performed. (This gives you more control over the process).
 /// 
 /// # Examples
 /// - Constructing using [`Video::from_url`] (or [`Video::from_id`]) (easiest way)
 /// ```no_run
 ///# use rustube::Video;
 ///# use url::Url;
+///# #[cfg(feature = "download")]
 ///# #[tokio::main]
 ///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// let url = Url::parse("https://youtube.com/watch?iv=5jlI4uzZGjU")?;
+/// let url = Url::parse("https://youtube.com/watch?v=5jlI4uzZGjU")?;
 /// let video: Video = Video::from_url(&url).await?;
 ///# Ok(())
 ///# }
+///# #[cfg(not(feature = "download"))]
+///# fn main() {}
 /// ``` 
 /// - Constructing using [`VideoDescrambler::descramble`]
 /// ```no_run
@@ -37,7 +82,7 @@ use crate::video_info::player_response::video_details::VideoDetails;
 ///# use url::Url;
 ///# #[tokio::main]
 ///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// let url = Url::parse("https://youtube.com/watch?iv=5jlI4uzZGjU")?;
+/// let url = Url::parse("https://youtube.com/watch?v=5jlI4uzZGjU")?;
 /// let fetcher: VideoFetcher = VideoFetcher::from_url(&url)?;
 /// let descrambler: VideoDescrambler = fetcher.fetch().await?;  
 /// let video: Video = descrambler.descramble()?;
@@ -50,7 +95,7 @@ use crate::video_info::player_response::video_details::VideoDetails;
 ///# use url::Url;
 ///# #[tokio::main]
 ///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// let url = Url::parse("https://youtube.com/watch?iv=5jlI4uzZGjU")?;
+/// let url = Url::parse("https://youtube.com/watch?v=5jlI4uzZGjU")?;
 /// let video: Video = VideoFetcher::from_url(&url)?
 ///    .fetch()
 ///    .await?
@@ -62,25 +107,29 @@ use crate::video_info::player_response::video_details::VideoDetails;
 /// ```no_run
 ///# use rustube::{Video, VideoFetcher, VideoDescrambler};
 ///# use url::Url;
+///# #[cfg(feature = "download")]
 ///# #[tokio::main]
 ///# async fn main() -> Result<(), Box<dyn std::error::Error>> {
-///# let url = Url::parse("https://youtube.com/watch?iv=5jlI4uzZGjU")?; 
+///# let url = Url::parse("https://youtube.com/watch?v=5jlI4uzZGjU")?; 
 ///# let video: Video = Video::from_url(&url).await?;
 /// let video_path = video
 ///    .streams()
 ///    .iter()
 ///    .filter(|stream| stream.includes_video_track && stream.includes_audio_track)
-///    .max_by_key(|stream| stream.quality_label)
+///    .max_by_key(|stream| stream.quality_label.as_ref())
 ///    .unwrap()
 ///    .download()
 ///    .await?;
 ///# Ok(())
 ///# }
+///# #[cfg(not(feature = "download"))]
+///# fn main() {}
 /// ``` 
 /// [`Url`]: url::Url
 /// [`VideoDescrambler`]: crate::descrambler::VideoDescrambler
 /// [`VideoDescrambler::descramble`]: crate::descrambler::VideoDescrambler::descramble
-#[derive(Clone, Debug, Display, PartialEq)]
+#[derive(Clone, Display, derivative::Derivative)]
+#[derivative(Debug, PartialEq)]
 #[display(fmt =
 "Video({}, streams: {})",
 "video_info.player_response.video_details.video_id", "streams.len()"
@@ -88,6 +137,12 @@ use crate::video_info::player_response::video_details::VideoDetails;
 pub struct Video {
     pub(crate) video_info: VideoInfo,
     pub(crate) streams: Vec<Stream>,
+    #[derivative(PartialEq = "ignore")]
+    pub(crate) descramble_errors: Arc<Vec<(u64, crate::Error)>>,
+    /// The player JavaScript, the streams were descrambled with, which is reused by
+    /// [`Video::refetch_streams_via`].
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub(crate) js: Option<Arc<str>>,
 }
 
 impl Video {
@@ -96,6 +151,8 @@ impl Video {
     /// - When [`VideoFetcher::from_url`](crate::VideoFetcher::from_url) fails.
     /// - When [`VideoFetcher::fetch`](crate::VideoFetcher::fetch) fails.
     /// - When [`VideoDescrambler::descramble`](crate::VideoDescrambler::descramble) fails.
+    /// - [`Error::NoStreamingData`](crate::Error::NoStreamingData), when the video has no streams
+    ///   yet. The error still contains the [`VideoInfo`].
     #[inline]
     #[cfg(all(feature = "download", feature = "regex"))]
     pub async fn from_url(url: &url::Url) -> crate::Result<Self> {
@@ -109,6 +166,8 @@ impl Video {
     /// ### Errors
     /// - When [`VideoFetcher::fetch`](crate::VideoFetcher::fetch) fails.
     /// - When [`VideoDescrambler::descramble`](crate::VideoDescrambler::descramble) fails.
+    /// - [`Error::NoStreamingData`](crate::Error::NoStreamingData), when the video has no streams
+    ///   yet. The error still contains the [`VideoInfo`].
     #[inline]
     #[cfg(feature = "download")]
     pub async fn from_id(id: crate::IdBuf) -> crate::Result<Self> {
@@ -118,18 +177,104 @@ impl Video {
             .descramble()
     }
 
+    /// Requests fresh streams for the video with `client`, i.e. one in the network of the host,
+    /// that downloads the streams, since their URLs are usually [bound](Stream::is_ip_bound) to
+    /// the IP address they were requested from.
+    ///
+    /// Only the player endpoint is requested, and the streams are descrambled with the player
+    /// JavaScript of this video (see [`VideoFetcher::refetch_streams`](crate::VideoFetcher::refetch_streams),
+    /// which also allows configuring the innertube clients).
+    ///
+    /// ### Errors
+    /// - When [`VideoFetcher::refetch_streams`](crate::VideoFetcher::refetch_streams) fails.
+    #[inline]
+    pub async fn refetch_streams_via(&self, client: reqwest::Client) -> crate::Result<Self> {
+        crate::VideoFetcher::from_id_with_client(self.id().as_owned(), client)
+            .refetch_streams(self)
+            .await
+    }
+
+    /// Creates a [`Video`] from it's raw parts. This is the inverse of [`Video::into_parts`].
+    ///
+    /// No requests are performed, and the `streams` are not validated against the `video_info`.
+    ///
+    /// Not part of the public API: it only exists, so the integration tests can build videos from
+    /// fixtures, and may change at any time.
+    #[doc(hidden)]
+    #[inline]
+    pub fn from_parts(video_info: VideoInfo, streams: Vec<Stream>) -> Self {
+        Self { video_info, streams, descramble_errors: Arc::default(), js: None }
+    }
+
     /// The [`VideoInfo`] of the video.
     #[inline]
     pub fn video_info(&self) -> &VideoInfo {
         &self.video_info
     }
 
+    /// The [`VideoInfo`] of the video (the same as [`Video::video_info`]).
+    #[inline]
+    pub fn info(&self) -> &VideoInfo {
+        &self.video_info
+    }
+
     /// All [`Stream`]s of the video.
     #[inline]
     pub fn streams(&self) -> &Vec<Stream> {
         &self.streams
     }
 
+    /// The formats, that were skipped, since their signature could not be descrambled, together
+    /// with the reason. Each entry starts with the itag of the format.
+    #[inline]
+    pub fn descramble_errors(&self) -> &[(u64, crate::Error)] {
+        &self.descramble_errors
+    }
+
+    /// Why a [`Stream`] could not be selected, i.e. when [`Video::best_quality`] returned `None`.
+    /// Used for [`Error::NoStreams`](crate::Error::NoStreams).
+    ///
+    /// If the video has any streams, they just don't match the selection, so the reason is
+    /// [`NoStreamsReason::NoMatchingStream`].
+    pub fn no_streams_reason(&self) -> NoStreamsReason {
+        if !self.streams.is_empty() {
+            return NoStreamsReason::NoMatchingStream;
+        }
+        if !self.descramble_errors.is_empty() {
+            return NoStreamsReason::AllDescrambleFailed;
+        }
+        NoStreamsReason::from_player_response(&self.video_info.player_response)
+            .unwrap_or(NoStreamsReason::EmptyStreamingData)
+    }
+
+    /// A snapshot of the [`Metrics`](crate::Metrics) recorded so far, if the video was fetched
+    /// using [`VideoFetcher::with_metrics`](crate::VideoFetcher::with_metrics).
+    ///
+    /// Downloads of the video's [`Stream`]s are recorded as well.
+    #[inline]
+    pub fn metrics(&self) -> Option<crate::MetricsReport> {
+        self.video_info.metrics()
+    }
+
+    /// The video and all its [`Stream`]s, serialized with only the fields of `level`.
+    /// See [`serialize`](crate::serialize).
+    #[cfg(feature = "serialize")]
+    #[inline]
+    pub fn serialize_with_level(&self, level: crate::serialize::OutputLevel) -> serde_json::Value {
+        let serializer = crate::serialize::VideoSerializer::new(&self.video_info, &self.streams, level);
+        serde_json::to_value(serializer).expect("serializing a video never fails")
+    }
+
+    /// Sets the [`ShutdownHandle`](crate::ShutdownHandle) of all [`Stream`]s of the video, so
+    /// downloads of any of them stop, once it's triggered (see [`shutdown`](crate::shutdown)).
+    #[cfg(feature = "download")]
+    pub fn with_shutdown(mut self, shutdown: crate::ShutdownHandle) -> Self {
+        for stream in &mut self.streams {
+            stream.shutdown = Some(shutdown.clone());
+        }
+        self
+    }
+
     /// Takes all [`Stream`]s of the video.
     #[inline]
     pub fn into_streams(self) -> Vec<Stream> {
@@ -137,6 +282,9 @@ impl Video {
     }
 
     /// Decomposes a `Video` into it's raw parts.
+    ///
+    /// This is the way of taking ownership of both the [`VideoInfo`] and the [`Stream`]s without
+    /// cloning them.
     #[inline]
     pub fn into_parts(self) -> (VideoInfo, Vec<Stream>) {
         (self.video_info, self.streams)
@@ -154,49 +302,280 @@ impl Video {
         self.video_info.player_response.video_details.video_id.as_borrowed()
     }
 
+    /// The absolute URL of the largest thumbnail of the video (see
+    /// [`VideoDetails::thumbnail_url_max`]). If YouTube didn't list any thumbnails, the URL of the
+    /// [`ThumbnailQuality::MaxRes`] thumbnail is constructed from the [`Id`].
+    pub fn thumbnail_url(&self) -> Url {
+        self.video_info.player_response.video_details
+            .thumbnail_url_max()
+            .unwrap_or_else(|| self.id().thumbnail_url(ThumbnailQuality::MaxRes))
+    }
+
     /// The title of the video.
     #[inline]
     pub fn title(&self) -> &str {
         self.video_info.player_response.video_details.title.as_str()
     }
 
+    /// The title of the video, without HTML entities, control characters and bidi overrides, so
+    /// it can be printed (see [`text::sanitize_for_display`](crate::text::sanitize_for_display)).
+    #[inline]
+    pub fn display_title(&self) -> String {
+        crate::text::sanitize_for_display(self.title())
+    }
+
     /// Whether or not the video is age restricted.
     #[inline]
     pub fn is_age_restricted(&self) -> bool {
         self.video_info.is_age_restricted
     }
 
+    /// The [`LiveStatus`] of the video.
+    ///
+    /// If it's [`LiveStatus::PostLiveProcessing`], YouTube may not serve the streams yet. Downloads
+    /// of such streams fail with [`Error::PostLiveProcessing`](crate::Error::PostLiveProcessing),
+    /// so you may want to wait and fetch the video again later.
+    #[inline]
+    pub fn live_status(&self) -> LiveStatus {
+        self.video_info.player_response.live_status()
+    }
+
+    /// The caption tracks of the video (see [`VideoInfo::caption_tracks`]).
+    #[inline]
+    pub fn caption_tracks(&self) -> &[crate::captions::CaptionTrack] {
+        self.video_info.caption_tracks()
+    }
+
+    /// The levels of the storyboards of the video (see [`VideoInfo::storyboards`]).
+    #[inline]
+    pub fn storyboards(&self) -> Vec<crate::video_info::player_response::storyboards::Storyboard> {
+        self.video_info.storyboards()
+    }
+
+    /// The elements of the end screen of the video (see [`VideoInfo::endscreen_items`]).
+    #[inline]
+    pub fn endscreen_items(&self) -> &[EndscreenElement] {
+        self.video_info.endscreen_items()
+    }
+
+    /// The info cards of the video (see [`VideoInfo::cards`]).
+    #[inline]
+    pub fn cards(&self) -> &[Card] {
+        self.video_info.cards()
+    }
+
+    /// The chapters of the video (see [`VideoInfo::chapters`]).
+    #[inline]
+    pub fn chapters(&self) -> Vec<crate::chapters::Chapter> {
+        self.video_info.chapters()
+    }
+
+    /// The heat map of the video (see [`VideoInfo::heat_map`]).
+    #[inline]
+    pub fn heat_map(&self) -> Option<Vec<crate::video_info::player_response::heat_map::HeatMarker>> {
+        self.video_info.heat_map()
+    }
+
+    /// The highest bitrate in bits per second, YouTube's player would choose for this video, if the
+    /// player response contained a hint (see
+    /// [`StreamSelectionConfig`](crate::video_info::player_response::player_config::StreamSelectionConfig)).
+    ///
+    /// Use [`SimpleDownloadOptions::respect_max_bitrate`](crate::SimpleDownloadOptions::respect_max_bitrate)
+    /// to skip streams above it.
+    #[inline]
+    pub fn suggested_max_bitrate(&self) -> Option<u64> {
+        self.video_info
+            .player_config()?
+            .stream_selection_config
+            .as_ref()?
+            .max_bitrate
+    }
+
+    /// The loudness normalization data of the video, and of its stream `stream`, as far as the
+    /// player response contains them (see
+    /// [`AudioConfig`](crate::video_info::player_response::player_config::AudioConfig)).
+    pub fn loudness(&self, stream: &Stream) -> Loudness {
+        let audio_config = self.video_info
+            .player_config()
+            .and_then(|config| config.audio_config.as_ref());
+        Loudness {
+            video_loudness_db: audio_config.and_then(|config| config.loudness_db),
+            perceptual_loudness_db: audio_config.and_then(|config| config.perceptual_loudness_db),
+            stream_loudness_db: stream.loudness_db,
+            per_format: audio_config
+                .and_then(|config| config.enable_per_format_loudness)
+                .unwrap_or(false),
+        }
+    }
+
+    /// The url of the DASH manifest of the video, if YouTube returned one.
+    #[inline]
+    pub fn dash_manifest_url(&self) -> Option<&url::Url> {
+        self.video_info.player_response.streaming_data
+            .as_ref()
+            .and_then(|streaming_data| streaming_data.dash_manifest_url.as_ref())
+    }
+
+    /// Whether the video should be downloaded from its DASH manifest (see
+    /// [`Video::download_from_dash_manifest`]), instead of its [`Stream`]s.
+    ///
+    /// This is the case for finished live streams, that have a DASH manifest, and either are
+    /// still being processed, or are longer than four hours, since their adaptive formats often
+    /// fail, or end after four hours.
+    pub fn prefers_dash_manifest(&self) -> bool {
+        let video_details = &self.video_info.player_response.video_details;
+        let long_live_vod = video_details.is_live_content
+            && !video_details.is_live
+            && video_details.length_seconds > ADAPTIVE_FORMAT_MAX_SECONDS;
+
+        self.dash_manifest_url().is_some() && (video_details.is_post_live_dvr || long_live_vod)
+    }
+
+    /// Requests and parses the DASH manifest of the video.
+    ///
+    /// ### Errors
+    /// - [`Error::UnexpectedResponse`](crate::Error::UnexpectedResponse), when the video has no
+    ///   DASH manifest, or it's invalid.
+    /// - When the request fails.
+    #[cfg(feature = "download")]
+    pub async fn dash_manifest(&self) -> crate::Result<DashManifest> {
+        let url = self.dash_manifest_url().ok_or_else(|| crate::Error::UnexpectedResponse(
+            "YouTube did not return a DASH manifest for the video".into()
+        ))?;
+        log::debug!("request the DASH manifest of {}", self.id());
+        let xml = self.segment_client().get_text(url).await?;
+        DashManifest::parse(&xml, url)
+    }
+
+    /// Downloads all segments of `representation`, which is part of the
+    /// [`dash_manifest`](Video::dash_manifest), and concatenates them into a single file at `path`.
+    /// Returns the number of bytes written.
+    ///
+    /// `on_progress` is called after each segment. Like the downloads of [`Stream`]s, the file is
+    /// written to `<path>.part`, until it's complete.
+    #[cfg(feature = "download")]
+    pub async fn download_from_dash_manifest<P: AsRef<std::path::Path>>(
+        &self,
+        representation: &Representation,
+        path: P,
+        on_progress: impl FnMut(SegmentProgress),
+    ) -> crate::Result<u64> {
+        crate::dash::download_representation(&self.segment_client(), representation, path.as_ref(), on_progress).await
+    }
+
+    /// The url of the HLS manifest of the video, if YouTube returned one. Live streams, and
+    /// recently finished ones, have one.
+    #[inline]
+    pub fn hls_manifest_url(&self) -> Option<&url::Url> {
+        self.video_info.player_response.streaming_data
+            .as_ref()
+            .and_then(|streaming_data| streaming_data.hls_manifest_url.as_ref())
+    }
+
+    /// Requests and parses the master playlist of the HLS manifest of the video, which lists its
+    /// variants.
+    ///
+    /// ### Errors
+    /// - [`Error::UnexpectedResponse`](crate::Error::UnexpectedResponse), when the video has no
+    ///   HLS manifest, or it's invalid.
+    /// - When the request fails.
+    #[cfg(feature = "download")]
+    pub async fn hls_playlist(&self) -> crate::Result<HlsMasterPlaylist> {
+        let url = self.hls_manifest_url().ok_or_else(|| crate::Error::UnexpectedResponse(
+            "YouTube did not return an HLS manifest for the video".into()
+        ))?;
+        log::debug!("request the HLS manifest of {}", self.id());
+        let m3u8 = self.segment_client().get_text(url).await?;
+        HlsMasterPlaylist::parse(&m3u8, url)
+    }
+
+    /// Downloads the segments of `variant`, which is part of the [`hls_playlist`](Video::hls_playlist),
+    /// and concatenates them into a single MPEG-TS file at `path`. Returns the number of bytes
+    /// written.
+    ///
+    /// The playlist of a running live stream is requested again for new segments, until the
+    /// stream ends, or `max_segments` segments were downloaded. Finished streams are downloaded
+    /// completely, unless `max_segments` is set.
+    ///
+    /// `on_progress` is called after each segment. Since the number of segments of a live stream
+    /// is unknown, [`SegmentProgress::total_segments`] only counts the segments known so far.
+    /// Like the downloads of [`Stream`]s, the file is written to `<path>.part`, until it's complete.
+    #[cfg(feature = "download")]
+    pub async fn download_from_hls_playlist<P: AsRef<std::path::Path>>(
+        &self,
+        variant: &HlsVariant,
+        path: P,
+        max_segments: Option<usize>,
+        on_progress: impl FnMut(SegmentProgress),
+    ) -> crate::Result<u64> {
+        crate::hls::download_variant(&self.segment_client(), &variant.url, path.as_ref(), max_segments, on_progress).await
+    }
+
+    /// The client, headers and [`TransferStats`](crate::TransferStats), the streams of the video
+    /// are requested with. Videos without streams, like live streams, that only have an HLS
+    /// manifest, use the ones of the [`VideoFetcher`](crate::VideoFetcher) they were fetched with.
+    #[cfg(feature = "download")]
+    fn segment_client(&self) -> SegmentClient {
+        match self.streams.first() {
+            Some(stream) => SegmentClient {
+                client: stream.client.clone(),
+                headers: stream.headers(),
+                transfer_stats: stream.transfer_stats.clone(),
+            },
+            None => SegmentClient {
+                client: self.video_info.client.clone().unwrap_or_default(),
+                headers: self.video_info.headers(),
+                transfer_stats: self.video_info.transfer_stats.clone(),
+            },
+        }
+    }
+
     /// The [`Stream`] with the best quality.
     /// This stream is guaranteed to contain both a video as well as an audio track. 
+    ///
+    /// Like all selection helpers, this compares streams by their [`Stream::rank`], and skips
+    /// streams, that are only available to YouTube Premium subscribers (see
+    /// [`Stream::is_premium_only`]). It's a shorthand for `StreamQuery::new().progressive().best(video)`
+    /// (see [`StreamQuery`]), which also skips streams of other than the original audio track.
     #[inline]
     pub fn best_quality(&self) -> Option<&Stream> {
-        self
-            .streams
-            .iter()
-            .filter(|stream| stream.includes_video_track && stream.includes_audio_track)
-            .max_by_key(|stream| stream.quality_label)
+        StreamQuery::new().progressive().best(self)
     }
 
-    /// The [`Stream`] with the worst quality.
+    /// The [`Stream`] with the worst quality, in the order of [`Video::best_quality`].
     /// This stream is guaranteed to contain both a video as well as an audio track.
     #[inline]
     pub fn worst_quality(&self) -> Option<&Stream> {
+        StreamQuery::new().progressive().worst(self)
+    }
+
+    /// The [`Stream`] with the best video quality.
+    /// This stream is guaranteed to contain only a video but no audio track.
+    ///
+    /// Streams, that are only available to YouTube Premium subscribers, are skipped. Use
+    /// [`best_video_including_premium`](Self::best_video_including_premium) to include them.
+    #[inline]
+    pub fn best_video(&self) -> Option<&Stream> {
         self
             .streams
             .iter()
-            .filter(|stream| stream.includes_video_track && stream.includes_audio_track)
-            .min_by_key(|stream| stream.quality_label)
+            .filter(|stream| stream.includes_video_track && !stream.includes_audio_track)
+            .filter(|stream| !stream.is_premium_only())
+            .max_by_key(|stream| stream.rank())
     }
 
-    /// The [`Stream`] with the best video quality.
+    /// The [`Stream`] with the best video quality, including streams, that are only available to
+    /// YouTube Premium subscribers (see [`Stream::is_premium_only`]).
     /// This stream is guaranteed to contain only a video but no audio track.
+    ///
+    /// Downloading a Premium stream fails without a Premium account.
     #[inline]
-    pub fn best_video(&self) -> Option<&Stream> {
+    pub fn best_video_including_premium(&self) -> Option<&Stream> {
         self
             .streams
             .iter()
             .filter(|stream| stream.includes_video_track && !stream.includes_audio_track)
-            .max_by_key(|stream| stream.width)
+            .max_by_key(|stream| (stream.rank(), stream.is_premium_only()))
     }
 
     /// The [`Stream`] with the worst video quality.
@@ -207,18 +586,72 @@ impl Video {
             .streams
             .iter()
             .filter(|stream| stream.includes_video_track && !stream.includes_audio_track)
-            .min_by_key(|stream| stream.width)
+            .filter(|stream| !stream.is_premium_only())
+            .min_by_key(|stream| stream.rank())
     }
 
     /// The [`Stream`] with the best audio quality.
-    /// This stream is guaranteed to contain only a audio but no video track.    
+    /// This stream is guaranteed to contain only a audio but no video track.
+    ///
+    /// If the video has multiple audio tracks (i.e. dubbed languages), only streams of the default
+    /// track are considered, as long as there are any.
     #[inline]
     pub fn best_audio(&self) -> Option<&Stream> {
         self
             .streams
             .iter()
             .filter(|stream| stream.includes_audio_track && !stream.includes_video_track)
-            .max_by_key(|stream| stream.bitrate)
+            .max_by_key(|stream| (
+                stream.audio_track.as_ref().is_none_or(|track| track.is_default),
+                stream.rank(),
+            ))
+    }
+
+    /// Downloads the [`best_audio`](Video::best_audio) stream to its
+    /// [`file_name`](Stream::file_name) in `dir`, i.e. `<video_id>.webm` for Opus audio, and
+    /// returns the path of the downloaded file.
+    ///
+    /// ### Errors
+    /// - [`Error::NoStreams`](crate::Error::NoStreams), when the video has no audio only streams.
+    /// - When the download fails.
+    #[cfg(feature = "download")]
+    pub async fn download_best_audio_to_dir<P: AsRef<std::path::Path>>(&self, dir: P) -> crate::Result<std::path::PathBuf> {
+        self
+            .best_audio()
+            .ok_or_else(|| crate::Error::NoStreams(self.no_streams_reason()))?
+            .download_to_dir(dir)
+            .await
+    }
+
+    /// All distinct [`AudioTrack`]s of the video, in the order they first appear in.
+    /// Videos with only one audio track usually don't include any [`AudioTrack`] information,
+    /// in which case the returned list is empty.
+    pub fn audio_tracks(&self) -> Vec<&AudioTrack> {
+        let mut tracks: Vec<&AudioTrack> = Vec::new();
+        for track in self.streams.iter().filter_map(|stream| stream.audio_track.as_ref()) {
+            if !tracks.iter().any(|t| t.id == track.id) {
+                tracks.push(track);
+            }
+        }
+        tracks
+    }
+
+    /// The [`Stream`] with the best audio quality in the language `lang_tag` (i.e. `en` or `de-DE`).
+    /// This stream is guaranteed to contain only a audio but no video track.
+    ///
+    /// See [`AudioTrack::is_language`] for how language tags are matched.
+    #[inline]
+    pub fn best_audio_for_language(&self, lang_tag: &str) -> Option<&Stream> {
+        self
+            .streams
+            .iter()
+            .filter(|stream| stream.includes_audio_track && !stream.includes_video_track)
+            .filter(|stream| {
+                stream.audio_track
+                    .as_ref()
+                    .is_some_and(|track| track.is_language(lang_tag))
+            })
+            .max_by_key(|stream| stream.rank())
     }
 
     /// The [`Stream`] with the worst audio quality.
@@ -229,6 +662,6 @@ impl Video {
             .streams
             .iter()
             .filter(|stream| stream.includes_audio_track && !stream.includes_video_track)
-            .min_by_key(|stream| stream.bitrate)
+            .min_by_key(|stream| stream.rank())
     }
 }
@@ -0,0 +1,173 @@
+//! Metadata of the watch page, that's neither part of the
+//! [`VideoDetails`](crate::VideoDetails), nor the `Microformat`.
+//!
+//! The category, the license, and the links above the title are only rendered on the watch page.
+//! They are part of the response of the `next` endpoint of the innertube API (see
+//! [`VideoFetcher::fetch_extended_metadata`](crate::VideoFetcher::fetch_extended_metadata)), and
+//! of the `ytInitialData` of the watch page (see [`VideoInfo::initial_data`](crate::VideoInfo::initial_data)).
+
+use serde_json::Value;
+use url::Url;
+
+/// The base URL, relative links of the watch page are resolved against.
+const YOUTUBE_URL: &str = "https://www.youtube.com/";
+
+/// The metadata of the watch page of a video.
+///
+/// All rows are optional, so every field has a sensible value, if YouTube did not render the
+/// corresponding row.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtendedMetadata {
+    /// The category of the video, i.e. `Music`, linking to the page of the category.
+    pub category: Option<MetadataLink>,
+    /// The license of the video. Videos without a license row use YouTube's standard license.
+    pub license: License,
+    /// Whether the video includes paid promotion, like product placements or sponsorships.
+    pub has_paid_promotion: bool,
+    /// The links above the title, i.e. hashtags, or the location of the video.
+    pub super_title_links: Vec<MetadataLink>,
+    /// All rows below the description, including the category and the license.
+    pub rows: Vec<MetadataRow>,
+}
+
+/// A text of the watch page, that may link somewhere.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetadataLink {
+    pub text: String,
+    pub url: Option<Url>,
+}
+
+/// A row below the description, i.e. `Category: Music`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetadataRow {
+    pub title: String,
+    pub contents: Vec<MetadataLink>,
+}
+
+/// The license, a video is published under.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum License {
+    /// The standard YouTube license.
+    #[default]
+    Standard,
+    /// A Creative Commons license, which allows reusing the video. Contains the text of the row,
+    /// i.e. `Creative Commons Attribution license (reuse allowed)`.
+    CreativeCommons(String),
+    /// A license, `rustube` does not know about.
+    Other(String),
+}
+
+impl ExtendedMetadata {
+    /// Parses the metadata from a response of the `next` endpoint, or the `ytInitialData` of a
+    /// watch page, which share the same renderers.
+    ///
+    /// Missing renderers and rows are skipped, so the result is empty, if the response does not
+    /// contain any metadata.
+    pub fn from_next_response(response: &Value) -> Self {
+        let contents = response["contents"]["twoColumnWatchNextResults"]["results"]["results"]["contents"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let renderer = |name: &str| contents
+            .iter()
+            .map(|content| &content[name])
+            .find(|renderer| !renderer.is_null());
+
+        let super_title_links = renderer("videoPrimaryInfoRenderer")
+            .and_then(|renderer| renderer["superTitleLink"]["runs"].as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(link)
+            .filter(|link| !link.text.trim().is_empty())
+            .collect();
+
+        let rows: Vec<_> = renderer("videoSecondaryInfoRenderer")
+            .and_then(|renderer| {
+                renderer["metadataRowContainer"]["metadataRowContainerRenderer"]["rows"].as_array()
+            })
+            .into_iter()
+            .flatten()
+            .filter_map(|row| {
+                let renderer = &row["metadataRowRenderer"];
+                Some(MetadataRow {
+                    title: text(&renderer["title"])?,
+                    contents: renderer["contents"]
+                        .as_array()?
+                        .iter()
+                        .filter_map(row_content)
+                        .collect(),
+                })
+            })
+            .collect();
+
+        let row = |title: &str| rows
+            .iter()
+            .find(|row| row.title.eq_ignore_ascii_case(title))
+            .and_then(|row| row.contents.first());
+        let category = row("Category").cloned();
+        let license = match row("License") {
+            Some(license) if license.text.contains("Creative Commons") => License::CreativeCommons(license.text.clone()),
+            Some(license) => License::Other(license.text.clone()),
+            None => License::Standard,
+        };
+
+        Self {
+            category,
+            license,
+            has_paid_promotion: contains_key(response, "paidContentOverlayRenderer"),
+            super_title_links,
+            rows,
+        }
+    }
+}
+
+/// The text of a `simpleText`, or `runs` object.
+fn text(value: &Value) -> Option<String> {
+    match value["simpleText"].as_str() {
+        Some(text) => Some(text.to_owned()),
+        None => Some(
+            value["runs"]
+                .as_array()?
+                .iter()
+                .filter_map(|run| run["text"].as_str())
+                .collect()
+        ),
+    }
+}
+
+/// A single run, that may have a `navigationEndpoint`.
+fn link(run: &Value) -> Option<MetadataLink> {
+    Some(MetadataLink {
+        text: run["text"].as_str()?.to_owned(),
+        url: url(&run["navigationEndpoint"]),
+    })
+}
+
+/// The content of a metadata row. Its text is the concatenation of all runs, and its url the
+/// first one, any of the runs links to.
+fn row_content(content: &Value) -> Option<MetadataLink> {
+    let url = content["runs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find_map(|run| url(&run["navigationEndpoint"]));
+    Some(MetadataLink { text: text(content)?, url })
+}
+
+/// The url of a `navigationEndpoint`, resolved against youtube.com.
+fn url(endpoint: &Value) -> Option<Url> {
+    let url = endpoint["commandMetadata"]["webCommandMetadata"]["url"]
+        .as_str()
+        .or_else(|| endpoint["urlEndpoint"]["url"].as_str())?;
+    Url::parse(YOUTUBE_URL).ok()?.join(url).ok()
+}
+
+/// Whether `key` appears anywhere in `value`.
+fn contains_key(value: &Value, key: &str) -> bool {
+    match value {
+        Value::Object(object) => object.contains_key(key) || object.values().any(|value| contains_key(value, key)),
+        Value::Array(array) => array.iter().any(|value| contains_key(value, key)),
+        _ => false,
+    }
+}
@@ -1,20 +1,218 @@
 //! All the types, that hold video information.
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
-use serde_with::{json::JsonString, serde_as};
+use serde_with::{json::JsonString, PickFirst, serde_as};
 
 use player_response::PlayerResponse;
+use player_response::annotations::{Card, EndscreenElement};
+use player_response::heat_map::HeatMarker;
+use player_response::playability_status::PlayabilityStatus;
+use player_response::player_config::PlayerConfig;
+use player_response::storyboards::Storyboard;
+
+use crate::captions::CaptionTrack;
+use crate::chapters::Chapter;
+use crate::fetcher::UserAgent;
+use crate::metrics::{Metrics, MetricsReport};
+use crate::transfer::TransferStats;
 
+pub mod extended_metadata;
 pub mod player_response;
 
+/// All information about a video, most of which is contained in the [`PlayerResponse`].
+///
+/// Cloning a `VideoInfo` is not cheap: it clones the whole [`PlayerResponse`], including all raw
+/// formats, and the `ytInitialData`, if it was kept. Only the
+/// [`VideoDetails`](player_response::video_details::VideoDetails) are shared. To take ownership of
+/// the `VideoInfo` of a [`Video`](crate::Video), use [`Video::into_parts`](crate::Video::into_parts),
+/// or [`VideoDescrambler::into_video_info`](crate::VideoDescrambler::into_video_info).
 #[serde_as]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct VideoInfo {
-    #[serde_as(deserialize_as = "JsonString")]
+    /// Deserialized from a JSON string, like in the `get_video_info` response, or from an
+    /// object, like it's serialized.
+    #[serde_as(as = "PickFirst<(_, JsonString)>")]
     pub player_response: PlayerResponse,
     #[serde(rename = "adaptive_fmts")]
     pub adaptive_fmts_raw: Option<String>,
 
     #[serde(skip)]
     pub is_age_restricted: bool,
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore")]
+    pub(crate) metrics: Option<Arc<Metrics>>,
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore")]
+    pub(crate) transfer_stats: Option<Arc<TransferStats>>,
+    #[serde(skip)]
+    #[derivative(PartialEq = "ignore")]
+    pub(crate) user_agent: Option<UserAgent>,
+    #[serde(skip)]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub(crate) extra_headers: reqwest::header::HeaderMap,
+    /// The [`Client`](reqwest::Client) of the [`VideoFetcher`](crate::VideoFetcher), the video
+    /// was fetched with.
+    #[serde(skip)]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub(crate) client: Option<reqwest::Client>,
+    #[serde(skip)]
+    pub(crate) initial_data: Option<serde_json::Value>,
+}
+
+impl VideoInfo {
+    /// Creates a [`VideoInfo`] from a [`PlayerResponse`].
+    #[inline]
+    pub fn from_player_response(player_response: PlayerResponse, is_age_restricted: bool) -> Self {
+        Self {
+            player_response,
+            adaptive_fmts_raw: None,
+            is_age_restricted,
+            metrics: None,
+            transfer_stats: None,
+            user_agent: None,
+            extra_headers: reqwest::header::HeaderMap::new(),
+            client: None,
+            initial_data: None,
+        }
+    }
+
+    /// The headers of all requests belonging to the video, which contain the user agent it was
+    /// fetched with, if there was a [`UserAgentPolicy`](crate::UserAgentPolicy), and the headers
+    /// set with [`VideoFetcher::with_headers`](crate::VideoFetcher::with_headers).
+    #[inline]
+    #[cfg(feature = "download")]
+    pub(crate) fn headers(&self) -> reqwest::header::HeaderMap {
+        let mut headers = self.user_agent
+            .as_ref()
+            .map(UserAgent::headers)
+            .unwrap_or_default();
+        headers.extend(self.extra_headers.clone());
+        headers
+    }
+
+    /// The [`PlayabilityStatus`] of the video, which tells whether, and if not why not, the video
+    /// can be watched.
+    #[inline]
+    pub fn playability(&self) -> &PlayabilityStatus {
+        &self.player_response.playability_status
+    }
+
+    /// Whether the video can be watched, i.e. is not private, deleted, or an offline live stream.
+    #[inline]
+    pub fn is_playable(&self) -> bool {
+        matches!(self.player_response.playability_status, PlayabilityStatus::Ok { .. })
+    }
+
+    /// The duration of the video, or [`Duration::ZERO`], if it's unknown, i.e. for live streams.
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.player_response.video_details.length_seconds)
+    }
+
+    /// Whether the video is tagged with `keyword`, ignoring the case.
+    #[inline]
+    pub fn has_keyword(&self, keyword: &str) -> bool {
+        let keyword = keyword.to_lowercase();
+        self.player_response.video_details.key_words
+            .iter()
+            .any(|key_word| key_word.to_lowercase() == keyword)
+    }
+
+    /// The caption tracks of the video. Most videos either have none, or only automatically
+    /// generated ones.
+    #[inline]
+    pub fn caption_tracks(&self) -> &[CaptionTrack] {
+        self.player_response.captions
+            .as_ref()
+            .map(|captions| captions.player_captions_tracklist_renderer.caption_tracks.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The levels of the storyboards of the video, which contain its preview thumbnails, ordered
+    /// from the smallest to the largest thumbnails. Live streams have none.
+    pub fn storyboards(&self) -> Vec<Storyboard> {
+        self.player_response.storyboards
+            .as_ref()
+            .and_then(|storyboards| storyboards.player_storyboard_spec_renderer.as_ref())
+            .map(|renderer| Storyboard::from_spec(&renderer.spec))
+            .unwrap_or_default()
+    }
+
+    /// The elements of the end screen, i.e. suggested videos and playlists, shown in the last
+    /// seconds of the video.
+    #[inline]
+    pub fn endscreen_items(&self) -> &[EndscreenElement] {
+        self.player_response.endscreen
+            .as_ref()
+            .map(|endscreen| endscreen.endscreen_renderer.elements.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The info cards of the video, which link to other videos and playlists.
+    #[inline]
+    pub fn cards(&self) -> &[Card] {
+        self.player_response.cards
+            .as_ref()
+            .map(|cards| cards.card_collection_renderer.cards.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The chapters of the video, from the chapter markers of the [`initial_data`](Self::initial_data),
+    /// if it was kept, and otherwise from the timestamps in the description.
+    pub fn chapters(&self) -> Vec<Chapter> {
+        let chapters = self.initial_data
+            .as_ref()
+            .map(Chapter::from_initial_data)
+            .unwrap_or_default();
+        match chapters.is_empty() {
+            true => Chapter::from_description(&self.player_response.video_details.short_description),
+            false => chapters,
+        }
+    }
+
+    /// The heat map of the video, which shows its most replayed parts, from the
+    /// [`initial_data`](Self::initial_data). It's `None`, if the `ytInitialData` was not kept,
+    /// or the video has no heat map.
+    #[inline]
+    pub fn heat_map(&self) -> Option<Vec<HeatMarker>> {
+        self.initial_data
+            .as_ref()
+            .and_then(HeatMarker::from_initial_data)
+    }
+
+    /// The hints of YouTube's player for choosing streams, if the player response contained them.
+    #[inline]
+    pub fn player_config(&self) -> Option<&PlayerConfig> {
+        self.player_response.player_config.as_ref()
+    }
+
+    /// The user agent the video was fetched with, and its streams are downloaded with, if the
+    /// [`VideoFetcher`](crate::VideoFetcher) had a [`UserAgentPolicy`](crate::UserAgentPolicy).
+    #[inline]
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_ref().map(UserAgent::as_str)
+    }
+
+    /// The raw `ytInitialData` of the watch page, if the [`VideoFetcher`](crate::VideoFetcher)
+    /// was told to keep it with [`with_initial_data`](crate::VideoFetcher::with_initial_data),
+    /// and it could be extracted.
+    ///
+    /// It contains the renderers around the player, like the like button, the description, and
+    /// the comments section, which rustube does not model.
+    #[inline]
+    pub fn initial_data(&self) -> Option<&serde_json::Value> {
+        self.initial_data.as_ref()
+    }
+
+    /// A snapshot of the [`Metrics`] recorded while fetching the video, and afterwards, while
+    /// descrambling and downloading it, if a collector was set with
+    /// [`VideoFetcher::with_metrics`](crate::VideoFetcher::with_metrics).
+    #[inline]
+    pub fn metrics(&self) -> Option<MetricsReport> {
+        self.metrics.as_deref().map(Metrics::report)
+    }
 }
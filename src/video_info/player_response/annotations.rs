@@ -0,0 +1,355 @@
+//! The end screen and the info cards of a video, which link to other videos, playlists, channels
+//! and websites.
+//!
+//! Both are part of the [`PlayerResponse`](super::PlayerResponse) (`endscreen` and `cards`). Use
+//! [`Video::endscreen_items`](crate::Video::endscreen_items) and [`Video::cards`](crate::Video::cards)
+//! to access them.
+//!
+//! YouTube adds new kinds of elements from time to time. Elements, that can't be deserialized,
+//! are skipped, so they never affect the rest of the player response.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeMap;
+use serde_with::{json::JsonString, PickFirst, serde_as};
+
+use crate::IdBuf;
+use crate::video_info::player_response::playability_status::Reason;
+use crate::video_info::player_response::video_details::Thumbnails;
+
+/// The `endscreen` of a [`PlayerResponse`](super::PlayerResponse).
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Endscreen {
+    pub endscreen_renderer: EndscreenRenderer,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EndscreenRenderer {
+    /// The elements of the end screen, without the ones of unknown kinds.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_endscreen_elements",
+        serialize_with = "serialize_endscreen_elements"
+    )]
+    pub elements: Vec<EndscreenElement>,
+    /// When the end screen starts, in milliseconds since the start of the video.
+    #[serde(default)]
+    #[serde_as(as = "Option<PickFirst<(JsonString, _)>>")]
+    pub start_ms: Option<u64>,
+}
+
+/// An element of the end screen (`endscreenElementRenderer`), i.e. a suggested video, or a
+/// subscribe button.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EndscreenElement {
+    pub style: EndscreenElementStyle,
+    /// The thumbnails of the linked video or playlist, or the avatar of the linked channel.
+    #[serde(default)]
+    pub image: Thumbnails,
+    pub title: Reason,
+    /// Additional information, i.e. the view count of a video.
+    pub metadata: Option<Reason>,
+    pub endpoint: Option<AnnotationEndpoint>,
+    /// When the element is shown, in milliseconds since the start of the video.
+    #[serde_as(as = "PickFirst<(JsonString, _)>")]
+    pub start_ms: u64,
+    /// When the element is hidden, in milliseconds since the start of the video.
+    #[serde_as(as = "PickFirst<(JsonString, _)>")]
+    pub end_ms: u64,
+    /// The position and size of the element, relative to the size of the player.
+    pub left: Option<f64>,
+    pub top: Option<f64>,
+    pub width: Option<f64>,
+    pub aspect_ratio: Option<f64>,
+    pub id: Option<String>,
+}
+
+impl EndscreenElement {
+    /// The title of the element, including the text of all its runs.
+    #[inline]
+    pub fn title(&self) -> String {
+        self.title.to_text()
+    }
+
+    /// The id of the linked video, or of the first video of the linked playlist.
+    #[inline]
+    pub fn video_id(&self) -> Option<&IdBuf> {
+        self.endpoint.as_ref()?.video_id()
+    }
+
+    /// The id of the linked playlist.
+    #[inline]
+    pub fn playlist_id(&self) -> Option<&str> {
+        self.endpoint.as_ref()?.playlist_id()
+    }
+
+    /// The id of the linked channel.
+    #[inline]
+    pub fn channel_id(&self) -> Option<&str> {
+        self.endpoint.as_ref()?.channel_id()
+    }
+
+    /// The url of the linked website. YouTube links them through its redirect page.
+    #[inline]
+    pub fn url(&self) -> Option<&str> {
+        self.endpoint.as_ref()?.url()
+    }
+}
+
+/// What an [`EndscreenElement`] links to.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EndscreenElementStyle {
+    Video,
+    Playlist,
+    Channel,
+    /// A subscribe button for the channel of the video.
+    Subscribe,
+    /// An approved external website.
+    Website,
+}
+
+/// The `cards` of a [`PlayerResponse`](super::PlayerResponse).
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Cards {
+    pub card_collection_renderer: CardCollectionRenderer,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardCollectionRenderer {
+    /// The cards, without the ones of unknown kinds.
+    #[serde(default, deserialize_with = "deserialize_cards", serialize_with = "serialize_cards")]
+    pub cards: Vec<Card>,
+    pub header_text: Option<Reason>,
+}
+
+/// An info card (`cardRenderer`), which is teased in the top right corner of the player, while
+/// one of its [`cue_ranges`](Card::cue_ranges) is active.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Card {
+    pub content: CardContent,
+    #[serde(default)]
+    pub cue_ranges: Vec<CueRange>,
+    pub card_id: Option<String>,
+}
+
+impl Card {
+    /// The title of the linked video or playlist.
+    #[inline]
+    pub fn title(&self) -> String {
+        match self.content {
+            CardContent::VideoInfoCardContentRenderer(ref video) => video.video_title.to_text(),
+            CardContent::PlaylistInfoCardContentRenderer(ref playlist) => playlist.playlist_title.to_text(),
+        }
+    }
+
+    /// The thumbnails of the linked video or playlist.
+    #[inline]
+    pub fn thumbnails(&self) -> &Thumbnails {
+        match self.content {
+            CardContent::VideoInfoCardContentRenderer(ref video) => &video.video_thumbnail,
+            CardContent::PlaylistInfoCardContentRenderer(ref playlist) => &playlist.playlist_thumbnail,
+        }
+    }
+
+    /// The id of the linked video, or of the first video of the linked playlist.
+    #[inline]
+    pub fn video_id(&self) -> Option<&IdBuf> {
+        self.action().video_id()
+    }
+
+    /// The id of the linked playlist.
+    #[inline]
+    pub fn playlist_id(&self) -> Option<&str> {
+        self.action().playlist_id()
+    }
+
+    /// When the card is first shown, in milliseconds since the start of the video.
+    #[inline]
+    pub fn start_ms(&self) -> Option<u64> {
+        self.cue_ranges.iter().map(|cue_range| cue_range.start_card_active_ms).min()
+    }
+
+    /// When the card is last hidden, in milliseconds since the start of the video.
+    #[inline]
+    pub fn end_ms(&self) -> Option<u64> {
+        self.cue_ranges.iter().map(|cue_range| cue_range.end_card_active_ms).max()
+    }
+
+    #[inline]
+    fn action(&self) -> &AnnotationEndpoint {
+        match self.content {
+            CardContent::VideoInfoCardContentRenderer(ref video) => &video.action,
+            CardContent::PlaylistInfoCardContentRenderer(ref playlist) => &playlist.action,
+        }
+    }
+}
+
+/// The content of a [`Card`]. Cards of other kinds, i.e. polls, are skipped.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CardContent {
+    VideoInfoCardContentRenderer(VideoCardContent),
+    PlaylistInfoCardContentRenderer(PlaylistCardContent),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoCardContent {
+    #[serde(default)]
+    pub video_thumbnail: Thumbnails,
+    pub video_title: Reason,
+    pub channel_name: Option<Reason>,
+    pub length_string: Option<Reason>,
+    pub view_count_text: Option<Reason>,
+    pub action: AnnotationEndpoint,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistCardContent {
+    #[serde(default)]
+    pub playlist_thumbnail: Thumbnails,
+    pub playlist_title: Reason,
+    pub channel_name: Option<Reason>,
+    pub playlist_video_count: Option<Reason>,
+    pub action: AnnotationEndpoint,
+}
+
+/// A time range, in which a [`Card`] is active, in milliseconds since the start of the video.
+#[serde_as]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct CueRange {
+    #[serde_as(as = "PickFirst<(JsonString, _)>")]
+    pub start_card_active_ms: u64,
+    #[serde_as(as = "PickFirst<(JsonString, _)>")]
+    pub end_card_active_ms: u64,
+    #[serde(default)]
+    #[serde_as(as = "Option<PickFirst<(JsonString, _)>>")]
+    pub teaser_duration_ms: Option<u64>,
+}
+
+/// Where an [`EndscreenElement`] or a [`Card`] navigates to. At most one of the endpoints is set.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationEndpoint {
+    pub watch_endpoint: Option<WatchEndpoint>,
+    pub browse_endpoint: Option<BrowseEndpoint>,
+    pub url_endpoint: Option<UrlEndpoint>,
+}
+
+impl AnnotationEndpoint {
+    #[inline]
+    pub fn video_id(&self) -> Option<&IdBuf> {
+        self.watch_endpoint.as_ref()?.video_id.as_ref()
+    }
+
+    #[inline]
+    pub fn playlist_id(&self) -> Option<&str> {
+        self.watch_endpoint.as_ref()?.playlist_id.as_deref()
+    }
+
+    #[inline]
+    pub fn channel_id(&self) -> Option<&str> {
+        Some(self.browse_endpoint.as_ref()?.browse_id.as_str())
+    }
+
+    #[inline]
+    pub fn url(&self) -> Option<&str> {
+        Some(self.url_endpoint.as_ref()?.url.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchEndpoint {
+    pub video_id: Option<IdBuf>,
+    pub playlist_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowseEndpoint {
+    pub browse_id: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlEndpoint {
+    pub url: String,
+}
+
+const ENDSCREEN_ELEMENT_RENDERER: &str = "endscreenElementRenderer";
+const CARD_RENDERER: &str = "cardRenderer";
+
+fn deserialize_endscreen_elements<'de, D>(deserializer: D) -> Result<Vec<EndscreenElement>, D::Error>
+    where D: Deserializer<'de> {
+    deserialize_renderers(deserializer, ENDSCREEN_ELEMENT_RENDERER)
+}
+
+fn serialize_endscreen_elements<S>(elements: &[EndscreenElement], serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+    serialize_renderers(elements, serializer, ENDSCREEN_ELEMENT_RENDERER)
+}
+
+fn deserialize_cards<'de, D>(deserializer: D) -> Result<Vec<Card>, D::Error>
+    where D: Deserializer<'de> {
+    deserialize_renderers(deserializer, CARD_RENDERER)
+}
+
+fn serialize_cards<S>(cards: &[Card], serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+    serialize_renderers(cards, serializer, CARD_RENDERER)
+}
+
+/// Deserializes a list of renderers, that are wrapped in an object with the key `renderer`, and
+/// skips all other renderers, and the ones, that can't be deserialized.
+fn deserialize_renderers<'de, D, T>(deserializer: D, renderer: &str) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: DeserializeOwned {
+    let values = Option::<Vec<serde_json::Value>>::deserialize(deserializer)?.unwrap_or_default();
+
+    Ok(values
+        .into_iter()
+        .filter_map(|mut value| match value.get_mut(renderer) {
+            Some(value) => serde_json::from_value(value.take())
+                .map_err(|err| log::debug!("skipping a {} that can't be deserialized: {}", renderer, err))
+                .ok(),
+            None => {
+                log::debug!("skipping an unknown renderer: {}", value);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Serializes `values` wrapped in an object with the key `renderer`, like YouTube does.
+fn serialize_renderers<S, T>(values: &[T], serializer: S, renderer: &str) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize {
+    struct Wrapped<'a, T> {
+        renderer: &'a str,
+        value: &'a T,
+    }
+
+    impl<T: Serialize> Serialize for Wrapped<'_, T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_entry(self.renderer, self.value)?;
+            map.end()
+        }
+    }
+
+    serializer.collect_seq(values.iter().map(|value| Wrapped { renderer, value }))
+}
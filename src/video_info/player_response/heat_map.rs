@@ -0,0 +1,100 @@
+//! The heat map of a video, which shows its most replayed parts above the progress bar.
+//!
+//! The heat map is not part of the [`PlayerResponse`](super::PlayerResponse), but of the
+//! `ytInitialData` of the watch page, which is only kept, if the
+//! [`VideoFetcher`](crate::VideoFetcher) was told to with
+//! [`with_initial_data`](crate::VideoFetcher::with_initial_data). YouTube used to put it into the
+//! markers of the player bar, and now sends it as an entity in the `frameworkUpdates`. Both are
+//! understood.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::{json::JsonString, PickFirst, serde_as};
+
+/// A segment of the heat map (`heatMarkerRenderer`).
+#[serde_as]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatMarker {
+    /// The start of the segment, in milliseconds since the start of the video.
+    #[serde(rename = "timeRangeStartMillis")]
+    #[serde_as(as = "PickFirst<(JsonString, _)>")]
+    pub time_range_start_ms: u64,
+    #[serde(rename = "markerDurationMillis")]
+    #[serde_as(as = "PickFirst<(JsonString, _)>")]
+    pub marker_duration_ms: u64,
+    /// How often the segment is replayed, relative to the most replayed one, from 0 to 1.
+    #[serde_as(as = "PickFirst<(JsonString, _)>")]
+    pub heat_marker_intensity_score_normalized: f64,
+}
+
+/// A marker of a `macroMarkersListEntity` in the `frameworkUpdates`.
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Marker {
+    #[serde_as(as = "PickFirst<(JsonString, _)>")]
+    start_millis: u64,
+    #[serde_as(as = "PickFirst<(JsonString, _)>")]
+    duration_millis: u64,
+    #[serde_as(as = "PickFirst<(JsonString, _)>")]
+    intensity_score_normalized: f64,
+}
+
+impl From<Marker> for HeatMarker {
+    #[inline]
+    fn from(marker: Marker) -> Self {
+        Self {
+            time_range_start_ms: marker.start_millis,
+            marker_duration_ms: marker.duration_millis,
+            heat_marker_intensity_score_normalized: marker.intensity_score_normalized,
+        }
+    }
+}
+
+impl HeatMarker {
+    /// The heat map in the `ytInitialData` of a watch page (see
+    /// [`VideoInfo::initial_data`](crate::VideoInfo::initial_data)), in the order of the video.
+    ///
+    /// Returns `None`, if there is no heat map, i.e. since the video has too few views. Markers,
+    /// that can't be deserialized, are skipped.
+    pub fn from_initial_data(initial_data: &Value) -> Option<Vec<Self>> {
+        let mut markers = Self::from_framework_updates(initial_data)
+            .or_else(|| Self::from_player_bar(initial_data))?;
+        markers.sort_by_key(|marker| marker.time_range_start_ms);
+        Some(markers)
+    }
+
+    fn from_framework_updates(initial_data: &Value) -> Option<Vec<Self>> {
+        let markers = initial_data["frameworkUpdates"]["entityBatchUpdate"]["mutations"]
+            .as_array()?
+            .iter()
+            .map(|mutation| &mutation["payload"]["macroMarkersListEntity"]["markersList"])
+            .find(|markers_list| markers_list["markerType"] == "MARKER_TYPE_HEATMAP")?["markers"]
+            .as_array()?;
+
+        Some(
+            markers
+                .iter()
+                .filter_map(|marker| Marker::deserialize(marker).ok())
+                .map(Self::from)
+                .collect()
+        )
+    }
+
+    fn from_player_bar(initial_data: &Value) -> Option<Vec<Self>> {
+        let markers = initial_data["playerOverlays"]["playerOverlayRenderer"]["decoratedPlayerBarRenderer"]
+            ["decoratedPlayerBarRenderer"]["playerBar"]["multiMarkersPlayerBarRenderer"]["markersMap"]
+            .as_array()?
+            .iter()
+            .find(|marker| marker["key"] == "HEATSEEKER")?["value"]["heatmap"]["heatmapRenderer"]["heatMarkers"]
+            .as_array()?;
+
+        Some(
+            markers
+                .iter()
+                .filter_map(|marker| Self::deserialize(&marker["heatMarkerRenderer"]).ok())
+                .collect()
+        )
+    }
+}
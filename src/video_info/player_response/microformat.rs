@@ -2,7 +2,8 @@ use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::{json::JsonString, serde_as};
 
-use crate::video_info::player_response::video_details::Thumbnail;
+use crate::CountryCode;
+use crate::video_info::player_response::video_details::Thumbnails;
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
@@ -14,8 +15,7 @@ pub struct Microformat {
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct PlayerMicroformatRenderer {
-    // TODO: use something specific for a ISO 3166-1 alpha-2 identifier
-    pub available_countries: Vec<String>,
+    pub available_countries: Vec<CountryCode>,
     // TODO: maybe also an enum
     pub category: String,
     pub description: SimpleText,
@@ -25,15 +25,14 @@ pub struct PlayerMicroformatRenderer {
     pub has_ypc_metadate: bool,
     pub is_unlisted: bool,
     pub length_seconds: String,
+    #[serde(rename = "liveBroadcastDetails")]
     pub live_brodcast_details: Option<LiveBroadcastDetails>,
     pub owner_channel_name: String,
     pub owner_profile_url: String,
     #[serde(with = "crate::serde_impl::date_ymd")]
     pub publish_date: NaiveDate,
-    #[serde(rename = "thumbnail")]
-    #[serde(serialize_with = "Thumbnail::serialize_vec")]
-    #[serde(deserialize_with = "Thumbnail::deserialize_vec")]
-    pub thumbnails: Vec<Thumbnail>,
+    #[serde(rename = "thumbnail", default)]
+    pub thumbnails: Thumbnails,
     pub title: SimpleText,
     #[serde(with = "crate::serde_impl::date_ymd")]
     pub upload_date: NaiveDate,
@@ -60,6 +59,8 @@ pub struct SimpleText {
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct LiveBroadcastDetails {
-    is_live_now: bool,
-    start_simestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub is_live_now: bool,
+    pub start_timestamp: Option<DateTime<Utc>>,
+    pub end_timestamp: Option<DateTime<Utc>>,
 }
@@ -2,15 +2,24 @@ use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
+use crate::captions::Captions;
+
+use annotations::{Cards, Endscreen};
 #[cfg(feature = "microformat")]
 use microformat::Microformat;
 use playability_status::PlayabilityStatus;
+use player_config::PlayerConfig;
+use storyboards::Storyboards;
 use streaming_data::StreamingData;
-use video_details::VideoDetails;
+use video_details::{LiveStatus, VideoDetails};
 
+pub mod annotations;
+pub mod heat_map;
 pub mod video_details;
 pub mod streaming_data;
+pub mod storyboards;
 pub mod playability_status;
+pub mod player_config;
 #[cfg(feature = "microformat")]
 pub mod microformat;
 
@@ -22,22 +31,54 @@ pub struct PlayerResponse {
     // todo:
     // attestation: _,
     // auxiliaryUi: _,
-    // captions: _,
-    // cards: _,
-    // endscreen: _,
+    pub captions: Option<Captions>,
+    pub cards: Option<Cards>,
+    pub endscreen: Option<Endscreen>,
     // messages: _,
     #[cfg(feature = "microformat")]
     pub microformat: Option<Microformat>,
     pub playability_status: PlayabilityStatus,
     // playbackTracking: _,
-    // playerConfig: _,
+    pub player_config: Option<PlayerConfig>,
     // response_context: ResponseContext,
-    // storyboards: _,
+    pub storyboards: Option<Storyboards>,
     pub streaming_data: Option<StreamingData>,
     pub video_details: Arc<VideoDetails>,
     pub tracking_params: String,
 }
 
+impl PlayerResponse {
+    /// The [`LiveStatus`] of the video.
+    ///
+    /// Derived from the flags in the [`VideoDetails`], and, if the `microformat` feature is
+    /// enabled, from the live broadcast details of the [`Microformat`]. The latter are the only
+    /// way to tell, that a video was premiered.
+    pub fn live_status(&self) -> LiveStatus {
+        let status = self.video_details.live_status();
+
+        #[cfg(feature = "microformat")]
+        if status == LiveStatus::NotLive {
+            let details = self.microformat
+                .as_ref()
+                .and_then(|microformat| microformat.player_microformat_renderer.live_brodcast_details.as_ref());
+
+            if let Some(details) = details {
+                return if details.is_live_now {
+                    LiveStatus::Live
+                } else if details.end_timestamp.is_some() {
+                    LiveStatus::WasLive
+                } else if details.start_timestamp.is_some_and(|start| start > std::time::SystemTime::now().into()) {
+                    LiveStatus::UpcomingPremiere
+                } else {
+                    status
+                };
+            }
+        }
+
+        status
+    }
+}
+
 #[derive(
 Clone, Default, Debug, derive_more::Display,
 Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash
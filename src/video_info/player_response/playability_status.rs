@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use serde_with::{json::JsonString, serde_as};
 
 use crate::IdBuf;
-use crate::video_info::player_response::video_details::Thumbnail;
+use crate::video_info::player_response::PlayerResponse;
+use crate::video_info::player_response::video_details::Thumbnails;
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(tag = "status", rename_all = "SCREAMING_SNAKE_CASE")]
@@ -31,6 +32,7 @@ pub enum PlayabilityStatus {
     LoginRequired {
         #[serde(default)]
         messages: Vec<String>,
+        reason: Option<String>,
         error_screen: Option<ErrorScreen>,
         desktop_legacy_age_gate_reason: Option<i64>,
         context_params: String,
@@ -51,6 +53,122 @@ pub enum PlayabilityStatus {
     },
 }
 
+impl PlayabilityStatus {
+    /// The [`PlayabilityKind`] of the status.
+    ///
+    /// A status, that asks to sign in to confirm you're not a bot, is a
+    /// [`PlayabilityKind::BotCheckRequired`], independent of its actual `status`.
+    pub fn kind(&self) -> PlayabilityKind {
+        if self.is_bot_check() {
+            return PlayabilityKind::BotCheckRequired;
+        }
+
+        match self {
+            Self::Ok { .. } => PlayabilityKind::Ok,
+            Self::Unplayable { .. } => PlayabilityKind::Unplayable,
+            Self::LoginRequired { .. } => PlayabilityKind::LoginRequired,
+            Self::LiveStreamOffline { .. } => PlayabilityKind::LiveStreamOffline,
+            Self::Error { .. } => PlayabilityKind::Error,
+        }
+    }
+
+    /// The reason, why the video is not playable, as shown to the user.
+    pub fn reason(&self) -> Option<String> {
+        let (reason, error_screen) = match self {
+            Self::Ok { .. } => return None,
+            Self::Unplayable { reason, error_screen, .. } => (Some(reason), error_screen),
+            Self::LoginRequired { reason, error_screen, .. } => (reason.as_ref(), error_screen),
+            Self::LiveStreamOffline { reason, .. } => (Some(reason), &None),
+            Self::Error { reason, error_screen, .. } => (Some(reason), error_screen),
+        };
+
+        reason
+            .cloned()
+            .or_else(|| error_screen
+                .as_ref()
+                .and_then(|error_screen| error_screen.player_error_message_renderer.as_ref())
+                .map(|renderer| renderer.reason.to_text())
+            )
+    }
+
+    /// Whether YouTube asks to sign in to confirm you're not a bot. This usually happens for
+    /// requests from datacenter IPs, or after too many requests.
+    pub fn is_bot_check(&self) -> bool {
+        let (reason, error_screen) = match self {
+            Self::LoginRequired { reason, error_screen, .. } => (reason.as_deref(), error_screen),
+            Self::Error { reason, error_screen, .. } => (Some(reason.as_str()), error_screen),
+            _ => return false,
+        };
+
+        reason.is_some_and(is_bot_check_reason) || error_screen
+            .as_ref()
+            .and_then(|error_screen| error_screen.player_error_message_renderer.as_ref())
+            .is_some_and(|renderer| is_bot_check_reason(&renderer.reason.to_text()))
+    }
+
+    /// The free trailer, YouTube offers instead of the video, i.e. for rentals, and movies, that
+    /// have to be bought. See [`PlayabilityStatus::trailer`].
+    pub fn trailer_renderer(&self) -> Option<&YpcTrailerRenderer> {
+        let error_screen = match self {
+            Self::Unplayable { error_screen, .. }
+            | Self::LoginRequired { error_screen, .. }
+            | Self::Error { error_screen, .. } => error_screen.as_ref()?,
+            _ => return None,
+        };
+
+        error_screen.ypc_trailer_renderer.as_ref()
+    }
+
+    /// The [`PlayerResponse`] of the free trailer, YouTube offers instead of the video, i.e. for
+    /// rentals.
+    ///
+    /// Returns `None`, if there's no trailer, or YouTube only included the id of the trailer
+    /// (see [`YpcTrailerRenderer::trailer_video_id`]). To download the trailer, use
+    /// [`VideoFetcher::fetch_trailer`](crate::VideoFetcher::fetch_trailer).
+    pub fn trailer(&self) -> Option<PlayerResponse> {
+        self.trailer_renderer()?.player_response()
+    }
+}
+
+impl core::fmt::Display for PlayabilityStatus {
+    /// The status, as YouTube names it, followed by the reason, if there is one, i.e.
+    /// `LOGIN_REQUIRED: Sign in to confirm your age`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Ok { .. } => "OK",
+            Self::Unplayable { .. } => "UNPLAYABLE",
+            Self::LoginRequired { .. } => "LOGIN_REQUIRED",
+            Self::LiveStreamOffline { .. } => "LIVE_STREAM_OFFLINE",
+            Self::Error { .. } => "ERROR",
+        })?;
+
+        match self.reason() {
+            Some(reason) => write!(f, ": {}", reason),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether a playability reason asks to confirm you're not a bot. YouTube uses both, straight and
+/// typographic apostrophes.
+fn is_bot_check_reason(reason: &str) -> bool {
+    let reason = reason.to_lowercase().replace('\u{2019}', "'");
+    reason.contains("confirm you're not a bot") || reason.contains("confirm that you're not a bot")
+}
+
+/// The kind of a [`PlayabilityStatus`], without any of its data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PlayabilityKind {
+    Ok,
+    Unplayable,
+    LoginRequired,
+    LiveStreamOffline,
+    Error,
+    /// YouTube asks to sign in to confirm you're not a bot.
+    BotCheckRequired,
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct MiniPlayer {
@@ -75,7 +193,50 @@ pub enum PlaybackMode {
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct ErrorScreen {
-    pub player_error_message_renderer: PlayerErrorMessageRenderer,
+    pub player_error_message_renderer: Option<PlayerErrorMessageRenderer>,
+    #[serde(alias = "playerLegacyDesktopYpcTrailerRenderer")]
+    pub ypc_trailer_renderer: Option<YpcTrailerRenderer>,
+}
+
+/// The free trailer, that's shown instead of a video, that has to be bought or rented.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct YpcTrailerRenderer {
+    pub trailer_video_id: Option<IdBuf>,
+    /// The [`PlayerResponse`] of the trailer, as JSON. YouTube either sends it as a string
+    /// (`playerResponse`), or as an object (`unserializedPlayerResponse`). Use
+    /// [`YpcTrailerRenderer::player_response`] to deserialize it.
+    #[serde(
+        default,
+        alias = "unserializedPlayerResponse",
+        deserialize_with = "deserialize_json_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub player_response: Option<String>,
+}
+
+impl YpcTrailerRenderer {
+    /// Deserializes the [`PlayerResponse`] of the trailer, if it's included.
+    ///
+    /// A trailer, that can't be deserialized, is logged, and treated like a missing one, since
+    /// it's not required to handle the actual video.
+    pub fn player_response(&self) -> Option<PlayerResponse> {
+        let player_response = self.player_response.as_deref()?;
+        serde_json::from_str(player_response)
+            .map_err(|err| log::warn!("failed to deserialize the player response of the trailer: {}", err))
+            .ok()
+    }
+}
+
+/// Accepts both, a JSON object, and a string containing one, since YouTube sends the trailer
+/// player response either way.
+fn deserialize_json_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+    where D: serde::Deserializer<'de> {
+    Ok(match Option::<serde_json::Value>::deserialize(deserializer)? {
+        Some(serde_json::Value::String(json)) => Some(json),
+        Some(serde_json::Value::Null) | None => None,
+        Some(value) => Some(value.to_string()),
+    })
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
@@ -84,10 +245,8 @@ pub struct PlayerErrorMessageRenderer {
     pub subreason: Option<Reason>,
     pub reason: Reason,
     pub proceed_button: Option<ProceedButton>,
-    #[serde(rename = "thumbnail")]
-    #[serde(serialize_with = "Thumbnail::serialize_vec")]
-    #[serde(deserialize_with = "Thumbnail::deserialize_vec")]
-    pub thumbnails: Vec<Thumbnail>,
+    #[serde(rename = "thumbnail", default)]
+    pub thumbnails: Thumbnails,
     pub icon: Icon,
 }
 
@@ -100,6 +259,17 @@ pub struct Reason {
     pub runs: Vec<Reason>,
 }
 
+impl Reason {
+    /// The text of the reason, including the text of all its runs.
+    pub fn to_text(&self) -> String {
+        let mut text = self.text.clone().unwrap_or_default();
+        for run in &self.runs {
+            text.push_str(&run.to_text());
+        }
+        text
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct ProceedButton {
@@ -231,8 +401,6 @@ pub struct LiveStreamOfflineSlateRenderer {
     scheduled_start_time: DateTime<Utc>,
     main_text: Reason,
     subtitle_text: Reason,
-    #[serde(rename = "thumbnail")]
-    #[serde(serialize_with = "Thumbnail::serialize_vec")]
-    #[serde(deserialize_with = "Thumbnail::deserialize_vec")]
-    pub thumbnails: Vec<Thumbnail>,
+    #[serde(rename = "thumbnail", default)]
+    pub thumbnails: Thumbnails,
 }
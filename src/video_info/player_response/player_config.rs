@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use serde_with::{json::JsonString, PickFirst, serde_as};
+
+/// Hints of the `playerConfig`, which YouTube's own player uses for adaptive bitrate (ABR)
+/// streaming.
+///
+/// Only the parts, that are useful for choosing streams, are deserialized. All other fields are
+/// ignored.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerConfig {
+    pub audio_config: Option<AudioConfig>,
+    pub stream_selection_config: Option<StreamSelectionConfig>,
+    pub media_common_config: Option<MediaCommonConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioConfig {
+    /// The loudness of the video in dB, relative to YouTube's reference level.
+    pub loudness_db: Option<f64>,
+    /// The perceived loudness of the video in dB.
+    pub perceptual_loudness_db: Option<f64>,
+    /// Whether the loudness of the individual formats (see
+    /// [`RawFormat::loudness_db`](super::streaming_data::RawFormat::loudness_db)) should be used
+    /// instead of the one of the video.
+    pub enable_per_format_loudness: Option<bool>,
+}
+
+/// The loudness normalization data of a video and one of its streams (see
+/// [`Video::loudness`](crate::Video::loudness)).
+///
+/// Loudness values are in dB, relative to YouTube's reference level, so a video with a loudness
+/// of `6.0` is 6 dB louder than the reference, and is turned down by YouTube's player.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Loudness {
+    /// The loudness of the video (see [`AudioConfig::loudness_db`]).
+    pub video_loudness_db: Option<f64>,
+    /// The perceived loudness of the video (see [`AudioConfig::perceptual_loudness_db`]).
+    pub perceptual_loudness_db: Option<f64>,
+    /// The loudness of the stream (see
+    /// [`RawFormat::loudness_db`](super::streaming_data::RawFormat::loudness_db)).
+    pub stream_loudness_db: Option<f64>,
+    /// Whether the loudness of the stream should be used instead of the one of the video (see
+    /// [`AudioConfig::enable_per_format_loudness`]).
+    pub per_format: bool,
+}
+
+impl Loudness {
+    /// The loudness YouTube's player normalizes with: the loudness of the stream, if per format
+    /// loudness is enabled and the stream has one, and the loudness of the video otherwise.
+    #[inline]
+    pub fn loudness_db(&self) -> Option<f64> {
+        match (self.per_format, self.stream_loudness_db) {
+            (true, Some(loudness_db)) => Some(loudness_db),
+            _ => self.video_loudness_db,
+        }
+    }
+
+    /// The factor, the samples have to be multiplied with, to play the stream at the reference
+    /// level (see [`gain`]).
+    #[inline]
+    pub fn gain(&self) -> Option<f64> {
+        self.loudness_db().map(gain)
+    }
+}
+
+/// The gain multiplier, that normalizes audio with a loudness of `loudness_db` to the reference
+/// level: `10^(-loudness_db / 20)`. Loud audio (a positive loudness) is turned down, quiet audio
+/// is turned up.
+///
+/// # Example
+/// ```
+///# use rustube::video_info::player_response::player_config::gain;
+/// assert_eq!(gain(0.), 1.);
+/// assert!((gain(20.) - 0.1).abs() < 1e-12);
+/// ```
+#[inline]
+pub fn gain(loudness_db: f64) -> f64 {
+    10_f64.powf(-loudness_db / 20.)
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamSelectionConfig {
+    /// The highest bitrate in bits per second, the player should choose.
+    #[serde(default)]
+    #[serde_as(as = "Option<PickFirst<(JsonString, _)>>")]
+    pub max_bitrate: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaCommonConfig {
+    pub dynamic_readahead_config: Option<DynamicReadaheadConfig>,
+}
+
+/// How far ahead of the playback position the player should buffer.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicReadaheadConfig {
+    pub max_read_ahead_media_time_ms: Option<u64>,
+    pub min_read_ahead_media_time_ms: Option<u64>,
+    pub read_ahead_growth_rate_ms: Option<u64>,
+}
@@ -0,0 +1,116 @@
+//! The storyboards of a video: sprite sheets of preview thumbnails, i.e. for the preview shown
+//! while scrubbing through the video.
+//!
+//! The [`PlayerResponse`](super::PlayerResponse) describes all levels of the storyboards in a
+//! single `spec` string. Use [`VideoInfo::storyboards`](crate::VideoInfo::storyboards) to get the
+//! parsed levels, and [`Storyboard::image_urls`] to get the URLs of their sprite sheets.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// The `storyboards` of a [`PlayerResponse`](super::PlayerResponse).
+///
+/// Live streams have a `playerLiveStoryboardSpecRenderer` instead, which is not deserialized.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Storyboards {
+    pub player_storyboard_spec_renderer: Option<StoryboardSpecRenderer>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryboardSpecRenderer {
+    /// The templated URL of the sprite sheets, followed by the levels, all separated by `|`
+    /// (see [`Storyboard::from_spec`]).
+    pub spec: String,
+    pub recommended_level: Option<u32>,
+}
+
+/// A level of the storyboards, whose thumbnails are arranged in sprite sheets of
+/// `columns` x `rows` thumbnails.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Storyboard {
+    /// The level, starting at 0 for the smallest thumbnails.
+    pub level: u32,
+    /// The URL of the sprite sheets, where `$M` is the index of the sheet.
+    pub url_template: String,
+    pub thumbnail_width: u32,
+    pub thumbnail_height: u32,
+    /// The number of thumbnails in all sprite sheets of the level.
+    pub thumbnail_count: u32,
+    pub columns: u32,
+    pub rows: u32,
+    /// The time between two thumbnails. It's zero, if the thumbnails are spread evenly over the
+    /// video, which is usually the case for level 0.
+    pub interval: Duration,
+}
+
+impl Storyboard {
+    /// Parses the levels of a `spec`, like
+    /// `https://i.ytimg.com/sb/<id>/storyboard3_L$L/$N.jpg?sqp=<sqp>|48#27#100#10#10#0#default#rs$<sigh>|...`.
+    ///
+    /// Each level consists of the width and height of the thumbnails, the number of thumbnails,
+    /// the columns and rows of a sprite sheet, the interval in milliseconds, the name of the
+    /// sprite sheets, and their signature. Levels, that don't match this format, are skipped.
+    pub fn from_spec(spec: &str) -> Vec<Self> {
+        let mut parts = spec.split('|');
+        let url = match parts.next() {
+            Some(url) if Url::parse(url).is_ok() => url,
+            _ => return Vec::new(),
+        };
+
+        parts
+            .enumerate()
+            .filter_map(|(level, args)| Self::from_level(url, level as u32, args))
+            .collect()
+    }
+
+    fn from_level(url: &str, level: u32, args: &str) -> Option<Self> {
+        let args: Vec<&str> = args.split('#').collect();
+        if args.len() != 8 {
+            log::warn!("skipping the storyboard level {}, since it has an unknown format: {:?}", level, args);
+            return None;
+        }
+        let numbers = args[..6]
+            .iter()
+            .map(|arg| arg.parse::<u32>().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        if numbers[..5].contains(&0) {
+            return None;
+        }
+
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let url_template = format!(
+            "{}{}sigh={}",
+            url.replace("$L", &level.to_string()).replace("$N", args[6]),
+            separator,
+            args[7],
+        );
+
+        Some(Self {
+            level,
+            url_template,
+            thumbnail_width: numbers[0],
+            thumbnail_height: numbers[1],
+            thumbnail_count: numbers[2],
+            columns: numbers[3],
+            rows: numbers[4],
+            interval: Duration::from_millis(numbers[5] as u64),
+        })
+    }
+
+    /// The number of sprite sheets, the thumbnails of the level are split into.
+    #[inline]
+    pub fn sheet_count(&self) -> u32 {
+        self.thumbnail_count.div_ceil(self.columns * self.rows)
+    }
+
+    /// The URLs of all sprite sheets of the level, in order.
+    pub fn image_urls(&self) -> Vec<Url> {
+        (0..self.sheet_count())
+            .filter_map(|sheet| Url::parse(&self.url_template.replace("$M", &sheet.to_string())).ok())
+            .collect()
+    }
+}
@@ -1,23 +1,124 @@
+use core::fmt;
+use core::str::FromStr;
 use std::ops::Range;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use mime::Mime;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_with::{DefaultOnNull, json::JsonString};
 use serde_with::serde_as;
 use url::Url;
 
+use crate::{Error, LanguageTag, Result};
+
 #[serde_as]
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", from = "RawStreamingData")]
 pub struct StreamingData {
     // todo: remove the field adaptive_formats, and deserialize all formats into formats
-    #[serde(default)]
     pub adaptive_formats: Vec<RawFormat>,
+    /// The url of the DASH manifest, which YouTube includes for live streams, and videos, that
+    /// were live (see [`DashManifest`](crate::dash::DashManifest)).
+    pub dash_manifest_url: Option<Url>,
     #[serde_as(as = "JsonString")]
     pub expires_in_seconds: u64,
-    #[serde(default)]
     pub formats: Vec<RawFormat>,
+    /// The url of the HLS manifest, which YouTube includes for live streams.
+    pub hls_manifest_url: Option<Url>,
+    /// The url of YouTube's server ABR (SABR) streaming protocol, which rustube does not support.
+    pub server_abr_streaming_url: Option<Url>,
+    /// The itags of the formats without a URL or signature cipher, which are only available via
+    /// the [`server_abr_streaming_url`](Self::server_abr_streaming_url). They are not part of
+    /// `formats` or `adaptive_formats`.
+    pub sabr_only_itags: Vec<u64>,
+}
+
+impl StreamingData {
+    /// When the [`server_abr_streaming_url`](Self::server_abr_streaming_url) expires, taken from
+    /// its `expire` query parameter.
+    pub fn server_abr_expiry(&self) -> Option<DateTime<Utc>> {
+        let (_, expire) = self.server_abr_streaming_url
+            .as_ref()?
+            .query_pairs()
+            .find(|(key, _)| key == "expire")?;
+        let secs = expire.parse::<i64>().ok()?;
+        Utc.timestamp_opt(secs, 0).single()
+    }
+}
+
+/// The [`StreamingData`] as YouTube sends it. Formats without a URL are only collected by their
+/// itag, so one SABR-only format doesn't make the whole response fail to deserialize.
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawStreamingData {
+    #[serde(default)]
+    adaptive_formats: Vec<MaybeSabrOnly>,
+    #[serde(default)]
+    dash_manifest_url: Option<Url>,
+    #[serde_as(as = "JsonString")]
+    expires_in_seconds: u64,
+    #[serde(default)]
+    formats: Vec<MaybeSabrOnly>,
+    #[serde(default)]
+    hls_manifest_url: Option<Url>,
+    #[serde(default)]
+    server_abr_streaming_url: Option<Url>,
+    // only present, when a serialized StreamingData is deserialized again
+    #[serde(default)]
+    sabr_only_itags: Vec<u64>,
+}
+
+enum MaybeSabrOnly {
+    Format(Box<RawFormat>),
+    SabrOnly(u64),
+}
+
+impl<'de> Deserialize<'de> for MaybeSabrOnly {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let has_url = ["url", "signatureCipher", "Cipher"]
+            .iter()
+            .any(|key| value.get(key).is_some());
+
+        match value.get("itag").and_then(serde_json::Value::as_u64) {
+            Some(itag) if !has_url => Ok(Self::SabrOnly(itag)),
+            // deserialized from a reference, since some fields borrow from the input
+            _ => RawFormat::deserialize(&value)
+                .map(|format| Self::Format(Box::new(format)))
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl From<RawStreamingData> for StreamingData {
+    fn from(raw: RawStreamingData) -> Self {
+        let mut sabr_only_itags = raw.sabr_only_itags;
+        let mut split = |formats: Vec<MaybeSabrOnly>| formats
+            .into_iter()
+            .filter_map(|format| match format {
+                MaybeSabrOnly::Format(format) => Some(*format),
+                MaybeSabrOnly::SabrOnly(itag) => {
+                    sabr_only_itags.push(itag);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        let formats = split(raw.formats);
+        let adaptive_formats = split(raw.adaptive_formats);
+
+        Self {
+            adaptive_formats,
+            dash_manifest_url: raw.dash_manifest_url,
+            expires_in_seconds: raw.expires_in_seconds,
+            formats,
+            hls_manifest_url: raw.hls_manifest_url,
+            server_abr_streaming_url: raw.server_abr_streaming_url,
+            sabr_only_itags,
+        }
+    }
 }
 
 #[serde_as]
@@ -34,6 +135,7 @@ pub struct RawFormat {
     #[serde(default)]
     #[serde_as(as = "Option<DefaultOnNull<JsonString>>")]
     pub audio_sample_rate: Option<u64>,
+    pub audio_track: Option<AudioTrack>,
     pub average_bitrate: Option<u64>,
     pub bitrate: Option<u64>,
     pub color_info: Option<ColorInfo>,
@@ -41,6 +143,11 @@ pub struct RawFormat {
     #[serde_as(as = "Option<JsonString>")]
     pub content_length: Option<u64>,
     #[serde(default)]
+    pub drm_families: Vec<String>,
+    /// Whether the audio has a dynamic range compression applied (YouTube's "stable volume").
+    #[serde(default)]
+    pub is_drc: bool,
+    #[serde(default)]
     pub fps: u8,
     pub height: Option<u64>,
     pub high_replication: Option<bool>,
@@ -59,7 +166,7 @@ pub struct RawFormat {
     pub projection_type: ProjectionType,
     pub quality: Quality,
     pub quality_label: Option<QualityLabel>,
-    #[serde(flatten, deserialize_with = "crate::serde_impl::signature_cipher::deserialize")]
+    #[serde(flatten, with = "crate::serde_impl::signature_cipher")]
     pub signature_cipher: SignatureCipher,
     pub width: Option<u64>,
 }
@@ -68,6 +175,48 @@ pub struct RawFormat {
 pub struct SignatureCipher {
     pub url: Url,
     pub s: Option<String>,
+    /// Whether the `n` parameter of the `url` was transformed by the descrambler.
+    #[serde(skip)]
+    pub(crate) n_transformed: bool,
+}
+
+/// One of possibly many audio tracks of a video, i.e. the original audio or a dubbed language.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioTrack {
+    /// The track id, consisting of a language tag and a track number, i.e. `en-US.4`.
+    pub id: String,
+    pub display_name: String,
+    #[serde(default, rename = "audioIsDefault")]
+    pub is_default: bool,
+}
+
+impl AudioTrack {
+    /// The language tag of the track, i.e. `en-US` for the id `en-US.4`.
+    #[inline]
+    pub fn language(&self) -> &str {
+        self.id
+            .split_once('.')
+            .map_or(self.id.as_str(), |(language, _)| language)
+    }
+
+    /// The [`LanguageTag`] of the track, i.e. `en-US` for the id `en-US.4`.
+    #[inline]
+    pub fn language_tag(&self) -> LanguageTag {
+        LanguageTag::from_raw(self.language())
+    }
+
+    /// Whether or not the track is in the language `lang_tag`.
+    ///
+    /// Tags are compared case-insensitively, and a primary tag like `en` also matches all its
+    /// regional variants, like `en-US` or `en-GB`.
+    #[inline]
+    pub fn is_language(&self, lang_tag: &str) -> bool {
+        let language = self.language();
+        language.eq_ignore_ascii_case(lang_tag) || language
+            .split_once('-')
+            .is_some_and(|(primary, _)| primary.eq_ignore_ascii_case(lang_tag))
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
@@ -84,28 +233,52 @@ pub struct ColorInfo {
     pub matrix_coefficients: Option<MatrixCoefficients>,
 }
 
+/// The color primaries of a format. Values, rustube does not know yet, are deserialized as
+/// [`ColorInfoPrimary::Unknown`].
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 pub enum ColorInfoPrimary {
     #[serde(rename = "COLOR_PRIMARIES_BT709")]
     BT709,
     #[serde(rename = "COLOR_PRIMARIES_BT2020")]
     BT2020,
+    #[serde(other)]
+    Unknown,
 }
 
+/// The transfer characteristics of a format. Values, rustube does not know yet, are deserialized
+/// as [`TransferCharacteristics::Unknown`].
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 pub enum TransferCharacteristics {
     #[serde(rename = "COLOR_TRANSFER_CHARACTERISTICS_BT709")]
     BT709,
+    /// Perceptual quantization (PQ), which is used by HDR10.
     #[serde(rename = "COLOR_TRANSFER_CHARACTERISTICS_SMPTEST2084")]
     SMPTEST2084,
+    /// Hybrid log-gamma (HLG).
+    #[serde(rename = "COLOR_TRANSFER_CHARACTERISTICS_ARIB_STD_B67")]
+    AribStdB67,
+    #[serde(other)]
+    Unknown,
 }
 
+impl TransferCharacteristics {
+    /// Whether the transfer characteristics are the ones of an HDR format (PQ, or HLG).
+    #[inline]
+    pub fn is_hdr(&self) -> bool {
+        matches!(self, Self::SMPTEST2084 | Self::AribStdB67)
+    }
+}
+
+/// The matrix coefficients of a format. Values, rustube does not know yet, are deserialized as
+/// [`MatrixCoefficients::Unknown`].
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 pub enum MatrixCoefficients {
     #[serde(rename = "COLOR_MATRIX_COEFFICIENTS_BT709")]
     BT709,
     #[serde(rename = "COLOR_MATRIX_COEFFICIENTS_BT2020_NCL")]
     BT2020NCL,
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -115,10 +288,130 @@ pub struct MimeType {
     pub codecs: Vec<String>,
 }
 
+/// The projection of the video track of a format. Values, rustube does not know yet, are
+/// deserialized as [`ProjectionType::Unknown`].
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum ProjectionType {
     Rectangular,
+    /// A 360° video.
+    Equirectangular,
+    /// A 360° video, or a VR180 video, which is projected onto a mesh.
+    Mesh,
+    #[serde(other)]
+    Unknown,
+}
+
+impl ProjectionType {
+    /// Whether the video is a 360°, or VR180 video, that has to be projected before it's played.
+    #[inline]
+    pub fn is_spherical(&self) -> bool {
+        matches!(self, Self::Equirectangular | Self::Mesh)
+    }
+}
+
+/// Implements [`Display`](fmt::Display) and [`FromStr`] for a fieldless enum, using the same
+/// strings as its serde representation, and adds `ALL` and `as_str`. `FromStr` also accepts the
+/// aliases after the `|`.
+macro_rules! string_enum {
+    ($ty:ident { $($variant:ident => $s:literal $(| $alias:literal)*,)* }) => {
+        impl $ty {
+            /// All variants, in the order of declaration.
+            pub const ALL: &'static [Self] = &[$(Self::$variant),*];
+
+            /// The string YouTube uses for the variant, which is also its serde representation.
+            #[inline]
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $s,)*
+                }
+            }
+        }
+
+        impl fmt::Display for $ty {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl FromStr for $ty {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                match s {
+                    $($s $(| $alias)* => Ok(Self::$variant),)*
+                    _ => Err(Error::BadQualityFormat(s.to_owned())),
+                }
+            }
+        }
+    };
+    // An enum with a catch-all variant, that keeps unknown values. It gets its serde
+    // implementations from the macro: deserialization is lenient, while `FromStr` stays strict.
+    ($ty:ident($unknown:ident) { $($variant:ident => $s:literal,)* }) => {
+        impl $ty {
+            /// All known variants, in the order of declaration.
+            pub const ALL: &'static [Self] = &[$(Self::$variant),*];
+
+            /// Creates the variant for `raw`, which is kept as
+            #[doc = concat!("[`", stringify!($ty), "::", stringify!($unknown), "`],")]
+            /// if it's not known.
+            pub fn from_raw(raw: impl Into<String>) -> Self {
+                let raw = raw.into();
+                match raw.as_str() {
+                    $($s => Self::$variant,)*
+                    _ => Self::$unknown(raw),
+                }
+            }
+
+            /// The string YouTube uses for the variant, which is also its serde representation.
+            #[inline]
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $s,)*
+                    Self::$unknown(raw) => raw,
+                }
+            }
+
+            /// Whether the value is known to rustube.
+            #[inline]
+            pub fn is_known(&self) -> bool {
+                !matches!(self, Self::$unknown(_))
+            }
+        }
+
+        impl fmt::Display for $ty {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl FromStr for $ty {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                match Self::from_raw(s) {
+                    Self::$unknown(_) => Err(Error::BadQualityFormat(s.to_owned())),
+                    known => Ok(known),
+                }
+            }
+        }
+
+        impl Serialize for $ty {
+            #[inline]
+            fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            #[inline]
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+                String::deserialize(deserializer).map(Self::from_raw)
+            }
+        }
+    };
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -131,9 +424,19 @@ pub enum AudioQuality {
     High,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[serde(rename_all = "lowercase")]
+string_enum!(AudioQuality {
+    Low => "AUDIO_QUALITY_LOW" | "low",
+    Medium => "AUDIO_QUALITY_MEDIUM" | "medium",
+    High => "AUDIO_QUALITY_HIGH" | "high",
+});
+
+/// The quality of a format.
+///
+/// Qualities, rustube does not know yet, are kept as [`Quality::Unknown`], which sorts below all
+/// known ones.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Quality {
+    Unknown(String),
     Tiny,
     Small,
     Medium,
@@ -145,71 +448,119 @@ pub enum Quality {
     Hd2160,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+string_enum!(Quality(Unknown) {
+    Tiny => "tiny",
+    Small => "small",
+    Medium => "medium",
+    Large => "large",
+    Highres => "highres",
+    Hd720 => "hd720",
+    Hd1080 => "hd1080",
+    Hd1440 => "hd1440",
+    Hd2160 => "hd2160",
+});
+
+/// The quality label of a format, i.e. `1080p60 HDR`.
+///
+/// Labels, rustube does not know yet, are kept as [`QualityLabel::Unknown`], which sorts below
+/// all known ones, so the selection helpers prefer streams with known labels.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[non_exhaustive]
 pub enum QualityLabel {
-    #[serde(rename = "144p")]
+    Unknown(String),
     P144,
-    #[serde(rename = "144p HDR")]
     P144HDR,
-    #[serde(rename = "144p60 HDR")]
     P144Hz60HDR,
-    #[serde(rename = "240p")]
     P240,
-    #[serde(rename = "240p HDR")]
     P240HDR,
-    #[serde(rename = "240p60 HDR")]
     P240Hz60HDR,
-    #[serde(rename = "360p")]
     P360,
-    #[serde(rename = "360p HDR")]
     P360HDR,
-    #[serde(rename = "360p60")]
     P360Hz60,
-    #[serde(rename = "360p60 HDR")]
     P360Hz60HDR,
-    #[serde(rename = "480p")]
     P480,
-    #[serde(rename = "480p HDR")]
     P480HDR,
-    #[serde(rename = "480p60")]
     P480Hz60,
-    #[serde(rename = "480p60 HDR")]
     P480Hz60HDR,
-    #[serde(rename = "720p")]
     P720,
-    #[serde(rename = "720p50")]
     P720Hz50,
-    #[serde(rename = "720p60")]
     P720Hz60,
-    #[serde(rename = "720p60 HDR")]
     P720Hz60HDR,
-    #[serde(rename = "1080p")]
     P1080,
-    #[serde(rename = "1080p50")]
     P1080Hz50,
-    #[serde(rename = "1080p60")]
     P1080Hz60,
-    #[serde(rename = "1080p60 HDR")]
     P1080Hz60HDR,
-    #[serde(rename = "1440p")]
+    /// A higher bitrate 1080p format, that's only available to YouTube Premium subscribers.
+    P1080Premium,
     P1440,
-    #[serde(rename = "1440p50")]
     P1440Hz50,
-    #[serde(rename = "1440p60")]
     P1440Hz60,
-    #[serde(rename = "1440p60 HDR")]
     P1440Hz60HDR,
-    #[serde(rename = "2160p")]
     P2160,
-    #[serde(rename = "2160p60")]
     P2160Hz60,
-    #[serde(rename = "2160p60 HDR")]
     P2160Hz60HDR,
-    #[serde(rename = "4320p")]
     P4320,
-    #[serde(rename = "4320p60")]
     P4320Hz60,
-    #[serde(rename = "4320p60 HDR")]
     P4320Hz60HDR,
 }
+
+string_enum!(QualityLabel(Unknown) {
+    P144 => "144p",
+    P144HDR => "144p HDR",
+    P144Hz60HDR => "144p60 HDR",
+    P240 => "240p",
+    P240HDR => "240p HDR",
+    P240Hz60HDR => "240p60 HDR",
+    P360 => "360p",
+    P360HDR => "360p HDR",
+    P360Hz60 => "360p60",
+    P360Hz60HDR => "360p60 HDR",
+    P480 => "480p",
+    P480HDR => "480p HDR",
+    P480Hz60 => "480p60",
+    P480Hz60HDR => "480p60 HDR",
+    P720 => "720p",
+    P720Hz50 => "720p50",
+    P720Hz60 => "720p60",
+    P720Hz60HDR => "720p60 HDR",
+    P1080 => "1080p",
+    P1080Hz50 => "1080p50",
+    P1080Hz60 => "1080p60",
+    P1080Hz60HDR => "1080p60 HDR",
+    P1080Premium => "1080p Premium",
+    P1440 => "1440p",
+    P1440Hz50 => "1440p50",
+    P1440Hz60 => "1440p60",
+    P1440Hz60HDR => "1440p60 HDR",
+    P2160 => "2160p",
+    P2160Hz60 => "2160p60",
+    P2160Hz60HDR => "2160p60 HDR",
+    P4320 => "4320p",
+    P4320Hz60 => "4320p60",
+    P4320Hz60HDR => "4320p60 HDR",
+});
+
+impl QualityLabel {
+    /// Whether the label belongs to a format, that's only available to YouTube Premium subscribers.
+    #[inline]
+    pub fn is_premium(&self) -> bool {
+        match self {
+            Self::Unknown(label) => label.ends_with("Premium"),
+            label => matches!(label, Self::P1080Premium),
+        }
+    }
+
+    /// Whether the label belongs to an HDR format.
+    #[inline]
+    pub fn is_hdr(&self) -> bool {
+        if let Self::Unknown(label) = self {
+            return label.ends_with("HDR");
+        }
+        matches!(
+            self,
+            Self::P144HDR | Self::P144Hz60HDR | Self::P240HDR | Self::P240Hz60HDR | Self::P360HDR
+                | Self::P360Hz60HDR | Self::P480HDR | Self::P480Hz60HDR | Self::P720Hz60HDR
+                | Self::P1080Hz60HDR | Self::P1440Hz60HDR | Self::P2160Hz60HDR | Self::P4320Hz60HDR
+        )
+    }
+}
@@ -1,8 +1,15 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use serde_with::{json::JsonString, serde_as};
+use serde::ser::SerializeStruct;
+use serde_with::{json::JsonString, PickFirst, serde_as};
+use url::Url;
 
 use crate::IdBuf;
 
+/// The base of relative thumbnail URLs.
+const YOUTUBE_URL: &str = "https://www.youtube.com/";
+
 #[serde_as]
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -12,7 +19,18 @@ pub struct VideoDetails {
     // todo: add Type ChannelId
     pub channel_id: String,
     pub is_crawlable: bool,
+    /// Whether the video is, or was, a live stream. Use [`VideoDetails::live_status`] to
+    /// distinguish between the two.
     pub is_live_content: bool,
+    /// Whether the video is a live stream, that's currently live.
+    #[serde(default)]
+    pub is_live: bool,
+    /// Whether the video is a premiere, or a live stream, that did not start yet.
+    #[serde(default)]
+    pub is_upcoming: bool,
+    /// Whether the video is a live stream, that just ended, and is still being processed.
+    #[serde(default)]
+    pub is_post_live_dvr: bool,
     #[serde(default)]
     pub is_live_default_broadcast: bool,
     #[serde(default)]
@@ -24,21 +42,112 @@ pub struct VideoDetails {
     pub is_unplugged_corpus: bool,
     pub latency_class: Option<LatencyClass>,
     pub live_chunk_readahead: Option<u64>,
-    #[serde(default)]
+    // YouTube calls the field `keywords`, but it's serialized as `keyWords` for compatibility
+    #[serde(default, alias = "keywords")]
     pub key_words: Vec<String>,
-    #[serde_as(as = "JsonString")]
+    /// YouTube sends the length as string, but it's serialized as number.
+    #[serde_as(as = "PickFirst<(_, JsonString)>")]
     pub length_seconds: u64,
     pub short_description: String,
-    #[serde(rename = "thumbnail")]
-    #[serde(serialize_with = "Thumbnail::serialize_vec")]
-    #[serde(deserialize_with = "Thumbnail::deserialize_vec")]
-    pub thumbnails: Vec<Thumbnail>,
+    #[serde(rename = "thumbnail", default)]
+    pub thumbnails: Thumbnails,
     pub title: String,
     pub video_id: IdBuf,
-    #[serde_as(as = "JsonString")]
+    /// YouTube sends the view count as string, but it's serialized as number.
+    #[serde_as(as = "PickFirst<(_, JsonString)>")]
     pub view_count: u64,
 }
 
+impl VideoDetails {
+    /// The details of a video, that YouTube did not return any details for, i.e. because it's
+    /// private or deleted. Everything except for the `video_id` is empty.
+    pub(crate) fn unknown(video_id: IdBuf) -> Self {
+        Self {
+            allow_ratings: false,
+            author: String::new(),
+            channel_id: String::new(),
+            is_crawlable: false,
+            is_live_content: false,
+            is_live: false,
+            is_upcoming: false,
+            is_post_live_dvr: false,
+            is_live_default_broadcast: false,
+            is_live_dvr_enabled: false,
+            is_low_latency_live_stream: false,
+            is_owner_viewing: false,
+            is_private: false,
+            is_unplugged_corpus: false,
+            latency_class: None,
+            live_chunk_readahead: None,
+            key_words: Vec::new(),
+            length_seconds: 0,
+            short_description: String::new(),
+            thumbnails: Thumbnails::default(),
+            title: String::new(),
+            video_id,
+            view_count: 0,
+        }
+    }
+
+    /// The length of the video, or [`Duration::ZERO`], if it's unknown, i.e. for live streams.
+    #[inline]
+    pub fn length(&self) -> Duration {
+        Duration::from_secs(self.length_seconds)
+    }
+
+    /// The number of views.
+    #[inline]
+    pub fn views(&self) -> u64 {
+        self.view_count
+    }
+
+    /// The keywords, the video is tagged with.
+    #[inline]
+    pub fn keywords(&self) -> &[String] {
+        &self.key_words
+    }
+
+    /// The absolute URL of the [largest](Thumbnails::best) thumbnail. Relative URLs are resolved
+    /// against `https://www.youtube.com/`.
+    pub fn thumbnail_url_max(&self) -> Option<Url> {
+        self.thumbnails.best()?.absolute_url()
+    }
+
+    /// The [`LiveStatus`] of the video, as far as it can be derived from the video details alone.
+    ///
+    /// Prefer [`PlayerResponse::live_status`](crate::PlayerResponse::live_status), which also
+    /// takes the microformat into account, if the `microformat` feature is enabled.
+    pub fn live_status(&self) -> LiveStatus {
+        if self.is_post_live_dvr {
+            LiveStatus::PostLiveProcessing
+        } else if self.is_upcoming {
+            LiveStatus::UpcomingPremiere
+        } else if self.is_live {
+            LiveStatus::Live
+        } else if self.is_live_content {
+            LiveStatus::WasLive
+        } else {
+            LiveStatus::NotLive
+        }
+    }
+}
+
+/// Whether, and in which way, a video is connected to a live broadcast.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LiveStatus {
+    /// A regular video.
+    NotLive,
+    /// A live stream, that's currently live.
+    Live,
+    /// A live stream or premiere, that ended, and is now available as a regular video.
+    WasLive,
+    /// A premiere or live stream, that's scheduled, but did not start yet.
+    UpcomingPremiere,
+    /// A live stream, that just ended. YouTube is still processing it, so its streams may not be
+    /// downloadable yet.
+    PostLiveProcessing,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum LatencyClass {
@@ -52,30 +161,118 @@ pub enum LatencyClass {
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 pub struct Thumbnail {
+    /// The width in pixels, or `0`, if YouTube did not provide it.
+    #[serde(default)]
     pub width: u64,
+    /// The height in pixels, or `0`, if YouTube did not provide it.
+    #[serde(default)]
     pub height: u64,
     /// a absolute or relative url
     pub url: String,
 }
 
-
 impl Thumbnail {
-    pub(crate) fn deserialize_vec<'de, D>(deserializer: D) -> Result<Vec<Self>, <D as Deserializer<'de>>::Error> where
-        D: Deserializer<'de> {
+    /// The largest of the `thumbnails` by area. YouTube lists the thumbnails from small to large,
+    /// so of thumbnails without a size, the last one is chosen.
+    #[inline]
+    pub fn best(thumbnails: &[Thumbnail]) -> Option<&Thumbnail> {
+        thumbnails.iter().max_by_key(|thumbnail| thumbnail.area())
+    }
+
+    /// The absolute URL of the thumbnail. Relative URLs are resolved against
+    /// `https://www.youtube.com/`.
+    pub fn absolute_url(&self) -> Option<Url> {
+        Url::parse(&self.url)
+            .or_else(|_| Url::parse(YOUTUBE_URL).unwrap().join(&self.url))
+            .ok()
+    }
+
+    /// Downloads the thumbnail to `path`, i.e. to save it as cover art next to a downloaded audio
+    /// stream. An existing file at `path` is overwritten.
+    #[cfg(feature = "download")]
+    pub async fn download(&self, client: &reqwest::Client, path: impl AsRef<std::path::Path>) -> crate::Result<()> {
+        let url = self
+            .absolute_url()
+            .ok_or_else(|| crate::Error::UnexpectedResponse(
+                format!("the thumbnail url `{}` is invalid", self.url).into()
+            ))?;
+        let bytes = client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        tokio::fs::write(path, bytes).await?;
+        Ok(())
+    }
+
+    #[inline]
+    fn area(&self) -> u64 {
+        self.width.saturating_mul(self.height)
+    }
+}
+
+/// The thumbnails of a video, an error screen, or an avatar, in all sizes YouTube provides.
+///
+/// YouTube usually wraps them in an object (`{"thumbnails": [...]}`), but sometimes sends a bare
+/// array, or an empty object, i.e. for channels without a custom avatar. All of these are accepted.
+/// They're always serialized as object.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, derive_more::Deref, derive_more::DerefMut, derive_more::From, derive_more::Into)]
+pub struct Thumbnails(pub Vec<Thumbnail>);
+
+impl Thumbnails {
+    /// The largest thumbnail. YouTube lists the thumbnails from small to large, so of
+    /// thumbnails without a size, the last one is chosen.
+    #[inline]
+    pub fn best(&self) -> Option<&Thumbnail> {
+        Thumbnail::best(&self.0)
+    }
+
+    /// The smallest thumbnail. Of thumbnails without a size, the first one is chosen.
+    #[inline]
+    pub fn smallest(&self) -> Option<&Thumbnail> {
+        self.0.iter().min_by_key(|thumbnail| thumbnail.area())
+    }
+}
+
+impl<'a> IntoIterator for &'a Thumbnails {
+    type Item = &'a Thumbnail;
+    type IntoIter = std::slice::Iter<'a, Thumbnail>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'de> Deserialize<'de> for Thumbnails {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de> {
         #[derive(Deserialize)]
-        struct Thumbnails { thumbnails: Vec<Thumbnail> }
+        #[serde(untagged)]
+        enum Repr {
+            Bare(Vec<Thumbnail>),
+            Wrapped {
+                #[serde(default)]
+                thumbnails: Vec<Thumbnail>,
+            },
+        }
 
-        Ok(
-            Thumbnails::deserialize(deserializer)?
-                .thumbnails
-        )
+        Ok(match Option::<Repr>::deserialize(deserializer)? {
+            Some(Repr::Bare(thumbnails) | Repr::Wrapped { thumbnails }) => Self(thumbnails),
+            None => Self::default(),
+        })
     }
-    pub(crate) fn serialize_vec<S>(thumbnails: &[Thumbnail], serializer: S) -> Result<S::Ok, S::Error>
+}
+
+impl Serialize for Thumbnails {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where
             S: Serializer {
-        #[derive(Serialize)]
-        struct Thumbnails<'a> { thumbnails: &'a [Thumbnail] }
-
-        Thumbnails { thumbnails }.serialize(serializer)
+        let mut state = serializer.serialize_struct("Thumbnails", 1)?;
+        state.serialize_field("thumbnails", &self.0)?;
+        state.end()
     }
 }
@@ -0,0 +1,164 @@
+//! A minimal XML parser for the few XML documents YouTube serves, like DASH manifests and
+//! captions.
+
+use crate::text;
+
+/// An element of an XML document. Namespace prefixes of element names are removed.
+#[derive(Debug, Default)]
+pub(crate) struct Element {
+    pub(crate) name: String,
+    pub(crate) attributes: Vec<(String, String)>,
+    pub(crate) children: Vec<Element>,
+    /// The text content of the element, excluding the text of its children.
+    pub(crate) text: String,
+}
+
+impl Element {
+    pub(crate) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub(crate) fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+/// Parses the XML `document`, and returns its root element.
+///
+/// This is not a complete XML parser. It only understands elements, attributes, text, CDATA
+/// sections, and the predefined and numeric entities, and skips comments, processing
+/// instructions and declarations.
+pub(crate) fn parse(document: &str) -> Result<Element, String> {
+    let mut stack = vec![Element::default()];
+    let mut rest = document;
+
+    while let Some(start) = rest.find('<') {
+        let text = unescape(&rest[..start])?;
+        stack.last_mut().unwrap().text.push_str(&text);
+        rest = &rest[start..];
+
+        let skipped = [("<!--", "-->"), ("<?", "?>")]
+            .iter()
+            .find(|(open, _)| rest.starts_with(open));
+        if let Some((_, close)) = skipped {
+            let end = rest.find(close).ok_or_else(|| "unclosed comment".to_owned())?;
+            rest = &rest[end + close.len()..];
+            continue;
+        }
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").ok_or_else(|| "unclosed CDATA section".to_owned())?;
+            stack.last_mut().unwrap().text.push_str(&cdata[..end]);
+            rest = &cdata[end + 3..];
+            continue;
+        }
+
+        let end = tag_end(rest).ok_or_else(|| "unclosed tag".to_owned())?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if tag.starts_with('!') {
+            // a declaration, like `<!DOCTYPE ...>`
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            let element = stack.pop().filter(|_| !stack.is_empty());
+            match element {
+                Some(element) if element.name == local_name(name.trim()) => {
+                    stack.last_mut().unwrap().children.push(element);
+                }
+                _ => return Err(format!("unexpected closing tag `{}`", name.trim())),
+            }
+            continue;
+        }
+
+        let (tag, self_closing) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let element = parse_start_tag(tag)?;
+        match self_closing {
+            true => stack.last_mut().unwrap().children.push(element),
+            false => stack.push(element),
+        }
+    }
+
+    match stack.pop() {
+        Some(document) if stack.is_empty() => document.children
+            .into_iter()
+            .next()
+            .ok_or_else(|| "the document is empty".to_owned()),
+        _ => Err("unclosed element".to_owned()),
+    }
+}
+
+/// The index of the `>`, that closes the tag at the start of `text`, ignoring quoted attribute
+/// values.
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('>', None) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parses the content of a start tag (`name attr="value" ...`).
+fn parse_start_tag(tag: &str) -> Result<Element, String> {
+    let tag = tag.trim();
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let mut element = Element { name: local_name(&tag[..name_end]).to_owned(), ..Element::default() };
+    let mut rest = tag[name_end..].trim_start();
+
+    while !rest.is_empty() {
+        let malformed = || format!("malformed attributes of `{}`", element.name);
+        let eq = rest.find('=').ok_or_else(malformed)?;
+        let name = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'').ok_or_else(malformed)?;
+        let end = value[1..].find(quote).ok_or_else(malformed)? + 1;
+
+        let attribute = (name.to_owned(), unescape(&value[1..end])?);
+        element.attributes.push(attribute);
+        rest = value[end + 1..].trim_start();
+    }
+
+    Ok(element)
+}
+
+/// Removes the namespace prefix of a name.
+#[inline]
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Replaces the predefined and numeric entities of `text`.
+pub(crate) fn unescape(text: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| "unterminated entity".to_owned())? + start;
+        let entity = &rest[start + 1..end];
+        let c = text::entity(entity);
+        unescaped.push(c.ok_or_else(|| format!("unknown entity `&{};`", entity))?);
+        rest = &rest[end + 1..];
+    }
+
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
@@ -0,0 +1,122 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{PlayerResponse, Video};
+use rustube::video_info::player_response::annotations::{CardContent, EndscreenElementStyle};
+
+#[macro_use]
+mod common;
+
+/// The multi audio fixture, with the cards and the end screen of the annotations fixture.
+fn player_response() -> Value {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    let mut annotations = json_fixture("annotations.json");
+    player_response["cards"] = annotations["cards"].take();
+    player_response["endscreen"] = annotations["endscreen"].take();
+    player_response
+}
+
+fn video() -> Video {
+    video_from_player_response(player_response())
+}
+
+#[test]
+fn endscreen_items() {
+    let video = video();
+    let items = video.endscreen_items();
+
+    // the `MERCH` element and the collaborator renderer are unknown, and skipped
+    let styles: Vec<_> = items.iter().map(|item| item.style).collect();
+    assert_eq!(styles, [
+        EndscreenElementStyle::Video,
+        EndscreenElementStyle::Playlist,
+        EndscreenElementStyle::Channel,
+        EndscreenElementStyle::Website,
+        EndscreenElementStyle::Subscribe,
+    ]);
+
+    let suggested = &items[0];
+    assert_eq!(suggested.title(), "How the cipher works");
+    assert_eq!(suggested.video_id().unwrap().as_str(), "9bZkp7q19f0");
+    assert_eq!(suggested.playlist_id(), None);
+    assert_eq!((suggested.start_ms, suggested.end_ms), (595_000, 615_000));
+    assert_eq!(suggested.image.best().unwrap().url, "https://i.ytimg.com/vi/9bZkp7q19f0/hqdefault.jpg");
+    assert_eq!(suggested.metadata.as_ref().unwrap().to_text(), "12K views");
+
+    let playlist = &items[1];
+    assert_eq!(playlist.title(), "Downloading videos");
+    assert_eq!(playlist.video_id().unwrap().as_str(), "kJQP7kiw5Fk");
+    assert_eq!(playlist.playlist_id(), Some("PLbpi6ZahtOH6Blw3RGYpWkSByi_T7Rygb"));
+
+    assert_eq!(items[2].channel_id(), Some("UCaYhcUwRBNscFNUKTjgPFiA"));
+    assert_eq!(items[2].video_id(), None);
+    // the times of the website are numbers instead of strings
+    assert_eq!(items[3].start_ms, 599_000);
+    assert_eq!(items[3].url(), Some("https://www.youtube.com/redirect?q=https%3A%2F%2Fdocs.rs%2Frustube"));
+    assert!(items[3].image.is_empty());
+
+    let endscreen = video.video_info().player_response.endscreen.as_ref().unwrap();
+    assert_eq!(endscreen.endscreen_renderer.start_ms, Some(594_000));
+}
+
+#[test]
+fn cards() {
+    let video = video();
+    let cards = video.cards();
+
+    // the poll is skipped
+    assert_eq!(cards.len(), 2);
+    assert!(matches!(cards[0].content, CardContent::VideoInfoCardContentRenderer(_)));
+    assert!(matches!(cards[1].content, CardContent::PlaylistInfoCardContentRenderer(_)));
+
+    let video_card = &cards[0];
+    assert_eq!(video_card.title(), "How the cipher works");
+    assert_eq!(video_card.video_id().unwrap().as_str(), "9bZkp7q19f0");
+    assert_eq!(video_card.playlist_id(), None);
+    assert_eq!((video_card.start_ms(), video_card.end_ms()), (Some(12_000), Some(17_000)));
+    assert_eq!(video_card.cue_ranges[0].teaser_duration_ms, Some(6_000));
+    assert_eq!(video_card.thumbnails().best().unwrap().url, "https://i.ytimg.com/vi/9bZkp7q19f0/hqdefault.jpg");
+    assert_eq!(video_card.card_id.as_deref(), Some("1"));
+
+    // the card is active in two ranges
+    let playlist_card = &cards[1];
+    assert_eq!(playlist_card.title(), "Downloading videos");
+    assert_eq!(playlist_card.playlist_id(), Some("PLbpi6ZahtOH6Blw3RGYpWkSByi_T7Rygb"));
+    assert_eq!((playlist_card.start_ms(), playlist_card.end_ms()), (Some(60_000), Some(95_000)));
+
+    let collection = &video.video_info().player_response.cards.as_ref().unwrap().card_collection_renderer;
+    assert_eq!(collection.header_text.as_ref().unwrap().to_text(), "From Rustube");
+}
+
+#[test]
+fn no_annotations() {
+    let video = video_from_fixture("multi_audio_player_response.json");
+    assert!(video.endscreen_items().is_empty());
+    assert!(video.cards().is_empty());
+
+    // broken annotations don't affect the rest of the player response
+    let mut player_response = player_response();
+    player_response["endscreen"]["endscreenRenderer"]["elements"] = json!([{ "endscreenElementRenderer": 42 }, "element"]);
+    player_response["cards"]["cardCollectionRenderer"]["cards"] = Value::Null;
+    let video = video_from_player_response(player_response);
+    assert!(video.endscreen_items().is_empty());
+    assert!(video.cards().is_empty());
+    assert!(!video.streams().is_empty());
+}
+
+#[test]
+fn serialize_round_trip() {
+    let video = video();
+    let player_response = &video.video_info().player_response;
+
+    let serialized = serde_json::to_string(player_response).unwrap();
+    let value: Value = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(value["endscreen"]["endscreenRenderer"]["elements"].as_array().unwrap().len(), 5);
+    assert!(value["cards"]["cardCollectionRenderer"]["cards"][0]["cardRenderer"].is_object());
+
+    let deserialized: PlayerResponse = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.endscreen, player_response.endscreen);
+    assert_eq!(deserialized.cards, player_response.cards);
+}
@@ -0,0 +1,215 @@
+#![cfg(feature = "download")]
+
+use std::path::PathBuf;
+
+use common::*;
+use rustube::{ArchiveOptions, Error, Video};
+use rustube::archive::{ARCHIVE_METADATA_FILE, ARCHIVE_SCHEMA_VERSION, ArchiveMetadata};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+
+/// All files of the multi audio fixture, in the order of its formats.
+const FILES: [&str; 8] = [
+    "18_360p.mp4",
+    "137_1080p.mp4",
+    "140_130k_de-DE.3.mp4",
+    "140_130k_en-US.4.mp4",
+    "140_130k_es-US.3.mp4",
+    "251_141k_de-DE.3.webm",
+    "251_140k_en-US.4.webm",
+    "251_141k_es-US.3.webm",
+];
+
+/// The body of the `i`th stream.
+fn body(i: usize) -> Vec<u8> {
+    (0..1000 + i).map(|b| (b % 251) as u8 ^ i as u8).collect()
+}
+
+/// Serves the body of the `i`th stream at `/streams/<i>`, except for the streams in `failing`.
+async fn server(failing: &'static [usize]) -> MockServer {
+    MockServer::start(move |request| {
+        let i: usize = request.path
+            .trim_start_matches("/streams/")
+            .parse()
+            .unwrap();
+        match failing.contains(&i) {
+            true => MockResponse::status(403),
+            false => MockResponse::ok(body(i)),
+        }
+    }).await
+}
+
+/// The multi audio fixture, with the streams pointing to `server`.
+fn video(server: &MockServer) -> Video {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    let mut i = 0;
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in player_response["streamingData"][formats].as_array_mut().unwrap() {
+            format["contentLength"] = body(i).len().to_string().into();
+            i += 1;
+        }
+    }
+
+    let (video_info, mut streams) = video_from_player_response(player_response).into_parts();
+    for (i, stream) in streams.iter_mut().enumerate() {
+        stream.signature_cipher.url = server.url.join(&format!("streams/{}", i)).unwrap();
+    }
+    Video::from_parts(video_info, streams)
+}
+
+fn file_names(paths: &[(u64, PathBuf)]) -> Vec<String> {
+    let mut names: Vec<String> = paths
+        .iter()
+        .map(|(_, path)| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+fn sorted(files: &[&str]) -> Vec<String> {
+    let mut files: Vec<String> = files.iter().map(|&file| file.to_owned()).collect();
+    files.sort();
+    files
+}
+
+#[test_log::test(tokio::test)]
+async fn archives_all_streams() {
+    let server = server(&[]).await;
+    let dir = temp_dir("all");
+
+    let report = video(&server)
+        .download_all_to_dir(&dir, ArchiveOptions::new())
+        .await
+        .unwrap();
+
+    let video_dir = dir.join(VIDEO_ID);
+    assert_eq!(report.dir, video_dir);
+    assert!(report.is_complete());
+    assert!(report.skipped.is_empty());
+    assert_eq!(file_names(&report.downloaded), sorted(&FILES));
+    for (i, file) in FILES.iter().enumerate() {
+        assert_eq!(std::fs::read(video_dir.join(file)).unwrap(), body(i), "{}", file);
+    }
+
+    let metadata: ArchiveMetadata = serde_json::from_slice(
+        &std::fs::read(video_dir.join(ARCHIVE_METADATA_FILE)).unwrap()
+    ).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(metadata.schema_version, ARCHIVE_SCHEMA_VERSION);
+    assert_eq!(metadata.video_details.video_id.as_str(), VIDEO_ID);
+    let itags: Vec<u64> = metadata.streams.iter().map(|stream| stream.itag).collect();
+    assert_eq!(itags, [18, 137, 140, 140, 140, 251, 251, 251]);
+    let en = metadata.streams
+        .iter()
+        .find(|stream| stream.file == "140_130k_en-US.4.mp4")
+        .unwrap();
+    assert_eq!(en.mime.essence_str(), "audio/mp4");
+    assert_eq!(en.audio_track.as_ref().unwrap().id, "en-US.4");
+    assert_eq!(en.content_length, body(3).len() as u64);
+}
+
+#[test_log::test(tokio::test)]
+async fn skips_complete_files() {
+    let server = server(&[]).await;
+    let dir = temp_dir("skip");
+    let video = video(&server);
+
+    video.download_all_to_dir(&dir, ArchiveOptions::new()).await.unwrap();
+    let truncated = dir.join(VIDEO_ID).join(FILES[1]);
+    std::fs::write(&truncated, &body(1)[..10]).unwrap();
+
+    let report = video
+        .download_all_to_dir(&dir, ArchiveOptions::new().concurrency(3))
+        .await
+        .unwrap();
+    let redownloaded = std::fs::read(&truncated).unwrap();
+    let metadata = std::fs::read(dir.join(VIDEO_ID).join(ARCHIVE_METADATA_FILE)).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(report.downloaded, [(137, truncated)]);
+    assert_eq!(report.skipped.len(), FILES.len() - 1);
+    assert_eq!(redownloaded, body(1));
+    assert_eq!(server.requests().len(), FILES.len() + 1);
+    let metadata: ArchiveMetadata = serde_json::from_slice(&metadata).unwrap();
+    assert_eq!(metadata.streams.len(), FILES.len());
+}
+
+#[test_log::test(tokio::test)]
+async fn skips_complete_files_without_requests() {
+    let server = server(&[]).await;
+    let dir = temp_dir("skip_unknown");
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in player_response["streamingData"][formats].as_array_mut().unwrap() {
+            format.as_object_mut().unwrap().remove("contentLength");
+        }
+    }
+    let (video_info, mut streams) = video_from_player_response(player_response).into_parts();
+    for (i, stream) in streams.iter_mut().enumerate() {
+        stream.signature_cipher.url = server.url.join(&format!("streams/{}", i)).unwrap();
+    }
+    let video = Video::from_parts(video_info, streams);
+
+    video.download_all_to_dir(&dir, ArchiveOptions::new()).await.unwrap();
+    let report = video
+        .download_all_to_dir(&dir, ArchiveOptions::new())
+        .await
+        .unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert!(report.downloaded.is_empty());
+    assert_eq!(file_names(&report.skipped), sorted(&FILES));
+    assert!(server.requests().iter().all(|request| request.method == "GET"));
+    assert_eq!(server.requests().len(), FILES.len());
+}
+
+#[test_log::test(tokio::test)]
+async fn collects_failures() {
+    let server = server(&[1, 6]).await;
+    let dir = temp_dir("failures");
+
+    let report = video(&server)
+        .download_all_to_dir(&dir, ArchiveOptions::new().concurrency(8))
+        .await
+        .unwrap();
+    let video_dir = dir.join(VIDEO_ID);
+    let failed_files_exist = video_dir.join(FILES[1]).exists() || video_dir.join(FILES[6]).exists();
+    let metadata: ArchiveMetadata = serde_json::from_slice(
+        &std::fs::read(video_dir.join(ARCHIVE_METADATA_FILE)).unwrap()
+    ).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert!(!report.is_complete());
+    assert_eq!(report.downloaded.len(), FILES.len() - 2);
+    let failed: Vec<u64> = report.failed.iter().map(|(itag, _)| *itag).collect();
+    assert_eq!(failed, [137, 251]);
+    assert!(report.failed.iter().all(|(_, err)| matches!(err, Error::Forbidden { .. })), "{:?}", report.failed);
+    assert!(!failed_files_exist);
+    assert_eq!(metadata.streams.len(), FILES.len() - 2);
+    assert!(metadata.streams.iter().all(|stream| stream.file != FILES[1] && stream.file != FILES[6]));
+}
+
+#[test_log::test(tokio::test)]
+async fn filtered_without_metadata() {
+    let server = server(&[]).await;
+    let dir = temp_dir("filtered");
+
+    let options = ArchiveOptions::new()
+        .filter(|stream| stream.audio_track.as_ref().is_some_and(|track| track.is_language("en")))
+        .write_metadata(false)
+        .concurrency(1);
+    let report = video(&server)
+        .download_all_to_dir(&dir, options)
+        .await
+        .unwrap();
+    let metadata_exists = dir.join(VIDEO_ID).join(ARCHIVE_METADATA_FILE).exists();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(file_names(&report.downloaded), sorted(&[FILES[3], FILES[6]]));
+    assert_eq!(server.requests().len(), 2);
+    assert!(!metadata_exists);
+}
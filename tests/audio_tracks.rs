@@ -0,0 +1,50 @@
+#![cfg(feature = "stream")]
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+const MULTI_AUDIO: &str = "multi_audio_player_response.json";
+
+#[test]
+fn audio_tracks_are_deserialized() {
+    let video = video_from_fixture(MULTI_AUDIO);
+
+    let tracks = video.audio_tracks();
+    let ids: Vec<&str> = tracks.iter().map(|track| track.id.as_str()).collect();
+    assert_eq!(ids, ["de-DE.3", "en-US.4", "es-US.3"]);
+
+    let default: Vec<_> = tracks.iter().filter(|track| track.is_default).collect();
+    assert_eq!(default.len(), 1);
+    assert_eq!(default[0].display_name, "English (United States) original");
+    assert_eq!(default[0].language(), "en-US");
+
+    let progressive = video.streams().iter().find(|stream| stream.itag == 18).unwrap();
+    assert!(progressive.audio_track.is_none());
+}
+
+#[test]
+fn best_audio_prefers_default_track() {
+    let video = video_from_fixture(MULTI_AUDIO);
+
+    // the dubbed tracks have a slightly higher bitrate, but the original should still win
+    let best = video.best_audio().unwrap();
+    assert_eq!(best.itag, 251);
+    assert_eq!(best.audio_track.as_ref().unwrap().id, "en-US.4");
+}
+
+#[test]
+fn best_audio_for_language() {
+    let video = video_from_fixture(MULTI_AUDIO);
+
+    let german = video.best_audio_for_language("de").unwrap();
+    assert_eq!(german.itag, 251);
+    assert_eq!(german.audio_track.as_ref().unwrap().id, "de-DE.3");
+
+    let spanish = video.best_audio_for_language("ES-us").unwrap();
+    assert_eq!(spanish.audio_track.as_ref().unwrap().id, "es-US.3");
+
+    assert!(video.best_audio_for_language("es-ES").is_none());
+    assert!(video.best_audio_for_language("fr").is_none());
+}
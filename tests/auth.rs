@@ -0,0 +1,216 @@
+#![cfg(feature = "fetch")]
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde_json::json;
+
+use common::*;
+use rustube::{Error, Id, InnertubeClient, VideoFetcher};
+use rustube::fetcher::auth::{AuthConfig, DeviceCode, OAuth};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+
+/// A server, that serves the video, and the OAuth endpoints. The token endpoint responds with
+/// `token_responses` in order, and repeats the last one.
+async fn server(token_responses: Vec<MockResponse>) -> MockServer {
+    let token_requests = AtomicUsize::new(0);
+    MockServer::start(move |request| {
+        let path = request.path.as_str();
+        if path.starts_with("/watch") {
+            MockResponse::ok(format!(
+                "<html><script>var ytInitialPlayerResponse = {};</script>\
+                <script src=\"{}\"></script></html>",
+                json_fixture("multi_audio_player_response.json"), BASE_JS_PATH,
+            ))
+        } else if path == BASE_JS_PATH {
+            MockResponse::ok(BASE_JS)
+        } else if path.starts_with("/youtubei/v1/player") {
+            MockResponse::json(&json_fixture("multi_audio_player_response.json"))
+        } else if path == "/device/code" {
+            MockResponse::json(&json!({
+                "device_code": "device-code",
+                "user_code": "ABC-DEF-GHI",
+                "verification_url": "https://www.google.com/device",
+                "expires_in": 1800,
+                "interval": 0,
+            }))
+        } else if path == "/token" {
+            let i = token_requests.fetch_add(1, Ordering::SeqCst);
+            token_responses[i.min(token_responses.len() - 1)].clone()
+        } else {
+            MockResponse::status(404)
+        }
+    }).await
+}
+
+fn token_error(status: u16, error: &str) -> MockResponse {
+    MockResponse { status, ..MockResponse::json(&json!({ "error": error })) }
+}
+
+fn token(access_token: &str, refresh_token: Option<&str>) -> MockResponse {
+    let mut token = json!({ "access_token": access_token, "expires_in": 3600, "token_type": "Bearer" });
+    if let Some(refresh_token) = refresh_token {
+        token["refresh_token"] = refresh_token.into();
+    }
+    MockResponse::json(&token)
+}
+
+fn token_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rustube_oauth_{}_{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn fetcher(server: &MockServer, oauth: OAuth) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap().into_owned())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_innertube_clients(vec![InnertubeClient::WebEmbedded])
+        .with_oauth(oauth.with_oauth_url(server.url.clone()))
+}
+
+fn form(request: &MockRequest) -> Vec<(String, String)> {
+    url::form_urlencoded::parse(&request.body).into_owned().collect()
+}
+
+fn player_authorization(server: &MockServer) -> Vec<Option<String>> {
+    server
+        .requests()
+        .iter()
+        .filter(|request| request.path.starts_with("/youtubei/v1/player"))
+        .map(|request| request.header("Authorization").map(str::to_owned))
+        .collect()
+}
+
+#[test_log::test(tokio::test)]
+async fn device_flow() {
+    let server = server(vec![
+        token_error(428, "authorization_pending"),
+        token("access", Some("refresh")),
+    ]).await;
+    let path = token_path("device_flow");
+    let codes = Arc::new(Mutex::new(Vec::new()));
+    let oauth = {
+        let codes = Arc::clone(&codes);
+        OAuth::new(&path).on_device_code(move |code| codes.lock().unwrap().push(code.clone()))
+    };
+
+    fetcher(&server, oauth).fetch().await.unwrap();
+
+    assert_eq!(*codes.lock().unwrap(), [DeviceCode {
+        user_code: "ABC-DEF-GHI".to_owned(),
+        verification_url: "https://www.google.com/device".to_owned(),
+        expires_in: 1800,
+    }]);
+    assert_eq!(player_authorization(&server), [Some("Bearer access".to_owned())]);
+    // the TV client is requested first
+    let player_request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path.starts_with("/youtubei/v1/player"))
+        .unwrap();
+    assert_eq!(player_request.json()["context"]["client"]["clientName"], InnertubeClient::TvEmbedded.name());
+
+    let token_requests: Vec<_> = server
+        .requests()
+        .into_iter()
+        .filter(|request| request.path == "/token")
+        .collect();
+    assert_eq!(token_requests.len(), 2);
+    assert!(form(&token_requests[0]).contains(&("device_code".to_owned(), "device-code".to_owned())));
+
+    let token: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(token["access_token"], "access");
+    assert_eq!(token["refresh_token"], "refresh");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+    assert!(!path.with_extension("tmp").exists());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test_log::test(tokio::test)]
+async fn refreshes_expired_token() {
+    let server = server(vec![token("new access", None)]).await;
+    let path = token_path("refresh");
+    std::fs::write(&path, json!({
+        "access_token": "old access",
+        "refresh_token": "refresh",
+        "expires_at": 0,
+    }).to_string()).unwrap();
+
+    fetcher(&server, OAuth::new(&path)).fetch().await.unwrap();
+
+    assert!(server.requests().iter().all(|request| request.path != "/device/code"));
+    let refresh_request = server.requests().into_iter().find(|request| request.path == "/token").unwrap();
+    let form = form(&refresh_request);
+    assert!(form.contains(&("grant_type".to_owned(), "refresh_token".to_owned())));
+    assert!(form.contains(&("refresh_token".to_owned(), "refresh".to_owned())));
+    assert_eq!(player_authorization(&server)[0].as_deref(), Some("Bearer new access"));
+
+    // the refresh token is kept, since the response did not contain a new one
+    let token: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(token["access_token"], "new access");
+    assert_eq!(token["refresh_token"], "refresh");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test_log::test(tokio::test)]
+async fn expired_refresh_token() {
+    let server = server(vec![token_error(400, "invalid_grant")]).await;
+    let path = token_path("expired");
+    std::fs::write(&path, json!({
+        "access_token": "old access",
+        "refresh_token": "revoked",
+        "expires_at": 0,
+    }).to_string()).unwrap();
+
+    let err = fetcher(&server, OAuth::new(&path)).fetch().await.unwrap_err();
+
+    assert!(matches!(err, Error::Authentication(_)), "{:?}", err);
+    assert!(player_authorization(&server).is_empty());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test_log::test(tokio::test)]
+async fn denied_device_flow() {
+    let server = server(vec![token_error(403, "access_denied")]).await;
+
+    let err = fetcher(&server, OAuth::in_memory()).fetch().await.unwrap_err();
+
+    assert!(matches!(err, Error::Authentication(_)), "{:?}", err);
+}
+
+#[test_log::test(tokio::test)]
+async fn authenticated_with_cookies() {
+    let server = server(Vec::new()).await;
+    let path = std::env::temp_dir().join(format!("rustube_auth_cookies_{}.txt", std::process::id()));
+    std::fs::write(&path, format!(
+        "{}\tFALSE\t/\tFALSE\t0\tSID\tsecret\n",
+        server.url.host_str().unwrap(),
+    )).unwrap();
+
+    VideoFetcher::from_id_authenticated(
+        Id::from_str(VIDEO_ID).unwrap().into_owned(),
+        AuthConfig::new().cookies(&path),
+    )
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_innertube_clients(vec![InnertubeClient::WebEmbedded])
+        .fetch()
+        .await
+        .unwrap();
+
+    let watch_request = server.requests().into_iter().find(|request| request.path.starts_with("/watch")).unwrap();
+    assert!(watch_request.header("Cookie").unwrap().contains("SID=secret"));
+    assert_eq!(player_authorization(&server), [None]);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,189 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{Error, Id, InnertubeClient, VideoFetcher};
+use rustube::video_info::player_response::playability_status::{PlayabilityKind, PlayabilityStatus};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+const PO_TOKEN: &str = "MnQ8Ky0tbzB3XzRHMUstRmhQ";
+
+/// The response of the player endpoint, when YouTube asks to confirm you're not a bot.
+fn bot_check() -> Value {
+    json_fixture("bot_check_player_response.json")
+}
+
+fn playable() -> Value {
+    json_fixture("multi_audio_player_response.json")
+}
+
+fn fetcher(server: &MockServer) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+}
+
+/// The client and po_token of every request to the player endpoint.
+fn player_requests(server: &MockServer) -> Vec<(String, Option<String>)> {
+    server
+        .requests()
+        .iter()
+        .filter(|request| request.path.starts_with("/youtubei/v1/player"))
+        .map(|request| {
+            let body = request.json();
+            (
+                body["context"]["client"]["clientName"].as_str().unwrap().to_owned(),
+                body["serviceIntegrityDimensions"]["poToken"].as_str().map(str::to_owned),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn detects_bot_check() {
+    let status: PlayabilityStatus = serde_json::from_value(bot_check()["playabilityStatus"].clone()).unwrap();
+    assert!(status.is_bot_check());
+    assert_eq!(status.kind(), PlayabilityKind::BotCheckRequired);
+    assert_eq!(status.reason().as_deref(), Some("Sign in to confirm you’re not a bot"));
+
+    // the reason of the error screen is enough
+    let mut raw = bot_check()["playabilityStatus"].clone();
+    raw.as_object_mut().unwrap().remove("reason");
+    let status: PlayabilityStatus = serde_json::from_value(raw).unwrap();
+    assert_eq!(status.kind(), PlayabilityKind::BotCheckRequired);
+    assert_eq!(status.reason().as_deref(), Some("Sign in to confirm you’re not a bot"));
+
+    let status: PlayabilityStatus = serde_json::from_value(json!({
+        "status": "ERROR",
+        "reason": "Sign in to confirm you're not a bot",
+        "contextParams": "Q0FFU0FnZ0I="
+    })).unwrap();
+    assert_eq!(status.kind(), PlayabilityKind::BotCheckRequired);
+}
+
+#[test]
+fn other_statuses_are_no_bot_check() {
+    let cases = [
+        (json!({
+            "status": "LOGIN_REQUIRED",
+            "reason": "Sign in to confirm your age",
+            "contextParams": "Q0FFU0FnZ0I="
+        }), PlayabilityKind::LoginRequired),
+        (json!({
+            "status": "ERROR",
+            "reason": "This content isn't available.",
+            "contextParams": "Q0FFU0FnZ0I="
+        }), PlayabilityKind::Error),
+        (playable()["playabilityStatus"].clone(), PlayabilityKind::Ok),
+    ];
+
+    for (raw, kind) in cases {
+        let status: PlayabilityStatus = serde_json::from_value(raw).unwrap();
+        assert!(!status.is_bot_check());
+        assert_eq!(status.kind(), kind);
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn watch_page_bot_check_fails_fetch() {
+    let server = mock_youtube(bot_check(), BASE_JS, |_| playable()).await;
+
+    let err = fetcher(&server).fetch().await.unwrap_err();
+    assert!(matches!(err, Error::BotCheck(_)), "{:?}", err);
+    assert!(err.is_bot_check());
+    assert!(player_requests(&server).is_empty());
+}
+
+#[test_log::test(tokio::test)]
+async fn api_bot_check_fails_fetch() {
+    let server = mock_youtube(playable(), BASE_JS, |_| bot_check()).await;
+
+    let err = fetcher(&server)
+        .with_innertube_clients(vec![InnertubeClient::Android, InnertubeClient::WebEmbedded])
+        .fetch()
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::BotCheck(status) => assert_eq!(status.kind(), PlayabilityKind::BotCheckRequired),
+        err => panic!("expected Error::BotCheck, got {:?}", err),
+    }
+    assert_eq!(player_requests(&server), [
+        ("ANDROID".to_owned(), None),
+        ("WEB_EMBEDDED_PLAYER".to_owned(), None),
+    ]);
+}
+
+#[test_log::test(tokio::test)]
+async fn retries_once_with_po_token() {
+    let server = mock_youtube_with(bot_check(), BASE_JS, |body| {
+        match body["serviceIntegrityDimensions"]["poToken"].as_str() {
+            Some(PO_TOKEN) => playable(),
+            _ => bot_check(),
+        }
+    }).await;
+
+    let descrambler = fetcher(&server)
+        .with_po_token(PO_TOKEN)
+        .fetch()
+        .await
+        .unwrap();
+
+    assert!(descrambler.video_info().player_response.streaming_data.is_some());
+    assert_eq!(player_requests(&server), [
+        ("WEB".to_owned(), None),
+        ("WEB".to_owned(), Some(PO_TOKEN.to_owned())),
+    ]);
+}
+
+#[test_log::test(tokio::test)]
+async fn gives_up_after_retrying_with_po_token() {
+    let server = mock_youtube(playable(), BASE_JS, |_| bot_check()).await;
+
+    let err = fetcher(&server)
+        .with_innertube_clients(vec![InnertubeClient::Android])
+        .with_po_token(PO_TOKEN)
+        .fetch()
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::BotCheck(_)), "{:?}", err);
+    assert_eq!(player_requests(&server), [
+        ("ANDROID".to_owned(), None),
+        ("ANDROID".to_owned(), Some(PO_TOKEN.to_owned())),
+    ]);
+}
+
+#[test_log::test(tokio::test)]
+async fn partial_bot_check_fails_all_clients() {
+    let server = mock_youtube(playable(), BASE_JS, |client| match client {
+        "ANDROID" => bot_check(),
+        _ => json!({
+            "playabilityStatus": {
+                "status": "ERROR",
+                "reason": "This content isn't available.",
+                "contextParams": "Q0FFU0FnZ0I="
+            }
+        }),
+    }).await;
+
+    let err = fetcher(&server)
+        .with_innertube_clients(vec![InnertubeClient::Android, InnertubeClient::WebEmbedded])
+        .fetch()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_bot_check());
+    match err {
+        Error::AllClientsFailed(failures) => {
+            assert!(matches!(failures[0], (InnertubeClient::Android, Error::BotCheck(_))));
+            assert!(matches!(failures[1], (InnertubeClient::WebEmbedded, Error::VideoUnavailable(_))));
+        }
+        err => panic!("expected Error::AllClientsFailed, got {:?}", err),
+    }
+}
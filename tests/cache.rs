@@ -0,0 +1,106 @@
+#![cfg(feature = "cache")]
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use rustube::{Id, VideoFetcher};
+use rustube::cache::{self, CachedResponse, CacheMode, FileCache, ResponseCache};
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustube_cache_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Serves a watch page without streams, so the player endpoint is requested as well.
+async fn server() -> MockServer {
+    let mut watch_player_response = json_fixture("multi_audio_player_response.json");
+    watch_player_response.as_object_mut().unwrap().remove("streamingData");
+    mock_youtube(watch_player_response, BASE_JS, |_| json_fixture("multi_audio_player_response.json")).await
+}
+
+fn fetcher(server: &MockServer, cache: &Arc<FileCache>, mode: CacheMode) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap().into_owned())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_response_cache(cache.clone())
+        .with_cache_mode(mode)
+}
+
+#[test_log::test(tokio::test)]
+async fn second_fetch_is_served_from_the_cache() {
+    let server = server().await;
+    let cache = Arc::new(FileCache::new(temp_dir("read_write")));
+
+    let first = fetcher(&server, &cache, CacheMode::ReadWrite).fetch().await.unwrap();
+    let requests = server.requests().len();
+    assert!(requests >= 3, "{:?}", server.requests());
+    assert!(server.requests().iter().any(|request| request.path.starts_with("/youtubei/v1/player")));
+
+    let second = fetcher(&server, &cache, CacheMode::ReadWrite).fetch().await.unwrap();
+    assert_eq!(server.requests().len(), requests);
+    assert_eq!(second.video_info(), first.video_info());
+    let _ = cache.clear();
+}
+
+#[test_log::test(tokio::test)]
+async fn modes() {
+    let server = server().await;
+    let cache = Arc::new(FileCache::new(temp_dir("modes")));
+
+    // neither writes, nor reads
+    fetcher(&server, &cache, CacheMode::Disabled).fetch().await.unwrap();
+    assert!(!cache.dir().exists());
+    let requests = server.requests().len();
+
+    // reads, but nothing's cached yet, and doesn't write
+    fetcher(&server, &cache, CacheMode::ReadOnly).fetch().await.unwrap();
+    assert!(!cache.dir().exists());
+    assert_eq!(server.requests().len(), 2 * requests);
+
+    // writes, but doesn't read
+    fetcher(&server, &cache, CacheMode::Refresh).fetch().await.unwrap();
+    fetcher(&server, &cache, CacheMode::Refresh).fetch().await.unwrap();
+    assert_eq!(server.requests().len(), 4 * requests);
+
+    fetcher(&server, &cache, CacheMode::ReadOnly).fetch().await.unwrap();
+    assert_eq!(server.requests().len(), 4 * requests);
+    let _ = cache.clear();
+}
+
+#[test]
+fn file_cache_respects_the_ttl() {
+    let cache = FileCache::new(temp_dir("ttl"));
+    let url = url::Url::parse("https://www.youtube.com/watch?v=fDzQ3kUg8Ss").unwrap();
+    let key = cache::key("GET", &url, &[]);
+    assert_ne!(key, cache::key("POST", &url, &[]));
+    assert_ne!(key, cache::key("GET", &url, b"{}"));
+    assert_eq!(cache.get(&key), None);
+
+    let response = CachedResponse::new(b"first line\nsecond line".to_vec());
+    cache.put(&key, response.clone(), Duration::from_secs(60));
+    let cached = cache.get(&key).unwrap();
+    assert_eq!(cached.body, response.body);
+    assert_eq!(
+        cached.stored_at.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs(),
+        response.stored_at.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs(),
+    );
+
+    let old = CachedResponse {
+        body: b"old".to_vec(),
+        stored_at: SystemTime::now() - Duration::from_secs(120),
+    };
+    cache.put(&key, old, Duration::from_secs(60));
+    assert_eq!(cache.get(&key), None);
+    assert!(!cache.dir().join(&key).exists());
+    cache.clear().unwrap();
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "callback")]
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use common::*;
+use rustube::{Callback, OnCompleteType, OnProgressType, Stream};
+
+#[macro_use]
+mod common;
+
+fn assert_send<T: Send>() {}
+
+#[test]
+fn is_send() {
+    assert_send::<Callback<'static>>();
+    assert_send::<OnProgressType<'static>>();
+    assert_send::<OnCompleteType<'static>>();
+
+    // the download future itself can be spawned
+    fn download_is_send(stream: &'static Stream, callback: Callback<'static>) {
+        fn assert_future_send<F: std::future::Future + Send>(_: F) {}
+        assert_future_send(stream.download_with_callback(callback));
+    }
+    let _ = download_is_send;
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
+async fn spawned_download() {
+    let server = MockServer::start(|_| MockResponse::ok(stream_body())).await;
+    let stream = progressive_stream_at(&server, "stream", None);
+    let path = temp_dir("callback").join("video.mp4");
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let completed = Arc::new(Mutex::new(None));
+    let callback = {
+        let progress = Arc::clone(&progress);
+        let completed = Arc::clone(&completed);
+        Callback::new()
+            .connect_on_progress_closure(move |args| progress.lock().unwrap().push(args.current_chunk))
+            .connect_on_complete_closure(move |path| *completed.lock().unwrap() = path)
+    };
+
+    let target = path.clone();
+    tokio::spawn(async move { stream.download_to_with_callback(&target, callback).await })
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(progress.lock().unwrap().last(), Some(&stream_body().len()));
+    assert_eq!(*completed.lock().unwrap(), Some::<PathBuf>(path.clone()));
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "descramble")]
+
+use std::time::Duration;
+
+use common::*;
+use rustube::{Error, LanguageTag, Video};
+use rustube::captions::{Cue, Transcript};
+
+#[macro_use]
+mod common;
+
+/// The multi audio fixture with the captions fixture, whose tracks point to `server`, if there
+/// is one.
+fn video(server: Option<&MockServer>) -> Video {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    let mut captions = json_fixture("captions.json");
+    if let Some(server) = server {
+        for track in captions["playerCaptionsTracklistRenderer"]["captionTracks"].as_array_mut().unwrap() {
+            let url = url::Url::parse(track["baseUrl"].as_str().unwrap()).unwrap();
+            let mut mocked = server.url.join("api/timedtext").unwrap();
+            mocked.set_query(url.query());
+            track["baseUrl"] = mocked.as_str().into();
+        }
+    }
+    player_response["captions"] = captions;
+    video_from_player_response(player_response)
+}
+
+#[test]
+fn caption_tracks() {
+    let video = video(None);
+    let tracks: Vec<(&str, String, bool, bool)> = video
+        .caption_tracks()
+        .iter()
+        .map(|track| (track.language_code.as_str(), track.display_name(), track.is_auto_generated(), track.is_translatable))
+        .collect();
+
+    assert_eq!(tracks, [
+        ("en", "English".to_owned(), false, true),
+        ("en", "English (auto-generated)".to_owned(), true, true),
+        ("pt-BR", "Portuguese (Brazil)".to_owned(), false, false),
+    ]);
+    let languages = &video.video_info().player_response.captions.as_ref().unwrap()
+        .player_captions_tracklist_renderer
+        .translation_languages;
+    assert_eq!(languages[1].language_code, "zh-Hant");
+    assert_eq!(languages[1].language_name.to_text(), "Chinese (Traditional)");
+
+    assert!(video_from_fixture("multi_audio_player_response.json").caption_tracks().is_empty());
+}
+
+#[test]
+fn parses_transcripts() {
+    let transcript = Transcript::parse(&read_fixture("captions.xml")).unwrap();
+
+    assert_eq!(transcript.cues.len(), 4);
+    assert_eq!(transcript.cues[1], Cue {
+        start: Duration::from_millis(2340),
+        duration: Duration::from_millis(3006),
+        text: "it's been a while & a lot happened".to_owned(),
+    });
+
+    assert_eq!(transcript.to_srt(), "\
+1
+00:00:00,240 --> 00:00:02,340
+Hey everyone, welcome back
+
+2
+00:00:02,340 --> 00:00:05,346
+it's been a while & a lot happened
+
+3
+00:00:05,346 --> 00:00:06,846
+[Music]
+
+4
+01:02:05,500 --> 01:02:09,500
+see you
+next time
+
+");
+    assert!(transcript.to_vtt().starts_with("WEBVTT\n\n00:00:00.240 --> 00:00:02.340\nHey everyone, welcome back\n\n"));
+    assert_eq!(Transcript::parse("<transcript/>").unwrap().to_srt(), "");
+}
+
+#[test]
+fn invalid_transcripts() {
+    for xml in ["", "<transcript>", "<timedtext/>", r#"<transcript><text start="x">a</text></transcript>"#, r#"<transcript><text dur="-1">a</text></transcript>"#] {
+        assert!(matches!(Transcript::parse(xml), Err(Error::UnexpectedResponse(_))), "{}", xml);
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn downloads_tracks() {
+    let server = MockServer::start(|_| MockResponse::ok(read_fixture("captions.xml"))).await;
+    let video = video(Some(&server));
+    let client = rustube::reqwest::Client::new();
+    let tracks = video.caption_tracks();
+
+    let transcript = tracks[0].download(&client).await.unwrap();
+    assert_eq!(transcript.cues.len(), 4);
+
+    let german: LanguageTag = "de".parse().unwrap();
+    tracks[1].download_translated(&client, &german).await.unwrap();
+    let requests = server.requests();
+    assert!(requests[0].path.ends_with("&lang=en"), "{}", requests[0].path);
+    assert!(requests[1].path.ends_with("&kind=asr&lang=en&tlang=de"), "{}", requests[1].path);
+
+    // the Portuguese track is not translatable
+    assert!(matches!(tracks[2].download_translated(&client, &german).await, Err(Error::Custom(_))));
+    assert_eq!(server.requests().len(), 2);
+}
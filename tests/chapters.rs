@@ -0,0 +1,70 @@
+#![cfg(feature = "descramble")]
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use common::*;
+use rustube::chapters::Chapter;
+
+#[macro_use]
+mod common;
+
+fn chapter(secs: u64, title: &str) -> Chapter {
+    Chapter { start: Duration::from_secs(secs), title: title.to_owned() }
+}
+
+#[test]
+fn from_description() {
+    let description = "My video\n\n0:00 Intro\n1:30 - The cipher\n  1:02:03   Outro  \nThanks for watching";
+    assert_eq!(Chapter::from_description(description), [
+        chapter(0, "Intro"),
+        chapter(90, "The cipher"),
+        chapter(3723, "Outro"),
+    ]);
+}
+
+#[test]
+fn from_description_requires_youtubes_rules() {
+    // less than three timestamps
+    assert!(Chapter::from_description("0:00 Intro\n1:30 Outro").is_empty());
+    // the first timestamp is not 0:00
+    assert!(Chapter::from_description("0:10 Intro\n1:30 Middle\n2:00 Outro").is_empty());
+    // not ascending
+    assert!(Chapter::from_description("0:00 Intro\n2:00 Middle\n1:30 Outro").is_empty());
+    // plain numbers are no timestamps
+    assert!(Chapter::from_description("0 Intro\n90 Middle\n120 Outro").is_empty());
+    assert!(Chapter::from_description("").is_empty());
+}
+
+#[test]
+fn from_initial_data() {
+    let initial_data = json!({
+        "playerOverlays": { "playerOverlayRenderer": { "decoratedPlayerBarRenderer": { "decoratedPlayerBarRenderer": {
+            "playerBar": { "multiMarkersPlayerBarRenderer": { "markersMap": [
+                { "key": "HEATSEEKER", "value": { "heatmap": {} } },
+                { "key": "DESCRIPTION_CHAPTERS", "value": { "chapters": [
+                    { "chapterRenderer": { "title": { "simpleText": "Outro" }, "timeRangeStartMillis": 90500 } },
+                    { "chapterRenderer": { "title": { "runs": [{ "text": "In" }, { "text": "tro" }] }, "timeRangeStartMillis": 0 } },
+                    { "chapterRenderer": { "title": { "simpleText": "No start" } } }
+                ] } }
+            ] } }
+        } } } }
+    });
+
+    assert_eq!(Chapter::from_initial_data(&initial_data), [
+        chapter(0, "Intro"),
+        Chapter { start: Duration::from_millis(90_500), title: "Outro".to_owned() },
+    ]);
+    assert!(Chapter::from_initial_data(&json!({})).is_empty());
+}
+
+#[test]
+fn video_chapters_fall_back_to_the_description() {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    assert!(video_from_player_response(player_response.clone()).chapters().is_empty());
+
+    player_response["videoDetails"]["shortDescription"] = "0:00 Intro\n1:00 Middle\n9:00 Outro".into();
+    let chapters = video_from_player_response(player_response).chapters();
+    assert_eq!(chapters, [chapter(0, "Intro"), chapter(60, "Middle"), chapter(540, "Outro")]);
+}
@@ -0,0 +1,182 @@
+#![cfg(feature = "download")]
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::*;
+use rustube::{ChunkedDownloadOptions, Error};
+
+#[macro_use]
+mod common;
+
+/// The requested range `start..end` of a `bytes=<first>-<last>` header.
+fn range(request: &MockRequest) -> Option<(usize, usize)> {
+    let (first, last) = request.header("Range")?.strip_prefix("bytes=")?.split_once('-')?;
+    Some((first.parse().ok()?, last.parse::<usize>().ok()? + 1))
+}
+
+fn path(name: &str) -> PathBuf {
+    temp_dir(name).join("video.mp4")
+}
+
+fn ranges(server: &MockServer) -> Vec<Option<String>> {
+    let mut ranges: Vec<_> = server
+        .requests()
+        .iter()
+        .map(|request| request.header("Range").map(str::to_owned))
+        .collect();
+    ranges.sort();
+    ranges
+}
+
+fn cleanup(path: &Path) {
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn downloads_all_chunks() {
+    let server = MockServer::start(|request| MockResponse::ranged(request, &stream_body())).await;
+    let path = path("all");
+
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+    stream.download_chunked(&path, 1000, 3).await.unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(ranges(&server), [
+        Some("bytes=0-999".to_owned()),
+        Some("bytes=1000-1999".to_owned()),
+        Some("bytes=2000-2999".to_owned()),
+        Some("bytes=3000-3999".to_owned()),
+        Some("bytes=4000-4095".to_owned()),
+    ]);
+    cleanup(&path);
+}
+
+#[test_log::test(tokio::test)]
+async fn failed_chunks_are_retried() {
+    let failures = Arc::new(AtomicUsize::new(0));
+    let server = {
+        let failures = Arc::clone(&failures);
+        MockServer::start(move |request| match range(request) {
+            // the first attempt of the second chunk is interrupted
+            Some((1000, _)) if failures.fetch_add(1, Ordering::SeqCst) == 0 => {
+                MockResponse::ranged(request, &stream_body()).abort_after(500)
+            }
+            _ => MockResponse::ranged(request, &stream_body()),
+        }).await
+    };
+    let path = path("retried");
+
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+    stream.download_chunked(&path, 1000, 2).await.unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(failures.load(Ordering::SeqCst), 2);
+    cleanup(&path);
+}
+
+#[test_log::test(tokio::test)]
+async fn fails_after_all_retries() {
+    let server = MockServer::start(|request| match range(request) {
+        Some((2000, _)) => MockResponse::status(500),
+        _ => MockResponse::ranged(request, &stream_body()),
+    }).await;
+    let path = path("failed");
+
+    let options = ChunkedDownloadOptions::new().chunk_size(1000).concurrency(1).retries(2);
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+    assert!(matches!(stream.download_chunked_with(&path, options).await, Err(Error::Request(_))));
+
+    let attempts = server.requests().iter().filter(|request| range(request) == Some((2000, 3000))).count();
+    assert_eq!(attempts, 3);
+    assert!(!path.exists());
+    assert!(!path.with_extension("mp4.part").exists());
+    cleanup(&path);
+}
+
+#[test_log::test(tokio::test)]
+async fn forbidden_chunks_are_not_retried() {
+    let server = MockServer::start(|request| match range(request) {
+        Some((2000, _)) => MockResponse::status(403),
+        _ => MockResponse::ranged(request, &stream_body()),
+    }).await;
+    let path = path("forbidden");
+
+    let options = ChunkedDownloadOptions::new().chunk_size(1000).concurrency(1).retries(2);
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+    assert!(matches!(stream.download_chunked_with(&path, options).await, Err(Error::Forbidden { .. })));
+
+    let attempts = server.requests().iter().filter(|request| range(request) == Some((2000, 3000))).count();
+    assert_eq!(attempts, 1);
+    cleanup(&path);
+}
+
+#[test_log::test(tokio::test)]
+async fn ignored_ranges_fail() {
+    let server = MockServer::start(|_| MockResponse::ok(stream_body())).await;
+    let path = path("ignored");
+
+    let options = ChunkedDownloadOptions::new().chunk_size(1000).retries(0);
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+    match stream.download_chunked_with(&path, options).await {
+        Err(Error::UnexpectedResponse(reason)) => assert!(reason.contains("ignored the range"), "{}", reason),
+        res => panic!("expected Error::UnexpectedResponse, got {:?}", res),
+    }
+    cleanup(&path);
+}
+
+#[test_log::test(tokio::test)]
+async fn falls_back_to_a_sequential_download() {
+    // the content length is unknown
+    let server = MockServer::start(|request| match request.method.as_str() {
+        "HEAD" => MockResponse::status(500),
+        _ => MockResponse::ranged(request, &stream_body()),
+    }).await;
+    let (_, mut streams) = video_with_streams_at(&server, "stream").into_parts();
+    let stream = streams.remove(0);
+    let path = path("sequential");
+
+    stream.download_chunked(&path, 1000, 4).await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(ranges(&server), [None, None]);
+    cleanup(&path);
+
+    // OTF streams cannot be requested in ranges, but only in segments
+    let server = MockServer::start(|request| {
+        MockResponse::ranged(request, &stream_body()).with_header("Segment-Count", "1")
+    }).await;
+    let mut stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+    stream.is_otf = true;
+    let path = self::path("otf");
+
+    stream.download_chunked(&path, 1000, 4).await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(ranges(&server), [None]);
+    cleanup(&path);
+}
+
+#[cfg(feature = "callback")]
+#[test_log::test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn progress_adds_up_all_chunks() {
+    use std::sync::Mutex;
+
+    use rustube::Callback;
+
+    let server = MockServer::start(|request| MockResponse::ranged(request, &stream_body())).await;
+    let path = path("callback");
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let callback = {
+        let progress = Arc::clone(&progress);
+        Callback::new().connect_on_progress_closure(move |args| progress.lock().unwrap().push(args.current_chunk))
+    };
+    let options = ChunkedDownloadOptions::new().chunk_size(1000).concurrency(4);
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+    stream.download_chunked_with_callback(&path, options, callback).await.unwrap();
+
+    let progress = progress.lock().unwrap();
+    assert!(progress.iter().all(|&current_chunk| current_chunk <= stream_body().len()), "{:?}", progress);
+    assert_eq!(progress.iter().max(), Some(&stream_body().len()));
+    cleanup(&path);
+}
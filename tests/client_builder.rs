@@ -0,0 +1,132 @@
+#![cfg(feature = "fetch")]
+
+use std::sync::Arc;
+
+use rustube::fetcher::{ClientBuilder, Jar};
+use rustube::reqwest::cookie::CookieStore;
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+fn consent_cookie(jar: &Jar) -> Option<String> {
+    jar
+        .cookies(&"https://www.youtube.com/watch?v=fDzQ3kUg8Ss".parse().unwrap())
+        .map(|header| header.to_str().unwrap().to_owned())
+}
+
+#[test_log::test(tokio::test)]
+async fn recommended_headers() {
+    let server = MockServer::start(|_| MockResponse::ok("")).await;
+
+    let client = ClientBuilder::recommended().build().unwrap();
+    client.get(server.url.clone()).send().await.unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.header("Accept-Language"), Some("en-US,en"));
+    assert_eq!(request.header("User-Agent"), Some("Mozilla/5.0"));
+}
+
+#[test_log::test(tokio::test)]
+async fn new_has_no_recommended_headers() {
+    let server = MockServer::start(|_| MockResponse::ok("")).await;
+
+    let client = ClientBuilder::new().build().unwrap();
+    client.get(server.url.clone()).send().await.unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.header("Accept-Language"), None);
+    assert_eq!(request.header("Cookie"), None);
+}
+
+#[test]
+fn recommended_adds_consent_cookie() {
+    let jar = Arc::new(Jar::default());
+    let _ = ClientBuilder::recommended_with_jar(Arc::clone(&jar));
+
+    assert_eq!(jar.len(), 1);
+    assert_eq!(consent_cookie(&jar).as_deref(), Some("CONSENT=YES+"));
+
+    // an existing consent cookie is kept
+    let jar = Arc::new(Jar::default());
+    jar.add_cookie_str("CONSENT=YES+cb; Domain=youtube.com; Path=/", &"https://youtube.com".parse().unwrap());
+    let _ = ClientBuilder::recommended_with_jar(Arc::clone(&jar));
+
+    assert_eq!(jar.len(), 1);
+    assert_eq!(consent_cookie(&jar).as_deref(), Some("CONSENT=YES+cb"));
+}
+
+#[test_log::test(tokio::test)]
+async fn recommended_uses_jar() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/set" => MockResponse::ok("").with_header("Set-Cookie", "VISITOR_INFO1_LIVE=Xk9aB3cD; Path=/"),
+        _ => MockResponse::ok(""),
+    }).await;
+    let jar = Arc::new(Jar::default());
+
+    let client = ClientBuilder::recommended_with_jar(Arc::clone(&jar)).build().unwrap();
+    client.get(server.url.join("set").unwrap()).send().await.unwrap();
+    client.get(server.url.join("get").unwrap()).send().await.unwrap();
+
+    assert_eq!(jar.len(), 2);
+    let requests = server.requests();
+    assert_eq!(requests[0].header("Cookie"), None);
+    assert_eq!(requests[1].header("Cookie"), Some("VISITOR_INFO1_LIVE=Xk9aB3cD"));
+}
+
+#[test_log::test(tokio::test)]
+async fn recommended_limits_redirects() {
+    let server = MockServer::start(|_| MockResponse::status(302).with_header("Location", "/loop")).await;
+
+    let client = ClientBuilder::recommended().build().unwrap();
+    let err = client.get(server.url.clone()).send().await.unwrap_err();
+
+    assert!(err.is_redirect(), "{:?}", err);
+    // the first request, and 9 redirects
+    assert_eq!(server.requests().len(), 10);
+}
+
+/// The fetcher, and the streams of the video, send all requests through the proxy.
+#[cfg(feature = "download")]
+#[test_log::test(tokio::test)]
+async fn proxy_applies_to_fetcher_and_streams() {
+    use rustube::{Id, PlayerResponse, Stream, VideoFetcher};
+    use rustube::reqwest::Proxy;
+
+    let proxy = MockServer::start(|_| MockResponse::ok("proxied")).await;
+    let client = ClientBuilder::recommended()
+        .proxy(Proxy::all(proxy.url.as_str()).unwrap())
+        .build()
+        .unwrap();
+
+    let fetcher = VideoFetcher::from_id_with_client(Id::from_str("fDzQ3kUg8Ss").unwrap().into_owned(), client.clone())
+        .with_base_url("http://www.youtube.invalid/".parse().unwrap());
+    assert!(fetcher.fetch().await.is_err());
+
+    let mut player_response: PlayerResponse = serde_json::from_str(&read_fixture("multi_audio_player_response.json")).unwrap();
+    let raw_format = player_response.streaming_data.as_mut().unwrap().formats.remove(0);
+    let mut stream = Stream::from_raw_format(raw_format, client, Arc::clone(&player_response.video_details));
+    stream.signature_cipher.url = "http://rr1---sn-4g5e6nsz.googlevideo.invalid/videoplayback?itag=18".parse().unwrap();
+    stream.download_to_writer(&mut Vec::new()).await.unwrap();
+
+    let requests = proxy.requests();
+    assert!(requests[0].path.starts_with("http://www.youtube.invalid/watch?v=fDzQ3kUg8Ss"), "{}", requests[0].path);
+    let download = requests.last().unwrap();
+    assert_eq!(download.path, "http://rr1---sn-4g5e6nsz.googlevideo.invalid/videoplayback?itag=18");
+    // the recommended headers are still sent
+    assert_eq!(download.header("Accept-Language"), Some("en-US,en"));
+}
+
+#[test_log::test(tokio::test)]
+async fn local_address() {
+    let server = MockServer::start(|_| MockResponse::ok("")).await;
+
+    let client = ClientBuilder::recommended()
+        .local_address("127.0.0.1".parse().unwrap())
+        .build()
+        .unwrap();
+    client.get(server.url.clone()).send().await.unwrap();
+
+    assert_eq!(server.requests().len(), 1);
+}
@@ -0,0 +1,253 @@
+#![cfg(feature = "stream")]
+
+use rustube::{AudioCodec, CodecSupport, VideoCodec};
+use rustube::stream::codec::Av1Tier;
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+const MULTI_AUDIO: &str = "multi_audio_player_response.json";
+
+fn avc1(profile: u8, level: u8) -> VideoCodec {
+    VideoCodec::Avc1 { profile, level }
+}
+
+fn other_video(codec: &str) -> VideoCodec {
+    VideoCodec::Other(codec.to_owned())
+}
+
+#[test]
+fn parse_video_codecs() {
+    let cases = [
+        // H.264, as used by YouTube
+        ("avc1.42001E", avc1(66, 30)),
+        ("avc1.4d401e", avc1(77, 30)),
+        ("avc1.4D401F", avc1(77, 31)),
+        ("avc1.4d4020", avc1(77, 32)),
+        ("avc1.640028", avc1(100, 40)),
+        ("avc1.64002a", avc1(100, 42)),
+        ("avc1.640033", avc1(100, 51)),
+        ("avc3.640028", avc1(100, 40)),
+        (" avc1.640028 ", avc1(100, 40)),
+        // VP9
+        ("vp9", VideoCodec::Vp9 { profile: 0, level: None, bit_depth: None }),
+        ("vp9.2", VideoCodec::Vp9 { profile: 2, level: None, bit_depth: None }),
+        ("vp09.00.10.08", VideoCodec::Vp9 { profile: 0, level: Some(10), bit_depth: Some(8) }),
+        ("vp09.00.51.08.01.01.01.01.00", VideoCodec::Vp9 { profile: 0, level: Some(51), bit_depth: Some(8) }),
+        ("vp09.02.51.10.01.09.16.09.00", VideoCodec::Vp9 { profile: 2, level: Some(51), bit_depth: Some(10) }),
+        // AV1
+        ("av01.0.08M.08", VideoCodec::Av1 { profile: 0, level: 8, tier: Av1Tier::Main, bit_depth: 8 }),
+        ("av01.0.12M.08", VideoCodec::Av1 { profile: 0, level: 12, tier: Av1Tier::Main, bit_depth: 8 }),
+        ("av01.0.13M.10.0.110.09.16.09.0", VideoCodec::Av1 { profile: 0, level: 13, tier: Av1Tier::Main, bit_depth: 10 }),
+        ("av01.1.16H.12", VideoCodec::Av1 { profile: 1, level: 16, tier: Av1Tier::High, bit_depth: 12 }),
+    ];
+
+    for (raw, expected) in cases {
+        assert_eq!(VideoCodec::parse(raw), expected, "{:?}", raw);
+    }
+}
+
+#[test]
+fn unknown_video_codecs_are_kept() {
+    let cases = [
+        "", "hev1.1.6.L93.B0", "avc1", "avc1.6400", "avc1.64002G", "avc1.64€28", "vp9.x",
+        "vp09.00.10", "vp09.00.10.08.xx", "av01.0.08.08", "av01.0.08X.08", "av01.0.€M.08",
+        "av01.0.08M", "mp4a.40.2",
+    ];
+
+    for raw in cases {
+        assert_eq!(VideoCodec::parse(raw), other_video(raw), "{:?}", raw);
+    }
+}
+
+#[test]
+fn parse_audio_codecs() {
+    let cases = [
+        ("mp4a.40.2", AudioCodec::Aac { object_type: 2 }),
+        ("mp4a.40.5", AudioCodec::Aac { object_type: 5 }),
+        ("mp4a.40.29", AudioCodec::Aac { object_type: 29 }),
+        ("opus", AudioCodec::Opus),
+        ("vorbis", AudioCodec::Vorbis),
+        ("ac-3", AudioCodec::Ac3),
+        ("ec-3", AudioCodec::Ec3),
+        ("flac", AudioCodec::Flac),
+        (" mp4a.40.2", AudioCodec::Aac { object_type: 2 }),
+        ("mp4a.40", AudioCodec::Other("mp4a.40".into())),
+        ("mp4a.69", AudioCodec::Other("mp4a.69".into())),
+        ("mp4a.40.x", AudioCodec::Other("mp4a.40.x".into())),
+        ("avc1.640028", AudioCodec::Other("avc1.640028".into())),
+        ("", AudioCodec::Other("".into())),
+    ];
+
+    for (raw, expected) in cases {
+        assert_eq!(AudioCodec::parse(raw), expected, "{:?}", raw);
+    }
+}
+
+#[test]
+fn fixture_stream_codecs() {
+    let video = video_from_fixture(MULTI_AUDIO);
+
+    let codecs: Vec<(u64, Option<VideoCodec>, Option<AudioCodec>)> = video
+        .streams()
+        .iter()
+        .map(|stream| (stream.itag, stream.video_codec(), stream.audio_codec()))
+        .collect();
+
+    let aac = Some(AudioCodec::Aac { object_type: 2 });
+    assert_eq!(codecs, [
+        (18, Some(avc1(66, 30)), aac.clone()),
+        (137, Some(avc1(100, 40)), None),
+        (140, None, aac.clone()),
+        (140, None, aac.clone()),
+        (140, None, aac),
+        (251, None, Some(AudioCodec::Opus)),
+        (251, None, Some(AudioCodec::Opus)),
+        (251, None, Some(AudioCodec::Opus)),
+    ]);
+}
+
+#[test]
+fn unknown_stream_codecs() {
+    let mut player_response = json_fixture(MULTI_AUDIO);
+    player_response["streamingData"]["formats"][0]["mimeType"] = "video/mp4; codecs=\"hev1.1.6.L93.B0, mp4a.40.2\"".into();
+    player_response["streamingData"]["adaptiveFormats"][0]["mimeType"] = "video/mp4; codecs=\"dvh1.05.06\"".into();
+    let video = video_from_player_response(player_response);
+
+    let progressive = &video.streams()[0];
+    assert_eq!(progressive.video_codec(), Some(other_video("hev1.1.6.L93.B0")));
+    assert_eq!(progressive.audio_codec(), Some(AudioCodec::Aac { object_type: 2 }));
+
+    let adaptive = &video.streams()[1];
+    assert_eq!(adaptive.video_codec(), Some(other_video("dvh1.05.06")));
+    assert_eq!(adaptive.audio_codec(), None);
+}
+
+#[test]
+fn video_codec_support() {
+    // H.264 up to High@4.1, VP9 profile 0, AV1 Main up to level 5.1 (seq_level_idx 13), 8 bit
+    let support = CodecSupport {
+        max_avc: Some((100, 41)),
+        max_vp9_profile: Some(0),
+        max_av1: Some((0, 13)),
+        max_bit_depth: Some(8),
+        ..CodecSupport::default()
+    };
+
+    let cases = [
+        ("avc1.42001E", true),
+        ("avc1.640028", true),
+        ("avc1.640029", true),
+        ("avc1.64002a", false),
+        ("avc1.6E0028", false),
+        ("vp9", true),
+        ("vp09.00.51.08", true),
+        ("vp9.2", false),
+        ("vp09.00.51.10", false),
+        ("av01.0.08M.08", true),
+        ("av01.0.13M.08", true),
+        ("av01.0.16M.08", false),
+        ("av01.0.08M.10", false),
+        ("av01.1.08M.08", false),
+        ("hev1.1.6.L93.B0", false),
+    ];
+    for (raw, supported) in cases {
+        assert_eq!(VideoCodec::parse(raw).is_supported_by(&support), supported, "{:?}", raw);
+    }
+
+    for raw in ["avc1.42001E", "vp9", "av01.0.08M.08"] {
+        assert!(!VideoCodec::parse(raw).is_supported_by(&CodecSupport::default()), "{:?}", raw);
+    }
+}
+
+#[test]
+fn audio_codec_support() {
+    let support = CodecSupport { aac: true, opus: true, ..CodecSupport::default() };
+
+    let cases = [
+        ("mp4a.40.2", true),
+        ("mp4a.40.5", false),
+        ("mp4a.40.29", false),
+        ("opus", true),
+        ("vorbis", false),
+        ("ac-3", false),
+        ("unknown", false),
+    ];
+    for (raw, supported) in cases {
+        assert_eq!(AudioCodec::parse(raw).is_supported_by(&support), supported, "{:?}", raw);
+    }
+
+    let support = CodecSupport { he_aac: true, ..support };
+    assert!(AudioCodec::parse("mp4a.40.5").is_supported_by(&support));
+}
+
+#[test]
+fn stream_support() {
+    let mut player_response = json_fixture(MULTI_AUDIO);
+    player_response["streamingData"]["adaptiveFormats"][2]["audioChannels"] = 6.into();
+    let video = video_from_player_response(player_response);
+
+    // H.264 up to High@4.1, and stereo AAC
+    let support = CodecSupport {
+        max_avc: Some((100, 41)),
+        aac: true,
+        max_audio_channels: Some(2),
+        ..CodecSupport::default()
+    };
+    let supported: Vec<(u64, bool)> = video
+        .streams()
+        .iter()
+        .map(|stream| (stream.itag, stream.is_supported_by(&support)))
+        .collect();
+    assert_eq!(supported, [
+        (18, true),
+        (137, true),
+        (140, true),
+        (140, false),
+        (140, true),
+        (251, false),
+        (251, false),
+        (251, false),
+    ]);
+
+    let baseline_only = CodecSupport { max_avc: Some((66, 31)), ..support };
+    assert!(video.streams()[0].is_supported_by(&baseline_only));
+    assert!(!video.streams()[1].is_supported_by(&baseline_only));
+}
+
+#[test]
+fn needs_remux_for_playback() {
+    let mut player_response = json_fixture(MULTI_AUDIO);
+    for format in player_response["streamingData"]["adaptiveFormats"].as_array_mut().unwrap() {
+        format["initRange"] = serde_json::json!({ "start": "0", "end": "631" });
+        format["indexRange"] = serde_json::json!({ "start": "632", "end": "1151" });
+    }
+    // OTF streams have no ranges, but are fragmented as well
+    let otf = &mut player_response["streamingData"]["adaptiveFormats"][2];
+    otf.as_object_mut().unwrap().retain(|key, _| key != "initRange" && key != "indexRange");
+    otf["type"] = "FORMAT_STREAM_TYPE_OTF".into();
+    // without ranges, the stream is not a DASH stream
+    let plain = &mut player_response["streamingData"]["adaptiveFormats"][3];
+    plain.as_object_mut().unwrap().retain(|key, _| key != "initRange" && key != "indexRange");
+    let video = video_from_player_response(player_response);
+
+    let needs_remux: Vec<(u64, bool)> = video
+        .streams()
+        .iter()
+        .map(|stream| (stream.itag, stream.needs_remux_for_playback()))
+        .collect();
+    assert_eq!(needs_remux, [
+        // progressive
+        (18, false),
+        (137, true),
+        (140, true),
+        (140, true),
+        (140, false),
+        // webm
+        (251, false),
+        (251, false),
+        (251, false),
+    ]);
+}
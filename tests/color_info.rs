@@ -0,0 +1,81 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::json;
+
+use common::*;
+use rustube::video_info::player_response::streaming_data::{
+    ColorInfoPrimary, MatrixCoefficients, ProjectionType, TransferCharacteristics,
+};
+
+#[macro_use]
+mod common;
+
+#[test]
+fn hdr_video() {
+    let video = video_from_fixture("corpus/hdr.json");
+
+    let hdr: Vec<u64> = video.streams().iter().filter(|stream| stream.is_hdr()).map(|stream| stream.itag).collect();
+    assert_eq!(hdr.len(), 1);
+    let stream = video.streams().iter().find(|stream| stream.itag == hdr[0]).unwrap();
+    let color_info = stream.color_info.as_ref().unwrap();
+    assert_eq!(color_info.primaries, Some(ColorInfoPrimary::BT2020));
+    assert_eq!(color_info.transfer_characteristics, TransferCharacteristics::SMPTEST2084);
+    assert_eq!(color_info.matrix_coefficients, Some(MatrixCoefficients::BT2020NCL));
+}
+
+#[test]
+fn hlg_is_hdr() {
+    let mut player_response = json_fixture("corpus/hdr.json");
+    for format in player_response["streamingData"]["adaptiveFormats"].as_array_mut().unwrap() {
+        if format["colorInfo"]["transferCharacteristics"] == "COLOR_TRANSFER_CHARACTERISTICS_SMPTEST2084" {
+            format["colorInfo"]["transferCharacteristics"] = "COLOR_TRANSFER_CHARACTERISTICS_ARIB_STD_B67".into();
+            // the detection does not depend on the quality label
+            format["qualityLabel"] = "2160p60".into();
+        }
+    }
+
+    let video = video_from_player_response(player_response);
+
+    let stream = video.streams().iter().find(|stream| stream.is_hdr()).unwrap();
+    assert_eq!(stream.color_info.as_ref().unwrap().transfer_characteristics, TransferCharacteristics::AribStdB67);
+    assert!(TransferCharacteristics::AribStdB67.is_hdr());
+    assert!(!TransferCharacteristics::BT709.is_hdr());
+}
+
+#[test]
+fn spherical_video() {
+    let video = video_from_fixture("corpus/vr360.json");
+
+    let projections: Vec<ProjectionType> = video.streams().iter().map(|stream| stream.projection_type).collect();
+    assert!(projections.contains(&ProjectionType::Equirectangular));
+    assert!(projections.contains(&ProjectionType::Mesh));
+    assert!(projections.contains(&ProjectionType::Rectangular));
+    assert!(ProjectionType::Mesh.is_spherical());
+    assert!(!ProjectionType::Rectangular.is_spherical());
+}
+
+#[test]
+fn unknown_values_dont_break_deserialization() {
+    let mut player_response = json_fixture("corpus/hdr.json");
+    let format = &mut player_response["streamingData"]["adaptiveFormats"][0];
+    format["projectionType"] = "CUBEMAP".into();
+    format["colorInfo"] = json!({
+        "primaries": "COLOR_PRIMARIES_P3",
+        "transferCharacteristics": "COLOR_TRANSFER_CHARACTERISTICS_LINEAR",
+        "matrixCoefficients": "COLOR_MATRIX_COEFFICIENTS_BT2020_CL",
+    });
+
+    let video = video_from_player_response(player_response);
+
+    let stream = video
+        .streams()
+        .iter()
+        .find(|stream| stream.projection_type == ProjectionType::Unknown)
+        .unwrap();
+    let color_info = stream.color_info.as_ref().unwrap();
+    assert_eq!(color_info.primaries, Some(ColorInfoPrimary::Unknown));
+    assert_eq!(color_info.transfer_characteristics, TransferCharacteristics::Unknown);
+    assert_eq!(color_info.matrix_coefficients, Some(MatrixCoefficients::Unknown));
+    assert!(!ProjectionType::Unknown.is_spherical());
+    assert!(!TransferCharacteristics::Unknown.is_hdr());
+}
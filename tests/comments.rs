@@ -0,0 +1,159 @@
+#![cfg(feature = "comments")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio_stream::StreamExt;
+
+use common::*;
+use rustube::{Comment, Comments, CommentSort, Error, Id};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+
+/// A server, that answers requests to the `next` endpoint with the fixture of their continuation
+/// token, or the watch page's `next_response` without one.
+async fn comments_server(next_response: &'static str) -> MockServer {
+    MockServer::start(move |request| {
+        if !request.path.starts_with("/youtubei/v1/next") {
+            return MockResponse::status(404);
+        }
+        let fixture = match request.json()["continuation"].as_str() {
+            None => next_response,
+            Some("comments-section" | "sort-top") => "comments_first_page.json",
+            Some("page-2") => "comments_second_page.json",
+            Some("sort-newest") => "comments_newest.json",
+            Some(_) => return MockResponse::status(400),
+        };
+        MockResponse::json(&json_fixture(fixture))
+    }).await
+}
+
+fn comments(server: &MockServer) -> Comments {
+    Comments::new(Id::from_str(VIDEO_ID).unwrap(), &reqwest::Client::new())
+        .with_base_url(server.url.clone())
+}
+
+async fn collect(comments: Comments) -> Vec<Result<Comment, Error>> {
+    comments.stream().collect().await
+}
+
+fn continuations(server: &MockServer) -> Vec<Option<String>> {
+    server
+        .requests()
+        .iter()
+        .map(|request| request.json()["continuation"].as_str().map(str::to_owned))
+        .collect()
+}
+
+#[test_log::test(tokio::test)]
+async fn top_comments() {
+    let server = comments_server("next_comments.json").await;
+
+    let comments: Vec<Comment> = collect(comments(&server))
+        .await
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let ids: Vec<_> = comments.iter().map(|comment| comment.comment_id.as_str()).collect();
+    // the comment without an entity in the framework updates is skipped
+    assert_eq!(ids, ["UgzPinned", "UgxSecond", "UgxThird", "UgyFourth", "UgyFifth"]);
+
+    assert_eq!(comments[0], Comment {
+        comment_id: "UgzPinned".to_owned(),
+        author: "@BlenderOfficial".to_owned(),
+        author_channel_id: Some("UCSMOQeBJ2RAnuFungnQOxLg".to_owned()),
+        text: "Thanks for watching! Download the film at studio.blender.org".to_owned(),
+        like_count: 1_200,
+        published_time_text: "1 year ago".to_owned(),
+        reply_count: 12,
+        is_pinned: true,
+    });
+    assert_eq!(comments[1].like_count, 3_456);
+    assert!(!comments[1].is_pinned);
+    assert_eq!((comments[2].text.as_str(), comments[2].like_count), ("first", 0));
+
+    // comments of the view model are looked up in the framework updates
+    assert_eq!(comments[3], Comment {
+        comment_id: "UgyFourth".to_owned(),
+        author: "@animator".to_owned(),
+        author_channel_id: Some("UCk0w0p1d1kSaMW4L5tZ1Ywg".to_owned()),
+        text: "Every frame is a painting".to_owned(),
+        like_count: 987,
+        published_time_text: "10 months ago".to_owned(),
+        reply_count: 1_500,
+        is_pinned: false,
+    });
+    assert_eq!((comments[4].like_count, comments[4].reply_count), (0, 0));
+
+    assert_eq!(continuations(&server), [None, Some("comments-section".to_owned()), Some("page-2".to_owned())]);
+    assert_eq!(server.requests()[0].json()["videoId"], VIDEO_ID);
+}
+
+#[test_log::test(tokio::test)]
+async fn newest_comments() {
+    let server = comments_server("next_comments.json").await;
+
+    let comments = collect(comments(&server).sort(CommentSort::Newest)).await;
+
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].as_ref().unwrap().comment_id, "UgzNewest");
+    // the first page is only requested for its sort menu
+    assert_eq!(
+        continuations(&server),
+        [None, Some("comments-section".to_owned()), Some("sort-newest".to_owned())],
+    );
+}
+
+#[test_log::test(tokio::test)]
+async fn disabled_comments() {
+    let server = comments_server("next_creative_commons.json").await;
+
+    let comments = collect(comments(&server)).await;
+
+    assert!(comments.is_empty());
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test_log::test(tokio::test)]
+async fn retries_rate_limited_requests() {
+    let requests = AtomicUsize::new(0);
+    let server = MockServer::start(move |request| match requests.fetch_add(1, Ordering::SeqCst) {
+        1 => MockResponse::status(429).with_header("Retry-After", "0"),
+        _ => match request.json()["continuation"].as_str() {
+            None => MockResponse::json(&json_fixture("next_comments.json")),
+            Some(_) => MockResponse::json(&json_fixture("comments_newest.json")),
+        },
+    }).await;
+
+    let comments = collect(comments(&server)).await;
+
+    assert_eq!(comments.len(), 1);
+    assert!(comments[0].is_ok());
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test_log::test(tokio::test)]
+async fn rate_limited() {
+    let server = MockServer::start(|_| MockResponse::status(429).with_header("Retry-After", "0")).await;
+
+    let comments = collect(comments(&server).with_rate_limit_retries(1)).await;
+
+    assert_eq!(comments.len(), 1);
+    assert!(matches!(comments[0], Err(Error::RateLimited { endpoint: "next", .. })), "{:?}", comments[0]);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test_log::test(tokio::test)]
+#[ignore]
+async fn live_comments() {
+    let comments: Vec<_> = Comments::fetch(Id::from_str(VIDEO_ID).unwrap(), &reqwest::Client::new())
+        .take(30)
+        .collect()
+        .await;
+
+    assert_eq!(comments.len(), 30);
+    assert!(comments.iter().all(Result::is_ok));
+}
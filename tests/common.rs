@@ -33,6 +33,8 @@ pub const REGION_BLOCKED: &[&str] = &[];
 pub const MISSING_RECORDING: &[&str] = &[
     "5YceQ8YqYMc"
 ];
+/// Videos, whose adaptive streams are served as OTF (`FORMAT_STREAM_TYPE_OTF`).
+pub const OTF: &[&str] = &[];
 pub const LIVE_STREAM: &[&str] = &[
     "ASGNUnPINdM",
     "FwwgBB8l2vs",
@@ -87,8 +89,395 @@ pub fn random_id(ids: &'static [&'static str]) -> IdBuf {
         .unwrap()
 }
 
-pub fn random_entry<T>(vec: &Vec<T>) -> &T {
+pub fn random_entry<T>(vec: &[T]) -> &T {
     let i = rand::thread_rng()
         .gen_range(0..vec.len());
     &vec[i]
 }
+
+pub const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+/// The path of the player JavaScript served by [`mock_youtube`].
+pub const BASE_JS_PATH: &str = "/s/player/3ba3e2d4/player_ias.vflset/en_US/base.js";
+
+pub fn read_fixture(name: &str) -> String {
+    let path = std::path::Path::new(FIXTURE_DIR).join(name);
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("Failed to read fixture {:?}: {}", path, err))
+}
+
+pub fn json_fixture(name: &str) -> serde_json::Value {
+    serde_json::from_str(&read_fixture(name)).expect("The fixture is not valid json")
+}
+
+/// A captured player response of `tests/fixtures/corpus`, together with its TOML sidecar, which
+/// declares the expectations (see `tests/corpus.rs`).
+#[derive(Clone, Debug)]
+pub struct CorpusFixture {
+    /// The file name, without the extension.
+    pub name: String,
+    pub player_response: String,
+    pub sidecar: String,
+}
+
+/// Loads all fixtures of the corpus, sorted by name.
+///
+/// Panics, if a player response (`<NAME>.json`) has no sidecar (`<NAME>.toml`), or the other way
+/// around.
+pub fn corpus_fixtures() -> Vec<CorpusFixture> {
+    let dir = std::path::Path::new(FIXTURE_DIR).join("corpus");
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap_or_else(|err| panic!("Failed to read the corpus {:?}: {}", dir, err))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json" || extension == "toml"))
+        .map(|path| path.file_stem().unwrap().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let read = |extension: &str| {
+                let path = dir.join(&name).with_extension(extension);
+                std::fs::read_to_string(&path)
+                    .unwrap_or_else(|err| panic!("Failed to read {:?}, every fixture needs a .json and a .toml file: {}", path, err))
+            };
+            CorpusFixture { player_response: read("json"), sidecar: read("toml"), name }
+        })
+        .collect()
+}
+
+/// Builds a [`Video`](rustube::Video) from a pre-signed player response fixture, without performing
+/// any requests.
+#[cfg(feature = "descramble")]
+pub fn video_from_fixture(name: &str) -> rustube::Video {
+    video_from_player_response(json_fixture(name))
+}
+
+/// Builds a [`Video`](rustube::Video) from a pre-signed player response, without performing any
+/// requests.
+#[cfg(feature = "descramble")]
+pub fn video_from_player_response(player_response: serde_json::Value) -> rustube::Video {
+    use std::sync::Arc;
+
+    use rustube::{PlayerResponse, Stream, Video, VideoInfo};
+
+    // some fields borrow from the input, so they can't be deserialized from a `Value`
+    let mut player_response: PlayerResponse = serde_json::from_str(&player_response.to_string())
+        .expect("Failed to deserialize the player response");
+
+    let video_details = Arc::clone(&player_response.video_details);
+    let streaming_data = player_response.streaming_data
+        .as_mut()
+        .expect("The player response contains no streaming data");
+    let streams = streaming_data.formats
+        .drain(..)
+        .chain(streaming_data.adaptive_formats.drain(..))
+        .map(|raw_format| Stream::from_raw_format(
+            raw_format,
+            rustube::reqwest::Client::new(),
+            Arc::clone(&video_details),
+        ))
+        .collect();
+
+    let video_info = VideoInfo::from_player_response(player_response, false);
+    Video::from_parts(video_info, streams)
+}
+
+/// Points all streams of `video` to `path` on `server`.
+#[cfg(feature = "descramble")]
+pub fn with_streams_at(video: rustube::Video, server: &MockServer, path: &str) -> rustube::Video {
+    let url = server.url.join(path).unwrap();
+    let (video_info, mut streams) = video.into_parts();
+    for stream in streams.iter_mut() {
+        stream.signature_cipher.url = url.clone();
+    }
+    rustube::Video::from_parts(video_info, streams)
+}
+
+/// The multi audio fixture, with all streams pointing to `path` on `server`.
+#[cfg(feature = "descramble")]
+pub fn video_with_streams_at(server: &MockServer, path: &str) -> rustube::Video {
+    with_streams_at(video_from_fixture("multi_audio_player_response.json"), server, path)
+}
+
+/// The progressive stream of the multi audio fixture, pointing to `path` on `server`. Its format
+/// includes `content_length`, if it's given.
+#[cfg(feature = "descramble")]
+pub fn progressive_stream_at(server: &MockServer, path: &str, content_length: Option<usize>) -> rustube::Stream {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    if let Some(len) = content_length {
+        player_response["streamingData"]["formats"][0]["contentLength"] = len.to_string().into();
+    }
+
+    let video = with_streams_at(video_from_player_response(player_response), server, path);
+    let (_, mut streams) = video.into_parts();
+    streams.remove(0)
+}
+
+/// Sets the content length of all formats of `player_response` to `len`.
+pub fn set_content_length(player_response: &mut serde_json::Value, len: usize) {
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in player_response["streamingData"][formats].as_array_mut().unwrap() {
+            format["contentLength"] = len.to_string().into();
+        }
+    }
+}
+
+/// A fixed byte pattern of `len` bytes.
+pub fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|b| (b % 251) as u8).collect()
+}
+
+/// The body of the streams served in the download tests.
+pub fn stream_body() -> Vec<u8> {
+    pattern(4096)
+}
+
+/// A fresh, empty directory in the temp dir, unique to `name` and the running test binary.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustube_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A request received by a [`MockServer`].
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    pub method: String,
+    /// The path including the query.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("The request body is not valid json")
+    }
+}
+
+/// A response sent by a [`MockServer`].
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// If set, the body is sent in chunks of the given size, with the given delay before each chunk.
+    pub chunks: Option<(usize, std::time::Duration)>,
+    /// If set, the connection is closed after sending the given number of body bytes.
+    pub abort_after: Option<usize>,
+}
+
+impl MockResponse {
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self { status: 200, headers: Vec::new(), body: body.into(), chunks: None, abort_after: None }
+    }
+
+    pub fn json(value: &serde_json::Value) -> Self {
+        Self::ok(value.to_string())
+            .with_header("Content-Type", "application/json")
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self { status, headers: Vec::new(), body: Vec::new(), chunks: None, abort_after: None }
+    }
+
+    /// Answers a request with a `bytes=<first>-[<last>]` `Range` header with the requested part of
+    /// `body`, and any other request with the whole `body`.
+    pub fn ranged(request: &MockRequest, body: &[u8]) -> Self {
+        let range = request.header("Range").and_then(|range| {
+            let (first, last) = range.strip_prefix("bytes=")?.split_once('-')?;
+            let end = match last {
+                "" => body.len(),
+                last => last.parse::<usize>().ok()? + 1,
+            };
+            Some((first.parse::<usize>().ok()?, end))
+        });
+        match range {
+            Some((start, end)) => Self {
+                status: 206,
+                ..Self::ok(&body[start..end])
+                    .with_header("Content-Range", &format!("bytes {}-{}/{}", start, end - 1, body.len()))
+            },
+            None => Self::ok(body),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Sends the body in chunks of `size` bytes, and waits `delay` before sending each of them.
+    pub fn slow(mut self, size: usize, delay: std::time::Duration) -> Self {
+        self.chunks = Some((size, delay));
+        self
+    }
+
+    /// Closes the connection after sending `bytes` bytes of the body, while still announcing the
+    /// full content length.
+    pub fn abort_after(mut self, bytes: usize) -> Self {
+        self.abort_after = Some(bytes);
+        self
+    }
+}
+
+/// A minimal HTTP/1.1 server, that answers every request using a handler, and records all
+/// requests it received.
+#[derive(Debug)]
+pub struct MockServer {
+    pub url: url::Url,
+    requests: std::sync::Arc<std::sync::Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    pub async fn start<F>(handler: F) -> Self
+        where F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static {
+        use std::sync::{Arc, Mutex};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = url::Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler = Arc::new(handler);
+
+        let recorded = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let handler = Arc::clone(&handler);
+                let recorded = Arc::clone(&recorded);
+                tokio::spawn(async move {
+                    let _ = Self::handle_connection(socket, handler.as_ref(), &recorded).await;
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// All requests, the server received so far.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    async fn handle_connection<F>(
+        mut socket: tokio::net::TcpStream,
+        handler: &F,
+        recorded: &std::sync::Mutex<Vec<MockRequest>>,
+    ) -> Option<()>
+        where F: Fn(&MockRequest) -> MockResponse {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut buf = Vec::new();
+        let header_end = loop {
+            let mut chunk = [0; 4096];
+            let n = socket.read(&mut chunk).await.ok()?;
+            if n == 0 { return None; }
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break i + 4;
+            }
+        };
+
+        let head = String::from_utf8_lossy(&buf[..header_end]).into_owned();
+        let mut lines = head.lines();
+        let mut request_line = lines.next()?.split(' ');
+        let method = request_line.next()?.to_owned();
+        let path = request_line.next()?.to_owned();
+        let headers: Vec<(String, String)> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_owned(), value.trim().to_owned()))
+            .collect();
+
+        let content_length = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.parse::<usize>().ok())
+            .unwrap_or(0);
+        let mut body = buf.split_off(header_end);
+        while body.len() < content_length {
+            let mut chunk = [0; 4096];
+            let n = socket.read(&mut chunk).await.ok()?;
+            if n == 0 { break; }
+            body.extend_from_slice(&chunk[..n]);
+        }
+
+        let request = MockRequest { method, path, headers, body };
+        let response = handler(&request);
+        recorded.lock().unwrap().push(request);
+
+        let mut raw = format!(
+            "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+            response.status,
+            response.body.len(),
+        );
+        for (name, value) in &response.headers {
+            raw.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        raw.push_str("\r\n");
+
+        socket.write_all(raw.as_bytes()).await.ok()?;
+        let body = match response.abort_after {
+            Some(bytes) => &response.body[..bytes.min(response.body.len())],
+            None => &response.body[..],
+        };
+        match response.chunks {
+            Some((size, delay)) => for chunk in body.chunks(size) {
+                tokio::time::sleep(delay).await;
+                socket.write_all(chunk).await.ok()?;
+                socket.flush().await.ok()?;
+            },
+            None => socket.write_all(body).await.ok()?,
+        }
+        socket.shutdown().await.ok()
+    }
+}
+
+/// Serves a watch page containing `watch_player_response`, the player JavaScript `base_js`, and
+/// answers requests to the innertube player endpoint with `api_player_response`, depending on the
+/// requesting client.
+pub async fn mock_youtube<F>(
+    watch_player_response: serde_json::Value,
+    base_js: impl Into<String>,
+    api_player_response: F,
+) -> MockServer
+    where F: Fn(&str) -> serde_json::Value + Send + Sync + 'static {
+    mock_youtube_with(watch_player_response, base_js, move |body| {
+        api_player_response(body["context"]["client"]["clientName"].as_str().unwrap())
+    }).await
+}
+
+/// Like [`mock_youtube`], but `api_player_response` gets the whole body of the player request.
+pub async fn mock_youtube_with<F>(
+    watch_player_response: serde_json::Value,
+    base_js: impl Into<String>,
+    api_player_response: F,
+) -> MockServer
+    where F: Fn(&serde_json::Value) -> serde_json::Value + Send + Sync + 'static {
+    let base_js = base_js.into();
+    MockServer::start(move |request| {
+        let path = request.path.as_str();
+        if path.starts_with("/watch") {
+            MockResponse::ok(format!(
+                "<html><script>var ytInitialPlayerResponse = {};</script>\
+                <script src=\"{}\"></script></html>",
+                watch_player_response, BASE_JS_PATH,
+            ))
+        } else if path == BASE_JS_PATH {
+            MockResponse::ok(base_js.clone())
+        } else if path.starts_with("/youtubei/v1/player") {
+            MockResponse::json(&api_player_response(&request.json()))
+        } else {
+            MockResponse::status(404)
+        }
+    }).await
+}
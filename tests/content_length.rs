@@ -0,0 +1,154 @@
+#![cfg(feature = "download")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use url::Url;
+
+use common::*;
+use rustube::{Error, InnertubeClient, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+fn heads(server: &MockServer) -> usize {
+    server.requests().iter().filter(|request| request.method == "HEAD").count()
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn concurrent_calls_share_one_head_request() {
+    let server = MockServer::start(|request| {
+        if request.method == "HEAD" {
+            // keep the request in flight, while the other callers arrive
+            std::thread::sleep(Duration::from_millis(200));
+        }
+        MockResponse::ok(stream_body())
+    }).await;
+    let stream = progressive_stream_at(&server, "stream", None);
+
+    let tasks: Vec<_> = (0..10)
+        .map(|_| {
+            let stream = stream.clone();
+            tokio::spawn(async move { stream.content_length().await })
+        })
+        .collect();
+    for task in tasks {
+        assert_eq!(task.await.unwrap().unwrap(), stream_body().len() as u64);
+    }
+
+    assert_eq!(heads(&server), 1);
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(stream.content_length().await.unwrap(), stream_body().len() as u64);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+async fn failed_head_request_is_cached() {
+    let server = MockServer::start(|request| match request.method.as_str() {
+        "HEAD" => MockResponse::status(500),
+        _ => MockResponse::ok(stream_body()),
+    }).await;
+    let stream = progressive_stream_at(&server, "stream", None);
+
+    assert!(matches!(stream.content_length().await, Err(Error::Request(_))));
+    let tasks: Vec<_> = (0..10)
+        .map(|_| {
+            let stream = stream.clone();
+            tokio::spawn(async move { stream.content_length().await })
+        })
+        .collect();
+    for task in tasks {
+        assert!(task.await.unwrap().is_err());
+    }
+    assert!(matches!(stream.content_length().await, Err(Error::UnexpectedResponse(_))));
+
+    assert_eq!(heads(&server), 1);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test_log::test(tokio::test)]
+async fn refresh_requests_the_content_length_again() {
+    let heads_seen = AtomicUsize::new(0);
+    let media = MockServer::start(move |request| {
+        if request.method == "HEAD" && heads_seen.fetch_add(1, Ordering::SeqCst) == 0 {
+            return MockResponse::status(500);
+        }
+        MockResponse::ok(stream_body())
+    }).await;
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    for format in player_response["streamingData"]["formats"].as_array_mut().unwrap() {
+        let mut url = media.url.join("stream").unwrap();
+        url.set_query(Url::parse(format["url"].as_str().unwrap()).unwrap().query());
+        format["url"] = url.as_str().into();
+    }
+    let youtube = {
+        let api_player_response = player_response.clone();
+        mock_youtube(
+            player_response.clone(),
+            "var config={signatureTimestamp:19876};",
+            move |_| api_player_response.clone(),
+        ).await
+    };
+    let (_, mut streams) = video_from_player_response(player_response).into_parts();
+    let mut stream = streams.remove(0);
+
+    assert!(stream.content_length().await.is_err());
+    assert!(stream.content_length().await.is_err());
+    assert_eq!(heads(&media), 1);
+
+    let fetcher = VideoFetcher::from_id(stream.video_details.video_id.clone())
+        .unwrap()
+        .with_base_url(youtube.url.clone())
+        .with_innertube_clients(vec![InnertubeClient::Android]);
+    stream.refresh_with(fetcher).await.unwrap();
+    assert_eq!(stream.content_length().await.unwrap(), stream_body().len() as u64);
+    assert_eq!(heads(&media), 2);
+}
+
+#[test_log::test(tokio::test)]
+async fn known_content_length_makes_no_request() {
+    let server = MockServer::start(|_| MockResponse::ok(stream_body())).await;
+    let (_, streams) = video_from_fixture("multi_audio_player_response.json").into_parts();
+
+    assert_eq!(streams[1].content_length().await.unwrap(), 180241553);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn estimated_content_length() {
+    let (_, streams) = video_from_fixture("multi_audio_player_response.json").into_parts();
+
+    // 503313 bits/s * 615.04 s
+    assert_eq!(streams[0].estimated_content_length(), Some(38694703));
+    assert!(streams.iter().all(|stream| stream.estimated_content_length().is_some()));
+}
+
+#[cfg(feature = "callback")]
+#[test_log::test(tokio::test)]
+async fn callback_falls_back_to_the_estimate() {
+    use std::sync::{Arc, Mutex};
+
+    use rustube::Callback;
+
+    let server = MockServer::start(|request| match request.method.as_str() {
+        "HEAD" => MockResponse::status(500),
+        _ => MockResponse::ok(stream_body()),
+    }).await;
+    let stream = progressive_stream_at(&server, "stream", None);
+    let path = temp_dir("callback").join("video.mp4");
+
+    let content_lengths = Arc::new(Mutex::new(Vec::new()));
+    let callback = {
+        let content_lengths = Arc::clone(&content_lengths);
+        Callback::new()
+            .connect_on_progress_closure(move |args| content_lengths.lock().unwrap().push(args.content_length))
+    };
+    stream.download_to_with_callback(&path, callback).await.unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    let content_lengths = content_lengths.lock().unwrap();
+    assert!(!content_lengths.is_empty());
+    assert!(content_lengths.iter().all(|cl| *cl == stream.estimated_content_length()));
+    assert_eq!(heads(&server), 1);
+    let _ = std::fs::remove_file(&path);
+}
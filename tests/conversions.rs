@@ -0,0 +1,209 @@
+#![cfg(feature = "descramble")]
+
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use common::*;
+use rustube::{Error, PlayerResponse, Video, VideoInfo};
+use rustube::video_info::player_response::playability_status::PlayabilityStatus;
+use rustube::video_info::player_response::streaming_data::{AudioQuality, Quality, QualityLabel};
+
+#[macro_use]
+mod common;
+
+/// Checks, that `Display` and `FromStr` round-trip, and agree with the serde representation.
+fn assert_round_trips<T>(all: &[T])
+    where T: Display + FromStr<Err=Error> + Serialize + DeserializeOwned + PartialEq + std::fmt::Debug {
+    for value in all {
+        let string = value.to_string();
+        assert_eq!(&T::from_str(&string).unwrap(), value, "{}", string);
+        assert_eq!(serde_json::to_value(value).unwrap(), string.as_str());
+        assert_eq!(&serde_json::from_value::<T>(string.clone().into()).unwrap(), value);
+    }
+}
+
+#[test]
+fn quality_strings_round_trip() {
+    assert_round_trips(Quality::ALL);
+    assert_round_trips(QualityLabel::ALL);
+    assert_round_trips(AudioQuality::ALL);
+
+    assert_eq!(Quality::ALL.len(), 9);
+    assert_eq!(QualityLabel::ALL.len(), 33);
+    assert_eq!(Quality::Hd1080.to_string(), "hd1080");
+    assert_eq!(QualityLabel::P1080Hz60HDR.to_string(), "1080p60 HDR");
+    assert_eq!(AudioQuality::Medium.to_string(), "AUDIO_QUALITY_MEDIUM");
+}
+
+#[test]
+fn quality_aliases_and_errors() {
+    assert_eq!("low".parse::<AudioQuality>().unwrap(), AudioQuality::Low);
+    assert_eq!("medium".parse::<AudioQuality>().unwrap(), AudioQuality::Medium);
+    assert_eq!("high".parse::<AudioQuality>().unwrap(), AudioQuality::High);
+    assert_eq!("1080p Premium".parse::<QualityLabel>().unwrap(), QualityLabel::P1080Premium);
+
+    for invalid in ["", "HD1080", "1080", "1080p60HDR", "AUDIO_QUALITY_ULTRA"] {
+        assert!(matches!(invalid.parse::<Quality>(), Err(Error::BadQualityFormat(s)) if s == invalid));
+        assert!(invalid.parse::<QualityLabel>().is_err());
+        assert!(invalid.parse::<AudioQuality>().is_err());
+    }
+}
+
+#[test]
+fn unknown_qualities_are_kept() {
+    let label: QualityLabel = serde_json::from_value("8640p120 HDR".into()).unwrap();
+    assert_eq!(label, QualityLabel::Unknown("8640p120 HDR".to_owned()));
+    assert!(!label.is_known());
+    assert!(label.is_hdr());
+    assert!(!label.is_premium());
+    assert_eq!(label.to_string(), "8640p120 HDR");
+    assert_eq!(serde_json::to_value(&label).unwrap(), "8640p120 HDR");
+    assert!(QualityLabel::from_raw("1440p Premium").is_premium());
+    assert_eq!(QualityLabel::from_raw("2160p60"), QualityLabel::P2160Hz60);
+
+    let quality: Quality = serde_json::from_value("hd4320".into()).unwrap();
+    assert_eq!(quality, Quality::Unknown("hd4320".to_owned()));
+    assert!(Quality::Hd1440.is_known());
+
+    // parsing user input stays strict
+    assert!(matches!("8640p120 HDR".parse::<QualityLabel>(), Err(Error::BadQualityFormat(_))));
+    assert!(matches!("hd4320".parse::<Quality>(), Err(Error::BadQualityFormat(_))));
+
+    // unknown values sort below all known ones
+    assert!(label < QualityLabel::P144);
+    assert!(QualityLabel::P1080 < QualityLabel::P1440 && QualityLabel::P1440 < QualityLabel::P2160Hz60);
+    assert!(quality < Quality::Tiny);
+}
+
+#[test]
+fn unknown_quality_labels_dont_break_deserialization() {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    for format in player_response["streamingData"]["adaptiveFormats"].as_array_mut().unwrap() {
+        if format["itag"] == 137 {
+            format["quality"] = "hd4320".into();
+            format["qualityLabel"] = "4320p120".into();
+        }
+    }
+
+    let video = video_from_player_response(player_response);
+
+    let stream = video.streams().iter().find(|stream| stream.itag == 137).unwrap();
+    assert_eq!(stream.quality, Quality::Unknown("hd4320".to_owned()));
+    assert_eq!(stream.quality_label, Some(QualityLabel::Unknown("4320p120".to_owned())));
+    // the stream can still be selected
+    assert_eq!(video.best_video().map(|stream| stream.itag), Some(137));
+}
+
+#[test]
+fn stream_display() {
+    let video = video_from_fixture("multi_audio_player_response.json");
+    let streams: Vec<String> = video.streams().iter().take(4).map(ToString::to_string).collect();
+
+    assert_eq!(streams, [
+        // the content length of the progressive stream is unknown
+        "18: mp4, 640x360@25fps",
+        "137: mp4, 1920x1080@25fps, video only, 180.24 MB",
+        "140: mp4, 130kbps, audio only, 9.95 MB",
+        "140: mp4, 130kbps, audio only, 9.95 MB",
+    ]);
+    let opus = video.streams().iter().find(|stream| stream.itag == 251).unwrap();
+    assert_eq!(opus.to_string(), "251: webm, 141kbps, audio only, 9.44 MB");
+}
+
+#[test_log::test(tokio::test)]
+async fn video_conversions() {
+    let player_response = json_fixture("multi_audio_player_response.json");
+    let server = mock_youtube(player_response.clone(), read_fixture("base.js"), move |_| player_response.clone()).await;
+    let descrambler = rustube::VideoFetcher::from_id(rustube::Id::from_str("fDzQ3kUg8Ss").unwrap().into_owned())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .fetch()
+        .await
+        .unwrap();
+    let expected = descrambler.video_info().clone();
+
+    let video = Video::try_from(descrambler).unwrap();
+    assert_eq!(video.id().as_str(), "fDzQ3kUg8Ss");
+    assert!(!video.streams().is_empty());
+
+    let video_info: VideoInfo = video.into();
+    assert_eq!(video_info.player_response.video_details, expected.player_response.video_details);
+}
+
+#[test]
+fn playability_status_display() {
+    let ok: PlayerResponse = serde_json::from_str(&read_fixture("multi_audio_player_response.json")).unwrap();
+    assert_eq!(ok.playability_status.to_string(), "OK");
+
+    let private: PlayabilityStatus = serde_json::from_value(
+        json_fixture("private_player_response.json")["playabilityStatus"].clone()
+    ).unwrap();
+    assert_eq!(private.to_string(), "LOGIN_REQUIRED: Private video");
+}
+
+const PLAYER_RESPONSES: [&str; 14] = [
+    "multi_audio_player_response.json",
+    "android_player_response.json",
+    "ios_player_response.json",
+    "corpus/age_restricted_embed.json",
+    "corpus/ciphered.json",
+    "corpus/hdr.json",
+    "corpus/live.json",
+    "corpus/members_only.json",
+    "corpus/multi_audio.json",
+    "corpus/post_live_dvr.json",
+    "corpus/pre_signed.json",
+    "corpus/premium.json",
+    "corpus/region_blocked.json",
+    "corpus/vr360.json",
+];
+
+#[test]
+fn player_response_round_trips() {
+    for name in PLAYER_RESPONSES {
+        let player_response: PlayerResponse = serde_json::from_str(&read_fixture(name)).unwrap();
+        let serialized = serde_json::to_string(&player_response).unwrap();
+        let deserialized: PlayerResponse = serde_json::from_str(&serialized)
+            .unwrap_or_else(|err| panic!("{}: {}\n{}", name, err, serialized));
+        assert_eq!(deserialized, player_response, "{}", name);
+    }
+
+    // the custom deserialized fields are serialized in the shape YouTube sends them
+    let fixture = json_fixture("corpus/ciphered.json");
+    let player_response: PlayerResponse = serde_json::from_value(fixture.clone()).unwrap();
+    let serialized = serde_json::to_value(&player_response).unwrap();
+    for (format, original) in serialized["streamingData"]["adaptiveFormats"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .zip(fixture["streamingData"]["adaptiveFormats"].as_array().unwrap()) {
+        assert_eq!(format["mimeType"], original["mimeType"]);
+        assert_eq!(format["indexRange"], original["indexRange"]);
+        assert_eq!(format["lastModified"], original["lastModified"]);
+        assert!(format["signatureCipher"].is_string());
+        assert!(format.get("url").is_none() && format.get("s").is_none());
+    }
+}
+
+#[test]
+fn video_info_round_trips() {
+    for name in PLAYER_RESPONSES {
+        let player_response: PlayerResponse = serde_json::from_str(&read_fixture(name)).unwrap();
+        let video_info = VideoInfo::from_player_response(player_response, false);
+        let serialized = serde_json::to_string(&video_info).unwrap();
+        let deserialized: VideoInfo = serde_json::from_str(&serialized)
+            .unwrap_or_else(|err| panic!("{}: {}", name, err));
+        assert_eq!(deserialized, video_info, "{}", name);
+    }
+
+    // the player response is still accepted as JSON string
+    let video_info: VideoInfo = serde_json::from_value(serde_json::json!({
+        "player_response": read_fixture("multi_audio_player_response.json"),
+        "adaptive_fmts": null,
+    })).unwrap();
+    assert_eq!(video_info.player_response.video_details.video_id.as_str(), "fDzQ3kUg8Ss");
+}
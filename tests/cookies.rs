@@ -0,0 +1,135 @@
+#![cfg(feature = "fetch")]
+
+use std::path::PathBuf;
+
+use rustube::Error;
+use rustube::fetcher::{cookies_from_file, Jar, save_cookies_to_file};
+use rustube::reqwest::cookie::CookieStore;
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+fn fixture_path() -> PathBuf {
+    std::path::Path::new(FIXTURE_DIR).join("cookies.txt")
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rustube_{}_{}.txt", name, std::process::id()))
+}
+
+/// The cookies `jar` sends to `url`, sorted by name.
+fn request_cookies(jar: &Jar, url: &str) -> Vec<String> {
+    let mut cookies: Vec<String> = jar
+        .cookies(&url.parse().unwrap())
+        .map(|header| header.to_str().unwrap().split("; ").map(str::to_owned).collect())
+        .unwrap_or_default();
+    cookies.sort();
+    cookies
+}
+
+#[test]
+fn load_cookies() {
+    let jar = cookies_from_file(fixture_path()).unwrap();
+
+    // the expired cookie is skipped
+    assert_eq!(jar.len(), 6);
+    assert_eq!(request_cookies(&jar, "https://www.youtube.com/watch?v=fDzQ3kUg8Ss"), [
+        "CONSENT=YES+cb.20210328-17-p0.en+FX+999",
+        "LOGIN_INFO=AFmmF2swRQIhAL",
+        "VISITOR_INFO1_LIVE=Xk9aB3cD",
+        "YSC=q1w2e3r4t5y",
+    ]);
+    // host only, and restricted to /feed
+    let pref = "PREF=f6=40000000&tz=Europe.Berlin".to_owned();
+    assert!(request_cookies(&jar, "https://www.youtube.com/feed/subscriptions").contains(&pref));
+    assert!(!request_cookies(&jar, "https://www.youtube.com/").contains(&pref));
+    assert!(!request_cookies(&jar, "https://m.youtube.com/feed").contains(&pref));
+    // secure cookies are not sent via http
+    assert_eq!(request_cookies(&jar, "http://www.youtube.com/"), ["YSC=q1w2e3r4t5y"]);
+    assert_eq!(request_cookies(&jar, "https://www.google.com/"), ["NID="]);
+}
+
+#[test]
+fn round_trip() {
+    let path = temp_path("round_trip");
+    let jar = cookies_from_file(fixture_path()).unwrap();
+    save_cookies_to_file(&jar, &path).unwrap();
+
+    let saved = std::fs::read_to_string(&path).unwrap();
+    let reloaded = cookies_from_file(&path).unwrap();
+    save_cookies_to_file(&reloaded, &path).unwrap();
+    let resaved = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(saved.starts_with("# Netscape HTTP Cookie File\n"));
+    assert!(saved.contains("#HttpOnly_.youtube.com\tTRUE\t/\tTRUE\t2000000000\tLOGIN_INFO\tAFmmF2swRQIhAL\n"));
+    assert!(saved.contains("www.youtube.com\tFALSE\t/feed\tFALSE\t2000000000\tPREF\tf6=40000000&tz=Europe.Berlin\n"));
+    assert!(saved.contains(".youtube.com\tTRUE\t/\tFALSE\t0\tYSC\tq1w2e3r4t5y\n"));
+    assert!(!saved.contains("EXPIRED"));
+
+    let mut saved_lines: Vec<&str> = saved.lines().collect();
+    let mut resaved_lines: Vec<&str> = resaved.lines().collect();
+    saved_lines.sort_unstable();
+    resaved_lines.sort_unstable();
+    assert_eq!(saved_lines, resaved_lines);
+    assert_eq!(reloaded.len(), jar.len());
+}
+
+#[test]
+fn recommended_cookies_do_not_override_loaded_ones() {
+    let jar = cookies_from_file(fixture_path()).unwrap();
+    jar.add_recommended_cookies();
+    assert!(request_cookies(&jar, "https://www.youtube.com/")
+        .contains(&"CONSENT=YES+cb.20210328-17-p0.en+FX+999".to_owned()));
+
+    let jar = Jar::default();
+    jar.add_recommended_cookies();
+    assert_eq!(request_cookies(&jar, "https://www.youtube.com/"), ["CONSENT=YES+"]);
+}
+
+#[test]
+fn garbled_line() {
+    let path = temp_path("garbled_line");
+    std::fs::write(&path, "# Netscape HTTP Cookie File\n\n\
+        .youtube.com\tTRUE\t/\tTRUE\t2000000000\tYSC\tabc\n\
+        .youtube.com\tMAYBE\t/\tTRUE\t2000000000\tPREF\tdef\n").unwrap();
+    let res = cookies_from_file(&path);
+    let _ = std::fs::remove_file(&path);
+
+    match res {
+        Err(err @ Error::CookieFile { line: 4, .. }) => {
+            assert!(err.to_string().contains("line 4"), "{}", err);
+            assert!(err.to_string().contains("MAYBE"), "{}", err);
+        }
+        res => panic!("expected Error::CookieFile at line 4, got {:?}", res),
+    }
+}
+
+#[test]
+fn too_few_fields() {
+    let path = temp_path("too_few_fields");
+    std::fs::write(&path, ".youtube.com\tTRUE\t/\n").unwrap();
+    let res = cookies_from_file(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert!(matches!(res, Err(Error::CookieFile { line: 1, .. })), "{:?}", res);
+}
+
+#[test]
+fn invalid_utf8() {
+    let path = temp_path("invalid_utf8");
+    std::fs::write(&path, b"# comment\n.youtube.com\tTRUE\t/\tTRUE\t0\tYSC\t\xff\xfe\n").unwrap();
+    let res = cookies_from_file(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert!(matches!(res, Err(Error::CookieFile { line: 2, .. })), "{:?}", res);
+}
+
+#[test]
+fn unreadable_file() {
+    let res = cookies_from_file(temp_path("does_not_exist"));
+
+    assert!(matches!(res, Err(Error::IO(_))), "{:?}", res);
+}
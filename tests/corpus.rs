@@ -0,0 +1,259 @@
+#![cfg(feature = "descramble")]
+//! Data driven compatibility tests against the corpus of captured payloads in
+//! `tests/fixtures/corpus`.
+//!
+//! Each fixture consists of two files: the player response `<NAME>.json`, and the sidecar
+//! `<NAME>.toml`, which declares what rustube should make of it. Every fixture is deserialized
+//! into a `PlayerResponse`, served by a mock YouTube together with `tests/fixtures/base.js`, and
+//! fetched and descrambled like a real video. Adding a capture only requires adding both files.
+//!
+//! The sidecar schema:
+//! ```toml
+//! # What's special about the payload.
+//! description = "A live stream, that's currently live"
+//! # The innertube client, that returned the player response. [default: "WEB"]
+//! client = "ANDROID"
+//! # Whether the watch page shows an age gate, so the embed page is requested. [default: false]
+//! age_gate = false
+//!
+//! [expect]
+//! # The `PlayabilityKind` of the player response.
+//! playability = "Ok"
+//! # The name of the `Error` variant, fetching or descrambling fails with. If it's set, all other
+//! # expectations, except the playability, are skipped.
+//! error = "VideoUnavailable"
+//! # The number of streams.
+//! streams = 2
+//! # The quality label of `Video::best_quality`, and `Video::best_video`.
+//! best_quality = "360p"
+//! best_video = "1080p"
+//! # The `LiveStatus` of the video.
+//! live_status = "Live"
+//! # Exactly the flags, that apply to the video: `age_restricted`, `ciphered`, `dash_manifest`,
+//! # `hdr`, `multi_audio`, `premium`, and `360`. Not checked, if it's missing.
+//! flags = ["dash_manifest"]
+//! ```
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{Error, Id, InnertubeClient, PlayerResponse, Video, VideoFetcher};
+use rustube::video_info::player_response::streaming_data::{ProjectionType, TransferCharacteristics};
+
+#[macro_use]
+mod common;
+
+const FLAGS: &[&str] = &["age_restricted", "ciphered", "dash_manifest", "hdr", "multi_audio", "premium", "360"];
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Sidecar {
+    description: String,
+    #[serde(default = "web")]
+    client: String,
+    #[serde(default)]
+    age_gate: bool,
+    expect: Expect,
+}
+
+fn web() -> String {
+    InnertubeClient::Web.name().to_owned()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Expect {
+    playability: String,
+    error: Option<String>,
+    streams: Option<usize>,
+    best_quality: Option<String>,
+    best_video: Option<String>,
+    live_status: Option<String>,
+    flags: Option<Vec<String>>,
+}
+
+/// Serves the watch page, the embed page, `base.js`, and the player endpoint for the `fixture`.
+async fn mock_youtube_for(player_response: Value, age_gate: bool) -> MockServer {
+    let watch_page = match age_gate {
+        true => format!(
+            "<html><meta property=\"og:restrictions:age\" content=\"18+\">\
+            <script>var ytInitialPlayerResponse = {};</script></html>",
+            json!({
+                "playabilityStatus": {
+                    "status": "LOGIN_REQUIRED",
+                    "reason": "Sign in to confirm your age",
+                    "desktopLegacyAgeGateReason": 1,
+                    "contextParams": "Q0FFU0FnZ0I="
+                }
+            }),
+        ),
+        false => format!(
+            "<html><script>var ytInitialPlayerResponse = {};</script>\
+            <script src=\"{}\"></script></html>",
+            player_response, BASE_JS_PATH,
+        ),
+    };
+    let embed_page = format!("<html><script src=\"{}\"></script></html>", BASE_JS_PATH);
+    let base_js = read_fixture("base.js");
+
+    MockServer::start(move |request| {
+        let path = request.path.as_str();
+        if path.starts_with("/watch") {
+            MockResponse::ok(watch_page.clone())
+        } else if path.starts_with("/embed/") {
+            MockResponse::ok(embed_page.clone())
+        } else if path == BASE_JS_PATH {
+            MockResponse::ok(base_js.clone())
+        } else if path.starts_with("/youtubei/v1/player") {
+            MockResponse::json(&player_response)
+        } else {
+            MockResponse::status(404)
+        }
+    }).await
+}
+
+/// The name of the variant of `err`, i.e. `VideoUnavailable`.
+fn variant_name(err: &Error) -> String {
+    format!("{:?}", err)
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap()
+        .to_owned()
+}
+
+/// The flags, that apply to `video` (see the module level documentation).
+fn flags(video: &Video) -> Vec<String> {
+    let streams = video.streams();
+    let applies = |flag: &str| match flag {
+        "age_restricted" => video.is_age_restricted(),
+        "ciphered" => streams.iter().any(|stream| stream.signature_cipher.s.is_some()),
+        "dash_manifest" => video.dash_manifest_url().is_some(),
+        "hdr" => streams.iter().any(|stream| stream.color_info.as_ref().is_some_and(|color_info| {
+            color_info.transfer_characteristics == TransferCharacteristics::SMPTEST2084
+        })),
+        "multi_audio" => video.audio_tracks().len() > 1,
+        "premium" => streams.iter().any(|stream| stream.is_premium_only()),
+        "360" => streams.iter().any(|stream| stream.projection_type != ProjectionType::Rectangular),
+        flag => unreachable!("unknown flag {}", flag),
+    };
+
+    FLAGS.iter().copied().filter(|flag| applies(flag)).map(str::to_owned).collect()
+}
+
+/// Checks all expectations of `sidecar`, and returns the ones, that are not met.
+async fn check(fixture: &CorpusFixture, sidecar: &Sidecar) -> Vec<String> {
+    let mut failures = Vec::new();
+    macro_rules! expect_eq {
+        ($what:literal, $expected:expr, $got:expr) => {
+            if let Some(expected) = $expected {
+                let got = $got;
+                if *expected != got {
+                    failures.push(format!("{}: expected {:?}, got {:?}", $what, expected, got));
+                }
+            }
+        };
+    }
+
+    let player_response: PlayerResponse = match serde_json::from_str(&fixture.player_response) {
+        Ok(player_response) => player_response,
+        Err(err) => return vec![format!("failed to deserialize the player response: {}", err)],
+    };
+    let playability = format!("{:?}", player_response.playability_status.kind());
+    expect_eq!("playability", Some(&sidecar.expect.playability), playability);
+
+    let client = match InnertubeClient::DEFAULT_CHAIN.iter().find(|client| client.name() == sidecar.client) {
+        Some(&client) => client,
+        None => return vec![format!("unknown client {:?}", sidecar.client)],
+    };
+    let video_id = player_response.video_details.video_id.clone();
+    let server = mock_youtube_for(serde_json::from_str(&fixture.player_response).unwrap(), sidecar.age_gate).await;
+    let res = VideoFetcher::from_id(video_id)
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_innertube_clients(vec![client])
+        .fetch()
+        .await
+        .and_then(|descrambler| descrambler.descramble());
+
+    let video = match (res, &sidecar.expect.error) {
+        (Ok(video), None) => video,
+        (Ok(_), Some(error)) => return vec![format!("expected the error {}, but the video was descrambled", error)],
+        (Err(err), None) => return vec![format!("failed to fetch, or descramble the video: {}", err)],
+        (Err(err), expected) => {
+            expect_eq!("error", expected.as_ref(), variant_name(&err));
+            return failures;
+        }
+    };
+
+    if !video.descramble_errors().is_empty() {
+        failures.push(format!("descramble errors: {:?}", video.descramble_errors()));
+    }
+    let unsigned: Vec<u64> = video.streams()
+        .iter()
+        .filter(|stream| !stream.signature_cipher.url.query_pairs().any(|(key, _)| key == "sig"))
+        .map(|stream| stream.itag)
+        .collect();
+    if !unsigned.is_empty() {
+        failures.push(format!("the streams {:?} are not signed", unsigned));
+    }
+
+    let label = |stream: Option<&rustube::Stream>| stream
+        .and_then(|stream| stream.quality_label.as_ref())
+        .map_or_else(String::new, |quality_label| quality_label.to_string());
+    expect_eq!("streams", sidecar.expect.streams.as_ref(), video.streams().len());
+    expect_eq!("best_quality", sidecar.expect.best_quality.as_ref(), label(video.best_quality()));
+    expect_eq!("best_video", sidecar.expect.best_video.as_ref(), label(video.best_video()));
+    expect_eq!("live_status", sidecar.expect.live_status.as_ref(), format!("{:?}", video.live_status()));
+    expect_eq!("flags", sidecar.expect.flags.as_ref(), flags(&video));
+
+    failures
+}
+
+#[test_log::test(tokio::test)]
+async fn corpus() {
+    let fixtures = corpus_fixtures();
+    assert!(!fixtures.is_empty());
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        let sidecar: Sidecar = toml::from_str(&fixture.sidecar)
+            .unwrap_or_else(|err| panic!("invalid sidecar {}.toml: {}", fixture.name, err));
+        failures.extend(
+            check(fixture, &sidecar)
+                .await
+                .into_iter()
+                .map(|failure| format!("{} ({}): {}", fixture.name, sidecar.description, failure))
+        );
+    }
+
+    assert!(failures.is_empty(), "the corpus does not meet its expectations:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn corpus_covers_the_tricky_cases() {
+    let names: Vec<String> = corpus_fixtures().into_iter().map(|fixture| fixture.name).collect();
+    for name in [
+        "pre_signed", "ciphered", "age_restricted_embed", "live", "post_live_dvr", "hdr", "vr360",
+        "multi_audio", "members_only", "region_blocked", "premium",
+    ] {
+        assert!(names.iter().any(|n| n == name), "the corpus lacks the fixture {}", name);
+    }
+}
+
+#[test]
+fn ids_are_unique() {
+    let mut ids: Vec<String> = corpus_fixtures()
+        .iter()
+        .map(|fixture| {
+            let player_response: Value = serde_json::from_str(&fixture.player_response).unwrap();
+            let id = player_response["videoDetails"]["videoId"].as_str().unwrap().to_owned();
+            assert!(Id::from_str(&id).is_ok(), "{}: invalid video id {}", fixture.name, id);
+            id
+        })
+        .collect();
+    let len = ids.len();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), len, "the video ids of the fixtures are not unique");
+}
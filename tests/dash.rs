@@ -0,0 +1,212 @@
+#![cfg(feature = "fetch")]
+
+use std::time::Duration;
+
+use url::Url;
+
+use common::*;
+use rustube::dash::DashManifest;
+
+#[macro_use]
+mod common;
+
+const MANIFEST_URL: &str = "https://manifest.googlevideo.com/api/manifest/dash/expire/1700000000/id/dvr/source/yt_live_broadcast/";
+
+fn manifest() -> DashManifest {
+    DashManifest::parse(&read_fixture("dvr_manifest.mpd"), &Url::parse(MANIFEST_URL).unwrap()).unwrap()
+}
+
+fn urls<'a>(urls: impl IntoIterator<Item=&'a Url>) -> Vec<&'a str> {
+    urls.into_iter().map(Url::as_str).collect()
+}
+
+#[test]
+fn expands_dvr_manifest() {
+    let manifest = manifest();
+
+    assert_eq!(manifest.duration, Some(Duration::from_secs(20)));
+    let ids: Vec<&str> = manifest.representations.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids, ["140", "137", "136", "251"]);
+
+    // segment list
+    let audio = manifest.representation_by_itag(140).unwrap();
+    assert_eq!(audio.mime_type.as_deref(), Some("audio/mp4"));
+    assert_eq!(audio.codecs.as_deref(), Some("mp4a.40.2"));
+    assert_eq!(audio.bandwidth, Some(144000));
+    let base = "https://rr2---sn-4g5e6nsz.googlevideo.com/videoplayback/expire/1700000000/itag/140/source/yt_live_broadcast/sig/AOq0QJ8wRQIgDvr/";
+    assert_eq!(urls(audio.segment_urls()), [
+        format!("{}sq/0", base),
+        format!("{}sq/1", base),
+        format!("{}sq/2", base),
+        format!("{}sq/3", base),
+    ]);
+
+    // `$Time$` with a timeline, including a repetition until the end of the period
+    let video = manifest.representation("137").unwrap();
+    assert_eq!((video.width, video.height), (Some(1920), Some(1080)));
+    assert_eq!(urls(&video.initialization), [format!("{}video/137/init.mp4", MANIFEST_URL)]);
+    assert_eq!(urls(&video.segments), [
+        format!("{}video/137/t0.m4s", MANIFEST_URL),
+        format!("{}video/137/t450000.m4s", MANIFEST_URL),
+        format!("{}video/137/t900000.m4s", MANIFEST_URL),
+        format!("{}video/137/t1350000.m4s", MANIFEST_URL),
+        format!("{}video/137/t1710000.m4s", MANIFEST_URL),
+        format!("{}video/137/t1755000.m4s", MANIFEST_URL),
+    ]);
+
+    // `$Number$` with a format tag, and the timeline of the adaptation set
+    let video = manifest.representation("136").unwrap();
+    assert_eq!(urls(&video.initialization), [format!("{}video/136/init.mp4", MANIFEST_URL)]);
+    assert_eq!(urls(&video.segments), [
+        format!("{}video/136/00010.m4s", MANIFEST_URL),
+        format!("{}video/136/00011.m4s", MANIFEST_URL),
+        format!("{}video/136/00012.m4s", MANIFEST_URL),
+        format!("{}video/136/00013.m4s", MANIFEST_URL),
+        format!("{}video/136/00014.m4s", MANIFEST_URL),
+        format!("{}video/136/00015.m4s", MANIFEST_URL),
+    ]);
+
+    // a fixed segment duration, without a timeline
+    let opus = manifest.representation("251").unwrap();
+    assert_eq!(opus.codecs.as_deref(), Some("opus"));
+    assert_eq!(opus.segment_count(), 5);
+    assert_eq!(urls(opus.segment_urls()), [
+        format!("{}opus/160000/init.webm", MANIFEST_URL),
+        format!("{}opus/160000/seg-1.webm?rn=1&alr=yes", MANIFEST_URL),
+        format!("{}opus/160000/seg-2.webm?rn=2&alr=yes", MANIFEST_URL),
+        format!("{}opus/160000/seg-3.webm?rn=3&alr=yes", MANIFEST_URL),
+        format!("{}opus/160000/seg-4.webm?rn=4&alr=yes", MANIFEST_URL),
+    ]);
+}
+
+#[test]
+fn invalid_manifests() {
+    let url = Url::parse(MANIFEST_URL).unwrap();
+    let template = |template: &str| format!(
+        r#"<MPD mediaPresentationDuration="PT10S"><Period><AdaptationSet><Representation id="1">{}</Representation></AdaptationSet></Period></MPD>"#,
+        template,
+    );
+
+    let invalid = [
+        String::new(),
+        "<MPD><Period></MPD>".to_owned(),
+        "<Period></Period>".to_owned(),
+        template(r#"<SegmentTemplate duration="1" media="$Unknown$"/>"#),
+        template(r#"<SegmentTemplate duration="1" media="$Number"/>"#),
+        template(r#"<SegmentTemplate duration="0" media="$Number$"/>"#),
+        template(r#"<SegmentTemplate media="$Number$"/>"#),
+        template(r#"<SegmentTemplate initialization="$Number$" duration="1" media="$Number$"/>"#),
+        template(r#"<SegmentList><SegmentURL mediaRange="0-100"/></SegmentList>"#),
+        // repeats until the end of a period with an unknown duration
+        r#"<MPD><Period><AdaptationSet><Representation id="1"><SegmentTemplate media="$Number$"><SegmentTimeline><S d="1" r="-1"/></SegmentTimeline></SegmentTemplate></Representation></AdaptationSet></Period></MPD>"#.to_owned(),
+    ];
+    for xml in &invalid {
+        assert!(matches!(
+            DashManifest::parse(xml, &url),
+            Err(rustube::Error::UnexpectedResponse(_))
+        ), "{}", xml);
+    }
+
+    // a representation without any segment information is a single file
+    let manifest = DashManifest::parse(&template("<BaseURL>video.mp4</BaseURL>"), &url).unwrap();
+    assert_eq!(urls(manifest.representations[0].segment_urls()), [format!("{}video.mp4", MANIFEST_URL)]);
+}
+
+#[cfg(feature = "download")]
+mod download {
+    use std::sync::{Arc, Mutex};
+
+    use rustube::dash::SegmentProgress;
+
+    use super::*;
+
+    const MANIFEST: &str = r#"<?xml version="1.0"?>
+<MPD mediaPresentationDuration="PT3S">
+  <Period>
+    <AdaptationSet mimeType="video/mp4">
+      <BaseURL>segments/</BaseURL>
+      <Representation id="137" bandwidth="4400000">
+        <SegmentTemplate initialization="$RepresentationID$/init" media="$RepresentationID$/$Number$" duration="1"/>
+      </Representation>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+
+    fn player_response(server: &MockServer) -> serde_json::Value {
+        let mut player_response = json_fixture("multi_audio_player_response.json");
+        player_response["videoDetails"]["isPostLiveDvr"] = true.into();
+        player_response["streamingData"]["dashManifestUrl"] = server.url.join("manifest/").unwrap().as_str().into();
+        player_response
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn downloads_all_segments() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/manifest/" => MockResponse::ok(MANIFEST),
+            path => MockResponse::ok(path),
+        }).await;
+        let video = video_from_player_response(player_response(&server));
+        assert!(video.prefers_dash_manifest());
+
+        let manifest = video.dash_manifest().await.unwrap();
+        let representation = manifest.representation_by_itag(137).unwrap();
+        let path = std::env::temp_dir().join(format!("rustube_dash_{}.mp4", std::process::id()));
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let bytes = {
+            let progress = Arc::clone(&progress);
+            video
+                .download_from_dash_manifest(representation, &path, move |p| progress.lock().unwrap().push(p))
+                .await
+                .unwrap()
+        };
+
+        let expected = "/manifest/segments/137/init/manifest/segments/137/1/manifest/segments/137/2/manifest/segments/137/3";
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), expected);
+        assert_eq!(bytes, expected.len() as u64);
+        let progress = progress.lock().unwrap();
+        let counts: Vec<(usize, usize)> = progress
+            .iter()
+            .map(|p: &SegmentProgress| (p.downloaded_segments, p.total_segments))
+            .collect();
+        assert_eq!(counts, [(1, 4), (2, 4), (3, 4), (4, 4)]);
+        assert_eq!(progress[0].bytes, "/manifest/segments/137/init".len() as u64);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn failed_segment_leaves_no_file() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/manifest/" => MockResponse::ok(MANIFEST),
+            "/manifest/segments/137/2" => MockResponse::status(404),
+            path => MockResponse::ok(path),
+        }).await;
+        let video = video_from_player_response(player_response(&server));
+        let manifest = video.dash_manifest().await.unwrap();
+        let path = std::env::temp_dir().join(format!("rustube_dash_failed_{}.mp4", std::process::id()));
+
+        let result = video.download_from_dash_manifest(&manifest.representations[0], &path, |_| {}).await;
+
+        assert!(matches!(result, Err(rustube::Error::Request(_))));
+        assert!(!path.exists());
+        assert!(!std::env::temp_dir().join(format!("rustube_dash_failed_{}.mp4.part", std::process::id())).exists());
+    }
+}
+
+#[test]
+fn prefers_dash_manifest() {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    assert!(!video_from_player_response(player_response.clone()).prefers_dash_manifest());
+
+    player_response["streamingData"]["dashManifestUrl"] = MANIFEST_URL.into();
+    assert!(!video_from_player_response(player_response.clone()).prefers_dash_manifest());
+
+    // a finished live stream, that's longer than four hours
+    player_response["videoDetails"]["isLiveContent"] = true.into();
+    player_response["videoDetails"]["lengthSeconds"] = "14401".into();
+    let video = video_from_player_response(player_response.clone());
+    assert!(video.prefers_dash_manifest());
+    assert_eq!(video.dash_manifest_url().map(Url::as_str), Some(MANIFEST_URL));
+
+    player_response["videoDetails"]["lengthSeconds"] = "14400".into();
+    assert!(!video_from_player_response(player_response).prefers_dash_manifest());
+}
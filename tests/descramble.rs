@@ -0,0 +1,249 @@
+#![cfg(feature = "descramble")]
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{Error, Id, Metrics, NoStreamsReason, PlayerResponse, VideoDescrambler, VideoFetcher, VideoInfo};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const VALID_S: &str = "0123456789";
+/// Descrambling operates on bytes, so shuffling multi-byte characters results in invalid UTF-8.
+const CORRUPT_S: &str = "€€€€€€€€€€";
+
+/// Replaces the pre-signed url of `format` with a signature cipher containing `s`.
+fn set_signature_cipher(format: &mut Value, s: &str) {
+    let url = format.as_object_mut().unwrap().remove("url").unwrap();
+    let url = url.as_str().unwrap().replace("&sig=AOq0QJ8wRQIgPreSigned", "");
+    let url: String = url::form_urlencoded::byte_serialize(url.as_bytes()).collect();
+    let s: String = url::form_urlencoded::byte_serialize(s.as_bytes()).collect();
+    format["signatureCipher"] = json!(format!("s={}&sp=sig&url={}", s, url));
+}
+
+/// The multi audio fixture, where the formats at the given indices (counting the progressive
+/// formats first) require their signature `s` to be descrambled.
+fn player_response(ciphers: &[(usize, &str)]) -> Value {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    let mut i = 0;
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in player_response["streamingData"][formats].as_array_mut().unwrap() {
+            if let Some((_, s)) = ciphers.iter().find(|(index, _)| *index == i) {
+                set_signature_cipher(format, s);
+            }
+            i += 1;
+        }
+    }
+    player_response
+}
+
+async fn descrambler(player_response: Value) -> (VideoDescrambler, MockServer) {
+    descrambler_with_base_js(player_response, read_fixture("base.js")).await
+}
+
+async fn descrambler_with_base_js(player_response: Value, base_js: String) -> (VideoDescrambler, MockServer) {
+    let server = mock_youtube(player_response, base_js, |_| Value::Null).await;
+    let descrambler = VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .fetch()
+        .await
+        .unwrap();
+    (descrambler, server)
+}
+
+#[test_log::test(tokio::test)]
+async fn corrupt_signature_skips_only_its_format() {
+    let (descrambler, _server) = descrambler(player_response(&[(0, VALID_S), (1, CORRUPT_S)])).await;
+    let video = descrambler.descramble().unwrap();
+
+    let itags: Vec<u64> = video.streams().iter().map(|stream| stream.itag).collect();
+    assert_eq!(itags, [18, 140, 140, 140, 251, 251, 251]);
+    match video.descramble_errors() {
+        [(137, Error::Fatal(_))] => {}
+        errors => panic!("expected a single error for itag 137, got {:?}", errors),
+    }
+
+    let sig = video.streams()[0]
+        .signature_cipher
+        .url
+        .query_pairs()
+        .find(|(key, _)| key == "sig")
+        .map(|(_, value)| value.into_owned());
+    assert_eq!(sig.as_deref(), Some("76540213"));
+}
+
+#[test_log::test(tokio::test)]
+async fn missing_signature_skips_only_its_format() {
+    let mut player_response = player_response(&[]);
+    let url = player_response["streamingData"]["adaptiveFormats"][0]["url"].as_str().unwrap();
+    player_response["streamingData"]["adaptiveFormats"][0]["url"] = json!(url.replace("&sig=AOq0QJ8wRQIgPreSigned", ""));
+
+    let (descrambler, _server) = descrambler(player_response).await;
+    let video = descrambler.descramble().unwrap();
+
+    assert_eq!(video.streams().len(), 7);
+    assert!(matches!(video.descramble_errors(), [(137, Error::UnexpectedResponse(_))]), "{:?}", video.descramble_errors());
+}
+
+#[test_log::test(tokio::test)]
+async fn pre_signed_formats_have_no_errors() {
+    let (descrambler, _server) = descrambler(player_response(&[(0, VALID_S)])).await;
+    let video = descrambler.descramble().unwrap();
+
+    assert_eq!(video.streams().len(), 8);
+    assert!(video.descramble_errors().is_empty());
+}
+
+#[test_log::test(tokio::test)]
+async fn all_formats_failing_is_an_error() {
+    let ciphers: Vec<(usize, &str)> = (0..8).map(|i| (i, CORRUPT_S)).collect();
+    let (descrambler, _server) = descrambler(player_response(&ciphers)).await;
+
+    let err = descrambler.descramble().unwrap_err();
+    assert!(err.to_string().contains("- itag 251:"), "{}", err);
+    assert_eq!(err.no_streams_reason(), Some(NoStreamsReason::AllDescrambleFailed));
+    match err {
+        Error::AllFormatsFailed(failures) => assert_eq!(failures.len(), 8),
+        err => panic!("expected Error::AllFormatsFailed, got {:?}", err),
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn unknown_player_layout_keeps_pre_signed_formats() {
+    // a player without any of the scrambling functions, like after YouTube changed its layout
+    let base_js = "var config={signatureTimestamp:19876};".to_owned();
+    let (descrambler, _server) = descrambler_with_base_js(player_response(&[(1, VALID_S), (4, VALID_S)]), base_js).await;
+    let video = descrambler.descramble().unwrap();
+
+    let itags: Vec<u64> = video.streams().iter().map(|stream| stream.itag).collect();
+    assert_eq!(itags, [18, 140, 140, 251, 251, 251]);
+    match video.descramble_errors() {
+        [(137, Error::CipherUnavailable(_)), (140, Error::CipherUnavailable(_))] => {}
+        errors => panic!("expected the cipher error for itag 137 and 140, got {:?}", errors),
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn unknown_player_layout_without_pre_signed_formats_is_an_error() {
+    let base_js = "var config={signatureTimestamp:19876};".to_owned();
+    let ciphers: Vec<(usize, &str)> = (0..8).map(|i| (i, VALID_S)).collect();
+    let (descrambler, _server) = descrambler_with_base_js(player_response(&ciphers), base_js).await;
+
+    match descrambler.descramble().unwrap_err() {
+        Error::AllFormatsFailed(failures) => {
+            assert_eq!(failures.len(), 8);
+            assert!(failures.iter().all(|(_, err)| matches!(err, Error::CipherUnavailable(_))), "{:?}", failures);
+        }
+        err => panic!("expected Error::AllFormatsFailed, got {:?}", err),
+    }
+}
+
+/// A watch page like the one of [`mock_youtube`], with the `player_response` inlined.
+fn watch_html(player_response: &Value) -> String {
+    format!(
+        "<html><script>var ytInitialPlayerResponse = {};</script>\
+        <script src=\"{}\"></script></html>",
+        player_response, BASE_JS_PATH,
+    )
+}
+
+fn fetcher() -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap().into_owned()).unwrap()
+}
+
+fn sigs(video: &rustube::Video) -> Vec<Option<String>> {
+    video
+        .streams()
+        .iter()
+        .map(|stream| stream.signature_cipher.url
+            .query_pairs()
+            .find(|(key, _)| key == "sig")
+            .map(|(_, value)| value.into_owned()))
+        .collect()
+}
+
+#[test_log::test(tokio::test)]
+async fn offline_matches_online() {
+    let player_response = player_response(&[(0, VALID_S), (4, VALID_S)]);
+    let (online, _server) = descrambler(player_response.clone()).await;
+
+    let offline = fetcher()
+        .fetch_offline(&watch_html(&player_response), &read_fixture("base.js"))
+        .unwrap();
+    assert_eq!(offline, online);
+    let (online, offline) = (online.descramble().unwrap(), offline.descramble().unwrap());
+
+    assert_eq!(sigs(&offline), sigs(&online));
+    assert_eq!(sigs(&offline)[0].as_deref(), Some("76540213"));
+    assert!(offline.descramble_errors().is_empty());
+}
+
+#[test]
+fn offline_watch_page() {
+    let descrambler = fetcher()
+        .fetch_offline(&read_fixture("watch_page.html"), "")
+        .unwrap();
+    assert_eq!(descrambler.video_id().as_str(), VIDEO_ID);
+
+    let video = descrambler.descramble().unwrap();
+    assert!(!video.streams().is_empty());
+}
+
+#[test]
+fn offline_errors() {
+    let private = watch_html(&json_fixture("private_player_response.json"));
+    assert!(matches!(fetcher().fetch_offline(&private, ""), Err(Error::VideoUnavailable(_))));
+
+    let without_player_response = "<html><script>var ytInitialPlayerResponse = {\"playabilityStatus\": {\"status\": \"OK\"}};</script></html>";
+    assert!(matches!(
+        fetcher().fetch_offline(without_player_response, ""),
+        Err(Error::UnexpectedResponse(_)),
+    ));
+}
+
+#[test]
+fn from_parts() {
+    let player_response: PlayerResponse = serde_json::from_str(
+        &player_response(&[(0, VALID_S)]).to_string()
+    ).unwrap();
+    let video_info = VideoInfo::from_player_response(player_response, false);
+
+    let video = VideoDescrambler::from_parts(video_info, read_fixture("base.js"), reqwest::Client::new())
+        .descramble()
+        .unwrap();
+    assert_eq!(video.streams().len(), 8);
+    assert_eq!(sigs(&video)[0].as_deref(), Some("76540213"));
+}
+
+#[test]
+fn cipher_is_parsed_once_per_player() {
+    // the cache is shared by all tests, so the player has to be unique
+    let base_js = format!("{}\n// cipher_is_parsed_once_per_player", read_fixture("base.js"));
+    let watch_html = watch_html(&player_response(&[(0, VALID_S), (4, VALID_S)]));
+    let metrics = Arc::new(Metrics::new());
+    let descramble = |uncached: bool| {
+        let descrambler = fetcher()
+            .with_metrics(Arc::clone(&metrics))
+            .fetch_offline(&watch_html, &base_js)
+            .unwrap();
+        match uncached {
+            false => descrambler.descramble(),
+            true => descrambler.descramble_uncached(),
+        }.unwrap()
+    };
+
+    let first = descramble(false);
+    let second = descramble(false);
+    assert_eq!(metrics.cipher_parses(), 1);
+    assert_eq!(sigs(&first), sigs(&second));
+    assert_eq!(sigs(&second)[0].as_deref(), Some("76540213"));
+
+    let uncached = descramble(true);
+    assert_eq!(metrics.cipher_parses(), 2);
+    assert_eq!(metrics.report().cipher_parses, 2);
+    assert_eq!(sigs(&uncached), sigs(&first));
+}
@@ -8,7 +8,7 @@ use rustube::VideoFetcher;
 #[macro_use]
 mod common;
 
-#[test_env_log::test(tokio::test)]
+#[test_log::test(tokio::test)]
 #[ignore]
 async fn download() {
     let id = random_id(PRE_SIGNED);
@@ -24,7 +24,7 @@ async fn download() {
     correct_path!(path, expected_path);
 }
 
-#[test_env_log::test(tokio::test)]
+#[test_log::test(tokio::test)]
 #[ignore]
 async fn download_age_restricted_to_dir() {
     let id = random_id(AGE_RESTRICTED);
@@ -60,7 +60,7 @@ fn blocking_download_to_dir() {
     correct_path!(path, expected_path);
 }
 
-#[test_env_log::test(tokio::test)]
+#[test_log::test(tokio::test)]
 #[ignore]
 async fn download_to_dir() {
     let id = random_id(PRE_SIGNED);
@@ -76,7 +76,7 @@ async fn download_to_dir() {
     correct_path!(path, expected_path);
 }
 
-#[test_env_log::test(tokio::test)]
+#[test_log::test(tokio::test)]
 #[ignore]
 async fn download_to() {
     let id = random_id(PRE_SIGNED);
@@ -91,3 +91,48 @@ async fn download_to() {
 
     correct_path!(&path, path);
 }
+
+#[test_log::test(tokio::test)]
+#[ignore]
+#[cfg(feature = "regex")]
+async fn simple_download() {
+    use rustube::SimpleDownloadOptions;
+
+    let id = random_id(PRE_SIGNED);
+    let expected_path = download_path_from_id(id.as_borrowed()).await;
+
+    let options = SimpleDownloadOptions::new()
+        .target_dir(DOWNLOAD_DIR)
+        .max_resolution(360)
+        .filename(format!("{}.mp4", id.as_str()));
+    let path = rustube::download(id.as_str(), options)
+        .await
+        .unwrap();
+
+    correct_path!(path, expected_path);
+}
+
+#[test_log::test(tokio::test)]
+#[ignore]
+async fn download_all_to_dir() {
+    use rustube::ArchiveOptions;
+    use rustube::archive::ARCHIVE_METADATA_FILE;
+
+    let id = random_id(PRE_SIGNED);
+    let video = video!(id.as_owned());
+
+    let options = ArchiveOptions::new()
+        .filter(|stream| !stream.includes_video_track);
+    let report = video
+        .download_all_to_dir(DOWNLOAD_DIR, options)
+        .await
+        .unwrap();
+
+    assert!(report.is_complete(), "{:?}", report.failed);
+    assert!(!report.downloaded.is_empty());
+    assert!(report.dir.join(ARCHIVE_METADATA_FILE).exists());
+    for (_, path) in &report.downloaded {
+        assert!(path.starts_with(&report.dir));
+        assert!(path.exists());
+    }
+}
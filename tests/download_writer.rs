@@ -0,0 +1,76 @@
+#![cfg(feature = "download")]
+
+use common::*;
+use rustube::Error;
+
+#[macro_use]
+mod common;
+
+#[test_log::test(tokio::test)]
+async fn download_to_writer() {
+    let server = MockServer::start(|_| MockResponse::ok(stream_body())).await;
+    let stream = progressive_stream_at(&server, "stream", None);
+
+    let mut buf = Vec::new();
+    assert_eq!(stream.download_to_writer(&mut buf).await.unwrap(), stream_body().len() as u64);
+    assert_eq!(buf, stream_body());
+
+    // any writer works, i.e. a trait object
+    let mut writer: Box<dyn tokio::io::AsyncWrite + Unpin + Send> = Box::new(std::io::Cursor::new(Vec::new()));
+    assert_eq!(stream.download_to_writer(&mut *writer).await.unwrap(), stream_body().len() as u64);
+}
+
+#[test_log::test(tokio::test)]
+async fn content_length_mismatch() {
+    let server = MockServer::start(|_| MockResponse::ok(stream_body())).await;
+    let stream = progressive_stream_at(&server, "stream", Some(2 * stream_body().len()));
+
+    let mut buf = Vec::new();
+    match stream.download_to_writer(&mut buf).await {
+        Err(Error::UnexpectedResponse(reason)) => {
+            assert_eq!(reason, "the download ended after 4096 of 8192 bytes");
+        }
+        res => panic!("expected Error::UnexpectedResponse, got {:?}", res),
+    }
+    // the received bytes were written anyway
+    assert_eq!(buf, stream_body());
+}
+
+#[test_log::test(tokio::test)]
+async fn failed_request() {
+    let server = MockServer::start(|_| MockResponse::status(500)).await;
+    let stream = progressive_stream_at(&server, "stream", None);
+
+    let mut buf = Vec::new();
+    assert!(matches!(stream.download_to_writer(&mut buf).await, Err(Error::Request(_))));
+    assert!(buf.is_empty());
+}
+
+#[cfg(feature = "callback")]
+#[test_log::test(tokio::test)]
+async fn download_to_writer_with_callback() {
+    use std::sync::{Arc, Mutex};
+
+    use rustube::Callback;
+
+    let server = MockServer::start(|_| MockResponse::ok(stream_body())).await;
+    let stream = progressive_stream_at(&server, "stream", None);
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let completed = Arc::new(Mutex::new(Vec::new()));
+    let callback = {
+        let progress = Arc::clone(&progress);
+        let completed = Arc::clone(&completed);
+        Callback::new()
+            .connect_on_progress_closure(move |args| progress.lock().unwrap().push(args.current_chunk))
+            .connect_on_complete_closure(move |path| completed.lock().unwrap().push(path))
+    };
+
+    let mut buf = Vec::new();
+    let bytes = stream.download_to_writer_with_callback(&mut buf, callback).await.unwrap();
+    assert_eq!(bytes, stream_body().len() as u64);
+    assert_eq!(buf, stream_body());
+    assert_eq!(progress.lock().unwrap().last(), Some(&stream_body().len()));
+    // there's no file
+    assert_eq!(*completed.lock().unwrap(), [None]);
+}
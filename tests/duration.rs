@@ -0,0 +1,105 @@
+#![cfg(feature = "descramble")]
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use rustube::{Error, Stream, Video};
+use rustube::duration::{DEFAULT_DURATION_TOLERANCE, DurationMismatch, DurationReport};
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+/// The streams of the multi audio video (615 s long), with the durations `durations`.
+fn streams(durations: &[Option<u64>]) -> Vec<Stream> {
+    let mut streams = video_from_fixture("multi_audio_player_response.json").into_streams();
+    streams.truncate(durations.len());
+    for (stream, &duration) in streams.iter_mut().zip(durations) {
+        stream.approx_duration_ms = duration;
+    }
+    streams
+}
+
+#[test]
+fn tolerance() {
+    let streams = streams(&[Some(615_040), Some(616_500), Some(613_499), None]);
+    let report = DurationReport::new(Some(615_000), &streams, DEFAULT_DURATION_TOLERANCE);
+
+    // a deviation of exactly the tolerance is still fine
+    assert_eq!(report.tolerance_ms, 1500);
+    assert_eq!(report.mismatches, [DurationMismatch { itag: 140, expected_ms: 615_000, got_ms: 613_499 }]);
+    assert_eq!(report.unknown, [140]);
+    assert!(!report.is_consistent());
+
+    let mismatch = report.mismatch(140).unwrap();
+    assert_eq!(mismatch.deviation_ms(), 1501);
+    assert!(mismatch.is_truncated());
+    assert!(report.mismatch(137).is_none());
+
+    let report = DurationReport::new(Some(615_000), &streams, Duration::from_secs(2));
+    assert!(report.is_consistent());
+    let report = DurationReport::new(Some(615_000), &streams, Duration::ZERO);
+    assert_eq!(report.mismatches.iter().map(|mismatch| mismatch.itag).collect::<Vec<_>>(), [18, 137, 140]);
+    assert!(!report.mismatches[1].is_truncated());
+}
+
+#[test]
+fn unknown_video_duration() {
+    let streams = streams(&[Some(1000), None]);
+    let report = DurationReport::new(None, &streams, DEFAULT_DURATION_TOLERANCE);
+
+    assert_eq!(report, DurationReport { tolerance_ms: 1500, ..DurationReport::default() });
+}
+
+#[test]
+fn ensure_consistent() {
+    let streams = streams(&[Some(615_000), Some(600_000)]);
+    let report = DurationReport::new(Some(615_000), &streams, DEFAULT_DURATION_TOLERANCE);
+
+    assert!(report.ensure_consistent(&[18, 140]).is_ok());
+    let err = report.ensure_consistent(&[18, 137]).unwrap_err();
+    assert!(
+        matches!(err, Error::DurationMismatch { itag: 137, expected_ms: 615_000, got_ms: 600_000 }),
+        "{:?}", err,
+    );
+    assert_eq!(err.to_string(), "the stream 137 is 600000 ms long, but the video is 615000 ms long");
+}
+
+#[test]
+fn serialization() {
+    let streams = streams(&[Some(615_000), Some(600_000), None]);
+    let report = DurationReport::new(Some(615_000), &streams, DEFAULT_DURATION_TOLERANCE);
+
+    assert_eq!(serde_json::to_value(&report).unwrap(), json!({
+        "expected_ms": 615000,
+        "tolerance_ms": 1500,
+        "mismatches": [{ "itag": 137, "expected_ms": 615000, "got_ms": 600000 }],
+        "unknown": [140],
+    }));
+}
+
+#[test]
+fn video_duration_consistency() {
+    let video = video_from_fixture("multi_audio_player_response.json");
+    let report = video.duration_consistency();
+    assert_eq!(report.expected_ms, Some(615_000));
+    assert!(report.is_consistent(), "{:?}", report);
+
+    let (video_info, mut streams) = video.into_parts();
+    streams[1].approx_duration_ms = Some(612_000);
+    let video = Video::from_parts(video_info, streams);
+    assert_eq!(video.duration_consistency().mismatches.len(), 1);
+    assert!(video.duration_consistency_with_tolerance(Duration::from_secs(5)).is_consistent());
+}
+
+#[test]
+fn live_streams_are_not_compared() {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    player_response["videoDetails"]["lengthSeconds"] = "0".into();
+    let report = video_from_player_response(player_response).duration_consistency();
+
+    assert_eq!(report.expected_ms, None);
+    assert!(report.is_consistent());
+}
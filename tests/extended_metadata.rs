@@ -0,0 +1,105 @@
+#![cfg(feature = "fetch")]
+
+use std::sync::Arc;
+
+use common::*;
+use rustube::{ExtendedMetadata, Id, Metrics, Phase, VideoFetcher};
+use rustube::video_info::extended_metadata::{License, MetadataLink};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+
+/// A server, that answers requests to the `next` endpoint with the fixture `name`.
+async fn next_server(name: &'static str) -> MockServer {
+    MockServer::start(move |request| match request.path.starts_with("/youtubei/v1/next") {
+        true => MockResponse::json(&json_fixture(name)),
+        false => MockResponse::status(404),
+    }).await
+}
+
+fn fetcher(server: &MockServer) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+}
+
+fn link(text: &str, url: Option<&str>) -> MetadataLink {
+    MetadataLink { text: text.to_owned(), url: url.map(|url| url.parse().unwrap()) }
+}
+
+#[test_log::test(tokio::test)]
+async fn creative_commons() {
+    let server = next_server("next_creative_commons.json").await;
+    let metrics = Arc::new(Metrics::new());
+
+    let metadata = fetcher(&server)
+        .with_metrics(Arc::clone(&metrics))
+        .fetch_extended_metadata()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        metadata.category,
+        Some(link("Film & Animation", Some("https://www.youtube.com/channel/UCFTYy7e5b4ZxeYxTr6RzuSg"))),
+    );
+    assert_eq!(
+        metadata.license,
+        License::CreativeCommons("Creative Commons Attribution license (reuse allowed)".to_owned()),
+    );
+    assert!(!metadata.has_paid_promotion);
+    // the whitespace between the hashtags is no link
+    assert_eq!(metadata.super_title_links, [
+        link("#blender", Some("https://www.youtube.com/hashtag/blender")),
+        link("#openmovie", Some("https://www.youtube.com/hashtag/openmovie")),
+    ]);
+    let titles: Vec<_> = metadata.rows.iter().map(|row| row.title.as_str()).collect();
+    assert_eq!(titles, ["Category", "License"]);
+
+    // a single request as the web client
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let body = requests[0].json();
+    assert_eq!(body["videoId"], VIDEO_ID);
+    assert_eq!(body["context"]["client"]["clientName"], "WEB");
+    assert_eq!(metrics.phase(Phase::NextApi).count, 1);
+}
+
+#[test_log::test(tokio::test)]
+async fn paid_promotion() {
+    let server = next_server("next_paid_promotion.json").await;
+
+    let metadata = fetcher(&server).fetch_extended_metadata().await.unwrap();
+
+    assert!(metadata.has_paid_promotion);
+    // a category without a link, and no license row
+    assert_eq!(metadata.category, Some(link("Science & Technology", None)));
+    assert_eq!(metadata.license, License::Standard);
+    assert!(metadata.super_title_links.is_empty());
+}
+
+#[test]
+fn tolerates_missing_renderers() {
+    assert_eq!(ExtendedMetadata::from_next_response(&serde_json::json!({})), ExtendedMetadata::default());
+
+    let mut response = json_fixture("next_creative_commons.json");
+    let contents = &mut response["contents"]["twoColumnWatchNextResults"]["results"]["results"]["contents"];
+    contents.as_array_mut().unwrap().remove(0);
+    // the license row has no usable contents anymore
+    contents[0]["videoSecondaryInfoRenderer"]["metadataRowContainer"]["metadataRowContainerRenderer"]["rows"][2]
+        ["metadataRowRenderer"]["contents"][0] = "not a run".into();
+
+    let metadata = ExtendedMetadata::from_next_response(&response);
+    assert!(metadata.super_title_links.is_empty());
+    assert_eq!(metadata.category.unwrap().text, "Film & Animation");
+    assert_eq!(metadata.license, License::Standard);
+}
+
+#[test_log::test(tokio::test)]
+async fn failed_request() {
+    let server = MockServer::start(|_| MockResponse::status(500)).await;
+
+    let err = fetcher(&server).fetch_extended_metadata().await.unwrap_err();
+    assert!(matches!(err, rustube::Error::Request(_)), "{:?}", err);
+}
@@ -0,0 +1,335 @@
+//! Compile time checks of the API each documented feature enables.
+//!
+//! The tests only check, that the items exist with the expected signatures. CI builds this file
+//! for each feature on its own (`cargo hack check --each-feature --test feature_combos`), so a
+//! feature, that does not compile on its own, or changes its API by accident, is caught.
+
+#![allow(dead_code, clippy::let_underscore_future)]
+
+use rustube::IdBuf;
+
+#[test]
+fn id() {
+    let id = IdBuf::from_string("fDzQ3kUg8Ss".to_owned()).unwrap();
+    assert_eq!(id.as_str(), "fDzQ3kUg8Ss");
+    let _: rustube::prelude::IdBuf = id;
+}
+
+#[cfg(all(feature = "regex", feature = "std"))]
+#[test]
+fn regex() {
+    let id = rustube::Id::from_raw("https://youtube.com/watch?v=fDzQ3kUg8Ss").unwrap();
+    assert_eq!(id.as_str(), "fDzQ3kUg8Ss");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn std() {
+    let _: fn(&rustube::Error) -> String = ToString::to_string;
+    let _: rustube::Result<()> = Ok(());
+    let _: rustube::prelude::Result<(), rustube::prelude::Error> = Ok(());
+}
+
+#[cfg(feature = "fetch")]
+#[test]
+fn fetch() {
+    use rustube::{VideoFetcher, VideoInfo};
+    use rustube::fetcher::ClientBuilder;
+
+    let _: fn(IdBuf) -> rustube::Result<VideoFetcher> = VideoFetcher::from_id;
+    let _: fn() -> ClientBuilder = ClientBuilder::recommended;
+    let _: fn(ClientBuilder, std::net::IpAddr) -> ClientBuilder = ClientBuilder::local_address;
+    let _: fn(&rustube::Error) -> bool = rustube::Error::is_bot_check;
+    let _: fn(&rustube::Error) -> bool = rustube::Error::is_retryable;
+    let _: fn(VideoFetcher, u32) -> VideoFetcher = VideoFetcher::with_rate_limit_retries;
+    let _: fn(VideoFetcher, rustube::reqwest::header::HeaderMap) -> VideoFetcher = VideoFetcher::with_headers;
+    let _: fn(IdBuf, rustube::fetcher::auth::AuthConfig) -> rustube::Result<VideoFetcher> = VideoFetcher::from_id_authenticated;
+    let _: fn(VideoFetcher, rustube::fetcher::auth::OAuth) -> VideoFetcher = VideoFetcher::with_oauth;
+    let _: fn(rustube::InnertubeClient) -> rustube::reqwest::header::HeaderMap = rustube::fetcher::recommended_headers_for;
+    let _: fn(&rustube::Metrics) -> u64 = rustube::Metrics::rate_limit_hits;
+    let _: fn(&rustube::Metrics) -> u64 = rustube::Metrics::cipher_parses;
+    let _ = |fetcher: VideoFetcher| async move {
+        let _: rustube::Result<VideoInfo> = fetcher.clone().fetch_info().await;
+        let _: rustube::fetcher::SelfCheckReport = fetcher.self_check().await;
+    };
+    let _ = async {
+        let _: rustube::Result<rustube::SelfCheckReport> = rustube::self_check(None).await;
+    };
+    let _: fn(&str, &url::Url) -> rustube::Result<rustube::dash::DashManifest> = rustube::dash::DashManifest::parse;
+    let _: fn(&str, &url::Url) -> rustube::Result<rustube::hls::HlsMasterPlaylist> = rustube::hls::HlsMasterPlaylist::parse;
+    let _: fn(&str, &url::Url) -> rustube::Result<rustube::hls::HlsMediaPlaylist> = rustube::hls::HlsMediaPlaylist::parse;
+    let _: fn(&rustube::video_info::player_response::video_details::Thumbnails) -> Option<&rustube::video_info::player_response::video_details::Thumbnail> =
+        rustube::video_info::player_response::video_details::Thumbnails::best;
+    let _: (rustube::prelude::Quality, rustube::prelude::QualityLabel, rustube::prelude::AudioQuality) =
+        (rustube::prelude::Quality::Hd720, rustube::prelude::QualityLabel::P720, rustube::prelude::AudioQuality::Medium);
+    let _: fn(IdBuf) -> rustube::Result<rustube::prelude::VideoFetcher> = rustube::prelude::VideoFetcher::from_id;
+    let _: fn(&rustube::video_info::player_response::playability_status::PlayabilityStatus) -> Option<rustube::PlayerResponse> =
+        rustube::video_info::player_response::playability_status::PlayabilityStatus::trailer;
+    let _: fn(VideoFetcher, std::sync::Arc<rustube::TransferStats>) -> VideoFetcher = VideoFetcher::with_transfer_stats;
+    let _: fn(u64) -> rustube::TransferStats = rustube::TransferStats::limit;
+    let _: fn(&rustube::TransferStats) = rustube::TransferStats::reset;
+    let _: fn(&VideoInfo) -> &[rustube::video_info::player_response::annotations::EndscreenElement] = VideoInfo::endscreen_items;
+    let _: fn(&VideoInfo) -> &[rustube::video_info::player_response::annotations::Card] = VideoInfo::cards;
+    let _: fn(&VideoInfo) -> Vec<rustube::video_info::player_response::storyboards::Storyboard> = VideoInfo::storyboards;
+    let _: fn(&VideoInfo) -> Option<Vec<rustube::video_info::player_response::heat_map::HeatMarker>> = VideoInfo::heat_map;
+    let _: fn(&VideoInfo) -> Vec<rustube::chapters::Chapter> = VideoInfo::chapters;
+    let _: fn(&str) -> Vec<rustube::chapters::Chapter> = rustube::chapters::Chapter::from_description;
+    let _: fn(VideoFetcher, rustube::ParseMode) -> VideoFetcher = VideoFetcher::with_parse_mode;
+    let _: fn(&VideoFetcher) -> rustube::ParseMode = VideoFetcher::parse_mode;
+    let _: fn(&rustube::ParseMode) -> bool = rustube::ParseMode::is_strict;
+    let _ = |fetcher: VideoFetcher| async move {
+        let _: rustube::Result<rustube::ExtendedMetadata> = fetcher.fetch_extended_metadata().await;
+    };
+    let _: fn(&serde_json::Value) -> rustube::ExtendedMetadata = rustube::ExtendedMetadata::from_next_response;
+    let _: fn(&str, usize) -> String = rustube::text::safe_title_for_filename;
+    let _: fn(&str) -> String = rustube::text::sanitize_for_display;
+    let _: fn(&rustube::Error) -> Option<rustube::NoStreamsReason> = rustube::Error::no_streams_reason;
+    let _: fn(&rustube::NoStreamsReason) -> &'static str = rustube::NoStreamsReason::advice;
+    let _: fn(&VideoInfo) -> std::time::Duration = VideoInfo::duration;
+    let _: fn(&VideoInfo, &str) -> bool = VideoInfo::has_keyword;
+    let _: fn(&rustube::FetchFilter, &VideoInfo) -> rustube::FilterDecision = rustube::FetchFilter::decide;
+    let _ = |fetcher: VideoFetcher, filter: rustube::FetchFilter| async move {
+        let _: rustube::Result<rustube::FetchOutcome> = fetcher.fetch_filtered(&filter).await;
+    };
+}
+
+#[cfg(feature = "descramble")]
+#[test]
+fn descramble() {
+    use std::path::PathBuf;
+
+    use rustube::{Video, VideoDescrambler, VideoFetcher};
+
+    let _ = |fetcher: VideoFetcher| async move {
+        let _: rustube::Result<VideoDescrambler> = fetcher.clone().fetch().await;
+        let _: rustube::Result<Option<Video>> = fetcher.fetch_trailer().await;
+    };
+    let _: fn(&Video) -> String = Video::display_title;
+    // descrambling does not make any requests, so it's synchronous
+    let _: fn(VideoDescrambler) -> rustube::Result<Video> = VideoDescrambler::descramble;
+    let _: fn(VideoDescrambler) -> rustube::Result<Video> = VideoDescrambler::descramble_uncached;
+    let _: fn(rustube::VideoInfo, String, rustube::reqwest::Client) -> VideoDescrambler = VideoDescrambler::from_parts;
+    let _: fn(VideoFetcher, &str, &str) -> rustube::Result<VideoDescrambler> = VideoFetcher::fetch_offline;
+    let _: fn(VideoDescrambler) -> rustube::VideoInfo = VideoDescrambler::into_video_info;
+    let _: fn(Video) -> (rustube::VideoInfo, Vec<rustube::Stream>) = Video::into_parts;
+    let _: fn(VideoDescrambler) -> rustube::Result<Video> = std::convert::TryFrom::try_from;
+    let _: fn(Video) -> rustube::VideoInfo = Into::into;
+    let _: fn(&Video) -> Option<u64> = Video::suggested_max_bitrate;
+    let _: fn(&Video) -> &[rustube::captions::CaptionTrack] = Video::caption_tracks;
+    let _: fn(&Video) -> rustube::DurationReport = Video::duration_consistency;
+    let _: fn(&Video) -> rustube::NoStreamsReason = Video::no_streams_reason;
+    let _: fn(&rustube::Stream) -> Option<std::net::IpAddr> = rustube::Stream::bound_ip;
+    let _: fn(&rustube::Stream) -> bool = rustube::Stream::is_ip_bound;
+    let _: fn(&rustube::Stream) -> Option<chrono::DateTime<chrono::Utc>> = rustube::Stream::expires_at;
+    let _: fn(&rustube::Stream) -> bool = rustube::Stream::is_expired;
+    let _ = |mut stream: rustube::Stream, fetcher: VideoFetcher| async move {
+        let _: rustube::Result<()> = stream.refresh().await;
+        let _: rustube::Result<()> = stream.refresh_with(fetcher).await;
+    };
+    let _ = |video: Video, fetcher: VideoFetcher| async move {
+        let _: rustube::Result<Video> = video.refetch_streams_via(rustube::reqwest::Client::new()).await;
+        let _: rustube::Result<Video> = fetcher.refetch_streams(&video).await;
+    };
+    let _: fn(&str) -> rustube::Result<rustube::FilenameTemplate> = rustube::FilenameTemplate::parse;
+    let _: fn(&rustube::FilenameTemplate, &rustube::VideoInfo, &rustube::Stream) -> PathBuf =
+        rustube::FilenameTemplate::render;
+}
+
+#[cfg(feature = "stream")]
+#[test]
+fn stream() {
+    use rustube::{CodecSupport, Stream, StreamQuery, StreamRank, VideoCodec};
+
+    let _: fn(&Stream) -> Option<VideoCodec> = Stream::video_codec;
+    let _: fn(&Stream, &CodecSupport) -> bool = Stream::is_supported_by;
+    let _: fn(&Stream) -> StreamRank = Stream::rank;
+    let _: fn(&Stream) -> Option<u64> = Stream::estimated_content_length;
+    let _: fn(&StreamQuery, &Stream) -> bool = StreamQuery::matches;
+    let _ = |query: StreamQuery, streams: &[Stream]| {
+        let _: Option<&Stream> = query.only_video().mime("video/mp4").best(streams);
+    };
+}
+
+#[cfg(feature = "download")]
+#[test]
+fn download() {
+    use std::path::PathBuf;
+
+    use rustube::{SimpleDownloadOptions, Stream};
+
+    let _ = |stream: Stream| async move {
+        let _: rustube::Result<PathBuf> = stream.download().await;
+    };
+    let _ = SimpleDownloadOptions::new()
+        .audio_only(true)
+        .write_buffer_size(rustube::stream::DEFAULT_WRITE_BUFFER_SIZE)
+        .respect_max_bitrate(true)
+        .filename_from_title(true)
+        .filename_template(rustube::FilenameTemplate::parse("{title}.{ext}").unwrap());
+    let _ = |video: rustube::Video| async move {
+        let manifest: rustube::dash::DashManifest = video.dash_manifest().await.unwrap();
+        let _: rustube::Result<u64> = video
+            .download_from_dash_manifest(&manifest.representations[0], "video.mp4", |_| {})
+            .await;
+    };
+    let _ = |video: rustube::Video| async move {
+        let _: rustube::Result<PathBuf> = video.download_best_audio_to_dir("videos").await;
+    };
+    let _ = |video: rustube::Video| async move {
+        let playlist: rustube::hls::HlsMasterPlaylist = video.hls_playlist().await.unwrap();
+        let _: rustube::Result<u64> = video
+            .download_from_hls_playlist(playlist.best_variant().unwrap(), "video.ts", None, |_| {})
+            .await;
+    };
+    let _: &str = rustube::stream::forbidden::N_NOT_TRANSFORMED;
+    let _ = |stream: Stream| async move {
+        let _: rustube::Result<rustube::DownloadSummary> = stream.download_to_with_summary("video.mp4").await;
+    };
+    let _: fn(&rustube::DownloadSummary) -> &Option<String> = |summary| &summary.host;
+    let _ = |stream: Stream| async move {
+        let mut buf = Vec::new();
+        let _: rustube::Result<u64> = stream.download_to_writer(&mut buf).await;
+    };
+    let _ = |stream: Stream| async move {
+        let _: rustube::Result<()> = stream.download_to_resumable("video.mp4").await;
+    };
+    let _ = |stream: Stream| async move {
+        let bytes: rustube::StreamReader = stream.bytes_stream().await.unwrap();
+        let _: &dyn tokio_stream::Stream<Item = rustube::Result<bytes::Bytes>> = &bytes;
+        let _: rustube::Result<rustube::StreamReader> = stream.bytes_stream_from(1024).await;
+    };
+    let _ = |stream: Stream| async move {
+        let _: rustube::Result<()> = stream.download_chunked("video.mp4", 1024 * 1024, 4).await;
+        let options = rustube::ChunkedDownloadOptions::new()
+            .chunk_size(rustube::stream::chunked::DEFAULT_CHUNK_SIZE)
+            .concurrency(8)
+            .retries(5);
+        let _: rustube::Result<()> = stream.download_chunked_with("video.mp4", options).await;
+    };
+    let shutdown = rustube::ShutdownHandle::new();
+    let _ = SimpleDownloadOptions::new().shutdown(shutdown.clone());
+    let _: fn(Stream, rustube::ShutdownHandle) -> Stream = Stream::with_shutdown;
+    let policy = rustube::RetryPolicy::new()
+        .retries(5)
+        .backoff(rustube::stream::retry::DEFAULT_RETRY_BACKOFF)
+        .refresh_on_forbidden(true)
+        .refresh_expired(true);
+    let _ = SimpleDownloadOptions::new().retry_policy(policy.clone());
+    let _: fn(Stream, rustube::RetryPolicy) -> Stream = Stream::with_retry_policy;
+    let _ = |shutdown: rustube::ShutdownHandle| async move {
+        let _: bool = shutdown.shutdown(std::time::Duration::from_secs(1)).await;
+    };
+    let _ = || rustube::fetch_many(Vec::new(), rustube::FetchManyOptions::new().concurrency(2));
+}
+
+#[cfg(feature = "remux")]
+#[test]
+fn remux() {
+    use std::path::PathBuf;
+
+    let _: fn(PathBuf) -> rustube::Result<()> = rustube::postprocess::remux_fmp4_to_mp4;
+    let _: fn(&rustube::Stream) -> bool = rustube::Stream::needs_remux_for_playback;
+}
+
+#[cfg(feature = "package")]
+#[test]
+fn package() {
+    use rustube::package::{Container, MuxJob, PackageOptions, PackageProgress};
+
+    let _ = PackageOptions::new()
+        .subtitle_langs(["en"])
+        .container(Container::Mp4)
+        .strict_duration(true)
+        .on_progress(|_: PackageProgress| {});
+    let _: fn(&MuxJob) -> Vec<std::ffi::OsString> = MuxJob::args;
+    let _ = |video: rustube::Video| async move {
+        let _: rustube::Result<()> = video.download_package_to("video.mkv", PackageOptions::new()).await;
+    };
+}
+
+#[cfg(feature = "search")]
+#[test]
+fn search() {
+    use rustube::{Search, SearchResults};
+
+    let _: fn() -> rustube::Result<Search> = Search::new;
+    let _: fn(rustube::reqwest::Client) -> Search = Search::with_client;
+    let _ = |search: Search| async move {
+        let _: rustube::Result<SearchResults> = search.query("rust").await;
+    };
+    let _ = |results: SearchResults| async move {
+        let _: Vec<rustube::SearchVideo> = results.videos.clone();
+        let _: rustube::Result<Option<SearchResults>> = results.next_page().await;
+    };
+}
+
+#[cfg(feature = "comments")]
+#[test]
+fn comments() {
+    use rustube::{Comment, Comments, CommentSort, Id};
+
+    let _: fn(Id<'_>, &rustube::reqwest::Client) -> Comments = Comments::new;
+    let _: fn(Comments, CommentSort) -> Comments = Comments::sort;
+    let _: fn(Comments, u32) -> Comments = Comments::with_rate_limit_retries;
+    let _ = |comments: Comments| {
+        let _: Box<dyn tokio_stream::Stream<Item=rustube::Result<Comment>>> = Box::new(comments.stream());
+    };
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn cache() {
+    use std::sync::Arc;
+
+    use rustube::cache::{CacheMode, FileCache, ResponseCache};
+
+    let cache: Arc<dyn ResponseCache> = Arc::new(FileCache::new("cache"));
+    let _ = |fetcher: rustube::VideoFetcher| fetcher
+        .with_response_cache(cache.clone())
+        .with_cache_mode(CacheMode::ReadOnly)
+        .with_cache_ttl(rustube::cache::DEFAULT_TTL);
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn serialize() {
+    use rustube::serialize::{OutputLevel, StreamSerializer, VideoSerializer};
+
+    let _: fn(&rustube::Video, OutputLevel) -> serde_json::Value = rustube::Video::serialize_with_level;
+    let _: fn(&rustube::Stream, OutputLevel) -> serde_json::Value = rustube::Stream::serialize_with_level;
+    let _ = |video: &rustube::Video| {
+        let _: VideoSerializer = VideoSerializer::new(video.video_info(), video.streams(), OutputLevel::all());
+    };
+    let _ = |stream: &rustube::Stream| {
+        let _: StreamSerializer = StreamSerializer::new(stream, OutputLevel::URL | OutputLevel::THUMBNAILS);
+    };
+}
+
+#[cfg(feature = "callback")]
+#[test]
+fn callback() {
+    let _ = rustube::Callback::new().connect_on_progress_closure(|_| {});
+    let _ = rustube::prelude::Callback::new().connect_on_progress_closure(|_: rustube::prelude::CallbackArguments| {});
+}
+
+#[cfg(feature = "microformat")]
+#[test]
+fn microformat() {
+    let _: Option<&rustube::Microformat> = None;
+}
+
+#[cfg(all(feature = "blocking", feature = "descramble"))]
+#[test]
+fn blocking() {
+    use rustube::blocking::{Video, VideoDescrambler, VideoFetcher};
+
+    let _: fn(VideoFetcher) -> rustube::Result<VideoDescrambler> = VideoFetcher::fetch;
+    let _: fn(VideoDescrambler) -> rustube::Result<Video> = VideoDescrambler::descramble;
+}
+
+#[cfg(all(feature = "blocking", feature = "download"))]
+#[test]
+fn blocking_download() {
+    let _: fn(&rustube::Stream, &mut Vec<u8>) -> rustube::Result<u64> = rustube::Stream::blocking_download_to_writer;
+    let _: fn(&rustube::Stream, &'static str) -> rustube::Result<()> = rustube::Stream::blocking_download_to_resumable;
+}
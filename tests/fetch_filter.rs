@@ -0,0 +1,192 @@
+#![cfg(feature = "download")]
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio_stream::StreamExt;
+
+use common::*;
+use rustube::{
+    fetch_many, FetchFilter, FetchManyOptions, FetchOutcome, FilterDecision, Id, InnertubeClient,
+    VideoFetcher, VideoInfo,
+};
+
+#[macro_use]
+mod common;
+
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+const KEPT: &str = "fDzQ3kUg8Ss";
+const TOO_LONG: &str = "5jlI4uzZGjU";
+const UNTAGGED: &str = "dQw4w9WgXcQ";
+
+/// The multi audio fixture, with the id, duration, and keywords replaced.
+fn player_response(id: &str, length_seconds: u64, keywords: &[&str]) -> Value {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    let video_details = &mut player_response["videoDetails"];
+    video_details["videoId"] = id.into();
+    video_details["lengthSeconds"] = length_seconds.to_string().into();
+    video_details["keywords"] = json!(keywords);
+    player_response
+}
+
+fn video_info(length_seconds: u64, keywords: &[&str]) -> VideoInfo {
+    let player_response = player_response(KEPT, length_seconds, keywords);
+    VideoInfo::from_player_response(serde_json::from_value(player_response).unwrap(), false)
+}
+
+fn filter() -> FetchFilter {
+    FetchFilter::max_duration(Duration::from_secs(20 * 60)).and(FetchFilter::keyword("rust"))
+}
+
+/// Serves the watch page, and the player response of each video, by its id.
+async fn server() -> MockServer {
+    let videos: HashMap<&str, Value> = vec![
+        (KEPT, player_response(KEPT, 615, &["Rust", "Tutorial"])),
+        (TOO_LONG, player_response(TOO_LONG, 1800, &["rust"])),
+        (UNTAGGED, player_response(UNTAGGED, 300, &["cooking"])),
+    ].into_iter().collect();
+
+    MockServer::start(move |request| {
+        let path = request.path.as_str();
+        if let Some(player_response) = path.strip_prefix("/watch?v=").and_then(|id| videos.get(id)) {
+            MockResponse::ok(format!(
+                "<html><script>var ytInitialPlayerResponse = {};</script>\
+                <script src=\"{}\"></script></html>",
+                player_response, BASE_JS_PATH,
+            ))
+        } else if path == BASE_JS_PATH {
+            MockResponse::ok(BASE_JS)
+        } else if path.starts_with("/youtubei/v1/player") {
+            match videos.get(request.json()["videoId"].as_str().unwrap()) {
+                Some(player_response) => MockResponse::json(player_response),
+                None => MockResponse::status(404),
+            }
+        } else {
+            MockResponse::status(404)
+        }
+    }).await
+}
+
+fn fetcher(server: &MockServer, id: &str) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(id).unwrap().into_owned())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_innertube_clients(vec![InnertubeClient::Android])
+}
+
+/// The number of requests to paths, that start with `prefix`.
+fn count(server: &MockServer, prefix: &str) -> usize {
+    server.requests().iter().filter(|request| request.path.starts_with(prefix)).count()
+}
+
+#[test]
+fn duration_and_keywords() {
+    let video_info = video_info(615, &["Rust", "Tutorial"]);
+
+    assert_eq!(video_info.duration(), Duration::from_secs(615));
+    assert!(video_info.has_keyword("rust"));
+    assert!(video_info.has_keyword("TUTORIAL"));
+    assert!(!video_info.has_keyword("rus"));
+    assert!(!video_info.has_keyword("cooking"));
+}
+
+#[test]
+fn decisions() {
+    assert_eq!(filter().decide(&video_info(615, &["RUST"])), FilterDecision::Keep);
+    assert_eq!(
+        filter().decide(&video_info(1800, &[])),
+        FilterDecision::Skip("the video is 1800 s long, which is longer than 1200 s".to_owned()),
+    );
+    assert_eq!(
+        filter().decide(&video_info(300, &["cooking"])),
+        FilterDecision::Skip("the video is not tagged with \"rust\"".to_owned()),
+    );
+
+    // live streams have no duration
+    assert_eq!(video_info(0, &[]).duration(), Duration::ZERO);
+    assert_eq!(FetchFilter::max_duration(Duration::ZERO).decide(&video_info(0, &[])), FilterDecision::Keep);
+    assert_eq!(FetchFilter::default().decide(&video_info(1800, &[])), FilterDecision::Keep);
+}
+
+#[test_log::test(tokio::test)]
+async fn skipped_videos_only_request_the_watch_page() {
+    let server = server().await;
+
+    let outcome = fetcher(&server, TOO_LONG).fetch_filtered(&filter()).await.unwrap();
+    match outcome {
+        FetchOutcome::Skipped { video_info, reason } => {
+            assert_eq!(video_info.player_response.video_details.video_id.as_str(), TOO_LONG);
+            assert!(reason.contains("1800 s"), "{}", reason);
+        }
+        FetchOutcome::Fetched(video) => panic!("expected {} to be skipped", video.id()),
+    }
+
+    assert_eq!(count(&server, "/watch"), 1);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test_log::test(tokio::test)]
+async fn kept_videos_request_the_watch_page_once() {
+    let server = server().await;
+
+    let outcome = fetcher(&server, KEPT).fetch_filtered(&filter()).await.unwrap();
+    match outcome {
+        FetchOutcome::Fetched(video) => assert_eq!(video.streams().len(), 8),
+        FetchOutcome::Skipped { reason, .. } => panic!("expected {} to be kept: {}", KEPT, reason),
+    }
+
+    assert_eq!(count(&server, "/watch"), 1);
+    assert_eq!(count(&server, BASE_JS_PATH), 1);
+    assert_eq!(count(&server, "/youtubei/v1/player"), 1);
+}
+
+#[test_log::test(tokio::test)]
+async fn many() {
+    let server = server().await;
+    let fetchers: Vec<_> = [KEPT, TOO_LONG, UNTAGGED]
+        .iter()
+        .map(|id| fetcher(&server, id))
+        .collect();
+
+    let results: Vec<_> = fetch_many(fetchers, FetchManyOptions::new().filter(filter()).concurrency(2))
+        .collect()
+        .await;
+    let mut outcomes: HashMap<String, FetchOutcome> = results
+        .into_iter()
+        .map(|(id, result)| (id.as_str().to_owned(), result.unwrap()))
+        .collect();
+
+    assert!(matches!(outcomes.remove(KEPT), Some(FetchOutcome::Fetched(_))));
+    for id in [TOO_LONG, UNTAGGED] {
+        assert!(matches!(outcomes.remove(id), Some(FetchOutcome::Skipped { .. })), "{}", id);
+    }
+    assert!(outcomes.is_empty());
+
+    // only the kept video requested base.js, and the player response
+    assert_eq!(count(&server, "/watch"), 3);
+    assert_eq!(count(&server, BASE_JS_PATH), 1);
+    let player_requests: Vec<String> = server
+        .requests()
+        .iter()
+        .filter(|request| request.path.starts_with("/youtubei/v1/player"))
+        .map(|request| request.json()["videoId"].as_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(player_requests, [KEPT]);
+}
+
+#[test_log::test(tokio::test)]
+async fn failures_do_not_stop_the_others() {
+    let server = server().await;
+    let fetchers = vec![fetcher(&server, "aaaaaaaaaaa"), fetcher(&server, KEPT)];
+
+    let results: Vec<_> = fetch_many(fetchers, FetchManyOptions::new()).collect().await;
+
+    assert_eq!(results.len(), 2);
+    for (id, result) in results {
+        match id.as_str() {
+            KEPT => assert!(matches!(result, Ok(FetchOutcome::Fetched(_)))),
+            _ => assert!(result.is_err()),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "download")]
+
+use common::*;
+use rustube::{SimpleDownloadOptions, Video};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+
+/// The multi audio fixture, with the URLs of all streams pointing to `server`, which answers with
+/// the requested path.
+fn video(server: &MockServer) -> Video {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in player_response["streamingData"][formats].as_array_mut().unwrap() {
+            format.as_object_mut().unwrap().remove("contentLength");
+        }
+    }
+    let (video_info, mut streams) = video_from_player_response(player_response).into_parts();
+    for stream in &mut streams {
+        stream.signature_cipher.url = server.url.join(&format!("videoplayback?itag={}", stream.itag)).unwrap();
+    }
+    Video::from_parts(video_info, streams)
+}
+
+#[test]
+fn file_names_follow_the_container() {
+    let video = video_from_fixture("multi_audio_player_response.json");
+
+    let file_names: Vec<(u64, String)> = video
+        .streams()
+        .iter()
+        .map(|stream| (stream.itag, stream.file_name()))
+        .collect();
+    assert_eq!(file_names[..3], [
+        (18, format!("{}.mp4", VIDEO_ID)),
+        (137, format!("{}.mp4", VIDEO_ID)),
+        (140, format!("{}.mp4", VIDEO_ID)),
+    ]);
+    let opus = video.streams().iter().find(|stream| stream.itag == 251).unwrap();
+    assert_eq!(opus.extension(), "webm");
+    assert_eq!(opus.file_name(), format!("{}.webm", VIDEO_ID));
+}
+
+#[test_log::test(tokio::test)]
+async fn downloads_to_the_file_name() {
+    let server = MockServer::start(|request| MockResponse::ok(request.path.clone())).await;
+    let video = video(&server);
+    let dir = temp_dir("streams");
+
+    for itag in [18, 251] {
+        let stream = video.streams().iter().find(|stream| stream.itag == itag).unwrap();
+        let path = stream.download_to_dir(&dir).await.unwrap();
+        assert_eq!(path, dir.join(stream.file_name()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), format!("/videoplayback?itag={}", itag));
+    }
+    assert!(dir.join(format!("{}.mp4", VIDEO_ID)).is_file());
+    assert!(dir.join(format!("{}.webm", VIDEO_ID)).is_file());
+
+    // the options use the extension of the selected stream, too
+    let dir = temp_dir("options");
+    let options = SimpleDownloadOptions::new()
+        .target_dir(&dir)
+        .audio_only(true);
+    let path = video.download_with(options).await.unwrap();
+    assert_eq!(path, dir.join(video.best_audio().unwrap().file_name()));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test_log::test(tokio::test)]
+async fn downloads_best_audio() {
+    let server = MockServer::start(|request| MockResponse::ok(request.path.clone())).await;
+    let video = video(&server);
+    let dir = temp_dir("best_audio");
+
+    let path = video.download_best_audio_to_dir(&dir).await.unwrap();
+
+    let best_audio = video.best_audio().unwrap();
+    assert_eq!(best_audio.itag, 251);
+    assert_eq!(path, dir.join(format!("{}.webm", VIDEO_ID)));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "/videoplayback?itag=251");
+
+    // videos without audio only streams
+    let (video_info, streams) = video.into_parts();
+    let video = Video::from_parts(video_info, streams.into_iter().filter(|stream| stream.includes_video_track).collect());
+    assert!(matches!(video.download_best_audio_to_dir(&dir).await, Err(rustube::Error::NoStreams(_))));
+    let _ = std::fs::remove_dir_all(&dir);
+}
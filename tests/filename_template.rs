@@ -0,0 +1,148 @@
+#![cfg(feature = "descramble")]
+
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use common::*;
+use rustube::{Error, FilenameTemplate, Video};
+
+#[macro_use]
+mod common;
+
+fn video(edit: impl FnOnce(&mut Value)) -> Video {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    edit(&mut player_response);
+    video_from_player_response(player_response)
+}
+
+fn render(template: &str, video: &Video, itag: u64) -> PathBuf {
+    let stream = video.streams().iter().find(|stream| stream.itag == itag).unwrap();
+    FilenameTemplate::parse(template).unwrap().render(video.video_info(), stream)
+}
+
+fn assert_invalid(template: &str, reason: &str) {
+    match FilenameTemplate::parse(template) {
+        Err(Error::InvalidFilenameTemplate { template: t, reason: r }) => {
+            assert_eq!(t, template);
+            assert!(r.contains(reason), "{:?}: {}", template, r);
+        }
+        res => panic!("{:?}: expected Error::InvalidFilenameTemplate, got {:?}", template, res),
+    }
+}
+
+#[test]
+fn parse_errors() {
+    assert_invalid("{title}.{extension}", "unknown placeholder {extension}");
+    assert_invalid("{TITLE}", "unknown placeholder {TITLE}");
+    assert_invalid("{}", "unknown placeholder {}");
+    assert_invalid("{title", "unclosed placeholder");
+    assert_invalid("{title [{id}]", "unknown placeholder {title [{id}");
+    assert_invalid("title}", "`}` without a placeholder");
+    assert_invalid("", "the template is empty");
+    assert_invalid("/videos/{id}", "the path component \"\" is not allowed");
+    assert_invalid("{channel}//{id}", "the path component \"\" is not allowed");
+    assert_invalid("{channel}/", "the path component \"\" is not allowed");
+    assert_invalid("../{id}", "the path component \"..\" is not allowed");
+    assert_invalid("{channel}\\{id}", "separated by `/`");
+
+    let err = FilenameTemplate::parse("{foo}").unwrap_err();
+    assert_eq!(err.to_string(), "invalid filename template \"{foo}\": unknown placeholder {foo}");
+}
+
+#[test]
+fn parses() {
+    let template: FilenameTemplate = "{channel}/{upload_date} - {title} [{id}] [{quality}].{ext}".parse().unwrap();
+    assert_eq!(template.as_str(), "{channel}/{upload_date} - {title} [{id}] [{quality}].{ext}");
+    assert_eq!(template.to_string(), template.as_str());
+    assert!(FilenameTemplate::parse("./{id}").is_err());
+    assert!(FilenameTemplate::parse("videos/{itag}.{ext}").is_ok());
+}
+
+#[test]
+fn renders() {
+    let video = video(|_| {});
+
+    // the fixture has no microformat, so the upload date is unknown
+    assert_eq!(
+        render("{channel}/{upload_date} - {title} [{id}] [{quality}].{ext}", &video, 137),
+        PathBuf::from("rustube").join("NA - Multi-language audio sample [fDzQ3kUg8Ss] [1080p].mp4"),
+    );
+    // audio streams are named after their bitrate
+    assert_eq!(render("{itag}_{quality}.{ext}", &video, 251), PathBuf::from("251_141k.webm"));
+}
+
+#[cfg(feature = "microformat")]
+#[test]
+fn renders_the_upload_date() {
+    let video = video(|player_response| {
+        player_response["microformat"] = serde_json::json!({
+            "playerMicroformatRenderer": {
+                "availableCountries": ["DE", "US"],
+                "category": "Music",
+                "description": { "simpleText": "" },
+                "externalChannelId": "UC1yBKRuGpC1tSM73A0ZjYjQ",
+                "isUnlisted": false,
+                "lengthSeconds": "615",
+                "ownerChannelName": "rustube",
+                "ownerProfileUrl": "http://www.youtube.com/@rustube",
+                "publishDate": "2024-05-02",
+                "thumbnail": { "thumbnails": [] },
+                "title": { "simpleText": "Multi-language audio sample" },
+                "uploadDate": "2024-05-01",
+                "viewCount": "1034829"
+            }
+        });
+    });
+    assert_eq!(render("{upload_date}", &video, 18), PathBuf::from("20240501"));
+}
+
+#[test]
+fn values_cannot_escape_the_directory() {
+    let video = video(|player_response| {
+        player_response["videoDetails"]["title"] = "../../etc/passwd".into();
+        player_response["videoDetails"]["author"] = "AC/DC".into();
+    });
+
+    let path = render("{channel}/{title}.{ext}", &video, 18);
+    assert_eq!(path, PathBuf::from("AC_DC").join("_.._etc_passwd.mp4"));
+    assert_eq!(path.components().count(), 2);
+}
+
+#[test]
+fn empty_values() {
+    let video = video(|player_response| {
+        player_response["videoDetails"]["title"] = "\0\0".into();
+        player_response["videoDetails"]["author"] = "...".into();
+    });
+
+    // titles fall back to the id, other empty components become `_`
+    assert_eq!(render("{channel}/{title}.{ext}", &video, 18), PathBuf::from("_").join("fDzQ3kUg8Ss.mp4"));
+}
+
+#[cfg(feature = "download")]
+#[test_log::test(tokio::test)]
+async fn download_creates_directories() {
+    use rustube::SimpleDownloadOptions;
+
+    let server = MockServer::start(|_| MockResponse::ok(vec![7; 100])).await;
+    let dir = temp_dir("filename_template");
+
+    let video = video_with_streams_at(&server, "stream");
+    let template = FilenameTemplate::parse("{channel}/{id}/{itag}.{ext}").unwrap();
+
+    let options = SimpleDownloadOptions::new().target_dir(&dir).filename_template(template.clone());
+    let path = video.download_with(options).await.unwrap();
+    assert_eq!(path, dir.join("rustube").join("fDzQ3kUg8Ss").join("18.mp4"));
+    assert_eq!(std::fs::read(&path).unwrap(), vec![7; 100]);
+
+    // an explicit filename takes precedence
+    let options = SimpleDownloadOptions::new()
+        .target_dir(&dir)
+        .filename("video.mp4")
+        .filename_template(template);
+    let path = video.download_with(options).await.unwrap();
+    assert_eq!(path, dir.join("video.mp4"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,11 @@
+var _yt_player={};(function(g){var window=this;
+var Xo={Tq:function(a){a.reverse()},
+Vm:function(a,b){a.splice(0,b)},
+Zp:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c}};
+Gla=function(a){a=a.split("");Xo.Zp(a,3);Xo.Tq(a,54);Xo.Vm(a,2);return a.join("")};
+Ela=function(a){var b=a.split(""),c=[function(d){d.reverse()},function(d,e){e=(e%d.length+d.length)%d.length;var f=d[0];d[0]=d[e];d[e]=f}];c[0](b);c[1](b,3);return b.join("")};
+var Dla=[Ela];
+g.Fp=function(a){var b;a.D&&(b=a.get("n"))&&(b=Dla[0](b),a.set("n",b))};
+g.Hp=function(a,b,c,d){c&&d.set(b,encodeURIComponent(Gla(decodeURIComponent(c))))};
+var Ky={signatureTimestamp:19876,disableSignatureTimestamp:!1};
+})(_yt_player);
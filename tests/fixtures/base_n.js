@@ -0,0 +1,22 @@
+var _yt_player={};(function(g){var window=this;
+var Xo={Tq:function(a){a.reverse()},
+Vm:function(a,b){a.splice(0,b)},
+Zp:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c}};
+Gla=function(a){a=a.split("");Xo.Zp(a,3);Xo.Tq(a,54);Xo.Vm(a,2);return a.join("")};
+var Zha=function(a){var b=a.split(""),c=[function(d,e){e=(e%d.length+d.length)%d.length;d.splice(e,1)},
+-1872339436,null,"pBT0",function(d,e){for(e=(e%d.length+d.length)%d.length;e--;)d.unshift(d.pop())},
+function(d,e){e=(e%d.length+d.length)%d.length;d.splice(0,1,d.splice(e,1,d[0])[0])},
+1429153716,b,/,,[/,913,/](,)}/,function(d){for(var e=d.length;e;)d.push(d.splice(--e,1)[0])},
+function(d,e){d.push(e)},function(d,e){e=(e%d.length+d.length)%d.length;var f=d[0];d[0]=d[e];d[e]=f},
+function(d){d.reverse()},
+function(d,e){for(var f=64,h=[];++f-h.length-32;){switch(f){case 58:f-=14;case 91:case 92:case 93:continue;case 123:f=47;case 94:case 95:case 96:continue;case 46:f=95;default:h.push(String.fromCharCode(f))}}d.forEach(function(l,m,n){this.push(n[m]=h[(h.indexOf(l)-h.indexOf(this[m])+m-32+f--)%h.length])},e.split(""))},
+function(d,e){e=(e%d.length+d.length)%d.length;d.splice(-e).reverse().forEach(function(f){d.unshift(f)})},
+"Xq7zN",-837252413,2055812318,913];c[2]=c;
+try{c[13](c[7],c[3]),c[4](c[7],c[1]),c[11](c[7],c[6]),c[12](c[2]),c[5](c[11],c[3]),c[13](c[11],c[2]),
+c[18](c[11],c[17]),c[4](c[11],c[1]),c[8](c[11],c[15]),c[7](c[16],c[0]),c[9](c[11]),c[14](c[11],c[1]),
+c[5](c[11],c[15]),c[6](c[11]),c[18](c[11],c[0])}catch(d){return"enhanced_except_gZ0B8-z-_w8_"+a}return b.join("")};
+var Yha=[Zha];
+g.Fp=function(a){var b;a.D&&(b=a.get("n"))&&(b=Yha[0](b),a.set("n",b))};
+g.Hp=function(a,b,c,d){c&&d.set(b,encodeURIComponent(Gla(decodeURIComponent(c))))};
+var Ky={signatureTimestamp:19876,disableSignatureTimestamp:!1};
+})(_yt_player);
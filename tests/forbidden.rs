@@ -0,0 +1,129 @@
+#![cfg(feature = "download")]
+
+use std::net::IpAddr;
+
+use chrono::{TimeZone, Utc};
+
+use common::*;
+use rustube::Error;
+use rustube::stream::forbidden;
+
+#[macro_use]
+mod common;
+
+/// A date long after the tests will stop being run.
+const FUTURE: i64 = 4_102_444_800;
+
+/// Tries to download the itag 137 stream of the multi audio fixture from `server`, with `query`
+/// as the query of its URL, and returns the error.
+async fn download_error(server: &MockServer, query: &str) -> Error {
+    let (_, streams) = video_from_fixture("multi_audio_player_response.json").into_parts();
+    let mut stream = streams.into_iter().find(|stream| stream.itag == 137).unwrap();
+    let mut url = server.url.join("videoplayback").unwrap();
+    url.set_query(Some(query));
+    stream.signature_cipher.url = url;
+
+    let path = std::env::temp_dir().join(format!("rustube_forbidden_{}.mp4", std::process::id()));
+    let err = stream.download_to(&path).await.unwrap_err();
+    let _ = std::fs::remove_file(&path);
+    err
+}
+
+async fn hint(query: &str) -> &'static str {
+    let server = MockServer::start(|_| MockResponse::status(403)).await;
+    match download_error(&server, query).await {
+        Error::Forbidden { hint, .. } => hint,
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn reports_the_parameters_of_the_url() {
+    let server = MockServer::start(|_| MockResponse::status(403)).await;
+    let err = download_error(&server, &format!("expire={}&ip=203.0.113.7&sig=abc&n=xyz&pot=token", FUTURE)).await;
+
+    match err {
+        Error::Forbidden { video_id, itag, has_sig, has_n_transformed, has_pot, url_ip, expire, host, hint } => {
+            assert_eq!(video_id.as_str(), "fDzQ3kUg8Ss");
+            assert_eq!(itag, 137);
+            assert!(has_sig);
+            assert!(!has_n_transformed);
+            assert!(has_pot);
+            assert_eq!(url_ip, Some("203.0.113.7".parse::<IpAddr>().unwrap()));
+            assert_eq!(expire, Some(Utc.timestamp_opt(FUTURE, 0).unwrap()));
+            assert_eq!(host.as_deref(), Some("127.0.0.1"));
+            assert_eq!(hint, forbidden::N_NOT_TRANSFORMED);
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn classifies_the_most_probable_cause() {
+    let cases = [
+        (&format!("expire={}&n=xyz", FUTURE), forbidden::UNSIGNED),
+        (&format!("expire={}&signature=abc&n=xyz", FUTURE), forbidden::N_NOT_TRANSFORMED),
+        (&format!("expire={}&sig=abc&c=WEB&ip=203.0.113.7", FUTURE), forbidden::MISSING_PO_TOKEN),
+        (&format!("expire={}&sig=abc&c=WEB&pot=token&ip=203.0.113.7", FUTURE), forbidden::IP_MISMATCH),
+        (&format!("expire={}&sig=abc&c=ANDROID&ip=2001:db8::1", FUTURE), forbidden::IP_MISMATCH),
+        (&format!("expire={}&sig=abc&c=ANDROID", FUTURE), forbidden::UNKNOWN),
+        (&"sig=abc".to_owned(), forbidden::UNKNOWN),
+    ];
+
+    for (query, expected) in cases {
+        assert_eq!(hint(query).await, expected, "{}", query);
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn other_statuses_are_not_diagnosed() {
+    let server = MockServer::start(|_| MockResponse::status(500)).await;
+    let err = download_error(&server, "expire=1000").await;
+
+    match err {
+        Error::Request(err) => assert_eq!(err.status(), Some(reqwest::StatusCode::INTERNAL_SERVER_ERROR)),
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn expired_urls() {
+    let server = MockServer::start(|_| MockResponse::status(403)).await;
+    let err = download_error(&server, "expire=1000&sig=abc").await;
+
+    match err {
+        Error::UrlExpired { ref video_id, itag, expire } => {
+            assert_eq!(video_id.as_str(), "fDzQ3kUg8Ss");
+            assert_eq!(itag, 137);
+            assert_eq!(expire, Utc.timestamp_opt(1000, 0).unwrap());
+        }
+        ref err => panic!("unexpected error: {:?}", err),
+    }
+    assert!(!err.is_retryable());
+}
+
+#[test_log::test(tokio::test)]
+async fn too_many_requests() {
+    let server = MockServer::start(|_| MockResponse::status(429).with_header("Retry-After", "7")).await;
+    let err = download_error(&server, &format!("expire={}&sig=abc", FUTURE)).await;
+
+    match err {
+        Error::RateLimited { retry_after, endpoint } => {
+            assert_eq!(retry_after, Some(std::time::Duration::from_secs(7)));
+            assert_eq!(endpoint, "videoplayback");
+        }
+        ref err => panic!("unexpected error: {:?}", err),
+    }
+    assert!(err.is_retryable());
+}
+
+#[test_log::test(tokio::test)]
+async fn retryable_errors() {
+    let server = MockServer::start(|request| match request.path.starts_with("/videoplayback?server_error") {
+        true => MockResponse::status(503),
+        false => MockResponse::status(404),
+    }).await;
+
+    assert!(download_error(&server, "server_error").await.is_retryable());
+    assert!(!download_error(&server, "not_found").await.is_retryable());
+}
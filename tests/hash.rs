@@ -0,0 +1,117 @@
+#![cfg(feature = "download")]
+
+use common::*;
+use rustube::{HashAlgorithm, SimpleDownloadOptions, Video};
+
+#[macro_use]
+mod common;
+
+const QUICK_BROWN_FOX: &[u8] = b"The quick brown fox jumps over the lazy dog";
+const TWO_BLOCKS: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+/// The multi audio fixture, with all streams pointing to `server`, and a content length of `len`.
+fn video(server: &MockServer, len: usize) -> Video {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    set_content_length(&mut player_response, len);
+    with_streams_at(video_from_player_response(player_response), server, "stream")
+}
+
+#[test]
+fn md5() {
+    let cases: [(&[u8], &str); 4] = [
+        (b"", "d41d8cd98f00b204e9800998ecf8427e"),
+        (b"abc", "900150983cd24fb0d6963f7d28e17f72"),
+        (QUICK_BROWN_FOX, "9e107d9d372bb6826bd81d3542a419d6"),
+        (&pattern(4096), "a0c16616c91907bd14e999986cf822d5"),
+    ];
+    for (data, digest) in cases {
+        assert_eq!(HashAlgorithm::Md5.digest(data), digest, "{:?}", data.len());
+    }
+    assert_eq!(HashAlgorithm::Md5.digest(&[b'a'; 1_000_000]), "7707d6ae4e027c70eea2a935c2296f21");
+}
+
+#[test]
+fn sha1() {
+    let cases: [(&[u8], &str); 4] = [
+        (b"", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+        (b"abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+        (TWO_BLOCKS, "84983e441c3bd26ebaae4aa1f95129e5e54670f1"),
+        (&pattern(4096), "92ca8f2b4163e64a1b53e0fde263ad56cbdb75fc"),
+    ];
+    for (data, digest) in cases {
+        assert_eq!(HashAlgorithm::Sha1.digest(data), digest, "{:?}", data.len());
+    }
+    assert_eq!(HashAlgorithm::Sha1.digest(&[b'a'; 1_000_000]), "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+}
+
+#[test]
+fn sha256() {
+    let cases: [(&[u8], &str); 4] = [
+        (b"", "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+        (b"abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+        (TWO_BLOCKS, "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"),
+        (&pattern(4096), "d67c656e01756650d77717b0839985a056ec28ffe174601d690fc407a2ceffca"),
+    ];
+    for (data, digest) in cases {
+        assert_eq!(HashAlgorithm::Sha256.digest(data), digest, "{:?}", data.len());
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn hashes_chunked_download() {
+    // chunks, that don't line up with the 64 byte blocks of the hashes
+    let server = MockServer::start(|_| {
+        MockResponse::ok(pattern(4096)).slow(500, std::time::Duration::from_millis(1))
+    }).await;
+    let dir = temp_dir("chunked");
+
+    let video = video(&server, 4096);
+    for (algorithm, digest) in [
+        (HashAlgorithm::Md5, "a0c16616c91907bd14e999986cf822d5"),
+        (HashAlgorithm::Sha1, "92ca8f2b4163e64a1b53e0fde263ad56cbdb75fc"),
+        (HashAlgorithm::Sha256, "d67c656e01756650d77717b0839985a056ec28ffe174601d690fc407a2ceffca"),
+    ] {
+        let path = dir.join(format!("{:?}.mp4", algorithm));
+        let download = video.streams()[0].download_to_with_hash(&path, algorithm).await.unwrap();
+
+        assert_eq!(download.path, path);
+        assert_eq!(download.bytes, 4096);
+        assert_eq!(download.digest.as_deref(), Some(digest));
+        assert_eq!(std::fs::read(&path).unwrap(), pattern(4096));
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn hashes_large_download() {
+    const LEN: usize = 1 << 22;
+    let server = MockServer::start(|_| MockResponse::ok(pattern(LEN))).await;
+    let dir = temp_dir("large");
+
+    let video = video(&server, LEN);
+    let options = SimpleDownloadOptions::new()
+        .target_dir(&dir)
+        .hash(HashAlgorithm::Sha256);
+    let download = video.download_with_summary(options).await.unwrap();
+
+    assert_eq!(download.path, dir.join("fDzQ3kUg8Ss.mp4"));
+    assert_eq!(download.bytes, LEN as u64);
+    assert_eq!(
+        download.digest.as_deref(),
+        Some("a117210941a0b00dcb2d8577e680d84b6fa0eaf760d2afc654c953b9859d54fa"),
+    );
+}
+
+#[test_log::test(tokio::test)]
+async fn no_digest_by_default() {
+    let server = MockServer::start(|_| MockResponse::ok(pattern(4096))).await;
+    let dir = temp_dir("default");
+
+    let video = video(&server, 4096);
+    let download = video
+        .download_with_summary(SimpleDownloadOptions::new().target_dir(&dir))
+        .await
+        .unwrap();
+
+    assert_eq!(download.bytes, 4096);
+    assert_eq!(download.digest, None);
+}
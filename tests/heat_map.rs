@@ -0,0 +1,99 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::json;
+
+use common::*;
+use rustube::{Id, VideoFetcher};
+use rustube::video_info::player_response::heat_map::HeatMarker;
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+
+fn marker(time_range_start_ms: u64, heat_marker_intensity_score_normalized: f64) -> HeatMarker {
+    HeatMarker { time_range_start_ms, marker_duration_ms: 6150, heat_marker_intensity_score_normalized }
+}
+
+#[test]
+fn from_framework_updates() {
+    let heat_map = HeatMarker::from_initial_data(&json_fixture("heat_map.json")).unwrap();
+
+    // the marker without an intensity is skipped
+    assert_eq!(heat_map, [
+        marker(0, 1.0),
+        marker(6150, 0.42),
+        marker(12300, 0.1875),
+    ]);
+}
+
+#[test]
+fn from_player_bar() {
+    let initial_data = json!({
+        "playerOverlays": { "playerOverlayRenderer": { "decoratedPlayerBarRenderer": { "decoratedPlayerBarRenderer": {
+            "playerBar": { "multiMarkersPlayerBarRenderer": { "markersMap": [
+                { "key": "DESCRIPTION_CHAPTERS", "value": { "chapters": [] } },
+                { "key": "HEATSEEKER", "value": { "heatmap": { "heatmapRenderer": {
+                    "maxHeightDp": 40,
+                    "heatMarkers": [
+                        { "heatMarkerRenderer": {
+                            "timeRangeStartMillis": 6150,
+                            "markerDurationMillis": 6150,
+                            "heatMarkerIntensityScoreNormalized": 0.5
+                        } },
+                        { "heatMarkerRenderer": {
+                            "timeRangeStartMillis": 0,
+                            "markerDurationMillis": 6150,
+                            "heatMarkerIntensityScoreNormalized": 1
+                        } }
+                    ]
+                } } } }
+            ] } }
+        } } } }
+    });
+
+    assert_eq!(HeatMarker::from_initial_data(&initial_data).unwrap(), [marker(0, 1.0), marker(6150, 0.5)]);
+}
+
+#[test]
+fn missing_heat_map() {
+    assert_eq!(HeatMarker::from_initial_data(&json!({})), None);
+    assert_eq!(HeatMarker::from_initial_data(&json!({ "frameworkUpdates": { "entityBatchUpdate": {
+        "mutations": [{ "payload": { "likeCountEntity": {} } }]
+    } } })), None);
+    // the initial data is not kept by default
+    assert_eq!(video_from_fixture("multi_audio_player_response.json").heat_map(), None);
+}
+
+#[test_log::test(tokio::test)]
+async fn fetched_heat_map() {
+    let watch_page = format!(
+        "<html><script>var ytInitialPlayerResponse = {};</script>\
+        <script>var ytInitialData = {};</script>\
+        <script src=\"{}\"></script></html>",
+        json_fixture("multi_audio_player_response.json"), json_fixture("heat_map.json"), BASE_JS_PATH,
+    );
+    let server = MockServer::start(move |request| {
+        let path = request.path.as_str();
+        if path.starts_with("/watch") {
+            MockResponse::ok(watch_page.clone())
+        } else if path == BASE_JS_PATH {
+            MockResponse::ok(BASE_JS)
+        } else {
+            MockResponse::status(404)
+        }
+    }).await;
+
+    let video = VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap().into_owned())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_initial_data(true)
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap();
+
+    assert_eq!(video.heat_map().unwrap().len(), 3);
+}
@@ -0,0 +1,310 @@
+#![cfg(feature = "fetch")]
+
+use std::time::Duration;
+
+use url::Url;
+
+use common::*;
+use rustube::hls::{HlsMasterPlaylist, HlsMediaPlaylist};
+
+#[macro_use]
+mod common;
+
+const MASTER_URL: &str = "https://manifest.googlevideo.com/api/manifest/hls_variant/expire/1700000000/id/dLiveNow123.1/file/index.m3u8";
+const MEDIA_URL: &str = "https://manifest.googlevideo.com/api/manifest/hls_playlist/expire/1700000000/id/dLiveNow123.1/itag/94/file/index.m3u8";
+
+#[test]
+fn parses_master_playlist() {
+    let master = HlsMasterPlaylist::parse(&read_fixture("hls_master.m3u8"), &Url::parse(MASTER_URL).unwrap()).unwrap();
+
+    let itags: Vec<Option<u64>> = master.variants.iter().map(|variant| variant.itag()).collect();
+    assert_eq!(itags, [Some(91), Some(94), Some(96), None]);
+
+    let variant = master.variant_by_itag(94).unwrap();
+    assert_eq!(variant.bandwidth, Some(1640976));
+    assert_eq!((variant.width, variant.height), (Some(854), Some(480)));
+    assert_eq!(variant.codecs.as_deref(), Some("avc1.4d401f,mp4a.40.2"));
+
+    assert_eq!(master.best_variant().unwrap().itag(), Some(96));
+    // relative urls are resolved against the url of the playlist
+    assert_eq!(
+        master.variants[3].url.as_str(),
+        "https://manifest.googlevideo.com/api/manifest/hls_variant/expire/1700000000/id/dLiveNow123.1/file/variant/95/index.m3u8",
+    );
+}
+
+#[test]
+fn parses_media_playlist() {
+    let media = HlsMediaPlaylist::parse(&read_fixture("hls_media.m3u8"), &Url::parse(MEDIA_URL).unwrap()).unwrap();
+
+    assert_eq!(media.target_duration, Duration::from_secs(5));
+    assert!(!media.ended);
+    let sequences: Vec<u64> = media.segments.iter().map(|segment| segment.sequence).collect();
+    assert_eq!(sequences, [4012, 4013, 4014]);
+    assert_eq!(media.duration(), Duration::from_millis(15_000));
+    assert_eq!(
+        media.segments[2].url.as_str(),
+        "https://manifest.googlevideo.com/api/manifest/hls_playlist/expire/1700000000/id/dLiveNow123.1/itag/94/file/seg/4014.ts",
+    );
+
+    let ended = format!("{}#EXT-X-ENDLIST\n", read_fixture("hls_media.m3u8"));
+    assert!(HlsMediaPlaylist::parse(&ended, &Url::parse(MEDIA_URL).unwrap()).unwrap().ended);
+}
+
+#[test]
+fn invalid_playlists() {
+    let url = Url::parse(MEDIA_URL).unwrap();
+
+    let invalid_master = [
+        "",
+        "#EXT-X-STREAM-INF:BANDWIDTH=1\nindex.m3u8",
+        "#EXTM3U\n#EXT-X-TARGETDURATION:5",
+        "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=1",
+        "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=fast\nindex.m3u8",
+        "#EXTM3U\n#EXT-X-STREAM-INF:RESOLUTION=1080p\nindex.m3u8",
+    ];
+    for m3u8 in &invalid_master {
+        assert!(matches!(
+            HlsMasterPlaylist::parse(m3u8, &url),
+            Err(rustube::Error::UnexpectedResponse(_))
+        ), "{}", m3u8);
+    }
+
+    let invalid_media = [
+        "",
+        "#EXTM3U\n#EXTINF:5,\n0.ts",
+        "#EXTM3U\n#EXT-X-TARGETDURATION:5\n0.ts",
+        "#EXTM3U\n#EXT-X-TARGETDURATION:5\n#EXTINF:-1,\n0.ts",
+        "#EXTM3U\n#EXT-X-TARGETDURATION:5\n#EXT-X-KEY:METHOD=AES-128,URI=\"key\"\n#EXTINF:5,\n0.ts",
+        "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=1\nindex.m3u8",
+    ];
+    for m3u8 in &invalid_media {
+        assert!(matches!(
+            HlsMediaPlaylist::parse(m3u8, &url),
+            Err(rustube::Error::UnexpectedResponse(_))
+        ), "{}", m3u8);
+    }
+}
+
+#[cfg(feature = "download")]
+mod download {
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rustube::dash::SegmentProgress;
+    use rustube::{Id, TransferStats, UserAgentPolicy, VideoFetcher};
+
+    use super::*;
+
+    const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+
+    const MASTER: &str = "#EXTM3U\n\
+        #EXT-X-STREAM-INF:BANDWIDTH=1640976,RESOLUTION=854x480\n\
+        /itag/94/media.m3u8\n";
+
+    /// A media playlist with the segments `sequences`.
+    fn media(sequences: std::ops::Range<u64>, ended: bool) -> String {
+        let mut m3u8 = format!("#EXTM3U\n#EXT-X-TARGETDURATION:1\n#EXT-X-MEDIA-SEQUENCE:{}\n", sequences.start);
+        for sequence in sequences {
+            m3u8.push_str(&format!("#EXTINF:1.0,\nseg/{}.ts\n", sequence));
+        }
+        if ended {
+            m3u8.push_str("#EXT-X-ENDLIST\n");
+        }
+        m3u8
+    }
+
+    /// A live stream, that only has an HLS manifest.
+    fn player_response(server: &MockServer) -> serde_json::Value {
+        let mut player_response = json_fixture("multi_audio_player_response.json");
+        player_response["videoDetails"]["isLive"] = true.into();
+        player_response["videoDetails"]["isLiveContent"] = true.into();
+        player_response["streamingData"]["formats"] = serde_json::json!([]);
+        player_response["streamingData"]["adaptiveFormats"] = serde_json::json!([]);
+        player_response["streamingData"]["hlsManifestUrl"] = server.url.join("master.m3u8").unwrap().as_str().into();
+        player_response
+    }
+
+    fn path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("rustube_hls_{}_{}.ts", name, std::process::id()))
+    }
+
+    fn paths(server: &MockServer) -> Vec<String> {
+        server.requests().into_iter().map(|request| request.path).collect()
+    }
+
+    /// Downloads the only variant of the HLS manifest served by `server`.
+    async fn download(server: &MockServer, path: &std::path::Path, max_segments: Option<usize>) -> rustube::Result<u64> {
+        let video = video_from_player_response(player_response(server));
+        let master = video.hls_playlist().await?;
+        video.download_from_hls_playlist(&master.variants[0], path, max_segments, |_| {}).await
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn live_streams_are_descrambled() {
+        let server = MockServer::start(|_| MockResponse::status(404)).await;
+        let live = player_response(&server);
+        let youtube = mock_youtube(live, BASE_JS, |_| serde_json::json!({})).await;
+
+        let video = VideoFetcher::from_id(Id::from_str("fDzQ3kUg8Ss").unwrap().into_owned())
+            .unwrap()
+            .with_base_url(youtube.url.clone())
+            .fetch()
+            .await
+            .unwrap()
+            .descramble()
+            .unwrap();
+
+        assert!(video.streams().is_empty());
+        assert_eq!(video.hls_manifest_url(), Some(&server.url.join("master.m3u8").unwrap()));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn uses_the_settings_of_the_fetcher() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/master.m3u8" => MockResponse::ok(MASTER),
+            "/itag/94/media.m3u8" => MockResponse::ok(media(0..2, true)),
+            path => MockResponse::ok(path),
+        }).await;
+        let youtube = mock_youtube(player_response(&server), BASE_JS, |_| serde_json::json!({})).await;
+
+        let jar = Arc::new(rustube::reqwest::cookie::Jar::default());
+        jar.add_cookie_str("SID=session", &server.url);
+        let client = rustube::reqwest::Client::builder().cookie_provider(jar).build().unwrap();
+        let stats = Arc::new(TransferStats::new());
+        let video = VideoFetcher::from_id_with_client(Id::from_str("fDzQ3kUg8Ss").unwrap().into_owned(), client)
+            .with_base_url(youtube.url.clone())
+            .with_user_agent_policy(&UserAgentPolicy::Fixed("rustube-test".to_owned()))
+            .with_transfer_stats(Arc::clone(&stats))
+            .fetch()
+            .await
+            .unwrap()
+            .descramble()
+            .unwrap();
+        assert!(video.streams().is_empty());
+        let requests = stats.request_count();
+
+        let path = path("fetcher");
+        let master = video.hls_playlist().await.unwrap();
+        let bytes = video.download_from_hls_playlist(&master.variants[0], &path, None, |_| {}).await.unwrap();
+
+        let requests_of_the_video = server.requests();
+        assert_eq!(requests_of_the_video.len(), 4);
+        for request in requests_of_the_video {
+            assert_eq!(request.header("Cookie"), Some("SID=session"), "{}", request.path);
+            assert_eq!(request.header("User-Agent"), Some("rustube-test"), "{}", request.path);
+        }
+        assert_eq!(stats.request_count(), requests + 4);
+        assert_eq!(stats.media_bytes(), bytes);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn downloads_finished_stream() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/master.m3u8" => MockResponse::ok(MASTER),
+            "/itag/94/media.m3u8" => MockResponse::ok(media(0..3, true)),
+            path => MockResponse::ok(path),
+        }).await;
+        let video = video_from_player_response(player_response(&server));
+        let master = video.hls_playlist().await.unwrap();
+        assert_eq!(master.variants[0].itag(), Some(94));
+
+        let path = path("finished");
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let bytes = {
+            let progress = Arc::clone(&progress);
+            video
+                .download_from_hls_playlist(&master.variants[0], &path, None, move |p| progress.lock().unwrap().push(p))
+                .await
+                .unwrap()
+        };
+
+        let expected = "/itag/94/seg/0.ts/itag/94/seg/1.ts/itag/94/seg/2.ts";
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), expected);
+        assert_eq!(bytes, expected.len() as u64);
+        let counts: Vec<(usize, usize)> = progress
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|p: &SegmentProgress| (p.downloaded_segments, p.total_segments))
+            .collect();
+        assert_eq!(counts, [(1, 3), (2, 3), (3, 3)]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn follows_live_stream_until_it_ends() {
+        let refreshes = AtomicUsize::new(0);
+        let server = MockServer::start(move |request| match request.path.as_str() {
+            "/master.m3u8" => MockResponse::ok(MASTER),
+            "/itag/94/media.m3u8" => match refreshes.fetch_add(1, Ordering::SeqCst) {
+                0 => MockResponse::ok(media(0..2, false)),
+                1 => MockResponse::ok(media(1..3, false)),
+                _ => MockResponse::ok(media(2..4, true)),
+            },
+            path => MockResponse::ok(path),
+        }).await;
+        let path = path("live");
+
+        download(&server, &path, None).await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "/itag/94/seg/0.ts/itag/94/seg/1.ts/itag/94/seg/2.ts/itag/94/seg/3.ts",
+        );
+        let playlist_requests = paths(&server).iter().filter(|path| path.ends_with("media.m3u8")).count();
+        assert_eq!(playlist_requests, 3);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn stops_after_max_segments() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/master.m3u8" => MockResponse::ok(MASTER),
+            "/itag/94/media.m3u8" => MockResponse::ok(media(7..10, false)),
+            path => MockResponse::ok(path),
+        }).await;
+        let path = path("max_segments");
+
+        download(&server, &path, Some(2)).await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "/itag/94/seg/7.ts/itag/94/seg/8.ts");
+        assert!(!paths(&server).iter().any(|path| path.ends_with("9.ts")));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn forbidden_segments_refresh_the_playlist() {
+        let forbidden = AtomicUsize::new(0);
+        let server = MockServer::start(move |request| match request.path.as_str() {
+            "/master.m3u8" => MockResponse::ok(MASTER),
+            "/itag/94/media.m3u8" => MockResponse::ok(media(0..2, true)),
+            "/itag/94/seg/1.ts" if forbidden.fetch_add(1, Ordering::SeqCst) == 0 => MockResponse::status(403),
+            path => MockResponse::ok(path),
+        }).await;
+        let path = path("forbidden");
+
+        download(&server, &path, None).await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "/itag/94/seg/0.ts/itag/94/seg/1.ts");
+        assert_eq!(paths(&server)[1..], [
+            "/itag/94/media.m3u8",
+            "/itag/94/seg/0.ts",
+            "/itag/94/seg/1.ts",
+            "/itag/94/media.m3u8",
+            "/itag/94/seg/1.ts",
+        ]);
+        std::fs::remove_file(&path).unwrap();
+
+        // a segment, that's refused again, fails the download
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/master.m3u8" => MockResponse::ok(MASTER),
+            "/itag/94/media.m3u8" => MockResponse::ok(media(0..2, true)),
+            "/itag/94/seg/1.ts" => MockResponse::status(403),
+            path => MockResponse::ok(path),
+        }).await;
+        assert!(matches!(download(&server, &path, None).await, Err(rustube::Error::Request(_))));
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,173 @@
+#![cfg(all(feature = "regex", feature = "std"))]
+
+use std::time::Duration;
+
+use rustube::{EmbedOptions, Error, Id, ThumbnailQuality};
+use rustube::id::parse_timestamp;
+
+const ID: &str = "nv2wQvn6Wxc";
+
+fn secs(secs: u64) -> Option<Duration> {
+    Some(Duration::from_secs(secs))
+}
+
+#[test]
+fn parse_timestamps() {
+    let cases = [
+        ("0", secs(0)),
+        ("90", secs(90)),
+        ("90s", secs(90)),
+        ("1m", secs(60)),
+        ("1m30s", secs(90)),
+        ("1h", secs(3600)),
+        ("1h2m3s", secs(3723)),
+        ("1h3s", secs(3603)),
+        ("120m", secs(7200)),
+        ("01:30", secs(90)),
+        ("1:30", secs(90)),
+        ("90:00", secs(5400)),
+        ("1:02:03", secs(3723)),
+        ("0:00", secs(0)),
+    ];
+    for (raw, expected) in cases {
+        assert_eq!(parse_timestamp(raw), expected, "{:?}", raw);
+    }
+}
+
+#[test]
+fn reject_invalid_timestamps() {
+    let cases = [
+        "", "s", "m30s", "1x", "-5", "1.5", "1m1m", "30s1m", "1h 2m", " 90",
+        ":", "1:", ":30", "1:60", "1:2:3:4", "1:-2", "1m:30", "99999999999999999999",
+    ];
+    for raw in cases {
+        assert_eq!(parse_timestamp(raw), None, "{:?}", raw);
+    }
+}
+
+#[test]
+fn from_raw_ignores_parameters() {
+    let cases = [
+        "https://www.youtube.com/watch?v=nv2wQvn6Wxc&t=90s",
+        "https://www.youtube.com/watch?t=90&v=nv2wQvn6Wxc",
+        "https://www.youtube.com/watch?feature=share&v=nv2wQvn6Wxc#t=1m30s",
+        "https://youtu.be/nv2wQvn6Wxc?t=90",
+        "https://youtu.be/nv2wQvn6Wxc?si=abc&t=01:30",
+        "youtu.be/nv2wQvn6Wxc#t=90",
+        "https://www.youtube.com/embed/nv2wQvn6Wxc?start=90&autoplay=1",
+        "https://www.youtube.com/shorts/nv2wQvn6Wxc?t=90",
+    ];
+    for raw in cases {
+        assert_eq!(Id::from_raw(raw).unwrap().as_str(), ID, "{:?}", raw);
+    }
+
+    assert!(matches!(Id::from_raw("https://youtu.be/nv2wQvn6Wxcx?t=90"), Err(Error::BadIdFormat)));
+    assert!(matches!(Id::from_raw("https://www.youtube.com/watch?t=90"), Err(Error::BadIdFormat)));
+}
+
+#[test]
+fn from_raw_with_timestamp() {
+    let cases = [
+        ("https://www.youtube.com/watch?v=nv2wQvn6Wxc", None),
+        ("nv2wQvn6Wxc", None),
+        ("https://www.youtube.com/watch?v=nv2wQvn6Wxc&t=90s", secs(90)),
+        ("https://www.youtube.com/watch?t=1m30s&v=nv2wQvn6Wxc", secs(90)),
+        ("https://www.youtube.com/watch?v=nv2wQvn6Wxc#t=01:30", secs(90)),
+        ("https://youtu.be/nv2wQvn6Wxc?t=90", secs(90)),
+        ("https://youtu.be/nv2wQvn6Wxc?si=abc&t=1h2m3s", secs(3723)),
+        ("https://www.youtube.com/embed/nv2wQvn6Wxc?start=90&end=120", secs(90)),
+        ("https://www.youtube.com/shorts/nv2wQvn6Wxc?t=5", secs(5)),
+        // invalid timestamps are ignored
+        ("https://youtu.be/nv2wQvn6Wxc?t=soon", None),
+        ("https://youtu.be/nv2wQvn6Wxc?tt=90", None),
+    ];
+    for (raw, expected) in cases {
+        let (id, timestamp) = Id::from_raw_with_timestamp(raw).unwrap();
+        assert_eq!(id.as_str(), ID, "{:?}", raw);
+        assert_eq!(timestamp, expected, "{:?}", raw);
+    }
+
+    assert!(matches!(Id::from_raw_with_timestamp("https://youtu.be/?t=90"), Err(Error::BadIdFormat)));
+}
+
+#[test]
+fn urls_at_timestamp() {
+    let id = Id::from_str(ID).unwrap();
+
+    assert_eq!(
+        id.watch_url_at(Duration::from_secs(90)).as_str(),
+        "https://www.youtube.com/watch?v=nv2wQvn6Wxc&t=90s",
+    );
+    assert_eq!(
+        id.share_url_at(Duration::from_millis(90_999)).as_str(),
+        "https://youtu.be/nv2wQvn6Wxc?t=90s",
+    );
+    assert_eq!(id.share_url_at(Duration::ZERO).as_str(), "https://youtu.be/nv2wQvn6Wxc?t=0s");
+}
+
+#[test]
+fn generated_urls_round_trip() {
+    let id = Id::from_str(ID).unwrap();
+    let timestamp = Duration::from_secs(3723);
+
+    for url in [id.watch_url_at(timestamp), id.share_url_at(timestamp)] {
+        let (parsed, parsed_timestamp) = Id::from_raw_with_timestamp(url.as_str()).unwrap();
+        assert_eq!(parsed, id);
+        assert_eq!(parsed_timestamp, Some(timestamp));
+    }
+}
+
+#[test]
+fn embed_url_with_options() {
+    let id = Id::from_str(ID).unwrap();
+
+    let cases = [
+        (EmbedOptions::default(), "https://www.youtube.com/embed/nv2wQvn6Wxc"),
+        (
+            EmbedOptions { start: secs(90), ..Default::default() },
+            "https://www.youtube.com/embed/nv2wQvn6Wxc?start=90",
+        ),
+        (
+            EmbedOptions { start: secs(90), end: Some(Duration::from_millis(120_500)), ..Default::default() },
+            "https://www.youtube.com/embed/nv2wQvn6Wxc?start=90&end=120",
+        ),
+        (
+            EmbedOptions { autoplay: true, mute: true, ..Default::default() },
+            "https://www.youtube.com/embed/nv2wQvn6Wxc?autoplay=1&mute=1",
+        ),
+        (
+            EmbedOptions { loop_: true, ..Default::default() },
+            "https://www.youtube.com/embed/nv2wQvn6Wxc?loop=1&playlist=nv2wQvn6Wxc",
+        ),
+        (
+            EmbedOptions { start: secs(5), end: secs(10), autoplay: true, loop_: true, mute: true },
+            "https://www.youtube.com/embed/nv2wQvn6Wxc?start=5&end=10&autoplay=1&loop=1&playlist=nv2wQvn6Wxc&mute=1",
+        ),
+    ];
+    for (options, expected) in cases {
+        assert_eq!(id.embed_url_with(&options).as_str(), expected, "{:?}", options);
+    }
+
+    let url = id.embed_url_with(&EmbedOptions { start: secs(42), ..Default::default() });
+    assert_eq!(Id::from_raw_with_timestamp(url.as_str()).unwrap(), (id, secs(42)));
+}
+
+#[test]
+fn thumbnail_urls() {
+    let id = Id::from_str(ID).unwrap();
+    let qualities = [
+        (ThumbnailQuality::Default, "default.jpg"),
+        (ThumbnailQuality::Medium, "mqdefault.jpg"),
+        (ThumbnailQuality::High, "hqdefault.jpg"),
+        (ThumbnailQuality::Standard, "sddefault.jpg"),
+        (ThumbnailQuality::MaxRes, "maxresdefault.jpg"),
+    ];
+    for (quality, file_name) in qualities {
+        assert_eq!(
+            id.thumbnail_url(quality).as_str(),
+            format!("https://i.ytimg.com/vi/nv2wQvn6Wxc/{}", file_name),
+        );
+    }
+    assert!(ThumbnailQuality::MaxRes > ThumbnailQuality::Standard);
+    assert_eq!(ThumbnailQuality::Standard.size(), (640, 480));
+}
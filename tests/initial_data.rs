@@ -0,0 +1,98 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::Value;
+
+use common::*;
+use rustube::{Id, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+
+/// A server, that answers with the saved watch page.
+async fn watch_page_server() -> MockServer {
+    MockServer::start(|request| {
+        let path = request.path.as_str();
+        if path.starts_with("/watch") {
+            MockResponse::ok(read_fixture("watch_page.html"))
+        } else if path == BASE_JS_PATH {
+            MockResponse::ok(BASE_JS)
+        } else if path.starts_with("/youtubei/v1/player") {
+            MockResponse::json(&json_fixture("multi_audio_player_response.json"))
+        } else {
+            MockResponse::status(404)
+        }
+    }).await
+}
+
+fn fetcher(server: &MockServer) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+}
+
+fn assert_watch_page_initial_data(initial_data: &Value) {
+    let contents = &initial_data["contents"]["twoColumnWatchNextResults"]["results"]["results"]["contents"];
+    assert_eq!(
+        contents[1]["videoSecondaryInfoRenderer"]["attributedDescription"]["content"],
+        "Chapters: 0:00 Intro }; 1:30 Outro",
+    );
+    assert_eq!(initial_data["trackingParams"], "CAAQg2ciEwj4w9m1kJaJAxV");
+}
+
+#[test_log::test(tokio::test)]
+async fn fetch_info_keeps_initial_data() {
+    let server = watch_page_server().await;
+
+    let video_info = fetcher(&server)
+        .with_initial_data(true)
+        .fetch_info()
+        .await
+        .unwrap();
+
+    assert_watch_page_initial_data(video_info.initial_data().unwrap());
+    assert_eq!(video_info.player_response.video_details.video_id.as_str(), VIDEO_ID);
+}
+
+#[test_log::test(tokio::test)]
+async fn fetch_keeps_initial_data() {
+    let server = watch_page_server().await;
+
+    let video = fetcher(&server)
+        .with_initial_data(true)
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap();
+
+    assert_watch_page_initial_data(video.video_info().initial_data().unwrap());
+}
+
+#[test_log::test(tokio::test)]
+async fn initial_data_is_dropped_by_default() {
+    let server = watch_page_server().await;
+
+    let video_info = fetcher(&server).fetch_info().await.unwrap();
+    assert!(video_info.initial_data().is_none());
+
+    let descrambler = fetcher(&server).fetch().await.unwrap();
+    assert!(descrambler.video_info().initial_data().is_none());
+}
+
+#[test_log::test(tokio::test)]
+async fn missing_initial_data() {
+    let player_response = json_fixture("multi_audio_player_response.json");
+    let server = mock_youtube(player_response.clone(), BASE_JS, move |_| player_response.clone()).await;
+
+    let video_info = fetcher(&server)
+        .with_initial_data(true)
+        .fetch_info()
+        .await
+        .unwrap();
+
+    assert!(video_info.initial_data().is_none());
+    assert!(video_info.is_playable());
+}
@@ -0,0 +1,322 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{Error, Id, InnertubeClient, NoStreamsReason, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+
+/// A player response with usable, pre-signed streams.
+fn playable() -> Value {
+    json_fixture("multi_audio_player_response.json")
+}
+
+/// A playable player response without any streams.
+fn without_streams() -> Value {
+    let mut player_response = playable();
+    player_response.as_object_mut().unwrap().remove("streamingData");
+    player_response
+}
+
+/// A player response, whose streams are all DRM protected.
+fn drm_protected() -> Value {
+    let mut player_response = playable();
+    let streaming_data = &mut player_response["streamingData"];
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in streaming_data[formats].as_array_mut().unwrap() {
+            format["drmFamilies"] = json!(["WIDEVINE", "PLAYREADY"]);
+        }
+    }
+    player_response
+}
+
+/// A player response, that's not playable for the requesting client.
+fn unavailable() -> Value {
+    let mut player_response = without_streams();
+    player_response["playabilityStatus"] = json!({
+        "status": "ERROR",
+        "reason": "This content isn't available.",
+        "contextParams": "Q0FFU0FnZ0I="
+    });
+    player_response
+}
+
+/// A player response, that requires the requesting client to sign in.
+fn login_required() -> Value {
+    let mut player_response = without_streams();
+    player_response["playabilityStatus"] = json!({
+        "status": "LOGIN_REQUIRED",
+        "reason": "Sign in to confirm your age",
+        "contextParams": "Q0FFU0FnZ0I="
+    });
+    player_response
+}
+
+fn fetcher(server: &MockServer) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+}
+
+/// Serves an age gated watch page, the embed page, and `api_player_response` for the client.
+async fn age_restricted_youtube(api_player_response: fn(&str) -> Value) -> MockServer {
+    let watch_page = format!(
+        "<html><meta property=\"og:restrictions:age\" content=\"18+\">\
+        <script>var ytInitialPlayerResponse = {};</script></html>",
+        login_required(),
+    );
+    let embed_page = format!("<html><script src=\"{}\"></script></html>", BASE_JS_PATH);
+
+    MockServer::start(move |request| {
+        let path = request.path.as_str();
+        if path.starts_with("/watch") {
+            MockResponse::ok(watch_page.clone())
+        } else if path.starts_with("/embed/") {
+            MockResponse::ok(embed_page.clone())
+        } else if path == BASE_JS_PATH {
+            MockResponse::ok(BASE_JS)
+        } else if path.starts_with("/youtubei/v1/player") {
+            MockResponse::json(&api_player_response(request.json()["context"]["client"]["clientName"].as_str().unwrap()))
+        } else {
+            MockResponse::status(404)
+        }
+    }).await
+}
+
+fn requested_clients(server: &MockServer) -> Vec<String> {
+    server
+        .requests()
+        .iter()
+        .filter(|request| request.path.starts_with("/youtubei/v1/player"))
+        .map(|request| request.json()["context"]["client"]["clientName"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+#[test_log::test(tokio::test)]
+async fn uses_watch_page_player_response() {
+    let server = mock_youtube(playable(), BASE_JS, |_| unavailable()).await;
+
+    let descrambler = fetcher(&server).fetch().await.unwrap();
+
+    assert!(descrambler.video_info().player_response.streaming_data.is_some());
+    assert!(requested_clients(&server).is_empty());
+}
+
+#[test_log::test(tokio::test)]
+async fn falls_back_to_next_client() {
+    let server = mock_youtube(without_streams(), BASE_JS, |client| match client {
+        "ANDROID" => playable(),
+        _ => unavailable(),
+    }).await;
+
+    let descrambler = fetcher(&server).fetch().await.unwrap();
+
+    assert!(descrambler.video_info().player_response.streaming_data.is_some());
+    assert_eq!(requested_clients(&server), ["ANDROID"]);
+
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path.starts_with("/youtubei/v1/player"))
+        .unwrap();
+    let body = request.json();
+    assert_eq!(body["videoId"], VIDEO_ID);
+    assert_eq!(body["playbackContext"]["contentPlaybackContext"]["signatureTimestamp"], 19876);
+    assert_eq!(request.header("X-YouTube-Client-Name"), Some("3"));
+    assert_eq!(request.header("User-Agent"), Some(InnertubeClient::Android.user_agent()));
+}
+
+#[test_log::test(tokio::test)]
+async fn skips_drm_protected_streams() {
+    let server = mock_youtube(drm_protected(), BASE_JS, |client| match client {
+        "WEB_EMBEDDED_PLAYER" => playable(),
+        _ => drm_protected(),
+    }).await;
+
+    let descrambler = fetcher(&server).fetch().await.unwrap();
+
+    assert!(descrambler.video_info().player_response.streaming_data.is_some());
+    assert_eq!(requested_clients(&server), ["ANDROID", "WEB_EMBEDDED_PLAYER"]);
+}
+
+#[test_log::test(tokio::test)]
+async fn all_clients_failed() {
+    let server = mock_youtube(without_streams(), BASE_JS, |client| match client {
+        "ANDROID" => unavailable(),
+        _ => drm_protected(),
+    }).await;
+
+    let failures = match fetcher(&server).fetch().await {
+        Err(Error::AllClientsFailed(failures)) => failures,
+        res => panic!("expected Error::AllClientsFailed, got {:?}", res),
+    };
+
+    let clients: Vec<InnertubeClient> = failures.iter().map(|(client, _)| *client).collect();
+    assert_eq!(clients, InnertubeClient::DEFAULT_CHAIN);
+    assert!(matches!(failures[0].1, Error::NoStreamingData { .. }));
+    assert!(matches!(failures[1].1, Error::VideoUnavailable(_)));
+    assert!(matches!(failures[2].1, Error::NoStreams(NoStreamsReason::AllDrm)));
+}
+
+#[test_log::test(tokio::test)]
+async fn custom_client_chain() {
+    let server = mock_youtube(playable(), BASE_JS, |_| playable()).await;
+
+    let descrambler = fetcher(&server)
+        .with_innertube_clients(vec![InnertubeClient::WebEmbedded])
+        .fetch()
+        .await
+        .unwrap();
+
+    assert!(descrambler.video_info().player_response.streaming_data.is_some());
+    assert_eq!(requested_clients(&server), ["WEB_EMBEDDED_PLAYER"]);
+}
+
+#[test_log::test(tokio::test)]
+async fn preferred_client_falls_back_when_login_is_required() {
+    let server = mock_youtube(without_streams(), BASE_JS, |client| match client {
+        "TVHTML5_SIMPLY_EMBEDDED_PLAYER" => login_required(),
+        "ANDROID" => json_fixture("android_player_response.json"),
+        _ => unavailable(),
+    }).await;
+
+    let fetcher = fetcher(&server).with_innertube_client(InnertubeClient::TvEmbedded);
+    assert_eq!(fetcher.innertube_clients(), [
+        InnertubeClient::TvEmbedded,
+        InnertubeClient::Web,
+        InnertubeClient::Android,
+        InnertubeClient::WebEmbedded,
+        InnertubeClient::Ios,
+    ]);
+    let video = fetcher.fetch().await.unwrap().descramble().unwrap();
+
+    let url = &video.streams()[0].signature_cipher.url;
+    assert!(url.query_pairs().any(|(key, value)| key == "c" && value == "ANDROID"), "{}", url);
+    assert_eq!(requested_clients(&server), ["TVHTML5_SIMPLY_EMBEDDED_PLAYER", "ANDROID"]);
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path.starts_with("/youtubei/v1/player"))
+        .unwrap();
+    assert_eq!(request.header("X-YouTube-Client-Name"), Some("85"));
+    assert_eq!(request.json()["context"]["thirdParty"]["embedUrl"], format!("https://www.youtube.com/embed/{}", VIDEO_ID));
+}
+
+#[test_log::test]
+fn app_clients_return_pre_signed_streams() {
+    for (fixture, client) in [
+        ("android_player_response.json", "ANDROID"),
+        ("ios_player_response.json", "IOS"),
+    ] {
+        let video = video_from_fixture(fixture);
+
+        assert!(!video.streams().is_empty());
+        for stream in video.streams() {
+            let url = &stream.signature_cipher.url;
+            assert!(url.query_pairs().any(|(key, value)| key == "c" && value == client), "{}", url);
+            assert!(url.query_pairs().any(|(key, _)| key == "sig"), "{}", url);
+            assert!(stream.signature_cipher.s.is_none());
+        }
+    }
+
+    let streaming_data = video_from_fixture("ios_player_response.json")
+        .video_info()
+        .player_response
+        .streaming_data
+        .clone()
+        .unwrap();
+    assert!(streaming_data.hls_manifest_url.is_some());
+}
+
+#[test_log::test(tokio::test)]
+async fn pre_signed_streams_need_no_cipher() {
+    // the base.js does not contain a signature cipher
+    let server = mock_youtube(without_streams(), BASE_JS, |client| match client {
+        "IOS" => json_fixture("ios_player_response.json"),
+        _ => unavailable(),
+    }).await;
+
+    let video = fetcher(&server)
+        .with_innertube_clients(vec![InnertubeClient::Ios])
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap();
+
+    // the iOS client only returns adaptive formats
+    assert_eq!(video.streams().len(), 7);
+    assert_eq!(requested_clients(&server), ["IOS"]);
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path.starts_with("/youtubei/v1/player"))
+        .unwrap();
+    assert_eq!(request.header("User-Agent"), Some(InnertubeClient::Ios.user_agent()));
+    assert_eq!(request.json()["context"]["client"]["deviceMake"], "Apple");
+}
+
+#[test_log::test(tokio::test)]
+async fn age_restricted_videos_use_the_embedded_tv_client() {
+    let server = age_restricted_youtube(|client| match client {
+        "TVHTML5_SIMPLY_EMBEDDED_PLAYER" => playable(),
+        _ => login_required(),
+    }).await;
+
+    let video = fetcher(&server).fetch().await.unwrap().descramble().unwrap();
+
+    assert!(video.is_age_restricted());
+    assert!(!video.streams().is_empty());
+    assert_eq!(requested_clients(&server), ["TVHTML5_SIMPLY_EMBEDDED_PLAYER"]);
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path.starts_with("/youtubei/v1/player"))
+        .unwrap();
+    assert_eq!(request.json(), json!({
+        "context": {
+            "client": {
+                "clientName": "TVHTML5_SIMPLY_EMBEDDED_PLAYER",
+                "clientVersion": "2.0",
+                "hl": "en",
+                "gl": "US",
+                "clientScreen": "EMBED"
+            },
+            "thirdParty": { "embedUrl": format!("https://www.youtube.com/embed/{}", VIDEO_ID) }
+        },
+        "videoId": VIDEO_ID,
+        "contentCheckOk": true,
+        "racyCheckOk": true,
+        "playbackContext": {
+            "contentPlaybackContext": { "signatureTimestamp": 19876 }
+        }
+    }));
+}
+
+#[test_log::test(tokio::test)]
+async fn age_restricted_videos_fall_back_to_the_chain() {
+    let server = age_restricted_youtube(|client| match client {
+        "ANDROID" => playable(),
+        _ => login_required(),
+    }).await;
+
+    let video = fetcher(&server).fetch().await.unwrap().descramble().unwrap();
+
+    assert!(video.is_age_restricted());
+    assert_eq!(requested_clients(&server), ["TVHTML5_SIMPLY_EMBEDDED_PLAYER", "WEB", "ANDROID"]);
+}
+
+#[test_log::test(tokio::test)]
+#[ignore]
+async fn made_for_kids_video() {
+    // JsGOGPTVkKg is flagged as made for kids
+    let id = Id::from_str("JsGOGPTVkKg").unwrap();
+    let video = video!(id);
+
+    assert!(!video.streams().is_empty());
+}
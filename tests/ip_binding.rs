@@ -0,0 +1,151 @@
+#![cfg(feature = "descramble")]
+
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde_json::Value;
+use url::Url;
+
+use common::*;
+use rustube::{Id, InnertubeClient, Video, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+/// The ciphered corpus fixture, whose stream URLs are bound to `ip`.
+fn ciphered(ip: &str) -> Value {
+    let mut player_response: Value = serde_json::from_str(&read_fixture("corpus/ciphered.json")).unwrap();
+    let streaming_data = &mut player_response["streamingData"];
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in streaming_data[formats].as_array_mut().unwrap() {
+            let signature_cipher = format["signatureCipher"].as_str().unwrap();
+            let bound: String = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(url::form_urlencoded::parse(signature_cipher.as_bytes()).map(|(key, value)| {
+                    match key.as_ref() {
+                        "url" => (key, format!("{}&ip={}&sparams=expire,ip,itag", value, ip).into()),
+                        _ => (key, value),
+                    }
+                }))
+                .finish();
+            format["signatureCipher"] = bound.into();
+        }
+    }
+    player_response
+}
+
+fn video_id() -> Id<'static> {
+    let player_response = ciphered("203.0.113.1");
+    Id::from_string(player_response["videoDetails"]["videoId"].as_str().unwrap().to_owned()).unwrap()
+}
+
+/// A mock YouTube, whose player endpoint binds the stream URLs to `203.0.113.<n>` on the n-th
+/// request.
+async fn server() -> MockServer {
+    let requests = Arc::new(AtomicUsize::new(0));
+    mock_youtube(ciphered("203.0.113.0"), read_fixture("base.js"), move |_| {
+        let n = requests.fetch_add(1, Ordering::SeqCst) + 1;
+        ciphered(&format!("203.0.113.{}", n))
+    }).await
+}
+
+fn fetcher(server: &MockServer) -> VideoFetcher {
+    VideoFetcher::from_id(video_id())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_innertube_clients(vec![InnertubeClient::Android])
+}
+
+fn paths(server: &MockServer) -> Vec<String> {
+    server
+        .requests()
+        .into_iter()
+        .map(|request| request.path.split('?').next().unwrap().to_owned())
+        .collect()
+}
+
+fn stream_with_url(url: &str) -> rustube::Stream {
+    let (_, mut streams) = video_from_fixture("multi_audio_player_response.json").into_parts();
+    let mut stream = streams.swap_remove(0);
+    stream.signature_cipher.url = Url::parse(url).unwrap();
+    stream
+}
+
+#[test]
+fn bound_ip() {
+    let stream = stream_with_url("https://rr1---sn-4g5e6nzz.googlevideo.com/videoplayback?expire=1&ip=203.0.113.7&sparams=expire,ei,ip,id");
+    assert_eq!(stream.bound_ip(), Some("203.0.113.7".parse::<IpAddr>().unwrap()));
+    assert!(stream.is_ip_bound());
+
+    let stream = stream_with_url("https://rr1---sn-4g5e6nzz.googlevideo.com/videoplayback?ip=2001%3Adb8%3A%3A1&sparams=ip");
+    assert_eq!(stream.bound_ip(), Some("2001:db8::1".parse::<IpAddr>().unwrap()));
+    assert!(stream.is_ip_bound());
+
+    // the ip is not signed, so it's not enforced
+    let stream = stream_with_url("https://rr1---sn-4g5e6nzz.googlevideo.com/videoplayback?ip=203.0.113.7&sparams=expire,ei,id,ipbits");
+    assert!(stream.bound_ip().is_some());
+    assert!(!stream.is_ip_bound());
+
+    let stream = stream_with_url("https://rr1---sn-4g5e6nzz.googlevideo.com/videoplayback?ip=localhost&sparams=expire");
+    assert_eq!(stream.bound_ip(), None);
+
+    let stream = stream_with_url("https://rr1---sn-4g5e6nzz.googlevideo.com/videoplayback?expire=1");
+    assert_eq!(stream.bound_ip(), None);
+    assert!(!stream.is_ip_bound());
+}
+
+#[test_log::test(tokio::test)]
+async fn refetch_reuses_the_js() {
+    let server = server().await;
+    let video = fetcher(&server).fetch().await.unwrap().descramble().unwrap();
+    assert_eq!(paths(&server), ["/watch", BASE_JS_PATH, "/youtubei/v1/player"]);
+    assert!(video.streams().iter().all(|stream| stream.bound_ip() == Some("203.0.113.1".parse().unwrap())));
+
+    let refetched = fetcher(&server).refetch_streams(&video).await.unwrap();
+
+    // only the player endpoint was requested
+    assert_eq!(paths(&server)[3..], ["/youtubei/v1/player"]);
+    assert_eq!(refetched.streams().len(), video.streams().len());
+    assert!(refetched.descramble_errors().is_empty());
+    for stream in refetched.streams() {
+        assert_eq!(stream.bound_ip(), Some("203.0.113.2".parse().unwrap()));
+        assert!(stream.is_ip_bound());
+        assert!(stream.signature_cipher.url.query_pairs().any(|(key, _)| key == "sig"));
+    }
+    assert_eq!(refetched.id(), video.id());
+
+    // the js is passed on to the refetched video
+    fetcher(&server).refetch_streams(&refetched).await.unwrap();
+    assert_eq!(paths(&server)[4..], ["/youtubei/v1/player"]);
+}
+
+#[test_log::test(tokio::test)]
+async fn refetch_without_js() {
+    let server = server().await;
+    let (video_info, streams) = video_from_player_response(ciphered("203.0.113.0")).into_parts();
+    let video = Video::from_parts(video_info, streams);
+
+    // the new streams are ciphered, so base.js is needed
+    let refetched = fetcher(&server).refetch_streams(&video).await.unwrap();
+    assert_eq!(paths(&server), ["/youtubei/v1/player", "/watch", BASE_JS_PATH]);
+    assert!(refetched.streams().iter().all(|stream| stream.signature_cipher.url.query_pairs().any(|(key, _)| key == "sig")));
+}
+
+#[test_log::test(tokio::test)]
+async fn refetch_pre_signed_without_js() {
+    let player_response = json_fixture("multi_audio_player_response.json");
+    let server = mock_youtube(player_response.clone(), "", move |_| player_response.clone()).await;
+    let video = video_from_fixture("multi_audio_player_response.json");
+
+    let refetched = VideoFetcher::from_id(Id::from_str("fDzQ3kUg8Ss").unwrap().into_owned())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_innertube_clients(vec![InnertubeClient::Android])
+        .refetch_streams(&video)
+        .await
+        .unwrap();
+
+    // all streams are pre-signed, so neither the watch page, nor base.js are needed
+    assert_eq!(paths(&server), ["/youtubei/v1/player"]);
+    assert_eq!(refetched.streams().len(), 8);
+}
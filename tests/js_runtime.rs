@@ -0,0 +1,151 @@
+#![cfg(feature = "descramble")]
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{Id, JsRuntime, Video, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const N: &str = "abcdefgh";
+
+/// A [`JsRuntime`], that records all invocations, and returns the reversed argument.
+#[derive(Debug, Default)]
+struct FakeRuntime {
+    calls: Mutex<Vec<(String, String, String)>>,
+}
+
+impl JsRuntime for FakeRuntime {
+    fn eval_function(&self, js_source: &str, fn_name: &str, arg: &str) -> rustube::Result<String> {
+        self.calls.lock().unwrap().push((js_source.to_owned(), fn_name.to_owned(), arg.to_owned()));
+        Ok(arg.chars().rev().collect())
+    }
+}
+
+/// The multi audio fixture, where every stream URL contains the `n` parameter [`N`], and the first
+/// stream requires its signature to be descrambled.
+fn player_response() -> Value {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    let streaming_data = &mut player_response["streamingData"];
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in streaming_data[formats].as_array_mut().unwrap() {
+            format["url"] = json!(format!("{}&n={}", format["url"].as_str().unwrap(), N));
+        }
+    }
+
+    let first = &mut streaming_data["formats"][0];
+    let url = first.as_object_mut().unwrap().remove("url").unwrap();
+    let url = url.as_str().unwrap().replace("&sig=AOq0QJ8wRQIgPreSigned", "");
+    let url: String = url::form_urlencoded::byte_serialize(url.as_bytes()).collect();
+    first["signatureCipher"] = json!(format!("s=0123456789&sp=sig&url={}", url));
+    player_response
+}
+
+async fn video(base_js: String, js_runtime: Option<Arc<dyn JsRuntime>>) -> Video {
+    let server = mock_youtube(player_response(), base_js, |_| Value::Null).await;
+    let mut fetcher = VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone());
+    if let Some(js_runtime) = js_runtime {
+        fetcher = fetcher.with_js_runtime(js_runtime);
+    }
+
+    fetcher
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap()
+}
+
+fn query_param(video: &Video, itag: u64, key: &str) -> Option<String> {
+    video
+        .streams()
+        .iter()
+        .find(|stream| stream.itag == itag)
+        .unwrap()
+        .signature_cipher
+        .url
+        .query_pairs()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value.into_owned())
+}
+
+/// `base.js`, with an n-parameter function, that cannot be translated to Rust.
+fn untranslatable_base_js() -> String {
+    read_fixture("base.js").replace("d.reverse()", "d.sort()")
+}
+
+#[test_log::test(tokio::test)]
+async fn n_is_transformed_without_runtime() {
+    let video = video(read_fixture("base.js"), None).await;
+
+    for stream in video.streams() {
+        assert_eq!(query_param(&video, stream.itag, "n").as_deref(), Some("egfhdcba"));
+    }
+    // the signature was descrambled by the Rust implementation
+    assert_eq!(query_param(&video, 18, "sig").as_deref(), Some("76540213"));
+}
+
+#[test_log::test(tokio::test)]
+async fn n_falls_back_to_runtime() {
+    let video = video(untranslatable_base_js(), None).await;
+    for stream in video.streams() {
+        assert_eq!(query_param(&video, stream.itag, "n").as_deref(), Some(N));
+    }
+
+    let runtime = Arc::new(FakeRuntime::default());
+    let video = self::video(untranslatable_base_js(), Some(runtime.clone())).await;
+
+    for stream in video.streams() {
+        assert_eq!(query_param(&video, stream.itag, "n").as_deref(), Some("hgfedcba"));
+    }
+
+    // all streams share the same `n`, and the signature did not need the runtime
+    let calls = runtime.calls.lock().unwrap();
+    assert_eq!(calls.len(), 1);
+    let (source, fn_name, arg) = &calls[0];
+    assert_eq!(fn_name, "Ela");
+    assert!(source.starts_with("var Ela=function(a){"));
+    assert!(source.ends_with("return b.join(\"\")};"));
+    assert_eq!(arg, N);
+}
+
+#[test_log::test(tokio::test)]
+async fn signature_falls_back_to_runtime() {
+    // the Rust implementation does not know `push`
+    let base_js = read_fixture("base.js").replace("a.splice(0,b)", "a.push(b)");
+    let runtime = Arc::new(FakeRuntime::default());
+    let video = video(base_js, Some(runtime.clone())).await;
+
+    assert_eq!(query_param(&video, 18, "sig").as_deref(), Some("9876543210"));
+
+    let calls = runtime.calls.lock().unwrap();
+    let (source, _, _) = calls
+        .iter()
+        .find(|(_, fn_name, _)| fn_name == "Gla")
+        .expect("the signature function was not evaluated");
+    assert!(source.starts_with("var Xo={"));
+    assert!(source.contains("var Gla=function(a){a=a.split(\"\");"));
+}
+
+#[cfg(feature = "boa")]
+#[test_log::test(tokio::test)]
+async fn boa_runtime() {
+    use rustube::BoaRuntime;
+
+    let video = video(untranslatable_base_js(), Some(Arc::new(BoaRuntime))).await;
+
+    for stream in video.streams() {
+        assert_eq!(query_param(&video, stream.itag, "n").as_deref(), Some("dbcaefgh"));
+    }
+
+    let result = BoaRuntime
+        .eval_function("var f=function(a){return a.split(\"\").reverse().join(\"\")};", "f", "a\"b")
+        .unwrap();
+    assert_eq!(result, "b\"a");
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "stream")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{LiveStatus, Video};
+
+#[macro_use]
+mod common;
+
+/// The multi audio fixture, with the given `videoDetails` flags.
+fn player_response(flags: Value) -> Value {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    for (flag, value) in flags.as_object().unwrap() {
+        player_response["videoDetails"][flag] = value.clone();
+    }
+    player_response
+}
+
+fn video(flags: Value) -> Video {
+    video_from_player_response(player_response(flags))
+}
+
+#[test]
+fn not_live() {
+    let video = video(json!({}));
+
+    assert_eq!(video.live_status(), LiveStatus::NotLive);
+    assert!(video.streams().iter().all(|stream| !stream.is_post_live_processing()));
+}
+
+#[test]
+fn live() {
+    let video = video(json!({ "isLiveContent": true, "isLive": true }));
+
+    assert_eq!(video.live_status(), LiveStatus::Live);
+}
+
+#[test]
+fn was_live() {
+    let video = video(json!({ "isLiveContent": true }));
+
+    assert_eq!(video.live_status(), LiveStatus::WasLive);
+}
+
+#[test]
+fn upcoming_premiere() {
+    let video = video(json!({ "isUpcoming": true }));
+
+    assert_eq!(video.live_status(), LiveStatus::UpcomingPremiere);
+}
+
+#[test]
+fn post_live_processing() {
+    let video = video(json!({ "isLiveContent": true, "isPostLiveDvr": true }));
+
+    assert_eq!(video.live_status(), LiveStatus::PostLiveProcessing);
+    assert!(!video.streams().is_empty());
+    assert!(video.streams().iter().all(|stream| stream.is_post_live_processing()));
+}
+
+#[cfg(feature = "download")]
+#[test_log::test(tokio::test)]
+async fn post_live_processing_download_error() {
+    use rustube::Error;
+
+    let server = MockServer::start(|_| MockResponse::status(404)).await;
+    let video = video(json!({ "isLiveContent": true, "isPostLiveDvr": true }));
+    let video = with_streams_at(video, &server, "videoplayback?itag=140");
+    let stream = video.best_audio().unwrap();
+
+    let path = temp_dir("post_live_processing").join("video.m4a");
+    let err = stream.download_to(&path).await.unwrap_err();
+    let _ = std::fs::remove_file(&path);
+
+    match &err {
+        Error::PostLiveProcessing { video_id, source } => {
+            assert_eq!(video_id.as_str(), "fDzQ3kUg8Ss");
+            assert!(matches!(**source, Error::Request(_)));
+        }
+        err => panic!("expected Error::PostLiveProcessing, got {:?}", err),
+    }
+    assert!(err.to_string().contains("still being processed"));
+}
+
+#[cfg(feature = "microformat")]
+mod microformat {
+    use super::*;
+
+    /// The multi audio fixture, with a microformat containing `live_broadcast_details`.
+    fn video(flags: Value, live_broadcast_details: Value) -> Video {
+        let mut player_response = player_response(flags);
+        player_response["microformat"] = json!({
+            "playerMicroformatRenderer": {
+                "availableCountries": ["DE", "US"],
+                "category": "Music",
+                "description": { "simpleText": "" },
+                "externalChannelId": "UC1yBKRuGpC1tSM73A0ZjYjQ",
+                "isUnlisted": false,
+                "lengthSeconds": "615",
+                "liveBroadcastDetails": live_broadcast_details,
+                "ownerChannelName": "rustube",
+                "ownerProfileUrl": "http://www.youtube.com/@rustube",
+                "publishDate": "2024-05-01",
+                "thumbnail": { "thumbnails": [] },
+                "title": { "simpleText": "Multi-language audio sample" },
+                "uploadDate": "2024-05-01",
+                "viewCount": "1034829"
+            }
+        });
+        video_from_player_response(player_response)
+    }
+
+    #[test]
+    fn finished_premiere() {
+        let video = video(json!({}), json!({
+            "isLiveNow": false,
+            "startTimestamp": "2024-05-01T18:00:00+00:00",
+            "endTimestamp": "2024-05-01T18:10:15+00:00"
+        }));
+
+        assert_eq!(video.live_status(), LiveStatus::WasLive);
+        assert!(!video.video_details().is_live_content);
+    }
+
+    #[test]
+    fn upcoming_premiere() {
+        let video = video(json!({}), json!({
+            "isLiveNow": false,
+            "startTimestamp": "2999-01-01T00:00:00+00:00"
+        }));
+
+        assert_eq!(video.live_status(), LiveStatus::UpcomingPremiere);
+    }
+
+    #[test]
+    fn live_now() {
+        let video = video(json!({}), json!({
+            "isLiveNow": true,
+            "startTimestamp": "2024-05-01T18:00:00+00:00"
+        }));
+
+        assert_eq!(video.live_status(), LiveStatus::Live);
+    }
+
+    #[test]
+    fn flags_take_precedence() {
+        let video = video(json!({ "isLiveContent": true, "isPostLiveDvr": true }), json!({
+            "isLiveNow": false,
+            "startTimestamp": "2024-05-01T18:00:00+00:00",
+            "endTimestamp": "2024-05-01T20:00:00+00:00"
+        }));
+
+        assert_eq!(video.live_status(), LiveStatus::PostLiveProcessing);
+    }
+}
@@ -0,0 +1,132 @@
+#![cfg(feature = "fetch")]
+
+use rustube::{CountryCode, Error, LanguageTag};
+
+#[allow(unused_imports)]
+use common::*;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn parse_country_code() {
+    let de: CountryCode = "de".parse().unwrap();
+    assert_eq!(de.as_str(), "DE");
+    assert_eq!(de.to_string(), "DE");
+    assert_eq!(de, "de");
+    assert_eq!(de, "DE");
+
+    for invalid in ["", "D", "DEU", "1A", "D-"] {
+        assert!(matches!(invalid.parse::<CountryCode>(), Err(Error::BadLocaleFormat(_))), "{:?}", invalid);
+    }
+}
+
+#[test]
+fn deserialize_country_codes() {
+    let countries: Vec<CountryCode> = serde_json::from_str(r#"["DE", "us", "YT", "", "XYZ", "1A"]"#).unwrap();
+
+    let codes: Vec<&str> = countries.iter().map(CountryCode::as_str).collect();
+    assert_eq!(codes, ["DE", "US", "YT", "", "XYZ", "1A"]);
+    let valid: Vec<bool> = countries.iter().map(CountryCode::is_valid).collect();
+    assert_eq!(valid, [true, true, true, false, false, false]);
+    assert_eq!(serde_json::to_string(&countries).unwrap(), r#"["DE","US","YT","","XYZ","1A"]"#);
+}
+
+#[test]
+fn parse_language_tag() {
+    let cases = [
+        ("en", "en"),
+        ("EN-us", "en-US"),
+        ("en_GB", "en-GB"),
+        ("zh-hant-tw", "zh-Hant-TW"),
+        ("es-419", "es-419"),
+        ("fil", "fil"),
+        ("de-DE-x-Bavarian", "de-DE-x-bavarian"),
+    ];
+    for (raw, normalized) in cases {
+        let tag: LanguageTag = raw.parse().unwrap();
+        assert_eq!(tag.as_str(), normalized);
+        assert!(tag.is_valid());
+    }
+
+    for invalid in ["", "e", "english", "en-", "en--US", "en-US.4", "1a"] {
+        assert!(matches!(invalid.parse::<LanguageTag>(), Err(Error::BadLocaleFormat(_))), "{:?}", invalid);
+    }
+}
+
+#[test]
+fn language_tag_subtags() {
+    let tag: LanguageTag = "zh-Hant-TW".parse().unwrap();
+    assert_eq!(tag.primary(), "zh");
+    assert_eq!(tag.region(), Some("TW"));
+
+    let tag: LanguageTag = "es-419".parse().unwrap();
+    assert_eq!(tag.region(), Some("419"));
+
+    let tag: LanguageTag = "en-x-us".parse().unwrap();
+    assert_eq!(tag.region(), None);
+}
+
+#[test]
+fn language_tag_matches() {
+    let tag = |tag: &str| LanguageTag::from_raw(tag);
+
+    assert!(tag("en").matches(&tag("en-US")));
+    assert!(tag("en-US").matches(&tag("en")));
+    assert!(tag("en-us").matches(&tag("EN-US")));
+    assert!(!tag("en-US").matches(&tag("en-GB")));
+    assert!(!tag("en").matches(&tag("es")));
+    assert!(!tag("en").matches(&tag("eng")));
+}
+
+#[test]
+fn deserialize_language_tags() {
+    let tags: Vec<LanguageTag> = serde_json::from_str(r#"["de-de", "en", "a.b", "", "iw"]"#).unwrap();
+
+    let raw: Vec<&str> = tags.iter().map(LanguageTag::as_str).collect();
+    assert_eq!(raw, ["de-DE", "en", "a.b", "", "iw"]);
+    assert_eq!(tags[2].primary(), "a.b");
+    assert_eq!(tags[3].primary(), "");
+    assert!(!tags[2].is_valid());
+}
+
+#[cfg(feature = "stream")]
+#[test]
+fn audio_track_language_tag() {
+    let video = video_from_fixture("multi_audio_player_response.json");
+    let tags: Vec<LanguageTag> = video
+        .audio_tracks()
+        .iter()
+        .map(|track| track.language_tag())
+        .collect();
+
+    assert_eq!(tags, ["de-DE", "en-US", "es-US"]);
+    assert_eq!(tags[1].region(), Some("US"));
+    assert!(tags[1].matches(&"en".parse().unwrap()));
+}
+
+#[cfg(feature = "microformat")]
+#[test]
+fn microformat_available_countries() {
+    use rustube::video_info::player_response::microformat::PlayerMicroformatRenderer;
+
+    let renderer: PlayerMicroformatRenderer = serde_json::from_str(r#"{
+        "availableCountries": ["DE", "us", "YT", "ZZZ"],
+        "category": "Music",
+        "description": { "simpleText": "" },
+        "externalChannelId": "UC1yBKRuGpC1tSM73A0ZjYjQ",
+        "isUnlisted": false,
+        "lengthSeconds": "615",
+        "ownerChannelName": "rustube",
+        "ownerProfileUrl": "http://www.youtube.com/@rustube",
+        "publishDate": "2024-05-01",
+        "thumbnail": { "thumbnails": [] },
+        "title": { "simpleText": "Multi-language audio sample" },
+        "uploadDate": "2024-05-01",
+        "viewCount": "1034829"
+    }"#).unwrap();
+
+    assert_eq!(renderer.available_countries, ["DE", "US", "YT", "ZZZ"]);
+    assert!(renderer.available_countries.iter().any(|country| country == "us"));
+    assert!(!renderer.available_countries[3].is_valid());
+}
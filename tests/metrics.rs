@@ -0,0 +1,128 @@
+#![cfg(feature = "fetch")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use rustube::{Metrics, Phase};
+use rustube::metrics::{Clock, PhaseMetrics};
+
+#[allow(unused_imports)]
+use common::*;
+
+#[macro_use]
+mod common;
+
+/// A clock, that only moves, when it's told to.
+#[derive(Clone, Default)]
+struct MockClock(Arc<AtomicU64>);
+
+impl MockClock {
+    fn advance(&self, millis: u64) {
+        self.0.fetch_add(millis, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Duration {
+        Duration::from_millis(self.0.load(Ordering::SeqCst))
+    }
+}
+
+#[test]
+fn timer_records_on_drop() {
+    let clock = MockClock::default();
+    let metrics = Metrics::with_clock(clock.clone());
+
+    let mut timer = metrics.start(Phase::WatchHtml);
+    clock.advance(120);
+    timer.add_bytes(1000);
+    timer.add_bytes(24);
+    assert_eq!(metrics.phase(Phase::WatchHtml), PhaseMetrics::default());
+    drop(timer);
+
+    assert_eq!(metrics.phase(Phase::WatchHtml), PhaseMetrics {
+        count: 1,
+        duration: Duration::from_millis(120),
+        bytes: 1024,
+    });
+}
+
+#[test]
+fn phases_accumulate() {
+    let clock = MockClock::default();
+    let metrics = Metrics::with_clock(clock.clone());
+
+    for millis in [100, 250] {
+        let _timer = metrics.start(Phase::PlayerApi);
+        clock.advance(millis);
+    }
+    metrics.record(Phase::Download, Duration::from_secs(2), 4096);
+    // time passing outside of a phase isn't recorded
+    clock.advance(10_000);
+
+    assert_eq!(metrics.phase(Phase::PlayerApi), PhaseMetrics {
+        count: 2,
+        duration: Duration::from_millis(350),
+        bytes: 0,
+    });
+    assert_eq!(metrics.phase(Phase::BaseJs), PhaseMetrics::default());
+
+    let report = metrics.report();
+    assert_eq!(report.phases.keys().copied().collect::<Vec<_>>(), [Phase::PlayerApi, Phase::Download]);
+    assert_eq!(report.total_duration, Duration::from_millis(2350));
+    assert_eq!(report.total_bytes, 4096);
+}
+
+#[test]
+fn report_serialization() {
+    let metrics = Metrics::with_clock(MockClock::default());
+    metrics.record(Phase::BaseJs, Duration::from_millis(1500), 2048);
+
+    let json = serde_json::to_value(metrics.report()).unwrap();
+    assert_eq!(json, serde_json::json!({
+        "phases": {
+            "base_js": { "count": 1, "duration": 1.5, "bytes": 2048 }
+        },
+        "total_duration": 1.5,
+        "total_bytes": 2048,
+    }));
+}
+
+#[cfg(feature = "descramble")]
+#[test_log::test(tokio::test)]
+async fn fetch_and_descramble_are_recorded() {
+    use rustube::{Id, VideoFetcher};
+
+    let base_js = read_fixture("base.js");
+    let base_js_len = base_js.len() as u64;
+    let player_response = json_fixture("multi_audio_player_response.json");
+    let server = mock_youtube(player_response, base_js, |_| serde_json::Value::Null).await;
+
+    let metrics = Arc::new(Metrics::new());
+    let video = VideoFetcher::from_id(Id::from_str("fDzQ3kUg8Ss").unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_metrics(Arc::clone(&metrics))
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap();
+
+    let report = video.metrics().unwrap();
+    assert_eq!(report, metrics.report());
+    assert_eq!(report.phases[&Phase::WatchHtml].count, 1);
+    assert!(report.phases[&Phase::WatchHtml].bytes > 0);
+    assert_eq!(report.phases[&Phase::BaseJs].count, 1);
+    assert_eq!(report.phases[&Phase::BaseJs].bytes, base_js_len);
+    assert_eq!(report.phases[&Phase::Descramble].count, 1);
+    assert!(!report.phases.contains_key(&Phase::Download));
+}
+
+#[cfg(feature = "descramble")]
+#[test]
+fn no_metrics_by_default() {
+    let video = video_from_fixture("multi_audio_player_response.json");
+    assert_eq!(video.metrics(), None);
+}
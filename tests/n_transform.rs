@@ -0,0 +1,88 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{Id, Video, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+
+/// `n` parameters, and what the n-parameter function of `base_n.js` transforms them into (as
+/// computed by node).
+const KNOWN_VALUES: [(&str, &str); 4] = [
+    ("abcdefgh", "hick66c"),
+    ("N7Y5UlXh3YR0zA", "48hYD8AU1Lg3y"),
+    ("wK1yEfWs0pW8Pjl2", "epxGtyB7ely6Ijf"),
+    ("0000000000", "1w_y0ZL3X"),
+];
+
+/// The multi audio fixture, where every stream URL contains the `n` parameter `n`.
+fn player_response(n: &str) -> Value {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in player_response["streamingData"][formats].as_array_mut().unwrap() {
+            format["url"] = json!(format!("{}&n={}", format["url"].as_str().unwrap(), n));
+        }
+    }
+    player_response
+}
+
+async fn video(n: &str, base_js: String) -> Video {
+    let server = mock_youtube(player_response(n), base_js, |_| Value::Null).await;
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap()
+}
+
+fn n_params(video: &Video) -> Vec<String> {
+    video
+        .streams()
+        .iter()
+        .map(|stream| {
+            stream.signature_cipher.url
+                .query_pairs()
+                .find(|(key, _)| key == "n")
+                .unwrap()
+                .1
+                .into_owned()
+        })
+        .collect()
+}
+
+#[test_log::test(tokio::test)]
+async fn known_values() {
+    for (n, expected) in KNOWN_VALUES {
+        let video = video(n, read_fixture("base_n.js")).await;
+
+        assert!(!video.streams().is_empty());
+        assert!(n_params(&video).iter().all(|new_n| new_n == expected), "{}: {:?}", n, n_params(&video));
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn unknown_helpers_leave_n_untouched() {
+    // the Rust translation does not know `sort`
+    let base_js = read_fixture("base_n.js").replace("function(d){d.reverse()}", "function(d){d.sort()}");
+    let (n, _) = KNOWN_VALUES[0];
+    let video = video(n, base_js).await;
+
+    assert!(n_params(&video).iter().all(|new_n| new_n == n));
+}
+
+#[test_log::test(tokio::test)]
+async fn enhanced_except_leaves_n_untouched() {
+    // there is no `c[19]`, so calling it fails
+    let base_js = read_fixture("base_n.js").replace("c[18](c[11],c[0])}catch", "c[18](c[11],c[0]),c[19](c[11])}catch");
+    let (n, _) = KNOWN_VALUES[0];
+    let video = video(n, base_js).await;
+
+    assert!(n_params(&video).iter().all(|new_n| new_n == n));
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{Error, Id, InnertubeClient, NoStreamsReason, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+
+fn playable() -> Value {
+    json_fixture("multi_audio_player_response.json")
+}
+
+/// A playable player response without any streams.
+fn without_streams() -> Value {
+    let mut player_response = playable();
+    player_response.as_object_mut().unwrap().remove("streamingData");
+    player_response
+}
+
+/// A premiere, that did not start yet.
+fn upcoming_premiere() -> Value {
+    let mut player_response = without_streams();
+    player_response["videoDetails"]["isUpcoming"] = true.into();
+    player_response
+}
+
+fn fetcher(server: &MockServer) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+}
+
+async fn descramble_err(server: &MockServer) -> Error {
+    fetcher(server)
+        .with_innertube_clients(vec![InnertubeClient::Android, InnertubeClient::WebEmbedded])
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap_err()
+}
+
+#[test_log::test(tokio::test)]
+async fn keeps_video_info() {
+    let mut player_response = without_streams();
+    player_response["playabilityStatus"]["messages"] = json!(["This video is still being processed."]);
+    let server = mock_youtube(without_streams(), BASE_JS, move |_| player_response.clone()).await;
+
+    match descramble_err(&server).await {
+        Error::NoStreamingData { video_info, reason } => {
+            assert_eq!(video_info.player_response.video_details.video_id.as_str(), VIDEO_ID);
+            assert_eq!(video_info.player_response.video_details.title, playable()["videoDetails"]["title"]);
+            assert_eq!(reason.as_deref(), Some("This video is still being processed."));
+        }
+        err => panic!("expected Error::NoStreamingData, got {:?}", err),
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn empty_formats() {
+    let mut player_response = playable();
+    player_response["streamingData"]["formats"] = json!([]);
+    player_response["streamingData"]["adaptiveFormats"] = json!([]);
+    let server = mock_youtube(without_streams(), BASE_JS, move |_| player_response.clone()).await;
+
+    let err = descramble_err(&server).await;
+    assert!(matches!(err, Error::NoStreamingData { reason: None, .. }), "{:?}", err);
+    assert_eq!(err.to_string(), "the video has no downloadable streams yet");
+    assert_eq!(err.no_streams_reason(), Some(NoStreamsReason::EmptyStreamingData));
+}
+
+#[test_log::test(tokio::test)]
+async fn upcoming_premiere_reason() {
+    let server = mock_youtube(upcoming_premiere(), BASE_JS, |_| upcoming_premiere()).await;
+
+    let err = descramble_err(&server).await;
+    assert_eq!(err.to_string(), "the video has no downloadable streams yet: the premiere did not start yet");
+    assert_eq!(err.no_streams_reason(), Some(NoStreamsReason::LiveNotStarted));
+}
+
+#[test_log::test(tokio::test)]
+async fn uses_streams_of_other_clients() {
+    let server = mock_youtube(without_streams(), BASE_JS, |client| match client {
+        "WEB_EMBEDDED_PLAYER" => playable(),
+        _ => without_streams(),
+    }).await;
+
+    let video = fetcher(&server)
+        .with_innertube_clients(vec![InnertubeClient::Android, InnertubeClient::WebEmbedded])
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap();
+    assert!(!video.streams().is_empty());
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{Error, Id, InnertubeClient, NoStreamsReason, PlayerResponse, Video, VideoFetcher};
+use rustube::video_info::player_response::streaming_data::StreamingData;
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+const SABR_URL: &str = "https://rr1---sn-4g5e6nzz.googlevideo.com/videoplayback?expire=1700000000&id=o-ABC&sabr=1";
+
+fn playable() -> Value {
+    json_fixture("multi_audio_player_response.json")
+}
+
+/// A player response, whose formats at the `indices` have no URL, and are only available via SABR.
+fn sabr_only(indices: &[usize]) -> Value {
+    let mut player_response = playable();
+    let streaming_data = &mut player_response["streamingData"];
+    streaming_data["serverAbrStreamingUrl"] = SABR_URL.into();
+
+    let formats = streaming_data["formats"].as_array().unwrap().len();
+    for &i in indices {
+        let format = match i.checked_sub(formats) {
+            Some(i) => &mut streaming_data["adaptiveFormats"][i],
+            None => &mut streaming_data["formats"][i],
+        };
+        format.as_object_mut().unwrap().remove("url");
+    }
+    player_response
+}
+
+/// A player response, whose streams are all DRM protected.
+fn drm_protected() -> Value {
+    let mut player_response = playable();
+    let streaming_data = &mut player_response["streamingData"];
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in streaming_data[formats].as_array_mut().unwrap() {
+            format["drmFamilies"] = json!(["WIDEVINE"]);
+        }
+    }
+    player_response
+}
+
+fn player_response(value: Value) -> PlayerResponse {
+    serde_json::from_str(&value.to_string()).unwrap()
+}
+
+async fn fetch_err(api_player_response: Value) -> Error {
+    let server = mock_youtube(api_player_response.clone(), BASE_JS, move |_| api_player_response.clone()).await;
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_innertube_clients(vec![InnertubeClient::Android])
+        .fetch()
+        .await
+        .unwrap_err()
+}
+
+#[test]
+fn sabr_only_formats_are_collected_by_itag() {
+    let player_response = player_response(sabr_only(&[1, 5]));
+    let streaming_data = player_response.streaming_data.unwrap();
+
+    assert_eq!(streaming_data.sabr_only_itags, [137, 251]);
+    assert_eq!(streaming_data.formats.len() + streaming_data.adaptive_formats.len(), 6);
+    assert_eq!(streaming_data.server_abr_streaming_url.unwrap().as_str(), SABR_URL);
+}
+
+#[test]
+fn server_abr_expiry() {
+    let streaming_data = player_response(sabr_only(&[])).streaming_data.unwrap();
+    assert_eq!(streaming_data.server_abr_expiry().unwrap().timestamp(), 1_700_000_000);
+
+    let streaming_data = player_response(playable()).streaming_data.unwrap();
+    assert!(streaming_data.server_abr_expiry().is_none());
+}
+
+#[test]
+fn sabr_only_itags_round_trip() {
+    let streaming_data = player_response(sabr_only(&[0])).streaming_data.unwrap();
+    let json = serde_json::to_string(&streaming_data).unwrap();
+
+    let deserialized: StreamingData = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.sabr_only_itags, [18]);
+    assert_eq!(deserialized.server_abr_streaming_url, streaming_data.server_abr_streaming_url);
+    assert_eq!(deserialized.adaptive_formats.len(), 7);
+}
+
+#[test]
+fn invalid_formats_still_fail() {
+    let mut value = playable();
+    value["streamingData"]["formats"][0]["mimeType"] = "not a mime type".into();
+
+    let err = serde_json::from_str::<PlayerResponse>(&value.to_string()).unwrap_err();
+    assert!(err.to_string().contains("not a mime type"), "{}", err);
+}
+
+#[test_log::test(tokio::test)]
+async fn all_sabr_only() {
+    let err = fetch_err(sabr_only(&(0..8).collect::<Vec<_>>())).await;
+
+    assert_eq!(err.no_streams_reason(), Some(NoStreamsReason::AllSabrOnly));
+    match err {
+        Error::AllClientsFailed(failures) => assert!(
+            matches!(failures[..], [(_, Error::NoStreams(NoStreamsReason::AllSabrOnly))]),
+            "{:?}", failures,
+        ),
+        err => panic!("expected Error::AllClientsFailed, got {:?}", err),
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn all_drm() {
+    let err = fetch_err(drm_protected()).await;
+    assert_eq!(err.no_streams_reason(), Some(NoStreamsReason::AllDrm));
+}
+
+#[test]
+fn no_matching_stream() {
+    let video = video_from_fixture("multi_audio_player_response.json");
+    assert_eq!(video.no_streams_reason(), NoStreamsReason::NoMatchingStream);
+
+    let (video_info, _) = video.into_parts();
+    let video = Video::from_parts(video_info, Vec::new());
+    assert_eq!(video.no_streams_reason(), NoStreamsReason::EmptyStreamingData);
+}
+
+#[test]
+fn message() {
+    let err = Error::NoStreams(NoStreamsReason::AllSabrOnly);
+
+    assert_eq!(
+        err.to_string(),
+        "the video contains no downloadable streams: all streams are only available via server ABR streaming (SABR)",
+    );
+    assert!(NoStreamsReason::AllSabrOnly.advice().contains("innertube client"));
+    assert_eq!(Error::BadIdFormat.no_streams_reason(), None);
+}
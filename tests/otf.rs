@@ -0,0 +1,130 @@
+#![cfg(feature = "download")]
+
+use tokio::io::AsyncReadExt;
+
+use common::*;
+use rustube::{Error, Stream, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+/// The first audio stream of the multi audio fixture as an OTF stream, pointing to
+/// `/videoplayback?itag=140` on `server`.
+fn stream(server: &MockServer) -> Stream {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    for format in player_response["streamingData"]["adaptiveFormats"].as_array_mut().unwrap() {
+        format["type"] = "FORMAT_STREAM_TYPE_OTF".into();
+        format.as_object_mut().unwrap().remove("contentLength");
+    }
+    let stream = with_streams_at(video_from_player_response(player_response), server, "videoplayback?itag=140")
+        .streams()
+        .iter()
+        .find(|stream| stream.itag == 140)
+        .unwrap()
+        .clone();
+    assert!(stream.is_otf);
+    stream
+}
+
+/// A server, that serves three segments, and answers the request without a sequence number with
+/// a `404`. The 0th segment contains the header `name: value`.
+async fn server(name: &'static str, value: &'static str) -> MockServer {
+    MockServer::start(move |request| match request.path.rsplit_once("sq=") {
+        None => MockResponse::status(404),
+        Some((_, "0")) => MockResponse::ok("header,").with_header(name, value),
+        Some((_, sq)) => MockResponse::ok(format!("segment {},", sq)),
+    }).await
+}
+
+fn paths(server: &MockServer) -> Vec<String> {
+    server.requests().into_iter().map(|request| request.path).collect()
+}
+
+fn path(name: &str) -> std::path::PathBuf {
+    temp_dir(name).join("video.mp4")
+}
+
+#[test_log::test(tokio::test)]
+async fn downloads_otf_streams_in_segments() {
+    let server = server("Segment-Count", "3").await;
+    let path = path("segments");
+
+    stream(&server).download_to(&path).await.unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "header,segment 1,segment 2,");
+    // the stream is not requested as a whole first
+    assert_eq!(paths(&server), [
+        "/videoplayback?itag=140&sq=0",
+        "/videoplayback?itag=140&sq=1",
+        "/videoplayback?itag=140&sq=2",
+    ]);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test_log::test(tokio::test)]
+async fn opens_otf_streams_in_segments() {
+    let server = server("Segment-Count", "2").await;
+
+    let mut reader = stream(&server).open_reader().await.unwrap();
+    let mut read = String::new();
+    reader.read_to_string(&mut read).await.unwrap();
+
+    assert_eq!(read, "header,segment 1,");
+    assert_eq!(paths(&server)[0], "/videoplayback?itag=140&sq=0");
+}
+
+#[test_log::test(tokio::test)]
+async fn segment_count_headers() {
+    // `X-Head-Seqnum` is the sequence number of the last segment
+    let server = server("X-Head-Seqnum", "2").await;
+    let path = path("head_seqnum");
+    stream(&server).download_to(&path).await.unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "header,segment 1,segment 2,");
+    let _ = std::fs::remove_file(&path);
+
+    for (name, value) in [("Segment-Count", "three"), ("X-Head-Seqnum", "-1"), ("Content-Type", "video/mp4")] {
+        let server = self::server(name, value).await;
+        let result = stream(&server).download_to(&path).await;
+        assert!(matches!(result, Err(Error::UnexpectedResponse(_))), "{}: {}", name, value);
+        assert!(!path.exists());
+    }
+}
+
+#[cfg(feature = "callback")]
+#[test_log::test(tokio::test)]
+async fn progress_includes_all_segments() {
+    use std::sync::{Arc, Mutex};
+
+    use rustube::Callback;
+
+    let server = server("Segment-Count", "3").await;
+    let path = path("progress");
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let callback = {
+        let progress = Arc::clone(&progress);
+        Callback::new().connect_on_progress_closure(move |args| progress.lock().unwrap().push(args.current_chunk))
+    };
+
+    stream(&server).download_to_with_callback(&path, callback).await.unwrap();
+
+    let len = "header,segment 1,segment 2,".len();
+    assert_eq!(*progress.lock().unwrap(), ["header,".len(), "header,segment 1,".len(), len]);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+#[ignore]
+async fn download_otf() {
+    let id = random_id(OTF);
+    let expected_path = download_path_from_id(id.as_borrowed()).await;
+
+    let video = video!(id);
+    let stream = video
+        .streams()
+        .iter()
+        .find(|stream| stream.is_otf)
+        .expect("the video has no OTF streams");
+    stream.download_to(&expected_path).await.unwrap();
+
+    assert!(std::fs::metadata(&expected_path).unwrap().len() > 0);
+}
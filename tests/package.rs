@@ -0,0 +1,303 @@
+#![cfg(feature = "package")]
+
+use std::ffi::OsString;
+use std::time::Duration;
+
+use common::*;
+use rustube::{Error, Id, Video};
+use rustube::chapters::Chapter;
+use rustube::package::{Container, ffmetadata, MuxJob, PackageOptions, probed_stream_count, SubtitleInput};
+
+#[macro_use]
+mod common;
+
+const LEN: usize = 4000;
+
+fn args(args: &[&str]) -> Vec<OsString> {
+    args.iter().map(OsString::from).collect()
+}
+
+fn job(container: Container) -> MuxJob {
+    MuxJob {
+        video: "work/video.webm".into(),
+        audio: "work/audio.webm".into(),
+        subtitles: Vec::new(),
+        chapters: None,
+        title: None,
+        container,
+        output: "work/package.mp4".into(),
+    }
+}
+
+#[test]
+fn mux_args() {
+    assert_eq!(job(Container::Mp4).args(), args(&[
+        "-hide_banner", "-loglevel", "error", "-y",
+        "-i", "work/video.webm", "-i", "work/audio.webm",
+        "-map", "0:v:0", "-map", "1:a:0",
+        "-c:v", "copy", "-c:a", "copy",
+        "-f", "mp4", "work/package.mp4",
+    ]));
+    assert_eq!(job(Container::Mp4).track_count(), 2);
+}
+
+#[test]
+fn mux_args_with_subtitles_and_chapters() {
+    let job = MuxJob {
+        subtitles: vec![
+            SubtitleInput { path: "work/subtitles_0.srt".into(), language: "en".to_owned() },
+            SubtitleInput { path: "work/subtitles_1.srt".into(), language: "pt-BR".to_owned() },
+        ],
+        chapters: Some("work/chapters.txt".into()),
+        title: Some("A = B".to_owned()),
+        output: "work/package.mkv".into(),
+        ..job(Container::Mkv)
+    };
+
+    assert_eq!(job.args(), args(&[
+        "-hide_banner", "-loglevel", "error", "-y",
+        "-i", "work/video.webm", "-i", "work/audio.webm",
+        "-i", "work/subtitles_0.srt", "-i", "work/subtitles_1.srt",
+        "-f", "ffmetadata", "-i", "work/chapters.txt",
+        "-map", "0:v:0", "-map", "1:a:0", "-map", "2:s:0", "-map", "3:s:0",
+        "-map_chapters", "4",
+        "-c:v", "copy", "-c:a", "copy", "-c:s", "srt",
+        "-metadata:s:s:0", "language=en", "-metadata:s:s:1", "language=pt-BR",
+        "-metadata", "title=A = B",
+        "-f", "matroska", "work/package.mkv",
+    ]));
+    assert_eq!(job.track_count(), 4);
+
+    // mp4 files store subtitles as mov_text
+    let mp4 = MuxJob { container: Container::Mp4, ..job }.args();
+    let codec = mp4.iter().position(|arg| arg == "-c:s").unwrap();
+    assert_eq!(mp4[codec + 1], "mov_text");
+}
+
+#[test]
+fn ffmetadata_chapters() {
+    let chapters = [
+        Chapter { start: Duration::ZERO, title: "Intro".to_owned() },
+        Chapter { start: Duration::from_millis(90_500), title: "A=B; #1 \\ done".to_owned() },
+    ];
+
+    assert_eq!(ffmetadata(&chapters, Duration::from_secs(615)), "\
+;FFMETADATA1
+
+[CHAPTER]
+TIMEBASE=1/1000
+START=0
+END=90500
+title=Intro
+
+[CHAPTER]
+TIMEBASE=1/1000
+START=90500
+END=615000
+title=A\\=B\\; \\#1 \\\\ done
+");
+    assert_eq!(ffmetadata(&[], Duration::from_secs(615)), ";FFMETADATA1\n");
+}
+
+#[test]
+fn probe_output() {
+    let output = "\
+Input #0, matroska,webm, from 'package.mkv':
+  Metadata:
+    title           : Stream #0:9 in a title
+  Duration: 00:10:15.00, start: 0.000000, bitrate: 2338 kb/s
+  Stream #0:0: Video: vp9 (Profile 0), yuv420p(tv, bt709), 1920x1080, SAR 1:1 DAR 16:9, 25 fps
+  Stream #0:1(eng): Audio: opus, 48000 Hz, stereo, fltp (default)
+  Stream #0:2(eng): Subtitle: subrip
+At least one output file must be specified";
+
+    assert_eq!(probed_stream_count(output), 3);
+    assert_eq!(probed_stream_count("package.mkv: No such file or directory"), 0);
+}
+
+/// Serves the body of all streams at `/stream`, and the captions at `/api/timedtext`.
+async fn server() -> MockServer {
+    MockServer::start(|request| {
+        let path = request.path.as_str();
+        if path.starts_with("/stream") {
+            MockResponse::ok(vec![7; LEN])
+        } else if path.starts_with("/api/timedtext") {
+            MockResponse::ok(read_fixture("captions.xml"))
+        } else {
+            MockResponse::status(404)
+        }
+    }).await
+}
+
+/// The multi audio video, with the captions of the captions fixture, chapters in its description,
+/// and all streams and captions pointing to `server`.
+fn video(server: &MockServer) -> Video {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    let captions = read_fixture("captions.json").replace("https://www.youtube.com/", server.url.as_str());
+    player_response["captions"] = serde_json::from_str(&captions).unwrap();
+    player_response["videoDetails"]["shortDescription"] = "0:00 Intro\n1:00 Middle\n9:00 Outro".into();
+    for format in player_response["streamingData"]["adaptiveFormats"].as_array_mut().unwrap() {
+        format["contentLength"] = LEN.to_string().into();
+    }
+
+    with_streams_at(video_from_player_response(player_response), server, "stream")
+}
+
+/// Tests with a fake `ffmpeg` shell script.
+#[cfg(unix)]
+mod fake_ffmpeg {
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+
+    use rustube::package::{PackagePhase, PackageProgress};
+
+    use super::*;
+
+    /// A fake `ffmpeg`, that writes its arguments to the output file, and reports `streams` streams,
+    /// when it's probing a file.
+    fn fake_ffmpeg(dir: &Path, streams: usize) -> PathBuf {
+        let path = dir.join("ffmpeg");
+        let probe: String = (0..streams).map(|i| format!("  Stream #0:{}: Video\\n", i)).collect();
+        std::fs::write(&path, format!(
+            "#!/bin/sh\n\
+            if [ \"$#\" -eq 3 ]; then printf '{}' >&2; exit 1; fi\n\
+            for last; do :; done\n\
+            echo \"$@\" > \"$last\"\n",
+            probe,
+        )).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn phases(progress: &Mutex<Vec<PackageProgress>>) -> Vec<PackagePhase> {
+        let mut phases: Vec<_> = progress.lock().unwrap().iter().map(|progress| progress.phase).collect();
+        phases.dedup();
+        phases
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn packages_all_tracks() {
+        let server = server().await;
+        let dir = temp_dir("all_tracks");
+        let path = dir.join("video.mkv");
+        let progress = Arc::new(Mutex::new(Vec::new()));
+
+        let options = PackageOptions::new()
+            .subtitle_langs(["pt-BR", "en"])
+            .ffmpeg(fake_ffmpeg(&dir, 4))
+            .on_progress({
+                let progress = Arc::clone(&progress);
+                move |p| progress.lock().unwrap().push(p)
+            });
+        video(&server).download_package_to(&path, options).await.unwrap();
+
+        // the fake ffmpeg wrote its arguments to the package
+        let args = std::fs::read_to_string(&path).unwrap();
+        assert!(args.contains("-map 2:s:0 -map 3:s:0 -map_chapters 4"), "{}", args);
+        assert!(args.contains("-c:s srt -metadata:s:s:0 language=pt-BR -metadata:s:s:1 language=en"), "{}", args);
+        assert!(args.contains("title=Multi-language audio sample -f matroska"), "{}", args);
+        assert!(!Path::new(&format!("{}.package", path.display())).exists());
+
+        assert_eq!(phases(&progress), [PackagePhase::Video, PackagePhase::Audio, PackagePhase::Subtitles, PackagePhase::Muxing]);
+        let progress = progress.lock().unwrap();
+        let video = progress.iter().rfind(|p| p.phase == PackagePhase::Video).unwrap();
+        assert_eq!(video.total, Some(LEN as u64));
+        assert!(progress.contains(&PackageProgress { phase: PackagePhase::Subtitles, current: 2, total: Some(2) }));
+        assert_eq!(progress.last(), Some(&PackageProgress { phase: PackagePhase::Muxing, current: 1, total: Some(1) }));
+
+        // the requests: the video, the audio, and the captions
+        let paths: Vec<_> = server.requests().into_iter().map(|request| request.path).collect();
+        assert_eq!(paths.len(), 4, "{:?}", paths);
+        assert!(paths[2].contains("lang=pt-BR") && paths[3].contains("lang=en") && !paths[3].contains("kind=asr"), "{:?}", paths);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn missing_tracks_fail() {
+        let server = server().await;
+        let dir = temp_dir("missing_tracks");
+        let path = dir.join("video.mp4");
+
+        let options = PackageOptions::new()
+            .container(Container::Mp4)
+            .embed_chapters(false)
+            .ffmpeg(fake_ffmpeg(&dir, 1));
+        let err = video(&server).download_package_to(&path, options).await.unwrap_err();
+
+        assert!(
+            matches!(err, Error::Package(ref reason) if reason == "the package should contain 2 tracks, but ffmpeg found 1"),
+            "{:?}", err,
+        );
+        assert!(!path.exists());
+        assert!(!dir.join("video.mp4.package").exists());
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn cleans_up_when_ffmpeg_is_missing() {
+    let server = server().await;
+    let dir = temp_dir("no_ffmpeg");
+    let path = dir.join("video.mkv");
+
+    let options = PackageOptions::new().ffmpeg(dir.join("no_ffmpeg"));
+    let err = video(&server).download_package_to(&path, options).await.unwrap_err();
+
+    assert!(matches!(err, Error::Package(ref reason) if reason.starts_with("failed to run")), "{:?}", err);
+    // both streams were downloaded before ffmpeg was started
+    assert_eq!(server.requests().len(), 2);
+    assert!(!path.exists());
+    assert!(!dir.join("video.mkv.package").exists());
+}
+
+#[test_log::test(tokio::test)]
+async fn unavailable_selection() {
+    let server = server().await;
+    let video = video(&server);
+    let path = temp_dir("selection").join("video.mkv");
+
+    let err = video.download_package_to(&path, PackageOptions::new().subtitle_langs(["fr"])).await.unwrap_err();
+    assert!(matches!(err, Error::Package(ref reason) if reason == "the video has no captions in `fr`"), "{:?}", err);
+
+    let err = video.download_package_to(&path, PackageOptions::new().audio_language("ja")).await.unwrap_err();
+    assert!(matches!(err, Error::Package(ref reason) if reason == "the video has no audio stream in `ja`"), "{:?}", err);
+
+    // nothing was downloaded
+    assert!(server.requests().is_empty());
+}
+
+#[test_log::test(tokio::test)]
+async fn strict_duration() {
+    let server = server().await;
+    let (video_info, mut streams) = video(&server).into_parts();
+    let truncated = streams.iter_mut().find(|stream| stream.itag == 137).unwrap();
+    truncated.approx_duration_ms = Some(600_000);
+    let video = Video::from_parts(video_info, streams);
+    let path = temp_dir("strict_duration").join("video.mkv");
+
+    let options = PackageOptions::new().strict_duration(true);
+    let err = video.download_package_to(&path, options).await.unwrap_err();
+    assert!(matches!(err, Error::DurationMismatch { itag: 137, got_ms: 600_000, .. }), "{:?}", err);
+
+    // a larger tolerance accepts the stream
+    let options = PackageOptions::new()
+        .strict_duration(true)
+        .duration_tolerance(Duration::from_secs(20))
+        .ffmpeg(path.with_file_name("no_ffmpeg"));
+    let err = video.download_package_to(&path, options).await.unwrap_err();
+    assert!(matches!(err, Error::Package(_)), "{:?}", err);
+
+    // only the second attempt downloaded the streams
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test_log::test(tokio::test)]
+#[ignore]
+async fn download_package() {
+    let id = Id::from_str("5jlI4uzZGjU").unwrap().into_owned();
+    let video = Video::from_id(id).await.unwrap();
+    let path = temp_dir("e2e").join("video.mkv");
+
+    let options = PackageOptions::new().subtitle_langs(["en"]);
+    video.download_package_to(&path, options).await.unwrap();
+    assert!(path.metadata().unwrap().len() > 0);
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "descramble")]
+
+use rustube::{Error, Id, ParseMode, VideoCodec, VideoFetcher};
+use rustube::fetcher::{SelfCheckStep, StepStatus};
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const UNKNOWN_CODEC: &str = "vvc1.1.L123";
+
+/// The multi audio fixture, in which the codec of itag 137 is replaced with one, `rustube` does
+/// not know.
+fn unknown_codec_player_response() -> serde_json::Value {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    let format = player_response["streamingData"]["adaptiveFormats"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|format| format["itag"] == 137)
+        .unwrap();
+    format["mimeType"] = format!("video/mp4; codecs=\"{}\"", UNKNOWN_CODEC).into();
+    player_response
+}
+
+async fn server(player_response: serde_json::Value) -> MockServer {
+    mock_youtube(player_response.clone(), read_fixture("base.js"), move |_| player_response.clone()).await
+}
+
+fn fetcher(server: &MockServer, parse_mode: ParseMode) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap().into_owned())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_parse_mode(parse_mode)
+}
+
+fn assert_unknown_codec(err: Error) {
+    match err {
+        Error::UnknownVariant { type_name, value } => {
+            assert_eq!(type_name, "VideoCodec");
+            assert_eq!(value, UNKNOWN_CODEC);
+        }
+        err => panic!("expected Error::UnknownVariant, got {:?}", err),
+    }
+}
+
+#[test]
+fn lenient_by_default() {
+    let fetcher = VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap().into_owned()).unwrap();
+    assert_eq!(fetcher.parse_mode(), ParseMode::Lenient);
+    assert!(!fetcher.parse_mode().is_strict());
+    assert!(fetcher.with_parse_mode(ParseMode::Strict).parse_mode().is_strict());
+}
+
+#[test_log::test(tokio::test)]
+async fn lenient_keeps_unknown_codecs() {
+    let server = server(unknown_codec_player_response()).await;
+
+    let video = fetcher(&server, ParseMode::Lenient)
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap();
+
+    let stream = video.streams().iter().find(|stream| stream.itag == 137).unwrap();
+    assert_eq!(stream.video_codec(), Some(VideoCodec::Other(UNKNOWN_CODEC.to_owned())));
+}
+
+#[test_log::test(tokio::test)]
+async fn strict_fails_on_unknown_codecs() {
+    let server = server(unknown_codec_player_response()).await;
+
+    assert_unknown_codec(fetcher(&server, ParseMode::Strict).fetch().await.unwrap_err());
+    assert_unknown_codec(fetcher(&server, ParseMode::Strict).fetch_info().await.unwrap_err());
+}
+
+#[test_log::test(tokio::test)]
+async fn strict_accepts_known_codecs() {
+    let server = server(json_fixture("multi_audio_player_response.json")).await;
+
+    let video = fetcher(&server, ParseMode::Strict)
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap();
+    assert!(!video.streams().is_empty());
+}
+
+#[test_log::test(tokio::test)]
+async fn strict_self_check() {
+    let server = server(unknown_codec_player_response()).await;
+
+    let report = fetcher(&server, ParseMode::Lenient).self_check().await;
+    assert!(report.is_ok(), "{:#?}", report);
+
+    let report = fetcher(&server, ParseMode::Strict).self_check().await;
+    let failures: Vec<SelfCheckStep> = report.failures().map(|step| step.step).collect();
+    assert_eq!(failures, [SelfCheckStep::PlayerConfig, SelfCheckStep::PlayerEndpoint]);
+    // the player JavaScript is still checked
+    assert_eq!(report.step(SelfCheckStep::Cipher).unwrap().status, StepStatus::Passed);
+}
+
+#[test_log::test(tokio::test)]
+async fn strict_fails_on_unknown_quality_labels() {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    player_response["streamingData"]["adaptiveFormats"][0]["qualityLabel"] = "4320p120".into();
+    let server = server(player_response).await;
+
+    let video = fetcher(&server, ParseMode::Lenient)
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap();
+    assert!(video.streams().iter().any(|stream| stream.quality_label.as_ref().is_some_and(|label| !label.is_known())));
+
+    match fetcher(&server, ParseMode::Strict).fetch().await.unwrap_err() {
+        Error::UnknownVariant { type_name, value } => {
+            assert_eq!(type_name, "QualityLabel");
+            assert_eq!(value, "4320p120");
+        }
+        err => panic!("expected Error::UnknownVariant, got {:?}", err),
+    }
+}
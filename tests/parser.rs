@@ -0,0 +1,95 @@
+#![cfg(feature = "fetch")]
+
+use proptest::prelude::*;
+use serde_json::{Map, Value};
+
+use rustube::parser::extract_json_object;
+
+fn extract(text: &str) -> Option<&str> {
+    extract_json_object(text).map(|range| &text[range])
+}
+
+#[test]
+fn extracts_first_object() {
+    assert_eq!(extract(r#"a = {"b": {"c": [1, {}]}}; d = {}"#), Some(r#"{"b": {"c": [1, {}]}}"#));
+    assert_eq!(extract("{}"), Some("{}"));
+    assert_eq!(extract("[1, 2] {}"), Some("{}"));
+}
+
+#[test]
+fn skips_delimiters_in_strings() {
+    assert_eq!(extract(r#"{"a": "}]{["}x"#), Some(r#"{"a": "}]{["}"#));
+    assert_eq!(extract(r#"{"a": "\"}"}x"#), Some(r#"{"a": "\"}"}"#));
+    // an escaped backslash doesn't escape the closing quote
+    assert_eq!(extract(r#"{"a": "\\"}x"#), Some(r#"{"a": "\\"}"#));
+    assert_eq!(extract(r#"{"a": "\\\"}"}x"#), Some(r#"{"a": "\\\"}"}"#));
+    assert_eq!(extract(r#"{"ä": "€}"}x"#), Some(r#"{"ä": "€}"}"#));
+}
+
+#[test]
+fn returns_byte_offsets() {
+    let text = r#"€ = {"a": 1};"#;
+    assert_eq!(extract_json_object(text), Some(6..14));
+}
+
+#[test]
+fn rejects_incomplete_objects() {
+    assert_eq!(extract(""), None);
+    assert_eq!(extract("no object"), None);
+    assert_eq!(extract(r#"{"a": 1"#), None);
+    assert_eq!(extract(r#"{"a": "}"#), None);
+    assert_eq!(extract(r#"{"a": "\"#), None);
+    assert_eq!(extract(r#"{"a": [1}"#), None);
+    assert_eq!(extract(r#"{"a": 1]}"#), None);
+}
+
+/// Arbitrary JSON values, with strings, that contain delimiters, escapes and non-ASCII chars.
+fn json_value() -> impl Strategy<Value=Value> {
+    let string = r#"[a-z{}\[\]"\\/\n\t\u{0}-\u{1f}äöü€😀 ]{0,12}"#;
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        string.prop_map(Value::String),
+    ];
+
+    leaf.prop_recursive(4, 64, 8, move |inner| prop_oneof![
+        prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+        prop::collection::vec((string, inner), 0..8)
+            .prop_map(|entries| Value::Object(entries.into_iter().collect::<Map<_, _>>())),
+    ])
+}
+
+fn json_object() -> impl Strategy<Value=Value> {
+    prop::collection::vec((r#"[a-z{}"\\]{0,8}"#, json_value()), 0..8)
+        .prop_map(|entries| Value::Object(entries.into_iter().collect()))
+}
+
+proptest! {
+    #[test]
+    fn extracts_embedded_objects(
+        object in json_object(),
+        pretty in any::<bool>(),
+        prefix in r"[^{]{0,32}",
+        suffix in r".{0,32}",
+    ) {
+        let json = match pretty {
+            true => serde_json::to_string_pretty(&object).unwrap(),
+            false => serde_json::to_string(&object).unwrap(),
+        };
+        let text = format!("{}{}{}", prefix, json, suffix);
+
+        let range = extract_json_object(&text).unwrap();
+        prop_assert_eq!(range.clone(), prefix.len()..prefix.len() + json.len());
+        prop_assert_eq!(serde_json::from_str::<Value>(&text[range]).unwrap(), object);
+    }
+
+    #[test]
+    fn never_panics(text in r"(?s).{0,256}") {
+        if let Some(range) = extract_json_object(&text) {
+            prop_assert!(text.is_char_boundary(range.start) && text.is_char_boundary(range.end));
+            let object = &text[range];
+            prop_assert!(object.starts_with('{') && object.ends_with('}'), "{:?}", object);
+        }
+    }
+}
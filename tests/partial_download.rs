@@ -0,0 +1,142 @@
+#![cfg(feature = "download")]
+
+use std::path::{Path, PathBuf};
+
+use common::*;
+use rustube::{SimpleDownloadOptions, Video};
+
+#[macro_use]
+mod common;
+
+/// The multi audio fixture, with all streams pointing to `server`.
+fn video(server: &MockServer) -> Video {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    set_content_length(&mut player_response, stream_body().len());
+    with_streams_at(video_from_player_response(player_response), server, "stream")
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut part_path = path.as_os_str().to_owned();
+    part_path.push(".part");
+    PathBuf::from(part_path)
+}
+
+#[test_log::test(tokio::test)]
+async fn renames_complete_download() {
+    let server = MockServer::start(|_| MockResponse::ok(stream_body())).await;
+    let path = temp_dir("complete").join("video.mp4");
+
+    let video = video(&server);
+    video.streams()[0].download_to(&path).await.unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert!(!part_path(&path).exists());
+}
+
+#[test_log::test(tokio::test)]
+async fn replaces_existing_file() {
+    let server = MockServer::start(|_| MockResponse::ok(stream_body())).await;
+    let dir = temp_dir("replace");
+    let path = dir.join("video.mp4");
+    std::fs::write(&path, b"an older download").unwrap();
+
+    let video = video(&server);
+    let downloaded = video.streams()[0].download_to_dir(&dir).await.unwrap();
+    assert_eq!(downloaded, dir.join("fDzQ3kUg8Ss.mp4"));
+    video.streams()[0].download_to(&path).await.unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(std::fs::read(&downloaded).unwrap(), stream_body());
+    assert!(!part_path(&path).exists());
+}
+
+#[test_log::test(tokio::test)]
+async fn aborted_download_leaves_no_file() {
+    let server = MockServer::start(|_| MockResponse::ok(stream_body()).abort_after(1000)).await;
+    let dir = temp_dir("aborted");
+    let path = dir.join("video.mp4");
+
+    let video = video(&server);
+    assert!(video.streams()[0].download_to(&path).await.is_err());
+
+    assert!(!path.exists());
+    assert!(!part_path(&path).exists());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+}
+
+#[test_log::test(tokio::test)]
+async fn aborted_download_keeps_existing_file() {
+    let server = MockServer::start(|_| MockResponse::ok(stream_body()).abort_after(1000)).await;
+    let path = temp_dir("aborted_existing").join("video.mp4");
+    std::fs::write(&path, b"an older download").unwrap();
+
+    let video = video(&server);
+    assert!(video.streams()[0].download_to(&path).await.is_err());
+
+    assert_eq!(std::fs::read(&path).unwrap(), b"an older download");
+    assert!(!part_path(&path).exists());
+}
+
+#[test_log::test(tokio::test)]
+async fn truncated_download_is_an_error() {
+    // the response is complete, but shorter than the content length of the stream
+    let server = MockServer::start(|_| MockResponse::ok(&stream_body()[..1000])).await;
+    let path = temp_dir("truncated").join("video.mp4");
+
+    let video = video(&server);
+    let err = video.streams()[0].download_to(&path).await.unwrap_err();
+
+    assert!(err.to_string().contains("1000 of 4096 bytes"), "{}", err);
+    assert!(!path.exists());
+    assert!(!part_path(&path).exists());
+}
+
+#[test_log::test(tokio::test)]
+async fn keep_partial() {
+    let server = MockServer::start(|_| {
+        MockResponse::ok(stream_body())
+            .slow(500, std::time::Duration::from_millis(10))
+            .abort_after(1000)
+    }).await;
+    let dir = temp_dir("keep_partial");
+
+    let video = video(&server);
+    let options = SimpleDownloadOptions::new()
+        .target_dir(&dir)
+        .filename("video.mp4")
+        .keep_partial(true);
+    assert!(video.download_with(options).await.is_err());
+
+    let path = dir.join("video.mp4");
+    assert!(!path.exists());
+    assert_eq!(std::fs::read(part_path(&path)).unwrap(), &stream_body()[..1000]);
+}
+
+#[cfg(feature = "callback")]
+#[test_log::test(tokio::test)]
+async fn on_complete_gets_final_path() {
+    use std::sync::{Arc, Mutex};
+
+    use rustube::Callback;
+
+    let server = MockServer::start(|_| MockResponse::ok(stream_body())).await;
+    let dir = temp_dir("on_complete");
+
+    let completed = Arc::new(Mutex::new(None));
+    let callback = {
+        let completed = Arc::clone(&completed);
+        Callback::new()
+            .connect_on_progress_closure(|_| {})
+            .connect_on_complete_closure(move |path| *completed.lock().unwrap() = Some(path))
+    };
+
+    let video = video(&server);
+    let options = SimpleDownloadOptions::new()
+        .target_dir(&dir)
+        .on_progress(callback);
+    let path = video.download_with(options).await.unwrap();
+
+    assert_eq!(path, dir.join("fDzQ3kUg8Ss.mp4"));
+    assert_eq!(*completed.lock().unwrap(), Some(Some(path.clone())));
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+}
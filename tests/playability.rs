@@ -0,0 +1,105 @@
+#![cfg(feature = "fetch")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{Id, InnertubeClient, VideoFetcher};
+use rustube::video_info::player_response::playability_status::PlayabilityKind;
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+
+fn fetcher(server: &MockServer) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+}
+
+/// The client names of all requests to the player endpoint.
+fn player_requests(server: &MockServer) -> Vec<String> {
+    server
+        .requests()
+        .iter()
+        .filter(|request| request.path.starts_with("/youtubei/v1/player"))
+        .map(|request| request.json()["context"]["client"]["clientName"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+/// A server, that returns `player_response` both in the watch page, and from the player endpoint.
+async fn server(player_response: Value) -> MockServer {
+    let api_player_response = player_response.clone();
+    mock_youtube(player_response, BASE_JS, move |_| api_player_response.clone()).await
+}
+
+#[test_log::test(tokio::test)]
+async fn fetch_info_of_private_video() {
+    let server = server(json_fixture("private_player_response.json")).await;
+
+    let video_info = fetcher(&server).fetch_info().await.unwrap();
+
+    assert!(!video_info.is_playable());
+    assert_eq!(video_info.playability().kind(), PlayabilityKind::LoginRequired);
+    assert_eq!(video_info.playability().reason().as_deref(), Some("Private video"));
+    assert_eq!(video_info.player_response.video_details.video_id.as_str(), VIDEO_ID);
+    assert!(video_info.player_response.streaming_data.is_none());
+    assert_eq!(player_requests(&server), ["WEB"]);
+}
+
+#[test_log::test(tokio::test)]
+async fn fetch_info_of_deleted_video() {
+    let server = server(json_fixture("deleted_player_response.json")).await;
+
+    let video_info = fetcher(&server)
+        .with_innertube_clients(vec![InnertubeClient::Android, InnertubeClient::Web])
+        .fetch_info()
+        .await
+        .unwrap();
+
+    assert!(!video_info.is_playable());
+    assert_eq!(video_info.playability().kind(), PlayabilityKind::Error);
+    assert_eq!(video_info.playability().reason().as_deref(), Some("This video isn't available anymore"));
+    assert_eq!(video_info.player_response.tracking_params, "CAAQu2kiEwiR9b_Yr5aJAxVN1REIHbcUA6Y=");
+    assert_eq!(player_requests(&server), ["ANDROID"]);
+}
+
+#[test_log::test(tokio::test)]
+async fn fetch_info_of_playable_video() {
+    let server = server(json_fixture("multi_audio_player_response.json")).await;
+
+    let video_info = fetcher(&server).fetch_info().await.unwrap();
+
+    assert!(video_info.is_playable());
+    assert_eq!(video_info.playability().kind(), PlayabilityKind::Ok);
+    assert_eq!(video_info.player_response.video_details.title, "Multi-language audio sample");
+    // the watch page contains everything
+    assert!(player_requests(&server).is_empty());
+}
+
+#[test_log::test(tokio::test)]
+async fn fetch_info_fails_without_playability_status() {
+    let server = mock_youtube(json!({}), BASE_JS, |_| json!({ "responseContext": {} })).await;
+
+    assert!(fetcher(&server).fetch_info().await.is_err());
+}
+
+#[test_log::test(tokio::test)]
+async fn fetch_playability() {
+    let cases = [
+        ("private_player_response.json", PlayabilityKind::LoginRequired),
+        ("deleted_player_response.json", PlayabilityKind::Error),
+        ("multi_audio_player_response.json", PlayabilityKind::Ok),
+    ];
+
+    for (fixture, kind) in cases {
+        let server = server(json_fixture(fixture)).await;
+
+        let status = fetcher(&server).fetch_playability().await.unwrap();
+        assert_eq!(status.kind(), kind, "{}", fixture);
+        // a single request, without the watch page
+        assert_eq!(server.requests().len(), 1, "{}", fixture);
+        assert_eq!(player_requests(&server), ["WEB"], "{}", fixture);
+    }
+}
@@ -0,0 +1,161 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::PlayerResponse;
+use rustube::video_info::player_response::player_config::{DynamicReadaheadConfig, gain, Loudness, PlayerConfig};
+
+#[macro_use]
+mod common;
+
+const MULTI_AUDIO: &str = "multi_audio_player_response.json";
+
+fn player_response_with_config(player_config: Value) -> Value {
+    let mut player_response = json_fixture(MULTI_AUDIO);
+    player_response["playerConfig"] = player_config;
+    player_response
+}
+
+#[test]
+fn deserializes_player_config() {
+    let config: PlayerConfig = serde_json::from_value(json_fixture("player_config.json")).unwrap();
+
+    let audio_config = config.audio_config.unwrap();
+    assert_eq!(audio_config.loudness_db, Some(-1.4799995));
+    assert_eq!(audio_config.perceptual_loudness_db, Some(-15.48));
+    assert_eq!(audio_config.enable_per_format_loudness, Some(true));
+    assert_eq!(config.stream_selection_config.unwrap().max_bitrate, Some(8_000_000));
+    assert_eq!(config.media_common_config.unwrap().dynamic_readahead_config, Some(DynamicReadaheadConfig {
+        max_read_ahead_media_time_ms: Some(120000),
+        min_read_ahead_media_time_ms: Some(15000),
+        read_ahead_growth_rate_ms: Some(1000),
+    }));
+}
+
+#[test]
+fn tolerates_partial_configs() {
+    let config: PlayerConfig = serde_json::from_value(json!({})).unwrap();
+    assert_eq!(config, PlayerConfig::default());
+
+    // the bitrate is usually a string, but a number is accepted as well
+    let config: PlayerConfig = serde_json::from_value(json!({
+        "streamSelectionConfig": { "maxBitrate": 2500000 },
+        "mediaCommonConfig": {},
+    })).unwrap();
+    assert_eq!(config.stream_selection_config.unwrap().max_bitrate, Some(2_500_000));
+    assert_eq!(config.media_common_config.unwrap().dynamic_readahead_config, None);
+
+    let config: PlayerConfig = serde_json::from_value(json!({ "streamSelectionConfig": {} })).unwrap();
+    assert_eq!(config.stream_selection_config.unwrap().max_bitrate, None);
+}
+
+#[test]
+fn exposed_on_player_response_and_video() {
+    let player_response = player_response_with_config(json_fixture("player_config.json"));
+    let deserialized: PlayerResponse = serde_json::from_str(&player_response.to_string()).unwrap();
+    assert!(deserialized.player_config.is_some());
+
+    let video = video_from_player_response(player_response);
+    assert_eq!(video.suggested_max_bitrate(), Some(8_000_000));
+    assert_eq!(
+        video.video_info().player_config().and_then(|config| config.audio_config.as_ref()).and_then(|audio| audio.loudness_db),
+        Some(-1.4799995),
+    );
+
+    let video = video_from_fixture(MULTI_AUDIO);
+    assert!(video.video_info().player_config().is_none());
+    assert_eq!(video.suggested_max_bitrate(), None);
+}
+
+#[test]
+fn loudness() {
+    let mut player_response = player_response_with_config(json_fixture("player_config.json"));
+    player_response["streamingData"]["adaptiveFormats"][0]["loudnessDb"] = json!(4.5);
+    let video = video_from_player_response(player_response);
+    let with_loudness = video.streams().iter().find(|stream| stream.loudness_db.is_some()).unwrap();
+    let without_loudness = video.streams().iter().find(|stream| stream.loudness_db.is_none()).unwrap();
+
+    let loudness = video.loudness(with_loudness);
+    assert_eq!(loudness, Loudness {
+        video_loudness_db: Some(-1.4799995),
+        perceptual_loudness_db: Some(-15.48),
+        stream_loudness_db: Some(4.5),
+        per_format: true,
+    });
+    // per format loudness is enabled, so the loudness of the stream applies
+    assert_eq!(loudness.loudness_db(), Some(4.5));
+    assert!((loudness.gain().unwrap() - 0.595662).abs() < 1e-6);
+
+    // without a loudness of the stream, the one of the video applies
+    let loudness = video.loudness(without_loudness);
+    assert_eq!(loudness.loudness_db(), Some(-1.4799995));
+    assert!(loudness.gain().unwrap() > 1.);
+}
+
+#[test]
+fn loudness_without_audio_config() {
+    let mut player_response = player_response_with_config(json!({ "audioConfig": { "loudnessDb": -3.0 } }));
+    player_response["streamingData"]["adaptiveFormats"][0]["loudnessDb"] = json!(4.5);
+    let video = video_from_player_response(player_response);
+    let stream = video.streams().iter().find(|stream| stream.loudness_db.is_some()).unwrap();
+
+    // per format loudness is disabled, unless the config enables it
+    let loudness = video.loudness(stream);
+    assert_eq!((loudness.per_format, loudness.perceptual_loudness_db), (false, None));
+    assert_eq!(loudness.loudness_db(), Some(-3.0));
+
+    let video = video_from_fixture(MULTI_AUDIO);
+    let loudness = video.loudness(&video.streams()[0]);
+    assert_eq!(loudness, Loudness { stream_loudness_db: video.streams()[0].loudness_db, ..Loudness::default() });
+    assert_eq!(video.loudness(&video.streams()[0]).gain(), None);
+}
+
+#[test]
+fn gain_of_loudness() {
+    assert_eq!(gain(0.), 1.);
+    assert!((gain(6.) - 0.501187).abs() < 1e-6);
+    assert!((gain(-6.) - 1.995262).abs() < 1e-6);
+}
+
+#[cfg(feature = "download")]
+#[test_log::test(tokio::test)]
+async fn respect_max_bitrate_skips_streams_above_the_hint() {
+    use rustube::SimpleDownloadOptions;
+
+    let server = MockServer::start(|request| MockResponse::ok(request.path.clone())).await;
+    let format = |itag: u64, label: &str, height: u64, bitrate: u64| json!({
+        "itag": itag,
+        "url": server.url.join(&itag.to_string()).unwrap().as_str(),
+        "mimeType": r#"video/mp4; codecs="avc1.640033, mp4a.40.2""#,
+        "bitrate": bitrate,
+        "width": height * 16 / 9,
+        "height": height,
+        "fps": 30,
+        "quality": "hd1080",
+        "qualityLabel": label,
+        "projectionType": "RECTANGULAR",
+    });
+    let mut player_response = player_response_with_config(json_fixture("player_config.json"));
+    player_response["streamingData"]["formats"] = json!([
+        format(1080, "1080p", 1080, 5_000_000),
+        format(2160, "2160p", 2160, 20_000_000),
+    ]);
+    let video = video_from_player_response(player_response);
+    let dir = std::env::temp_dir().join(format!("rustube_player_config_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let download = video
+        .download_with_summary(SimpleDownloadOptions::new().target_dir(&dir).filename("all"))
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&download.path).unwrap(), "/2160");
+
+    let options = SimpleDownloadOptions::new()
+        .target_dir(&dir)
+        .filename("capped")
+        .respect_max_bitrate(true);
+    let download = video.download_with_summary(options).await.unwrap();
+    assert_eq!(std::fs::read_to_string(&download.path).unwrap(), "/1080");
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "stream")]
+
+use rustube::video_info::player_response::streaming_data::QualityLabel;
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+const PREMIUM: &str = "premium_player_response.json";
+
+#[test]
+fn detects_premium_formats() {
+    let video = video_from_fixture(PREMIUM);
+
+    let premium: Vec<(u64, bool)> = video
+        .streams()
+        .iter()
+        .map(|stream| (stream.itag, stream.is_premium_only()))
+        .collect();
+    assert_eq!(premium, [
+        (18, false),
+        (137, false),
+        (248, false),
+        (616, true),
+        (136, false),
+        (140, false),
+        (140, false),
+        (251, false),
+    ]);
+
+    let stream = &video.streams()[3];
+    assert_eq!(stream.quality_label, Some(QualityLabel::P1080Premium));
+    assert!(QualityLabel::P1080Premium.is_premium());
+    assert!(!QualityLabel::P1080.is_premium());
+}
+
+#[test]
+fn best_video_skips_premium_formats() {
+    let video = video_from_fixture(PREMIUM);
+
+    // both regular 1080p formats have the same resolution and frame rate, so the higher bitrate
+    // of the H.264 format decides (see `StreamRank`)
+    let best = video.best_video().unwrap();
+    assert_eq!(best.itag, 137);
+    assert_eq!(best.quality_label, Some(QualityLabel::P1080));
+    assert!(!best.is_premium_only());
+
+    assert_eq!(video.best_video_including_premium().unwrap().itag, 616);
+    assert_eq!(video.worst_video().unwrap().itag, 136);
+}
+
+#[test]
+fn order_of_premium_formats_does_not_matter() {
+    // the premium format is listed before the regular ones, so its position doesn't decide
+    let mut player_response = json_fixture(PREMIUM);
+    let adaptive_formats = player_response["streamingData"]["adaptiveFormats"].as_array_mut().unwrap();
+    let premium = adaptive_formats.remove(2);
+    adaptive_formats.insert(0, premium);
+    let video = video_from_player_response(player_response);
+
+    assert_eq!(video.best_video().unwrap().itag, 137);
+    assert_eq!(video.best_video_including_premium().unwrap().itag, 616);
+}
+
+#[test]
+fn drc_audio() {
+    let video = video_from_fixture(PREMIUM);
+
+    let drc: Vec<(u64, bool)> = video
+        .streams()
+        .iter()
+        .filter(|stream| stream.includes_audio_track && !stream.includes_video_track)
+        .map(|stream| (stream.itag, stream.is_drc))
+        .collect();
+    assert_eq!(drc, [(140, false), (140, true), (251, false)]);
+}
@@ -0,0 +1,125 @@
+#![cfg(feature = "fetch")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use rustube::{Error, Id, Metrics, VideoFetcher};
+use rustube::fetcher::RATE_LIMIT_BACKOFF;
+use rustube::video_info::player_response::playability_status::PlayabilityStatus;
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+
+fn fetcher(server: &MockServer) -> VideoFetcher {
+    // without a connection pool, so there are no idle timers, that paused tests would skip to
+    let client = reqwest::Client::builder().pool_max_idle_per_host(0).build().unwrap();
+    VideoFetcher::from_id_with_client(Id::from_str(VIDEO_ID).unwrap().into_owned(), client)
+        .with_base_url(server.url.clone())
+}
+
+/// Answers the first `limited` requests with `limited_response`, and all following ones with a
+/// playable player response.
+async fn server_limiting_first(limited: usize, limited_response: MockResponse) -> MockServer {
+    let count = AtomicUsize::new(0);
+    MockServer::start(move |_| match count.fetch_add(1, Ordering::SeqCst) < limited {
+        true => limited_response.clone(),
+        false => MockResponse::json(&json_fixture("multi_audio_player_response.json")),
+    }).await
+}
+
+#[test_log::test(tokio::test)]
+async fn too_many_requests() {
+    let server = MockServer::start(|_| MockResponse::status(429).with_header("Retry-After", "7")).await;
+    let metrics = Arc::new(Metrics::new());
+
+    let err = fetcher(&server).with_metrics(Arc::clone(&metrics)).fetch_playability().await.unwrap_err();
+    match err {
+        Error::RateLimited { retry_after, endpoint } => {
+            assert_eq!(retry_after, Some(Duration::from_secs(7)));
+            assert_eq!(endpoint, "player");
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+    assert_eq!(metrics.rate_limit_hits(), 1);
+    assert_eq!(metrics.report().rate_limit_hits, 1);
+
+    let server = MockServer::start(|_| MockResponse::status(429)).await;
+    let err = fetcher(&server).fetch_info().await.unwrap_err();
+    assert!(
+        matches!(err, Error::RateLimited { retry_after: None, endpoint: "watch" }),
+        "{:?}", err,
+    );
+}
+
+#[test_log::test(tokio::test)]
+async fn retry_after_http_date() {
+    use chrono::TimeZone;
+
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap();
+    let date = chrono::Utc.timestamp_opt(now.as_secs() as i64 + 120, 0).unwrap();
+    let retry_after = date.to_rfc2822();
+    let server = MockServer::start(move |_| MockResponse::status(429).with_header("Retry-After", &retry_after)).await;
+
+    match fetcher(&server).fetch_playability().await.unwrap_err() {
+        Error::RateLimited { retry_after: Some(retry_after), .. } => {
+            assert!(retry_after > Duration::from_secs(100) && retry_after <= Duration::from_secs(120), "{:?}", retry_after);
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn forbidden_with_rate_limit_body() {
+    let server = MockServer::start(|_| {
+        let mut response = MockResponse::status(403);
+        response.body = b"Our systems have detected unusual traffic from your computer network.".to_vec();
+        response
+    }).await;
+    let err = fetcher(&server).fetch_info().await.unwrap_err();
+    assert!(matches!(err, Error::RateLimited { retry_after: None, endpoint: "watch" }), "{:?}", err);
+
+    // other 403s are plain request errors
+    let server = MockServer::start(|_| MockResponse::status(403)).await;
+    let metrics = Arc::new(Metrics::new());
+    let err = fetcher(&server).with_metrics(Arc::clone(&metrics)).fetch_playability().await.unwrap_err();
+    assert!(matches!(err, Error::Request(ref err) if err.status() == Some(reqwest::StatusCode::FORBIDDEN)), "{:?}", err);
+    assert_eq!(metrics.rate_limit_hits(), 0);
+}
+
+#[test_log::test(tokio::test(start_paused = true))]
+async fn retry_waits_for_retry_after() {
+    let server = server_limiting_first(1, MockResponse::status(429).with_header("Retry-After", "30")).await;
+
+    let start = tokio::time::Instant::now();
+    let status = fetcher(&server).with_rate_limit_retries(1).fetch_playability().await.unwrap();
+    let elapsed = start.elapsed();
+
+    assert!(matches!(status, PlayabilityStatus::Ok { .. }), "{:?}", status);
+    assert_eq!(server.requests().len(), 2);
+    assert!(elapsed >= Duration::from_secs(30) && elapsed < Duration::from_secs(31), "{:?}", elapsed);
+}
+
+#[test_log::test(tokio::test(start_paused = true))]
+async fn retry_backs_off_without_retry_after() {
+    let server = server_limiting_first(2, MockResponse::status(429)).await;
+
+    let start = tokio::time::Instant::now();
+    let status = fetcher(&server).with_rate_limit_retries(2).fetch_playability().await.unwrap();
+    let elapsed = start.elapsed();
+
+    assert!(matches!(status, PlayabilityStatus::Ok { .. }), "{:?}", status);
+    assert_eq!(server.requests().len(), 3);
+    let expected = RATE_LIMIT_BACKOFF * 3;
+    assert!(elapsed >= expected && elapsed < expected + Duration::from_secs(1), "{:?}", elapsed);
+
+    // gives up after the last retry
+    let server = server_limiting_first(2, MockResponse::status(429)).await;
+    let err = fetcher(&server).with_rate_limit_retries(1).fetch_playability().await.unwrap_err();
+    assert!(matches!(err, Error::RateLimited { .. }), "{:?}", err);
+    assert_eq!(server.requests().len(), 2);
+}
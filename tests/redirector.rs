@@ -0,0 +1,146 @@
+#![cfg(feature = "download")]
+
+use common::*;
+use rustube::{Error, Stream};
+
+#[macro_use]
+mod common;
+
+const QUERY: &str = "expire=4102444800&sig=abc&n=xyz";
+const MEDIA: [u8; 64] = [7; 64];
+
+/// The itag 140 stream of the multi audio fixture, with its URL pointing to `server`, and without
+/// a content length.
+fn stream(server: &MockServer) -> Stream {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    for format in player_response["streamingData"]["adaptiveFormats"].as_array_mut().unwrap() {
+        format.as_object_mut().unwrap().remove("contentLength");
+    }
+    let (_, streams) = video_from_player_response(player_response).into_parts();
+    let mut stream = streams.into_iter().find(|stream| stream.itag == 140).unwrap();
+    let mut url = server.url.join("videoplayback").unwrap();
+    url.set_query(Some(QUERY));
+    stream.signature_cipher.url = url;
+    stream
+}
+
+/// Whether `request` was sent to the alternate host `localhost`, instead of `127.0.0.1`.
+fn is_alternate(request: &MockRequest) -> bool {
+    request.header("Host").unwrap().starts_with("localhost:")
+}
+
+/// The address of `request`s host with `localhost` as the host name.
+fn alternate_host(request: &MockRequest) -> String {
+    request.header("Host").unwrap().replace("127.0.0.1", "localhost")
+}
+
+/// Serves the media from `localhost`, if the full query was sent.
+fn serve_media(request: &MockRequest) -> MockResponse {
+    match request.path.ends_with(QUERY) {
+        true => MockResponse::ok(MEDIA),
+        false => MockResponse::status(403),
+    }
+}
+
+fn path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("rustube_redirector_{}_{}.m4a", name, std::process::id()))
+}
+
+#[test_log::test(tokio::test)]
+async fn redirect_without_query() {
+    let server = MockServer::start(|request| match is_alternate(request) {
+        true => serve_media(request),
+        // the proxy drops the query of the redirect
+        false => MockResponse::status(302)
+            .with_header("Location", &format!("http://{}/videoplayback", alternate_host(request))),
+    }).await;
+
+    let path = path("302");
+    let summary = stream(&server).download_to_with_summary(&path).await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), MEDIA);
+    assert_eq!(summary.host.as_deref(), Some("localhost"));
+    let _ = std::fs::remove_file(&path);
+
+    let paths: Vec<(bool, String)> = server
+        .requests()
+        .iter()
+        .map(|request| (is_alternate(request), request.path.clone()))
+        .collect();
+    assert_eq!(paths, [
+        (false, format!("/videoplayback?{}", QUERY)),
+        (true, "/videoplayback".to_owned()),
+        (true, format!("/videoplayback?{}", QUERY)),
+    ]);
+}
+
+#[test_log::test(tokio::test)]
+async fn alternate_host_body() {
+    for full_url in [false, true] {
+        let server = MockServer::start(move |request| match is_alternate(request) {
+            true => serve_media(request),
+            false => {
+                let host = alternate_host(request);
+                let body = match full_url {
+                    true => format!("http://{}/videoplayback?other=query\n", host),
+                    false => format!("{}\n", host),
+                };
+                MockResponse::ok(body).with_header("Content-Type", "text/plain; charset=utf-8")
+            }
+        }).await;
+
+        let path = path(&format!("body_{}", full_url));
+        let summary = stream(&server).download_to_with_summary(&path).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), MEDIA);
+        assert_eq!(summary.host.as_deref(), Some("localhost"));
+        let _ = std::fs::remove_file(&path);
+
+        // the original path and query are sent to the alternate host
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(is_alternate(&requests[1]));
+        assert_eq!(requests[1].path, format!("/videoplayback?{}", QUERY));
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn no_redirect() {
+    let server = MockServer::start(serve_media).await;
+
+    let path = path("direct");
+    let summary = stream(&server).download_to_with_summary(&path).await.unwrap();
+    assert_eq!(summary.host.as_deref(), Some("127.0.0.1"));
+    assert_eq!(server.requests().len(), 1);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test_log::test(tokio::test)]
+async fn limits_the_hops() {
+    let server = MockServer::start(|request| {
+        MockResponse::ok(alternate_host(request).replace("localhost", "127.0.0.1"))
+            .with_header("Content-Type", "text/plain")
+    }).await;
+
+    let path = path("loop");
+    let err = stream(&server).download_to(&path).await.unwrap_err();
+    assert!(matches!(err, Error::UnexpectedResponse(_)), "{:?}", err);
+    assert_eq!(server.requests().len(), 4);
+    assert!(!path.exists());
+}
+
+#[test_log::test(tokio::test)]
+async fn forbidden_reports_the_redirected_host() {
+    let server = MockServer::start(|request| match is_alternate(request) {
+        true => MockResponse::status(403),
+        false => MockResponse::status(302)
+            .with_header("Location", &format!("http://{}/videoplayback?{}", alternate_host(request), QUERY)),
+    }).await;
+
+    let path = path("forbidden");
+    match stream(&server).download_to(&path).await.unwrap_err() {
+        Error::Forbidden { host, has_sig, .. } => {
+            assert_eq!(host.as_deref(), Some("localhost"));
+            assert!(has_sig);
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
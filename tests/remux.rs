@@ -0,0 +1,148 @@
+#![cfg(feature = "remux")]
+
+use std::convert::TryInto;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::default::formats::IsoMp4Reader;
+
+use common::*;
+use rustube::postprocess::{remux_fmp4, remux_fmp4_to_mp4};
+use rustube::Error;
+
+#[macro_use]
+mod common;
+
+fn fixture(name: &str) -> Vec<u8> {
+    std::fs::read(Path::new(FIXTURE_DIR).join(name)).unwrap()
+}
+
+fn remux(input: &[u8]) -> rustube::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let written = remux_fmp4(Cursor::new(input), &mut output)?;
+    assert_eq!(written, output.len() as u64);
+    Ok(output)
+}
+
+/// The timestamp, duration, and data of all packets of the single track of `file`.
+fn packets(file: Vec<u8>) -> Vec<(u64, u64, Vec<u8>)> {
+    let source = MediaSourceStream::new(Box::new(Cursor::new(file)), Default::default());
+    let mut reader = IsoMp4Reader::try_new(source, &FormatOptions::default()).unwrap();
+    assert_eq!(reader.tracks().len(), 1);
+
+    let mut packets = Vec::new();
+    while let Ok(packet) = reader.next_packet() {
+        packets.push((packet.ts, packet.dur, packet.data.to_vec()));
+    }
+    packets
+}
+
+/// The payload of the box at `path`, i.e. `["moov", "trak", "mdia"]`.
+fn find_box<'a>(mut data: &'a [u8], path: &[&str]) -> Option<&'a [u8]> {
+    let (kind, rest) = path.split_first()?;
+    while data.len() >= 8 {
+        let size = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+        if &data[4..8] == kind.as_bytes() {
+            return match rest.is_empty() {
+                true => Some(&data[8..size]),
+                false => find_box(&data[8..size], rest),
+            };
+        }
+        data = &data[size..];
+    }
+    None
+}
+
+fn sample_table<'a>(file: &'a [u8], kind: &str) -> Option<&'a [u8]> {
+    find_box(file, &["moov", "trak", "mdia", "minf", "stbl", kind])
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rustube_remux_{}_{}.mp4", name, std::process::id()))
+}
+
+#[test]
+fn audio_golden_file() {
+    let output = remux(&fixture("fragmented_audio.mp4")).unwrap();
+    assert_eq!(output, fixture("remuxed_audio.mp4"));
+}
+
+#[test]
+fn video_golden_file() {
+    let output = remux(&fixture("fragmented_video.mp4")).unwrap();
+    assert_eq!(output, fixture("remuxed_video.mp4"));
+}
+
+#[test]
+fn keeps_all_packets() {
+    for name in ["fragmented_audio.mp4", "fragmented_video.mp4"] {
+        let input = fixture(name);
+        let expected = packets(input.clone());
+        assert_eq!(expected.len(), 4 + 5 + 6, "{}", name);
+
+        let output = remux(&input).unwrap();
+        assert_eq!(packets(output.clone()), expected, "{}", name);
+
+        assert!(find_box(&output, &["moov", "mvex"]).is_none());
+        assert_eq!(find_box(&output, &["moof"]), None);
+        assert_eq!(find_box(&output, &["sidx"]), None);
+    }
+}
+
+#[test]
+fn indexes_samples() {
+    let output = remux(&fixture("fragmented_audio.mp4")).unwrap();
+
+    // the durations of all samples, and the duration of the track in the headers
+    assert_eq!(
+        sample_table(&output, "stts").unwrap(),
+        [0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 3, 0, 0, 4, 0, 0, 0, 0, 1, 0, 0, 2, 0, 0, 0, 0, 10, 0, 0, 4, 0, 0, 0, 0, 1, 0, 0, 6, 0],
+    );
+    let mdhd = find_box(&output, &["moov", "trak", "mdia", "mdhd"]).unwrap();
+    assert_eq!(&mdhd[16..20], 15360_u32.to_be_bytes());
+    let mvhd = find_box(&output, &["moov", "mvhd"]).unwrap();
+    assert_eq!(&mvhd[16..20], (15360 * 1000 / 44100_u32).to_be_bytes());
+
+    // audio samples are all sync samples, and have no composition offsets
+    assert!(sample_table(&output, "stss").is_none());
+    assert!(sample_table(&output, "ctts").is_none());
+
+    let output = remux(&fixture("fragmented_video.mp4")).unwrap();
+    // only the first sample of each fragment is a sync sample
+    assert_eq!(sample_table(&output, "stss").unwrap(), [0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 1, 0, 0, 0, 5, 0, 0, 0, 10]);
+    assert!(sample_table(&output, "ctts").is_some());
+}
+
+#[test]
+fn remux_file_in_place() {
+    let path = temp_path("in_place");
+    std::fs::write(&path, fixture("fragmented_audio.mp4")).unwrap();
+
+    remux_fmp4_to_mp4(&path).unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), fixture("remuxed_audio.mp4"));
+    let mut part_path = path.clone().into_os_string();
+    part_path.push(".part");
+    assert!(!Path::new(&part_path).exists());
+}
+
+#[test]
+fn rejects_unfragmented_files() {
+    let err = remux(&fixture("remuxed_audio.mp4")).unwrap_err();
+    assert!(matches!(err, Error::Remux(_)), "{:?}", err);
+
+    // the file is left untouched
+    let path = temp_path("unfragmented");
+    std::fs::write(&path, fixture("remuxed_audio.mp4")).unwrap();
+    assert!(matches!(remux_fmp4_to_mp4(&path), Err(Error::Remux(_))));
+    assert_eq!(std::fs::read(&path).unwrap(), fixture("remuxed_audio.mp4"));
+}
+
+#[test]
+fn rejects_truncated_files() {
+    let input = fixture("fragmented_audio.mp4");
+    let err = remux(&input[..input.len() - 10]).unwrap_err();
+    assert!(matches!(err, Error::Remux(_)), "{:?}", err);
+}
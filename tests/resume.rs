@@ -0,0 +1,127 @@
+#![cfg(feature = "download")]
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+fn paths(name: &str) -> (PathBuf, PathBuf) {
+    let dir = temp_dir(name);
+    (dir.join("video.mp4"), dir.join("video.mp4.part"))
+}
+
+fn ranges(server: &MockServer) -> Vec<Option<String>> {
+    server
+        .requests()
+        .iter()
+        .map(|request| request.header("Range").map(str::to_owned))
+        .collect()
+}
+
+fn cleanup(path: &Path) {
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test_log::test(tokio::test)]
+async fn resumes_the_part_file() {
+    let server = MockServer::start(|request| MockResponse::ranged(request, &stream_body())).await;
+    let (path, part_path) = paths("resume");
+    std::fs::write(&part_path, &stream_body()[..1000]).unwrap();
+
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+    stream.download_to_resumable(&path).await.unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert!(!part_path.exists());
+    assert_eq!(ranges(&server), [Some("bytes=1000-".to_owned())]);
+    cleanup(&path);
+}
+
+#[test_log::test(tokio::test)]
+async fn interrupted_download_is_resumed() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let server = MockServer::start(move |request| match requests.fetch_add(1, Ordering::SeqCst) {
+        0 => MockResponse::ok(stream_body()).abort_after(1500),
+        _ => MockResponse::ranged(request, &stream_body()),
+    }).await;
+    let (path, part_path) = paths("interrupted");
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+
+    // the part file is kept, if the download fails
+    assert!(stream.download_to_resumable(&path).await.is_err());
+    assert!(!path.exists());
+    assert_eq!(std::fs::read(&part_path).unwrap(), &stream_body()[..1500]);
+
+    stream.download_to_resumable(&path).await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(ranges(&server), [None, Some("bytes=1500-".to_owned())]);
+    cleanup(&path);
+}
+
+#[test_log::test(tokio::test)]
+async fn ignored_range_downloads_everything() {
+    let server = MockServer::start(|_| MockResponse::ok(stream_body())).await;
+    let (path, part_path) = paths("ignored");
+    std::fs::write(&part_path, vec![0; 1000]).unwrap();
+
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+    stream.download_to_resumable(&path).await.unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(ranges(&server), [Some("bytes=1000-".to_owned())]);
+    cleanup(&path);
+}
+
+#[test_log::test(tokio::test)]
+async fn complete_and_oversized_part_files() {
+    let server = MockServer::start(|request| MockResponse::ranged(request, &stream_body())).await;
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+
+    // a complete part file is only renamed
+    let (path, part_path) = paths("complete");
+    std::fs::write(&part_path, stream_body()).unwrap();
+    stream.download_to_resumable(&path).await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert!(server.requests().is_empty());
+    cleanup(&path);
+
+    // a part file, that's larger than the stream, cannot belong to it
+    let (path, part_path) = paths("oversized");
+    std::fs::write(&part_path, vec![0; 5000]).unwrap();
+    stream.download_to_resumable(&path).await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(ranges(&server), [None]);
+    cleanup(&path);
+}
+
+#[cfg(feature = "callback")]
+#[test_log::test(tokio::test)]
+async fn progress_includes_the_resumed_bytes() {
+    use std::sync::Mutex;
+
+    use rustube::Callback;
+
+    let server = MockServer::start(|request| MockResponse::ranged(request, &stream_body())).await;
+    let (path, part_path) = paths("callback");
+    std::fs::write(&part_path, &stream_body()[..1000]).unwrap();
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let callback = {
+        let progress = Arc::clone(&progress);
+        Callback::new()
+            .connect_on_progress_closure(move |args| progress.lock().unwrap().push((args.current_chunk, args.content_length)))
+    };
+    let stream = progressive_stream_at(&server, "stream", Some(stream_body().len()));
+    stream.download_to_resumable_with_callback(&path, callback).await.unwrap();
+
+    let progress = progress.lock().unwrap();
+    assert!(progress.iter().all(|&(current_chunk, content_length)| {
+        current_chunk > 1000 && content_length == Some(stream_body().len() as u64)
+    }), "{:?}", progress);
+    assert_eq!(progress.last().map(|&(current_chunk, _)| current_chunk), Some(stream_body().len()));
+    cleanup(&path);
+}
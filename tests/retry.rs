@@ -0,0 +1,200 @@
+#![cfg(feature = "download")]
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+use url::Url;
+
+use common::*;
+use rustube::{Error, Id, InnertubeClient, RetryPolicy, SimpleDownloadOptions, Stream, Video, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+
+/// An `expire` parameter, that's an hour in the future.
+fn unexpired() -> i64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    now.as_secs() as i64 + 3600
+}
+
+/// The multi audio fixture, with the URLs of all streams pointing to `/stream` on `media`,
+/// expiring at `expire`, and tagged with `gen=<generation>`.
+fn player_response(media: &Url, generation: usize, expire: i64) -> Value {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    for formats in ["formats", "adaptiveFormats"] {
+        for format in player_response["streamingData"][formats].as_array_mut().unwrap() {
+            let original = Url::parse(format["url"].as_str().unwrap()).unwrap();
+            let mut url = media.join("stream").unwrap();
+            url.query_pairs_mut()
+                .extend_pairs(original.query_pairs().filter(|(key, _)| key != "expire"))
+                .append_pair("expire", &expire.to_string())
+                .append_pair("gen", &generation.to_string());
+            format["url"] = url.as_str().into();
+        }
+    }
+    player_response
+}
+
+/// A media server, that answers the first `failures` requests with `status`.
+async fn media(failures: usize, status: u16) -> MockServer {
+    let requests = AtomicUsize::new(0);
+    MockServer::start(move |_| match requests.fetch_add(1, Ordering::SeqCst) < failures {
+        true => MockResponse::status(status),
+        false => MockResponse::ok(stream_body()),
+    }).await
+}
+
+/// A mock YouTube, whose n-th player response points to the stream URLs of generation n.
+async fn youtube(media: &MockServer) -> MockServer {
+    let url = media.url.clone();
+    let generations = AtomicUsize::new(1);
+    mock_youtube(player_response(&url, 0, unexpired()), BASE_JS, move |_| {
+        player_response(&url, generations.fetch_add(1, Ordering::SeqCst), unexpired())
+    }).await
+}
+
+/// The progressive stream of the video, whose URL has the generation 0, and expires at `expire`.
+fn stream_expiring(media: &MockServer, expire: i64) -> Stream {
+    let (_, mut streams) = video_from_player_response(player_response(&media.url, 0, expire)).into_parts();
+    streams.remove(0)
+}
+
+/// The progressive stream of the video, whose URL has the generation 0.
+fn stream(media: &MockServer) -> Stream {
+    let (_, mut streams) = video_from_player_response(player_response(&media.url, 0, unexpired())).into_parts();
+    streams.remove(0)
+}
+
+fn fetcher(youtube: &MockServer) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap().into_owned())
+        .unwrap()
+        .with_base_url(youtube.url.clone())
+        .with_innertube_clients(vec![InnertubeClient::Android])
+}
+
+fn policy() -> RetryPolicy {
+    RetryPolicy::new().backoff(Duration::from_millis(10))
+}
+
+fn path(name: &str) -> PathBuf {
+    temp_dir(name).join("video.mp4")
+}
+
+/// The generations of the URLs, `media` was requested with.
+fn generations(media: &MockServer) -> Vec<String> {
+    media
+        .requests()
+        .iter()
+        .map(|request| media.url.join(&request.path).unwrap())
+        .map(|url| url.query_pairs().find(|(key, _)| key == "gen").unwrap().1.into_owned())
+        .collect()
+}
+
+fn player_requests(youtube: &MockServer) -> usize {
+    youtube.requests().iter().filter(|request| request.path.starts_with("/youtubei/v1/player")).count()
+}
+
+#[test_log::test(tokio::test)]
+async fn forbidden_refreshes_the_url() {
+    let media = media(2, 403).await;
+    let youtube = youtube(&media).await;
+    let path = path("forbidden");
+
+    let stream = stream(&media).with_retry_policy(policy().fetcher(fetcher(&youtube)));
+    stream.download_to(&path).await.unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(generations(&media), ["0", "1", "2"]);
+    assert_eq!(player_requests(&youtube), 2);
+    // the stream keeps its URL
+    assert!(stream.signature_cipher.url.as_str().ends_with("&gen=0"));
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test_log::test(tokio::test)]
+async fn server_errors_are_retried_without_refreshing() {
+    let media = media(2, 503).await;
+    let youtube = youtube(&media).await;
+    let path = path("server_error");
+
+    let stream = stream(&media).with_retry_policy(policy().fetcher(fetcher(&youtube)));
+    stream.download_to(&path).await.unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(generations(&media), ["0"; 3]);
+    assert_eq!(player_requests(&youtube), 0);
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test_log::test(tokio::test)]
+async fn gives_up_after_all_retries() {
+    let media = media(usize::MAX, 403).await;
+    let path = path("give_up");
+
+    let stream = stream(&media).with_retry_policy(policy().retries(2).refresh_on_forbidden(false));
+    assert!(matches!(stream.download_to(&path).await, Err(Error::Forbidden { .. })));
+    assert_eq!(media.requests().len(), 3);
+    assert!(!path.exists());
+
+    // permanent errors are not retried
+    let media = self::media(usize::MAX, 404).await;
+    let stream = self::stream(&media).with_retry_policy(policy());
+    assert!(stream.download_to(&path).await.is_err());
+    // the second request is the sequenced download
+    assert_eq!(media.requests().len(), 2);
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test_log::test(tokio::test)]
+async fn simple_download_options() {
+    let media = media(1, 500).await;
+    let path = path("options");
+
+    let (video_info, streams) = video_from_player_response(player_response(&media.url, 0, unexpired())).into_parts();
+    let video = Video::from_parts(video_info, streams);
+    let options = SimpleDownloadOptions::new()
+        .target_dir(path.parent().unwrap())
+        .retry_policy(policy());
+    let path = video.download_with(options).await.unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(media.requests().len(), 2);
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test_log::test(tokio::test)]
+async fn expired_urls_are_refreshed() {
+    let media = media(0, 200).await;
+    let youtube = youtube(&media).await;
+
+    let expired = stream_expiring(&media, 1000);
+    assert_eq!(expired.expires_at(), Some(Utc.timestamp_opt(1000, 0).unwrap()));
+    assert!(expired.is_expired());
+    assert!(!stream(&media).is_expired());
+
+    let mut refreshed = expired.clone();
+    refreshed.refresh_with(fetcher(&youtube)).await.unwrap();
+    assert!(!refreshed.is_expired());
+    assert!(refreshed.signature_cipher.url.as_str().ends_with("&gen=1"));
+
+    // the policy refreshes the URL before downloading
+    let path = path("expired");
+    let stream = expired.clone().with_retry_policy(policy().fetcher(fetcher(&youtube)));
+    stream.download_to(&path).await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), stream_body());
+    assert_eq!(generations(&media), ["2"]);
+    assert_eq!(player_requests(&youtube), 2);
+
+    // unless it shouldn't
+    let stream = expired.with_retry_policy(policy().refresh_expired(false).fetcher(fetcher(&youtube)));
+    stream.download_to(&path).await.unwrap();
+    assert_eq!(generations(&media), ["2", "0"]);
+    assert_eq!(player_requests(&youtube), 2);
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "search")]
+
+use std::time::Duration;
+
+use common::*;
+use rustube::{Error, Search, SearchVideo};
+
+#[macro_use]
+mod common;
+
+const CONTINUATION: &str = "EooDEgRydXN0GoADU0JTQ0FRdDZSak0wWkZKcGRrcFBkNElCQzJwbVMyWlFabmxLVW1SbA%3D%3D";
+
+/// A server, that answers the first request to the `search` endpoint with `search_response.json`,
+/// and requests with a continuation token with `search_continuation.json`.
+async fn search_server() -> MockServer {
+    MockServer::start(|request| match request.path.starts_with("/youtubei/v1/search") {
+        true if request.json()["continuation"] == CONTINUATION =>
+            MockResponse::json(&json_fixture("search_continuation.json")),
+        true => MockResponse::json(&json_fixture("search_response.json")),
+        false => MockResponse::status(404),
+    }).await
+}
+
+fn search(server: &MockServer) -> Search {
+    Search::new()
+        .unwrap()
+        .with_base_url(server.url.clone())
+}
+
+fn ids(videos: &[SearchVideo]) -> Vec<&str> {
+    videos
+        .iter()
+        .map(|video| video.video_id.as_str())
+        .collect()
+}
+
+#[test_log::test(tokio::test)]
+async fn first_page() {
+    let server = search_server().await;
+
+    let results = search(&server).query("rust").await.unwrap();
+
+    // ads, channels, playlists, shelves and videos without an id are skipped
+    assert_eq!(ids(&results.videos), ["zF34dRivLOw", "jfKfPfyJRdk"]);
+    assert!(results.has_next_page());
+
+    let video = &results.videos[0];
+    assert_eq!(video.title, "Rust Crash Course | Rustlang");
+    assert_eq!(video.author.as_deref(), Some("Traversy Media"));
+    assert_eq!(video.length_seconds, Some(5396));
+    assert_eq!(video.view_count_text.as_deref(), Some("1,234,567 views"));
+    assert_eq!(video.thumbnails.best().unwrap().width, 720);
+
+    let live = &results.videos[1];
+    assert_eq!(live.length_seconds, None);
+    assert_eq!(live.view_count_text.as_deref(), Some("12K watching"));
+
+    let request = server.requests().remove(0);
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.json()["query"], "rust");
+    assert_eq!(request.json()["context"]["client"]["clientName"], "WEB");
+}
+
+#[test_log::test(tokio::test)]
+async fn next_page() {
+    let server = search_server().await;
+
+    let first = search(&server).query("rust").await.unwrap();
+    let second = first.next_page().await.unwrap().unwrap();
+
+    assert_eq!(ids(&second.videos), ["BpPEoZW5IiY"]);
+    assert_eq!(second.videos[0].length_seconds, Some(609));
+    assert!(!second.has_next_page());
+    assert!(second.next_page().await.unwrap().is_none());
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].json()["continuation"], CONTINUATION);
+    assert!(requests[1].json().get("query").is_none());
+}
+
+#[test_log::test(tokio::test)]
+async fn rate_limited() {
+    let server = MockServer::start(|_| MockResponse::status(429).with_header("Retry-After", "7")).await;
+
+    let err = search(&server).query("rust").await.unwrap_err();
+
+    match err {
+        Error::RateLimited { retry_after, endpoint } => {
+            assert_eq!(retry_after, Some(Duration::from_secs(7)));
+            assert_eq!(endpoint, "search");
+        }
+        err => panic!("expected Error::RateLimited, got {:?}", err),
+    }
+}
+
+#[test_log::test(tokio::test)]
+#[ignore]
+async fn live_search() {
+    let results = Search::new().unwrap().query("rust programming language").await.unwrap();
+    assert!(!results.videos.is_empty());
+    assert!(results.has_next_page());
+
+    let next = results.next_page().await.unwrap().unwrap();
+    assert!(!next.videos.is_empty());
+}
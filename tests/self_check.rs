@@ -0,0 +1,135 @@
+#![cfg(feature = "fetch")]
+
+use rustube::{Id, VideoFetcher};
+use rustube::fetcher::{API_CONTRACT_VERSION, SelfCheckReport, SelfCheckStep, StepStatus};
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+
+async fn self_check(server: &MockServer) -> SelfCheckReport {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap().into_owned())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .self_check()
+        .await
+}
+
+fn statuses(report: &SelfCheckReport) -> Vec<(SelfCheckStep, StepStatus)> {
+    report.steps.iter().map(|step| (step.step, step.status)).collect()
+}
+
+#[test_log::test(tokio::test)]
+async fn all_steps_pass() {
+    let player_response = json_fixture("multi_audio_player_response.json");
+    let server = mock_youtube(player_response.clone(), read_fixture("base.js"), move |_| player_response.clone()).await;
+
+    let report = self_check(&server).await;
+
+    assert!(report.is_ok(), "{:#?}", report);
+    assert_eq!(report.video_id.as_str(), VIDEO_ID);
+    assert_eq!(report.steps.len(), 8);
+    assert!(report.steps.iter().all(|step| step.error.is_none()));
+
+    // the signature timestamp of the player JavaScript is sent to the player endpoint
+    let requests = server.requests();
+    let player_request = requests
+        .iter()
+        .find(|request| request.path.starts_with("/youtubei/v1/player"))
+        .unwrap();
+    assert_eq!(
+        player_request.json()["playbackContext"]["contentPlaybackContext"]["signatureTimestamp"],
+        19876,
+    );
+    // no media is downloaded
+    assert_eq!(requests.len(), 3);
+}
+
+#[test_log::test(tokio::test)]
+async fn broken_player_js() {
+    let player_response = json_fixture("multi_audio_player_response.json");
+    let base_js = "var config={signatureTimestamp:19876};";
+    let server = mock_youtube(player_response.clone(), base_js, move |_| player_response.clone()).await;
+
+    let report = self_check(&server).await;
+
+    assert!(!report.is_ok());
+    assert_eq!(statuses(&report), [
+        (SelfCheckStep::WatchHtml, StepStatus::Passed),
+        (SelfCheckStep::PlayerConfig, StepStatus::Passed),
+        (SelfCheckStep::JsUrl, StepStatus::Passed),
+        (SelfCheckStep::BaseJs, StepStatus::Passed),
+        (SelfCheckStep::Cipher, StepStatus::Failed),
+        (SelfCheckStep::SignatureTimestamp, StepStatus::Passed),
+        (SelfCheckStep::NFunction, StepStatus::Failed),
+        (SelfCheckStep::PlayerEndpoint, StepStatus::Passed),
+    ]);
+    let failures: Vec<SelfCheckStep> = report.failures().map(|step| step.step).collect();
+    assert_eq!(failures, [SelfCheckStep::Cipher, SelfCheckStep::NFunction]);
+    assert!(report.step(SelfCheckStep::Cipher).unwrap().error.is_some());
+}
+
+#[test_log::test(tokio::test)]
+async fn unavailable_watch_page_skips_dependent_steps() {
+    let player_response = json_fixture("multi_audio_player_response.json");
+    let server = MockServer::start(move |request| {
+        if request.path.starts_with("/youtubei/v1/player") {
+            MockResponse::json(&player_response)
+        } else {
+            MockResponse::status(500)
+        }
+    }).await;
+
+    let report = self_check(&server).await;
+
+    assert_eq!(statuses(&report), [
+        (SelfCheckStep::WatchHtml, StepStatus::Failed),
+        (SelfCheckStep::PlayerConfig, StepStatus::Skipped),
+        (SelfCheckStep::JsUrl, StepStatus::Skipped),
+        (SelfCheckStep::BaseJs, StepStatus::Skipped),
+        (SelfCheckStep::Cipher, StepStatus::Skipped),
+        (SelfCheckStep::SignatureTimestamp, StepStatus::Skipped),
+        (SelfCheckStep::NFunction, StepStatus::Skipped),
+        (SelfCheckStep::PlayerEndpoint, StepStatus::Passed),
+    ]);
+    assert_eq!(report.failures().count(), 1);
+    assert!(report.steps[0].error.as_deref().unwrap().contains("500"));
+    assert_eq!(report.steps[1].error, None);
+}
+
+#[test_log::test(tokio::test)]
+async fn player_endpoint_without_streams() {
+    let player_response = json_fixture("multi_audio_player_response.json");
+    let mut without_streams = player_response.clone();
+    without_streams.as_object_mut().unwrap().remove("streamingData");
+    let server = mock_youtube(player_response, read_fixture("base.js"), move |_| without_streams.clone()).await;
+
+    let report = self_check(&server).await;
+
+    let failures: Vec<SelfCheckStep> = report.failures().map(|step| step.step).collect();
+    assert_eq!(failures, [SelfCheckStep::PlayerEndpoint]);
+}
+
+#[test_log::test(tokio::test)]
+async fn report_serialization() {
+    let server = MockServer::start(|_| MockResponse::status(404)).await;
+
+    let report = self_check(&server).await;
+    let json = serde_json::to_value(&report).unwrap();
+
+    assert_eq!(json["contract_version"], API_CONTRACT_VERSION);
+    assert_eq!(json["rustube_version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(json["video_id"], VIDEO_ID);
+    assert_eq!(json["steps"][0]["step"], "watch_html");
+    assert_eq!(json["steps"][0]["status"], "failed");
+    assert!(json["steps"][0]["error"].is_string());
+    assert_eq!(json["steps"][1], serde_json::json!({
+        "step": "player_config",
+        "status": "skipped",
+        "error": null,
+    }));
+    assert_eq!(json["steps"][7]["step"], "player_endpoint");
+}
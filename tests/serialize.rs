@@ -0,0 +1,121 @@
+#![cfg(feature = "serialize")]
+
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use common::*;
+use rustube::Error;
+use rustube::serialize::OutputLevel;
+
+#[macro_use]
+mod common;
+
+const MULTI_AUDIO: &str = "multi_audio_player_response.json";
+
+/// The golden files, and the level they were serialized with.
+const LEVELS: [(&str, &str); 7] = [
+    ("url", "url"),
+    ("general", "general"),
+    ("video_track", "video-track"),
+    ("audio_track", "audio-track"),
+    ("thumbnails", "thumbnails"),
+    ("verbose", "general | video-track | audio-track | verbose"),
+    ("full", "full"),
+];
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(FIXTURE_DIR).join("serialize").join(format!("{}.json", name))
+}
+
+/// Compares `value` with the golden file `name`. With `RUSTUBE_BLESS=1`, the golden file is
+/// overwritten instead.
+fn assert_golden(name: &str, value: &Value) {
+    // the microformat is only part of the player response with the `microformat` feature
+    let mut value = value.clone();
+    if let Some(player_response) = value.pointer_mut("/video_info/player_response") {
+        player_response.as_object_mut().unwrap().remove("microformat");
+    }
+    let value = &value;
+
+    let path = golden_path(name);
+    if std::env::var_os("RUSTUBE_BLESS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, serde_json::to_string_pretty(value).unwrap() + "\n").unwrap();
+        return;
+    }
+
+    let golden: Value = serde_json::from_str(&read_fixture(&format!("serialize/{}.json", name))).unwrap();
+    assert_eq!(
+        value, &golden,
+        "the output of level `{}` changed, rerun with RUSTUBE_BLESS=1 to update {:?}", name, path,
+    );
+}
+
+#[test]
+fn video_per_level() {
+    let video = video_from_fixture(MULTI_AUDIO);
+
+    for (name, level) in LEVELS {
+        let value = video.serialize_with_level(level.parse().unwrap());
+        assert_golden(name, &value);
+    }
+}
+
+#[test]
+fn stream_matches_video() {
+    let video = video_from_fixture(MULTI_AUDIO);
+    let level = OutputLevel::GENERAL | OutputLevel::AUDIO_TRACK;
+    let serialized = video.serialize_with_level(level);
+
+    let streams = serialized["streams"].as_array().unwrap();
+    assert_eq!(streams.len(), video.streams().len());
+    for (stream, serialized) in video.streams().iter().zip(streams) {
+        assert_eq!(&stream.serialize_with_level(level), serialized);
+    }
+}
+
+#[test]
+fn levels_select_fields() {
+    let video = video_from_fixture(MULTI_AUDIO);
+    let stream = video.streams().iter().find(|stream| stream.includes_audio_track).unwrap();
+
+    let url = stream.serialize_with_level(OutputLevel::URL);
+    assert_eq!(url.as_object().unwrap().keys().collect::<Vec<_>>(), ["signature_cipher"]);
+
+    let empty = stream.serialize_with_level(OutputLevel::empty());
+    assert!(empty.as_object().unwrap().is_empty());
+
+    // verbose only adds fields to the other levels
+    let verbose = stream.serialize_with_level(OutputLevel::VERBOSE);
+    assert!(verbose.as_object().unwrap().is_empty());
+
+    let audio = stream.serialize_with_level(OutputLevel::AUDIO_TRACK);
+    assert!(audio["audio_codec"].is_object() || audio["audio_codec"].is_string(), "{}", audio);
+    assert!(audio.get("is_drc").is_none());
+    assert!(stream.serialize_with_level(OutputLevel::AUDIO_TRACK | OutputLevel::VERBOSE).get("is_drc").is_some());
+
+    let video_without_thumbnails = video.serialize_with_level(OutputLevel::URL);
+    assert!(video_without_thumbnails.get("thumbnails").is_none());
+    assert!(video_without_thumbnails.get("video_info").is_none());
+    let thumbnails = video.serialize_with_level(OutputLevel::THUMBNAILS);
+    assert_eq!(
+        thumbnails["thumbnails"].as_array().unwrap().len(),
+        video.video_details().thumbnails.len(),
+    );
+}
+
+#[test]
+fn parse_output_level() {
+    assert_eq!("url".parse::<OutputLevel>().unwrap(), OutputLevel::URL);
+    assert_eq!(
+        " url | general,thumbnails  audio-track ".parse::<OutputLevel>().unwrap(),
+        OutputLevel::URL | OutputLevel::GENERAL | OutputLevel::THUMBNAILS | OutputLevel::AUDIO_TRACK,
+    );
+    assert_eq!("full".parse::<OutputLevel>().unwrap(), OutputLevel::all());
+    assert_eq!("".parse::<OutputLevel>().unwrap(), OutputLevel::empty());
+    assert!(matches!(
+        "url | everything".parse::<OutputLevel>(),
+        Err(Error::BadOutputLevel(level)) if level == "everything"
+    ));
+}
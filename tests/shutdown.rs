@@ -0,0 +1,117 @@
+#![cfg(feature = "download")]
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use common::*;
+use rustube::{Error, ShutdownHandle, SimpleDownloadOptions};
+
+#[macro_use]
+mod common;
+
+const LEN: usize = 64 * 1024;
+const CHUNK: usize = 1024;
+
+/// Serves the streams in chunks of `CHUNK` bytes every 20 ms, so a download takes more than a
+/// second.
+async fn server() -> MockServer {
+    MockServer::start(|_| MockResponse::ok(vec![7; LEN]).slow(CHUNK, Duration::from_millis(20))).await
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut part_path = path.as_os_str().to_owned();
+    part_path.push(".part");
+    PathBuf::from(part_path)
+}
+
+#[test_log::test(tokio::test)]
+async fn cancels_all_downloads() {
+    let server = server().await;
+    let video = video_with_streams_at(&server, "stream");
+    let dir = temp_dir("all");
+    let shutdown = ShutdownHandle::new();
+
+    let downloads: Vec<_> = (0..3)
+        .map(|i| {
+            let video = video.clone();
+            let options = SimpleDownloadOptions::new()
+                .target_dir(&dir)
+                .filename(format!("{}.mp4", i))
+                .keep_partial(true)
+                .write_buffer_size(0)
+                .shutdown(shutdown.clone());
+            tokio::spawn(async move { video.download_with(options).await })
+        })
+        .collect();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(shutdown.active_downloads(), 3);
+
+    let started = Instant::now();
+    assert!(shutdown.shutdown(Duration::from_secs(5)).await);
+    // each download stops after its current chunk
+    assert!(started.elapsed() < Duration::from_secs(1), "{:?}", started.elapsed());
+    assert_eq!(shutdown.active_downloads(), 0);
+
+    for (i, download) in downloads.into_iter().enumerate() {
+        let err = download.await.unwrap().unwrap_err();
+        assert!(matches!(err, Error::Cancelled), "{:?}", err);
+
+        // the partial file contains all bytes, that were received
+        let path = dir.join(format!("{}.mp4", i));
+        let partial = std::fs::read(part_path(&path)).unwrap();
+        assert!(!partial.is_empty() && partial.len() < LEN, "{}", partial.len());
+        assert!(partial.iter().all(|&b| b == 7));
+        assert!(!path.exists());
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn removes_partial_downloads() {
+    let server = server().await;
+    let shutdown = ShutdownHandle::new();
+    let video = video_with_streams_at(&server, "stream").with_shutdown(shutdown.clone());
+    let path = temp_dir("remove").join("video.mp4");
+
+    let download = tokio::spawn({
+        let path = path.clone();
+        async move { video.streams()[0].download_to(&path).await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(shutdown.shutdown(Duration::from_secs(5)).await);
+
+    assert!(matches!(download.await.unwrap(), Err(Error::Cancelled)));
+    assert!(!path.exists());
+    assert!(!part_path(&path).exists());
+}
+
+#[test_log::test(tokio::test)]
+async fn triggered_handle_cancels_new_downloads() {
+    let server = server().await;
+    let shutdown = ShutdownHandle::new();
+    shutdown.trigger();
+    assert!(shutdown.is_triggered());
+    let path = temp_dir("triggered").join("video.mp4");
+
+    let stream = video_with_streams_at(&server, "stream").streams()[0].clone().with_shutdown(shutdown.clone());
+    let err = stream.download_to(&path).await.unwrap_err();
+
+    assert!(matches!(err, Error::Cancelled), "{:?}", err);
+    assert!(server.requests().is_empty());
+    assert_eq!(shutdown.active_downloads(), 0);
+}
+
+#[test_log::test(tokio::test)]
+async fn other_downloads_continue() {
+    let server = MockServer::start(|_| MockResponse::ok(vec![7; CHUNK])).await;
+    let video = video_with_streams_at(&server, "stream");
+    let path = temp_dir("other").join("video.mp4");
+
+    // a handle only stops the downloads, it's attached to
+    ShutdownHandle::new().trigger();
+    video.streams()[0].download_to(&path).await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap().len(), CHUNK);
+
+    // without any active downloads, shutting down returns immediately
+    assert!(ShutdownHandle::new().shutdown(Duration::ZERO).await);
+}
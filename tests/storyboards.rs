@@ -0,0 +1,72 @@
+#![cfg(feature = "descramble")]
+
+use std::time::Duration;
+
+use common::*;
+use rustube::Video;
+use rustube::video_info::player_response::storyboards::Storyboard;
+
+#[macro_use]
+mod common;
+
+const SQP: &str = "sqp=-oaymwENSDfyq4qpAwVwAcABBqLzl_8DBgjdqfqoBg==";
+
+fn video() -> Video {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    player_response["storyboards"] = json_fixture("storyboards.json");
+    video_from_player_response(player_response)
+}
+
+fn urls(storyboard: &Storyboard) -> Vec<String> {
+    storyboard.image_urls().iter().map(|url| url.as_str().to_owned()).collect()
+}
+
+#[test]
+fn storyboards() {
+    let storyboards = video().storyboards();
+
+    let levels: Vec<_> = storyboards
+        .iter()
+        .map(|storyboard| (storyboard.level, storyboard.thumbnail_width, storyboard.thumbnail_height))
+        .collect();
+    assert_eq!(levels, [(0, 48, 27), (1, 80, 45), (2, 160, 90)]);
+
+    let largest = &storyboards[2];
+    assert_eq!(largest.thumbnail_count, 62);
+    assert_eq!((largest.columns, largest.rows), (5, 5));
+    assert_eq!(largest.interval, Duration::from_secs(10));
+    assert_eq!(largest.sheet_count(), 3);
+    assert_eq!(storyboards[0].interval, Duration::ZERO);
+}
+
+#[test]
+fn image_urls() {
+    let storyboards = video().storyboards();
+
+    assert_eq!(urls(&storyboards[0]), [
+        format!("https://i.ytimg.com/sb/fDzQ3kUg8Ss/storyboard3_L0/default.jpg?{}&sigh=rs$AOn4CLBxCDlVY3bKd2ZeZ6OdGLqV1dJ2WQ", SQP),
+    ]);
+    assert_eq!(urls(&storyboards[1]), [
+        format!("https://i.ytimg.com/sb/fDzQ3kUg8Ss/storyboard3_L1/M0.jpg?{}&sigh=rs$AOn4CLDvW7Rq2sK2v9yWbJx1nq8HXq2Yfw", SQP),
+    ]);
+    assert_eq!(urls(&storyboards[2]), (0..3)
+        .map(|sheet| format!(
+            "https://i.ytimg.com/sb/fDzQ3kUg8Ss/storyboard3_L2/M{}.jpg?{}&sigh=rs$AOn4CLB1uZxH0qJdN3kGfV2Q4bY8k3fLpQ",
+            sheet, SQP,
+        ))
+        .collect::<Vec<_>>());
+}
+
+#[test]
+fn invalid_levels_are_skipped() {
+    let storyboards = Storyboard::from_spec(
+        "https://i.ytimg.com/sb/fDzQ3kUg8Ss/storyboard3_L$L/$N.jpg|48#27#100#10#10#0#default|80#45#0#10#10#0#M$M#rs$A|160#90#62#5#5#10000#M$M#rs$B"
+    );
+    assert_eq!(storyboards.len(), 1);
+    assert_eq!(storyboards[0].level, 2);
+    assert_eq!(urls(&storyboards[0])[0], "https://i.ytimg.com/sb/fDzQ3kUg8Ss/storyboard3_L2/M0.jpg?sigh=rs$B");
+
+    assert!(Storyboard::from_spec("").is_empty());
+    // the multi audio fixture has no storyboards
+    assert!(video_from_fixture("multi_audio_player_response.json").storyboards().is_empty());
+}
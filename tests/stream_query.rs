@@ -0,0 +1,176 @@
+#![cfg(feature = "stream")]
+
+use serde_json::{json, Value};
+
+use rustube::{StreamQuery, Video};
+use rustube::video_info::player_response::streaming_data::{AudioQuality, QualityLabel};
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+/// A synthetic format. Formats without a resolution are audio only, formats with a `label` have
+/// a video track.
+fn format(itag: u64, mime_type: &str, label: Option<(&str, u64, u64)>, bitrate: u64) -> Value {
+    let mut format = json!({
+        "itag": itag,
+        "url": format!("https://rr1---sn-4g5e6nsz.googlevideo.com/videoplayback?itag={}", itag),
+        "mimeType": mime_type,
+        "bitrate": bitrate,
+        "quality": "medium",
+        "projectionType": "RECTANGULAR",
+    });
+    if let Some((label, width, height)) = label {
+        format["qualityLabel"] = label.into();
+        format["width"] = width.into();
+        format["height"] = height.into();
+        format["fps"] = 30.into();
+    }
+    format
+}
+
+fn audio(itag: u64, mime_type: &str, bitrate: u64, quality: &str, track: Option<(&str, bool)>) -> Value {
+    let mut format = format(itag, mime_type, None, bitrate);
+    format["audioQuality"] = quality.into();
+    if let Some((id, is_default)) = track {
+        format["audioTrack"] = json!({ "displayName": id, "id": id, "audioIsDefault": is_default });
+    }
+    format
+}
+
+/// The player response of a video with two progressive streams, four video only streams
+/// (including a Premium one), and three audio only streams in two languages.
+fn player_response() -> Value {
+    const MP4_AV: &str = r#"video/mp4; codecs="avc1.42001E, mp4a.40.2""#;
+    const MP4: &str = r#"video/mp4; codecs="avc1.640028""#;
+    const WEBM: &str = r#"video/webm; codecs="vp9""#;
+    const M4A: &str = r#"audio/mp4; codecs="mp4a.40.2""#;
+    const OPUS: &str = r#"audio/webm; codecs="opus""#;
+
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    player_response["streamingData"]["formats"] = json!([
+        format(18, MP4_AV, Some(("360p", 640, 360)), 500_000),
+        format(22, MP4_AV, Some(("720p", 1280, 720)), 1_500_000),
+    ]);
+    player_response["streamingData"]["adaptiveFormats"] = json!([
+        format(134, MP4, Some(("360p", 640, 360)), 400_000),
+        format(136, MP4, Some(("720p", 1280, 720)), 2_500_000),
+        format(247, WEBM, Some(("720p", 1280, 720)), 1_200_000),
+        format(356, WEBM, Some(("1080p Premium", 1920, 1080)), 9_000_000),
+        audio(140, M4A, 130_000, "AUDIO_QUALITY_MEDIUM", Some(("en.4", true))),
+        audio(251, OPUS, 160_000, "AUDIO_QUALITY_MEDIUM", Some(("en.4", true))),
+        audio(250, OPUS, 70_000, "AUDIO_QUALITY_LOW", Some(("de-DE.3", false))),
+    ]);
+    player_response
+}
+
+fn video() -> Video {
+    video_from_player_response(player_response())
+}
+
+fn itags<'a>(streams: impl IntoIterator<Item=&'a rustube::Stream>) -> Vec<u64> {
+    streams
+        .into_iter()
+        .map(|stream| stream.itag)
+        .collect()
+}
+
+#[test]
+fn default_query() {
+    let video = video();
+    let query = StreamQuery::new();
+
+    // the Premium stream, and the dubbed audio track are skipped
+    assert_eq!(itags(query.all(&video)), [22, 18, 136, 247, 134, 251, 140]);
+    assert_eq!(query.best(&video).unwrap().itag, 22);
+    assert_eq!(query.worst(&video).unwrap().itag, 140);
+}
+
+#[test]
+fn tracks() {
+    let video = video();
+
+    assert_eq!(itags(StreamQuery::new().progressive().all(&video)), [22, 18]);
+    assert_eq!(itags(StreamQuery::new().only_video().all(&video)), [136, 247, 134]);
+    assert_eq!(itags(StreamQuery::new().only_audio().all(&video)), [251, 140]);
+    assert_eq!(itags(StreamQuery::new().adaptive().all(&video)), [136, 247, 134, 251, 140]);
+    assert_eq!(itags(StreamQuery::new().has_audio(true).all(&video)), [22, 18, 251, 140]);
+}
+
+#[test]
+fn constraints() {
+    let video = video();
+
+    assert_eq!(itags(StreamQuery::new().only_video().mime("video/webm").all(&video)), [247]);
+    assert_eq!(itags(StreamQuery::new().mime("VIDEO/MP4").all(&video)), [22, 18, 136, 134]);
+    assert_eq!(itags(StreamQuery::new().min_resolution(720).all(&video)), [22, 136, 247]);
+    assert_eq!(itags(StreamQuery::new().max_resolution(360).all(&video)), [18, 134]);
+    assert_eq!(itags(StreamQuery::new().progressive().max_bitrate(1_000_000).all(&video)), [18]);
+    assert_eq!(itags(StreamQuery::new().only_video().max_bitrate(1_500_000).all(&video)), [247, 134]);
+    assert_eq!(itags(StreamQuery::new().quality_label(QualityLabel::P360).all(&video)), [18, 134]);
+    assert_eq!(itags(StreamQuery::new().audio_quality(AudioQuality::Low).audio_language("de").all(&video)), [250]);
+    assert_eq!(StreamQuery::new().only_audio().audio_language("de-DE").best(&video).unwrap().itag, 250);
+    // the original audio track is not German
+    assert!(StreamQuery::new().audio_quality(AudioQuality::Low).best(&video).is_none());
+}
+
+#[test]
+fn premium() {
+    let video = video();
+
+    assert_eq!(StreamQuery::new().only_video().best(&video).unwrap().itag, 136);
+    assert_eq!(StreamQuery::new().only_video().include_premium(true).best(&video).unwrap().itag, 356);
+    // asking for a Premium quality label includes Premium streams
+    assert_eq!(
+        itags(StreamQuery::new().quality_label(QualityLabel::P1080Premium).all(&video)),
+        [356],
+    );
+}
+
+#[test]
+fn conflicting_constraints() {
+    let video = video();
+
+    assert!(StreamQuery::new().only_audio().progressive().all(&video).is_empty());
+    assert!(StreamQuery::new().only_audio().min_resolution(360).best(&video).is_none());
+    assert!(StreamQuery::new().progressive().mime("video/webm").worst(&video).is_none());
+    assert!(StreamQuery::new().quality_label(QualityLabel::P720).min_resolution(1080).all(&video).is_empty());
+    // setting the tracks again replaces them
+    assert_eq!(itags(StreamQuery::new().only_video().only_audio().all(&video)), [251, 140]);
+}
+
+#[test]
+fn order_by_bitrate() {
+    let video = video();
+
+    // by rank, progressive streams come first, by bitrate, the 720p video only mp4 stream does
+    let query = StreamQuery::new().has_video(true).order_by_bitrate();
+    assert_eq!(itags(query.all(&video)), [136, 22, 247, 18, 134]);
+    assert_eq!(query.best(video.streams()).unwrap().itag, 136);
+    assert_eq!(query.worst(video.streams().iter()).unwrap().itag, 134);
+}
+
+#[test_log::test(tokio::test)]
+#[cfg(feature = "download")]
+async fn simple_download_options_select_like_the_query() {
+    use rustube::SimpleDownloadOptions;
+
+    let server = MockServer::start(|_| MockResponse::ok("stream")).await;
+    let mut player_response = player_response();
+    let formats = player_response["streamingData"]["formats"].as_array_mut().unwrap();
+    for format in formats {
+        format["url"] = server.url.join(&format["itag"].to_string()).unwrap().as_str().into();
+    }
+    let video = video_from_player_response(player_response);
+    let dir = temp_dir("stream_query");
+
+    video.download_with(SimpleDownloadOptions::new().target_dir(&dir)).await.unwrap();
+    video.download_with(SimpleDownloadOptions::new().target_dir(&dir).max_resolution(480)).await.unwrap();
+
+    let paths: Vec<_> = server.requests().into_iter().map(|request| request.path).collect();
+    assert_eq!(paths, ["/22", "/18"]);
+    assert_eq!(video.best_quality().unwrap().itag, 22);
+    assert_eq!(video.worst_quality().unwrap().itag, 18);
+    let _ = std::fs::remove_dir_all(&dir);
+}
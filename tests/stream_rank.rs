@@ -0,0 +1,185 @@
+#![cfg(feature = "stream")]
+
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+use rustube::{Stream, StreamRank, Video};
+
+use common::*;
+
+#[macro_use]
+mod common;
+
+const MULTI_AUDIO: &str = "multi_audio_player_response.json";
+
+const RESOLUTIONS: [(u64, u64, &str); 5] = [
+    (256, 144, "144p"),
+    (640, 360, "360p"),
+    (1280, 720, "720p"),
+    (1920, 1080, "1080p"),
+    (3840, 2160, "2160p"),
+];
+
+/// The kind of track(s) of a synthetic stream, together with its mime type.
+const KINDS: [&str; 6] = [
+    r#"video/mp4; codecs="avc1.42001E, mp4a.40.2""#,
+    r#"video/mp4; codecs="avc1.640028""#,
+    r#"video/webm; codecs="vp9""#,
+    r#"video/mp4; codecs="av01.0.08M.08""#,
+    r#"audio/mp4; codecs="mp4a.40.2""#,
+    r#"audio/webm; codecs="opus""#,
+];
+
+/// A synthetic format, with the given mime type, resolution, fps, HDR, and bitrate.
+fn synthetic_format(itag: u64, mime_type: &str, resolution: usize, fps: u8, hdr: bool, bitrate: u64) -> Value {
+    let mut format = json!({
+        "itag": itag,
+        "url": format!("https://rr1---sn-4g5e6nsz.googlevideo.com/videoplayback?itag={}", itag),
+        "mimeType": mime_type,
+        "bitrate": bitrate,
+        "quality": "medium",
+        "projectionType": "RECTANGULAR",
+    });
+    if mime_type.starts_with("video") {
+        let (width, height, label) = RESOLUTIONS[resolution];
+        format["width"] = width.into();
+        format["height"] = height.into();
+        format["fps"] = fps.into();
+        format["qualityLabel"] = label.into();
+        if hdr {
+            format["colorInfo"] = json!({
+                "primaries": "COLOR_PRIMARIES_BT2020",
+                "transferCharacteristics": "COLOR_TRANSFER_CHARACTERISTICS_SMPTEST2084",
+                "matrixCoefficients": "COLOR_MATRIX_COEFFICIENTS_BT2020_NCL",
+            });
+        }
+    }
+    format
+}
+
+fn video(formats: Vec<Value>) -> Video {
+    let mut player_response = json_fixture(MULTI_AUDIO);
+    player_response["streamingData"]["formats"] = json!([]);
+    player_response["streamingData"]["adaptiveFormats"] = formats.into();
+    video_from_player_response(player_response)
+}
+
+fn formats() -> impl Strategy<Value=Vec<Value>> {
+    let format_strategy = (0..KINDS.len(), 0..RESOLUTIONS.len(), prop::sample::select(vec![24_u8, 30, 60]), any::<bool>(), 1..10_000_000_u64);
+    prop::collection::vec(format_strategy, 1..12).prop_map(|formats| {
+        formats
+            .into_iter()
+            .enumerate()
+            .map(|(i, (kind, resolution, fps, hdr, bitrate))| synthetic_format(i as u64, KINDS[kind], resolution, fps, hdr, bitrate))
+            .collect()
+    })
+}
+
+fn max_rank<'a>(streams: impl Iterator<Item=&'a Stream>) -> Option<StreamRank> {
+    streams.map(Stream::rank).max()
+}
+
+proptest! {
+    // building a video is comparatively slow, so only a few cases are run
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn rank_is_a_total_order(formats in formats()) {
+        let video = video(formats);
+        let ranks: Vec<StreamRank> = video.streams().iter().map(Stream::rank).collect();
+
+        for a in &ranks {
+            prop_assert_eq!(a.cmp(a), std::cmp::Ordering::Equal);
+            for b in &ranks {
+                // antisymmetry
+                prop_assert_eq!(a.cmp(b), b.cmp(a).reverse());
+                if a <= b && b <= a {
+                    prop_assert_eq!(a, b);
+                }
+                // transitivity
+                for c in &ranks {
+                    if a <= b && b <= c {
+                        prop_assert!(a <= c);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn selection_helpers_use_the_rank(formats in formats()) {
+        let video = video(formats);
+        let streams = video.streams();
+        let progressive = || streams.iter().filter(|s| s.includes_video_track && s.includes_audio_track);
+        let video_only = || streams.iter().filter(|s| s.includes_video_track && !s.includes_audio_track);
+        let audio_only = || streams.iter().filter(|s| !s.includes_video_track && s.includes_audio_track);
+
+        prop_assert_eq!(video.best_quality().map(Stream::rank), max_rank(progressive()));
+        prop_assert_eq!(video.worst_quality().map(Stream::rank), progressive().map(Stream::rank).min());
+        prop_assert_eq!(video.best_video().map(Stream::rank), max_rank(video_only()));
+        prop_assert_eq!(video.worst_video().map(Stream::rank), video_only().map(Stream::rank).min());
+        prop_assert_eq!(video.best_audio().map(Stream::rank), max_rank(audio_only()));
+        prop_assert_eq!(video.worst_audio().map(Stream::rank), audio_only().map(Stream::rank).min());
+    }
+}
+
+#[test]
+fn documented_order() {
+    let video = video(vec![
+        synthetic_format(0, KINDS[1], 3, 30, false, 4_000_000),
+        // a higher fps beats the bitrate
+        synthetic_format(1, KINDS[1], 3, 60, false, 3_000_000),
+        // HDR beats SDR
+        synthetic_format(2, KINDS[2], 3, 60, true, 3_000_000),
+        // the resolution beats everything else
+        synthetic_format(3, KINDS[1], 4, 24, false, 1_000_000),
+        // a progressive stream beats all adaptive ones
+        synthetic_format(4, KINDS[0], 0, 24, false, 100_000),
+    ]);
+    let mut itags: Vec<(StreamRank, u64)> = video.streams().iter().map(|s| (s.rank(), s.itag)).collect();
+    itags.sort();
+    assert_eq!(itags.iter().map(|(_, itag)| *itag).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+
+    // with everything else being equal, newer codecs are preferred
+    let video = video_with_codecs();
+    let best = video.best_video().unwrap();
+    assert_eq!(best.itag, 3);
+    assert!(best.rank() > video.streams()[1].rank());
+    assert_eq!(video.best_audio().unwrap().itag, 5);
+}
+
+fn video_with_codecs() -> Video {
+    video(vec![
+        synthetic_format(1, KINDS[1], 3, 30, false, 2_000_000),
+        synthetic_format(2, KINDS[2], 3, 30, false, 2_000_000),
+        synthetic_format(3, KINDS[3], 3, 30, false, 2_000_000),
+        synthetic_format(4, KINDS[4], 0, 0, false, 128_000),
+        synthetic_format(5, KINDS[5], 0, 0, false, 128_000),
+    ])
+}
+
+#[test]
+fn fixture_best_streams() {
+    let video = video_from_fixture(MULTI_AUDIO);
+
+    assert_eq!(video.best_quality().unwrap().itag, 18);
+    assert_eq!(video.worst_quality().unwrap().itag, 18);
+    assert_eq!(video.best_video().unwrap().itag, 137);
+
+    // opus of the default audio track, which has a higher bitrate than the AAC stream
+    let best_audio = video.best_audio().unwrap();
+    assert_eq!((best_audio.itag, best_audio.bitrate), (251, Some(139947)));
+    let worst_audio = video.worst_audio().unwrap();
+    assert_eq!((worst_audio.itag, worst_audio.bitrate), (140, Some(130240)));
+
+    let rank = video.streams()[1].rank();
+    assert_eq!(rank, StreamRank {
+        has_video_and_audio: false,
+        resolution: 1920 * 1080,
+        fps: 25,
+        is_hdr: false,
+        video_bitrate: 4395693,
+        audio_bitrate: 0,
+        codec_preference: 1,
+    });
+}
@@ -0,0 +1,197 @@
+#![cfg(feature = "download")]
+
+use std::time::Duration;
+
+use tokio::io::AsyncReadExt;
+use tokio_stream::StreamExt;
+
+use common::*;
+use rustube::{Error, Stream};
+
+#[macro_use]
+mod common;
+
+const CHUNK_DELAY: Duration = Duration::from_millis(5);
+
+/// The audio stream of the multi audio fixture, pointing to `path` on `server`.
+fn stream(server: &MockServer, path: &str) -> Stream {
+    video_with_streams_at(server, path)
+        .streams()
+        .iter()
+        .find(|stream| stream.itag == 140)
+        .unwrap()
+        .clone()
+}
+
+/// Waits until the background download stopped making progress.
+async fn settle(reader: &rustube::StreamReader) {
+    let mut buffered = reader.buffered();
+    loop {
+        tokio::time::sleep(CHUNK_DELAY * 10).await;
+        if reader.buffered() == buffered { break; }
+        buffered = reader.buffered();
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn reads_slowly_delivered_body() {
+    let server = MockServer::start(|_| MockResponse::ok(pattern(64 * 1024)).slow(1000, CHUNK_DELAY)).await;
+
+    let mut reader = stream(&server, "videoplayback?itag=140")
+        .open_reader()
+        .await
+        .unwrap();
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).await.unwrap();
+
+    assert_eq!(read, pattern(64 * 1024));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test_log::test(tokio::test)]
+async fn prefetch_is_bounded() {
+    const PREFETCH: usize = 8 * 1024;
+    let server = MockServer::start(|_| MockResponse::ok(pattern(64 * 1024)).slow(1000, CHUNK_DELAY)).await;
+
+    let mut reader = stream(&server, "videoplayback?itag=140")
+        .open_reader_with_prefetch(PREFETCH)
+        .await
+        .unwrap();
+    assert_eq!(reader.prefetch(), PREFETCH);
+
+    // without a reader, the download stops once the buffer is full
+    settle(&reader).await;
+    assert_eq!(reader.buffered(), PREFETCH);
+
+    let mut read = vec![0; 4 * 1024];
+    reader.read_exact(&mut read).await.unwrap();
+    assert!(reader.buffered() <= PREFETCH);
+    settle(&reader).await;
+    assert_eq!(reader.buffered(), PREFETCH);
+
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).await.unwrap();
+    read.extend(rest);
+    assert_eq!(read, pattern(64 * 1024));
+    assert_eq!(reader.buffered(), 0);
+}
+
+#[test_log::test(tokio::test)]
+async fn falls_back_to_sequenced_requests() {
+    let server = MockServer::start(|request| match request.path.rsplit_once("sq=") {
+        None => MockResponse::status(404),
+        Some((_, "0")) => MockResponse::ok("header,").with_header("Segment-Count", "3"),
+        Some((_, sq)) => MockResponse::ok(format!("segment {},", sq)).slow(3, CHUNK_DELAY),
+    }).await;
+
+    let mut reader = stream(&server, "videoplayback?itag=140")
+        .open_reader()
+        .await
+        .unwrap();
+    let mut read = String::new();
+    reader.read_to_string(&mut read).await.unwrap();
+
+    assert_eq!(read, "header,segment 1,segment 2,");
+    let paths: Vec<String> = server.requests().into_iter().map(|request| request.path).collect();
+    assert_eq!(paths, [
+        "/videoplayback?itag=140",
+        "/videoplayback?itag=140&sq=0",
+        "/videoplayback?itag=140&sq=1",
+        "/videoplayback?itag=140&sq=2",
+    ]);
+}
+
+#[test_log::test(tokio::test)]
+async fn initial_request_fails() {
+    let server = MockServer::start(|_| MockResponse::status(500)).await;
+    let res = stream(&server, "videoplayback?itag=140")
+        .open_reader()
+        .await;
+    assert!(matches!(res, Err(Error::Request(e)) if e.status().map(|s| s.as_u16()) == Some(500)));
+
+    // 403s are diagnosed
+    let server = MockServer::start(|_| MockResponse::status(403)).await;
+    let res = stream(&server, "videoplayback?itag=140")
+        .open_reader()
+        .await;
+    assert!(matches!(res, Err(Error::Forbidden { itag: 140, .. })));
+}
+
+#[test_log::test(tokio::test)]
+async fn failing_segment_is_returned_by_reader() {
+    let server = MockServer::start(|request| match request.path.rsplit_once("sq=") {
+        None => MockResponse::status(404),
+        Some((_, "0")) => MockResponse::ok("header,").with_header("Segment-Count", "2"),
+        Some(_) => MockResponse::status(500),
+    }).await;
+
+    let mut reader = stream(&server, "videoplayback?itag=140")
+        .open_reader()
+        .await
+        .unwrap();
+    let mut read = vec![0; 7];
+    reader.read_exact(&mut read).await.unwrap();
+    assert_eq!(read, b"header,");
+
+    let err = reader.read_to_end(&mut Vec::new()).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Other);
+}
+
+#[test_log::test(tokio::test)]
+async fn bytes_stream_forwards_all_chunks() {
+    let server = MockServer::start(|_| MockResponse::ok(pattern(64 * 1024)).slow(1000, CHUNK_DELAY)).await;
+
+    let stream = stream(&server, "videoplayback?itag=140");
+    fn assert_send<T: Send>(t: T) -> T { t }
+    let mut bytes = assert_send(stream.bytes_stream()).await.unwrap();
+    let mut read = Vec::new();
+    while let Some(chunk) = assert_send(bytes.next()).await {
+        read.extend_from_slice(&chunk.unwrap());
+    }
+
+    assert_eq!(read, pattern(64 * 1024));
+    assert_eq!(bytes.buffered(), 0);
+}
+
+#[test_log::test(tokio::test)]
+async fn bytes_stream_falls_back_to_sequenced_requests() {
+    let server = MockServer::start(|request| match request.path.rsplit_once("sq=") {
+        None => MockResponse::status(404),
+        Some((_, "0")) => MockResponse::ok("header,").with_header("Segment-Count", "3"),
+        Some((_, sq)) => MockResponse::ok(format!("segment {},", sq)),
+    }).await;
+
+    let chunks: Vec<_> = stream(&server, "videoplayback?itag=140")
+        .bytes_stream()
+        .await
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    assert_eq!(chunks.concat(), b"header,segment 1,segment 2,");
+
+    // segmented streams cannot be requested in ranges
+    let res = stream(&server, "videoplayback?itag=140").bytes_stream_from(3).await;
+    assert!(matches!(res, Err(Error::Request(e)) if e.status().map(|s| s.as_u16()) == Some(404)));
+}
+
+#[test_log::test(tokio::test)]
+async fn bytes_stream_from_offset() {
+    let server = MockServer::start(|request| MockResponse::ranged(request, &pattern(4096))).await;
+
+    let mut read = Vec::new();
+    stream(&server, "videoplayback?itag=140")
+        .bytes_stream_from(1000)
+        .await
+        .unwrap()
+        .read_to_end(&mut read)
+        .await
+        .unwrap();
+    assert_eq!(read, &pattern(4096)[1000..]);
+    assert_eq!(server.requests()[0].header("Range"), Some("bytes=1000-"));
+
+    // a server, that ignores the range, would send the wrong bytes
+    let server = MockServer::start(|_| MockResponse::ok(pattern(4096))).await;
+    let res = stream(&server, "videoplayback?itag=140").bytes_stream_from(1000).await;
+    assert!(matches!(res, Err(Error::UnexpectedResponse(_))));
+}
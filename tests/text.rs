@@ -0,0 +1,102 @@
+#![cfg(feature = "fetch")]
+
+use proptest::prelude::*;
+use unicode_normalization::is_nfc;
+
+use rustube::text::{decode_html_entities, MAX_FILENAME_BYTES, safe_title_for_filename, sanitize_for_display};
+
+#[macro_use]
+mod common;
+
+/// Characters, that must never end up in a file name.
+const FORBIDDEN: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|', '\u{202e}', '\u{2066}'];
+
+#[test]
+fn decodes_entities() {
+    assert_eq!(decode_html_entities("Tom &amp; Jerry &#39;s &quot;best&quot; &#x1F600;"), "Tom & Jerry 's \"best\" 😀");
+    // unknown and unterminated entities are kept
+    assert_eq!(decode_html_entities("R&B &unknown; &#xZZ; & AT&T"), "R&B &unknown; &#xZZ; & AT&T");
+    assert_eq!(decode_html_entities("&&amp;;"), "&&;");
+    assert!(matches!(decode_html_entities("no entities"), std::borrow::Cow::Borrowed(_)));
+}
+
+#[test]
+fn display() {
+    assert_eq!(sanitize_for_display("evil\u{202e}txt.exe\0 title"), "eviltxt.exe title");
+    assert_eq!(sanitize_for_display("two\nlines\tand\u{7}bell"), "two lines andbell");
+    // the zero width joiner of the family emoji is kept
+    assert_eq!(sanitize_for_display("👨\u{200d}👩\u{200d}👧"), "👨\u{200d}👩\u{200d}👧");
+}
+
+#[test]
+fn filenames() {
+    assert_eq!(safe_title_for_filename("AC/DC: Back in Black?", 200), "AC_DC_ Back in Black_");
+    assert_eq!(safe_title_for_filename("  ...hidden. ", 200), "hidden");
+    assert_eq!(safe_title_for_filename("Tom &amp; Jerry", 200), "Tom & Jerry");
+    assert_eq!(safe_title_for_filename("con", 200), "con_");
+    assert_eq!(safe_title_for_filename("\0\u{202e}", 200), "");
+    // decomposed characters are composed (NFC)
+    assert_eq!(safe_title_for_filename("Cafe\u{301}", 200), "Café");
+}
+
+#[test]
+fn truncates_at_graphemes() {
+    let family = "👨\u{200d}👩\u{200d}👧";
+    let title = format!("a{}{}", family, family);
+
+    // the second family does not fit, and is not cut in half
+    let budget = 1 + family.len() + 3;
+    assert_eq!(safe_title_for_filename(&title, budget), format!("a{}", family));
+    // combining characters stay with their base character
+    assert_eq!(safe_title_for_filename("xe\u{301}\u{323}", 3), "x");
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn safe_filenames(title in any::<String>(), max_bytes in 0..MAX_FILENAME_BYTES) {
+        let filename = safe_title_for_filename(&title, max_bytes);
+
+        prop_assert!(std::str::from_utf8(filename.as_bytes()).is_ok());
+        prop_assert!(filename.len() <= max_bytes, "{:?} is longer than {}", filename, max_bytes);
+        prop_assert!(!filename.chars().any(|c| c.is_control() || FORBIDDEN.contains(&c)), "{:?}", filename);
+        prop_assert!(is_nfc(&filename), "{:?}", filename);
+        prop_assert!(!filename.starts_with('.') && !filename.ends_with(['.', ' ']), "{:?}", filename);
+    }
+
+    #[test]
+    fn displayable(text in r"(?s).{0,64}(&[a-z#0-9]{0,6};?)?.{0,64}") {
+        let displayed = sanitize_for_display(&text);
+
+        prop_assert!(!displayed.chars().any(char::is_control), "{:?}", displayed);
+        prop_assert!(!displayed.contains(['\u{202a}', '\u{202b}', '\u{202c}', '\u{202d}', '\u{202e}']), "{:?}", displayed);
+    }
+}
+
+#[cfg(feature = "download")]
+#[test_log::test(tokio::test)]
+async fn download_filename_from_title() {
+    use common::*;
+    use rustube::SimpleDownloadOptions;
+
+    let server = MockServer::start(|_| MockResponse::ok(vec![7; 100])).await;
+    let dir = temp_dir("text");
+
+    let video = |title: &str| {
+        let mut player_response = json_fixture("multi_audio_player_response.json");
+        player_response["videoDetails"]["title"] = title.into();
+        with_streams_at(video_from_player_response(player_response), &server, "stream")
+    };
+    let options = || SimpleDownloadOptions::new().target_dir(&dir).filename_from_title(true);
+
+    let path = video("Tom &amp; Jerry: \u{202e}Part 1/2").download_with(options()).await.unwrap();
+    assert_eq!(path, dir.join("Tom & Jerry_ Part 1_2.mp4"));
+    assert_eq!(std::fs::read(&path).unwrap(), vec![7; 100]);
+
+    // nothing usable is left of the title
+    let path = video("\0\0").download_with(options()).await.unwrap();
+    assert_eq!(path, dir.join("fDzQ3kUg8Ss.mp4"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
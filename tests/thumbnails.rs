@@ -0,0 +1,200 @@
+#![cfg(feature = "fetch")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{ThumbnailQuality, VideoDetails};
+use rustube::video_info::player_response::playability_status::{LiveStreamAbility, PlayerErrorMessageRenderer};
+use rustube::video_info::player_response::video_details::{Thumbnail, Thumbnails};
+
+#[macro_use]
+mod common;
+
+const SHAPES: [&str; 6] = ["videoDetails", "microformat", "errorScreen", "offlineSlate", "bareArray", "channelWithoutAvatar"];
+
+fn shape(name: &str) -> Value {
+    json_fixture("thumbnails.json")[name].take()
+}
+
+fn thumbnails(name: &str) -> Thumbnails {
+    serde_json::from_value(shape(name)).unwrap()
+}
+
+fn thumbnail(url: &str, width: u64, height: u64) -> Thumbnail {
+    Thumbnail { width, height, url: url.to_owned() }
+}
+
+#[test]
+fn deserializes_all_shapes() {
+    assert_eq!(thumbnails("videoDetails").len(), 3);
+    assert_eq!(thumbnails("microformat")[0], thumbnail("https://i.ytimg.com/vi/fDzQ3kUg8Ss/maxresdefault.jpg", 1280, 720));
+    assert_eq!(thumbnails("errorScreen")[0], thumbnail("//s.ytimg.com/yts/img/meh7-vflGevej7.png", 140, 100));
+    assert_eq!(thumbnails("offlineSlate")[1], thumbnail("https://i.ytimg.com/vi/fDzQ3kUg8Ss/maxresdefault_live.jpg", 0, 0));
+    assert_eq!(thumbnails("bareArray").len(), 2);
+    assert!(thumbnails("channelWithoutAvatar").is_empty());
+    assert!(serde_json::from_value::<Thumbnails>(Value::Null).unwrap().is_empty());
+}
+
+#[test]
+fn serializes_wrapped() {
+    for name in SHAPES {
+        let thumbnails = thumbnails(name);
+        let serialized = serde_json::to_value(&thumbnails).unwrap();
+        assert!(serialized["thumbnails"].is_array(), "{}", name);
+        assert_eq!(serde_json::from_value::<Thumbnails>(serialized).unwrap(), thumbnails, "{}", name);
+    }
+}
+
+#[test]
+fn best_and_smallest() {
+    let video_details = thumbnails("videoDetails");
+    assert_eq!(video_details.best().unwrap().url, "https://i.ytimg.com/vi/fDzQ3kUg8Ss/hqdefault.jpg");
+    assert_eq!(video_details.smallest().unwrap().url, "https://i.ytimg.com/vi/fDzQ3kUg8Ss/default.jpg");
+
+    // without sizes, YouTube's order from small to large is kept
+    let offline_slate = thumbnails("offlineSlate");
+    assert_eq!(offline_slate.best().unwrap().url, "https://i.ytimg.com/vi/fDzQ3kUg8Ss/maxresdefault_live.jpg");
+    assert_eq!(offline_slate.smallest().unwrap().url, "https://i.ytimg.com/vi/fDzQ3kUg8Ss/hqdefault_live.jpg");
+
+    assert_eq!(thumbnails("channelWithoutAvatar").best(), None);
+    assert_eq!(thumbnails("channelWithoutAvatar").smallest(), None);
+}
+
+#[test]
+fn best_of_slice() {
+    let listed = [
+        thumbnail("https://i.ytimg.com/vi/fDzQ3kUg8Ss/mqdefault.jpg", 320, 180),
+        thumbnail("https://i.ytimg.com/vi/fDzQ3kUg8Ss/sddefault.jpg", 640, 480),
+        thumbnail("https://i.ytimg.com/vi/fDzQ3kUg8Ss/maxresdefault.jpg", 1280, 720),
+        thumbnail("https://i.ytimg.com/vi/fDzQ3kUg8Ss/hqdefault.jpg", 480, 360),
+    ];
+    assert_eq!(Thumbnail::best(&listed).unwrap().url, "https://i.ytimg.com/vi/fDzQ3kUg8Ss/maxresdefault.jpg");
+    // by area, not by width
+    assert_eq!(
+        Thumbnail::best(&[thumbnail("wide", 1000, 10), thumbnail("square", 200, 200)]).unwrap().url,
+        "square",
+    );
+    assert_eq!(Thumbnail::best(&[]), None);
+    assert_eq!(Thumbnail::best(&thumbnails("videoDetails")), thumbnails("videoDetails").best());
+}
+
+#[test]
+fn absolute_url() {
+    assert_eq!(
+        thumbnails("errorScreen")[0].absolute_url().unwrap().as_str(),
+        "https://s.ytimg.com/yts/img/meh7-vflGevej7.png",
+    );
+    assert_eq!(
+        thumbnail("/img/avatar.jpg", 0, 0).absolute_url().unwrap().as_str(),
+        "https://www.youtube.com/img/avatar.jpg",
+    );
+}
+
+#[test]
+fn video_thumbnail_url() {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    player_response["videoDetails"]["thumbnail"] = shape("videoDetails");
+    let video = video_from_player_response(player_response.clone());
+    assert_eq!(video.thumbnail_url().as_str(), "https://i.ytimg.com/vi/fDzQ3kUg8Ss/hqdefault.jpg");
+
+    // without thumbnails, the url of the maxres thumbnail is constructed
+    player_response["videoDetails"]["thumbnail"] = shape("channelWithoutAvatar");
+    let video = video_from_player_response(player_response);
+    assert_eq!(video.thumbnail_url(), video.id().thumbnail_url(ThumbnailQuality::MaxRes));
+}
+
+#[cfg(feature = "download")]
+#[test_log::test(tokio::test)]
+async fn download() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/vi/fDzQ3kUg8Ss/maxresdefault.jpg" => MockResponse::ok(&b"\xFF\xD8jpeg"[..]),
+        _ => MockResponse::status(404),
+    }).await;
+    let client = reqwest::Client::new();
+    let path = std::env::temp_dir().join(format!("rustube_thumbnail_{}.jpg", std::process::id()));
+
+    let url = server.url.join("vi/fDzQ3kUg8Ss/maxresdefault.jpg").unwrap();
+    thumbnail(url.as_str(), 1280, 720).download(&client, &path).await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"\xFF\xD8jpeg");
+    std::fs::remove_file(&path).unwrap();
+
+    let url = server.url.join("vi/fDzQ3kUg8Ss/sddefault.jpg").unwrap();
+    let result = thumbnail(url.as_str(), 640, 480).download(&client, &path).await;
+    assert!(matches!(result, Err(rustube::Error::Request(_))), "{:?}", result);
+    assert!(!path.exists());
+}
+
+#[test]
+fn video_details_accept_all_shapes() {
+    for name in SHAPES {
+        let mut video_details = json_fixture("multi_audio_player_response.json")["videoDetails"].take();
+        video_details["thumbnail"] = shape(name);
+        let video_details: VideoDetails = serde_json::from_value(video_details).unwrap();
+        assert_eq!(video_details.thumbnails, thumbnails(name), "{}", name);
+    }
+
+    let mut video_details = json_fixture("multi_audio_player_response.json")["videoDetails"].take();
+    video_details.as_object_mut().unwrap().remove("thumbnail");
+    let video_details: VideoDetails = serde_json::from_value(video_details).unwrap();
+    assert!(video_details.thumbnails.is_empty());
+}
+
+#[test]
+fn error_screen() {
+    let mut renderer = json_fixture("private_player_response.json")["playabilityStatus"]["errorScreen"]["playerErrorMessageRenderer"].take();
+    let renderer_from_fixture: PlayerErrorMessageRenderer = serde_json::from_value(renderer.clone()).unwrap();
+    assert_eq!(renderer_from_fixture.thumbnails, thumbnails("errorScreen"));
+
+    renderer["thumbnail"] = shape("channelWithoutAvatar");
+    let renderer: PlayerErrorMessageRenderer = serde_json::from_value(renderer).unwrap();
+    assert!(renderer.thumbnails.is_empty());
+}
+
+#[test]
+fn offline_slate() {
+    let live_streamability = json!({
+        "liveStreamabilityRenderer": {
+            "videoId": "fDzQ3kUg8Ss",
+            "offlineSlate": {
+                "liveStreamOfflineSlateRenderer": {
+                    "scheduledStartTime": "1700000000",
+                    "mainText": { "simpleText": "Live in 3 days" },
+                    "subtitleText": { "simpleText": "November 14, 11:13 PM" },
+                    "thumbnail": shape("offlineSlate"),
+                }
+            },
+            "pollDelayMs": "15000",
+        }
+    });
+    let live_streamability: LiveStreamAbility = serde_json::from_value(live_streamability).unwrap();
+
+    let serialized = serde_json::to_value(&live_streamability).unwrap();
+    let thumbnail = &serialized["liveStreamabilityRenderer"]["offlineSlate"]["liveStreamOfflineSlateRenderer"]["thumbnail"];
+    assert_eq!(serde_json::from_value::<Thumbnails>(thumbnail.clone()).unwrap(), thumbnails("offlineSlate"));
+}
+
+#[cfg(feature = "microformat")]
+#[test]
+fn microformat() {
+    use rustube::video_info::player_response::microformat::PlayerMicroformatRenderer;
+
+    for name in SHAPES {
+        // the dates are deserialized as borrowed strings, so `from_value` can't be used
+        let renderer: PlayerMicroformatRenderer = serde_json::from_str(&json!({
+            "availableCountries": ["DE"],
+            "category": "Music",
+            "description": { "simpleText": "" },
+            "externalChannelId": "UC1yBKRuGpC1tSM73A0ZjYjQ",
+            "isUnlisted": false,
+            "lengthSeconds": "615",
+            "ownerChannelName": "rustube",
+            "ownerProfileUrl": "http://www.youtube.com/@rustube",
+            "publishDate": "2024-05-01",
+            "thumbnail": shape(name),
+            "title": { "simpleText": "Multi-language audio sample" },
+            "uploadDate": "2024-05-01",
+            "viewCount": "1034829"
+        }).to_string()).unwrap();
+        assert_eq!(renderer.thumbnails, thumbnails(name), "{}", name);
+    }
+}
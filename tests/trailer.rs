@@ -0,0 +1,135 @@
+#![cfg(feature = "descramble")]
+
+use serde_json::{json, Value};
+
+use common::*;
+use rustube::{Id, PlayerResponse, VideoFetcher};
+use rustube::video_info::player_response::playability_status::{PlayabilityKind, PlayabilityStatus};
+
+#[macro_use]
+mod common;
+
+const RENTAL: &str = "rental_player_response.json";
+const RENTAL_ID: &str = "Rn7dWYvZQ0M";
+const TRAILER_ID: &str = "Tr4iLerV1d0";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+
+fn playability_status(player_response: &Value) -> PlayabilityStatus {
+    // some fields borrow from the input, so they can't be deserialized from a `Value`
+    serde_json::from_str(&player_response["playabilityStatus"].to_string()).unwrap()
+}
+
+fn trailer_renderer(player_response: &mut Value) -> &mut Value {
+    &mut player_response["playabilityStatus"]["errorScreen"]["ypcTrailerRenderer"]
+}
+
+fn fetcher(server: &MockServer) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(RENTAL_ID).unwrap().into_owned())
+        .unwrap()
+        .with_base_url(server.url.clone())
+}
+
+#[test]
+fn trailer_player_response() {
+    let status = playability_status(&json_fixture(RENTAL));
+    assert_eq!(status.kind(), PlayabilityKind::Unplayable);
+    assert_eq!(status.reason().as_deref(), Some("This video requires payment to watch."));
+
+    let trailer: PlayerResponse = status.trailer().unwrap();
+    assert_eq!(trailer.video_details.video_id.as_str(), TRAILER_ID);
+    assert!(matches!(trailer.playability_status, PlayabilityStatus::Ok { .. }));
+    let streaming_data = trailer.streaming_data.unwrap();
+    assert_eq!(streaming_data.formats.len() + streaming_data.adaptive_formats.len(), 3);
+    assert_eq!(status.trailer_renderer().unwrap().trailer_video_id.as_ref().unwrap().as_str(), TRAILER_ID);
+
+    // serializing keeps the trailer
+    let serialized = serde_json::to_string(&status).unwrap();
+    assert_eq!(serde_json::from_str::<PlayabilityStatus>(&serialized).unwrap(), status);
+}
+
+#[test]
+fn unserialized_trailer_player_response() {
+    let mut player_response = json_fixture(RENTAL);
+    let renderer = trailer_renderer(&mut player_response);
+    let trailer: Value = serde_json::from_str(renderer["playerResponse"].as_str().unwrap()).unwrap();
+    renderer.as_object_mut().unwrap().remove("playerResponse");
+    renderer["unserializedPlayerResponse"] = trailer;
+
+    let status = playability_status(&player_response);
+    assert_eq!(status.trailer().unwrap().video_details.video_id.as_str(), TRAILER_ID);
+}
+
+#[test]
+fn no_trailer() {
+    assert_eq!(playability_status(&json_fixture("multi_audio_player_response.json")).trailer(), None);
+    assert_eq!(playability_status(&json_fixture("private_player_response.json")).trailer(), None);
+
+    // only the id of the trailer
+    let mut player_response = json_fixture(RENTAL);
+    trailer_renderer(&mut player_response).as_object_mut().unwrap().remove("playerResponse");
+    let status = playability_status(&player_response);
+    assert_eq!(status.trailer(), None);
+    assert!(status.trailer_renderer().unwrap().trailer_video_id.is_some());
+
+    // a trailer, that can't be deserialized, doesn't affect the video
+    let mut player_response = json_fixture(RENTAL);
+    trailer_renderer(&mut player_response)["playerResponse"] = json!("{\"videoDetails\": 42}");
+    assert_eq!(playability_status(&player_response).trailer(), None);
+}
+
+#[test_log::test(tokio::test)]
+async fn fetch_included_trailer() {
+    let server = mock_youtube(json_fixture(RENTAL), BASE_JS, |_| Value::Null).await;
+
+    let trailer = fetcher(&server).fetch_trailer().await.unwrap().unwrap();
+    assert_eq!(trailer.video_details().video_id.as_str(), TRAILER_ID);
+    let itags: Vec<u64> = trailer.streams().iter().map(|stream| stream.itag).collect();
+    assert_eq!(itags, [18, 137, 140]);
+    assert!(trailer.descramble_errors().is_empty());
+
+    // all streams of the trailer are pre-signed, so base.js is not needed
+    assert!(server.requests().iter().all(|request| request.path != BASE_JS_PATH), "{:?}", server.requests());
+}
+
+#[test_log::test(tokio::test)]
+async fn fetch_trailer_by_id() {
+    let mut rental = json_fixture(RENTAL);
+    let renderer = trailer_renderer(&mut rental).take();
+    rental["playabilityStatus"]["errorScreen"] = json!({
+        "playerLegacyDesktopYpcTrailerRenderer": { "trailerVideoId": renderer["trailerVideoId"] }
+    });
+    let trailer: Value = serde_json::from_str(renderer["playerResponse"].as_str().unwrap()).unwrap();
+
+    let api_trailer = trailer.clone();
+    let server = MockServer::start(move |request| {
+        let path = request.path.as_str();
+        if path.starts_with("/watch") {
+            let player_response = match path.contains(TRAILER_ID) {
+                true => &trailer,
+                false => &rental,
+            };
+            MockResponse::ok(format!(
+                "<html><script>var ytInitialPlayerResponse = {};</script>\
+                <script src=\"{}\"></script></html>",
+                player_response, BASE_JS_PATH,
+            ))
+        } else if path == BASE_JS_PATH {
+            MockResponse::ok(BASE_JS)
+        } else if path.starts_with("/youtubei/v1/player") {
+            MockResponse::json(&api_trailer)
+        } else {
+            MockResponse::status(404)
+        }
+    }).await;
+
+    let trailer = fetcher(&server).fetch_trailer().await.unwrap().unwrap();
+    assert_eq!(trailer.video_details().video_id.as_str(), TRAILER_ID);
+    assert_eq!(trailer.streams().len(), 3);
+    assert!(server.requests().iter().any(|request| request.path.contains(&format!("v={}", TRAILER_ID))));
+}
+
+#[test_log::test(tokio::test)]
+async fn playable_videos_have_no_trailer() {
+    let server = mock_youtube(json_fixture("multi_audio_player_response.json"), BASE_JS, |_| Value::Null).await;
+    assert!(fetcher(&server).fetch_trailer().await.unwrap().is_none());
+}
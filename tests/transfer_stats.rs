@@ -0,0 +1,163 @@
+#![cfg(feature = "download")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::AsyncReadExt;
+
+use common::*;
+use rustube::{Error, Id, Stream, TransferStats, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+const LEN: usize = 4000;
+const CHUNK: usize = 1000;
+
+fn watch_html() -> String {
+    format!(
+        "<html><script>var ytInitialPlayerResponse = {};</script>\
+        <script src=\"{}\"></script></html>",
+        json_fixture("multi_audio_player_response.json"), BASE_JS_PATH,
+    )
+}
+
+/// A server, that serves the watch page, base.js, and the body of all streams at `/stream`, in
+/// chunks of [`CHUNK`] bytes.
+async fn server() -> MockServer {
+    let watch_html = watch_html();
+    MockServer::start(move |request| {
+        let path = request.path.as_str();
+        if path.starts_with("/watch") {
+            MockResponse::ok(watch_html.clone())
+        } else if path == BASE_JS_PATH {
+            MockResponse::ok(BASE_JS)
+        } else if path.starts_with("/stream") {
+            MockResponse::ok(vec![7; LEN]).slow(CHUNK, Duration::from_millis(5))
+        } else {
+            MockResponse::status(404)
+        }
+    }).await
+}
+
+/// The audio stream of the multi audio fixture, pointing to `/stream` on `server`, with a
+/// content length of [`LEN`].
+fn stream(server: &MockServer, transfer_stats: &Arc<TransferStats>) -> Stream {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    set_content_length(&mut player_response, LEN);
+
+    with_streams_at(video_from_player_response(player_response), server, "stream")
+        .streams()
+        .iter()
+        .find(|stream| stream.itag == 140)
+        .unwrap()
+        .clone()
+        .with_transfer_stats(Arc::clone(transfer_stats))
+}
+
+#[test_log::test(tokio::test)]
+async fn counts_metadata_and_media() {
+    let server = server().await;
+    let stats = Arc::new(TransferStats::new());
+
+    let video = VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap().into_owned())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_transfer_stats(Arc::clone(&stats))
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap();
+    assert_eq!(stats.request_count(), 2);
+    assert_eq!(stats.metadata_bytes(), (watch_html().len() + BASE_JS.len()) as u64);
+    assert_eq!(stats.media_bytes(), 0);
+
+    // the streams of the video share the counters of the fetcher
+    let video = with_streams_at(video, &server, "stream");
+    let stream = video.streams().iter().find(|stream| stream.itag == 18).unwrap();
+    let mut body = Vec::new();
+    stream.open_reader().await.unwrap().read_to_end(&mut body).await.unwrap();
+    assert_eq!(body.len(), LEN);
+    assert_eq!(stats.request_count(), 3);
+    assert_eq!(stats.media_bytes(), LEN as u64);
+
+    stats.reset();
+    assert_eq!((stats.request_count(), stats.metadata_bytes(), stats.media_bytes()), (0, 0, 0));
+}
+
+#[test_log::test(tokio::test)]
+async fn download_returns_the_transferred_bytes() {
+    let server = server().await;
+    let stats = Arc::new(TransferStats::new());
+    let dir = temp_dir("download");
+
+    let summary = stream(&server, &stats).download_to_with_summary(dir.join("first.m4a")).await.unwrap();
+    assert_eq!(summary.bytes, LEN as u64);
+    assert_eq!(summary.path, dir.join("first.m4a"));
+    assert_eq!(stats.media_bytes(), LEN as u64);
+    assert_eq!(stats.request_count(), 1);
+
+    // downloads sharing the counters add up
+    stream(&server, &stats).download_to(dir.join("second.m4a")).await.unwrap();
+    assert_eq!(stats.media_bytes(), 2 * LEN as u64);
+    assert_eq!(stats.request_count(), 2);
+    assert_eq!(stats.metadata_bytes(), 0);
+}
+
+#[test_log::test(tokio::test)]
+async fn quota_boundary() {
+    let server = server().await;
+    let dir = temp_dir("quota");
+
+    // exactly reaching the cap is fine
+    let stats = Arc::new(TransferStats::limit(LEN as u64));
+    assert_eq!(stats.media_bytes_cap(), Some(LEN as u64));
+    stream(&server, &stats).download_to(dir.join("exact.m4a")).await.unwrap();
+    assert_eq!(stats.media_bytes(), LEN as u64);
+
+    // the next download crosses the cap with its first chunk
+    let path = dir.join("crossed.m4a");
+    let err = stream(&server, &stats).download_to(&path).await.unwrap_err();
+    match err {
+        Error::QuotaExceeded { cap, media_bytes } => {
+            assert_eq!(cap, LEN as u64);
+            assert!(media_bytes > cap && media_bytes <= cap + LEN as u64, "{}", media_bytes);
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+    assert!(!path.exists());
+    assert!(!dir.join("crossed.m4a.part").exists());
+
+    // after a reset, the cap applies from zero again
+    stats.reset();
+    stream(&server, &stats).download_to(&path).await.unwrap();
+    assert_eq!(stats.media_bytes(), LEN as u64);
+
+    // a cap in the middle of the stream aborts the download in between chunks
+    let stats = Arc::new(TransferStats::limit(LEN as u64 - 1));
+    std::fs::remove_file(&path).unwrap();
+    let err = stream(&server, &stats).download_to(&path).await.unwrap_err();
+    assert!(matches!(err, Error::QuotaExceeded { media_bytes, .. } if media_bytes == LEN as u64), "{:?}", err);
+    assert!(!path.exists());
+}
+
+#[test_log::test(tokio::test)]
+async fn quota_aborts_reader() {
+    let server = server().await;
+    let stats = Arc::new(TransferStats::limit(CHUNK as u64));
+
+    let mut body = Vec::new();
+    let err = stream(&server, &stats)
+        .open_reader()
+        .await
+        .unwrap()
+        .read_to_end(&mut body)
+        .await
+        .unwrap_err();
+    let err = err.into_inner().unwrap().downcast::<Error>().unwrap();
+    assert!(matches!(*err, Error::QuotaExceeded { cap, .. } if cap == CHUNK as u64), "{:?}", err);
+    assert!(body.len() <= CHUNK, "{}", body.len());
+}
@@ -0,0 +1,224 @@
+#![cfg(feature = "download")]
+
+use tokio::io::AsyncReadExt;
+
+use common::*;
+use rustube::fetcher::recommended_headers_for;
+use rustube::reqwest::header::{HeaderMap, HeaderValue};
+use rustube::{Id, InnertubeClient, UserAgentPolicy, Video, VideoFetcher};
+
+#[macro_use]
+mod common;
+
+const VIDEO_ID: &str = "fDzQ3kUg8Ss";
+const BASE_JS: &str = "var config={signatureTimestamp:19876};";
+const FIXED: &str = "Mozilla/5.0 (X11; Linux x86_64; rv:133.0) Gecko/20100101 Firefox/133.0";
+
+/// A server, that serves the body of all streams at `/stream`.
+async fn server() -> MockServer {
+    MockServer::start(|request| {
+        let path = request.path.as_str();
+        if path.starts_with("/watch") {
+            MockResponse::ok(format!(
+                "<html><script>var ytInitialPlayerResponse = {};</script>\
+                <script src=\"{}\"></script></html>",
+                json_fixture("multi_audio_player_response.json"), BASE_JS_PATH,
+            ))
+        } else if path == BASE_JS_PATH {
+            MockResponse::ok(BASE_JS)
+        } else if path.starts_with("/youtubei/v1/player") {
+            MockResponse::json(&json_fixture("multi_audio_player_response.json"))
+        } else if path.starts_with("/stream") {
+            MockResponse::ok(vec![0; 1024])
+        } else {
+            MockResponse::status(404)
+        }
+    }).await
+}
+
+/// A fetcher, that requests the player endpoint, instead of using the player response of the
+/// watch page.
+fn fetcher(server: &MockServer) -> VideoFetcher {
+    VideoFetcher::from_id(Id::from_str(VIDEO_ID).unwrap())
+        .unwrap()
+        .with_base_url(server.url.clone())
+        .with_innertube_clients(vec![InnertubeClient::WebEmbedded])
+}
+
+/// Fetches the video, and reads its first stream from the mock server.
+async fn fetch_and_download(server: &MockServer, fetcher: VideoFetcher) -> Video {
+    let video = fetcher
+        .fetch()
+        .await
+        .unwrap()
+        .descramble()
+        .unwrap();
+
+    let video = with_streams_at(video, server, "stream");
+    let mut body = Vec::new();
+    video.streams()[0].open_reader().await.unwrap().read_to_end(&mut body).await.unwrap();
+    assert_eq!(body.len(), 1024);
+    video
+}
+
+/// The value of the header `name` of all requests, by path (without the query).
+fn headers(server: &MockServer, name: &str) -> Vec<(String, Option<String>)> {
+    server
+        .requests()
+        .iter()
+        .map(|request| (
+            request.path.split('?').next().unwrap().to_owned(),
+            request.header(name).map(str::to_owned),
+        ))
+        .collect()
+}
+
+#[test_log::test(tokio::test)]
+async fn fixed_user_agent_is_used_for_all_requests() {
+    let server = server().await;
+    let policy = UserAgentPolicy::Fixed(FIXED.to_owned());
+
+    let video = fetch_and_download(&server, fetcher(&server).with_user_agent_policy(&policy)).await;
+
+    assert_eq!(video.video_info().user_agent(), Some(FIXED));
+    let fixed = Some(FIXED.to_owned());
+    assert_eq!(headers(&server, "user-agent"), [
+        ("/watch".to_owned(), fixed.clone()),
+        (BASE_JS_PATH.to_owned(), fixed.clone()),
+        ("/youtubei/v1/player".to_owned(), fixed.clone()),
+        ("/stream".to_owned(), fixed.clone()),
+    ]);
+
+    let player_request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path.starts_with("/youtubei/v1/player"))
+        .unwrap();
+    assert_eq!(player_request.json()["context"]["client"]["userAgent"], FIXED);
+    // only ModernChrome sends client hints
+    assert!(headers(&server, "sec-ch-ua").iter().all(|(_, value)| value.is_none()));
+}
+
+#[test_log::test(tokio::test)]
+async fn modern_chrome_sends_client_hints() {
+    let server = server().await;
+
+    let video = fetch_and_download(
+        &server,
+        fetcher(&server).with_user_agent_policy(&UserAgentPolicy::ModernChrome),
+    ).await;
+
+    let user_agent = video.video_info().user_agent().unwrap().to_owned();
+    assert!(user_agent.contains("Chrome/"), "{}", user_agent);
+    for (path, value) in headers(&server, "user-agent") {
+        assert_eq!(value.as_ref(), Some(&user_agent), "{}", path);
+    }
+    for name in ["sec-ch-ua", "sec-ch-ua-mobile", "sec-ch-ua-platform"] {
+        for (path, value) in headers(&server, name) {
+            assert!(value.is_some(), "{} is missing for {}", name, path);
+        }
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn rotates_between_fetchers() {
+    let server = server().await;
+    let policy = UserAgentPolicy::RotatePerRequest(vec!["first/1.0".to_owned(), "second/1.0".to_owned()]);
+
+    let first = fetcher(&server).with_user_agent_policy(&policy).fetch_info().await.unwrap();
+    let second = fetcher(&server).with_user_agent_policy(&policy).fetch_info().await.unwrap();
+
+    let mut user_agents = [first.user_agent().unwrap(), second.user_agent().unwrap()];
+    user_agents.sort_unstable();
+    assert_eq!(user_agents, ["first/1.0", "second/1.0"]);
+
+    let user_agents: Vec<_> = headers(&server, "user-agent").into_iter().map(|(_, value)| value.unwrap()).collect();
+    assert_eq!(user_agents, [first.user_agent().unwrap(), second.user_agent().unwrap()]);
+}
+
+#[test_log::test(tokio::test)]
+async fn android_client_keeps_its_user_agent() {
+    let server = server().await;
+    let policy = UserAgentPolicy::Fixed(FIXED.to_owned());
+
+    let video = fetch_and_download(
+        &server,
+        fetcher(&server)
+            .with_innertube_clients(vec![InnertubeClient::Android])
+            .with_user_agent_policy(&policy),
+    ).await;
+
+    let android = InnertubeClient::Android.user_agent();
+    assert_eq!(video.video_info().user_agent(), Some(android));
+    assert_eq!(headers(&server, "user-agent"), [
+        ("/watch".to_owned(), Some(FIXED.to_owned())),
+        (BASE_JS_PATH.to_owned(), Some(FIXED.to_owned())),
+        ("/youtubei/v1/player".to_owned(), Some(android.to_owned())),
+        ("/stream".to_owned(), Some(android.to_owned())),
+    ]);
+}
+
+#[test_log::test(tokio::test)]
+async fn no_policy() {
+    let server = server().await;
+
+    let video_info = fetcher(&server).fetch_info().await.unwrap();
+
+    assert_eq!(video_info.user_agent(), None);
+    assert_eq!(headers(&server, "user-agent")[0].1.as_deref(), Some("Mozilla/5.0"));
+}
+
+#[test_log::test(tokio::test)]
+async fn custom_headers_are_used_for_all_requests() {
+    let server = server().await;
+    let mut custom = HeaderMap::new();
+    custom.insert("referer", HeaderValue::from_static("https://www.youtube.com/"));
+    custom.insert("user-agent", HeaderValue::from_static("custom/1.0"));
+
+    fetch_and_download(
+        &server,
+        fetcher(&server)
+            .with_user_agent_policy(&UserAgentPolicy::Fixed(FIXED.to_owned()))
+            .with_headers(custom),
+    ).await;
+
+    let paths = ["/watch", BASE_JS_PATH, "/youtubei/v1/player", "/stream"];
+    let expected = |value: &str| paths
+        .iter()
+        .map(|path| (path.to_string(), Some(value.to_owned())))
+        .collect::<Vec<_>>();
+    assert_eq!(headers(&server, "referer"), expected("https://www.youtube.com/"));
+    // the custom headers take precedence over the user agent policy
+    assert_eq!(headers(&server, "user-agent"), expected("custom/1.0"));
+    // the default headers of the client are still sent
+    assert_eq!(headers(&server, "accept-language"), expected("en-US,en"));
+}
+
+#[test]
+fn recommended_headers_for_clients() {
+    let web = recommended_headers_for(InnertubeClient::Web);
+    assert_eq!(web["user-agent"], InnertubeClient::Web.user_agent());
+    assert_eq!(web["accept-language"], "en-US,en");
+    assert_eq!(web["origin"], "https://www.youtube.com");
+    assert_eq!(web["referer"], "https://www.youtube.com/");
+
+    for client in [InnertubeClient::Android, InnertubeClient::Ios] {
+        let headers = recommended_headers_for(client);
+        assert_eq!(headers["user-agent"], client.user_agent());
+        assert!(!headers.contains_key("origin"));
+        assert!(!headers.contains_key("referer"));
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn recommended_headers_are_used_for_streams() {
+    let server = server().await;
+
+    fetch_and_download(
+        &server,
+        fetcher(&server).with_headers(recommended_headers_for(InnertubeClient::Android)),
+    ).await;
+
+    let android = Some(InnertubeClient::Android.user_agent().to_owned());
+    assert_eq!(headers(&server, "user-agent").last().unwrap(), &("/stream".to_owned(), android));
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "fetch")]
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use common::*;
+use rustube::VideoDetails;
+
+#[macro_use]
+mod common;
+
+fn video_details() -> serde_json::Value {
+    json_fixture("multi_audio_player_response.json")["videoDetails"].take()
+}
+
+#[test]
+fn typed_accessors() {
+    let mut video_details = video_details();
+    video_details["keywords"] = json!(["rustube", "audio tracks"]);
+    let video_details: VideoDetails = serde_json::from_value(video_details).unwrap();
+
+    assert_eq!(video_details.length(), Duration::from_secs(615));
+    assert_eq!(video_details.views(), 1_034_829);
+    assert_eq!(video_details.keywords(), ["rustube", "audio tracks"]);
+    assert_eq!(
+        video_details.thumbnail_url_max().unwrap().as_str(),
+        "https://i.ytimg.com/vi/fDzQ3kUg8Ss/default.jpg",
+    );
+}
+
+#[test]
+fn serializes_numbers() {
+    let video_details: VideoDetails = serde_json::from_value(video_details()).unwrap();
+
+    let serialized = serde_json::to_value(&video_details).unwrap();
+    assert_eq!(serialized["lengthSeconds"], 615);
+    assert_eq!(serialized["viewCount"], 1_034_829);
+    // both, YouTube's strings and the serialized numbers, are accepted
+    assert_eq!(serde_json::from_value::<VideoDetails>(serialized).unwrap(), video_details);
+}
+
+#[test]
+fn thumbnail_url_max() {
+    let mut video_details = video_details();
+    video_details["thumbnail"] = json_fixture("thumbnails.json")["videoDetails"].take();
+    let video_details: VideoDetails = serde_json::from_value(video_details).unwrap();
+    assert_eq!(
+        video_details.thumbnail_url_max().unwrap().as_str(),
+        "https://i.ytimg.com/vi/fDzQ3kUg8Ss/hqdefault.jpg",
+    );
+
+    // relative URLs are made absolute
+    let mut video_details = video_details;
+    video_details.thumbnails = serde_json::from_value(json_fixture("thumbnails.json")["errorScreen"].take()).unwrap();
+    assert_eq!(
+        video_details.thumbnail_url_max().unwrap().as_str(),
+        "https://s.ytimg.com/yts/img/meh7-vflGevej7.png",
+    );
+
+    video_details.thumbnails.clear();
+    assert_eq!(video_details.thumbnail_url_max(), None);
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "download")]
+
+use std::time::Duration;
+
+use common::*;
+use rustube::{Error, HashAlgorithm, SimpleDownloadOptions, Video};
+use rustube::stream::DEFAULT_WRITE_BUFFER_SIZE;
+
+#[macro_use]
+mod common;
+
+/// Not a multiple of any of the chunk or buffer sizes.
+const LEN: usize = (1 << 20) + 17;
+
+/// The multi audio fixture, with all streams pointing to `server`, and a content length of `len`.
+fn video(server: &MockServer, len: usize) -> Video {
+    let mut player_response = json_fixture("multi_audio_player_response.json");
+    set_content_length(&mut player_response, len);
+    with_streams_at(video_from_player_response(player_response), server, "stream")
+}
+
+#[test_log::test(tokio::test)]
+async fn output_is_independent_of_the_buffer_size() {
+    // small network chunks, so most of them end up in the buffer
+    let server = MockServer::start(|_| MockResponse::ok(pattern(LEN)).slow(3000, Duration::ZERO)).await;
+    let video = video(&server, LEN);
+    let expected_digest = HashAlgorithm::Sha256.digest(&pattern(LEN));
+
+    for size in [0, 1, 4096, DEFAULT_WRITE_BUFFER_SIZE, 4 * LEN] {
+        let dir = temp_dir(&size.to_string());
+        let options = SimpleDownloadOptions::new()
+            .target_dir(&dir)
+            .hash(HashAlgorithm::Sha256)
+            .write_buffer_size(size);
+        let download = video.download_with_summary(options).await.unwrap();
+
+        assert_eq!(download.bytes, LEN as u64, "{}", size);
+        assert_eq!(download.digest.as_deref(), Some(expected_digest.as_str()), "{}", size);
+        assert!(std::fs::read(&download.path).unwrap() == pattern(LEN), "{}", size);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn buffer_is_flushed_before_the_length_check() {
+    let server = MockServer::start(|_| MockResponse::ok(pattern(LEN))).await;
+    // announces more bytes than the server sends
+    let video = video(&server, LEN + 1);
+    let dir = temp_dir("length");
+
+    let options = SimpleDownloadOptions::new()
+        .target_dir(&dir)
+        .keep_partial(true)
+        .write_buffer_size(4 * LEN);
+    let result = video.download_with_summary(options).await;
+
+    match result {
+        Err(Error::UnexpectedResponse(message)) => {
+            assert_eq!(message, format!("the download ended after {} of {} bytes", LEN, LEN + 1));
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+    assert!(std::fs::read(dir.join("fDzQ3kUg8Ss.mp4.part")).unwrap() == pattern(LEN));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[cfg(feature = "callback")]
+#[test_log::test(tokio::test)]
+async fn progress_ticks_while_the_buffer_fills() {
+    use std::sync::{Arc, Mutex};
+
+    use rustube::Callback;
+
+    const SMALL: usize = 64 * 1024;
+    let server = MockServer::start(|_| {
+        MockResponse::ok(pattern(SMALL)).slow(4096, Duration::from_millis(1))
+    }).await;
+    let video = video(&server, SMALL);
+    let dir = temp_dir("progress");
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let callback = {
+        let progress = Arc::clone(&progress);
+        Callback::new().connect_on_progress_closure(move |args| progress.lock().unwrap().push(args.current_chunk))
+    };
+    // the whole download fits into the buffer
+    let options = SimpleDownloadOptions::new()
+        .target_dir(&dir)
+        .write_buffer_size(DEFAULT_WRITE_BUFFER_SIZE)
+        .on_progress(callback);
+    video.download_with_summary(options).await.unwrap();
+
+    let progress = progress.lock().unwrap();
+    assert!(progress.len() > 1, "{:?}", progress);
+    assert!(progress.windows(2).all(|w| w[0] < w[1]), "{:?}", progress);
+    assert_eq!(progress.last(), Some(&SMALL));
+    let _ = std::fs::remove_dir_all(&dir);
+}